@@ -18,10 +18,10 @@ pub fn validate_wasm(instance_json: &str) -> Result<JsValue, JsError> {
 
     // Build a JS array of {instancePath, schemaPath} objects
     let arr = js_sys::Array::new();
-    for (ip, sp) in errors {
+    for e in errors {
         let obj = js_sys::Object::new();
-        js_sys::Reflect::set(&obj, &"instancePath".into(), &ip.into()).unwrap();
-        js_sys::Reflect::set(&obj, &"schemaPath".into(), &sp.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"instancePath".into(), &e.instance_path.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"schemaPath".into(), &e.schema_path.into()).unwrap();
         arr.push(&obj);
     }
     Ok(arr.into())
@@ -1,6 +1,9 @@
 /// Test the WASM validator outside a browser using bun/node.
 /// Validates that the AOT-compiled WASM module produces correct errors.
-import init, { validate } from '../../wasm-pkg/jtd_wasm_validator.js';
+import init, * as wasm from '../../wasm-pkg/jtd_wasm_validator.js';
+import { rawErrors } from '../../jtd-wasm-validator/js/raw-errors.js';
+
+const { validate } = wasm;
 
 await init();
 
@@ -46,6 +49,63 @@ if (validErrors.length !== 0) {
   pass = false;
 }
 
+// The zero-copy API reports the same errors
+const raw = [...rawErrors(wasm, wasm.validate_raw(instance))];
+if (JSON.stringify(raw) !== JSON.stringify(errors)) {
+  console.error(`validate_raw disagrees with validate: ${JSON.stringify(raw)}`);
+  pass = false;
+}
+if (wasm.validate_raw(JSON.stringify({ name: "Bob", age: 25, tags: ["x"] })) !== 0) {
+  console.error("Expected validate_raw to return 0 for valid instance");
+  pass = false;
+}
+
+// Already-parsed values give the same errors; undefined members are left out
+const parsed = wasm.validate_value({ name: "Alice", age: 300, tags: ["a", 42], extra: true });
+if (JSON.stringify(parsed) !== JSON.stringify(errors)) {
+  console.error(`validate_value disagrees with validate: ${JSON.stringify(parsed)}`);
+  pass = false;
+}
+if (wasm.validate_value({ name: "Bob", age: 25, tags: ["x"], nickname: undefined }).length !== 0) {
+  console.error("Expected validate_value to leave out undefined members");
+  pass = false;
+}
+
+// Values with no JSON form are rejected with their location
+const cyclic = { name: "Bob", age: 25, tags: [] };
+cyclic.tags.push(cyclic);
+const notJson = [
+  [new Map([["name", "Bob"]]), "the root"],
+  [{ name: "Bob", age: 25, tags: new Set(["x"]) }, "/tags"],
+  [{ name: "Bob", age: NaN, tags: [] }, "/age"],
+  [{ name: "Bob", age: 25, tags: [, "x"] }, "/tags/0"],
+  [Object.defineProperty({ name: "Bob", tags: [] }, "age", { value: 25 }), "/age"],
+  [{ name: "Bob", age: 25, tags: [], [Symbol("id")]: 1 }, "the root"],
+  [cyclic, "/tags/0"],
+];
+for (const [value, at] of notJson) {
+  try {
+    wasm.validate_value(value);
+    console.error(`validate_value accepted a non-JSON value (expected an error at ${at})`);
+    pass = false;
+  } catch (e) {
+    if (!String(e.message).endsWith(` at ${at}`)) {
+      console.error(`validate_value error does not name ${at}: ${e.message}`);
+      pass = false;
+    }
+  }
+}
+
+// Feeding the document in small chunks gives the same errors
+wasm.begin();
+const bytes = new TextEncoder().encode(instance);
+for (let i = 0; i < bytes.length; i += 5) wasm.feed(bytes.subarray(i, i + 5));
+const streamed = wasm.end();
+if (JSON.stringify(streamed) !== JSON.stringify(errors)) {
+  console.error(`begin/feed/end disagrees with validate: ${JSON.stringify(streamed)}`);
+  pass = false;
+}
+
 if (pass) {
   console.log("WASM E2E TEST PASSED");
   process.exit(0);
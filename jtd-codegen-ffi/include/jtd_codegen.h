@@ -0,0 +1,41 @@
+/* C interface to jtd-codegen (libjtd_codegen_ffi).
+ *
+ * Calls that can fail return NULL; jtd_last_error() then describes the
+ * failure. Errors are per thread. Release every returned pointer with the
+ * matching *_free function.
+ */
+#ifndef JTD_CODEGEN_H
+#define JTD_CODEGEN_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JtdSchema JtdSchema;
+
+enum {
+    JTD_TARGET_JS = 0,
+    JTD_TARGET_LUA = 1,
+    JTD_TARGET_PYTHON = 2,
+    JTD_TARGET_RUST = 3
+};
+
+/* Compile a NUL-terminated JTD schema (JSON text). NULL on error. */
+JtdSchema *jtd_compile(const char *schema_json);
+
+/* Emit validator source for a JTD_TARGET_* value. NULL on error.
+ * Free the result with jtd_string_free. */
+char *jtd_emit(const JtdSchema *schema, int target);
+
+/* Message for the last failed call on this thread, or NULL.
+ * Valid until the next jtd_* call on the same thread. */
+const char *jtd_last_error(void);
+
+void jtd_schema_free(JtdSchema *schema);
+void jtd_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JTD_CODEGEN_H */
@@ -0,0 +1,204 @@
+/// C ABI over jtd-codegen, declared in `include/jtd_codegen.h`.
+///
+/// Functions that can fail return NULL and record a message retrievable with
+/// `jtd_last_error()` on the same thread. Every pointer handed out must be
+/// released with the matching `*_free` function.
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use jtd_codegen::ast::CompiledSchema;
+use jtd_codegen::options::EmitOptions;
+use jtd_codegen::Target;
+
+/// Opaque handle to a compiled schema (`JtdSchema` in C).
+pub struct JtdSchema(CompiledSchema);
+
+pub const JTD_TARGET_JS: c_int = 0;
+pub const JTD_TARGET_LUA: c_int = 1;
+pub const JTD_TARGET_PYTHON: c_int = 2;
+pub const JTD_TARGET_RUST: c_int = 3;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(msg: impl Into<String>) {
+    let msg = CString::new(msg.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+fn clear_error() {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+}
+
+/// Run `f`, turning an `Err` or a panic into NULL plus a recorded error.
+fn guard<T>(f: impl FnOnce() -> Result<*mut T, String>) -> *mut T {
+    clear_error();
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(p)) => p,
+        Ok(Err(msg)) => {
+            set_error(msg);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_error("internal error: jtd-codegen panicked");
+            ptr::null_mut()
+        }
+    }
+}
+
+fn target_from_int(target: c_int) -> Option<Target> {
+    match target {
+        JTD_TARGET_JS => Some(Target::JavaScript),
+        JTD_TARGET_LUA => Some(Target::Lua),
+        JTD_TARGET_PYTHON => Some(Target::Python),
+        JTD_TARGET_RUST => Some(Target::Rust),
+        _ => None,
+    }
+}
+
+/// Compile a NUL-terminated JTD schema. Returns NULL on error.
+///
+/// # Safety
+/// `schema_json` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn jtd_compile(schema_json: *const c_char) -> *mut JtdSchema {
+    guard(|| {
+        if schema_json.is_null() {
+            return Err("schema_json is NULL".into());
+        }
+        let text = CStr::from_ptr(schema_json)
+            .to_str()
+            .map_err(|e| format!("schema is not UTF-8: {e}"))?;
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("invalid JSON: {e}"))?;
+        let compiled = jtd_codegen::compiler::compile(&value)
+            .map_err(|e| format!("invalid JTD schema: {e}"))?;
+        Ok(Box::into_raw(Box::new(JtdSchema(compiled))))
+    })
+}
+
+/// Emit validator source for `target` (a `JTD_TARGET_*` value). Returns a
+/// string owned by the caller (free with `jtd_string_free`), or NULL.
+///
+/// # Safety
+/// `schema` must be NULL or a pointer returned by `jtd_compile` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn jtd_emit(schema: *const JtdSchema, target: c_int) -> *mut c_char {
+    guard(|| {
+        let schema = schema.as_ref().ok_or("schema is NULL")?;
+        let target = target_from_int(target).ok_or(format!("unknown target: {target}"))?;
+        let code = jtd_codegen::generate::emit(&schema.0, target, &EmitOptions::default());
+        CString::new(code)
+            .map(CString::into_raw)
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Message for the last failed call on this thread, or NULL. The pointer is
+/// valid until the next `jtd_*` call on the same thread.
+#[no_mangle]
+pub extern "C" fn jtd_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Release a schema returned by `jtd_compile`. NULL is ignored.
+///
+/// # Safety
+/// `schema` must be NULL or a pointer returned by `jtd_compile`, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn jtd_schema_free(schema: *mut JtdSchema) {
+    if !schema.is_null() {
+        drop(Box::from_raw(schema));
+    }
+}
+
+/// Release a string returned by `jtd_emit`. NULL is ignored.
+///
+/// # Safety
+/// `s` must be NULL or a pointer returned by `jtd_emit`, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn jtd_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/jtd_codegen.h");
+
+    fn last_error() -> Option<String> {
+        let p = jtd_last_error();
+        (!p.is_null()).then(|| unsafe { CStr::from_ptr(p) }.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_compile_and_emit() {
+        let json = CString::new(r#"{"type": "string"}"#).unwrap();
+        unsafe {
+            let schema = jtd_compile(json.as_ptr());
+            assert!(!schema.is_null());
+            let code = jtd_emit(schema, JTD_TARGET_JS);
+            assert!(!code.is_null());
+            let text = CStr::from_ptr(code).to_str().unwrap();
+            assert!(text.contains("export function validate(instance)"));
+            assert!(last_error().is_none());
+            jtd_string_free(code);
+            jtd_schema_free(schema);
+        }
+    }
+
+    #[test]
+    fn test_compile_error_sets_last_error() {
+        let json = CString::new(r#"{"type": "nope"}"#).unwrap();
+        let schema = unsafe { jtd_compile(json.as_ptr()) };
+        assert!(schema.is_null());
+        assert!(last_error().unwrap().starts_with("invalid JTD schema"));
+    }
+
+    #[test]
+    fn test_null_and_unknown_target() {
+        assert!(unsafe { jtd_compile(ptr::null()) }.is_null());
+        assert_eq!(last_error().as_deref(), Some("schema_json is NULL"));
+
+        let json = CString::new("{}").unwrap();
+        unsafe {
+            let schema = jtd_compile(json.as_ptr());
+            assert!(jtd_emit(schema, 42).is_null());
+            assert_eq!(last_error().as_deref(), Some("unknown target: 42"));
+            jtd_schema_free(schema);
+        }
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        for name in [
+            "jtd_compile",
+            "jtd_emit",
+            "jtd_last_error",
+            "jtd_schema_free",
+            "jtd_string_free",
+        ] {
+            assert!(
+                HEADER.contains(&format!("{name}(")),
+                "{name} missing from header"
+            );
+        }
+        for (name, value) in [
+            ("JTD_TARGET_JS", JTD_TARGET_JS),
+            ("JTD_TARGET_LUA", JTD_TARGET_LUA),
+            ("JTD_TARGET_PYTHON", JTD_TARGET_PYTHON),
+            ("JTD_TARGET_RUST", JTD_TARGET_RUST),
+        ] {
+            assert!(
+                HEADER.contains(&format!("{name} = {value}")),
+                "{name} mismatch"
+            );
+        }
+    }
+}
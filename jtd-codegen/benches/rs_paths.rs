@@ -0,0 +1,63 @@
+//! Generated Rust validators carry instance and schema paths as linked
+//! segments, formatted only when an error reports them. This compares the
+//! validator for `rs_paths/order.json` (`rs_paths/lazy.rs`, kept current by
+//! a test) with the same schema's validator from before, which formatted a
+//! new `String` for both paths at every property, element and value
+//! (`rs_paths/eager.rs`).
+//!
+//! Run with `cargo bench -p jtd-codegen --bench rs_paths`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+#[allow(dead_code, unused_variables, clippy::all)]
+mod eager {
+    include!("rs_paths/eager.rs");
+}
+
+#[allow(dead_code, unused_variables, clippy::all)]
+mod lazy {
+    include!("rs_paths/lazy.rs");
+}
+
+/// An order with `lines` lines; with `errors`, every fifth line has a
+/// negative quantity.
+fn order(lines: usize, errors: bool) -> Value {
+    let lines: Vec<Value> = (0..lines)
+        .map(|i| {
+            let quantity = if errors && i % 5 == 0 { -1 } else { 2 };
+            json!({
+                "sku": format!("SKU-{i}"),
+                "quantity": quantity,
+                "price": {"currency": "EUR", "cents": 1999},
+                "tags": ["gift", "fragile"],
+                "attributes": {"colour": "red", "size": "M"}
+            })
+        })
+        .collect();
+    json!({
+        "id": "order-1",
+        "customer": {"name": "Ada", "email": "ada@example.com", "phone": null},
+        "lines": lines,
+        "total": {"currency": "EUR", "cents": 1999 * 2},
+        "notes": {"gate": "B", "floor": "3"}
+    })
+}
+
+fn bench(c: &mut Criterion) {
+    for (name, instance) in [("valid", order(100, false)), ("invalid", order(100, true))] {
+        let lazy_errors: Vec<(String, String)> = lazy::validate(&instance)
+            .into_iter()
+            .map(lazy::ValidationError::into_tuple)
+            .collect();
+        assert_eq!(eager::validate(&instance), lazy_errors);
+        let mut group = c.benchmark_group(format!("rs_paths/{name}"));
+        group.bench_function("eager", |b| {
+            b.iter(|| eager::validate(black_box(&instance)))
+        });
+        group.bench_function("lazy", |b| b.iter(|| lazy::validate(black_box(&instance))));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
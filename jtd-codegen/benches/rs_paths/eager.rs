@@ -0,0 +1,206 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+
+use serde_json::Value;
+
+fn validate_line(v: &Value, e: &mut Vec<(String, String)>, p: &str, sp: &str) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("price") {
+      let ip_p0 = format!("{p}/price");
+      let sp_p0 = format!("{sp}/properties/price");
+      validate_money(pv, e, &ip_p0, &format!("/definitions/money"));
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/price")));
+    }
+    if let Some(pv) = obj.get("quantity") {
+      let ip_p1 = format!("{p}/quantity");
+      let sp_p1 = format!("{sp}/properties/quantity");
+      if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= 0_f64 && n <= 65535_f64) {
+        e.push((ip_p1.to_string(), format!("{sp_p1}/type")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/quantity")));
+    }
+    if let Some(pv) = obj.get("sku") {
+      let ip_p2 = format!("{p}/sku");
+      let sp_p2 = format!("{sp}/properties/sku");
+      if !pv.is_string() {
+        e.push((ip_p2.to_string(), format!("{sp_p2}/type")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/sku")));
+    }
+    if let Some(pv) = obj.get("attributes") {
+      let ip_o0 = format!("{p}/attributes");
+      let sp_o0 = format!("{sp}/optionalProperties/attributes");
+      if let Some(obj) = pv.as_object() {
+        for (k, vv) in obj {
+          let ip_v0 = format!("{ip_o0}/{}", k.replace('~', "~0").replace('/', "~1"));
+          let sp_v0 = format!("{sp_o0}/values");
+          if !vv.is_string() {
+            e.push((ip_v0.to_string(), format!("{sp_v0}/type")));
+          }
+        }
+      } else {
+        e.push((ip_o0.to_string(), format!("{sp_o0}/values")));
+      }
+    }
+    if let Some(pv) = obj.get("tags") {
+      let ip_o1 = format!("{p}/tags");
+      let sp_o1 = format!("{sp}/optionalProperties/tags");
+      if let Some(arr) = pv.as_array() {
+        for (i, elem) in arr.iter().enumerate() {
+          let ip_e0 = format!("{ip_o1}/{i}");
+          let sp_e0 = format!("{sp_o1}/elements");
+          if !elem.is_string() {
+            e.push((ip_e0.to_string(), format!("{sp_e0}/type")));
+          }
+        }
+      } else {
+        e.push((ip_o1.to_string(), format!("{sp_o1}/elements")));
+      }
+    }
+    for k in obj.keys() {
+      if k.as_str() != "price" && k.as_str() != "quantity" && k.as_str() != "sku" && k.as_str() != "attributes" && k.as_str() != "tags" {
+        e.push((format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), sp.to_string()));
+      }
+    }
+  } else {
+    e.push((p.to_string(), format!("{sp}/properties")));
+  }
+}
+
+fn validate_money(v: &Value, e: &mut Vec<(String, String)>, p: &str, sp: &str) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("cents") {
+      let ip_p0 = format!("{p}/cents");
+      let sp_p0 = format!("{sp}/properties/cents");
+      if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= -2147483648_f64 && n <= 2147483647_f64) {
+        e.push((ip_p0.to_string(), format!("{sp_p0}/type")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/cents")));
+    }
+    if let Some(pv) = obj.get("currency") {
+      let ip_p1 = format!("{p}/currency");
+      let sp_p1 = format!("{sp}/properties/currency");
+      if !pv.as_str().map_or(false, |s| ["EUR", "GBP", "USD"].contains(&s)) {
+        e.push((ip_p1.to_string(), format!("{sp_p1}/enum")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/currency")));
+    }
+    for k in obj.keys() {
+      if k.as_str() != "cents" && k.as_str() != "currency" {
+        e.push((format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), sp.to_string()));
+      }
+    }
+  } else {
+    e.push((p.to_string(), format!("{sp}/properties")));
+  }
+}
+
+pub fn validate(instance: &Value) -> Vec<(String, String)> {
+  let mut e: Vec<(String, String)> = Vec::new();
+  let p = "";
+  let sp = "";
+  if let Some(obj) = instance.as_object() {
+    if let Some(pv) = obj.get("customer") {
+      let ip_p0 = format!("{p}/customer");
+      let sp_p0 = format!("{sp}/properties/customer");
+      if let Some(obj) = pv.as_object() {
+        if let Some(pv) = obj.get("email") {
+          let ip_p0 = format!("{ip_p0}/email");
+          let sp_p0 = format!("{sp_p0}/properties/email");
+          if !pv.is_string() {
+            e.push((ip_p0.to_string(), format!("{sp_p0}/type")));
+          }
+        } else {
+          e.push((ip_p0.to_string(), format!("{sp_p0}/properties/email")));
+        }
+        if let Some(pv) = obj.get("name") {
+          let ip_p1 = format!("{ip_p0}/name");
+          let sp_p1 = format!("{sp_p0}/properties/name");
+          if !pv.is_string() {
+            e.push((ip_p1.to_string(), format!("{sp_p1}/type")));
+          }
+        } else {
+          e.push((ip_p0.to_string(), format!("{sp_p0}/properties/name")));
+        }
+        if let Some(pv) = obj.get("phone") {
+          let ip_o0 = format!("{ip_p0}/phone");
+          let sp_o0 = format!("{sp_p0}/optionalProperties/phone");
+          if !pv.is_null() {
+            if !pv.is_string() {
+              e.push((ip_o0.to_string(), format!("{sp_o0}/type")));
+            }
+          }
+        }
+        for k in obj.keys() {
+          if k.as_str() != "email" && k.as_str() != "name" && k.as_str() != "phone" {
+            e.push((format!("{ip_p0}/{}", k.replace('~', "~0").replace('/', "~1")), sp_p0.to_string()));
+          }
+        }
+      } else {
+        e.push((ip_p0.to_string(), format!("{sp_p0}/properties")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/customer")));
+    }
+    if let Some(pv) = obj.get("id") {
+      let ip_p1 = format!("{p}/id");
+      let sp_p1 = format!("{sp}/properties/id");
+      if !pv.is_string() {
+        e.push((ip_p1.to_string(), format!("{sp_p1}/type")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/id")));
+    }
+    if let Some(pv) = obj.get("lines") {
+      let ip_p2 = format!("{p}/lines");
+      let sp_p2 = format!("{sp}/properties/lines");
+      if let Some(arr) = pv.as_array() {
+        for (i, elem) in arr.iter().enumerate() {
+          let ip_e0 = format!("{ip_p2}/{i}");
+          let sp_e0 = format!("{sp_p2}/elements");
+          validate_line(elem, &mut e, &ip_e0, &format!("/definitions/line"));
+        }
+      } else {
+        e.push((ip_p2.to_string(), format!("{sp_p2}/elements")));
+      }
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/lines")));
+    }
+    if let Some(pv) = obj.get("total") {
+      let ip_p3 = format!("{p}/total");
+      let sp_p3 = format!("{sp}/properties/total");
+      validate_money(pv, &mut e, &ip_p3, &format!("/definitions/money"));
+    } else {
+      e.push((p.to_string(), format!("{sp}/properties/total")));
+    }
+    if let Some(pv) = obj.get("notes") {
+      let ip_o0 = format!("{p}/notes");
+      let sp_o0 = format!("{sp}/optionalProperties/notes");
+      if let Some(obj) = pv.as_object() {
+        for (k, vv) in obj {
+          let ip_v0 = format!("{ip_o0}/{}", k.replace('~', "~0").replace('/', "~1"));
+          let sp_v0 = format!("{sp_o0}/values");
+          if !vv.is_string() {
+            e.push((ip_v0.to_string(), format!("{sp_v0}/type")));
+          }
+        }
+      } else {
+        e.push((ip_o0.to_string(), format!("{sp_o0}/values")));
+      }
+    }
+    for k in obj.keys() {
+      if k.as_str() != "customer" && k.as_str() != "id" && k.as_str() != "lines" && k.as_str() != "total" && k.as_str() != "notes" {
+        e.push((format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), sp.to_string()));
+      }
+    }
+  } else {
+    e.push((p.to_string(), format!("{sp}/properties")));
+  }
+  e
+}
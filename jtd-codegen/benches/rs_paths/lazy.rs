@@ -0,0 +1,257 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+
+use serde_json::Value;
+
+/// A JSON Pointer built only when an error reports it: each descent
+/// links a segment onto its parent instead of formatting a new string.
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+enum LazyPointer<'a> {
+  /// A whole pointer, e.g. `""` or `"/definitions/node"`.
+  Base(&'a str),
+  /// The parent followed by already escaped text, e.g. `"/properties/id"`.
+  Lit(&'a LazyPointer<'a>, &'static str),
+  /// The parent followed by an object key, escaped per RFC 6901.
+  Key(&'a LazyPointer<'a>, &'a str),
+  /// The parent followed by an array index.
+  Index(&'a LazyPointer<'a>, usize),
+}
+
+impl std::fmt::Display for LazyPointer<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LazyPointer::Base(s) => f.write_str(s),
+      LazyPointer::Lit(p, s) => write!(f, "{p}{s}"),
+      LazyPointer::Key(p, k) => write!(f, "{p}/{}", k.replace('~', "~0").replace('/', "~1")),
+      LazyPointer::Index(p, i) => write!(f, "{p}/{i}"),
+    }
+  }
+}
+
+/// A validation error: the value at `instance_path` fails the schema
+/// keyword at `schema_path`, both JSON Pointers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValidationError {
+  /// Where the value is in the instance, e.g. `/items/0`.
+  pub instance_path: String,
+  /// The keyword it fails, e.g. `/properties/items/elements/type`.
+  pub schema_path: String,
+}
+
+impl ValidationError {
+  /// The error as an `(instance path, schema path)` tuple.
+  pub fn into_tuple(self) -> (String, String) {
+    (self.instance_path, self.schema_path)
+  }
+}
+
+impl std::fmt::Display for ValidationError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "value at {:?} fails {:?}", self.instance_path, self.schema_path)
+  }
+}
+
+impl std::error::Error for ValidationError {}
+
+fn validate_line(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("price") {
+      let ip_p0 = LazyPointer::Lit(&p, "/price");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/price");
+      validate_money(pv, e, ip_p0, LazyPointer::Base("/definitions/money"));
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/price") });
+    }
+    if let Some(pv) = obj.get("quantity") {
+      let ip_p1 = LazyPointer::Lit(&p, "/quantity");
+      let sp_p1 = LazyPointer::Lit(&sp, "/properties/quantity");
+      if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= 0_f64 && n <= 65535_f64) {
+        e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/quantity") });
+    }
+    if let Some(pv) = obj.get("sku") {
+      let ip_p2 = LazyPointer::Lit(&p, "/sku");
+      let sp_p2 = LazyPointer::Lit(&sp, "/properties/sku");
+      if !pv.is_string() {
+        e.push(ValidationError { instance_path: ip_p2.to_string(), schema_path: format!("{sp_p2}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/sku") });
+    }
+    if let Some(pv) = obj.get("attributes") {
+      let ip_o0 = LazyPointer::Lit(&p, "/attributes");
+      let sp_o0 = LazyPointer::Lit(&sp, "/optionalProperties/attributes");
+      if let Some(obj) = pv.as_object() {
+        for (k, vv) in obj {
+          let ip_v0 = LazyPointer::Key(&ip_o0, k);
+          let sp_v0 = LazyPointer::Lit(&sp_o0, "/values");
+          if !vv.is_string() {
+            e.push(ValidationError { instance_path: ip_v0.to_string(), schema_path: format!("{sp_v0}/type") });
+          }
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_o0.to_string(), schema_path: format!("{sp_o0}/values") });
+      }
+    }
+    if let Some(pv) = obj.get("tags") {
+      let ip_o1 = LazyPointer::Lit(&p, "/tags");
+      let sp_o1 = LazyPointer::Lit(&sp, "/optionalProperties/tags");
+      if let Some(arr) = pv.as_array() {
+        for (i, elem) in arr.iter().enumerate() {
+          let ip_e0 = LazyPointer::Index(&ip_o1, i);
+          let sp_e0 = LazyPointer::Lit(&sp_o1, "/elements");
+          if !elem.is_string() {
+            e.push(ValidationError { instance_path: ip_e0.to_string(), schema_path: format!("{sp_e0}/type") });
+          }
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_o1.to_string(), schema_path: format!("{sp_o1}/elements") });
+      }
+    }
+    for k in obj.keys() {
+      if k.as_str() != "price" && k.as_str() != "quantity" && k.as_str() != "sku" && k.as_str() != "attributes" && k.as_str() != "tags" {
+        e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp.to_string() });
+      }
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+}
+
+fn validate_money(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("cents") {
+      let ip_p0 = LazyPointer::Lit(&p, "/cents");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/cents");
+      if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= -2147483648_f64 && n <= 2147483647_f64) {
+        e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/cents") });
+    }
+    if let Some(pv) = obj.get("currency") {
+      let ip_p1 = LazyPointer::Lit(&p, "/currency");
+      let sp_p1 = LazyPointer::Lit(&sp, "/properties/currency");
+      if !pv.as_str().map_or(false, |s| ["EUR", "GBP", "USD"].contains(&s)) {
+        e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/enum") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/currency") });
+    }
+    for k in obj.keys() {
+      if k.as_str() != "cents" && k.as_str() != "currency" {
+        e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp.to_string() });
+      }
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+}
+
+pub fn validate(instance: &Value) -> Vec<ValidationError> {
+  let mut e: Vec<ValidationError> = Vec::new();
+  let p = LazyPointer::Base("");
+  let sp = LazyPointer::Base("");
+  if let Some(obj) = instance.as_object() {
+    if let Some(pv) = obj.get("customer") {
+      let ip_p0 = LazyPointer::Lit(&p, "/customer");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/customer");
+      if let Some(obj) = pv.as_object() {
+        if let Some(pv) = obj.get("email") {
+          let ip_p0 = LazyPointer::Lit(&ip_p0, "/email");
+          let sp_p0 = LazyPointer::Lit(&sp_p0, "/properties/email");
+          if !pv.is_string() {
+            e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/type") });
+          }
+        } else {
+          e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/properties/email") });
+        }
+        if let Some(pv) = obj.get("name") {
+          let ip_p1 = LazyPointer::Lit(&ip_p0, "/name");
+          let sp_p1 = LazyPointer::Lit(&sp_p0, "/properties/name");
+          if !pv.is_string() {
+            e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+          }
+        } else {
+          e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/properties/name") });
+        }
+        if let Some(pv) = obj.get("phone") {
+          let ip_o0 = LazyPointer::Lit(&ip_p0, "/phone");
+          let sp_o0 = LazyPointer::Lit(&sp_p0, "/optionalProperties/phone");
+          if !pv.is_null() {
+            if !pv.is_string() {
+              e.push(ValidationError { instance_path: ip_o0.to_string(), schema_path: format!("{sp_o0}/type") });
+            }
+          }
+        }
+        for k in obj.keys() {
+          if k.as_str() != "email" && k.as_str() != "name" && k.as_str() != "phone" {
+            e.push(ValidationError { instance_path: format!("{ip_p0}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp_p0.to_string() });
+          }
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/properties") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/customer") });
+    }
+    if let Some(pv) = obj.get("id") {
+      let ip_p1 = LazyPointer::Lit(&p, "/id");
+      let sp_p1 = LazyPointer::Lit(&sp, "/properties/id");
+      if !pv.is_string() {
+        e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/id") });
+    }
+    if let Some(pv) = obj.get("lines") {
+      let ip_p2 = LazyPointer::Lit(&p, "/lines");
+      let sp_p2 = LazyPointer::Lit(&sp, "/properties/lines");
+      if let Some(arr) = pv.as_array() {
+        for (i, elem) in arr.iter().enumerate() {
+          let ip_e0 = LazyPointer::Index(&ip_p2, i);
+          let sp_e0 = LazyPointer::Lit(&sp_p2, "/elements");
+          validate_line(elem, &mut e, ip_e0, LazyPointer::Base("/definitions/line"));
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_p2.to_string(), schema_path: format!("{sp_p2}/elements") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/lines") });
+    }
+    if let Some(pv) = obj.get("total") {
+      let ip_p3 = LazyPointer::Lit(&p, "/total");
+      let sp_p3 = LazyPointer::Lit(&sp, "/properties/total");
+      validate_money(pv, &mut e, ip_p3, LazyPointer::Base("/definitions/money"));
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/total") });
+    }
+    if let Some(pv) = obj.get("notes") {
+      let ip_o0 = LazyPointer::Lit(&p, "/notes");
+      let sp_o0 = LazyPointer::Lit(&sp, "/optionalProperties/notes");
+      if let Some(obj) = pv.as_object() {
+        for (k, vv) in obj {
+          let ip_v0 = LazyPointer::Key(&ip_o0, k);
+          let sp_v0 = LazyPointer::Lit(&sp_o0, "/values");
+          if !vv.is_string() {
+            e.push(ValidationError { instance_path: ip_v0.to_string(), schema_path: format!("{sp_v0}/type") });
+          }
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_o0.to_string(), schema_path: format!("{sp_o0}/values") });
+      }
+    }
+    for k in obj.keys() {
+      if k.as_str() != "customer" && k.as_str() != "id" && k.as_str() != "lines" && k.as_str() != "total" && k.as_str() != "notes" {
+        e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp.to_string() });
+      }
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+  e
+}
@@ -0,0 +1,292 @@
+/// Apache Arrow schemas derived from a compiled schema, so validated JSON
+/// streams can be written to Parquet with a consistent columnar layout.
+/// Needs the `arrow` feature.
+///
+/// - Types map to their Arrow counterparts; timestamps are microseconds in
+///   UTC and enums are `Dictionary(Int32, Utf8)`.
+/// - Elements are `List`s, values `Map`s with `Utf8` keys and properties
+///   `Struct`s. Refs are inlined.
+/// - A field is nullable unless it is required and not nullable.
+/// - A discriminator is one `Struct` with the tag and every variant's
+///   fields, all nullable since a row has one variant's. Unions would say
+///   more, but Parquet cannot store them.
+/// - The empty form, a ref back into a definition already being inlined and
+///   a field two variants declare differently are `Utf8` columns of JSON
+///   text, marked with the canonical `arrow.json` extension type.
+/// - A schema whose root is not a Properties form gets a single field,
+///   `value`.
+/// - `metadata.description` is kept as the field's `description` metadata.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use arrow_schema::extension::{EXTENSION_TYPE_METADATA_KEY, EXTENSION_TYPE_NAME_KEY};
+use arrow_schema::{DataType, Field, Fields, Schema, TimeUnit};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+
+/// The Arrow schema for records matching `schema`.
+pub fn arrow_schema(schema: &CompiledSchema) -> Schema {
+    let mut inlining = Vec::new();
+    let fields = match &schema.root {
+        node @ Node::Properties { .. } => struct_fields(schema, node, "", &mut inlining),
+        node => vec![field(schema, "value", node, "", true, &mut inlining)],
+    };
+    Schema::new(fields)
+}
+
+/// The field `name` for `node` at schema path `path`.
+fn field<'a>(
+    schema: &'a CompiledSchema,
+    name: &str,
+    node: &'a Node,
+    path: &str,
+    required: bool,
+    inlining: &mut Vec<&'a str>,
+) -> Field {
+    let depth = inlining.len();
+    let (node, node_path, nullable) = resolve(schema, node, path, inlining);
+    let data_type = data_type(schema, node, &node_path, inlining);
+    inlining.truncate(depth);
+    let nullable = nullable || !required;
+    let mut out = match data_type {
+        Some(data_type) => Field::new(name, data_type, nullable),
+        None => json_field(name),
+    };
+    let desc = schema
+        .description(path)
+        .or_else(|| schema.description(&node_path));
+    if let Some(desc) = desc {
+        let mut metadata = out.metadata().clone();
+        metadata.insert("description".into(), desc.into());
+        out = out.with_metadata(metadata);
+    }
+    out
+}
+
+/// A nullable `Utf8` field holding JSON text.
+fn json_field(name: &str) -> Field {
+    Field::new(name, DataType::Utf8, true).with_metadata(HashMap::from([
+        (EXTENSION_TYPE_NAME_KEY.into(), "arrow.json".into()),
+        (EXTENSION_TYPE_METADATA_KEY.into(), String::new()),
+    ]))
+}
+
+/// Strip nullable and follow refs from `node` at `path`, pushing each
+/// definition followed onto `inlining`. Returns the form reached, its
+/// schema path and whether null is allowed on the way. A ref back into a
+/// definition already on the stack resolves to the empty form.
+fn resolve<'a>(
+    schema: &'a CompiledSchema,
+    mut node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> (&'a Node, String, bool) {
+    let mut path = path.to_string();
+    let mut nullable = false;
+    loop {
+        match node {
+            Node::Nullable { inner } => {
+                nullable = true;
+                node = inner;
+            }
+            Node::Ref { name } if inlining.contains(&name.as_str()) => {
+                return (&Node::Empty, path, nullable);
+            }
+            Node::Ref { name } => {
+                inlining.push(name);
+                node = &schema.definitions[name];
+                path = format!("/definitions/{}", escape_pointer(name));
+            }
+            _ => return (node, path, nullable),
+        }
+    }
+}
+
+/// The Arrow type for `node`, `None` for JSON text.
+fn data_type<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Option<DataType> {
+    let data_type = match node {
+        Node::Empty | Node::Ref { .. } | Node::Nullable { .. } => return None,
+        Node::Type { type_kw } => match type_kw {
+            TypeKeyword::Boolean => DataType::Boolean,
+            TypeKeyword::String => DataType::Utf8,
+            TypeKeyword::Timestamp => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+            }
+            TypeKeyword::Float32 => DataType::Float32,
+            TypeKeyword::Float64 => DataType::Float64,
+            TypeKeyword::Int8 => DataType::Int8,
+            TypeKeyword::Uint8 => DataType::UInt8,
+            TypeKeyword::Int16 => DataType::Int16,
+            TypeKeyword::Uint16 => DataType::UInt16,
+            TypeKeyword::Int32 => DataType::Int32,
+            TypeKeyword::Uint32 => DataType::UInt32,
+        },
+        Node::Enum { .. } => {
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+        }
+        Node::Elements { schema: item } => {
+            let item = field(
+                schema,
+                "item",
+                item,
+                &format!("{path}/elements"),
+                true,
+                inlining,
+            );
+            DataType::List(Arc::new(item))
+        }
+        Node::Values { schema: value } => {
+            let key = Field::new("key", DataType::Utf8, false);
+            let value = field(
+                schema,
+                "value",
+                value,
+                &format!("{path}/values"),
+                true,
+                inlining,
+            );
+            let entries = DataType::Struct(Fields::from(vec![key, value]));
+            DataType::Map(Arc::new(Field::new("entries", entries, false)), false)
+        }
+        Node::Properties { .. } => {
+            DataType::Struct(Fields::from(struct_fields(schema, node, path, inlining)))
+        }
+        Node::Discriminator { tag, mapping } => {
+            let fields = union_fields(schema, tag, mapping, path, inlining);
+            DataType::Struct(Fields::from(fields))
+        }
+    };
+    Some(data_type)
+}
+
+fn struct_fields<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Vec<Field> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return Vec::new();
+    };
+    let required = required.iter().map(|(k, n)| (k, n, "properties", true));
+    let optional = optional
+        .iter()
+        .map(|(k, n)| (k, n, "optionalProperties", false));
+    required
+        .chain(optional)
+        .map(|(key, child, keyword, is_required)| {
+            let child_path = format!("{path}/{keyword}/{}", escape_pointer(key));
+            field(schema, key, child, &child_path, is_required, inlining)
+        })
+        .collect()
+}
+
+/// The tag and the fields of every variant, each nullable.
+fn union_fields<'a>(
+    schema: &'a CompiledSchema,
+    tag: &str,
+    mapping: &'a BTreeMap<String, Node>,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Vec<Field> {
+    let mut fields = vec![Field::new(tag, DataType::Utf8, false)];
+    for (value, variant) in mapping {
+        let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+        for f in struct_fields(schema, variant, &variant_path, inlining) {
+            let f = f.with_nullable(true);
+            match fields.iter_mut().find(|g| g.name() == f.name()) {
+                Some(g) if *g != f => *g = json_field(f.name()),
+                Some(_) => {}
+                None => fields.push(f),
+            }
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_arrow_schema() {
+        let schema = compile(&json!({
+            "definitions": {
+                "node": {
+                    "properties": {"n": {"type": "uint8"}},
+                    "optionalProperties": {"next": {"ref": "node"}}
+                }
+            },
+            "properties": {
+                "id": {"type": "string", "metadata": {"description": "Event id"}},
+                "at": {"type": "timestamp", "nullable": true},
+                "status": {"enum": ["new", "done"]},
+                "grid": {"elements": {"elements": {"type": "float64"}}},
+                "head": {"ref": "node"},
+                "labels": {"values": {"type": "int32", "nullable": true}},
+                "pet": {"discriminator": "kind", "mapping": {
+                    "cat": {"properties": {"name": {"type": "string"}}},
+                    "dog": {"properties": {"name": {"type": "boolean"}}}
+                }}
+            },
+            "optionalProperties": {"any": {}}
+        }))
+        .unwrap();
+        let arrow = arrow_schema(&schema);
+        let field = |name: &str| arrow.field_with_name(name).unwrap().clone();
+
+        let id = field("id");
+        assert_eq!(id.data_type(), &DataType::Utf8);
+        assert!(!id.is_nullable());
+        assert_eq!(id.metadata()["description"], "Event id");
+        assert!(field("at").is_nullable());
+        assert_eq!(
+            field("status").data_type(),
+            &DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+        );
+        let float = Field::new("item", DataType::Float64, false);
+        let inner = Field::new("item", DataType::List(Arc::new(float)), false);
+        assert_eq!(field("grid").data_type(), &DataType::List(Arc::new(inner)));
+        let DataType::Struct(head) = field("head").data_type().clone() else {
+            panic!("head is a struct");
+        };
+        assert_eq!(head[0].as_ref(), &Field::new("n", DataType::UInt8, false));
+        assert_eq!(head[1].as_ref(), &json_field("next"));
+        let DataType::Map(entries, false) = field("labels").data_type().clone() else {
+            panic!("labels is a map");
+        };
+        let DataType::Struct(entry) = entries.data_type() else {
+            panic!("map entries are structs");
+        };
+        assert_eq!(
+            entry[1].as_ref(),
+            &Field::new("value", DataType::Int32, true)
+        );
+        assert_eq!(
+            field("pet").data_type(),
+            &DataType::Struct(Fields::from(vec![
+                Field::new("kind", DataType::Utf8, false),
+                json_field("name"),
+            ]))
+        );
+        assert_eq!(field("any"), json_field("any"));
+
+        let schema = compile(&json!({"elements": {"type": "int32"}})).unwrap();
+        let item = Field::new("item", DataType::Int32, false);
+        assert_eq!(
+            arrow_schema(&schema).fields()[0].as_ref(),
+            &Field::new("value", DataType::List(Arc::new(item)), false)
+        );
+    }
+}
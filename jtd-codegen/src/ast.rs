@@ -1,10 +1,16 @@
 /// JTD AST node types per Section 3 of the JTD Code Generation Specification.
 /// These are immutable, tagged values representing compiled schema forms.
-/// Used during code generation and discarded after emission.
+/// Used during code generation and discarded after emission, or serialized
+/// (`jtd-codegen --emit ast`) for other tools and for caching between build
+/// steps. In JSON a node is an object whose `form` names the variant, e.g.
+/// `{"form": "type", "type": "uint8"}`. Deserializing does not re-check the
+/// schema rules, so only load ASTs this crate wrote.
+use crate::compiler::escape_pointer;
 use std::collections::BTreeMap;
 
 /// The 12 type keywords defined in RFC 8927 Section 2.2.3.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TypeKeyword {
     Boolean,
     String,
@@ -56,14 +62,18 @@ impl TypeKeyword {
 
 /// An immutable AST node representing one compiled schema form.
 /// Section 3.1 of the spec.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "form", rename_all = "lowercase")]
 pub enum Node {
     /// `{}` -- accepts any JSON value
     Empty,
     /// `{"ref": "..."}` -- references a definition
     Ref { name: String },
     /// `{"type": "..."}` -- type check
-    Type { type_kw: TypeKeyword },
+    Type {
+        #[serde(rename = "type")]
+        type_kw: TypeKeyword,
+    },
     /// `{"enum": [...]}` -- set membership
     Enum { values: Vec<String> },
     /// `{"elements": ...}` -- array with element schema
@@ -105,8 +115,105 @@ impl Node {
 }
 
 /// A compiled JTD schema: root node + definitions.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct CompiledSchema {
     pub root: Node,
+    #[serde(default)]
+    pub definitions: BTreeMap<String, Node>,
+    /// `metadata` objects by schema path (`""` for the root,
+    /// `/definitions/a/properties/b`, ...). Validation ignores them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+}
+
+impl CompiledSchema {
+    /// `metadata.description` of the schema at `schema_path`, if it is a string.
+    pub fn description(&self, schema_path: &str) -> Option<&str> {
+        self.metadata.get(schema_path)?.get("description")?.as_str()
+    }
+
+    /// True when the schema at `schema_path` has `metadata.deprecated: true`.
+    pub fn is_deprecated(&self, schema_path: &str) -> bool {
+        self.metadata
+            .get(schema_path)
+            .and_then(|m| m.get("deprecated"))
+            .and_then(|d| d.as_bool())
+            == Some(true)
+    }
+
+    /// `metadata.default` of the schema at `schema_path`, if present.
+    pub fn default_value(&self, schema_path: &str) -> Option<&serde_json::Value> {
+        self.metadata.get(schema_path)?.get("default")
+    }
+
+    /// `metadata.examples` of the schema at `schema_path` (empty if absent).
+    pub fn examples(&self, schema_path: &str) -> &[serde_json::Value] {
+        self.metadata
+            .get(schema_path)
+            .and_then(|m| m.get("examples"))
+            .and_then(|e| e.as_array())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every `(schema path, definition name, examples)` that has examples:
+    /// the root first (name `None`), then definitions in order.
+    pub fn example_sets(&self) -> Vec<(String, Option<&str>, &[serde_json::Value])> {
+        let root = std::iter::once((String::new(), None));
+        let defs = self.definitions.keys().map(|name| {
+            let path = format!("/definitions/{}", escape_pointer(name));
+            (path, Some(name.as_str()))
+        });
+        root.chain(defs)
+            .map(|(path, name)| {
+                let examples = self.examples(&path);
+                (path, name, examples)
+            })
+            .filter(|(_, _, examples)| !examples.is_empty())
+            .collect()
+    }
+}
+
+/// Several root schemas compiled against one shared set of definitions.
+/// Emitters generate each definition once and one entry point per root.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SchemaBundle {
+    /// Entry points in input order: (name, root node).
+    pub roots: Vec<(String, Node)>,
     pub definitions: BTreeMap<String, Node>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde_round_trip() {
+        let compiled = compile(&json!({
+            "definitions": {"id": {"type": "uint32", "metadata": {"description": "An id."}}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["A"]}}},
+            "optionalProperties": {"v": {"discriminator": "k", "mapping": {"x": {"properties": {}}}}},
+            "additionalProperties": true,
+            "nullable": true
+        }))
+        .unwrap();
+        let ast = serde_json::to_value(&compiled).unwrap();
+        assert_eq!(
+            ast["definitions"]["id"],
+            json!({"form": "type", "type": "uint32"})
+        );
+        assert_eq!(ast["root"]["form"], "nullable");
+        assert_eq!(
+            ast["root"]["inner"]["required"]["tags"],
+            json!({"form": "elements", "schema": {"form": "enum", "values": ["A"]}})
+        );
+        assert_eq!(ast["metadata"]["/definitions/id"]["description"], "An id.");
+        let back: CompiledSchema = serde_json::from_value(ast).unwrap();
+        assert_eq!(back, compiled);
+
+        let empty: CompiledSchema =
+            serde_json::from_value(json!({"root": {"form": "empty"}})).unwrap();
+        assert_eq!(empty, compile(&json!({})).unwrap());
+    }
+}
@@ -0,0 +1,143 @@
+/// NDJSON batch mode for schema-registry pipelines: reads one
+/// `{"name": ..., "schema": ...}` request per line and writes one result per
+/// line, in order, as soon as it is ready. A result is
+/// `{"line": n, "name": ..., "code": ...}` or
+/// `{"line": n, "name": ..., "error": ...}`; a bad line never stops the
+/// stream.
+use crate::compiler::{self, CompileError};
+use crate::generate::{emit, Target};
+use crate::options::EmitOptions;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LineError {
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("expected an object with a string \"name\" and a \"schema\"")]
+    Shape,
+    #[error("invalid JTD schema: {0}")]
+    Compile(#[from] CompileError),
+}
+
+/// How many lines succeeded and failed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub ok: usize,
+    pub failed: usize,
+}
+
+/// Generate code for one request line. Returns the request's name (when it
+/// could be read) alongside the result.
+pub fn generate_line(
+    line: &str,
+    target: Target,
+    opts: &EmitOptions,
+) -> (Option<String>, Result<String, LineError>) {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => return (None, Err(e.into())),
+    };
+    let name = request
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+    let result = match (&name, request.get("schema")) {
+        (Some(_), Some(schema)) => compiler::compile(schema)
+            .map(|compiled| emit(&compiled, target, opts))
+            .map_err(LineError::from),
+        _ => Err(LineError::Shape),
+    };
+    (name, result)
+}
+
+/// Process every line of `input`, writing one result line to `output` per
+/// non-blank input line and flushing after each so downstream consumers see
+/// results as they are produced. Only I/O failures are returned as errors.
+pub fn run<R: BufRead, W: Write>(
+    input: R,
+    mut output: W,
+    target: Target,
+    opts: &EmitOptions,
+) -> io::Result<BatchSummary> {
+    let mut summary = BatchSummary::default();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (name, result) = generate_line(&line, target, opts);
+        let record = match result {
+            Ok(code) => {
+                summary.ok += 1;
+                json!({"line": index + 1, "name": name, "code": code})
+            }
+            Err(e) => {
+                summary.failed += 1;
+                json!({"line": index + 1, "name": name, "error": e.to_string()})
+            }
+        };
+        writeln!(output, "{record}")?;
+        output.flush()?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(input: &str) -> (BatchSummary, Vec<Value>) {
+        let mut out = Vec::new();
+        let summary = run(
+            input.as_bytes(),
+            &mut out,
+            Target::JavaScript,
+            &EmitOptions::default(),
+        )
+        .unwrap();
+        let lines = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        (summary, lines)
+    }
+
+    #[test]
+    fn test_one_result_per_line() {
+        let input = concat!(
+            r#"{"name": "user", "schema": {"properties": {"id": {"type": "string"}}}}"#,
+            "\n\n",
+            r#"{"name": "bad", "schema": {"type": "nope"}}"#,
+            "\n",
+        );
+        let (summary, lines) = results(input);
+        assert_eq!(summary, BatchSummary { ok: 1, failed: 1 });
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["line"], 1);
+        assert_eq!(lines[0]["name"], "user");
+        assert!(lines[0]["code"]
+            .as_str()
+            .unwrap()
+            .contains("export function validate(instance)"));
+        assert_eq!(lines[1]["line"], 3);
+        assert_eq!(lines[1]["name"], "bad");
+        assert!(lines[1]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JTD schema"));
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        let (summary, lines) = results("{\n{\"schema\": {}}\n");
+        assert_eq!(summary, BatchSummary { ok: 0, failed: 2 });
+        assert_eq!(lines[0]["name"], Value::Null);
+        assert!(lines[0]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JSON"));
+        assert!(lines[1]["error"].as_str().unwrap().contains("\"name\""));
+    }
+}
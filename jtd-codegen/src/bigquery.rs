@@ -0,0 +1,258 @@
+/// BigQuery table schemas: the JSON field list `bq mk --table` and the
+/// tables API take, so landing tables for events can be provisioned from
+/// their schemas.
+///
+/// - Required properties are `REQUIRED` columns unless nullable; optional
+///   and nullable ones are `NULLABLE`. Elements are `REPEATED`, which
+///   BigQuery cannot make null, so a missing or null array loads as empty.
+/// - Properties become `RECORD`s and refs are inlined. A discriminator is
+///   one `RECORD` with its tag (`REQUIRED`) and every variant's fields
+///   (`NULLABLE`); a field two variants declare differently is `JSON`.
+/// - What BigQuery cannot type becomes `JSON`: the empty form, values
+///   (maps), arrays of arrays and a ref back into a definition already
+///   being inlined.
+/// - A schema whose root is not a Properties form gets a single column,
+///   `value`.
+/// - `metadata.description` becomes the column description.
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+
+/// The table's field list for `schema`.
+pub fn table_schema(schema: &CompiledSchema) -> Value {
+    let mut inlining = Vec::new();
+    let fields = match &schema.root {
+        node @ Node::Properties { .. } => record_fields(schema, node, "", &mut inlining),
+        node => vec![field(schema, "value", node, "", true, &mut inlining)],
+    };
+    Value::Array(fields)
+}
+
+/// Pretty-printed output for `--target bigquery`.
+pub fn emit(schema: &CompiledSchema) -> String {
+    let mut out =
+        serde_json::to_string_pretty(&table_schema(schema)).expect("JSON values serialize");
+    out.push('\n');
+    out
+}
+
+/// The column `name` for `node` at schema path `path`.
+fn field<'a>(
+    schema: &'a CompiledSchema,
+    name: &str,
+    node: &'a Node,
+    path: &str,
+    required: bool,
+    inlining: &mut Vec<&'a str>,
+) -> Value {
+    let depth = inlining.len();
+    let (node, node_path, nullable) = resolve(schema, node, path, inlining);
+    let mut out = match node {
+        Node::Elements { schema: item } => {
+            let item_path = format!("{node_path}/elements");
+            let (item, item_path, _) = resolve(schema, item, &item_path, inlining);
+            let mut out = match item {
+                Node::Elements { .. } => json!({"name": name, "type": "JSON"}),
+                item => column(schema, name, item, &item_path, inlining),
+            };
+            if out["type"] != "JSON" {
+                out["mode"] = "REPEATED".into();
+            }
+            out
+        }
+        node => {
+            let mut out = column(schema, name, node, &node_path, inlining);
+            out["mode"] = if required && !nullable {
+                "REQUIRED"
+            } else {
+                "NULLABLE"
+            }
+            .into();
+            out
+        }
+    };
+    inlining.truncate(depth);
+    let desc = schema
+        .description(path)
+        .or_else(|| schema.description(&node_path));
+    if let Some(desc) = desc {
+        out["description"] = desc.into();
+    }
+    out
+}
+
+/// Strip nullable and follow refs from `node` at `path`, pushing each
+/// definition followed onto `inlining`. Returns the form reached, its
+/// schema path and whether null is allowed on the way. A ref back into a
+/// definition already on the stack resolves to the empty form.
+fn resolve<'a>(
+    schema: &'a CompiledSchema,
+    mut node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> (&'a Node, String, bool) {
+    let mut path = path.to_string();
+    let mut nullable = false;
+    loop {
+        match node {
+            Node::Nullable { inner } => {
+                nullable = true;
+                node = inner;
+            }
+            Node::Ref { name } if inlining.contains(&name.as_str()) => {
+                return (&Node::Empty, path, nullable);
+            }
+            Node::Ref { name } => {
+                inlining.push(name);
+                node = &schema.definitions[name];
+                path = format!("/definitions/{}", escape_pointer(name));
+            }
+            _ => return (node, path, nullable),
+        }
+    }
+}
+
+/// Name and type (with sub-fields for records) of a column for `node`.
+fn column<'a>(
+    schema: &'a CompiledSchema,
+    name: &str,
+    node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Value {
+    let t = match node {
+        Node::Type { type_kw } => match type_kw {
+            TypeKeyword::Boolean => "BOOLEAN",
+            TypeKeyword::String => "STRING",
+            TypeKeyword::Timestamp => "TIMESTAMP",
+            TypeKeyword::Float32 | TypeKeyword::Float64 => "FLOAT",
+            _ => "INTEGER",
+        },
+        Node::Enum { .. } => "STRING",
+        Node::Properties { .. } => {
+            let fields = record_fields(schema, node, path, inlining);
+            return json!({"name": name, "type": "RECORD", "fields": fields});
+        }
+        Node::Discriminator { tag, mapping } => {
+            let fields = union_fields(schema, tag, mapping, path, inlining);
+            return json!({"name": name, "type": "RECORD", "fields": fields});
+        }
+        _ => "JSON",
+    };
+    json!({"name": name, "type": t})
+}
+
+fn record_fields<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Vec<Value> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return Vec::new();
+    };
+    let required = required.iter().map(|(k, n)| (k, n, "properties", true));
+    let optional = optional
+        .iter()
+        .map(|(k, n)| (k, n, "optionalProperties", false));
+    required
+        .chain(optional)
+        .map(|(key, child, keyword, is_required)| {
+            let child_path = format!("{path}/{keyword}/{}", escape_pointer(key));
+            field(schema, key, child, &child_path, is_required, inlining)
+        })
+        .collect()
+}
+
+/// The tag and the fields of every variant, each `NULLABLE` since only one
+/// variant's are present in a row.
+fn union_fields<'a>(
+    schema: &'a CompiledSchema,
+    tag: &str,
+    mapping: &'a BTreeMap<String, Node>,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Vec<Value> {
+    let mut fields = vec![json!({"name": tag, "type": "STRING", "mode": "REQUIRED"})];
+    for (value, variant) in mapping {
+        let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+        for mut f in record_fields(schema, variant, &variant_path, inlining) {
+            if f["mode"] == "REQUIRED" {
+                f["mode"] = "NULLABLE".into();
+            }
+            match fields.iter_mut().find(|g| g["name"] == f["name"]) {
+                Some(g) if *g != f => {
+                    *g = json!({"name": f["name"], "type": "JSON", "mode": "NULLABLE"});
+                }
+                Some(_) => {}
+                None => fields.push(f),
+            }
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+
+    #[test]
+    fn test_table_schema() {
+        let schema = compile(&json!({
+            "definitions": {
+                "node": {
+                    "properties": {"n": {"type": "uint8"}},
+                    "optionalProperties": {"next": {"ref": "node"}}
+                }
+            },
+            "properties": {
+                "id": {"type": "string", "metadata": {"description": "Event id"}},
+                "at": {"type": "timestamp", "nullable": true},
+                "tags": {"elements": {"type": "string"}},
+                "grid": {"elements": {"elements": {"type": "float64"}}},
+                "head": {"ref": "node"},
+                "labels": {"values": {"type": "string"}},
+                "pet": {"discriminator": "kind", "mapping": {
+                    "cat": {"properties": {"lives": {"type": "uint8"}, "name": {"type": "string"}}},
+                    "dog": {"properties": {"name": {"type": "string"}, "good": {"type": "boolean"}}}
+                }}
+            },
+            "optionalProperties": {"score": {"type": "float32"}}
+        }))
+        .unwrap();
+        assert_eq!(
+            table_schema(&schema),
+            json!([
+                {"name": "at", "type": "TIMESTAMP", "mode": "NULLABLE"},
+                {"name": "grid", "type": "JSON"},
+                {"name": "head", "type": "RECORD", "mode": "REQUIRED", "fields": [
+                    {"name": "n", "type": "INTEGER", "mode": "REQUIRED"},
+                    {"name": "next", "type": "JSON", "mode": "NULLABLE"}
+                ]},
+                {"name": "id", "type": "STRING", "mode": "REQUIRED", "description": "Event id"},
+                {"name": "labels", "type": "JSON", "mode": "REQUIRED"},
+                {"name": "pet", "type": "RECORD", "mode": "REQUIRED", "fields": [
+                    {"name": "kind", "type": "STRING", "mode": "REQUIRED"},
+                    {"name": "lives", "type": "INTEGER", "mode": "NULLABLE"},
+                    {"name": "name", "type": "STRING", "mode": "NULLABLE"},
+                    {"name": "good", "type": "BOOLEAN", "mode": "NULLABLE"}
+                ]},
+                {"name": "tags", "type": "STRING", "mode": "REPEATED"},
+                {"name": "score", "type": "FLOAT", "mode": "NULLABLE"}
+            ])
+        );
+
+        let schema = compile(&json!({"elements": {"type": "int32"}})).unwrap();
+        assert_eq!(
+            table_schema(&schema),
+            json!([{"name": "value", "type": "INTEGER", "mode": "REPEATED"}])
+        );
+    }
+}
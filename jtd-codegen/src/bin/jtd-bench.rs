@@ -0,0 +1,298 @@
+/// Benchmark: the generated JavaScript validator against ajv's JTD
+/// validator, both run in embedded QuickJS on the same schemas and
+/// instances. Built with the `bench` feature.
+///
+/// Usage:
+///   jtd-bench --ajv ajvJTD.bundle.js --schema schema.json --instances instances.json
+///   jtd-bench --ajv ajvJTD.bundle.js --suite validation.json --json > report.json
+///
+/// `--ajv` is the browser bundle from the `ajv-dist` package. `--instances`
+/// holds a JSON array of instances; `--suite` takes the JTD validation suite
+/// and benchmarks each distinct schema on the instances its cases share.
+/// Each side is timed for at least `--time-ms` (default 200) per schema.
+/// ajv runs with `allErrors`, since the generated validator reports every
+/// error too.
+///
+/// The report gives validations per second for each side and their ratio,
+/// with the geometric mean of the ratios at the end. The exit status is 1
+/// when the two disagree on any instance, or when `--min-ratio <r>` is given
+/// and the mean falls below it, so CI can catch performance regressions.
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use jtd_codegen::options::EmitOptions;
+use quickjs_rs::Context;
+use serde_json::{json, Value};
+
+struct Row {
+    name: String,
+    instances: usize,
+    generated: f64,
+    ajv: f64,
+    mismatches: usize,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut ajv_path: Option<&str> = None;
+    let mut schema_path: Option<&str> = None;
+    let mut instances_path: Option<&str> = None;
+    let mut suite_path: Option<&str> = None;
+    let mut time = Duration::from_millis(200);
+    let mut min_ratio: Option<f64> = None;
+    let mut json_report = false;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = |i: usize| {
+            args.get(i + 1).map(String::as_str).unwrap_or_else(|| {
+                eprintln!("{} needs a value", args[i]);
+                std::process::exit(2);
+            })
+        };
+        match args[i].as_str() {
+            "--ajv" => ajv_path = Some(value(i)),
+            "--schema" => schema_path = Some(value(i)),
+            "--instances" => instances_path = Some(value(i)),
+            "--suite" => suite_path = Some(value(i)),
+            "--time-ms" => {
+                let ms = value(i).parse().unwrap_or_else(|_| {
+                    eprintln!("--time-ms needs a whole number of milliseconds");
+                    std::process::exit(2);
+                });
+                time = Duration::from_millis(ms);
+            }
+            "--min-ratio" => {
+                min_ratio = Some(value(i).parse().unwrap_or_else(|_| {
+                    eprintln!("--min-ratio needs a number");
+                    std::process::exit(2);
+                }));
+            }
+            "--json" => {
+                json_report = true;
+                i += 1;
+                continue;
+            }
+            "--help" | "-h" => {
+                eprintln!("Usage: jtd-bench --ajv <ajvJTD.bundle.js> (--schema <s.json> --instances <a.json> | --suite <validation.json>)");
+                eprintln!("         [--time-ms <n>] [--min-ratio <r>] [--json]");
+                std::process::exit(0);
+            }
+            other => {
+                eprintln!("Unknown argument: {other}");
+                std::process::exit(2);
+            }
+        }
+        i += 2;
+    }
+
+    let Some(ajv_path) = ajv_path else {
+        eprintln!("--ajv is required (dist/ajvJTD.bundle.js from the ajv-dist package)");
+        std::process::exit(2);
+    };
+    let ajv = read(ajv_path);
+    let cases = match (schema_path, instances_path, suite_path) {
+        (Some(schema), Some(instances), None) => {
+            let Value::Array(instances) = parse(&read(instances)) else {
+                eprintln!("{instances}: expected a JSON array of instances");
+                std::process::exit(2);
+            };
+            vec![(schema.to_string(), parse(&read(schema)), instances)]
+        }
+        (None, None, Some(suite)) => suite_cases(&parse(&read(suite))),
+        _ => {
+            eprintln!("Give either --schema and --instances, or --suite");
+            std::process::exit(2);
+        }
+    };
+
+    let mut rows = Vec::new();
+    for (name, schema, instances) in &cases {
+        match bench(&ajv, schema, instances, time) {
+            Ok((generated, ajv, mismatches)) => rows.push(Row {
+                name: name.clone(),
+                instances: instances.len(),
+                generated,
+                ajv,
+                mismatches,
+            }),
+            Err(e) => eprintln!("skipped {name}: {e}"),
+        }
+    }
+    let mean = geomean(&rows);
+    let mismatches: usize = rows.iter().map(|r| r.mismatches).sum();
+
+    if json_report {
+        let rows: Vec<Value> = rows
+            .iter()
+            .map(|r| {
+                json!({
+                    "name": r.name,
+                    "instances": r.instances,
+                    "generated": r.generated,
+                    "ajv": r.ajv,
+                    "ratio": r.generated / r.ajv,
+                    "mismatches": r.mismatches,
+                })
+            })
+            .collect();
+        let report = json!({"rows": rows, "geomeanRatio": mean});
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        println!(
+            "{:<40} {:>9} {:>14} {:>14} {:>7}",
+            "schema", "instances", "generated/s", "ajv/s", "ratio"
+        );
+        for r in &rows {
+            let mut line = format!(
+                "{:<40} {:>9} {:>14.0} {:>14.0} {:>7.2}",
+                r.name,
+                r.instances,
+                r.generated,
+                r.ajv,
+                r.generated / r.ajv
+            );
+            if r.mismatches > 0 {
+                line.push_str(&format!("  ({} disagreements)", r.mismatches));
+            }
+            println!("{line}");
+        }
+        println!("geometric mean ratio (generated / ajv): {mean:.2}");
+    }
+
+    if mismatches > 0 {
+        eprintln!("the validators disagree on {mismatches} instances");
+        std::process::exit(1);
+    }
+    if let Some(min) = min_ratio {
+        if mean < min {
+            eprintln!("geometric mean ratio {mean:.2} is below --min-ratio {min}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read(path: &str) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Cannot read {path}: {e}");
+        std::process::exit(2);
+    })
+}
+
+fn parse(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|e| {
+        eprintln!("Invalid JSON: {e}");
+        std::process::exit(2);
+    })
+}
+
+/// One case per distinct schema in the suite, named after its first test,
+/// with the instances of every test using it.
+fn suite_cases(suite: &Value) -> Vec<(String, Value, Vec<Value>)> {
+    let mut by_schema: BTreeMap<String, (String, Value, Vec<Value>)> = BTreeMap::new();
+    for (name, case) in suite.as_object().into_iter().flatten() {
+        let schema = &case["schema"];
+        by_schema
+            .entry(schema.to_string())
+            .or_insert_with(|| (name.clone(), schema.clone(), Vec::new()))
+            .2
+            .push(case["instance"].clone());
+    }
+    let mut cases: Vec<_> = by_schema.into_values().collect();
+    cases.sort_by(|a, b| a.0.cmp(&b.0));
+    cases
+}
+
+/// Validations per second of the generated validator and of ajv, and the
+/// number of instances they disagree on.
+fn bench(
+    ajv: &str,
+    schema: &Value,
+    instances: &[Value],
+    time: Duration,
+) -> Result<(f64, f64, usize), String> {
+    let compiled = jtd_codegen::compiler::compile(schema).map_err(|e| e.to_string())?;
+    let code = jtd_codegen::emit_js::emit_with_options(&compiled, &EmitOptions::default())
+        .replace("export function", "function");
+    let per_pass = instances.len();
+    let instances = js_string(&Value::Array(instances.to_vec()));
+
+    let generated = Context::new().map_err(|e| format!("{e:?}"))?;
+    let setup = format!(
+        "{code}\n{}",
+        harness(&instances, "validate(x).length === 0")
+    );
+    generated.eval(&setup).map_err(|e| format!("{e:?}"))?;
+
+    let ajv_ctx = Context::new().map_err(|e| format!("{e:?}"))?;
+    // the UMD bundle attaches itself to `window` in browsers
+    let setup = format!(
+        "var window = globalThis;\n{ajv}\n\
+         const Ajv = globalThis.ajvJTD.default || globalThis.ajvJTD;\n\
+         const ajvValidate = new Ajv({{ allErrors: true }}).compile(JSON.parse({}));\n{}",
+        js_string(schema),
+        harness(&instances, "ajvValidate(x)")
+    );
+    ajv_ctx
+        .eval(&setup)
+        .map_err(|e| format!("ajv setup failed: {e:?}"))?;
+
+    let accepts = |ctx: &Context| -> Result<Vec<bool>, String> {
+        let out: String = ctx.eval_as("accepts()").map_err(|e| format!("{e:?}"))?;
+        serde_json::from_str(&out).map_err(|e| e.to_string())
+    };
+    let mismatches = accepts(&generated)?
+        .iter()
+        .zip(accepts(&ajv_ctx)?)
+        .filter(|(a, b)| **a != *b)
+        .count();
+
+    Ok((
+        throughput(&generated, per_pass, time)?,
+        throughput(&ajv_ctx, per_pass, time)?,
+        mismatches,
+    ))
+}
+
+/// `instances` (a JS string literal of a JSON array) and two functions:
+/// `accepts()`, the verdict on each as a JSON string, and `run(n)`, which
+/// checks all of them `n` times.
+fn harness(instances: &str, accepts: &str) -> String {
+    format!(
+        "const instances = JSON.parse({instances});\n\
+         function accepts() {{ return JSON.stringify(instances.map((x) => {accepts})); }}\n\
+         function run(n) {{\n\
+           let ok = 0;\n\
+           for (let i = 0; i < n; i++) for (const x of instances) if ({accepts}) ok++;\n\
+           return ok;\n\
+         }}"
+    )
+}
+
+/// Validations per second, doubling the passes until a batch takes `time`.
+fn throughput(ctx: &Context, per_pass: usize, time: Duration) -> Result<f64, String> {
+    let mut passes: u64 = 1;
+    loop {
+        let start = Instant::now();
+        ctx.eval(&format!("run({passes})"))
+            .map_err(|e| format!("{e:?}"))?;
+        let elapsed = start.elapsed();
+        if elapsed >= time || passes >= 1 << 30 {
+            return Ok((passes * per_pass as u64) as f64 / elapsed.as_secs_f64());
+        }
+        passes *= 2;
+    }
+}
+
+fn geomean(rows: &[Row]) -> f64 {
+    if rows.is_empty() {
+        return f64::NAN;
+    }
+    let sum: f64 = rows.iter().map(|r| (r.generated / r.ajv).ln()).sum();
+    (sum / rows.len() as f64).exp()
+}
+
+/// `value` serialized, as a JS string literal.
+fn js_string(value: &Value) -> String {
+    serde_json::to_string(&value.to_string()).unwrap()
+}
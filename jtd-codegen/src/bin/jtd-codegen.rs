@@ -2,17 +2,103 @@
 ///
 /// Usage:
 ///   jtd-codegen --target js     < schema.json > validator.mjs
+///   jtd-codegen --target js --runtime node < schema.json > validator.js
+///   jtd-codegen --target js --js-format bare < schema.json > validator.js
+///   jtd-codegen --target js --export-definitions order.json > order.mjs
+///   jtd-codegen --target js --minify order.json > order.min.mjs
+///   jtd-codegen --target js --definitions defs.json order.json customer.json > validators.mjs
+///   jtd-codegen --target ts     < schema.json > validator.ts
+///   jtd-codegen --target js --types validator.d.mts order.json > validator.mjs
 ///   jtd-codegen --target lua    < schema.json > validator.lua
+///   jtd-codegen --target lua --lua-format openresty order.json > order_validator.lua
 ///   jtd-codegen --target python < schema.json > validator.py
+///   jtd-codegen --target rust --is-valid < schema.json > validator.rs
 ///   jtd-codegen --target rust   < schema.json > validator.rs
 ///   jtd-codegen --target rust   schema.json   > validator.rs
+///   jtd-codegen --target rust -o gen/order.rs --manifest gen/manifest.json order.json
+///   jtd-codegen --target rust -o gen/order.rs --source-map gen/order.rs.map.json order.json
+///   jtd-codegen --target rust --rs-axum order.json > order.rs
+///   jtd-codegen --target rust --rs-types --rs-axum order.json > order.rs
+///   jtd-codegen --target rust --rs-types --rs-deserialize order.json > order.rs
+///   jtd-codegen --target rust --rs-no-std order.json > src/order.rs
+///   jtd-codegen --target rust --rs-tests order.json > src/order.rs
+///   jtd-codegen --target jsonforms schema.json > form.json
+///   jtd-codegen --target bigquery event.json > event-table.json
+///   jtd-codegen --target graphql order.json > order.graphql
+///   jtd-codegen --target typebox order.json > order.schema.ts
+///   jtd-codegen --target proto --proto-package example.orders order.json > order.proto
+///   jtd-codegen --target mongodb --mongo-collection orders order.json > coll-mod.json
+///   jtd-codegen --target sql --sql-check orders.doc order.json > order.sql
+///   jtd-codegen --target wasm -o order.wasm order.json
+///   jtd-codegen --target c -o order.c order.json
+///   jtd-codegen --target kotlin --kt-package com.example.orders order.json > OrderValidator.kt
+///   jtd-codegen --target dart order.json > lib/order_validator.dart
+///   jtd-codegen --target python --py-tests test_validator.py schema.json > validator.py
+///   jtd-codegen --target python --py-numbers strict schema.json > validator.py
+///   jtd-codegen --target js --extends base_event.json order.json > order.mjs
+///   jtd-codegen --target js --partial order.json > order-patch.mjs
+///   jtd-codegen --target rust --strict order.json > order.rs
+///   jtd-codegen --target js --fail-fast order.json > order.mjs
+///   jtd-codegen --target python --max-errors 100 order.json > order.py
+///   jtd-codegen --target lua --messages order.json > order.lua
+///   jtd-codegen --target python --path-segments order.json > order.py
+///   jtd-codegen --target js --dedup generated.json > generated.mjs
+///   jtd-codegen --target js --inline-threshold 1 order.json > order.mjs
+///   jtd-codegen --target ts --bundle-refs api/order.json > order.ts
+///   jtd-codegen --emit ast order.json > order.ast.json
+///   jtd-codegen --target js --middleware --coerce query.json > query.mjs
+///   jtd-codegen --target js --ndjson < requests.ndjson > results.ndjson
+///   jtd-codegen --target rust --registry https://schemas.example.com --schema-ref orders/order@3 > order.rs
+///   jtd-codegen init --template wasm|rust|js [dir]
+///   jtd-codegen convert --to json-schema order.json > order.schema.json
+///   jtd-codegen diff --compat backward old/order.json order.json
+///   jtd-codegen infer samples/*.json > order.json
+///   jtd-codegen verify --schema schema.json --target rust --against src/generated.rs
+use jtd_codegen::manifest::{checksum, Artifact};
+use jtd_codegen::options::Banner;
+use jtd_codegen::Target;
 use std::io::Read;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("init") => init(&args[2..]),
+        Some("convert") => convert(&args[2..]),
+        Some("diff") => diff(&args[2..]),
+        Some("infer") => infer(&args[2..]),
+        Some("verify") => verify(&args),
+        _ => run(&args, None),
+    }
+}
 
-    let mut target = "rust";
-    let mut file_path: Option<&str> = None;
+/// Generate from `args`, printing the result, or with `against` set,
+/// comparing it with that file instead.
+fn run(args: &[String], against: Option<&str>) {
+    let mut target = Target::Rust;
+    let mut file_paths: Vec<&str> = Vec::new();
+    let mut definitions_path: Option<&str> = None;
+    let mut extends: Vec<&str> = Vec::new();
+    let mut py_tests: Option<&str> = None;
+    let mut py_module = "validator";
+    let mut py_test_cases: Option<&str> = None;
+    let mut types_path: Option<&str> = None;
+    let mut opts = jtd_codegen::options::EmitOptions::default();
+    let mut ndjson = false;
+    let mut registry_url: Option<&str> = None;
+    let mut registry_cache: Option<&str> = None;
+    let mut schema_ref: Option<&str> = None;
+    let mut pin: Option<&str> = None;
+    let mut partial = false;
+    let mut partial_depth: Option<usize> = None;
+    let mut banner = true;
+    let mut strict = false;
+    let mut bundle_refs = false;
+    let mut emit_ast = false;
+    let mut out_path: Option<&str> = None;
+    let mut manifest_path: Option<&str> = None;
+    let mut source_map_path: Option<&str> = None;
+    // --target wasm: the wat target's output, assembled
+    let mut wasm_binary = false;
 
     let mut i = 1;
     while i < args.len() {
@@ -20,33 +106,1136 @@ fn main() {
             "--target" | "-t" => {
                 i += 1;
                 if i < args.len() {
-                    target = match args[i].as_str() {
-                        "js" | "javascript" => "js",
-                        "lua" => "lua",
-                        "python" | "py" => "python",
-                        "rust" | "rs" => "rust",
-                        other => {
-                            eprintln!(
-                                "Unknown target: {other}. Use 'js', 'lua', 'python', or 'rust'."
-                            );
-                            std::process::exit(1);
-                        }
-                    };
+                    wasm_binary = args[i] == "wasm";
+                    let name = if wasm_binary { "wat" } else { &args[i] };
+                    target = Target::parse(name).unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown target: {}. Use 'bigquery', 'c', 'dart', 'graphql', 'js', 'jsonforms', 'kotlin', 'lua', 'mongodb', 'proto', 'python', 'rust', 'sql', 'ts', 'typebox', 'wat' or 'wasm'.",
+                            args[i]
+                        );
+                        std::process::exit(1);
+                    });
+                }
+            }
+            "--enum-constants" => {
+                opts.enum_constants = true;
+            }
+            "--self-test" => {
+                opts.self_test = true;
+            }
+            "--deprecation-warnings" => {
+                opts.deprecation_warnings = true;
+            }
+            "--is-valid" => {
+                opts.is_valid = true;
+            }
+            "--validate-at" => {
+                opts.validate_at = true;
+            }
+            "--fail-fast" => {
+                opts.fail_fast = true;
+            }
+            "--messages" => {
+                opts.messages = true;
+            }
+            "--path-segments" => {
+                opts.path_segments = true;
+            }
+            "--dedup" => {
+                opts.dedup = true;
+            }
+            "--inline-threshold" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.inline_threshold = value.parse().unwrap_or_else(|_| {
+                    eprintln!("--inline-threshold needs a number of references, not '{value}'.");
+                    std::process::exit(1);
+                });
+            }
+            "--max-errors" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.max_errors =
+                    Some(value.parse().ok().filter(|&n| n > 0).unwrap_or_else(|| {
+                        eprintln!("--max-errors needs a number of errors above 0, not '{value}'.");
+                        std::process::exit(1);
+                    }));
+            }
+            "--suggestions" => {
+                opts.suggestions = true;
+            }
+            "--coerce" => {
+                opts.coerce = true;
+            }
+            "--strip-additional" => {
+                opts.strip_additional = true;
+            }
+            "--apply-defaults" => {
+                opts.apply_defaults = true;
+            }
+            "--mock" => {
+                opts.mock = true;
+            }
+            "--shape" => {
+                opts.shape = true;
+            }
+            "--rs-phf" => {
+                opts.rs_phf = true;
+            }
+            "--rs-axum" => {
+                opts.rs_axum = true;
+            }
+            "--rs-deserialize" => {
+                opts.rs_deserialize = true;
+            }
+            "--rs-types" => {
+                opts.rs_types = true;
+            }
+            "--rs-no-std" => {
+                opts.rs_no_std = true;
+            }
+            "--rs-tests" => {
+                opts.rs_tests = true;
+            }
+            "--sax" => {
+                opts.js_sax = true;
+            }
+            "--export-definitions" => {
+                opts.js_export_definitions = true;
+            }
+            "--minify" => {
+                opts.js_minify = true;
+            }
+            "--middleware" => {
+                opts.js_middleware = true;
+            }
+            "--ndjson" => {
+                ndjson = true;
+            }
+            "--no-banner" => {
+                banner = false;
+            }
+            "--output" | "-o" => {
+                i += 1;
+                out_path = args.get(i).map(String::as_str);
+            }
+            "--manifest" => {
+                i += 1;
+                manifest_path = args.get(i).map(String::as_str);
+            }
+            "--source-map" => {
+                i += 1;
+                source_map_path = args.get(i).map(String::as_str);
+            }
+            "--strict" => {
+                strict = true;
+            }
+            "--bundle-refs" => {
+                bundle_refs = true;
+            }
+            "--emit" => {
+                i += 1;
+                emit_ast = match args.get(i).map(String::as_str) {
+                    Some("ast") => true,
+                    Some("code") => false,
+                    other => {
+                        eprintln!(
+                            "Unknown --emit: {} (expected code or ast)",
+                            other.unwrap_or("")
+                        );
+                        std::process::exit(1);
+                    }
+                };
+            }
+            "--partial" => {
+                partial = true;
+            }
+            "--partial-depth" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                partial = true;
+                partial_depth = Some(value.parse().unwrap_or_else(|_| {
+                    eprintln!("--partial-depth needs a number of levels, not '{value}'.");
+                    std::process::exit(1);
+                }));
+            }
+            "--registry" => {
+                i += 1;
+                registry_url = args.get(i).map(String::as_str);
+            }
+            "--registry-cache" => {
+                i += 1;
+                registry_cache = args.get(i).map(String::as_str);
+            }
+            "--schema-ref" => {
+                i += 1;
+                schema_ref = args.get(i).map(String::as_str);
+            }
+            "--pin" => {
+                i += 1;
+                pin = args.get(i).map(String::as_str);
+            }
+            "--lua-dialect" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.lua_dialect =
+                    jtd_codegen::options::LuaDialect::parse(value).unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown Lua dialect: {value}. Use '5.1', '5.3', '5.4', or 'luajit'."
+                        );
+                        std::process::exit(1);
+                    });
+            }
+            "--lua-null-sentinel" => {
+                i += 1;
+                if let Some(value) = args.get(i) {
+                    opts.lua_null_sentinel =
+                        Some(jtd_codegen::options::LuaNullSentinel::parse(value));
                 }
             }
+            "--lua-empty-table" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                let strategy = jtd_codegen::options::LuaEmptyTable::parse(value)
+                    .unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown empty-table strategy: {value}. Use 'metatable', 'n-field', 'cjson', or 'lenient'."
+                        );
+                        std::process::exit(1);
+                    });
+                opts.lua_empty_table = Some(strategy);
+            }
+            "--lua-format" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.lua_format = jtd_codegen::options::LuaModuleFormat::parse(value)
+                    .unwrap_or_else(|| {
+                        eprintln!("Unknown Lua format: {value}. Use 'plain' or 'openresty'.");
+                        std::process::exit(1);
+                    });
+            }
+            "--runtime" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.js_runtime =
+                    jtd_codegen::options::JsRuntime::parse(value).unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown JS runtime: {value}. Use 'generic', 'node', or 'browser' ('deno' and 'edge' are aliases for 'generic')."
+                        );
+                        std::process::exit(1);
+                    });
+            }
+            "--js-format" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.js_module_format = Some(
+                    jtd_codegen::options::JsModuleFormat::parse(value).unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown JS module format: {value}. Use 'esm', 'cjs', 'iife', or 'bare'."
+                        );
+                        std::process::exit(1);
+                    }),
+                );
+            }
+            "--rs-backend" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.rs_backend =
+                    jtd_codegen::options::RsBackend::parse(value).unwrap_or_else(|| {
+                        eprintln!(
+                            "Unknown Rust backend: {value}. Use 'serde_json' or 'simd-json'."
+                        );
+                        std::process::exit(1);
+                    });
+            }
+            "--py-numbers" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                opts.py_numbers =
+                    jtd_codegen::options::PyNumbers::parse(value).unwrap_or_else(|| {
+                        eprintln!("Unknown Python number mode: {value}. Use 'loose' or 'strict'.");
+                        std::process::exit(1);
+                    });
+            }
+            "--types" => {
+                i += 1;
+                types_path = args.get(i).map(String::as_str);
+            }
+            "--py-tests" => {
+                i += 1;
+                py_tests = args.get(i).map(String::as_str);
+            }
+            "--py-module" => {
+                i += 1;
+                if let Some(name) = args.get(i) {
+                    py_module = name;
+                }
+            }
+            "--py-test-cases" => {
+                i += 1;
+                py_test_cases = args.get(i).map(String::as_str);
+            }
+            "--kt-package" => {
+                i += 1;
+                opts.kt_package = args.get(i).cloned();
+            }
+            "--proto-package" => {
+                i += 1;
+                opts.proto_package = args.get(i).cloned();
+            }
+            "--sql-prefix" => {
+                i += 1;
+                opts.sql_prefix = args.get(i).cloned();
+            }
+            "--sql-check" => {
+                i += 1;
+                opts.sql_check = args.get(i).cloned();
+            }
+            "--mongo-collection" => {
+                i += 1;
+                opts.mongo_collection = args.get(i).cloned();
+            }
             "--help" | "-h" => {
-                eprintln!("Usage: jtd-codegen [--target js|lua|python|rust] [schema.json]");
+                eprintln!(
+                    "Usage: jtd-codegen [--target js|ts|lua|python|rust|kotlin|dart|c|wat|wasm|sql|bigquery|graphql|jsonforms|mongodb|proto|typebox] [schema.json]"
+                );
+                eprintln!("       jtd-codegen init --template wasm|rust|js [dir]");
+                eprintln!("       jtd-codegen convert --to json-schema [schema.json] [-o <file>]");
+                eprintln!("       jtd-codegen diff [--compat backward|forward|full] <old.json> <new.json>");
+                eprintln!("       jtd-codegen infer [--enum-max <n>] [--values-min-keys <n>] [samples.json...] [-o <file>]");
+                eprintln!("       jtd-codegen verify --schema <s.json> --against <file> [options]");
                 eprintln!("  Reads JTD schema from file or stdin, emits code to stdout.");
+                eprintln!("  bigquery emits a table schema instead, jsonforms a JSON Forms");
+                eprintln!("  {{schema, uischema}} document, mongodb a {{\"$jsonSchema\": ...}} validator,");
+                eprintln!("  graphql SDL types, proto a proto3 file, typebox TypeBox schemas.");
+                eprintln!("  wat emits a WebAssembly text module validating a token buffer; wasm");
+                eprintln!("  assembles it to binary (write it with -o).");
+                eprintln!("  sql emits PL/pgSQL functions validating a jsonb value.");
+                eprintln!(
+                    "  init scaffolds a validator project in dir (default: current directory)."
+                );
+                eprintln!("  convert writes an equivalent JSON Schema (Draft 2020-12) document.");
+                eprintln!(
+                    "  diff lists the changes between two schema versions and exits 1 if any"
+                );
+                eprintln!("  breaks the compatibility asked for (default: full).");
+                eprintln!(
+                    "  infer writes a best-effort schema for the JSON values in the sample files"
+                );
+                eprintln!("  (or stdin), one or more per file.");
+                eprintln!("  verify regenerates with the same options and exits 1 with a diff if");
+                eprintln!("  file differs.");
+                eprintln!();
+                eprintln!("Common options:");
+                eprintln!(
+                    "  --enum-constants         Export each enum's values (e.g. STATUS_VALUES)"
+                );
+                eprintln!("  --self-test              Add selfTest() checking metadata.examples");
+                eprintln!("  --is-valid               Add isValid(instance), a boolean stopping at the first failure");
+                eprintln!("  --fail-fast              Return from validate after the first error");
+                eprintln!("  --max-errors <n>         Return from validate after n errors");
+                eprintln!("  --messages               Add a human-readable message to each error (JS, Python, Lua, Rust)");
+                eprintln!("  --path-segments          Return error paths as segment arrays (JS, Python, Lua)");
+                eprintln!("  --dedup                  Check repeated subschemas with one shared function (JS)");
+                eprintln!("  --inline-threshold <n>   Inline definitions referenced at most n times, or leaf ones (JS)");
+                eprintln!(
+                    "  --extends <file>         Merge a base Properties schema into the schema (repeatable)"
+                );
+                eprintln!(
+                    "  --mock                   Add mock(seed) returning a random valid instance"
+                );
+                eprintln!(
+                    "  --shape                  Export SHAPE: keys, enum values and tags by instance path"
+                );
+                eprintln!(
+                    "  --strict                 Reject schema members outside the RFC 8927 keywords"
+                );
+                eprintln!(
+                    "  --bundle-refs            Resolve refs like ./common.jtd.json#address to"
+                );
+                eprintln!(
+                    "                           definitions in other files, copied into the schema"
+                );
+                eprintln!(
+                    "  --partial                Make every required property optional, for PATCH bodies"
+                );
+                eprintln!(
+                    "  --partial-depth <n>      Like --partial, for the first n levels of objects only"
+                );
+                eprintln!(
+                    "  --no-banner              Omit the header recording version, schema hash and options"
+                );
+                eprintln!(
+                    "  --ndjson                 Read {{\"name\", \"schema\"}} lines from stdin, write one result per line"
+                );
+                eprintln!("  -o, --output <file>      Write the code to file instead of stdout");
+                eprintln!(
+                    "  --manifest <file>        Record inputs, options and outputs with their hashes"
+                );
+                eprintln!(
+                    "  --source-map <file>      Also write which output lines came from which schema path"
+                );
+                eprintln!();
+                eprintln!("Registry options:");
+                eprintln!("  --registry <url>         Schema registry base URL");
+                eprintln!("  --schema-ref <n[@v]>     Fetch GET <url>/<n>/<v> (version defaults to latest)");
+                eprintln!("  --registry-cache <dir>   Cache responses and revalidate them by ETag");
+                eprintln!("  --pin <sha256:hex>       Reject a schema whose digest differs");
+                eprintln!();
+                eprintln!("JavaScript options:");
+                eprintln!(
+                    "  --runtime <r>            generic (default, ESM; deno and edge are aliases),"
+                );
+                eprintln!("                           node (CommonJS), or browser (IIFE global)");
+                eprintln!(
+                    "  --js-format <f>          Override the runtime's module format: esm, cjs, iife or bare"
+                );
+                eprintln!(
+                    "  --definitions <file>     Shared {{\"definitions\": ...}} for a bundle"
+                );
+                eprintln!("  a.json b.json ...        Emit one module exporting validateA, validateB, ...");
+                eprintln!(
+                    "  --export-definitions     Also export validateAddress, ... for each definition"
+                );
+                eprintln!(
+                    "  --minify                 Compact output: no comments or indentation, short internal names"
+                );
+                eprintln!(
+                    "  --sax                    Add createSaxValidator() for streaming parse events"
+                );
+                eprintln!(
+                    "  --middleware             Add Express and Fastify wrappers answering 400 with the errors"
+                );
+                eprintln!(
+                    "  --types <file>           Also write a .d.ts with the data's types (and, for js,"
+                );
+                eprintln!("                           the module's exports)");
+                eprintln!();
+                eprintln!("Lua options:");
+                eprintln!("  --lua-dialect <v>        5.1 (default), 5.3, 5.4, or luajit");
+                eprintln!(
+                    "  --lua-null-sentinel <s>  dkjson (default), cjson, nil, or a Lua expression"
+                );
+                eprintln!(
+                    "  --lua-empty-table <s>    metatable (default), n-field, cjson, or lenient"
+                );
+                eprintln!(
+                    "  --lua-format <f>         plain (default) or openresty (implies cjson)"
+                );
+                eprintln!();
+                eprintln!("Rust options:");
+                eprintln!(
+                    "  --rs-backend <b>         serde_json (default) or simd-json (BorrowedValue)"
+                );
+                eprintln!(
+                    "  --rs-phf                 Look up enums, property names and tags in phf tables"
+                );
+                eprintln!(
+                    "  --rs-axum                Add a ValidatedJson<T> Axum extractor (serde_json only)"
+                );
+                eprintln!(
+                    "  --rs-deserialize         Add a validating Validated<T> serde wrapper (serde_json only)"
+                );
+                eprintln!(
+                    "  --rs-types               Add serde structs and enums for the data (root: Root)"
+                );
+                eprintln!(
+                    "  --rs-no-std              Use only core and alloc, for #![no_std] crates (serde_json only)"
+                );
+                eprintln!(
+                    "  --rs-tests               Append #[cfg(test)] smoke tests synthesized from the schema"
+                );
+                eprintln!();
+                eprintln!("Python options:");
+                eprintln!(
+                    "  --py-tests <file>        Also write a pytest module for the validator"
+                );
+                eprintln!("  --py-module <name>       Module the pytest file imports (default: validator)");
+                eprintln!("  --py-test-cases <file>   Extra cases in validation-suite format");
+                eprintln!(
+                    "  --py-numbers <m>         loose (default) or strict: ints must be int, floats finite"
+                );
+                eprintln!();
+                eprintln!("Kotlin options:");
+                eprintln!("  --kt-package <p>         Package of the generated file");
+                eprintln!();
+                eprintln!("Protobuf options:");
+                eprintln!("  --proto-package <p>      Package of the generated file");
+                eprintln!();
+                eprintln!("PostgreSQL options:");
+                eprintln!("  --sql-prefix <p>         Prefix of the function names (default: jtd)");
+                eprintln!(
+                    "  --sql-check <t.c>        Add a CHECK constraint on jsonb column c of table t"
+                );
+                eprintln!();
+                eprintln!("MongoDB options:");
+                eprintln!(
+                    "  --mongo-collection <c>   Emit a collMod command applying the validator to c"
+                );
                 std::process::exit(0);
             }
+            "--extends" => {
+                i += 1;
+                if let Some(path) = args.get(i) {
+                    extends.push(path);
+                }
+            }
+            "--definitions" => {
+                i += 1;
+                definitions_path = args.get(i).map(String::as_str);
+            }
             path => {
-                file_path = Some(path);
+                file_paths.push(path);
             }
         }
         i += 1;
     }
 
-    let json_str = match file_path {
+    if opts.rs_axum && opts.rs_backend != jtd_codegen::options::RsBackend::SerdeJson {
+        eprintln!("--rs-axum needs the serde_json backend.");
+        std::process::exit(1);
+    }
+    if opts.rs_deserialize && opts.rs_backend != jtd_codegen::options::RsBackend::SerdeJson {
+        eprintln!("--rs-deserialize needs the serde_json backend.");
+        std::process::exit(1);
+    }
+    if opts.rs_no_std && opts.rs_backend != jtd_codegen::options::RsBackend::SerdeJson {
+        eprintln!("--rs-no-std needs the serde_json backend.");
+        std::process::exit(1);
+    }
+
+    if let Some(path) = types_path {
+        if !matches!(target, Target::JavaScript | Target::TypeScript) {
+            eprintln!("--types {path} needs --target js or ts.");
+            std::process::exit(1);
+        }
+        if target == Target::JavaScript
+            && matches!(
+                opts.js_format(),
+                jtd_codegen::options::JsModuleFormat::Iife
+                    | jtd_codegen::options::JsModuleFormat::Bare
+            )
+        {
+            eprintln!("--types needs a module; IIFE and bare output export nothing.");
+            std::process::exit(1);
+        }
+        if ndjson || definitions_path.is_some() || file_paths.len() > 1 {
+            eprintln!("--types works on a single schema.");
+            std::process::exit(1);
+        }
+    }
+
+    if partial && (ndjson || definitions_path.is_some() || file_paths.len() > 1) {
+        eprintln!("--partial works on a single schema.");
+        std::process::exit(1);
+    }
+
+    if let Some(path) = source_map_path {
+        if ndjson || definitions_path.is_some() || file_paths.len() > 1 {
+            eprintln!("--source-map works on a single schema.");
+            std::process::exit(1);
+        }
+        if emit_ast || target.extension() == "json" {
+            eprintln!("--source-map {path} needs generated code; JSON output has no lines to map.");
+            std::process::exit(1);
+        }
+        if wasm_binary {
+            eprintln!("--source-map maps lines of text; use --target wat.");
+            std::process::exit(1);
+        }
+        if opts.js_minify {
+            eprintln!("--source-map cannot map minified JavaScript, which keeps few line breaks.");
+            std::process::exit(1);
+        }
+    }
+
+    if ndjson {
+        if against.is_some() || out_path.is_some() || manifest_path.is_some() {
+            eprintln!(
+                "--ndjson writes every result to stdout; it takes no --output or --manifest."
+            );
+            std::process::exit(1);
+        }
+        if !file_paths.is_empty() || definitions_path.is_some() || !extends.is_empty() {
+            eprintln!("--ndjson reads every schema from stdin; it takes no schema files.");
+            std::process::exit(1);
+        }
+        let stdin = std::io::stdin();
+        let summary = jtd_codegen::batch::run(stdin.lock(), std::io::stdout(), target, &opts)
+            .unwrap_or_else(|e| {
+                eprintln!("NDJSON I/O error: {e}");
+                std::process::exit(1);
+            });
+        if summary.failed > 0 {
+            eprintln!(
+                "{} of {} schemas failed",
+                summary.failed,
+                summary.ok + summary.failed
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if definitions_path.is_some() || file_paths.len() > 1 {
+        if target != Target::JavaScript && !emit_ast {
+            eprintln!("Bundling several schemas is only supported for --target js.");
+            std::process::exit(1);
+        }
+        // These need the metadata of one schema, which a bundle does not keep
+        let single: Vec<&str> = [
+            (opts.self_test, "--self-test"),
+            (opts.deprecation_warnings, "--deprecation-warnings"),
+            (opts.validate_at, "--validate-at"),
+            (opts.suggestions, "--suggestions"),
+            (opts.coerce, "--coerce"),
+            (opts.strip_additional, "--strip-additional"),
+            (opts.apply_defaults, "--apply-defaults"),
+            (opts.mock, "--mock"),
+            (opts.shape, "--shape"),
+            (opts.js_sax, "--sax"),
+            (opts.js_middleware, "--middleware"),
+        ]
+        .into_iter()
+        .filter_map(|(on, flag)| on.then_some(flag))
+        .collect();
+        if !single.is_empty() && !emit_ast {
+            eprintln!(
+                "{} cannot be used when bundling several schemas; generate each schema on its own.",
+                single.join(", ")
+            );
+            std::process::exit(1);
+        }
+        let shared = definitions_path
+            .map(|path| resolve_refs(bundle_refs, read_json_file(path), Some(path)))
+            .unwrap_or_else(|| serde_json::json!({}));
+        let schemas: Vec<(String, serde_json::Value)> = file_paths
+            .iter()
+            .map(|path| {
+                let name = std::path::Path::new(path)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(path);
+                let schema = resolve_refs(bundle_refs, read_json_file(path), Some(path));
+                (name.to_string(), apply_extends(&extends, schema))
+            })
+            .collect();
+        let mut entries = std::collections::BTreeMap::new();
+        for (path, (name, _)) in file_paths.iter().zip(&schemas) {
+            let entry = jtd_codegen::emit_js::bundle_fn_name(name);
+            if let Some(other) = entries.insert(entry.clone(), path) {
+                eprintln!("{other} and {path} would both export {entry}; rename one.");
+                std::process::exit(1);
+            }
+        }
+        let inputs: Vec<&serde_json::Value> = schemas.iter().map(|(_, s)| s).collect();
+        let inputs = serde_json::json!({"definitions": shared, "schemas": inputs});
+        let stamp = Banner::new(&inputs, recorded_options(args, &file_paths));
+        let schema_hash = stamp.schema_hash.clone();
+        if banner {
+            opts.banner = Some(stamp);
+        }
+        if strict {
+            let members = std::iter::once(&shared).chain(schemas.iter().map(|(_, s)| s));
+            for schema in members {
+                if let Err(e) = jtd_codegen::compiler::check_keywords(schema) {
+                    eprintln!("Invalid JTD schema: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        let bundle = jtd_codegen::compiler::compile_bundle(&shared, &schemas).unwrap_or_else(|e| {
+            eprintln!("Invalid JTD schema: {e}");
+            std::process::exit(1);
+        });
+        for warning in jtd_codegen::recursion::warnings(&bundle.definitions) {
+            eprintln!("warning: {warning}");
+        }
+        if emit_ast {
+            return output(&ast_json(&bundle), against, out_path);
+        }
+        let code = jtd_codegen::emit_js::emit_bundle(&bundle, &opts);
+        output(code.as_bytes(), against, out_path);
+        if let (Some(path), None) = (manifest_path, against) {
+            let inputs = definitions_path.iter().chain(&extends).chain(&file_paths);
+            let artifact = Artifact {
+                target: target.name().to_string(),
+                options: recorded_options(args, &file_paths),
+                schema_hash,
+                inputs: input_hashes(inputs.copied()),
+                output: out_path.map(String::from),
+                output_hash: checksum(code.as_bytes()),
+            };
+            write_manifest(path, &[artifact]);
+        }
+        return;
+    }
+
+    let schema = match schema_ref {
+        Some(reference) => {
+            let Some(url) = registry_url else {
+                eprintln!("--schema-ref needs --registry <url>.");
+                std::process::exit(1);
+            };
+            fetch_schema(url, registry_cache, reference, pin)
+        }
+        None => read_schema(file_paths.first().copied()),
+    };
+    let schema = resolve_refs(bundle_refs, schema, file_paths.first().copied());
+    let schema = apply_extends(&extends, schema);
+    let stamp = Banner::new(&schema, recorded_options(args, &file_paths));
+    let schema_hash = stamp.schema_hash.clone();
+    if banner {
+        opts.banner = Some(stamp);
+    }
+
+    let compiled = if strict {
+        jtd_codegen::compiler::compile_strict_located(&schema).map_err(|e| e.to_string())
+    } else {
+        jtd_codegen::compiler::compile(&schema).map_err(|e| e.to_string())
+    };
+    let mut compiled = compiled.unwrap_or_else(|e| {
+        eprintln!("Invalid JTD schema: {e}");
+        std::process::exit(1);
+    });
+    for warning in jtd_codegen::recursion::warnings(&compiled.definitions) {
+        eprintln!("warning: {warning}");
+    }
+    if partial {
+        compiled = jtd_codegen::partial::partial(&compiled, partial_depth);
+    }
+
+    if emit_ast {
+        return output(&ast_json(&compiled), against, out_path);
+    }
+
+    let (code, source_map) = match source_map_path {
+        Some(_) => {
+            let (code, map) = jtd_codegen::generate::emit_with_source_map(&compiled, target, &opts);
+            (code, Some(map))
+        }
+        None => (jtd_codegen::generate::emit(&compiled, target, &opts), None),
+    };
+    let code = code.into_bytes();
+    let code = if wasm_binary { assemble(&code) } else { code };
+
+    let mut tests_hash = None;
+    // verify only checks the validator; it writes no files
+    if let Some(tests_path) = py_tests.filter(|_| against.is_none()) {
+        let mut cases = jtd_codegen::emit_py::example_cases(&schema);
+        if let Some(cases_path) = py_test_cases {
+            let suite: serde_json::Value = std::fs::read_to_string(cases_path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    eprintln!("Cannot read test cases {cases_path}: {e}");
+                    std::process::exit(1);
+                });
+            cases.extend(jtd_codegen::emit_py::suite_cases(&suite, &schema));
+        }
+        let test_code = jtd_codegen::emit_py::emit_pytest(py_module, &cases);
+        tests_hash = Some(checksum(test_code.as_bytes()));
+        std::fs::write(tests_path, test_code).unwrap_or_else(|e| {
+            eprintln!("Cannot write {tests_path}: {e}");
+            std::process::exit(1);
+        });
+    }
+
+    let mut types_hash = None;
+    if let Some(types_path) = types_path.filter(|_| against.is_none()) {
+        // The TypeScript module declares its own functions
+        let types = if target == Target::TypeScript {
+            jtd_codegen::emit_ts::emit_types(&compiled)
+        } else {
+            jtd_codegen::emit_ts::emit_declarations(&compiled, &opts)
+        };
+        types_hash = Some(checksum(types.as_bytes()));
+        std::fs::write(types_path, types).unwrap_or_else(|e| {
+            eprintln!("Cannot write {types_path}: {e}");
+            std::process::exit(1);
+        });
+    }
+
+    output(&code, against, out_path);
+
+    let mut source_map_hash = None;
+    if let (Some(map_path), Some(map), None) = (source_map_path, source_map, against) {
+        let doc = map.to_json(target, out_path);
+        let text = serde_json::to_string_pretty(&doc).expect("JSON values serialize") + "\n";
+        source_map_hash = Some(checksum(text.as_bytes()));
+        std::fs::write(map_path, text).unwrap_or_else(|e| {
+            eprintln!("Cannot write {map_path}: {e}");
+            std::process::exit(1);
+        });
+    }
+
+    if let (Some(path), None) = (manifest_path, against) {
+        let inputs = input_hashes(file_paths.iter().chain(&extends).copied());
+        let target = if wasm_binary { "wasm" } else { target.name() };
+        let mut artifacts = vec![Artifact {
+            target: target.to_string(),
+            options: recorded_options(args, &file_paths),
+            schema_hash: schema_hash.clone(),
+            inputs: inputs.clone(),
+            output: out_path.map(String::from),
+            output_hash: checksum(&code),
+        }];
+        if let (Some(types_path), Some(hash)) = (types_path, types_hash) {
+            artifacts.push(Artifact {
+                target: "d.ts".to_string(),
+                options: recorded_options(args, &file_paths),
+                schema_hash: schema_hash.clone(),
+                inputs: inputs.clone(),
+                output: Some(types_path.to_string()),
+                output_hash: hash,
+            });
+        }
+        if let (Some(map_path), Some(hash)) = (source_map_path, source_map_hash) {
+            artifacts.push(Artifact {
+                target: "source-map".to_string(),
+                options: recorded_options(args, &file_paths),
+                schema_hash: schema_hash.clone(),
+                inputs: inputs.clone(),
+                output: Some(map_path.to_string()),
+                output_hash: hash,
+            });
+        }
+        if let (Some(tests_path), Some(hash)) = (py_tests, tests_hash) {
+            let mut inputs = inputs;
+            inputs.extend(input_hashes(py_test_cases));
+            artifacts.push(Artifact {
+                target: "pytest".to_string(),
+                options: recorded_options(args, &file_paths),
+                schema_hash,
+                inputs,
+                output: Some(tests_path.to_string()),
+                output_hash: hash,
+            });
+        }
+        write_manifest(path, &artifacts);
+    }
+}
+
+/// `--emit ast` output: `ast` as pretty-printed JSON.
+fn ast_json(ast: &impl serde::Serialize) -> Vec<u8> {
+    let text = serde_json::to_string_pretty(ast).expect("ASTs serialize") + "\n";
+    text.into_bytes()
+}
+
+/// `(path, checksum)` of each file in `paths`.
+fn input_hashes<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    paths
+        .into_iter()
+        .map(|path| {
+            let bytes = std::fs::read(path).unwrap_or_else(|e| {
+                eprintln!("Cannot read {path}: {e}");
+                std::process::exit(1);
+            });
+            (path.to_string(), checksum(&bytes))
+        })
+        .collect()
+}
+
+fn write_manifest(path: &str, artifacts: &[Artifact]) {
+    let doc = jtd_codegen::manifest::manifest(artifacts);
+    let text = serde_json::to_string_pretty(&doc).expect("JSON values serialize") + "\n";
+    std::fs::write(path, text).unwrap_or_else(|e| {
+        eprintln!("Cannot write {path}: {e}");
+        std::process::exit(1);
+    });
+}
+
+/// Print `code` or write it to `out_path`; with `against` set, compare it
+/// with that file instead and exit 1 with a diff when they differ.
+fn output(code: &[u8], against: Option<&str>, out_path: Option<&str>) {
+    let Some(path) = against else {
+        let written = match out_path {
+            Some(out) => std::fs::write(out, code).map_err(|e| format!("Cannot write {out}: {e}")),
+            None => std::io::Write::write_all(&mut std::io::stdout(), code)
+                .map_err(|e| format!("Cannot write to stdout: {e}")),
+        };
+        if let Err(e) = written {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    };
+    let checked_in = std::fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Cannot read {path}: {e}");
+        std::process::exit(1);
+    });
+    let regenerated = format!("{path} (regenerated)");
+    let diff = match (std::str::from_utf8(&checked_in), std::str::from_utf8(code)) {
+        (Ok(old), Ok(new)) => jtd_codegen::verify::diff(path, &regenerated, old, new),
+        // A .wasm file; there is no useful diff to show
+        _ => (checked_in != code).then(String::new),
+    };
+    match diff {
+        None => eprintln!("{path} is up to date"),
+        Some(diff) => {
+            print!("{diff}");
+            eprintln!("{path} is stale; regenerate it from its schema");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn assemble(wat: &[u8]) -> Vec<u8> {
+    jtd_codegen::emit_wasm::assemble(std::str::from_utf8(wat).expect("WAT is UTF-8"))
+}
+
+#[cfg(not(feature = "wasm"))]
+fn assemble(_: &[u8]) -> Vec<u8> {
+    eprintln!("--target wasm needs jtd-codegen built with the `wasm` feature; use --target wat.");
+    std::process::exit(1);
+}
+
+/// `convert --to <format> [schema.json] [-o <file>]`: the schema in another
+/// schema language.
+fn convert(args: &[String]) {
+    let mut format = None;
+    let mut path = None;
+    let mut out_path = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--to" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                format = Some(
+                    jtd_codegen::convert::Format::parse(value).unwrap_or_else(|| {
+                        eprintln!("Unknown format: {value} (expected json-schema)");
+                        std::process::exit(1);
+                    }),
+                );
+            }
+            "--output" | "-o" => {
+                i += 1;
+                out_path = args.get(i).map(String::as_str);
+            }
+            other => path = Some(other),
+        }
+        i += 1;
+    }
+    let Some(format) = format else {
+        eprintln!("Usage: jtd-codegen convert --to json-schema [schema.json] [-o <file>]");
+        std::process::exit(1);
+    };
+    let schema = read_schema(path);
+    let compiled = jtd_codegen::compiler::compile(&schema).unwrap_or_else(|e| {
+        eprintln!("Invalid JTD schema: {e}");
+        std::process::exit(1);
+    });
+    let doc = jtd_codegen::convert::convert(&compiled, format);
+    let text = serde_json::to_string_pretty(&doc).expect("JSON values serialize") + "\n";
+    output(text.as_bytes(), None, out_path);
+}
+
+/// `diff [--compat backward|forward|full] <old.json> <new.json>`: print each
+/// change between the schemas, exiting 1 when one breaks `--compat`.
+fn diff(args: &[String]) {
+    let mut compat = jtd_codegen::diff::Compatibility::Full;
+    let mut paths = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--compat" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                compat = jtd_codegen::diff::Compatibility::parse(value).unwrap_or_else(|| {
+                    eprintln!(
+                        "Unknown compatibility: {value} (expected backward, forward or full)"
+                    );
+                    std::process::exit(1);
+                });
+            }
+            other => paths.push(other),
+        }
+        i += 1;
+    }
+    let [old, new] = paths[..] else {
+        eprintln!("Usage: jtd-codegen diff [--compat backward|forward|full] <old.json> <new.json>");
+        std::process::exit(1);
+    };
+    let compile = |path| {
+        jtd_codegen::compiler::compile(&read_schema(Some(path))).unwrap_or_else(|e| {
+            eprintln!("Invalid JTD schema {path}: {e}");
+            std::process::exit(1);
+        })
+    };
+    let changes = jtd_codegen::diff::diff(&compile(old), &compile(new));
+    for change in &changes {
+        println!("{change}");
+    }
+    if changes.iter().any(|change| change.breaks(compat)) {
+        std::process::exit(1);
+    }
+}
+
+/// `infer [--enum-max <n>] [--values-min-keys <n>] [samples.json...] [-o
+/// <file>]`: a schema for every JSON value in the files, or stdin.
+fn infer(args: &[String]) {
+    let mut opts = jtd_codegen::infer::InferOptions::default();
+    let mut paths = Vec::new();
+    let mut out_path = None;
+    let mut i = 0;
+    let number = |flag: &str, value: Option<&String>| -> usize {
+        let value = value.map(String::as_str).unwrap_or("");
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("{flag} needs a number, not '{value}'.");
+            std::process::exit(1);
+        })
+    };
+    while i < args.len() {
+        match args[i].as_str() {
+            "--enum-max" => {
+                i += 1;
+                opts.enum_max_values = number("--enum-max", args.get(i));
+            }
+            "--values-min-keys" => {
+                i += 1;
+                opts.values_min_keys = number("--values-min-keys", args.get(i));
+            }
+            "--output" | "-o" => {
+                i += 1;
+                out_path = args.get(i).map(String::as_str);
+            }
+            other => paths.push(other),
+        }
+        i += 1;
+    }
+    let texts: Vec<(&str, String)> = if paths.is_empty() {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .unwrap_or_else(|e| {
+                eprintln!("Cannot read stdin: {e}");
+                std::process::exit(1);
+            });
+        vec![("stdin", buf)]
+    } else {
+        paths
+            .iter()
+            .map(|&path| {
+                let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+                    eprintln!("Cannot read {path}: {e}");
+                    std::process::exit(1);
+                });
+                (path, text)
+            })
+            .collect()
+    };
+    let mut samples = Vec::new();
+    for (name, text) in &texts {
+        // A file may hold one value or several, e.g. NDJSON
+        for value in serde_json::Deserializer::from_str(text).into_iter() {
+            samples.push(value.unwrap_or_else(|e| {
+                eprintln!("Invalid JSON in {name}: {e}");
+                std::process::exit(1);
+            }));
+        }
+    }
+    let schema = jtd_codegen::infer::infer(&samples, &opts);
+    let text = serde_json::to_string_pretty(&schema).expect("JSON values serialize") + "\n";
+    output(text.as_bytes(), None, out_path);
+}
+
+/// `verify --schema <file> --against <file> [options]`: regenerate in
+/// memory and compare. `--schema` may repeat for a JS bundle; the other
+/// options are those the file was generated with.
+fn verify(args: &[String]) {
+    let mut rest = vec![args[0].clone()];
+    let mut schemas = Vec::new();
+    let mut against = None;
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--schema" => {
+                i += 1;
+                schemas.extend(args.get(i).cloned());
+            }
+            "--against" => {
+                i += 1;
+                against = args.get(i).map(String::as_str);
+            }
+            other => rest.push(other.to_string()),
+        }
+        i += 1;
+    }
+    let Some(against) = against else {
+        eprintln!("Usage: jtd-codegen verify --schema <s.json> --against <file> [options]");
+        std::process::exit(1);
+    };
+    // Schemas go last, where they sit in a generating command line, so the
+    // banner records the same options
+    rest.extend(schemas);
+    run(&rest, Some(against));
+}
+
+/// `init --template <t> [dir]`: scaffold a validator project.
+fn init(args: &[String]) {
+    let mut template = None;
+    let mut dir = ".";
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--template" | "-t" => {
+                i += 1;
+                let value = args.get(i).map(String::as_str).unwrap_or("");
+                template = Some(
+                    jtd_codegen::scaffold::Template::parse(value).unwrap_or_else(|| {
+                        eprintln!("Unknown template: {value} (expected wasm, rust or js)");
+                        std::process::exit(1);
+                    }),
+                );
+            }
+            path => dir = path,
+        }
+        i += 1;
+    }
+    let Some(template) = template else {
+        eprintln!("Usage: jtd-codegen init --template wasm|rust|js [dir]");
+        std::process::exit(1);
+    };
+    match jtd_codegen::scaffold::init(std::path::Path::new(dir), template) {
+        Ok(written) => {
+            for path in written {
+                eprintln!("created {}", path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("init: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The arguments for the banner: everything but the schema files, whose
+/// content the banner's hash already identifies, and where the output,
+/// manifest and source map go, which do not change the code.
+fn recorded_options(args: &[String], file_paths: &[&str]) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--output" | "-o" | "--manifest" | "--source-map" => {
+                rest.next();
+            }
+            _ => options.push(arg.clone()),
+        }
+    }
+    for path in file_paths {
+        if let Some(i) = options.iter().rposition(|a| a == path) {
+            options.remove(i);
+        }
+    }
+    options
+}
+
+/// The schema from `path`, or from stdin when there is none.
+fn read_schema(path: Option<&str>) -> serde_json::Value {
+    let json_str = match path {
         Some(path) => std::fs::read_to_string(path).unwrap_or_else(|e| {
             eprintln!("Cannot read {path}: {e}");
             std::process::exit(1);
@@ -63,23 +1252,75 @@ fn main() {
         }
     };
 
-    let schema: serde_json::Value = serde_json::from_str(&json_str).unwrap_or_else(|e| {
+    serde_json::from_str(&json_str).unwrap_or_else(|e| {
         eprintln!("Invalid JSON: {e}");
         std::process::exit(1);
+    })
+}
+
+#[cfg(feature = "registry")]
+fn fetch_schema(
+    url: &str,
+    cache: Option<&str>,
+    reference: &str,
+    pin: Option<&str>,
+) -> serde_json::Value {
+    use jtd_codegen::registry::{Registry, SchemaRef};
+    let fetched = SchemaRef::parse(reference).and_then(|schema| {
+        let mut registry = Registry::new(url);
+        if let Some(dir) = cache {
+            registry = registry.cache_dir(dir);
+        }
+        registry.fetch(&schema, pin)
     });
+    fetched.unwrap_or_else(|e| {
+        eprintln!("Cannot fetch {reference}: {e}");
+        std::process::exit(1);
+    })
+}
 
-    let compiled = jtd_codegen::compiler::compile(&schema).unwrap_or_else(|e| {
-        eprintln!("Invalid JTD schema: {e}");
+#[cfg(not(feature = "registry"))]
+fn fetch_schema(_: &str, _: Option<&str>, _: &str, _: Option<&str>) -> serde_json::Value {
+    eprintln!("This jtd-codegen was built without the `registry` feature.");
+    std::process::exit(1);
+}
+
+fn read_json_file(path: &str) -> serde_json::Value {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Cannot read {path}: {e}");
         std::process::exit(1);
     });
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        eprintln!("Invalid JSON in {path}: {e}");
+        std::process::exit(1);
+    })
+}
 
-    let code = match target {
-        "js" => jtd_codegen::emit_js::emit(&compiled),
-        "lua" => jtd_codegen::emit_lua::emit(&compiled),
-        "python" => jtd_codegen::emit_py::emit(&compiled),
-        "rust" => jtd_codegen::emit_rs::emit(&compiled),
-        _ => unreachable!(),
-    };
+/// With `--bundle-refs`, `schema` read from `path` (stdin when `None`, refs
+/// then being relative to the current directory) with the definitions its
+/// cross-file refs name copied in.
+fn resolve_refs(
+    bundle_refs: bool,
+    schema: serde_json::Value,
+    path: Option<&str>,
+) -> serde_json::Value {
+    if !bundle_refs {
+        return schema;
+    }
+    let origin = std::path::Path::new(path.unwrap_or("stdin"));
+    jtd_codegen::bundler::bundle(&schema, origin, &mut jtd_codegen::bundler::read_json)
+        .unwrap_or_else(|e| {
+            eprintln!("Cannot bundle refs: {e}");
+            std::process::exit(1);
+        })
+}
 
-    print!("{code}");
+/// Layer `schema` on top of each `--extends` base in turn.
+fn apply_extends(bases: &[&str], schema: serde_json::Value) -> serde_json::Value {
+    bases.iter().rev().fold(schema, |schema, path| {
+        jtd_codegen::compose::extend(&read_json_file(path), &schema).unwrap_or_else(|e| {
+            eprintln!("Cannot extend {path}: {e}");
+            std::process::exit(1);
+        })
+    })
 }
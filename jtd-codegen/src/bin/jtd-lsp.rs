@@ -0,0 +1,11 @@
+/// Language server for JTD schema files: speaks LSP over stdio.
+///
+/// Usage (configure your editor to start it for `*.jtd.json`):
+///   jtd-lsp
+fn main() {
+    let stdin = std::io::stdin();
+    if let Err(e) = jtd_codegen::lsp::run(stdin.lock(), std::io::stdout().lock()) {
+        eprintln!("jtd-lsp: {e}");
+        std::process::exit(1);
+    }
+}
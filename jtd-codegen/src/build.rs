@@ -0,0 +1,175 @@
+/// Helpers for `build.rs` files: read a schema, compile it, emit code for a
+/// target into `OUT_DIR` and tell Cargo to rerun when the schema changes,
+/// in one call per schema.
+///
+/// ```no_run
+/// let out_dir = std::env::var("OUT_DIR").unwrap();
+/// jtd_codegen::build::generate("schemas/user.json", jtd_codegen::Target::Rust, &out_dir)
+///     .unwrap();
+/// ```
+use std::path::{Path, PathBuf};
+
+use crate::generate::{GenerateError, Target};
+use crate::options::{EmitOptions, JsModuleFormat};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("cannot read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{}: {source}", path.display())]
+    Generate {
+        path: PathBuf,
+        source: GenerateError,
+    },
+    #[error("cannot write {}: {source}", path.display())]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Generate `target` code for the schema at `schema_path` into `out_dir`,
+/// named after the schema (`schemas/user.json` → `user.rs`), and print
+/// `cargo:rerun-if-changed` for the schema. Returns the written file.
+pub fn generate(
+    schema_path: impl AsRef<Path>,
+    target: Target,
+    out_dir: impl AsRef<Path>,
+) -> Result<PathBuf, BuildError> {
+    generate_with_options(schema_path, target, out_dir, &EmitOptions::default())
+}
+
+/// [`generate`] with the given options.
+pub fn generate_with_options(
+    schema_path: impl AsRef<Path>,
+    target: Target,
+    out_dir: impl AsRef<Path>,
+    opts: &EmitOptions,
+) -> Result<PathBuf, BuildError> {
+    let path = schema_path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+    let schema = std::fs::read_to_string(path).map_err(|source| BuildError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let code = crate::generate::generate(&schema, target, opts).map_err(|source| {
+        BuildError::Generate {
+            path: path.to_path_buf(),
+            source,
+        }
+    })?;
+    let mut name = path.file_stem().unwrap_or(path.as_os_str()).to_os_string();
+    name.push(".");
+    name.push(extension(target, opts));
+    let dest = out_dir.as_ref().join(name);
+    std::fs::write(&dest, code).map_err(|source| BuildError::Write {
+        path: dest.clone(),
+        source,
+    })?;
+    Ok(dest)
+}
+
+/// The output's extension. JavaScript follows the module format, so Node
+/// loads CommonJS as `.cjs` and ES modules as `.mjs` whatever the package's
+/// `"type"`; scripts (IIFE and bare) get `.js`.
+fn extension(target: Target, opts: &EmitOptions) -> &'static str {
+    match (target, opts.js_format()) {
+        (Target::JavaScript, JsModuleFormat::Esm) => "mjs",
+        (Target::JavaScript, JsModuleFormat::CommonJs) => "cjs",
+        (Target::JavaScript, JsModuleFormat::Iife | JsModuleFormat::Bare) => "js",
+        _ => target.extension(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::JsRuntime;
+
+    #[test]
+    fn test_generate_names_output_after_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = dir.path().join("user.json");
+        std::fs::write(&schema, r#"{"properties": {"name": {"type": "string"}}}"#).unwrap();
+        let dest = generate(&schema, Target::Rust, dir.path()).unwrap();
+        assert_eq!(dest, dir.path().join("user.rs"));
+        let code = std::fs::read_to_string(&dest).unwrap();
+        assert!(code.contains("pub fn validate(instance: &Value)"));
+        let js = generate(&schema, Target::JavaScript, dir.path()).unwrap();
+        assert_eq!(js, dir.path().join("user.mjs"));
+    }
+
+    #[test]
+    fn test_js_extension_follows_module_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = dir.path().join("user.json");
+        std::fs::write(&schema, r#"{"type": "string"}"#).unwrap();
+        for (runtime, ext) in [
+            (JsRuntime::Generic, "mjs"),
+            (JsRuntime::Node, "cjs"),
+            (JsRuntime::Browser, "js"),
+        ] {
+            let opts = EmitOptions {
+                js_runtime: runtime,
+                ..EmitOptions::default()
+            };
+            let dest =
+                generate_with_options(&schema, Target::JavaScript, dir.path(), &opts).unwrap();
+            assert_eq!(dest, dir.path().join(format!("user.{ext}")));
+        }
+        let bare = EmitOptions {
+            js_module_format: Some(JsModuleFormat::Bare),
+            ..EmitOptions::default()
+        };
+        let dest = generate_with_options(&schema, Target::JavaScript, dir.path(), &bare).unwrap();
+        assert_eq!(dest, dir.path().join("user.js"));
+    }
+
+    #[test]
+    fn test_js_extension_of_each_module_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = dir.path().join("user.json");
+        std::fs::write(&schema, r#"{"type": "string"}"#).unwrap();
+        for (format, ext) in [
+            (JsModuleFormat::Esm, "mjs"),
+            (JsModuleFormat::CommonJs, "cjs"),
+            (JsModuleFormat::Iife, "js"),
+            (JsModuleFormat::Bare, "js"),
+        ] {
+            // The format wins over the runtime's default
+            let opts = EmitOptions {
+                js_runtime: JsRuntime::Node,
+                js_module_format: Some(format),
+                ..EmitOptions::default()
+            };
+            let dest =
+                generate_with_options(&schema, Target::JavaScript, dir.path(), &opts).unwrap();
+            assert_eq!(dest, dir.path().join(format!("user.{ext}")), "{format:?}");
+        }
+        let opts = EmitOptions {
+            js_module_format: Some(JsModuleFormat::CommonJs),
+            ..EmitOptions::default()
+        };
+        let dest = generate_with_options(&schema, Target::Rust, dir.path(), &opts).unwrap();
+        assert_eq!(dest, dir.path().join("user.rs"));
+    }
+
+    #[test]
+    fn test_generate_errors_name_the_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.json");
+        let err = generate(&missing, Target::Rust, dir.path()).unwrap_err();
+        assert!(matches!(err, BuildError::Read { .. }));
+        assert!(err.to_string().contains("missing.json"));
+
+        let invalid = dir.path().join("invalid.json");
+        std::fs::write(&invalid, r#"{"type": "nope"}"#).unwrap();
+        let err = generate(&invalid, Target::Rust, dir.path()).unwrap_err();
+        assert!(matches!(err, BuildError::Generate { .. }));
+        assert!(err.to_string().starts_with(&invalid.display().to_string()));
+        assert!(!dir.path().join("invalid.rs").exists());
+    }
+}
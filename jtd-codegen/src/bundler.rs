@@ -0,0 +1,350 @@
+/// Cross-file refs, an opt-in extension to RFC 8927: with `--bundle-refs`,
+/// a `ref` such as `"./common.jtd.json#address"` names the definition
+/// `address` in another file, relative to the file holding the ref. The
+/// bundler copies every definition reached that way into the root schema's
+/// `definitions` and points the refs at the copies, so the result is one
+/// plain JTD schema that compiles and emits like any other.
+///
+/// Copied definitions are named `<file stem>_<name>`, the stem without
+/// `.json` and `.jtd` and with other characters than letters, digits and
+/// `_` replaced by `_` (`common_address`). A name already taken gets `_2`,
+/// `_3`, ... in the order refs are met, so the same files always bundle to
+/// the same schema. A ref whose value is a definition of its own file stays
+/// local, so valid JTD schemas bundle unchanged.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum BundleError {
+    #[error("cannot read {0}: {1}")]
+    Load(String, String),
+    #[error("{0} has no definition '{1}'")]
+    MissingDefinition(String, String),
+}
+
+/// Bundle the schema file at `path`.
+pub fn bundle_file(path: &Path) -> Result<Value, BundleError> {
+    let mut load = read_json;
+    let schema = load(path)?;
+    bundle(&schema, path, &mut load)
+}
+
+/// Bundle `schema`, read from `origin`, loading other files with `load`.
+pub fn bundle(
+    schema: &Value,
+    origin: &Path,
+    load: &mut dyn FnMut(&Path) -> Result<Value, BundleError>,
+) -> Result<Value, BundleError> {
+    let origin = normalize(origin);
+    let mut bundler = Bundler {
+        load,
+        files: BTreeMap::new(),
+        names: BTreeMap::new(),
+        taken: local_names(schema),
+        todo: Vec::new(),
+        root: origin.clone(),
+        root_names: local_names(schema),
+    };
+    let mut out = schema.clone();
+    bundler.rewrite(&mut out, &origin, true)?;
+
+    let mut copies = Map::new();
+    while let Some((file, name)) = bundler.todo.pop() {
+        let mut def = bundler
+            .file(&file)?
+            .get("definitions")
+            .and_then(|defs| defs.get(&name))
+            .cloned()
+            .ok_or_else(|| BundleError::MissingDefinition(display(&file), name.clone()))?;
+        bundler.rewrite(&mut def, &file, false)?;
+        copies.insert(bundler.names[&(file, name)].clone(), def);
+    }
+    if !copies.is_empty() {
+        if let Some(obj) = out.as_object_mut() {
+            let defs = obj
+                .entry("definitions")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(defs) = defs.as_object_mut() {
+                defs.extend(copies);
+            }
+        }
+    }
+    Ok(out)
+}
+
+struct Bundler<'a> {
+    load: &'a mut dyn FnMut(&Path) -> Result<Value, BundleError>,
+    /// Files loaded so far.
+    files: BTreeMap<PathBuf, Value>,
+    /// The root schema's name for each definition copied from another file.
+    names: BTreeMap<(PathBuf, String), String>,
+    taken: BTreeSet<String>,
+    /// Definitions named but not copied yet.
+    todo: Vec<(PathBuf, String)>,
+    root: PathBuf,
+    root_names: BTreeSet<String>,
+}
+
+impl Bundler<'_> {
+    fn file(&mut self, path: &Path) -> Result<&Value, BundleError> {
+        if !self.files.contains_key(path) {
+            let value = (self.load)(path)?;
+            self.files.insert(path.to_path_buf(), value);
+        }
+        Ok(&self.files[path])
+    }
+
+    /// Point the refs in `schema`, from `file`, at the root's definitions.
+    /// Definitions are only walked at the top of the root schema.
+    fn rewrite(&mut self, schema: &mut Value, file: &Path, top: bool) -> Result<(), BundleError> {
+        let local = if file == self.root {
+            self.root_names.clone()
+        } else {
+            local_names(self.file(file)?)
+        };
+        self.walk(schema, file, &local, top)
+    }
+
+    fn walk(
+        &mut self,
+        schema: &mut Value,
+        file: &Path,
+        local: &BTreeSet<String>,
+        top: bool,
+    ) -> Result<(), BundleError> {
+        let Some(obj) = schema.as_object_mut() else {
+            return Ok(());
+        };
+        if let Some(Value::String(name)) = obj.get_mut("ref") {
+            if let Some(target) = self.target(name, file, local)? {
+                *name = target;
+            }
+        }
+        for keyword in ["properties", "optionalProperties", "mapping", "definitions"] {
+            if keyword == "definitions" && !top {
+                continue;
+            }
+            if let Some(Value::Object(children)) = obj.get_mut(keyword) {
+                for child in children.values_mut() {
+                    self.walk(child, file, local, false)?;
+                }
+            }
+        }
+        for keyword in ["elements", "values"] {
+            if let Some(child) = obj.get_mut(keyword) {
+                self.walk(child, file, local, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The root's name for the definition `name` refers to from `file`, or
+    /// `None` when it already is one.
+    fn target(
+        &mut self,
+        name: &str,
+        file: &Path,
+        local: &BTreeSet<String>,
+    ) -> Result<Option<String>, BundleError> {
+        let is_root = file == self.root;
+        // Refs into a file's own definitions
+        if local.contains(name) {
+            if is_root {
+                return Ok(None);
+            }
+            return self.copy(file.to_path_buf(), name.to_string()).map(Some);
+        }
+        let (target_file, def) = match name.split_once('#') {
+            Some((path, def)) if !path.is_empty() => {
+                let dir = file.parent().unwrap_or(Path::new(""));
+                (normalize(&dir.join(path)), def.to_string())
+            }
+            // Left for the compiler to report
+            _ if is_root => return Ok(None),
+            _ => return Err(BundleError::MissingDefinition(display(file), name.into())),
+        };
+        if target_file == self.root {
+            return Ok(Some(def));
+        }
+        self.copy(target_file, def).map(Some)
+    }
+
+    /// The root's name for the definition `name` of `file`, queueing it to
+    /// be copied the first time.
+    fn copy(&mut self, file: PathBuf, name: String) -> Result<String, BundleError> {
+        let key = (file, name);
+        if let Some(mangled) = self.names.get(&key) {
+            return Ok(mangled.clone());
+        }
+        let mangled = self.mangle(&key.0, &key.1);
+        self.names.insert(key.clone(), mangled.clone());
+        self.todo.push(key);
+        Ok(mangled)
+    }
+
+    fn mangle(&mut self, file: &Path, name: &str) -> String {
+        let file_name = file.file_name().and_then(|s| s.to_str()).unwrap_or("");
+        let stem = file_name.strip_suffix(".json").unwrap_or(file_name);
+        let stem = stem.strip_suffix(".jtd").unwrap_or(stem);
+        let stem: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let base = format!("{stem}_{name}");
+        let mut mangled = base.clone();
+        let mut n = 2;
+        while !self.taken.insert(mangled.clone()) {
+            mangled = format!("{base}_{n}");
+            n += 1;
+        }
+        mangled
+    }
+}
+
+/// The names `schema` defines.
+fn local_names(schema: &Value) -> BTreeSet<String> {
+    schema
+        .get("definitions")
+        .and_then(Value::as_object)
+        .map(|defs| defs.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// `path` with `.` and `..` resolved without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+/// The JSON in the file at `path`.
+pub fn read_json(path: &Path) -> Result<Value, BundleError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| BundleError::Load(display(path), e.to_string()))?;
+    serde_json::from_str(&text).map_err(|e| BundleError::Load(display(path), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    fn bundle_in(files: &[(&str, Value)], root: &str) -> Result<Value, BundleError> {
+        let files: BTreeMap<PathBuf, Value> = files
+            .iter()
+            .map(|(path, value)| (PathBuf::from(path), value.clone()))
+            .collect();
+        let mut load = |path: &Path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| BundleError::Load(display(path), "not found".into()))
+        };
+        bundle(&files[Path::new(root)], Path::new(root), &mut load)
+    }
+
+    #[test]
+    fn test_bundle() {
+        let files = [
+            (
+                "api/order.json",
+                json!({
+                    "definitions": {"common_address": {"type": "string"}},
+                    "properties": {
+                        "ship": {"ref": "../shared/common.jtd.json#address"},
+                        "bill": {"ref": "./../shared/common.jtd.json#address"},
+                        "note": {"ref": "common_address"},
+                        "lines": {"elements": {"ref": "line.json#line"}}
+                    }
+                }),
+            ),
+            (
+                "shared/common.jtd.json",
+                json!({"definitions": {
+                    "address": {"properties": {"zip": {"ref": "zip"}}},
+                    "zip": {"type": "string"}
+                }}),
+            ),
+            (
+                "api/line.json",
+                json!({"definitions": {
+                    "line": {"properties": {"qty": {"type": "uint8"}, "to": {"ref": "../shared/common.jtd.json#address"}}}
+                }}),
+            ),
+        ];
+        let out = bundle_in(&files, "api/order.json").unwrap();
+        assert_eq!(
+            out["properties"]["ship"],
+            json!({"ref": "common_address_2"})
+        );
+        assert_eq!(
+            out["properties"]["bill"],
+            json!({"ref": "common_address_2"})
+        );
+        assert_eq!(out["properties"]["note"], json!({"ref": "common_address"}));
+        assert_eq!(
+            out["definitions"],
+            json!({
+                "common_address": {"type": "string"},
+                "common_address_2": {"properties": {"zip": {"ref": "common_zip"}}},
+                "common_zip": {"type": "string"},
+                "line_line": {"properties": {"qty": {"type": "uint8"}, "to": {"ref": "common_address_2"}}}
+            })
+        );
+        assert!(compile(&out).is_ok());
+    }
+
+    #[test]
+    fn test_bundle_errors() {
+        let files = [
+            ("a.json", json!({"ref": "b.json#missing"})),
+            ("b.json", json!({"definitions": {"x": {"ref": "y"}}})),
+        ];
+        assert_eq!(
+            bundle_in(&files, "a.json"),
+            Err(BundleError::MissingDefinition(
+                "b.json".into(),
+                "missing".into()
+            ))
+        );
+        let files = [("a.json", json!({"ref": "b.json#x"})), files[1].clone()];
+        assert_eq!(
+            bundle_in(&files, "a.json"),
+            Err(BundleError::MissingDefinition("b.json".into(), "y".into()))
+        );
+        let files = [("a.json", json!({"ref": "c.json#x"}))];
+        assert_eq!(
+            bundle_in(&files, "a.json"),
+            Err(BundleError::Load("c.json".into(), "not found".into()))
+        );
+    }
+
+    #[test]
+    fn test_bundle_plain_jtd_unchanged() {
+        let schema = json!({
+            "definitions": {"a#b": {"type": "string"}, "self": {"ref": "a.json#self"}},
+            "ref": "a#b"
+        });
+        let files = [("a.json", schema.clone())];
+        let out = bundle_in(&files, "a.json").unwrap();
+        assert_eq!(out["ref"], "a#b");
+        assert_eq!(out["definitions"]["self"], json!({"ref": "self"}));
+    }
+}
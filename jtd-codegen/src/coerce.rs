@@ -0,0 +1,211 @@
+/// Plan for the `coerce(instance)` function emitted with the `coerce`
+/// option: which parts of an instance hold a boolean or number slot that a
+/// query string or form post may have delivered as text.
+///
+/// Like ajv's `coerceTypes`, a slot typed `boolean` accepts `"true"` and
+/// `"false"`, a float slot accepts a JSON-number string, and an integer slot
+/// accepts a JSON-number string or a number with a zero fraction when the
+/// value is integral. Anything else is left for `validate` to reject. The
+/// plan keeps only paths that lead to such a slot.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+
+/// How a scalar slot is coerced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    Boolean,
+    Integer,
+    Float,
+}
+
+impl Scalar {
+    fn of(type_kw: TypeKeyword) -> Option<Scalar> {
+        match type_kw {
+            TypeKeyword::Boolean => Some(Scalar::Boolean),
+            TypeKeyword::Int8
+            | TypeKeyword::Uint8
+            | TypeKeyword::Int16
+            | TypeKeyword::Uint16
+            | TypeKeyword::Int32
+            | TypeKeyword::Uint32 => Some(Scalar::Integer),
+            TypeKeyword::Float32 | TypeKeyword::Float64 => Some(Scalar::Float),
+            TypeKeyword::String | TypeKeyword::Timestamp => None,
+        }
+    }
+}
+
+/// What to coerce for one schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coerce {
+    Scalar(Scalar),
+    /// Known properties of an object, when present.
+    Object(Vec<(String, Coerce)>),
+    /// Every element of an array.
+    Array(Box<Coerce>),
+    /// Every value of an object.
+    Map(Box<Coerce>),
+    /// A definition's coercion function (`coerce_<name>`).
+    Ref(String),
+    /// An object whose properties depend on the string at `tag`.
+    Tagged {
+        tag: String,
+        variants: Vec<(String, Vec<(String, Coerce)>)>,
+    },
+}
+
+/// Plans for the root and for each definition with something to coerce.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoercePlan {
+    pub root: Option<Coerce>,
+    pub definitions: BTreeMap<String, Coerce>,
+}
+
+impl CoercePlan {
+    /// True when some slot is coerced as `scalar`, so its helper is needed.
+    pub fn uses(&self, scalar: Scalar) -> bool {
+        fn walk(c: &Coerce, scalar: Scalar) -> bool {
+            match c {
+                Coerce::Scalar(s) => *s == scalar,
+                Coerce::Object(props) => props.iter().any(|(_, c)| walk(c, scalar)),
+                Coerce::Array(inner) | Coerce::Map(inner) => walk(inner, scalar),
+                Coerce::Ref(_) => false,
+                Coerce::Tagged { variants, .. } => variants
+                    .iter()
+                    .flat_map(|(_, props)| props)
+                    .any(|(_, c)| walk(c, scalar)),
+            }
+        }
+        self.root
+            .iter()
+            .chain(self.definitions.values())
+            .any(|c| walk(c, scalar))
+    }
+}
+
+/// Build the coercion plan for `schema`.
+pub fn coerce_plan(schema: &CompiledSchema) -> CoercePlan {
+    // Definitions that reach a scalar slot, directly or via refs.
+    let mut live: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let before = live.len();
+        for (name, node) in &schema.definitions {
+            if !live.contains(name) && plan(node, &live).is_some() {
+                live.insert(name.clone());
+            }
+        }
+        if live.len() == before {
+            break;
+        }
+    }
+
+    let definitions = live
+        .iter()
+        .filter_map(|name| Some((name.clone(), plan(&schema.definitions[name], &live)?)))
+        .collect();
+    CoercePlan {
+        root: plan(&schema.root, &live),
+        definitions,
+    }
+}
+
+fn plan(node: &Node, live: &BTreeSet<String>) -> Option<Coerce> {
+    match node {
+        Node::Type { type_kw } => Scalar::of(*type_kw).map(Coerce::Scalar),
+        Node::Nullable { inner } => plan(inner, live),
+        Node::Ref { name } => live.contains(name).then(|| Coerce::Ref(name.clone())),
+        Node::Elements { schema } => plan(schema, live).map(|c| Coerce::Array(Box::new(c))),
+        Node::Values { schema } => plan(schema, live).map(|c| Coerce::Map(Box::new(c))),
+        Node::Properties { .. } => {
+            let props = props(node, live);
+            (!props.is_empty()).then_some(Coerce::Object(props))
+        }
+        Node::Discriminator { tag, mapping } => {
+            let variants: Vec<(String, Vec<(String, Coerce)>)> = mapping
+                .iter()
+                .map(|(value, variant)| (value.clone(), props(variant, live)))
+                .filter(|(_, props)| !props.is_empty())
+                .collect();
+            (!variants.is_empty()).then(|| Coerce::Tagged {
+                tag: tag.clone(),
+                variants,
+            })
+        }
+        Node::Empty | Node::Enum { .. } => None,
+    }
+}
+
+fn props(node: &Node, live: &BTreeSet<String>) -> Vec<(String, Coerce)> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return Vec::new();
+    };
+    required
+        .iter()
+        .chain(optional)
+        .filter_map(|(key, child)| Some((key.clone(), plan(child, live)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn plan_of(schema: serde_json::Value) -> CoercePlan {
+        coerce_plan(&compiler::compile(&schema).unwrap())
+    }
+
+    #[test]
+    fn test_nothing_to_coerce() {
+        let p = plan_of(json!({"properties": {"a": {"type": "string"}, "b": {"enum": ["x"]}}}));
+        assert_eq!(p, CoercePlan::default());
+    }
+
+    #[test]
+    fn test_scalars() {
+        let p = plan_of(json!({
+            "properties": {
+                "page": {"type": "uint32"},
+                "debug": {"type": "boolean", "nullable": true},
+                "q": {"type": "string"}
+            },
+            "optionalProperties": {"ratio": {"type": "float64"}}
+        }));
+        assert_eq!(
+            p.root,
+            Some(Coerce::Object(vec![
+                ("debug".into(), Coerce::Scalar(Scalar::Boolean)),
+                ("page".into(), Coerce::Scalar(Scalar::Integer)),
+                ("ratio".into(), Coerce::Scalar(Scalar::Float)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_refs_and_containers() {
+        let p = plan_of(json!({
+            "definitions": {
+                "n": {"type": "int8"},
+                "s": {"type": "string"}
+            },
+            "properties": {
+                "ids": {"elements": {"ref": "n"}},
+                "names": {"values": {"ref": "s"}}
+            }
+        }));
+        assert_eq!(p.definitions.keys().collect::<Vec<_>>(), vec!["n"]);
+        assert!(p.uses(Scalar::Integer));
+        assert!(!p.uses(Scalar::Boolean));
+        assert_eq!(
+            p.root,
+            Some(Coerce::Object(vec![(
+                "ids".into(),
+                Coerce::Array(Box::new(Coerce::Ref("n".into())))
+            )]))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 /// Schema compiler: parses a JTD JSON schema into the intermediate AST.
 /// Implements Section 3.2 and 3.3 of the JTD Code Generation Specification.
-use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::ast::{CompiledSchema, Node, SchemaBundle, TypeKeyword};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashSet};
 
@@ -36,6 +36,20 @@ pub enum CompileError {
     MappingNotProperties,
     #[error("discriminator tag '{0}' must not appear in mapping variant properties")]
     TagInVariant(String),
+    #[error("discriminator mapping ref '{0}' contains itself as a variant")]
+    RecursiveMappingRef(String),
+    #[error("unknown keyword '{0}'")]
+    UnknownKeyword(String),
+    #[error("'{keyword}' is not allowed in the {form} form")]
+    KeywordNotAllowed { keyword: String, form: String },
+    #[error("nullable must be a boolean")]
+    NullableNotBoolean,
+    #[error("additionalProperties must be a boolean")]
+    AdditionalPropertiesNotBoolean,
+    #[error("metadata must be a JSON object")]
+    MetadataNotObject,
+    #[error("definition '{0}' differs between bundled schemas")]
+    ConflictingDefinition(String),
     #[error("{0}")]
     Other(String),
 }
@@ -43,9 +57,33 @@ pub enum CompileError {
 // We implement thiserror-like Display manually since we can't use the derive macro
 // without adding thiserror dependency. Let's just add it.
 
+/// A [`CompileError`] with the JSON Pointer of the schema location that
+/// caused it, e.g. `/properties/age/type` for an unknown type name.
+#[derive(Debug, thiserror::Error)]
+#[error("{error} (at '{path}')")]
+pub struct LocatedError {
+    pub path: String,
+    pub error: CompileError,
+}
+
+fn at(path: &str, error: CompileError) -> LocatedError {
+    LocatedError {
+        path: path.to_string(),
+        error,
+    }
+}
+
 /// Compile a JTD schema from a JSON value.
 pub fn compile(schema: &Value) -> Result<CompiledSchema, CompileError> {
-    let obj = schema.as_object().ok_or(CompileError::NotAnObject)?;
+    compile_located(schema).map_err(|e| e.error)
+}
+
+/// Like [`compile`], but report where in the schema the error is, for
+/// editors and other tools that point at the offending keyword.
+pub fn compile_located(schema: &Value) -> Result<CompiledSchema, LocatedError> {
+    let obj = schema
+        .as_object()
+        .ok_or_else(|| at("", CompileError::NotAnObject))?;
 
     let mut definitions = BTreeMap::new();
     let mut def_keys = Vec::new();
@@ -54,7 +92,7 @@ pub fn compile(schema: &Value) -> Result<CompiledSchema, CompileError> {
     if let Some(defs_val) = obj.get("definitions") {
         let defs_obj = defs_val
             .as_object()
-            .ok_or(CompileError::DefinitionsNotObject)?;
+            .ok_or_else(|| at("/definitions", CompileError::DefinitionsNotObject))?;
         for key in defs_obj.keys() {
             def_keys.push(key.clone());
             definitions.insert(key.clone(), Node::Empty); // placeholder
@@ -65,23 +103,196 @@ pub fn compile(schema: &Value) -> Result<CompiledSchema, CompileError> {
     if let Some(defs_val) = obj.get("definitions") {
         let defs_obj = defs_val.as_object().unwrap();
         for key in &def_keys {
-            let node = compile_node(defs_obj.get(key).unwrap(), false, &definitions)?;
+            let path = format!("/definitions/{}", escape_pointer(key));
+            let node = compile_node(defs_obj.get(key).unwrap(), &path, &definitions)?;
             definitions.insert(key.clone(), node);
         }
     }
 
     // Compile root (excluding definitions key)
-    let root = compile_node(schema, false, &definitions)?;
+    let mut root = compile_node(schema, "", &definitions)?;
+
+    // Pass 3: replace refs in discriminator mappings with their targets,
+    // now that every definition is compiled
+    let mut inlined = BTreeMap::new();
+    for (key, node) in &definitions {
+        let mut node = node.clone();
+        let path = format!("/definitions/{}", escape_pointer(key));
+        inline_mapping_refs(&mut node, &path, &definitions, &mut Vec::new())?;
+        inlined.insert(key.clone(), node);
+    }
+    inline_mapping_refs(&mut root, "", &definitions, &mut Vec::new())?;
+    let definitions = inlined;
+
+    let mut metadata = BTreeMap::new();
+    collect_metadata(schema, String::new(), &mut metadata);
+
+    Ok(CompiledSchema {
+        root,
+        definitions,
+        metadata,
+    })
+}
+
+/// Like [`compile`], but also reject members that are not JTD keywords,
+/// which RFC 8927 forbids and the default compile ignores, so a misspelt
+/// keyword is an error rather than a no-op.
+pub fn compile_strict(schema: &Value) -> Result<CompiledSchema, CompileError> {
+    compile_strict_located(schema).map_err(|e| e.error)
+}
+
+/// Like [`compile_strict`], reporting the schema path of the offending
+/// member.
+pub fn compile_strict_located(schema: &Value) -> Result<CompiledSchema, LocatedError> {
+    let compiled = compile_located(schema)?;
+    check_keywords(schema)?;
+    Ok(compiled)
+}
+
+/// The strict checks of [`compile_strict`] alone, for schemas compiled
+/// some other way, such as the members of a bundle.
+pub fn check_keywords(schema: &Value) -> Result<(), LocatedError> {
+    check_node(schema, "")
+}
+
+/// Every member RFC 8927 allows in a schema.
+const KEYWORDS: [&str; 13] = [
+    "definitions",
+    "metadata",
+    "nullable",
+    "ref",
+    "type",
+    "enum",
+    "elements",
+    "properties",
+    "optionalProperties",
+    "additionalProperties",
+    "values",
+    "discriminator",
+    "mapping",
+];
+
+fn check_node(json: &Value, path: &str) -> Result<(), LocatedError> {
+    // Shapes the compiler rejects anyway are left to it
+    let Some(obj) = json.as_object() else {
+        return Ok(());
+    };
+    if let Some(key) = obj.keys().find(|key| !KEYWORDS.contains(&key.as_str())) {
+        let key_path = format!("{path}/{}", escape_pointer(key));
+        return Err(at(&key_path, CompileError::UnknownKeyword(key.clone())));
+    }
+    for keyword in ["definitions", "properties", "optionalProperties", "mapping"] {
+        if let Some(Value::Object(children)) = obj.get(keyword) {
+            for (key, child) in children {
+                let child_path = format!("{path}/{keyword}/{}", escape_pointer(key));
+                check_node(child, &child_path)?;
+            }
+        }
+    }
+    for keyword in ["elements", "values"] {
+        if let Some(child) = obj.get(keyword) {
+            check_node(child, &format!("{path}/{keyword}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Record every `metadata` object by schema path. Runs after a successful
+/// compile, so the schema shape is already known to be valid.
+fn collect_metadata(
+    json: &Value,
+    path: String,
+    out: &mut BTreeMap<String, serde_json::Map<String, Value>>,
+) {
+    let Some(obj) = json.as_object() else {
+        return;
+    };
+    if let Some(Value::Object(meta)) = obj.get("metadata") {
+        out.insert(path.clone(), meta.clone());
+    }
+    for keyword in ["definitions", "properties", "optionalProperties", "mapping"] {
+        if let Some(Value::Object(children)) = obj.get(keyword) {
+            for (key, child) in children {
+                collect_metadata(
+                    child,
+                    format!("{path}/{keyword}/{}", escape_pointer(key)),
+                    out,
+                );
+            }
+        }
+    }
+    for keyword in ["elements", "values"] {
+        if let Some(child) = obj.get(keyword) {
+            collect_metadata(child, format!("{path}/{keyword}"), out);
+        }
+    }
+}
+
+/// Compile several named schemas that share the definitions in `shared`
+/// (a `{"definitions": {...}}` object). A schema may also carry its own
+/// definitions; a name defined differently in two places is an error.
+pub fn compile_bundle(
+    shared: &Value,
+    schemas: &[(String, Value)],
+) -> Result<SchemaBundle, CompileError> {
+    let empty = serde_json::Map::new();
+    let shared_defs = match shared.get("definitions") {
+        Some(defs) => defs.as_object().ok_or(CompileError::DefinitionsNotObject)?,
+        None => &empty,
+    };
 
-    Ok(CompiledSchema { root, definitions })
+    let mut definitions = BTreeMap::new();
+    let mut roots = Vec::new();
+    for (name, schema) in schemas {
+        let mut merged = schema.as_object().ok_or(CompileError::NotAnObject)?.clone();
+        let defs = merged
+            .entry("definitions")
+            .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            .as_object_mut()
+            .ok_or(CompileError::DefinitionsNotObject)?;
+        for (key, def) in shared_defs {
+            match defs.get(key) {
+                Some(own) if own != def => {
+                    return Err(CompileError::ConflictingDefinition(key.clone()))
+                }
+                Some(_) => {}
+                None => {
+                    defs.insert(key.clone(), def.clone());
+                }
+            }
+        }
+
+        let compiled = compile(&Value::Object(merged))?;
+        for (key, node) in compiled.definitions {
+            match definitions.get(&key) {
+                Some(existing) if *existing != node => {
+                    return Err(CompileError::ConflictingDefinition(key))
+                }
+                Some(_) => {}
+                None => {
+                    definitions.insert(key, node);
+                }
+            }
+        }
+        roots.push((name.clone(), compiled.root));
+    }
+
+    Ok(SchemaBundle { roots, definitions })
+}
+
+/// Escape a key for use as a JSON Pointer segment (RFC 6901).
+pub fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
 }
 
 fn compile_node(
     json: &Value,
-    _is_sub: bool,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
-    let obj = json.as_object().ok_or(CompileError::NotAnObject)?;
+) -> Result<Node, LocatedError> {
+    let obj = json
+        .as_object()
+        .ok_or_else(|| at(path, CompileError::NotAnObject))?;
 
     // Detect forms
     let mut forms = Vec::new();
@@ -108,20 +319,24 @@ fn compile_node(
     }
 
     if forms.len() > 1 {
-        return Err(CompileError::MultipleForms(
-            forms.iter().map(|s| s.to_string()).collect(),
+        return Err(at(
+            path,
+            CompileError::MultipleForms(forms.iter().map(|s| s.to_string()).collect()),
         ));
     }
 
-    let node = match forms.first().copied() {
+    let form = forms.first().copied();
+    check_members(obj, path, form.unwrap_or("empty"))?;
+
+    let node = match form {
         None => Node::Empty,
-        Some("ref") => compile_ref(obj, definitions)?,
-        Some("type") => compile_type(obj)?,
-        Some("enum") => compile_enum(obj)?,
-        Some("elements") => compile_elements(obj, definitions)?,
-        Some("properties") => compile_properties(obj, definitions)?,
-        Some("values") => compile_values(obj, definitions)?,
-        Some("discriminator") => compile_discriminator(obj, definitions)?,
+        Some("ref") => compile_ref(obj, path, definitions)?,
+        Some("type") => compile_type(obj, path)?,
+        Some("enum") => compile_enum(obj, path)?,
+        Some("elements") => compile_elements(obj, path, definitions)?,
+        Some("properties") => compile_properties(obj, path, definitions)?,
+        Some("values") => compile_values(obj, path, definitions)?,
+        Some("discriminator") => compile_discriminator(obj, path, definitions)?,
         _ => unreachable!(),
     };
 
@@ -137,46 +352,85 @@ fn compile_node(
     Ok(node)
 }
 
+/// RFC 8927 Section 2 rules on the members besides the form's own:
+/// `definitions` only at the root, a boolean `nullable`, an object
+/// `metadata`, and `additionalProperties` and `mapping` only in their forms.
+fn check_members(
+    obj: &serde_json::Map<String, Value>,
+    path: &str,
+    form: &str,
+) -> Result<(), LocatedError> {
+    for (key, value) in obj {
+        let key_path = format!("{path}/{}", escape_pointer(key));
+        let error = match key.as_str() {
+            "definitions" if !path.is_empty() => CompileError::DefinitionsInNonRoot,
+            "nullable" if !value.is_boolean() => CompileError::NullableNotBoolean,
+            "metadata" if !value.is_object() => CompileError::MetadataNotObject,
+            "additionalProperties" if form != "properties" => CompileError::KeywordNotAllowed {
+                keyword: key.clone(),
+                form: form.to_string(),
+            },
+            "additionalProperties" if !value.is_boolean() => {
+                CompileError::AdditionalPropertiesNotBoolean
+            }
+            "mapping" if form != "discriminator" => CompileError::KeywordNotAllowed {
+                keyword: key.clone(),
+                form: form.to_string(),
+            },
+            _ => continue,
+        };
+        return Err(at(&key_path, error));
+    }
+    Ok(())
+}
+
 fn compile_ref(
     obj: &serde_json::Map<String, Value>,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
+) -> Result<Node, LocatedError> {
+    let ref_path = format!("{path}/ref");
     let name = obj
         .get("ref")
         .and_then(|v| v.as_str())
-        .ok_or(CompileError::RefNotString)?;
+        .ok_or_else(|| at(&ref_path, CompileError::RefNotString))?;
     if !definitions.contains_key(name) {
-        return Err(CompileError::RefNotFound(name.to_string()));
+        return Err(at(&ref_path, CompileError::RefNotFound(name.to_string())));
     }
     Ok(Node::Ref {
         name: name.to_string(),
     })
 }
 
-fn compile_type(obj: &serde_json::Map<String, Value>) -> Result<Node, CompileError> {
+fn compile_type(obj: &serde_json::Map<String, Value>, path: &str) -> Result<Node, LocatedError> {
+    let type_path = format!("{path}/type");
     let type_str = obj
         .get("type")
         .and_then(|v| v.as_str())
-        .ok_or(CompileError::TypeNotString)?;
-    let type_kw =
-        TypeKeyword::parse(type_str).ok_or_else(|| CompileError::UnknownType(type_str.into()))?;
+        .ok_or_else(|| at(&type_path, CompileError::TypeNotString))?;
+    let type_kw = TypeKeyword::parse(type_str)
+        .ok_or_else(|| at(&type_path, CompileError::UnknownType(type_str.into())))?;
     Ok(Node::Type { type_kw })
 }
 
-fn compile_enum(obj: &serde_json::Map<String, Value>) -> Result<Node, CompileError> {
+fn compile_enum(obj: &serde_json::Map<String, Value>, path: &str) -> Result<Node, LocatedError> {
+    let enum_path = format!("{path}/enum");
     let arr = obj
         .get("enum")
         .and_then(|v| v.as_array())
-        .ok_or(CompileError::InvalidEnum)?;
+        .ok_or_else(|| at(&enum_path, CompileError::InvalidEnum))?;
     if arr.is_empty() {
-        return Err(CompileError::InvalidEnum);
+        return Err(at(&enum_path, CompileError::InvalidEnum));
     }
     let mut values = Vec::new();
     let mut seen = HashSet::new();
-    for v in arr {
-        let s = v.as_str().ok_or(CompileError::InvalidEnum)?;
+    for (i, v) in arr.iter().enumerate() {
+        let item_path = format!("{enum_path}/{i}");
+        let s = v
+            .as_str()
+            .ok_or_else(|| at(&item_path, CompileError::InvalidEnum))?;
         if !seen.insert(s) {
-            return Err(CompileError::EnumDuplicates);
+            return Err(at(&item_path, CompileError::EnumDuplicates));
         }
         values.push(s.to_string());
     }
@@ -185,10 +439,11 @@ fn compile_enum(obj: &serde_json::Map<String, Value>) -> Result<Node, CompileErr
 
 fn compile_elements(
     obj: &serde_json::Map<String, Value>,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
+) -> Result<Node, LocatedError> {
     let inner_val = obj.get("elements").unwrap();
-    let inner = compile_node(inner_val, true, definitions)?;
+    let inner = compile_node(inner_val, &format!("{path}/elements"), definitions)?;
     Ok(Node::Elements {
         schema: Box::new(inner),
     })
@@ -196,26 +451,38 @@ fn compile_elements(
 
 fn compile_properties(
     obj: &serde_json::Map<String, Value>,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
+) -> Result<Node, LocatedError> {
     let mut required = BTreeMap::new();
     let mut optional = BTreeMap::new();
 
     if let Some(props) = obj.get("properties") {
-        let props_obj = props.as_object().ok_or(CompileError::NotAnObject)?;
+        let props_path = format!("{path}/properties");
+        let props_obj = props
+            .as_object()
+            .ok_or_else(|| at(&props_path, CompileError::NotAnObject))?;
         for (key, schema) in props_obj {
-            let node = compile_node(schema, true, definitions)?;
+            let child_path = format!("{props_path}/{}", escape_pointer(key));
+            let node = compile_node(schema, &child_path, definitions)?;
             required.insert(key.clone(), node);
         }
     }
 
     if let Some(opt_props) = obj.get("optionalProperties") {
-        let opt_obj = opt_props.as_object().ok_or(CompileError::NotAnObject)?;
+        let opt_path = format!("{path}/optionalProperties");
+        let opt_obj = opt_props
+            .as_object()
+            .ok_or_else(|| at(&opt_path, CompileError::NotAnObject))?;
         for (key, schema) in opt_obj {
+            let child_path = format!("{opt_path}/{}", escape_pointer(key));
             if required.contains_key(key) {
-                return Err(CompileError::OverlappingProperties(key.clone()));
+                return Err(at(
+                    &child_path,
+                    CompileError::OverlappingProperties(key.clone()),
+                ));
             }
-            let node = compile_node(schema, true, definitions)?;
+            let node = compile_node(schema, &child_path, definitions)?;
             optional.insert(key.clone(), node);
         }
     }
@@ -234,10 +501,11 @@ fn compile_properties(
 
 fn compile_values(
     obj: &serde_json::Map<String, Value>,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
+) -> Result<Node, LocatedError> {
     let inner_val = obj.get("values").unwrap();
-    let inner = compile_node(inner_val, true, definitions)?;
+    let inner = compile_node(inner_val, &format!("{path}/values"), definitions)?;
     Ok(Node::Values {
         schema: Box::new(inner),
     })
@@ -245,32 +513,35 @@ fn compile_values(
 
 fn compile_discriminator(
     obj: &serde_json::Map<String, Value>,
+    path: &str,
     definitions: &BTreeMap<String, Node>,
-) -> Result<Node, CompileError> {
+) -> Result<Node, LocatedError> {
     let tag = obj
         .get("discriminator")
         .and_then(|v| v.as_str())
-        .ok_or(CompileError::DiscriminatorNotString)?
+        .ok_or_else(|| {
+            at(
+                &format!("{path}/discriminator"),
+                CompileError::DiscriminatorNotString,
+            )
+        })?
         .to_string();
 
-    let mapping_val = obj.get("mapping").ok_or(CompileError::MissingMapping)?;
+    let mapping_path = format!("{path}/mapping");
+    let mapping_val = obj
+        .get("mapping")
+        .ok_or_else(|| at(path, CompileError::MissingMapping))?;
     let mapping_obj = mapping_val
         .as_object()
-        .ok_or(CompileError::MissingMapping)?;
+        .ok_or_else(|| at(&mapping_path, CompileError::MissingMapping))?;
 
     let mut mapping = BTreeMap::new();
     for (key, schema) in mapping_obj {
-        let node = compile_node(schema, true, definitions)?;
-        // Verify it's a Properties node (not nullable)
-        match &node {
-            Node::Properties {
-                required, optional, ..
-            } => {
-                if required.contains_key(&tag) || optional.contains_key(&tag) {
-                    return Err(CompileError::TagInVariant(tag));
-                }
-            }
-            _ => return Err(CompileError::MappingNotProperties),
+        let variant_path = format!("{mapping_path}/{}", escape_pointer(key));
+        let node = compile_node(schema, &variant_path, definitions)?;
+        // A ref is checked once definitions are compiled; see inline_mapping_refs
+        if !matches!(node, Node::Ref { .. }) {
+            check_variant(&node, &tag, &variant_path)?;
         }
         mapping.insert(key.clone(), node);
     }
@@ -278,6 +549,84 @@ fn compile_discriminator(
     Ok(Node::Discriminator { tag, mapping })
 }
 
+/// Verify a mapping variant is a Properties node (not nullable) without
+/// the tag among its properties.
+fn check_variant(node: &Node, tag: &str, variant_path: &str) -> Result<(), LocatedError> {
+    match node {
+        Node::Properties {
+            required, optional, ..
+        } => {
+            if required.contains_key(tag) || optional.contains_key(tag) {
+                return Err(at(variant_path, CompileError::TagInVariant(tag.into())));
+            }
+            Ok(())
+        }
+        _ => Err(at(variant_path, CompileError::MappingNotProperties)),
+    }
+}
+
+/// Extension to RFC 8927: a mapping value may be `{"ref": "name"}` when the
+/// definition (after following refs) is a non-nullable Properties form. The
+/// variant is replaced by a copy of that form, so emitters only ever see
+/// inline variants. `inlining` holds the definitions being copied, to reject
+/// a variant that contains itself.
+fn inline_mapping_refs(
+    node: &mut Node,
+    path: &str,
+    definitions: &BTreeMap<String, Node>,
+    inlining: &mut Vec<String>,
+) -> Result<(), LocatedError> {
+    match node {
+        Node::Discriminator { tag, mapping } => {
+            for (key, variant) in mapping.iter_mut() {
+                let variant_path = format!("{path}/mapping/{}", escape_pointer(key));
+                let Node::Ref { name } = variant else {
+                    inline_mapping_refs(variant, &variant_path, definitions, inlining)?;
+                    continue;
+                };
+                let name = name.clone();
+                if inlining.contains(&name) {
+                    return Err(at(&variant_path, CompileError::RecursiveMappingRef(name)));
+                }
+                // Follow ref-to-ref definitions, giving up on a cycle
+                let mut target = &definitions[&name];
+                for _ in 0..definitions.len() {
+                    match target {
+                        Node::Ref { name } => target = &definitions[name],
+                        _ => break,
+                    }
+                }
+                check_variant(target, tag, &variant_path)?;
+                *variant = target.clone();
+                inlining.push(name);
+                inline_mapping_refs(variant, &variant_path, definitions, inlining)?;
+                inlining.pop();
+            }
+        }
+        Node::Properties {
+            required, optional, ..
+        } => {
+            for (key, child) in required.iter_mut() {
+                let child_path = format!("{path}/properties/{}", escape_pointer(key));
+                inline_mapping_refs(child, &child_path, definitions, inlining)?;
+            }
+            for (key, child) in optional.iter_mut() {
+                let child_path = format!("{path}/optionalProperties/{}", escape_pointer(key));
+                inline_mapping_refs(child, &child_path, definitions, inlining)?;
+            }
+        }
+        Node::Elements { schema } => {
+            inline_mapping_refs(schema, &format!("{path}/elements"), definitions, inlining)?
+        }
+        Node::Values { schema } => {
+            inline_mapping_refs(schema, &format!("{path}/values"), definitions, inlining)?
+        }
+        Node::Nullable { inner } => inline_mapping_refs(inner, path, definitions, inlining)?,
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => {}
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +785,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mapping_refs_are_inlined() {
+        let schema = json!({
+            "definitions": {
+                "cat": {"properties": {"lives": {"type": "uint8"}}},
+                "kitten": {"ref": "cat"},
+                "name": {"type": "string"},
+                "box": {"properties": {"pet": {"discriminator": "kind", "mapping": {"box": {"ref": "box"}}}}}
+            },
+            "discriminator": "kind",
+            "mapping": {"cat": {"ref": "cat"}, "kitten": {"ref": "kitten"}}
+        });
+        let err = compile(&json!({"definitions": schema["definitions"].clone(), "ref": "cat"}))
+            .unwrap_err();
+        assert!(matches!(err, CompileError::RecursiveMappingRef(name) if name == "box"));
+
+        let mut schema = schema;
+        schema["definitions"].as_object_mut().unwrap().remove("box");
+        let compiled = compile(&schema).unwrap();
+        let Node::Discriminator { mapping, .. } = &compiled.root else {
+            panic!("expected a discriminator");
+        };
+        assert_eq!(mapping["cat"], compiled.definitions["cat"]);
+        assert_eq!(mapping["kitten"], compiled.definitions["cat"]);
+
+        let path = |schema: Value| compile_located(&schema).unwrap_err().path;
+        let defs = json!({
+            "name": {"type": "string"},
+            "maybe": {"properties": {}, "nullable": true},
+            "tagged": {"properties": {"kind": {}}}
+        });
+        for target in ["name", "maybe", "tagged", "missing"] {
+            let schema = json!({
+                "definitions": defs,
+                "properties": {"p": {"discriminator": "kind", "mapping": {"a": {"ref": target}}}}
+            });
+            let expected = if target == "missing" {
+                "/properties/p/mapping/a/ref"
+            } else {
+                "/properties/p/mapping/a"
+            };
+            assert_eq!(path(schema), expected, "{target}");
+        }
+    }
+
     #[test]
     fn test_reject_multiple_forms() {
         let schema = json!({"type": "string", "enum": ["a"]});
@@ -456,4 +850,210 @@ mod tests {
         });
         assert!(compile(&schema).is_err());
     }
+
+    #[test]
+    fn test_reject_nested_definitions() {
+        let nested = json!({"definitions": {"a": {}}, "ref": "a"});
+        let cases = [
+            (json!({"elements": nested}), "/elements/definitions"),
+            (json!({"values": nested}), "/values/definitions"),
+            (
+                json!({"properties": {"a/b": nested}}),
+                "/properties/a~1b/definitions",
+            ),
+            (
+                json!({"optionalProperties": {"a": nested}}),
+                "/optionalProperties/a/definitions",
+            ),
+            (
+                json!({"discriminator": "k", "mapping": {"a": {"properties": {}, "definitions": {}}}}),
+                "/mapping/a/definitions",
+            ),
+            (
+                json!({"definitions": {"a": nested}}),
+                "/definitions/a/definitions",
+            ),
+        ];
+        for (schema, path) in cases {
+            let err = compile_located(&schema).unwrap_err();
+            assert_eq!(err.path, path);
+            assert!(matches!(err.error, CompileError::DefinitionsInNonRoot));
+        }
+        assert!(compile(&json!({"definitions": {"a": {}}, "ref": "a"})).is_ok());
+    }
+
+    #[test]
+    fn test_compile_bundle_shares_definitions() {
+        let shared = json!({"definitions": {"id": {"type": "string"}}});
+        let schemas = vec![
+            (
+                "order".to_string(),
+                json!({"properties": {"id": {"ref": "id"}}}),
+            ),
+            (
+                "customer".to_string(),
+                json!({"properties": {"id": {"ref": "id"}}}),
+            ),
+        ];
+        let bundle = compile_bundle(&shared, &schemas).unwrap();
+        assert_eq!(bundle.definitions.len(), 1);
+        assert_eq!(bundle.roots.len(), 2);
+        assert_eq!(bundle.roots[0].0, "order");
+        assert_eq!(bundle.roots[1].0, "customer");
+    }
+
+    #[test]
+    fn test_compile_bundle_rejects_conflicting_definitions() {
+        let shared = json!({"definitions": {"id": {"type": "string"}}});
+        let schemas = vec![(
+            "order".to_string(),
+            json!({"definitions": {"id": {"type": "uint32"}}, "ref": "id"}),
+        )];
+        assert!(matches!(
+            compile_bundle(&shared, &schemas),
+            Err(CompileError::ConflictingDefinition(name)) if name == "id"
+        ));
+    }
+
+    #[test]
+    fn test_compile_bundle_rejects_unresolved_ref() {
+        let schemas = vec![("order".to_string(), json!({"ref": "missing"}))];
+        assert!(matches!(
+            compile_bundle(&json!({}), &schemas),
+            Err(CompileError::RefNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_metadata_by_schema_path() {
+        let schema = json!({
+            "metadata": {"description": "An order"},
+            "definitions": {"id": {"type": "string", "metadata": {"description": "Order id"}}},
+            "properties": {
+                "items": {"elements": {"properties": {
+                    "sku": {"type": "string", "metadata": {"description": "Stock unit"}}
+                }}}
+            }
+        });
+        let compiled = compile(&schema).unwrap();
+        assert_eq!(compiled.description(""), Some("An order"));
+        assert_eq!(compiled.description("/definitions/id"), Some("Order id"));
+        assert_eq!(
+            compiled.description("/properties/items/elements/properties/sku"),
+            Some("Stock unit")
+        );
+        assert_eq!(compiled.description("/properties/items"), None);
+    }
+
+    #[test]
+    fn test_compile_located_paths() {
+        let path = |schema: Value| compile_located(&schema).unwrap_err().path;
+        assert_eq!(
+            path(json!({"properties": {"a/b": {"elements": {"type": "nope"}}}})),
+            "/properties/a~1b/elements/type"
+        );
+        assert_eq!(
+            path(json!({"definitions": {"x": {}}, "values": {"ref": "y"}})),
+            "/values/ref"
+        );
+        assert_eq!(path(json!({"enum": ["a", "b", "a"]})), "/enum/2");
+        assert_eq!(
+            path(json!({"discriminator": "t", "mapping": {"a": {"type": "string"}}})),
+            "/mapping/a"
+        );
+        assert_eq!(path(json!({"type": "string", "enum": ["a"]})), "");
+        let err = compile_located(&json!({"ref": 1})).unwrap_err();
+        assert!(matches!(err.error, CompileError::RefNotString));
+        assert_eq!(err.to_string(), "ref must be a string (at '/ref')");
+    }
+
+    #[test]
+    fn test_compile_strict() {
+        let schema = json!({
+            "definitions": {"id": {"type": "string", "metadata": {"x": 1}}},
+            "properties": {"id": {"ref": "id", "nullable": false}},
+            "additionalProperties": true
+        });
+        assert!(compile_strict(&schema).is_ok());
+
+        let err = compile_strict_located(
+            &json!({"properties": {"a": {"type": "string", "format": "x"}}}),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "/properties/a/format");
+        assert!(matches!(err.error, CompileError::UnknownKeyword(ref k) if k == "format"));
+        // The default compile ignores it
+        assert!(compile(&json!({"type": "string", "format": "x"})).is_ok());
+
+        let err = compile_strict_located(&json!({"elements": {}, "additionalProperties": true}))
+            .unwrap_err();
+        assert_eq!(err.path, "/additionalProperties");
+        assert_eq!(
+            err.error.to_string(),
+            "'additionalProperties' is not allowed in the elements form"
+        );
+
+        let err = compile_strict_located(&json!({"values": {"definitions": {}}})).unwrap_err();
+        assert_eq!(err.path, "/values/definitions");
+        assert!(matches!(err.error, CompileError::DefinitionsInNonRoot));
+
+        let err = compile_strict_located(&json!({"nullable": 1})).unwrap_err();
+        assert!(matches!(err.error, CompileError::NullableNotBoolean));
+        let err = compile_strict_located(&json!({"metadata": []})).unwrap_err();
+        assert!(matches!(err.error, CompileError::MetadataNotObject));
+        // Structural errors are reported as by compile
+        let err = compile_strict(&json!({"type": "string", "enum": ["a"]})).unwrap_err();
+        assert!(matches!(err, CompileError::MultipleForms(_)));
+    }
+
+    #[test]
+    fn test_section2_rules() {
+        let err = |schema: Value| {
+            let e = compile_located(&schema).unwrap_err();
+            (e.path, e.error.to_string())
+        };
+        assert_eq!(
+            err(json!({"type": "string", "nullable": "true"})),
+            ("/nullable".into(), "nullable must be a boolean".into())
+        );
+        assert_eq!(
+            err(json!({"elements": {"metadata": "x"}})),
+            (
+                "/elements/metadata".into(),
+                "metadata must be a JSON object".into()
+            )
+        );
+        assert_eq!(
+            err(json!({"properties": {}, "additionalProperties": 1})),
+            (
+                "/additionalProperties".into(),
+                "additionalProperties must be a boolean".into()
+            )
+        );
+        assert_eq!(
+            err(json!({"additionalProperties": true})),
+            (
+                "/additionalProperties".into(),
+                "'additionalProperties' is not allowed in the empty form".into()
+            )
+        );
+        assert_eq!(
+            err(json!({"values": {}, "mapping": {}})),
+            (
+                "/mapping".into(),
+                "'mapping' is not allowed in the values form".into()
+            )
+        );
+        assert_eq!(
+            err(
+                json!({"discriminator": "k", "mapping": {"a": {"properties": {}, "nullable": true}}})
+            ),
+            (
+                "/mapping/a".into(),
+                "discriminator mapping values must be Properties forms (not nullable)".into()
+            )
+        );
+        // Unknown members are left to compile_strict
+        assert!(compile(&json!({"type": "string", "x-extension": 1})).is_ok());
+    }
 }
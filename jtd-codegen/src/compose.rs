@@ -0,0 +1,235 @@
+/// Schema composition: `extend(base, overlay)` merges two Properties-form
+/// schemas before compilation, so "order = base_event + these fields" can be
+/// written once instead of copying the base fields into every schema.
+///
+/// Properties, optional properties and definitions are unioned. A name present
+/// on both sides is accepted only when both sides say exactly the same thing;
+/// anything else is a conflict rather than a silent override.
+use serde_json::{Map, Value};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ExtendError {
+    #[error("{0} schema must be a JSON object")]
+    NotAnObject(&'static str),
+    #[error("{0} schema must be a Properties form (found '{1}')")]
+    NotProperties(&'static str, String),
+    #[error("{0} must be a JSON object")]
+    NotAMap(String),
+    #[error("property '{0}' is defined differently in base and overlay")]
+    ConflictingProperty(String),
+    #[error("definition '{0}' is defined differently in base and overlay")]
+    ConflictingDefinition(String),
+    #[error("'{0}' differs between base and overlay")]
+    ConflictingKeyword(String),
+}
+
+/// Keywords that select a non-Properties form.
+const OTHER_FORMS: &[&str] = &["ref", "type", "enum", "elements", "values", "discriminator"];
+
+/// Merge `overlay` into `base`. `metadata` is merged key by key with the
+/// overlay winning, since it is descriptive only; every other keyword must
+/// agree when both sides set it.
+pub fn extend(base: &Value, overlay: &Value) -> Result<Value, ExtendError> {
+    let base = properties_form(base, "base")?;
+    let overlay = properties_form(overlay, "overlay")?;
+    let mut out = base.clone();
+
+    for (key, value) in overlay {
+        match key.as_str() {
+            "properties" | "optionalProperties" => {}
+            "definitions" => {
+                let merged = merge_maps(base.get(key), value, key, |name| {
+                    ExtendError::ConflictingDefinition(name.to_string())
+                })?;
+                out.insert(key.clone(), Value::Object(merged));
+            }
+            "metadata" => {
+                let mut merged = as_map(base.get(key), key)?.cloned().unwrap_or_default();
+                let extra = as_map(Some(value), key)?.cloned().unwrap_or_default();
+                merged.extend(extra);
+                out.insert(key.clone(), Value::Object(merged));
+            }
+            _ => match base.get(key) {
+                Some(existing) if existing != value => {
+                    return Err(ExtendError::ConflictingKeyword(key.clone()));
+                }
+                _ => {
+                    out.insert(key.clone(), value.clone());
+                }
+            },
+        }
+    }
+
+    // A property keeps its requiredness: the same name may not be required
+    // on one side and optional on the other.
+    for (keyword, other) in [
+        ("properties", "optionalProperties"),
+        ("optionalProperties", "properties"),
+    ] {
+        let merged = merge_maps(
+            base.get(keyword),
+            overlay.get(keyword).unwrap_or(&Value::Null),
+            keyword,
+            |name| ExtendError::ConflictingProperty(name.to_string()),
+        )?;
+        let clash = |side: &Map<String, Value>| -> Result<(), ExtendError> {
+            if let Some(theirs) = as_map(side.get(other), other)? {
+                if let Some(name) = merged.keys().find(|k| theirs.contains_key(*k)) {
+                    return Err(ExtendError::ConflictingProperty(name.clone()));
+                }
+            }
+            Ok(())
+        };
+        clash(base)?;
+        clash(overlay)?;
+        if !merged.is_empty() || out.contains_key(keyword) {
+            out.insert(keyword.to_string(), Value::Object(merged));
+        }
+    }
+
+    Ok(Value::Object(out))
+}
+
+fn properties_form<'a>(
+    schema: &'a Value,
+    side: &'static str,
+) -> Result<&'a Map<String, Value>, ExtendError> {
+    let obj = schema.as_object().ok_or(ExtendError::NotAnObject(side))?;
+    match OTHER_FORMS.iter().find(|k| obj.contains_key(**k)) {
+        Some(keyword) => Err(ExtendError::NotProperties(side, keyword.to_string())),
+        None => Ok(obj),
+    }
+}
+
+fn as_map<'a>(
+    value: Option<&'a Value>,
+    keyword: &str,
+) -> Result<Option<&'a Map<String, Value>>, ExtendError> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(map)) => Ok(Some(map)),
+        Some(_) => Err(ExtendError::NotAMap(keyword.to_string())),
+    }
+}
+
+/// Union two name → schema maps; a shared name must map to equal schemas.
+fn merge_maps(
+    base: Option<&Value>,
+    overlay: &Value,
+    keyword: &str,
+    conflict: impl Fn(&str) -> ExtendError,
+) -> Result<Map<String, Value>, ExtendError> {
+    let mut merged = as_map(base, keyword)?.cloned().unwrap_or_default();
+    if let Some(extra) = as_map(Some(overlay), keyword)? {
+        for (name, schema) in extra {
+            match merged.get(name) {
+                Some(existing) if existing != schema => return Err(conflict(name)),
+                _ => {
+                    merged.insert(name.clone(), schema.clone());
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn base_event() -> Value {
+        json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "at": {"type": "timestamp"}},
+            "metadata": {"description": "An event"}
+        })
+    }
+
+    #[test]
+    fn test_extend_adds_fields() {
+        let order = extend(
+            &base_event(),
+            &json!({
+                "definitions": {"sku": {"type": "string"}},
+                "properties": {"sku": {"ref": "sku"}},
+                "optionalProperties": {"note": {"type": "string"}},
+                "metadata": {"description": "An order event"}
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            order,
+            json!({
+                "definitions": {"id": {"type": "string"}, "sku": {"type": "string"}},
+                "properties": {
+                    "id": {"ref": "id"},
+                    "at": {"type": "timestamp"},
+                    "sku": {"ref": "sku"}
+                },
+                "optionalProperties": {"note": {"type": "string"}},
+                "metadata": {"description": "An order event"}
+            })
+        );
+        assert!(compiler::compile(&order).is_ok());
+    }
+
+    #[test]
+    fn test_identical_property_is_not_a_conflict() {
+        let merged = extend(
+            &base_event(),
+            &json!({"properties": {"at": {"type": "timestamp"}}}),
+        );
+        assert!(merged.is_ok());
+    }
+
+    #[test]
+    fn test_conflicting_property() {
+        let err = extend(
+            &base_event(),
+            &json!({"properties": {"at": {"type": "string"}}}),
+        );
+        assert_eq!(err, Err(ExtendError::ConflictingProperty("at".into())));
+    }
+
+    #[test]
+    fn test_required_vs_optional_conflict() {
+        let err = extend(
+            &base_event(),
+            &json!({"optionalProperties": {"at": {"type": "timestamp"}}}),
+        );
+        assert_eq!(err, Err(ExtendError::ConflictingProperty("at".into())));
+    }
+
+    #[test]
+    fn test_conflicting_definition() {
+        let err = extend(
+            &base_event(),
+            &json!({"definitions": {"id": {"type": "uint32"}}}),
+        );
+        assert_eq!(err, Err(ExtendError::ConflictingDefinition("id".into())));
+    }
+
+    #[test]
+    fn test_conflicting_keyword() {
+        let open = extend(&base_event(), &json!({"additionalProperties": true}));
+        assert!(open.is_ok());
+        let err = extend(
+            &json!({"properties": {}, "additionalProperties": false}),
+            &json!({"additionalProperties": true}),
+        );
+        assert_eq!(
+            err,
+            Err(ExtendError::ConflictingKeyword(
+                "additionalProperties".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_non_properties_form() {
+        let err = extend(&json!({"type": "string"}), &base_event());
+        assert_eq!(err, Err(ExtendError::NotProperties("base", "type".into())));
+    }
+}
@@ -0,0 +1,314 @@
+/// Schema-to-schema conversion, for tooling that speaks another schema
+/// language. `jtd-codegen convert --to json-schema` writes a JSON Schema
+/// (Draft 2020-12) document accepting exactly the instances the JTD schema
+/// does:
+///
+/// - Definitions become `$defs` and refs `$ref`s, so recursion carries over.
+/// - Integer types are `integer` with the type's bounds, which like JTD
+///   admits `3.0`; floats are `number`. Timestamps are strings with
+///   `format: date-time` and the RFC 3339 pattern, since validators treat
+///   `format` as an annotation by default.
+/// - Properties forms are objects with `additionalProperties: false` unless
+///   the schema allows extra properties; a discriminator is a `oneOf` of
+///   such objects, each pinning the tag with `const`.
+/// - Nullable adds `"null"` to the `type` (and `enum`) or wraps the schema
+///   in `anyOf`.
+/// - `metadata.description` becomes `description`, `label` `title`,
+///   `default` `default`, `examples` `examples` and `deprecated`
+///   `deprecated`; any other metadata is kept as `x-jtd-metadata`.
+use serde_json::{json, Map, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler;
+use crate::mock::int_range;
+
+/// The dialect URI of the documents [`to_json_schema`] produces.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// RFC 3339 date-times, as in the generated validators (which also reject
+/// impossible dates).
+const TIMESTAMP_PATTERN: &str =
+    r"^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:(\d{2}|60)(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$";
+
+/// Metadata keys with a JSON Schema keyword of their own.
+const MAPPED_METADATA: [(&str, &str); 5] = [
+    ("description", "description"),
+    ("label", "title"),
+    ("default", "default"),
+    ("examples", "examples"),
+    ("deprecated", "deprecated"),
+];
+
+/// Formats `jtd-codegen convert` can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON Schema Draft 2020-12.
+    JsonSchema,
+}
+
+impl Format {
+    /// Parse a `--to` value: `json-schema`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json-schema" | "jsonschema" => Some(Format::JsonSchema),
+            _ => None,
+        }
+    }
+}
+
+/// `schema` in `format`.
+pub fn convert(schema: &CompiledSchema, format: Format) -> Value {
+    match format {
+        Format::JsonSchema => to_json_schema(schema),
+    }
+}
+
+/// The JSON Schema document for `schema`.
+pub fn to_json_schema(schema: &CompiledSchema) -> Value {
+    let mut out = Map::new();
+    out.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+    if !schema.definitions.is_empty() {
+        let defs: Map<String, Value> = schema
+            .definitions
+            .iter()
+            .map(|(name, node)| {
+                let path = format!("/definitions/{}", compiler::escape_pointer(name));
+                (name.clone(), node_schema(schema, node, &path))
+            })
+            .collect();
+        out.insert("$defs".into(), defs.into());
+    }
+    match node_schema(schema, &schema.root, "") {
+        Value::Object(root) => out.extend(root),
+        _ => unreachable!("node schemas are objects"),
+    }
+    out.into()
+}
+
+/// The JSON Schema for `node` at schema path `path`, with its metadata.
+fn node_schema(schema: &CompiledSchema, node: &Node, path: &str) -> Value {
+    let mut out = form_schema(schema, node, path);
+    if let Some(meta) = schema.metadata.get(path) {
+        let mut rest = meta.clone();
+        for (key, keyword) in MAPPED_METADATA {
+            if let Some(value) = rest.remove(key) {
+                out[keyword] = value;
+            }
+        }
+        if !rest.is_empty() {
+            out["x-jtd-metadata"] = rest.into();
+        }
+    }
+    out
+}
+
+/// The JSON Schema for the form of `node` at schema path `path`.
+fn form_schema(schema: &CompiledSchema, node: &Node, path: &str) -> Value {
+    match node {
+        Node::Empty => json!({}),
+        Node::Ref { name } => json!({"$ref": format!("#/$defs/{}", escape_pointer(name))}),
+        Node::Type { type_kw } => type_schema(*type_kw),
+        Node::Enum { values } => json!({"type": "string", "enum": values}),
+        Node::Elements { schema: item } => json!({
+            "type": "array",
+            "items": node_schema(schema, item, &format!("{path}/elements")),
+        }),
+        Node::Values { schema: item } => json!({
+            "type": "object",
+            "additionalProperties": node_schema(schema, item, &format!("{path}/values")),
+        }),
+        Node::Properties { .. } => object(schema, node, path, None),
+        // Every tag is unknown; `oneOf` may not be empty
+        Node::Discriminator { mapping, .. } if mapping.is_empty() => json!({"not": {}}),
+        Node::Discriminator { tag, mapping } => {
+            let one_of: Vec<Value> = mapping
+                .iter()
+                .map(|(value, variant)| {
+                    let variant_path =
+                        format!("{path}/mapping/{}", compiler::escape_pointer(value));
+                    object(schema, variant, &variant_path, Some((tag, value)))
+                })
+                .collect();
+            json!({"type": "object", "oneOf": one_of})
+        }
+        Node::Nullable { inner } => nullable(form_schema(schema, inner, path)),
+    }
+}
+
+fn type_schema(type_kw: TypeKeyword) -> Value {
+    if let Some((lo, hi)) = int_range(type_kw) {
+        return json!({"type": "integer", "minimum": lo, "maximum": hi});
+    }
+    match type_kw {
+        TypeKeyword::Boolean => json!({"type": "boolean"}),
+        TypeKeyword::Timestamp => json!({
+            "type": "string",
+            "format": "date-time",
+            "pattern": TIMESTAMP_PATTERN,
+        }),
+        TypeKeyword::Float32 | TypeKeyword::Float64 => json!({"type": "number"}),
+        _ => json!({"type": "string"}),
+    }
+}
+
+/// Also admit null: as one more `type` (and enum member) where the schema
+/// has a single type, through `anyOf` otherwise.
+fn nullable(mut inner: Value) -> Value {
+    if inner.as_object().is_some_and(Map::is_empty) {
+        return inner;
+    }
+    if inner.get("oneOf").is_none() {
+        if let Some(Value::String(t)) = inner.get("type") {
+            inner["type"] = json!([t, "null"]);
+            if let Some(values) = inner.get_mut("enum").and_then(Value::as_array_mut) {
+                values.push(Value::Null);
+            }
+            return inner;
+        }
+    }
+    json!({"anyOf": [inner, {"type": "null"}]})
+}
+
+/// An object for the Properties node `node`, with a discriminator `tag`
+/// pinned to its value when given.
+fn object(
+    schema: &CompiledSchema,
+    node: &Node,
+    path: &str,
+    tag: Option<(&String, &String)>,
+) -> Value {
+    let Node::Properties {
+        required,
+        optional,
+        additional,
+    } = node
+    else {
+        return json!({});
+    };
+    let mut props = Map::new();
+    let mut names = Vec::new();
+    if let Some((tag, value)) = tag {
+        props.insert(tag.clone(), json!({"const": value}));
+        names.push(tag.clone());
+    }
+    for (key, child) in required {
+        let child_path = format!("{path}/properties/{}", compiler::escape_pointer(key));
+        props.insert(key.clone(), node_schema(schema, child, &child_path));
+        names.push(key.clone());
+    }
+    for (key, child) in optional {
+        let child_path = format!(
+            "{path}/optionalProperties/{}",
+            compiler::escape_pointer(key)
+        );
+        props.insert(key.clone(), node_schema(schema, child, &child_path));
+    }
+    let mut out = json!({"type": "object", "properties": props});
+    if !names.is_empty() {
+        out["required"] = names.into();
+    }
+    if !additional {
+        out["additionalProperties"] = false.into();
+    }
+    out
+}
+
+/// `name` as a JSON Pointer token inside a URI fragment.
+fn escape_pointer(name: &str) -> String {
+    let token = name.replace('~', "~0").replace('/', "~1");
+    let mut out = String::new();
+    for b in token.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+
+    #[test]
+    fn test_to_json_schema() {
+        let schema = compile(&json!({
+            "definitions": {"node": {
+                "properties": {"next": {"ref": "node", "nullable": true}}
+            }},
+            "properties": {
+                "id": {"type": "uint8", "metadata": {"description": "The id.", "unit": "n"}},
+                "status": {"enum": ["A", "B"], "nullable": true},
+                "at": {"type": "timestamp"},
+                "head": {"ref": "node"}
+            },
+            "optionalProperties": {"tags": {"values": {}}},
+            "additionalProperties": true
+        }))
+        .unwrap();
+        let out = to_json_schema(&schema);
+        assert_eq!(out["$schema"], JSON_SCHEMA_DIALECT);
+        assert_eq!(
+            out["$defs"]["node"],
+            json!({
+                "type": "object",
+                "properties": {"next": {"anyOf": [{"$ref": "#/$defs/node"}, {"type": "null"}]}},
+                "required": ["next"],
+                "additionalProperties": false
+            })
+        );
+        assert_eq!(out["type"], "object");
+        assert_eq!(out["required"], json!(["at", "head", "id", "status"]));
+        assert!(out.get("additionalProperties").is_none());
+        let props = &out["properties"];
+        assert_eq!(
+            props["id"],
+            json!({
+                "type": "integer", "minimum": 0, "maximum": 255,
+                "description": "The id.", "x-jtd-metadata": {"unit": "n"}
+            })
+        );
+        assert_eq!(
+            props["status"],
+            json!({"type": ["string", "null"], "enum": ["A", "B", null]})
+        );
+        assert_eq!(props["at"]["format"], "date-time");
+        assert_eq!(
+            props["tags"],
+            json!({"type": "object", "additionalProperties": {}})
+        );
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let schema = compile(&json!({
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"x": {"type": "string"}}}},
+            "nullable": true
+        }))
+        .unwrap();
+        assert_eq!(
+            to_json_schema(&schema),
+            json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "anyOf": [
+                    {"type": "object", "oneOf": [{
+                        "type": "object",
+                        "properties": {"kind": {"const": "a"}, "x": {"type": "string"}},
+                        "required": ["kind", "x"],
+                        "additionalProperties": false
+                    }]},
+                    {"type": "null"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_escape_pointer() {
+        assert_eq!(escape_pointer("a/b~c"), "a~1b~0c");
+        assert_eq!(escape_pointer("a b%"), "a%20b%25");
+    }
+}
@@ -0,0 +1,206 @@
+/// Structural deduplication of subschemas.
+///
+/// Machine-generated schemas often repeat the same shape (an address, a
+/// money amount) under many properties without naming it as a definition.
+/// Emitting each copy inline repeats its checks; instead, a subschema that
+/// occurs more than once is found here by hashing every subtree, and an
+/// emitter generates one function for it that each use calls with its own
+/// value, instance path and schema path.
+///
+/// Only non-leaf subtrees are shared (elements, values, properties,
+/// discriminators, and nullable ones of these): a leaf check is no longer
+/// than the call. Properties directly under a discriminator mapping are
+/// not shared themselves, as their checks skip the tag.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::ast::Node;
+
+/// The shared subschemas of a module, in the order first met.
+#[derive(Debug, Default)]
+pub struct Shapes {
+    shapes: Vec<Node>,
+    by_hash: HashMap<u64, Vec<usize>>,
+}
+
+impl Shapes {
+    /// The subschemas worth sharing among `nodes` (the roots and
+    /// definitions of a module): those the emitted code would still check
+    /// in more than one place once each shared one is emitted once.
+    pub fn find<'a>(nodes: impl IntoIterator<Item = &'a Node>) -> Self {
+        let nodes: Vec<&Node> = nodes.into_iter().collect();
+        let mut all = Self::default();
+        for node in &nodes {
+            all.count_all(node, false);
+        }
+        // Drop those used once until the rest are all used more: sharing
+        // fewer only ever raises the uses of the others
+        loop {
+            let mut uses = vec![0usize; all.shapes.len()];
+            for node in &nodes {
+                all.count_uses(node, false, &mut uses);
+            }
+            if uses.iter().all(|&n| n > 1) {
+                return all;
+            }
+            let kept = all
+                .shapes
+                .iter()
+                .zip(&uses)
+                .filter(|(_, &n)| n > 1)
+                .map(|(node, _)| node);
+            let mut next = Self::default();
+            for node in kept {
+                next.insert(node);
+            }
+            all = next;
+        }
+    }
+
+    /// The index of the shared function checking `node`, if it is one.
+    pub fn index(&self, node: &Node) -> Option<usize> {
+        self.by_hash
+            .get(&shape_hash(node))?
+            .iter()
+            .copied()
+            .find(|&i| self.shapes[i] == *node)
+    }
+
+    /// The shared subschemas; the one at `i` is checked by function `i`.
+    pub fn iter(&self) -> impl Iterator<Item = &Node> {
+        self.shapes.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    fn insert(&mut self, node: &Node) {
+        if self.index(node).is_none() {
+            self.by_hash
+                .entry(shape_hash(node))
+                .or_default()
+                .push(self.shapes.len());
+            self.shapes.push(node.clone());
+        }
+    }
+
+    /// Every candidate subtree, once each.
+    fn count_all(&mut self, node: &Node, variant: bool) {
+        if !variant && shareable(node) {
+            self.insert(node);
+        }
+        for_children(node, |child, variant| self.count_all(child, variant));
+    }
+
+    /// How often the emitted code uses each shape: a shape's own body is
+    /// walked at its first use only, as it is emitted once.
+    fn count_uses(&self, node: &Node, variant: bool, uses: &mut [usize]) {
+        if !variant {
+            if let Some(i) = self.index(node) {
+                uses[i] += 1;
+                if uses[i] > 1 {
+                    return;
+                }
+            }
+        }
+        for_children(node, |child, variant| self.count_uses(child, variant, uses));
+    }
+}
+
+/// Non-leaf subtrees, which are worth a function.
+fn shareable(node: &Node) -> bool {
+    match node {
+        Node::Nullable { inner } => shareable(inner),
+        Node::Elements { .. }
+        | Node::Values { .. }
+        | Node::Properties { .. }
+        | Node::Discriminator { .. } => true,
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => false,
+    }
+}
+
+/// Call `f` on each child of `node`, with whether it is a discriminator
+/// variant.
+fn for_children<'a>(node: &'a Node, mut f: impl FnMut(&'a Node, bool)) {
+    match node {
+        Node::Nullable { inner } => f(inner, false),
+        Node::Elements { schema } | Node::Values { schema } => f(schema, false),
+        Node::Properties {
+            required, optional, ..
+        } => required
+            .values()
+            .chain(optional.values())
+            .for_each(|c| f(c, false)),
+        Node::Discriminator { mapping, .. } => mapping.values().for_each(|c| f(c, true)),
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => {}
+    }
+}
+
+/// A hash of the structure of `node`: equal subtrees hash equal.
+fn shape_hash(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(node)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_repeated_shapes() {
+        let address = json!({"properties": {"street": {"type": "string"}, "tags": {"elements": {"type": "string"}}}});
+        let compiled = compiler::compile(&json!({
+            "properties": {
+                "home": address,
+                "work": address,
+                "names": {"elements": {"type": "string"}},
+                "once": {"values": {"type": "uint8"}}
+            }
+        }))
+        .unwrap();
+        let shapes = Shapes::find([&compiled.root]);
+        // The address twice, and `elements: string` under `names` and in
+        // the one address body emitted
+        assert_eq!(shapes.iter().count(), 2);
+        let Node::Properties { required, .. } = &compiled.root else {
+            panic!("expected properties");
+        };
+        assert_eq!(
+            shapes.index(&required["home"]),
+            shapes.index(&required["work"])
+        );
+        assert!(shapes.index(&required["home"]).is_some());
+        assert!(shapes.index(&required["names"]).is_some());
+        assert_eq!(shapes.index(&required["once"]), None);
+        assert_eq!(shapes.index(&compiled.root), None);
+    }
+
+    #[test]
+    fn test_shapes_only_inside_a_shape_are_not_shared() {
+        let item = json!({"properties": {"tags": {"elements": {"type": "string"}}}});
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": item, "b": item}
+        }))
+        .unwrap();
+        let shapes = Shapes::find([&compiled.root]);
+        assert_eq!(shapes.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_discriminator_variants_are_not_shared() {
+        let variant = json!({"properties": {"n": {"type": "string"}}});
+        let compiled = compiler::compile(&json!({
+            "discriminator": "k",
+            "mapping": {"a": variant, "b": variant}
+        }))
+        .unwrap();
+        assert!(Shapes::find([&compiled.root]).is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+/// Plan for `applyDefaults(instance)`, emitted with the `apply_defaults`
+/// option: where an instance can be missing an optional property whose
+/// schema has a `metadata.default`, and the value to fill in.
+///
+/// Only optional properties are filled; a default on a required property
+/// (or anywhere else) is ignored. Defaults are used as written; nothing
+/// checks them against their schema, so run `validate` on the result.
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+
+/// What to fill for one schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Object(Shape),
+    /// Every element of an array.
+    Array(Box<Fill>),
+    /// Every value of an object.
+    Map(Box<Fill>),
+    /// A definition's fill function (`defaults_<name>`).
+    Ref(String),
+    /// An object whose shape depends on the string at `tag`.
+    Tagged {
+        tag: String,
+        variants: Vec<(String, Shape)>,
+    },
+}
+
+/// One Properties schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    /// Optional properties to add, with their default, when absent.
+    pub defaults: Vec<(String, Value)>,
+    /// Properties with something to fill inside, visited after `defaults`
+    /// so a filled-in object gets its own defaults too.
+    pub props: Vec<(String, Fill)>,
+}
+
+/// Plans for the root and for each definition with something to fill.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DefaultsPlan {
+    pub root: Option<Fill>,
+    pub definitions: BTreeMap<String, Fill>,
+}
+
+/// Build the defaults plan for `schema`.
+pub fn defaults_plan(schema: &CompiledSchema) -> DefaultsPlan {
+    // Definitions that reach a default, directly or via refs.
+    let mut live: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let before = live.len();
+        for (name, node) in &schema.definitions {
+            let sp = format!("/definitions/{}", escape_pointer(name));
+            if !live.contains(name) && plan(schema, node, &sp, &live).is_some() {
+                live.insert(name.clone());
+            }
+        }
+        if live.len() == before {
+            break;
+        }
+    }
+
+    let definitions = live
+        .iter()
+        .filter_map(|name| {
+            let sp = format!("/definitions/{}", escape_pointer(name));
+            let fill = plan(schema, &schema.definitions[name], &sp, &live)?;
+            Some((name.clone(), fill))
+        })
+        .collect();
+    DefaultsPlan {
+        root: plan(schema, &schema.root, "", &live),
+        definitions,
+    }
+}
+
+/// Fill `instance` in place following `plan`; the interpreter's
+/// counterpart of the generated functions.
+pub fn apply(plan: &DefaultsPlan, instance: &mut Value) {
+    if let Some(fill) = &plan.root {
+        apply_fill(plan, fill, instance);
+    }
+}
+
+fn apply_fill(plan: &DefaultsPlan, fill: &Fill, v: &mut Value) {
+    match fill {
+        Fill::Object(shape) => apply_shape(plan, shape, v),
+        Fill::Array(inner) => {
+            if let Some(arr) = v.as_array_mut() {
+                for x in arr {
+                    apply_fill(plan, inner, x);
+                }
+            }
+        }
+        Fill::Map(inner) => {
+            if let Some(obj) = v.as_object_mut() {
+                for x in obj.values_mut() {
+                    apply_fill(plan, inner, x);
+                }
+            }
+        }
+        Fill::Ref(name) => apply_fill(plan, &plan.definitions[name], v),
+        Fill::Tagged { tag, variants } => {
+            let tag_value = v.get(tag).and_then(Value::as_str);
+            if let Some((_, shape)) = variants.iter().find(|(t, _)| Some(t.as_str()) == tag_value) {
+                apply_shape(plan, shape, v);
+            }
+        }
+    }
+}
+
+fn apply_shape(plan: &DefaultsPlan, shape: &Shape, v: &mut Value) {
+    let Some(obj) = v.as_object_mut() else {
+        return;
+    };
+    for (key, default) in &shape.defaults {
+        if !obj.contains_key(key) {
+            obj.insert(key.clone(), default.clone());
+        }
+    }
+    for (key, fill) in &shape.props {
+        if let Some(x) = obj.get_mut(key) {
+            apply_fill(plan, fill, x);
+        }
+    }
+}
+
+fn plan(schema: &CompiledSchema, node: &Node, sp: &str, live: &BTreeSet<String>) -> Option<Fill> {
+    match node {
+        Node::Nullable { inner } => plan(schema, inner, sp, live),
+        Node::Ref { name } => live.contains(name).then(|| Fill::Ref(name.clone())),
+        Node::Elements { schema: elem } => {
+            plan(schema, elem, &format!("{sp}/elements"), live).map(|f| Fill::Array(Box::new(f)))
+        }
+        Node::Values { schema: value } => {
+            plan(schema, value, &format!("{sp}/values"), live).map(|f| Fill::Map(Box::new(f)))
+        }
+        Node::Properties { .. } => shape(schema, node, sp, live).map(Fill::Object),
+        Node::Discriminator { tag, mapping } => {
+            let variants: Vec<(String, Shape)> = mapping
+                .iter()
+                .filter_map(|(value, variant)| {
+                    let vsp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    Some((value.clone(), shape(schema, variant, &vsp, live)?))
+                })
+                .collect();
+            (!variants.is_empty()).then(|| Fill::Tagged {
+                tag: tag.clone(),
+                variants,
+            })
+        }
+        Node::Empty | Node::Type { .. } | Node::Enum { .. } => None,
+    }
+}
+
+fn shape(schema: &CompiledSchema, node: &Node, sp: &str, live: &BTreeSet<String>) -> Option<Shape> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return None;
+    };
+    let defaults: Vec<(String, Value)> = optional
+        .keys()
+        .filter_map(|key| {
+            let default = schema
+                .default_value(&format!("{sp}/optionalProperties/{}", escape_pointer(key)))?;
+            Some((key.clone(), default.clone()))
+        })
+        .collect();
+    let required = required.iter().map(|(k, n)| (k, n, "properties"));
+    let optional = optional.iter().map(|(k, n)| (k, n, "optionalProperties"));
+    let props: Vec<(String, Fill)> = required
+        .chain(optional)
+        .filter_map(|(key, child, keyword)| {
+            let fill = plan(
+                schema,
+                child,
+                &format!("{sp}/{keyword}/{}", escape_pointer(key)),
+                live,
+            )?;
+            Some((key.clone(), fill))
+        })
+        .collect();
+    (!defaults.is_empty() || !props.is_empty()).then_some(Shape { defaults, props })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn plan_of(schema: Value) -> DefaultsPlan {
+        defaults_plan(&compiler::compile(&schema).unwrap())
+    }
+
+    #[test]
+    fn test_no_defaults() {
+        let p = plan_of(json!({
+            "properties": {"a": {"type": "string", "metadata": {"default": "x"}}}
+        }));
+        assert_eq!(p, DefaultsPlan::default());
+    }
+
+    #[test]
+    fn test_optional_defaults() {
+        let p = plan_of(json!({
+            "optionalProperties": {
+                "page": {"type": "uint32", "metadata": {"default": 1}},
+                "tags": {"elements": {"type": "string"}, "metadata": {"default": []}},
+                "q": {"type": "string"}
+            }
+        }));
+        assert_eq!(
+            p.root,
+            Some(Fill::Object(Shape {
+                defaults: vec![("page".into(), json!(1)), ("tags".into(), json!([]))],
+                props: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_apply_through_refs() {
+        let schema = json!({
+            "definitions": {
+                "item": {"optionalProperties": {"qty": {"type": "uint8", "metadata": {"default": 1}}}}
+            },
+            "properties": {"items": {"elements": {"ref": "item"}}},
+            "optionalProperties": {"currency": {"type": "string", "metadata": {"default": "EUR"}}}
+        });
+        let p = plan_of(schema);
+        let mut instance = json!({"items": [{}, {"qty": 3}]});
+        apply(&p, &mut instance);
+        assert_eq!(
+            instance,
+            json!({"items": [{"qty": 1}, {"qty": 3}], "currency": "EUR"})
+        );
+    }
+}
@@ -0,0 +1,229 @@
+/// Plan for the `warnings(instance)` walker emitted with the
+/// `deprecation_warnings` option: which parts of an instance can hold a
+/// property whose schema has `metadata.deprecated: true`.
+///
+/// The plan keeps only the paths that lead to a deprecated property, so the
+/// generated walker never touches the rest of the instance. Warnings are
+/// `(instancePath, schemaPath)` pairs like errors; schema paths are absolute
+/// (`/definitions/a/properties/b`) because refs restart at the definition.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+
+/// What to walk for one schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Walk {
+    /// An object with known properties.
+    Object(Vec<Prop>),
+    /// Every element of an array.
+    Array(Box<Walk>),
+    /// Every value of an object.
+    Map(Box<Walk>),
+    /// A definition walker (`warn_<name>`).
+    Ref(String),
+    /// An object whose shape depends on the string at `tag`.
+    Tagged {
+        tag: String,
+        variants: Vec<(String, Vec<Prop>)>,
+    },
+}
+
+/// One property to look at when present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prop {
+    pub key: String,
+    /// Absolute schema path of the property schema, used for its warning.
+    pub schema_path: String,
+    /// Report a warning whenever the property is present.
+    pub deprecated: bool,
+    /// Deprecated properties nested inside this one.
+    pub child: Option<Walk>,
+}
+
+/// Walk plans for the root and for each definition that can reach a
+/// deprecated property. `root` is `None` when nothing in the schema is
+/// deprecated through the root.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WarningPlan {
+    pub root: Option<Walk>,
+    pub definitions: BTreeMap<String, Walk>,
+}
+
+/// Build the walk plans for `schema`.
+pub fn warning_plan(schema: &CompiledSchema) -> WarningPlan {
+    // Definitions that reach a deprecated property, directly or via refs.
+    let mut live: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let before = live.len();
+        for (name, node) in &schema.definitions {
+            let sp = format!("/definitions/{}", escape_pointer(name));
+            if !live.contains(name) && walk(schema, node, &sp, &live).is_some() {
+                live.insert(name.clone());
+            }
+        }
+        if live.len() == before {
+            break;
+        }
+    }
+
+    let definitions = live
+        .iter()
+        .filter_map(|name| {
+            let sp = format!("/definitions/{}", escape_pointer(name));
+            let w = walk(schema, &schema.definitions[name], &sp, &live)?;
+            Some((name.clone(), w))
+        })
+        .collect();
+    WarningPlan {
+        root: walk(schema, &schema.root, "", &live),
+        definitions,
+    }
+}
+
+fn walk(schema: &CompiledSchema, node: &Node, sp: &str, live: &BTreeSet<String>) -> Option<Walk> {
+    match node {
+        Node::Nullable { inner } => walk(schema, inner, sp, live),
+        Node::Ref { name } => live.contains(name).then(|| Walk::Ref(name.clone())),
+        Node::Elements { schema: elem } => {
+            walk(schema, elem, &format!("{sp}/elements"), live).map(|w| Walk::Array(Box::new(w)))
+        }
+        Node::Values { schema: value } => {
+            walk(schema, value, &format!("{sp}/values"), live).map(|w| Walk::Map(Box::new(w)))
+        }
+        Node::Properties { .. } => {
+            let props = props(schema, node, sp, live);
+            (!props.is_empty()).then_some(Walk::Object(props))
+        }
+        Node::Discriminator { tag, mapping } => {
+            let variants: Vec<(String, Vec<Prop>)> = mapping
+                .iter()
+                .map(|(value, variant)| {
+                    let vsp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    (value.clone(), props(schema, variant, &vsp, live))
+                })
+                .filter(|(_, props)| !props.is_empty())
+                .collect();
+            (!variants.is_empty()).then(|| Walk::Tagged {
+                tag: tag.clone(),
+                variants,
+            })
+        }
+        Node::Empty | Node::Type { .. } | Node::Enum { .. } => None,
+    }
+}
+
+fn props(schema: &CompiledSchema, node: &Node, sp: &str, live: &BTreeSet<String>) -> Vec<Prop> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return Vec::new();
+    };
+    let required = required.iter().map(|(k, n)| (k, n, "properties"));
+    let optional = optional.iter().map(|(k, n)| (k, n, "optionalProperties"));
+    required
+        .chain(optional)
+        .filter_map(|(key, child, keyword)| {
+            let schema_path = format!("{sp}/{keyword}/{}", escape_pointer(key));
+            let deprecated = schema.is_deprecated(&schema_path);
+            let child = walk(schema, child, &schema_path, live);
+            (deprecated || child.is_some()).then(|| Prop {
+                key: key.clone(),
+                schema_path,
+                deprecated,
+                child,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn plan(schema: serde_json::Value) -> WarningPlan {
+        warning_plan(&compiler::compile(&schema).unwrap())
+    }
+
+    #[test]
+    fn test_nothing_deprecated() {
+        let p = plan(json!({"properties": {"a": {"type": "string"}}}));
+        assert_eq!(p, WarningPlan::default());
+    }
+
+    #[test]
+    fn test_deprecated_property() {
+        let p = plan(json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {"old": {"type": "string", "metadata": {"deprecated": true}}}
+        }));
+        assert_eq!(
+            p.root,
+            Some(Walk::Object(vec![Prop {
+                key: "old".into(),
+                schema_path: "/optionalProperties/old".into(),
+                deprecated: true,
+                child: None,
+            }]))
+        );
+    }
+
+    #[test]
+    fn test_escaped_schema_path() {
+        let p = plan(json!({
+            "definitions": {
+                "a/b~c": {"optionalProperties": {"x/y": {"metadata": {"deprecated": true}}}}
+            },
+            "elements": {"ref": "a/b~c"}
+        }));
+        let Walk::Object(props) = &p.definitions["a/b~c"] else {
+            panic!("expected object walk")
+        };
+        assert_eq!(
+            props[0].schema_path,
+            "/definitions/a~1b~0c/optionalProperties/x~1y"
+        );
+        assert!(props[0].deprecated);
+    }
+
+    #[test]
+    fn test_through_refs_and_elements() {
+        let p = plan(json!({
+            "definitions": {
+                "item": {"optionalProperties": {"sku": {"metadata": {"deprecated": true}}}},
+                "unused": {"properties": {"x": {"type": "string"}}},
+                "node": {"optionalProperties": {"next": {"ref": "node"}}}
+            },
+            "properties": {"items": {"elements": {"ref": "item"}}, "n": {"ref": "node"}}
+        }));
+        assert_eq!(p.definitions.keys().collect::<Vec<_>>(), vec!["item"]);
+        let Some(Walk::Object(props)) = p.root else {
+            panic!("expected object walk")
+        };
+        assert_eq!(props.len(), 1);
+        assert_eq!(
+            props[0].child,
+            Some(Walk::Array(Box::new(Walk::Ref("item".into()))))
+        );
+    }
+
+    #[test]
+    fn test_discriminator_variants() {
+        let p = plan(json!({
+            "discriminator": "kind",
+            "mapping": {
+                "a": {"properties": {"x": {"metadata": {"deprecated": true}}}},
+                "b": {"properties": {"y": {}}}
+            }
+        }));
+        let Some(Walk::Tagged { tag, variants }) = p.root else {
+            panic!("expected tagged walk")
+        };
+        assert_eq!(tag, "kind");
+        assert_eq!(variants.len(), 1);
+        assert_eq!(variants[0].1[0].schema_path, "/mapping/a/properties/x");
+    }
+}
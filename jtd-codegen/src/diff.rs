@@ -0,0 +1,581 @@
+/// Schema compatibility: what changed between two versions of a schema and
+/// which readers each change breaks. `jtd-codegen diff old.json new.json`
+/// prints the changes and exits 1 on a break, for CI contract checks.
+///
+/// A change breaks *backward* compatibility when data valid under the old
+/// schema can fail the new one (consumers upgraded first), and *forward*
+/// compatibility when data valid under the new schema can fail the old one
+/// (producers upgraded first). Removing a required property breaks forward
+/// compatibility, narrowing an enum or removing a mapping variant backward,
+/// and changing the type keyword usually both, though widening an integer
+/// type or a timestamp to a string breaks forward compatibility only.
+///
+/// Refs are followed on both sides, so renaming a definition is not a
+/// change; changes are reported at their schema path in the new schema.
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::mock::int_range;
+
+/// The compatibility a schema change must keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Data written with the old schema still validates.
+    Backward,
+    /// Data written with the new schema validates against the old one.
+    Forward,
+    /// Both.
+    Full,
+}
+
+impl Compatibility {
+    /// Parse a `--compat` value: `backward`, `forward` or `full`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "backward" => Some(Compatibility::Backward),
+            "forward" => Some(Compatibility::Forward),
+            "full" => Some(Compatibility::Full),
+            _ => None,
+        }
+    }
+}
+
+/// What changed at a schema path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A different form, e.g. `type` became `elements`.
+    FormChanged {
+        old: &'static str,
+        new: &'static str,
+    },
+    TypeChanged {
+        old: TypeKeyword,
+        new: TypeKeyword,
+    },
+    EnumValuesRemoved(Vec<String>),
+    EnumValuesAdded(Vec<String>),
+    NullableRemoved,
+    NullableAdded,
+    RequiredPropertyAdded(String),
+    RequiredPropertyRemoved(String),
+    OptionalPropertyAdded(String),
+    OptionalPropertyRemoved(String),
+    PropertyMadeRequired(String),
+    PropertyMadeOptional(String),
+    AdditionalPropertiesDisallowed,
+    AdditionalPropertiesAllowed,
+    DiscriminatorChanged {
+        old: String,
+        new: String,
+    },
+    MappingVariantAdded(String),
+    MappingVariantRemoved(String),
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quoted = |values: &[String]| {
+            let values: Vec<String> = values.iter().map(|v| format!("'{v}'")).collect();
+            values.join(", ")
+        };
+        match self {
+            ChangeKind::FormChanged { old, new } => write!(f, "{old} form became {new}"),
+            ChangeKind::TypeChanged { old, new } => {
+                write!(f, "type {} became {}", old.as_str(), new.as_str())
+            }
+            ChangeKind::EnumValuesRemoved(values) => {
+                write!(f, "enum values removed: {}", quoted(values))
+            }
+            ChangeKind::EnumValuesAdded(values) => {
+                write!(f, "enum values added: {}", quoted(values))
+            }
+            ChangeKind::NullableRemoved => write!(f, "no longer nullable"),
+            ChangeKind::NullableAdded => write!(f, "became nullable"),
+            ChangeKind::RequiredPropertyAdded(key) => write!(f, "required property '{key}' added"),
+            ChangeKind::RequiredPropertyRemoved(key) => {
+                write!(f, "required property '{key}' removed")
+            }
+            ChangeKind::OptionalPropertyAdded(key) => write!(f, "optional property '{key}' added"),
+            ChangeKind::OptionalPropertyRemoved(key) => {
+                write!(f, "optional property '{key}' removed")
+            }
+            ChangeKind::PropertyMadeRequired(key) => write!(f, "property '{key}' became required"),
+            ChangeKind::PropertyMadeOptional(key) => write!(f, "property '{key}' became optional"),
+            ChangeKind::AdditionalPropertiesDisallowed => {
+                write!(f, "additional properties no longer allowed")
+            }
+            ChangeKind::AdditionalPropertiesAllowed => write!(f, "additional properties allowed"),
+            ChangeKind::DiscriminatorChanged { old, new } => {
+                write!(f, "discriminator '{old}' became '{new}'")
+            }
+            ChangeKind::MappingVariantAdded(tag) => write!(f, "mapping variant '{tag}' added"),
+            ChangeKind::MappingVariantRemoved(tag) => write!(f, "mapping variant '{tag}' removed"),
+        }
+    }
+}
+
+/// One change between the schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Where the change is, as a path into the new schema.
+    pub schema_path: String,
+    pub kind: ChangeKind,
+    /// Data valid under the old schema can fail the new one.
+    pub backward: bool,
+    /// Data valid under the new schema can fail the old one.
+    pub forward: bool,
+}
+
+impl Change {
+    /// Whether the change breaks `compat`.
+    pub fn breaks(&self, compat: Compatibility) -> bool {
+        match compat {
+            Compatibility::Backward => self.backward,
+            Compatibility::Forward => self.forward,
+            Compatibility::Full => self.backward || self.forward,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = match (self.backward, self.forward) {
+            (true, true) => "breaks backward and forward",
+            (true, false) => "breaks backward",
+            (false, true) => "breaks forward",
+            (false, false) => "compatible",
+        };
+        let at = if self.schema_path.is_empty() {
+            "the root"
+        } else {
+            &self.schema_path
+        };
+        write!(f, "{verdict}: {} at {at}", self.kind)
+    }
+}
+
+/// The changes from `old` to `new`, in schema path order.
+pub fn diff(old: &CompiledSchema, new: &CompiledSchema) -> Vec<Change> {
+    let mut differ = Differ {
+        old,
+        new,
+        seen: HashSet::new(),
+        changes: Vec::new(),
+    };
+    differ.node(&old.root, &new.root, "", "");
+    differ.changes
+}
+
+struct Differ<'a> {
+    old: &'a CompiledSchema,
+    new: &'a CompiledSchema,
+    /// Pairs of definitions already compared, which ends recursion.
+    seen: HashSet<(String, String)>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    fn change(&mut self, path: &str, kind: ChangeKind, backward: bool, forward: bool) {
+        self.changes.push(Change {
+            schema_path: path.to_string(),
+            kind,
+            backward,
+            forward,
+        });
+    }
+
+    /// Compare `old` at `old_path` with `new` at `new_path`.
+    fn node(&mut self, old: &'a Node, new: &'a Node, old_path: &str, new_path: &str) {
+        let (old, old_to, old_nullable) = flatten(self.old, old, old_path);
+        let (new, new_to, new_nullable) = flatten(self.new, new, new_path);
+        // The empty form accepts null too; its form change says as much
+        if !matches!(old, Node::Empty) && !matches!(new, Node::Empty) {
+            match (old_nullable, new_nullable) {
+                (true, false) => self.change(new_path, ChangeKind::NullableRemoved, true, false),
+                (false, true) => self.change(new_path, ChangeKind::NullableAdded, false, true),
+                _ => {}
+            }
+        }
+        if (old_to != old_path || new_to != new_path)
+            && !self.seen.insert((old_to.clone(), new_to.clone()))
+        {
+            return;
+        }
+        let (old_path, new_path) = (old_to.as_str(), new_to.as_str());
+
+        match (old, new) {
+            (Node::Empty, Node::Empty) | (Node::Ref { .. }, Node::Ref { .. }) => {}
+            (Node::Type { type_kw: a }, Node::Type { type_kw: b }) if a != b => {
+                let kind = ChangeKind::TypeChanged { old: *a, new: *b };
+                self.change(new_path, kind, !accepts(*b, *a), !accepts(*a, *b));
+            }
+            (Node::Type { .. }, Node::Type { .. }) => {}
+            (Node::Enum { values: a }, Node::Enum { values: b }) => {
+                let removed: Vec<String> = a.iter().filter(|v| !b.contains(v)).cloned().collect();
+                let added: Vec<String> = b.iter().filter(|v| !a.contains(v)).cloned().collect();
+                if !removed.is_empty() {
+                    self.change(
+                        new_path,
+                        ChangeKind::EnumValuesRemoved(removed),
+                        true,
+                        false,
+                    );
+                }
+                if !added.is_empty() {
+                    self.change(new_path, ChangeKind::EnumValuesAdded(added), false, true);
+                }
+            }
+            (Node::Elements { schema: a }, Node::Elements { schema: b }) => self.node(
+                a,
+                b,
+                &format!("{old_path}/elements"),
+                &format!("{new_path}/elements"),
+            ),
+            (Node::Values { schema: a }, Node::Values { schema: b }) => self.node(
+                a,
+                b,
+                &format!("{old_path}/values"),
+                &format!("{new_path}/values"),
+            ),
+            (Node::Properties { .. }, Node::Properties { .. }) => {
+                self.properties(old, new, old_path, new_path)
+            }
+            (
+                Node::Discriminator {
+                    tag: old_tag,
+                    mapping: a,
+                },
+                Node::Discriminator {
+                    tag: new_tag,
+                    mapping: b,
+                },
+            ) => {
+                if old_tag != new_tag {
+                    let kind = ChangeKind::DiscriminatorChanged {
+                        old: old_tag.clone(),
+                        new: new_tag.clone(),
+                    };
+                    return self.change(new_path, kind, true, true);
+                }
+                let tags: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+                for tag in tags {
+                    let path = format!("{new_path}/mapping/{}", escape_pointer(tag));
+                    match (a.get(tag), b.get(tag)) {
+                        (Some(a), Some(b)) => self.node(
+                            a,
+                            b,
+                            &format!("{old_path}/mapping/{}", escape_pointer(tag)),
+                            &path,
+                        ),
+                        (Some(_), None) => {
+                            let kind = ChangeKind::MappingVariantRemoved(tag.clone());
+                            self.change(&path, kind, true, false);
+                        }
+                        _ => {
+                            let kind = ChangeKind::MappingVariantAdded(tag.clone());
+                            self.change(&path, kind, false, true);
+                        }
+                    }
+                }
+            }
+            _ => {
+                let kind = ChangeKind::FormChanged {
+                    old: form(old),
+                    new: form(new),
+                };
+                // Accepting anything, or any string for an enum, only widens
+                let (backward, forward) = match (old, new) {
+                    (Node::Empty, _) => (true, false),
+                    (_, Node::Empty) => (false, true),
+                    (
+                        Node::Enum { .. },
+                        Node::Type {
+                            type_kw: TypeKeyword::String,
+                        },
+                    ) => (false, true),
+                    (
+                        Node::Type {
+                            type_kw: TypeKeyword::String,
+                        },
+                        Node::Enum { .. },
+                    ) => (true, false),
+                    _ => (true, true),
+                };
+                self.change(new_path, kind, backward, forward);
+            }
+        }
+    }
+
+    /// Compare two Properties nodes.
+    fn properties(&mut self, old: &'a Node, new: &'a Node, old_path: &str, new_path: &str) {
+        let (
+            Node::Properties {
+                required: old_req,
+                optional: old_opt,
+                additional: old_add,
+            },
+            Node::Properties {
+                required: new_req,
+                optional: new_opt,
+                additional: new_add,
+            },
+        ) = (old, new)
+        else {
+            return;
+        };
+        let (old_add, new_add) = (*old_add, *new_add);
+        let keys: BTreeSet<&String> = old_req
+            .keys()
+            .chain(old_opt.keys())
+            .chain(new_req.keys())
+            .chain(new_opt.keys())
+            .collect();
+        for key in keys {
+            let old_child = old_req
+                .get(key)
+                .map(|n| (n, "properties"))
+                .or_else(|| old_opt.get(key).map(|n| (n, "optionalProperties")));
+            let new_child = new_req
+                .get(key)
+                .map(|n| (n, "properties"))
+                .or_else(|| new_opt.get(key).map(|n| (n, "optionalProperties")));
+            let key_path =
+                |path: &str, keyword: &str| format!("{path}/{keyword}/{}", escape_pointer(key));
+            match (old_child, new_child) {
+                (Some((a, old_kw)), Some((b, new_kw))) => {
+                    let path = key_path(new_path, new_kw);
+                    match (old_kw, new_kw) {
+                        ("optionalProperties", "properties") => {
+                            let kind = ChangeKind::PropertyMadeRequired(key.clone());
+                            self.change(&path, kind, true, false);
+                        }
+                        ("properties", "optionalProperties") => {
+                            let kind = ChangeKind::PropertyMadeOptional(key.clone());
+                            self.change(&path, kind, false, true);
+                        }
+                        _ => {}
+                    }
+                    self.node(a, b, &key_path(old_path, old_kw), &path);
+                }
+                // New data carries the property, which an old closed object rejects
+                (None, Some((_, new_kw))) => {
+                    let path = key_path(new_path, new_kw);
+                    if new_kw == "properties" {
+                        let kind = ChangeKind::RequiredPropertyAdded(key.clone());
+                        self.change(&path, kind, true, !old_add);
+                    } else {
+                        let kind = ChangeKind::OptionalPropertyAdded(key.clone());
+                        self.change(&path, kind, false, !old_add);
+                    }
+                }
+                (Some((_, old_kw)), None) => {
+                    let path = key_path(new_path, old_kw);
+                    if old_kw == "properties" {
+                        let kind = ChangeKind::RequiredPropertyRemoved(key.clone());
+                        self.change(&path, kind, !new_add, true);
+                    } else {
+                        let kind = ChangeKind::OptionalPropertyRemoved(key.clone());
+                        self.change(&path, kind, !new_add, false);
+                    }
+                }
+                (None, None) => unreachable!("keys come from either side"),
+            }
+        }
+        match (old_add, new_add) {
+            (true, false) => self.change(
+                new_path,
+                ChangeKind::AdditionalPropertiesDisallowed,
+                true,
+                false,
+            ),
+            (false, true) => self.change(
+                new_path,
+                ChangeKind::AdditionalPropertiesAllowed,
+                false,
+                true,
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// `node` with refs followed and nullable unwrapped, its schema path and
+/// whether it accepts null. A ref cycle stays a ref.
+fn flatten<'s>(
+    schema: &'s CompiledSchema,
+    mut node: &'s Node,
+    path: &str,
+) -> (&'s Node, String, bool) {
+    let mut path = path.to_string();
+    let mut nullable = false;
+    let mut followed = BTreeSet::new();
+    loop {
+        match node {
+            Node::Nullable { inner } => {
+                nullable = true;
+                node = inner;
+            }
+            Node::Ref { name } if followed.insert(name) => {
+                node = &schema.definitions[name];
+                path = format!("/definitions/{}", escape_pointer(name));
+            }
+            _ => return (node, path, nullable),
+        }
+    }
+}
+
+fn form(node: &Node) -> &'static str {
+    match node {
+        Node::Empty => "empty",
+        Node::Ref { .. } => "ref",
+        Node::Type { .. } => "type",
+        Node::Enum { .. } => "enum",
+        Node::Elements { .. } => "elements",
+        Node::Properties { .. } => "properties",
+        Node::Values { .. } => "values",
+        Node::Discriminator { .. } => "discriminator",
+        Node::Nullable { inner } => form(inner),
+    }
+}
+
+/// Whether every value `b` accepts, `a` accepts too.
+fn accepts(a: TypeKeyword, b: TypeKeyword) -> bool {
+    use TypeKeyword::*;
+    if a == b {
+        return true;
+    }
+    match (a, b) {
+        // Floats accept any number, integers included
+        (Float32 | Float64, _) => int_range(b).is_some() || matches!(b, Float32 | Float64),
+        (String, Timestamp) => true,
+        _ => match (int_range(a), int_range(b)) {
+            (Some((a_lo, a_hi)), Some((b_lo, b_hi))) => a_lo <= b_lo && b_hi <= a_hi,
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::{json, Value};
+
+    fn changes(old: Value, new: Value) -> Vec<String> {
+        diff(&compile(&old).unwrap(), &compile(&new).unwrap())
+            .iter()
+            .map(Change::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = json!({
+            "properties": {
+                "id": {"type": "string"},
+                "qty": {"type": "uint8"},
+                "status": {"enum": ["OPEN", "CLOSED"]},
+                "note": {"type": "string"}
+            },
+            "optionalProperties": {"at": {"type": "timestamp"}}
+        });
+        let new = json!({
+            "properties": {
+                "qty": {"type": "int32"},
+                "status": {"enum": ["OPEN", "PENDING"]},
+                "note": {"type": "string", "nullable": true},
+                "at": {"type": "timestamp"}
+            },
+            "optionalProperties": {"tags": {"elements": {"type": "string"}}}
+        });
+        assert_eq!(
+            changes(old, new),
+            vec![
+                "breaks backward: property 'at' became required at /properties/at",
+                "breaks backward and forward: required property 'id' removed at /properties/id",
+                "breaks forward: became nullable at /properties/note",
+                "breaks forward: type uint8 became int32 at /properties/qty",
+                "breaks backward: enum values removed: 'CLOSED' at /properties/status",
+                "breaks forward: enum values added: 'PENDING' at /properties/status",
+                "breaks forward: optional property 'tags' added at /optionalProperties/tags",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_forms() {
+        assert_eq!(
+            changes(json!({"type": "string"}), json!({"elements": {}})),
+            vec!["breaks backward and forward: type form became elements at the root"]
+        );
+        assert_eq!(
+            changes(json!({"type": "float64"}), json!({"type": "float32"})),
+            vec!["compatible: type float64 became float32 at the root"]
+        );
+        assert_eq!(
+            changes(json!({"enum": ["A"]}), json!({"type": "string"})),
+            vec!["breaks forward: enum form became type at the root"]
+        );
+        assert_eq!(
+            changes(json!({}), json!({"values": {}, "nullable": true})),
+            vec!["breaks backward: empty form became values at the root"]
+        );
+        let open = json!({"properties": {"a": {}}, "additionalProperties": true});
+        assert_eq!(
+            changes(open, json!({"properties": {}})),
+            vec![
+                "breaks backward and forward: required property 'a' removed at /properties/a",
+                "breaks backward: additional properties no longer allowed at the root",
+            ]
+        );
+        let closed = json!({"properties": {}});
+        assert_eq!(
+            changes(
+                closed,
+                json!({"optionalProperties": {"b": {}}, "additionalProperties": true})
+            ),
+            vec![
+                "breaks forward: optional property 'b' added at /optionalProperties/b",
+                "breaks forward: additional properties allowed at the root",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_discriminator_and_refs() {
+        let old = json!({
+            "definitions": {"node": {"properties": {"next": {"ref": "node", "nullable": true}}}},
+            "discriminator": "kind",
+            "mapping": {
+                "a": {"properties": {"n": {"ref": "node"}}},
+                "b": {"properties": {}}
+            }
+        });
+        let new = json!({
+            "definitions": {"link": {"properties": {"next": {"ref": "link"}}}},
+            "discriminator": "kind",
+            "mapping": {
+                "a": {"properties": {"n": {"ref": "link"}}},
+                "c": {"properties": {}}
+            }
+        });
+        assert_eq!(
+            changes(old, new),
+            vec![
+                "breaks backward: no longer nullable at /definitions/link/properties/next",
+                "breaks backward: mapping variant 'b' removed at /mapping/b",
+                "breaks forward: mapping variant 'c' added at /mapping/c",
+            ]
+        );
+        let tag = changes(
+            json!({"discriminator": "kind", "mapping": {}}),
+            json!({"discriminator": "type", "mapping": {}}),
+        );
+        assert_eq!(
+            tag,
+            vec!["breaks backward and forward: discriminator 'kind' became 'type' at the root"]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+/// Doc comments from `metadata.description`, rendered as plain text lines
+/// that each emitter wraps in its own comment syntax (JSDoc, docstring,
+/// `///`, `---`). A generated function documents its own schema and lists
+/// the described fields it checks inline, keyed by instance path.
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+
+/// Plain-text doc lines for the function validating `node`, whose schema
+/// lives at `schema_path`. Empty when nothing in it has a description.
+pub fn function_doc(schema: &CompiledSchema, schema_path: &str, node: &Node) -> Vec<String> {
+    let mut lines: Vec<String> = schema
+        .description(schema_path)
+        .map(|d| d.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let mut fields = Vec::new();
+    collect_fields(schema, node, schema_path, "", &mut fields);
+    if !fields.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("Fields:".into());
+        for (path, desc) in fields {
+            let mut desc_lines = desc.lines();
+            lines.push(format!("  {path}: {}", desc_lines.next().unwrap_or("")));
+            lines.extend(desc_lines.map(|l| format!("    {l}")));
+        }
+    }
+    // Emitters put each line in a line comment: nothing may end it early
+    lines
+        .into_iter()
+        .map(|l| l.replace(|c: char| breaks_comment(c), " "))
+        .collect()
+}
+
+/// Control characters (other than tab) and the Unicode line separators,
+/// which some languages end a line comment at.
+fn breaks_comment(c: char) -> bool {
+    (c.is_control() && c != '\t') || c == '\u{2028}' || c == '\u{2029}'
+}
+
+/// Walk the schema checked inline by one function (refs are separate
+/// functions with their own docs), collecting `(instance path, description)`.
+fn collect_fields(
+    schema: &CompiledSchema,
+    node: &Node,
+    sp: &str,
+    ip: &str,
+    out: &mut Vec<(String, String)>,
+) {
+    let mut child = |child: &Node, child_sp: String, child_ip: String| {
+        if let Some(desc) = schema.description(&child_sp) {
+            out.push((child_ip.clone(), desc.to_string()));
+        }
+        collect_fields(schema, child, &child_sp, &child_ip, out);
+    };
+    match node {
+        Node::Nullable { inner } => collect_fields(schema, inner, sp, ip, out),
+        Node::Elements { schema: elem } => child(elem, format!("{sp}/elements"), format!("{ip}/*")),
+        Node::Values { schema: value } => child(value, format!("{sp}/values"), format!("{ip}/*")),
+        Node::Properties {
+            required, optional, ..
+        } => {
+            for (key, node) in required {
+                let key = escape_pointer(key);
+                child(
+                    node,
+                    format!("{sp}/properties/{key}"),
+                    format!("{ip}/{key}"),
+                );
+            }
+            for (key, node) in optional {
+                let key = escape_pointer(key);
+                child(
+                    node,
+                    format!("{sp}/optionalProperties/{key}"),
+                    format!("{ip}/{key}"),
+                );
+            }
+        }
+        Node::Discriminator { mapping, .. } => {
+            for (tag, variant) in mapping {
+                let variant_sp = format!("{sp}/mapping/{}", escape_pointer(tag));
+                collect_fields(schema, variant, &variant_sp, ip, out);
+            }
+        }
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_function_doc() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"description": "A customer.\nCreated at signup."},
+            "properties": {
+                "name": {"type": "string", "metadata": {"description": "Full name"}},
+                "tags": {"elements": {"type": "string", "metadata": {"description": "A tag"}}}
+            },
+            "optionalProperties": {"home": {"ref": "address"}},
+            "definitions": {"address": {"metadata": {"description": "Postal address"}}}
+        }))
+        .unwrap();
+        assert_eq!(
+            function_doc(&compiled, "", &compiled.root),
+            vec![
+                "A customer.",
+                "Created at signup.",
+                "",
+                "Fields:",
+                "  /name: Full name",
+                "  /tags/*: A tag",
+            ]
+        );
+        let address = &compiled.definitions["address"];
+        assert_eq!(
+            function_doc(&compiled, "/definitions/address", address),
+            vec!["Postal address"]
+        );
+    }
+
+    #[test]
+    fn test_no_descriptions() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        assert!(function_doc(&compiled, "", &compiled.root).is_empty());
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use super::runtime::{HEADER, RUNTIME, TIMESTAMP};
+use super::writer::{c_string, comment, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::options::EmitOptions;
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::source_map;
+
+/// Emit a C file validating parsed instances against `schema`.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a C file with the given options. Only the banner, fail-fast,
+/// `max_errors` and `is_valid` apply; the limit is fixed when generating.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut file = File {
+        defs: schema
+            .definitions
+            .keys()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect(),
+        timestamps: false,
+        fail_fast: opts.fail_fast,
+        max_errors: opts.error_limit(),
+        source_map: opts.source_map,
+    };
+
+    let root_doc = function_doc(schema, "", &schema.root);
+    let recursive = recursive(&schema.definitions);
+    let mut funcs = vec![file.func("jtd__root", "root", &root_doc, &schema.root, "", false)];
+    for (name, node) in &schema.definitions {
+        let id = format!("jtd__d{}", file.defs[name.as_str()]);
+        let sp = format!("/definitions/{}", escape_pointer(name));
+        let doc = function_doc(schema, &sp, node);
+        let guarded = recursive.contains(name.as_str());
+        funcs.push(file.func(&id, &comment(&sp), &doc, node, &sp, guarded));
+    }
+    if opts.is_valid {
+        funcs.push(file.predicate("jtd__is_root", "root", &schema.root, false));
+        for (name, node) in &schema.definitions {
+            let id = format!("jtd__is_d{}", file.defs[name.as_str()]);
+            let note = comment(&format!("/definitions/{}", escape_pointer(name)));
+            let guarded = recursive.contains(name.as_str());
+            funcs.push(file.predicate(&id, &note, node, guarded));
+        }
+    }
+
+    let mut w = CodeWriter::new();
+    w.line("// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
+    w.line("// This code is generated from a JSON Type Definition schema.");
+    w.line("// Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("c") {
+            w.line(&format!("// {line}"));
+        }
+    }
+    let mut out = w.finish();
+    out.push_str(HEADER);
+    if opts.is_valid {
+        out.push_str(IS_VALID_HEADER);
+    }
+    out.push_str(RUNTIME);
+    if file.timestamps {
+        out.push_str(TIMESTAMP);
+    }
+
+    let mut w = CodeWriter::new();
+    w.blank();
+    for i in 0..schema.definitions.len() {
+        w.line(&format!("static void jtd__d{i}(jtd__ctx *c, size_t t);"));
+    }
+    if opts.is_valid {
+        for i in 0..schema.definitions.len() {
+            w.line(&format!("static int jtd__is_d{i}(jtd__ctx *c, size_t t);"));
+        }
+    }
+    out.push_str(&w.finish());
+    for f in funcs {
+        out.push('\n');
+        out.push_str(&f);
+    }
+
+    let mut w = CodeWriter::new();
+    w.blank();
+    w.open("size_t jtd_validate(const jtd_node *nodes, jtd_error *errors, size_t cap)");
+    w.line("jtd__ctx c = {nodes, errors, cap, 0, 0};");
+    w.line("jtd__root(&c, 0);");
+    w.line("return c.count;");
+    w.close();
+    if opts.is_valid {
+        w.blank();
+        w.open("int jtd_is_valid(const jtd_node *nodes)");
+        w.line("jtd__ctx c = {nodes, NULL, 0, 0, 0};");
+        w.line("return jtd__is_root(&c, 0);");
+        w.close();
+    }
+    out.push_str(&w.finish());
+    out
+}
+
+/// The declaration of `jtd_is_valid`, after the API in [`HEADER`].
+const IS_VALID_HEADER: &str = r#"
+/* 1 when the parsed value at nodes[0] is valid, 0 at the first failure;
+ * no errors are recorded. */
+int jtd_is_valid(const jtd_node *nodes);
+"#;
+
+struct File<'a> {
+    /// Index of each definition's function, `jtd__d<index>`.
+    defs: BTreeMap<&'a str, usize>,
+    timestamps: bool,
+    /// Return after the first error.
+    fail_fast: bool,
+    /// Return once there are this many errors.
+    max_errors: Option<usize>,
+    /// Wrap the checks of each node in source-map markers.
+    source_map: bool,
+}
+
+/// A function body under construction.
+struct Func {
+    w: CodeWriter,
+    vars: usize,
+    /// Statement leaving the function after an error, when failing fast.
+    stop: Option<&'static str>,
+    /// The error count from which `stop` runs, rather than after any error.
+    limit: Option<usize>,
+    /// Whether this is a predicate for `jtd_is_valid`: a failure runs
+    /// `stop`, which returns 0, without recording an error.
+    predicate: bool,
+}
+
+impl Func {
+    fn var(&mut self, prefix: &str) -> String {
+        self.vars += 1;
+        format!("{prefix}{}", self.vars)
+    }
+
+    fn err(&mut self, t: &str, sp: &str) {
+        if let (true, Some(stop)) = (self.predicate, self.stop) {
+            self.w.line(stop);
+            return;
+        }
+        let err = format!("jtd__err(c, {t}, {});", c_string(sp));
+        match (self.stop, self.limit) {
+            (Some(stop), Some(n)) => self
+                .w
+                .line(&format!("{err} if (c->count >= {n}) {{ {stop} }}")),
+            (Some(stop), None) => self.w.line(&format!("{err} {stop}")),
+            (None, _) => self.w.line(&err),
+        }
+    }
+}
+
+impl<'a> File<'a> {
+    /// The function `id` validating node `t` against `node`, documented
+    /// with `doc` from `metadata.description`. `guarded` functions, those of
+    /// recursive definitions, count how deeply they nest.
+    fn func(
+        &mut self,
+        id: &str,
+        note: &str,
+        doc: &[String],
+        node: &'a Node,
+        sp: &str,
+        guarded: bool,
+    ) -> String {
+        let mut f = Func {
+            w: CodeWriter::new(),
+            vars: 0,
+            stop: None,
+            limit: self.max_errors,
+            predicate: false,
+        };
+        f.w.line(&format!("// {note}"));
+        // A block comment: a `//` line ending in `\` would swallow the next
+        if !doc.is_empty() {
+            f.w.line("/*");
+            for line in doc {
+                let line = format!(" * {}", line.replace("*/", "*\\/"));
+                f.w.line(line.trim_end());
+            }
+            f.w.line(" */");
+        }
+        f.w.open(&format!("static void {id}(jtd__ctx *c, size_t t)"));
+        if matches!(node, Node::Empty)
+            || matches!(node, Node::Nullable { inner } if matches!(**inner, Node::Empty))
+        {
+            f.w.line("(void)c;");
+            f.w.line("(void)t;");
+        }
+        // From MAX_REF_DEPTH nested calls on, report the value rather than
+        // descend, so deep instances cannot overflow the stack
+        if guarded {
+            f.w.open(&format!("if (c->depth >= {MAX_REF_DEPTH})"));
+            f.err("t", sp);
+            f.w.line("return;");
+            f.w.close();
+            f.w.line("c->depth++;");
+        }
+        if self.fail_fast || self.max_errors.is_some() {
+            f.stop = Some(if guarded {
+                "c->depth--; return;"
+            } else {
+                "return;"
+            });
+        }
+        self.node(&mut f, node, "t", sp, None);
+        if guarded {
+            f.w.line("c->depth--;");
+        }
+        f.w.close();
+        f.w.finish()
+    }
+
+    /// The predicate `id` for `jtd_is_valid`, returning 1 when node `t`
+    /// is valid against `node` and 0 at the first failure. `guarded` ones
+    /// share the validators' depth count.
+    fn predicate(&mut self, id: &str, note: &str, node: &'a Node, guarded: bool) -> String {
+        let mut f = Func {
+            w: CodeWriter::new(),
+            vars: 0,
+            stop: Some("return 0;"),
+            limit: None,
+            predicate: true,
+        };
+        f.w.line(&format!("// {note}"));
+        f.w.open(&format!("static int {id}(jtd__ctx *c, size_t t)"));
+        if matches!(node, Node::Empty)
+            || matches!(node, Node::Nullable { inner } if matches!(**inner, Node::Empty))
+        {
+            f.w.line("(void)c;");
+            f.w.line("(void)t;");
+        }
+        if guarded {
+            f.w.line(&format!("if (c->depth >= {MAX_REF_DEPTH}) return 0;"));
+            f.w.line("c->depth++;");
+            f.stop = Some("c->depth--; return 0;");
+        }
+        self.node(&mut f, node, "t", "", None);
+        if guarded {
+            f.w.line("c->depth--;");
+        }
+        f.w.line("return 1;");
+        f.w.close();
+        f.w.finish()
+    }
+
+    /// [`Self::node_checks`], in source-map markers when asked for.
+    fn node(&mut self, f: &mut Func, node: &'a Node, t: &str, sp: &str, tag: Option<&str>) {
+        if !self.source_map {
+            return self.node_checks(f, node, t, sp, tag);
+        }
+        f.w.line(&source_map::open(node));
+        self.node_checks(f, node, t, sp, tag);
+        f.w.line(source_map::close());
+    }
+
+    /// Validate node `t` (a C expression) against `node` at schema path
+    /// `sp`. `tag` is a discriminator tag the properties of `node` may
+    /// leave undeclared.
+    fn node_checks(&mut self, f: &mut Func, node: &'a Node, t: &str, sp: &str, tag: Option<&str>) {
+        match node {
+            Node::Empty => {}
+
+            Node::Type { type_kw } => {
+                let check = self.type_check(*type_kw, t);
+                f.w.open(&format!("if (!{check})"));
+                f.err(t, &format!("{sp}/type"));
+                f.w.close();
+            }
+
+            Node::Enum { values } => {
+                let checks: Vec<String> = values
+                    .iter()
+                    .map(|value| format!("!jtd__eq(&c->nodes[{t}], {})", c_string(value)))
+                    .collect();
+                f.w.open(&format!("if ({})", checks.join(" && ")));
+                f.err(t, &format!("{sp}/enum"));
+                f.w.close();
+            }
+
+            Node::Ref { name } if f.predicate => {
+                let index = self.defs[name.as_str()];
+                let stop = f.stop.unwrap_or("return 0;");
+                f.w.line(&format!("if (!jtd__is_d{index}(c, {t})) {{ {stop} }}"));
+            }
+
+            Node::Ref { name } => {
+                let index = self.defs[name.as_str()];
+                f.w.line(&format!("jtd__d{index}(c, {t});"));
+                if let Some(stop) = f.stop {
+                    let full = match f.limit {
+                        Some(n) => format!("c->count >= {n}"),
+                        None => "c->count > 0".to_string(),
+                    };
+                    f.w.line(&format!("if ({full}) {{ {stop} }}"));
+                }
+            }
+
+            Node::Nullable { inner } => {
+                if matches!(**inner, Node::Empty) {
+                    return;
+                }
+                f.w.open(&format!("if (c->nodes[{t}].kind != JTD_NULL)"));
+                self.node(f, inner, t, sp, tag);
+                f.w.close();
+            }
+
+            Node::Elements { schema: item } => {
+                let item_sp = format!("{sp}/elements");
+                f.w.open(&format!("if (c->nodes[{t}].kind != JTD_ARRAY)"));
+                f.err(t, &item_sp);
+                if matches!(**item, Node::Empty) {
+                    f.w.close();
+                    return;
+                }
+                let v = f.var("v");
+                f.w.close_open(&format!(
+                    "else for (size_t {v} = {t} + 1; {v} < c->nodes[{t}].next; {v} = c->nodes[{v}].next)"
+                ));
+                self.node(f, item, &v, &item_sp, None);
+                f.w.close();
+            }
+
+            Node::Values { schema: item } => {
+                let item_sp = format!("{sp}/values");
+                f.w.open(&format!("if (c->nodes[{t}].kind != JTD_OBJECT)"));
+                f.err(t, &item_sp);
+                if matches!(**item, Node::Empty) {
+                    f.w.close();
+                    return;
+                }
+                let (k, v) = (f.var("k"), f.var("v"));
+                f.w.close_open(&format!(
+                    "else for (size_t {k} = {t} + 1; {k} < c->nodes[{t}].next; {k} = c->nodes[{k} + 1].next)"
+                ));
+                f.w.line(&format!("size_t {v} = {k} + 1;"));
+                self.node(f, item, &v, &item_sp, None);
+                f.w.close();
+            }
+
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                f.w.open(&format!("if (c->nodes[{t}].kind != JTD_OBJECT)"));
+                f.err(t, &format!("{sp}{guard}"));
+                f.w.close_open("else");
+                for (key, child) in required {
+                    let child_sp = format!("{sp}/properties/{}", escape_pointer(key));
+                    let v = f.var("v");
+                    f.w.line(&format!(
+                        "size_t {v} = jtd__find(c->nodes, {t}, {});",
+                        c_string(key)
+                    ));
+                    f.w.open(&format!("if ({v} == JTD__NONE)"));
+                    f.err(t, &child_sp);
+                    if !matches!(child, Node::Empty) {
+                        f.w.close_open("else");
+                        self.node(f, child, &v, &child_sp, None);
+                    }
+                    f.w.close();
+                }
+                for (key, child) in optional {
+                    if matches!(child, Node::Empty) {
+                        continue;
+                    }
+                    let child_sp = format!("{sp}/optionalProperties/{}", escape_pointer(key));
+                    let v = f.var("v");
+                    f.w.line(&format!(
+                        "size_t {v} = jtd__find(c->nodes, {t}, {});",
+                        c_string(key)
+                    ));
+                    f.w.open(&format!("if ({v} != JTD__NONE)"));
+                    self.node(f, child, &v, &child_sp, None);
+                    f.w.close();
+                }
+                if !additional {
+                    let k = f.var("k");
+                    f.w.open(&format!(
+                        "for (size_t {k} = {t} + 1; {k} < c->nodes[{t}].next; {k} = c->nodes[{k} + 1].next)"
+                    ));
+                    let keys = required.keys().chain(optional.keys());
+                    let checks: Vec<String> = keys
+                        .map(String::as_str)
+                        .chain(tag)
+                        .map(|key| format!("!jtd__eq(&c->nodes[{k}], {})", c_string(key)))
+                        .collect();
+                    if checks.is_empty() {
+                        f.err(&k, sp);
+                    } else {
+                        f.w.open(&format!("if ({})", checks.join(" && ")));
+                        f.err(&k, sp);
+                        f.w.close();
+                    }
+                    f.w.close();
+                }
+                f.w.close();
+            }
+
+            Node::Discriminator { tag, mapping } => {
+                let tag_sp = format!("{sp}/discriminator");
+                f.w.open(&format!("if (c->nodes[{t}].kind != JTD_OBJECT)"));
+                f.err(t, &tag_sp);
+                f.w.close_open("else");
+                let v = f.var("v");
+                f.w.line(&format!(
+                    "size_t {v} = jtd__find(c->nodes, {t}, {});",
+                    c_string(tag)
+                ));
+                f.w.open(&format!("if ({v} == JTD__NONE)"));
+                f.err(t, &tag_sp);
+                f.w.close_open(&format!("else if (c->nodes[{v}].kind != JTD_STRING)"));
+                f.err(&v, &tag_sp);
+                for (value, variant) in mapping {
+                    f.w.close_open(&format!(
+                        "else if (jtd__eq(&c->nodes[{v}], {}))",
+                        c_string(value)
+                    ));
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    self.node(f, variant, t, &variant_sp, Some(tag));
+                }
+                f.w.close_open("else");
+                f.err(&v, &format!("{sp}/mapping"));
+                f.w.close();
+                f.w.close();
+            }
+        }
+    }
+
+    /// A C expression, nonzero when node `t` has type `type_kw`.
+    fn type_check(&mut self, type_kw: TypeKeyword, t: &str) -> String {
+        let int = |min: &str, max: &str| format!("jtd__int(&c->nodes[{t}], {min}, {max})");
+        match type_kw {
+            TypeKeyword::Boolean => {
+                format!("(c->nodes[{t}].kind == JTD_TRUE || c->nodes[{t}].kind == JTD_FALSE)")
+            }
+            TypeKeyword::String => format!("(c->nodes[{t}].kind == JTD_STRING)"),
+            TypeKeyword::Timestamp => {
+                self.timestamps = true;
+                format!("jtd__timestamp(&c->nodes[{t}])")
+            }
+            TypeKeyword::Float32 | TypeKeyword::Float64 => {
+                format!("(c->nodes[{t}].kind == JTD_NUMBER)")
+            }
+            TypeKeyword::Int8 => int("-128.0", "127.0"),
+            TypeKeyword::Uint8 => int("0.0", "255.0"),
+            TypeKeyword::Int16 => int("-32768.0", "32767.0"),
+            TypeKeyword::Uint16 => int("0.0", "65535.0"),
+            TypeKeyword::Int32 => int("-2147483648.0", "2147483647.0"),
+            TypeKeyword::Uint32 => int("0.0", "4294967295.0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["a", "b"]}}}
+        }))
+        .unwrap();
+        let c = emit(&compiled);
+        assert!(c.contains(
+            "size_t jtd_validate(const jtd_node *nodes, jtd_error *errors, size_t cap) {"
+        ));
+        assert!(c.contains("static void jtd__d0(jtd__ctx *c, size_t t);"));
+        assert!(c.contains("// \"/definitions/id\"\nstatic void jtd__d0(jtd__ctx *c, size_t t) {"));
+        assert!(c.contains("jtd__d0(c, v1);"));
+        assert!(c.contains("jtd__err(c, v3, \"/properties/tags/elements/enum\", 30);"));
+        // The RFC 3339 helpers only come with timestamps
+        assert!(!c.contains("jtd__timestamp"));
+        let compiled = compiler::compile(&json!({"type": "timestamp"})).unwrap();
+        assert!(emit(&compiled).contains("if (!jtd__timestamp(&c->nodes[t])) {"));
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string", "metadata": {"description": "An id */ here."}}},
+            "properties": {"id": {"ref": "id"}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let c = emit(&compiled);
+        assert!(c.contains("// root\n/*\n * An order.\n */\nstatic void jtd__root("));
+        assert!(c.contains(
+            "// \"/definitions/id\"\n/*\n * An id *\\/ here.\n */\nstatic void jtd__d0("
+        ));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            is_valid: true,
+            ..Default::default()
+        };
+        let c = emit_with_options(&compiled, &opts);
+        assert!(c.contains("int jtd_is_valid(const jtd_node *nodes);"));
+        assert!(c.contains("static int jtd__is_d0(jtd__ctx *c, size_t t);"));
+        assert!(c.contains("if (!jtd__is_d0(c, v2)) { return 0; }"));
+        assert!(c.contains("if (!jtd__is_d0(c, v1)) { c->depth--; return 0; }"));
+        assert!(c.contains("int jtd_is_valid(const jtd_node *nodes) {"));
+        assert!(!emit(&compiled).contains("jtd_is_valid"));
+    }
+}
@@ -0,0 +1,19 @@
+/// C emitter: one dependency-light C99 file per schema for firmware and
+/// other embedded hosts. Besides the validator it carries a small JSON DOM
+/// and parser, so the only requirements are `<stddef.h>`, `<stdlib.h>`
+/// (for `strtod`) and `<string.h>`, and nothing is allocated.
+///
+/// `jtd_parse` decodes the instance into a caller-supplied array of
+/// `jtd_node`s in document order, the layout [`crate::emit_wasm`] takes as
+/// tokens: an array's elements follow it, an object's members follow it as
+/// a key string node then the value's nodes, and each node records the
+/// index of the first node after it. Strings are unescaped in place in the
+/// input buffer. `jtd_validate` writes up to `cap` `jtd_error`s of
+/// `(node, schema_path)` and returns the error count, which may exceed
+/// `cap`; `jtd_instance_path` turns a node index back into its JSON
+/// Pointer.
+mod emit;
+mod runtime;
+mod writer;
+
+pub use emit::{emit, emit_with_options};
@@ -0,0 +1,100 @@
+/// Indentation-aware string builder for emitting C.
+pub struct CodeWriter {
+    buf: String,
+    depth: usize,
+}
+
+impl Default for CodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeWriter {
+    pub fn new() -> Self {
+        Self {
+            buf: String::new(),
+            depth: 0,
+        }
+    }
+
+    /// Write a line at the current indentation level.
+    pub fn line(&mut self, text: &str) {
+        for _ in 0..self.depth {
+            self.buf.push_str("    ");
+        }
+        self.buf.push_str(text);
+        self.buf.push('\n');
+    }
+
+    /// Write `text {` and indent.
+    pub fn open(&mut self, text: &str) {
+        self.line(&format!("{text} {{"));
+        self.depth += 1;
+    }
+
+    /// Dedent and write `}`.
+    pub fn close(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line("}");
+    }
+
+    /// `} text {` between the arms of an `if`.
+    pub fn close_open(&mut self, text: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(&format!("}} {text} {{"));
+        self.depth += 1;
+    }
+
+    /// Write an empty line.
+    pub fn blank(&mut self) {
+        self.buf.push('\n');
+    }
+
+    /// Consume and return the built string.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// `s` as a C string literal. Bytes outside printable ASCII are octal
+/// escapes, which unlike `\x` cannot run into the next character.
+pub fn c_literal(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for &b in s.as_bytes() {
+        match b {
+            b'"' | b'\\' | b'?' => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\{b:03o}")),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `s` as a C string literal and its byte length, e.g. `"id", 2`, since
+/// keys may hold NUL.
+pub fn c_string(s: &str) -> String {
+    format!("{}, {}", c_literal(s), s.len())
+}
+
+/// `s` made safe for a `//` comment, which runs to the end of the line.
+pub fn comment(s: &str) -> String {
+    format!("{s:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_string() {
+        assert_eq!(c_string("id"), r#""id", 2"#);
+        assert_eq!(c_literal("a\"b\\c?"), r#""a\"b\\c\?""#);
+        assert_eq!(c_string("é\n\0"), r#""\303\251\012\000", 4"#);
+    }
+}
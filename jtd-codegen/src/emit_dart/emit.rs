@@ -0,0 +1,602 @@
+use super::writer::{dart_string, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::emit_js::def_fn_name;
+use crate::options::EmitOptions;
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::source_map;
+
+/// Emit a Dart library from a compiled schema.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a Dart library with the given options. Only the banner, fail-fast,
+/// `max_errors` and `is_valid` apply.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut file = File {
+        source_map: opts.source_map,
+        ..Default::default()
+    };
+    let mut funcs = CodeWriter::new();
+
+    emit_dartdoc(&mut funcs, &function_doc(schema, "", &schema.root));
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    file.limit = limit.is_some();
+    let limit_param = limit.map_or(String::new(), |n| format!(", [int maxErrors = {n}]"));
+    funcs.open(&format!(
+        "List<ValidationError> validate(Object? instance{limit_param})"
+    ));
+    funcs.line("final e = <ValidationError>[];");
+    file.stop = stops.then_some("return e;");
+    file.node(&mut funcs, &schema.root, "instance", "''", "", None);
+    funcs.line("return e;");
+    funcs.close();
+
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        let sp = format!("/definitions/{}", escape_pointer(name));
+        funcs.line("");
+        emit_dartdoc(&mut funcs, &function_doc(schema, &sp, node));
+        funcs.open(&format!(
+            "void _{}(Object? v, List<ValidationError> e, String p{})",
+            def_fn_name(name),
+            if file.limit { ", int maxErrors" } else { "" }
+        ));
+        file.vars = 0;
+        // From MAX_REF_DEPTH nested calls on, report the value rather than
+        // descend, so deep instances cannot overflow the stack
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            funcs.open(&format!("if (_refDepth >= {MAX_REF_DEPTH})"));
+            funcs.line(&format!("e.add(ValidationError(p, {}));", dart_string(&sp)));
+            funcs.line("return;");
+            funcs.close();
+            funcs.line("_refDepth++;");
+        }
+        file.stop = if !stops {
+            None
+        } else if guarded {
+            Some("_refDepth--; return;")
+        } else {
+            Some("return;")
+        };
+        file.node(&mut funcs, node, "v", "p", &sp, None);
+        if guarded {
+            funcs.line("_refDepth--;");
+        }
+        funcs.close();
+    }
+
+    if opts.is_valid {
+        emit_is_valid(&mut file, &mut funcs, schema);
+    }
+
+    let mut w = CodeWriter::new();
+    w.line("// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
+    w.line("// This code is generated from a JSON Type Definition schema.");
+    w.line("// Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("dart") {
+            w.line(&format!("// {line}"));
+        }
+    }
+    // Definition functions keep their JSON names, and mapping variants
+    // repeat the map check their discriminator made
+    w.line("// ignore_for_file: non_constant_identifier_names, prefer_interpolation_to_compose_strings, unnecessary_type_check, unused_element");
+    w.line("");
+    w.line("/// A validation failure, as JSON Pointers into the instance and the schema.");
+    w.open("class ValidationError");
+    w.line("final String instancePath;");
+    w.line("final String schemaPath;");
+    w.line("");
+    w.line("const ValidationError(this.instancePath, this.schemaPath);");
+    w.line("");
+    w.line("@override");
+    w.line("String toString() => 'ValidationError($instancePath, $schemaPath)';");
+    w.close();
+    w.line("");
+    for (i, values) in file.enums.iter().enumerate() {
+        let values: Vec<String> = values.iter().map(|v| dart_string(v)).collect();
+        w.line(&format!("const _enum{i} = {{{}}};", values.join(", ")));
+    }
+    if !file.enums.is_empty() {
+        w.line("");
+    }
+    if !recursive.is_empty() {
+        w.line("/// Calls of recursive definitions in progress.");
+        w.line("var _refDepth = 0;");
+        w.line("");
+    }
+    let mut out = w.finish();
+    out.push_str(&funcs.finish());
+    if file.integers {
+        out.push_str(INTEGER_HELPER);
+    }
+    if file.timestamps {
+        out.push_str(TIMESTAMP_HELPER);
+    }
+    out
+}
+
+/// `isValid(instance)`: the checks `validate` makes, returning false at the
+/// first failure without building errors, and a predicate per definition.
+/// Recursive ones share the validators' depth count.
+fn emit_is_valid(file: &mut File, funcs: &mut CodeWriter, schema: &CompiledSchema) {
+    file.predicate = true;
+    file.limit = false;
+    funcs.line("");
+    funcs.line("/// Whether `instance` is valid; stops at the first failure and builds no errors.");
+    funcs.open("bool isValid(Object? instance)");
+    file.vars = 0;
+    file.stop = Some("return false;");
+    file.node(funcs, &schema.root, "instance", "''", "", None);
+    funcs.line("return true;");
+    funcs.close();
+
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        funcs.line("");
+        funcs.open(&format!("bool _{}(Object? v)", pred_fn_name(name)));
+        file.vars = 0;
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            funcs.line(&format!("if (_refDepth >= {MAX_REF_DEPTH}) return false;"));
+            funcs.line("_refDepth++;");
+            file.stop = Some("_refDepth--; return false;");
+        } else {
+            file.stop = Some("return false;");
+        }
+        file.node(funcs, node, "v", "''", "", None);
+        if guarded {
+            funcs.line("_refDepth--;");
+        }
+        funcs.line("return true;");
+        funcs.close();
+    }
+}
+
+/// Name of a definition's predicate in `isValid`, before its `_`.
+fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "isValid_", 1)
+}
+
+/// `p`, a path expression, followed by `/` and the key in `k`, with `~` as
+/// `~0` and `/` as `~1`.
+fn join_key(p: &str, k: &str) -> String {
+    format!("{p} + '/' + {k}.replaceAll('~', '~0').replaceAll('/', '~1')")
+}
+
+/// Whether a number is whole and in range; `jsonDecode` gives `int` or
+/// `double`, and `1.0` counts.
+const INTEGER_HELPER: &str = r#"
+bool _isInteger(Object? v, num min, num max) =>
+    v is num && v % 1 == 0 && v >= min && v <= max;
+"#;
+
+/// RFC 3339 with the checks of [`crate::timestamp::is_rfc3339`]: days per
+/// month, leap years and a leap second.
+const TIMESTAMP_HELPER: &str = r#"
+final _rfc3339 = RegExp(
+    r'^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))$');
+
+bool _isTimestamp(Object? v) {
+  if (v is! String) return false;
+  final m = _rfc3339.firstMatch(v);
+  if (m == null) return false;
+  final year = int.parse(m[1]!);
+  final month = int.parse(m[2]!);
+  final day = int.parse(m[3]!);
+  if (month < 1 || month > 12) return false;
+  if (int.parse(m[4]!) > 23 || int.parse(m[5]!) > 59 || int.parse(m[6]!) > 60) return false;
+  final leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+  final days = switch (month) {
+    2 => leap ? 29 : 28,
+    4 || 6 || 9 || 11 => 30,
+    _ => 31,
+  };
+  if (day < 1 || day > days) return false;
+  return m[8] == null || (int.parse(m[8]!) <= 23 && int.parse(m[9]!) <= 59);
+}
+"#;
+
+/// Dartdoc from plain doc lines.
+fn emit_dartdoc(w: &mut CodeWriter, lines: &[String]) {
+    for line in lines {
+        if line.is_empty() {
+            w.line("///");
+        } else {
+            w.line(&format!("/// {line}"));
+        }
+    }
+}
+
+#[derive(Default)]
+struct File {
+    /// The values of each enum, hoisted to `_enum<index>` sets.
+    enums: Vec<Vec<String>>,
+    integers: bool,
+    timestamps: bool,
+    /// Locals used so far in the current function.
+    vars: usize,
+    /// Statements leaving the current function after an error, when
+    /// failing fast.
+    stop: Option<&'static str>,
+    /// Whether functions take `maxErrors` and only leave with `stop` once
+    /// there are that many errors.
+    limit: bool,
+    /// Whether the checks are those of an `isValid` predicate: a failure
+    /// runs `stop`, which returns false, without recording an error.
+    predicate: bool,
+    /// Wrap the checks of each node in source-map markers.
+    source_map: bool,
+}
+
+impl File {
+    fn var(&mut self, prefix: &str) -> String {
+        self.vars += 1;
+        format!("{prefix}{}", self.vars)
+    }
+
+    /// [`Self::node_checks`], in source-map markers when asked for.
+    fn node(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        if !self.source_map {
+            return self.node_checks(w, node, v, p, sp, tag);
+        }
+        w.line(&source_map::open(node));
+        self.node_checks(w, node, v, p, sp, tag);
+        w.line(source_map::close());
+    }
+
+    /// Validate the value in local `v`, whose instance path is the Dart
+    /// expression `p`, against `node` at schema path `sp`. `tag` is a
+    /// discriminator tag the properties of `node` may leave undeclared.
+    fn node_checks(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        let stop = self.stop;
+        let limit = self.limit;
+        let predicate = self.predicate;
+        // Statements on a line of their own
+        let add = move |p: &str, sp: &str| {
+            if let (true, Some(stop)) = (predicate, stop) {
+                return stop.to_string();
+            }
+            let add = format!("e.add(ValidationError({p}, {}));", dart_string(sp));
+            match stop {
+                Some(stop) if limit => format!("{add} if (e.length >= maxErrors) {{ {stop} }}"),
+                Some(stop) => format!("{add} {stop}"),
+                None => add,
+            }
+        };
+        // The same as the body of an `if`
+        let err = move |p: &str, sp: &str| match stop {
+            Some(_) => format!("{{ {} }}", add(p, sp)),
+            None => add(p, sp),
+        };
+        match node {
+            Node::Empty => {}
+
+            Node::Type { type_kw } => {
+                let check = self.type_check(*type_kw, v);
+                w.line(&format!("if ({check}) {}", err(p, &format!("{sp}/type"))));
+            }
+
+            Node::Enum { values } => {
+                let index = match self.enums.iter().position(|e| e == values) {
+                    Some(index) => index,
+                    None => {
+                        self.enums.push(values.clone());
+                        self.enums.len() - 1
+                    }
+                };
+                w.line(&format!(
+                    "if ({v} is! String || !_enum{index}.contains({v})) {}",
+                    err(p, &format!("{sp}/enum"))
+                ));
+            }
+
+            Node::Ref { name } if predicate => {
+                let stop = stop.unwrap_or("return false;");
+                w.line(&format!("if (!_{}({v})) {{ {stop} }}", pred_fn_name(name)));
+            }
+
+            Node::Ref { name } => {
+                let limit_arg = if limit { ", maxErrors" } else { "" };
+                w.line(&format!("_{}({v}, e, {p}{limit_arg});", def_fn_name(name)));
+                match stop {
+                    Some(stop) if limit => {
+                        w.line(&format!("if (e.length >= maxErrors) {{ {stop} }}"))
+                    }
+                    Some(stop) => w.line(&format!("if (e.isNotEmpty) {{ {stop} }}")),
+                    None => {}
+                }
+            }
+
+            Node::Nullable { inner } => {
+                if matches!(**inner, Node::Empty) {
+                    return;
+                }
+                w.open(&format!("if ({v} != null)"));
+                self.node(w, inner, v, p, sp, tag);
+                w.close();
+            }
+
+            Node::Elements { schema: item } => {
+                let item_sp = format!("{sp}/elements");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!("if ({v} is! List) {}", err(p, &item_sp)));
+                    return;
+                }
+                w.open(&format!("if ({v} is! List)"));
+                w.line(&add(p, &item_sp));
+                let (i, x) = (self.var("i"), self.var("v"));
+                w.close_open("else");
+                w.open(&format!("for (var {i} = 0; {i} < {v}.length; {i}++)"));
+                w.line(&format!("final {x} = {v}[{i}];"));
+                self.node(
+                    w,
+                    item,
+                    &x,
+                    &format!("{p} + '/' + {i}.toString()"),
+                    &item_sp,
+                    None,
+                );
+                w.close();
+                w.close();
+            }
+
+            Node::Values { schema: item } => {
+                let item_sp = format!("{sp}/values");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!(
+                        "if ({v} is! Map<String, Object?>) {}",
+                        err(p, &item_sp)
+                    ));
+                    return;
+                }
+                w.open(&format!("if ({v} is! Map<String, Object?>)"));
+                w.line(&add(p, &item_sp));
+                let (k, x) = (self.var("k"), self.var("v"));
+                w.close_open("else");
+                w.open(&format!("for (final {k} in {v}.keys)"));
+                w.line(&format!("final {x} = {v}[{k}];"));
+                self.node(w, item, &x, &join_key(p, &k), &item_sp, None);
+                w.close();
+                w.close();
+            }
+
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                w.open(&format!("if ({v} is! Map<String, Object?>)"));
+                w.line(&add(p, &format!("{sp}{guard}")));
+                w.close_open("else");
+                for (key, child) in required {
+                    let child_sp = format!("{sp}/properties/{}", escape_pointer(key));
+                    let lit = dart_string(key);
+                    if matches!(child, Node::Empty) {
+                        w.line(&format!(
+                            "if (!{v}.containsKey({lit})) {}",
+                            err(p, &child_sp)
+                        ));
+                        continue;
+                    }
+                    w.open(&format!("if (!{v}.containsKey({lit}))"));
+                    w.line(&add(p, &child_sp));
+                    w.close_open("else");
+                    let x = self.var("v");
+                    w.line(&format!("final {x} = {v}[{lit}];"));
+                    let child_p = format!(
+                        "{p} + {}",
+                        dart_string(&format!("/{}", escape_pointer(key)))
+                    );
+                    self.node(w, child, &x, &child_p, &child_sp, None);
+                    w.close();
+                }
+                for (key, child) in optional {
+                    if matches!(child, Node::Empty) {
+                        continue;
+                    }
+                    let child_sp = format!("{sp}/optionalProperties/{}", escape_pointer(key));
+                    let lit = dart_string(key);
+                    w.open(&format!("if ({v}.containsKey({lit}))"));
+                    let x = self.var("v");
+                    w.line(&format!("final {x} = {v}[{lit}];"));
+                    let child_p = format!(
+                        "{p} + {}",
+                        dart_string(&format!("/{}", escape_pointer(key)))
+                    );
+                    self.node(w, child, &x, &child_p, &child_sp, None);
+                    w.close();
+                }
+                if !additional {
+                    let k = self.var("k");
+                    let keys = required.keys().chain(optional.keys());
+                    let checks: Vec<String> = keys
+                        .map(String::as_str)
+                        .chain(tag)
+                        .map(|key| format!("{k} != {}", dart_string(key)))
+                        .collect();
+                    let key_p = join_key(p, &k);
+                    w.open(&format!("for (final {k} in {v}.keys)"));
+                    if checks.is_empty() {
+                        w.line(&add(&key_p, sp));
+                    } else {
+                        let error = err(&key_p, sp);
+                        w.line(&format!("if ({}) {error}", checks.join(" && ")));
+                    }
+                    w.close();
+                }
+                w.close();
+            }
+
+            Node::Discriminator { tag, mapping } => {
+                let tag_sp = format!("{sp}/discriminator");
+                let tag_p = format!(
+                    "{p} + {}",
+                    dart_string(&format!("/{}", escape_pointer(tag)))
+                );
+                let lit = dart_string(tag);
+                w.open(&format!("if ({v} is! Map<String, Object?>)"));
+                w.line(&add(p, &tag_sp));
+                w.close_open(&format!("else if (!{v}.containsKey({lit}))"));
+                w.line(&add(p, &tag_sp));
+                w.close_open("else");
+                let t = self.var("t");
+                w.line(&format!("final {t} = {v}[{lit}];"));
+                w.open(&format!("if ({t} is! String)"));
+                w.line(&add(&tag_p, &tag_sp));
+                for (value, variant) in mapping {
+                    w.close_open(&format!("else if ({t} == {})", dart_string(value)));
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    self.node(w, variant, v, p, &variant_sp, Some(tag));
+                }
+                w.close_open("else");
+                w.line(&add(&tag_p, &format!("{sp}/mapping")));
+                w.close();
+                w.close();
+            }
+        }
+    }
+
+    /// A Dart expression, true when the value in `v` does not have type
+    /// `type_kw`.
+    fn type_check(&mut self, type_kw: TypeKeyword, v: &str) -> String {
+        let mut int = |min: &str, max: &str| {
+            self.integers = true;
+            format!("!_isInteger({v}, {min}, {max})")
+        };
+        match type_kw {
+            TypeKeyword::Boolean => format!("{v} is! bool"),
+            TypeKeyword::String => format!("{v} is! String"),
+            TypeKeyword::Timestamp => {
+                self.timestamps = true;
+                format!("!_isTimestamp({v})")
+            }
+            TypeKeyword::Float32 | TypeKeyword::Float64 => format!("{v} is! num"),
+            TypeKeyword::Int8 => int("-128", "127"),
+            TypeKeyword::Uint8 => int("0", "255"),
+            TypeKeyword::Int16 => int("-32768", "32767"),
+            TypeKeyword::Uint16 => int("0", "65535"),
+            TypeKeyword::Int32 => int("-2147483648", "2147483647"),
+            TypeKeyword::Uint32 => int("0", "4294967295"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["a", "$b"]}}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let dart = emit(&compiled);
+        assert!(dart.contains("/// An order.\nList<ValidationError> validate(Object? instance) {"));
+        assert!(dart.contains("const _enum0 = {'a', '\\$b'};"));
+        assert!(dart.contains("_validate_id(v1, e, '' + '/id');"));
+        assert!(dart.contains("void _validate_id(Object? v, List<ValidationError> e, String p) {"));
+        assert!(
+            dart.contains("if (v is! String) e.add(ValidationError(p, '/definitions/id/type'));")
+        );
+        assert!(dart.contains("final v4 = v2[i3];"));
+        // Helpers only come with the types that need them
+        assert!(!dart.contains("_isTimestamp"));
+        let compiled = compiler::compile(&json!({"type": "timestamp"})).unwrap();
+        assert!(emit(&compiled).contains("if (!_isTimestamp(instance)) e.add("));
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            fail_fast: true,
+            ..Default::default()
+        };
+        let dart = emit_with_options(&compiled, &opts);
+        assert!(dart.contains(
+            "if (v1 is! String) { e.add(ValidationError('' + '/id', '/properties/id/type')); return e; }"
+        ));
+        assert!(dart.contains(
+            "e.add(ValidationError(p, '/definitions/node/optionalProperties')); _refDepth--; return;\n"
+        ));
+        assert!(dart.contains("if (e.isNotEmpty) { _refDepth--; return; }"));
+    }
+
+    #[test]
+    fn test_max_errors() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            max_errors: Some(3),
+            ..Default::default()
+        };
+        let dart = emit_with_options(&compiled, &opts);
+        assert!(dart.contains("validate(Object? instance, [int maxErrors = 3])"));
+        assert!(dart.contains("String p, int maxErrors)"));
+        assert!(dart.contains(
+            "{ e.add(ValidationError('' + '/id', '/properties/id/type')); if (e.length >= maxErrors) { return e; } }"
+        ));
+        assert!(dart.contains(", maxErrors);\n"));
+        assert!(dart.contains("if (e.length >= maxErrors) { _refDepth--; return; }"));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            is_valid: true,
+            max_errors: Some(3),
+            ..Default::default()
+        };
+        let dart = emit_with_options(&compiled, &opts);
+        assert!(dart.contains("bool isValid(Object? instance) {"));
+        assert!(dart.contains("if (v1 is! String) { return false; }"));
+        assert!(dart.contains("bool _isValid_node(Object? v) {"));
+        assert!(dart.contains("if (!_isValid_node(v1)) { _refDepth--; return false; }"));
+        // Only the validators count errors
+        assert!(!dart.contains("_isValid_node(v1, "));
+    }
+}
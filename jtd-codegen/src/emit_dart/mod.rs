@@ -0,0 +1,6 @@
+/// Dart emitter: validators over the values `jsonDecode` returns, compiled
+/// ahead of time with Flutter apps. The file imports nothing.
+mod emit;
+mod writer;
+
+pub use emit::{emit, emit_with_options};
@@ -0,0 +1,112 @@
+/// `coerce(instance)`: returns a copy of `instance` with string-typed
+/// booleans and numbers converted where the schema expects them, following a
+/// [`CoercePlan`]. Containers on the way to a converted slot are shallow
+/// copies, so the caller's document is never modified.
+use super::nodes::def_fn_name;
+use super::writer::{escape_js, CodeWriter};
+use crate::coerce::{Coerce, CoercePlan, Scalar};
+
+const IS_OBJECT: &str = "typeof {v} === \"object\" && {v} !== null && !Array.isArray({v})";
+
+/// Emit the conversion helpers, the definition coercers and the `coerce`
+/// entry point.
+pub(super) fn emit_coerce(w: &mut CodeWriter, plan: &CoercePlan, export: bool) {
+    w.line("const JSON_NUMBER = /^-?(0|[1-9][0-9]*)(\\.[0-9]+)?([eE][+-]?[0-9]+)?$/;");
+    w.line("");
+    w.open("function coerceBoolean(x)");
+    w.line("return x === \"true\" ? true : x === \"false\" ? false : x;");
+    w.close();
+    w.line("");
+    w.open("function coerceNumber(x, integer)");
+    w.line("if (typeof x !== \"string\" || !JSON_NUMBER.test(x)) return x;");
+    w.line("const n = Number(x);");
+    w.line("return Number.isFinite(n) && (!integer || Number.isInteger(n)) ? n : x;");
+    w.close();
+    w.line("");
+    for (name, c) in &plan.definitions {
+        w.open(&format!("function {}(v)", coerce_fn_name(name)));
+        emit_value(w, c, "v", 0);
+        w.line("return v;");
+        w.close();
+        w.line("");
+    }
+    w.line("/** A copy of `instance` with \"42\" / \"true\" style strings converted where the schema expects numbers or booleans. */");
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    w.open(&format!("{decl} coerce(instance)"));
+    w.line("let v = instance;");
+    if let Some(c) = &plan.root {
+        emit_value(w, c, "v", 0);
+    }
+    w.line("return v;");
+    w.close();
+}
+
+fn coerce_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "coerce_", 1)
+}
+
+fn is_object(v: &str) -> String {
+    IS_OBJECT.replace("{v}", v)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, c: &Coerce, v: &str, depth: usize) {
+    match c {
+        Coerce::Scalar(Scalar::Boolean) => w.line(&format!("{v} = coerceBoolean({v});")),
+        Coerce::Scalar(Scalar::Integer) => w.line(&format!("{v} = coerceNumber({v}, true);")),
+        Coerce::Scalar(Scalar::Float) => w.line(&format!("{v} = coerceNumber({v}, false);")),
+        Coerce::Ref(name) => w.line(&format!("{v} = {}({v});", coerce_fn_name(name))),
+        Coerce::Object(props) => {
+            w.open(&format!("if ({})", is_object(v)));
+            w.line(&format!("{v} = {{...{v}}};"));
+            emit_props(w, props, v, depth);
+            w.close();
+        }
+        Coerce::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if (Array.isArray({v}))"));
+            w.line(&format!("{v} = [...{v}];"));
+            w.open(&format!("for (let {i} = 0; {i} < {v}.length; {i}++)"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Coerce::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if ({})", is_object(v)));
+            w.line(&format!("{v} = {{...{v}}};"));
+            w.open(&format!("for (const {k} in {v})"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Coerce::Tagged { tag, variants } => {
+            w.open(&format!("if ({})", is_object(v)));
+            w.line(&format!("{v} = {{...{v}}};"));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] === \"{}\"", escape_js(tag), escape_js(value));
+                if i == 0 {
+                    w.open(&format!("if ({cond})"));
+                } else {
+                    w.close_open(&format!("else if ({cond})"));
+                }
+                emit_props(w, props, v, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[(String, Coerce)], v: &str, depth: usize) {
+    for (key, c) in props {
+        let key = escape_js(key);
+        w.open(&format!("if (\"{key}\" in {v})"));
+        emit_value(w, c, &format!("{v}[\"{key}\"]"), depth);
+        w.close();
+    }
+}
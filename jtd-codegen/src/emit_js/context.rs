@@ -3,6 +3,14 @@
 /// Tracks the JS expressions for the current value, error list,
 /// instance path, and schema path. Each descent into a child node
 /// produces a new context via pure methods -- no mutation.
+use std::rc::Rc;
+
+use super::hoist::Hoisted;
+use super::nodes::DESCRIBE_FN;
+use super::writer::escape_js;
+use crate::compiler::escape_pointer;
+use crate::messages::{Message, Subject};
+
 #[derive(Clone)]
 pub struct EmitContext {
     /// JS expression for the value being validated (e.g. "v", "v[\"name\"]")
@@ -15,6 +23,20 @@ pub struct EmitContext {
     pub sp: String,
     /// Nesting depth for generating unique loop variable names.
     pub depth: usize,
+    /// Statement leaving the function after an error, for fail-fast
+    /// validation; `None` collects every error.
+    pub stop: Option<String>,
+    /// The variable holding the error limit, with `--max-errors`: `stop`
+    /// then only runs once there are that many errors.
+    pub limit: Option<String>,
+    /// Whether errors carry a `message`, with `--messages`.
+    pub messages: bool,
+    /// The module's shared helpers for repeated checks; empty keeps every
+    /// check inline.
+    pub hoisted: Rc<Hoisted>,
+    /// Whether the checks are those of an `isValid` predicate: a failure
+    /// runs `stop`, which returns false, without recording an error.
+    pub predicate: bool,
 }
 
 impl EmitContext {
@@ -26,6 +48,11 @@ impl EmitContext {
             ip: "\"\"".into(),
             sp: "\"\"".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            hoisted: Rc::default(),
+            predicate: false,
         }
     }
 
@@ -37,6 +64,45 @@ impl EmitContext {
             ip: "p".into(),
             sp: "sp".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            hoisted: Rc::default(),
+            predicate: false,
+        }
+    }
+
+    /// Context for a `validateAt` branch: the fragment is `value` and the
+    /// schema path starts at the requested `pointer`.
+    pub fn at_pointer() -> Self {
+        Self {
+            val: "value".into(),
+            err: "e".into(),
+            ip: "\"\"".into(),
+            sp: "pointer".into(),
+            depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            hoisted: Rc::default(),
+            predicate: false,
+        }
+    }
+
+    /// Context for a SAX leaf check `sax_N(v, e, p)`, whose schema path is
+    /// the fixed `schema_path`.
+    pub fn sax_leaf(schema_path: &str) -> Self {
+        Self {
+            val: "v".into(),
+            err: "e".into(),
+            ip: "p".into(),
+            sp: format!("\"{}\"", super::writer::escape_js(schema_path)),
+            depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            hoisted: Rc::default(),
+            predicate: false,
         }
     }
 
@@ -61,22 +127,40 @@ impl EmitContext {
     /// Descend into a required property value.
     pub fn required_prop(&self, key: &str) -> Self {
         Self {
-            val: format!("{}[\"{}\"]", self.val, key),
+            val: format!("{}[\"{}\"]", self.val, escape_js(key)),
             err: self.err.clone(),
-            ip: format!("{} + \"/{}\"", self.ip, key),
-            sp: format!("{} + \"/properties/{}\"", self.sp, key),
+            ip: format!("{} + \"/{}\"", self.ip, escape_js(&escape_pointer(key))),
+            sp: format!(
+                "{} + \"/properties/{}\"",
+                self.sp,
+                escape_js(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            hoisted: self.hoisted.clone(),
+            predicate: self.predicate,
         }
     }
 
     /// Descend into an optional property value.
     pub fn optional_prop(&self, key: &str) -> Self {
         Self {
-            val: format!("{}[\"{}\"]", self.val, key),
+            val: format!("{}[\"{}\"]", self.val, escape_js(key)),
             err: self.err.clone(),
-            ip: format!("{} + \"/{}\"", self.ip, key),
-            sp: format!("{} + \"/optionalProperties/{}\"", self.sp, key),
+            ip: format!("{} + \"/{}\"", self.ip, escape_js(&escape_pointer(key))),
+            sp: format!(
+                "{} + \"/optionalProperties/{}\"",
+                self.sp,
+                escape_js(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            hoisted: self.hoisted.clone(),
+            predicate: self.predicate,
         }
     }
 
@@ -88,6 +172,11 @@ impl EmitContext {
             ip: format!("{} + \"/\" + {}", self.ip, idx_var),
             sp: format!("{} + \"/elements\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            hoisted: self.hoisted.clone(),
+            predicate: self.predicate,
         }
     }
 
@@ -96,9 +185,14 @@ impl EmitContext {
         Self {
             val: format!("{}[{}]", self.val, key_var),
             err: self.err.clone(),
-            ip: format!("{} + \"/\" + {}", self.ip, key_var),
+            ip: format!("{} + \"/\" + {}", self.ip, pointer_segment(key_var)),
             sp: format!("{} + \"/values\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            hoisted: self.hoisted.clone(),
+            predicate: self.predicate,
         }
     }
 
@@ -108,27 +202,76 @@ impl EmitContext {
             val: self.val.clone(),
             err: self.err.clone(),
             ip: self.ip.clone(),
-            sp: format!("{} + \"/mapping/{}\"", self.sp, variant_key),
+            sp: format!(
+                "{} + \"/mapping/{}\"",
+                self.sp,
+                escape_js(&escape_pointer(variant_key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            hoisted: self.hoisted.clone(),
+            predicate: self.predicate,
+        }
+    }
+
+    /// Check an inlined definition's body in place of the call to it: the
+    /// same value and instance path, with the schema path its function
+    /// would start from.
+    pub fn inlined_ref(&self, def_name: &str) -> Self {
+        Self {
+            sp: format!("\"/definitions/{}\"", escape_js(&escape_pointer(def_name))),
+            ..self.clone()
+        }
+    }
+
+    /// This context, leaving the function with `stop` after an error.
+    pub fn with_stop(self, stop: Option<String>) -> Self {
+        Self { stop, ..self }
+    }
+
+    /// This context, leaving the function with `stop` once there are
+    /// `limit` errors rather than after any.
+    pub fn with_limit(self, limit: Option<String>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// This context, adding a `message` to each error when `messages`.
+    pub fn with_messages(self, messages: bool) -> Self {
+        Self { messages, ..self }
+    }
+
+    /// This context, calling the module's `hoisted` helpers.
+    pub fn with_hoisted(self, hoisted: Rc<Hoisted>) -> Self {
+        Self { hoisted, ..self }
+    }
+
+    /// This context, for a predicate leaving with `fail` at the first
+    /// failure.
+    pub fn predicate(self, fail: &str) -> Self {
+        Self {
+            stop: Some(fail.to_string()),
+            limit: None,
+            messages: false,
+            predicate: true,
+            ..self
         }
     }
 
-    /// Push an error with the given schema path suffix.
+    /// Push an error with the given schema path suffix, about `msg`.
     /// Returns the JS statement string.
-    pub fn push_error(&self, sp_suffix: &str) -> String {
+    pub fn push_error(&self, sp_suffix: &str, msg: Message) -> String {
         let sp_expr = if sp_suffix.is_empty() {
             self.sp.clone()
         } else {
             format!("{} + \"{}\"", self.sp, sp_suffix)
         };
-        format!(
-            "{}.push({{instancePath: {}, schemaPath: {}}});",
-            self.err, self.ip, sp_expr
-        )
+        self.push(&self.ip, &sp_expr, msg)
     }
 
     /// Push an error with a custom instance path suffix and schema path suffix.
-    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str) -> String {
+    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str, msg: Message) -> String {
         let ip_expr = if ip_suffix.is_empty() {
             self.ip.clone()
         } else {
@@ -139,16 +282,81 @@ impl EmitContext {
         } else {
             format!("{} + \"{}\"", self.sp, sp_suffix)
         };
-        format!(
-            "{}.push({{instancePath: {}, schemaPath: {}}});",
-            self.err, ip_expr, sp_expr
-        )
+        self.push(&ip_expr, &sp_expr, msg)
+    }
+
+    /// Push an error with the given path expressions, then leave the
+    /// function when failing fast or at the limit. A predicate just
+    /// leaves; see [`EmitContext::fail`].
+    pub fn push(&self, ip_expr: &str, sp_expr: &str, msg: Message) -> String {
+        if self.predicate {
+            return self.fail();
+        }
+        let message = if self.messages {
+            format!(", message: {}", self.message(msg))
+        } else {
+            String::new()
+        };
+        let push = format!(
+            "{}.push({{instancePath: {ip_expr}, schemaPath: {sp_expr}{message}}});",
+            self.err
+        );
+        let Some(stop) = &self.stop else {
+            return push;
+        };
+        match &self.limit {
+            Some(limit) => format!(
+                "{{ {push} if ({}.length >= {limit}) {{ {stop} }} }}",
+                self.err
+            ),
+            None => format!("{{ {push} {stop} }}"),
+        }
+    }
+
+    /// The JS expression for the message about `msg`.
+    pub fn message(&self, msg: Message) -> String {
+        let parts = msg.parts();
+        let text = format!("\"{}\"", escape_js(&parts.text));
+        let subject = match parts.subject {
+            Subject::None => return text,
+            Subject::Value => format!("{DESCRIBE_FN}({})", self.val),
+            Subject::Member(key) => format!("{DESCRIBE_FN}({}[\"{}\"])", self.val, escape_js(key)),
+            Subject::MemberString(key) => format!("{}[\"{}\"]", self.val, escape_js(key)),
+            Subject::Variable(var) => var.to_string(),
+        };
+        if parts.end.is_empty() {
+            format!("{text} + {subject}")
+        } else {
+            format!("{text} + {subject} + \"{}\"", escape_js(parts.end))
+        }
+    }
+
+    /// The block a predicate runs on failure.
+    pub fn fail(&self) -> String {
+        format!("{{ {} }}", self.stop.as_deref().unwrap_or("return false;"))
+    }
+
+    /// After a call that may have pushed errors: leave the function when
+    /// failing fast and it did, or when it reached the limit.
+    pub fn check_stop(&self) -> Option<String> {
+        let stop = self.stop.as_ref()?;
+        Some(match &self.limit {
+            Some(limit) => format!("if ({}.length >= {limit}) {{ {stop} }}", self.err),
+            None => format!("if ({}.length > 0) {{ {stop} }}", self.err),
+        })
     }
 }
 
+/// The JS expression escaping the key in `key_var` as a JSON Pointer
+/// segment (RFC 6901), so keys with `/` or `~` give unambiguous paths.
+pub fn pointer_segment(key_var: &str) -> String {
+    format!("{key_var}.replace(/~/g, \"~0\").replace(/\\//g, \"~1\")")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::TypeKeyword;
 
     #[test]
     fn test_root_context() {
@@ -196,21 +404,24 @@ mod tests {
         let ctx = EmitContext::definition();
         let child = ctx.values_entry("k");
         assert_eq!(child.val, "v[k]");
-        assert_eq!(child.ip, "p + \"/\" + k");
+        assert_eq!(
+            child.ip,
+            "p + \"/\" + k.replace(/~/g, \"~0\").replace(/\\//g, \"~1\")"
+        );
         assert_eq!(child.sp, "sp + \"/values\"");
     }
 
     #[test]
     fn test_push_error_no_suffix() {
         let ctx = EmitContext::root();
-        let stmt = ctx.push_error("");
+        let stmt = ctx.push_error("", Message::TooDeep);
         assert_eq!(stmt, "e.push({instancePath: \"\", schemaPath: \"\"});");
     }
 
     #[test]
     fn test_push_error_with_suffix() {
         let ctx = EmitContext::root();
-        let stmt = ctx.push_error("/type");
+        let stmt = ctx.push_error("/type", Message::Type(TypeKeyword::String));
         assert_eq!(
             stmt,
             "e.push({instancePath: \"\", schemaPath: \"\" + \"/type\"});"
@@ -220,13 +431,85 @@ mod tests {
     #[test]
     fn test_push_error_at() {
         let ctx = EmitContext::definition();
-        let stmt = ctx.push_error_at("/name", "/properties/name");
+        let stmt = ctx.push_error_at(
+            "/name",
+            "/properties/name",
+            Message::MissingProperty("name"),
+        );
         assert_eq!(
             stmt,
             "e.push({instancePath: p + \"/name\", schemaPath: sp + \"/properties/name\"});"
         );
     }
 
+    #[test]
+    fn test_push_error_message() {
+        let ctx = EmitContext::definition().with_messages(true);
+        assert_eq!(
+            ctx.push_error("/type", Message::Type(TypeKeyword::Uint8)),
+            "e.push({instancePath: p, schemaPath: sp + \"/type\", message: \"expected uint8, got \" + describeValue(v)});"
+        );
+        assert_eq!(
+            ctx.push("p + \"/\" + k", "sp", Message::UnknownProperty("k")),
+            "e.push({instancePath: p + \"/\" + k, schemaPath: sp, message: \"unknown property '\" + k + \"'\"});"
+        );
+        assert_eq!(
+            ctx.message(Message::UnknownTag("kind")),
+            "\"unknown discriminator 'kind' value '\" + v[\"kind\"] + \"'\""
+        );
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        let ctx = EmitContext::definition().required_prop("a/b~c");
+        assert_eq!(ctx.ip, "p + \"/a~1b~0c\"");
+        assert_eq!(ctx.sp, "sp + \"/properties/a~1b~0c\"");
+    }
+
+    /// Keys are string literals in the generated code: whatever they hold,
+    /// each literal must decode back to the key (or its pointer segment).
+    #[test]
+    fn test_hostile_keys() {
+        let keys = [
+            "q\"uote",
+            "back\\slash",
+            "it's",
+            "new\nline",
+            "tab\tcr\r",
+            "nul\0",
+            "\u{1b}[0m",
+            "\u{2028}",
+            "${x}",
+            "`tick`",
+            "#{y}",
+            "]]",
+            "\\\"",
+            "a/b~c",
+        ];
+        let literal = |s: &str| serde_json::from_str::<String>(s).unwrap();
+        for key in keys {
+            let ctx = EmitContext::definition().required_prop(key);
+            let val = ctx
+                .val
+                .strip_prefix("v[")
+                .unwrap()
+                .strip_suffix(']')
+                .unwrap();
+            assert_eq!(literal(val), key);
+            let ip = ctx.ip.strip_prefix("p + ").unwrap();
+            assert_eq!(literal(ip), format!("/{}", escape_pointer(key)));
+            let ctx = EmitContext::definition().optional_prop(key);
+            let sp = ctx.sp.strip_prefix("sp + ").unwrap();
+            assert_eq!(
+                literal(sp),
+                format!("/optionalProperties/{}", escape_pointer(key))
+            );
+            let ctx = EmitContext::definition().discrim_variant(key);
+            let sp = ctx.sp.strip_prefix("sp + ").unwrap();
+            assert_eq!(literal(sp), format!("/mapping/{}", escape_pointer(key)));
+        }
+    }
+
     #[test]
     fn test_nested_descent() {
         // Simulate: root -> property "items" -> element [i]
@@ -0,0 +1,104 @@
+/// `applyDefaults(instance)`: returns a copy of `instance` with absent
+/// optional properties set to their `metadata.default`, following a
+/// [`DefaultsPlan`]. Objects and arrays on the way are shallow copies and
+/// each default literal is evaluated afresh, so nothing is shared with the
+/// caller's document or between calls.
+use super::nodes::def_fn_name;
+use super::writer::{escape_js, CodeWriter};
+use crate::defaults::{DefaultsPlan, Fill, Shape};
+
+const IS_OBJECT: &str = "typeof {v} === \"object\" && {v} !== null && !Array.isArray({v})";
+
+/// Emit the definition fillers and the `applyDefaults` entry point.
+pub(super) fn emit_defaults(w: &mut CodeWriter, plan: &DefaultsPlan, export: bool) {
+    for (name, fill) in &plan.definitions {
+        w.open(&format!("function {}(v)", defaults_fn_name(name)));
+        emit_value(w, fill, "v", 0);
+        w.line("return v;");
+        w.close();
+        w.line("");
+    }
+    w.line("/** A copy of `instance` with absent optional properties set to their metadata.default. */");
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    w.open(&format!("{decl} applyDefaults(instance)"));
+    w.line("let v = instance;");
+    if let Some(fill) = &plan.root {
+        emit_value(w, fill, "v", 0);
+    }
+    w.line("return v;");
+    w.close();
+}
+
+fn defaults_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "defaults_", 1)
+}
+
+fn is_object(v: &str) -> String {
+    IS_OBJECT.replace("{v}", v)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, fill: &Fill, v: &str, depth: usize) {
+    match fill {
+        Fill::Object(shape) => {
+            w.open(&format!("if ({})", is_object(v)));
+            emit_shape(w, shape, v, depth);
+            w.close();
+        }
+        Fill::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if (Array.isArray({v}))"));
+            w.line(&format!("{v} = [...{v}];"));
+            w.open(&format!("for (let {i} = 0; {i} < {v}.length; {i}++)"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Fill::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if ({})", is_object(v)));
+            w.line(&format!("{v} = {{...{v}}};"));
+            w.open(&format!("for (const {k} in {v})"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Fill::Ref(name) => w.line(&format!("{v} = {}({v});", defaults_fn_name(name))),
+        Fill::Tagged { tag, variants } => {
+            w.open(&format!("if ({})", is_object(v)));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] === \"{}\"", escape_js(tag), escape_js(value));
+                if i == 0 {
+                    w.open(&format!("if ({cond})"));
+                } else {
+                    w.close_open(&format!("else if ({cond})"));
+                }
+                emit_shape(w, shape, v, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize) {
+    w.line(&format!("{v} = {{...{v}}};"));
+    for (key, default) in &shape.defaults {
+        let key = escape_js(key);
+        // JSON text is a valid JS expression
+        let literal = serde_json::to_string(default).unwrap_or_default();
+        w.line(&format!(
+            "if (!(\"{key}\" in {v})) {v}[\"{key}\"] = {literal};"
+        ));
+    }
+    for (key, fill) in &shape.props {
+        let key = escape_js(key);
+        w.open(&format!("if (\"{key}\" in {v})"));
+        emit_value(w, fill, &format!("{v}[\"{key}\"]"), depth);
+        w.close();
+    }
+}
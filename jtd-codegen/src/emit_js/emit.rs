@@ -1,40 +1,704 @@
 /// Top-level composition: walks a CompiledSchema AST and produces
 /// a complete ES module by dispatching to the per-node emitters.
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
-use super::context::EmitContext;
+use super::coerce::emit_coerce;
+use super::context::{pointer_segment, EmitContext};
+use super::defaults::emit_defaults;
+use super::hoist::Hoisted;
+use super::is_valid::{emit_definition_predicates, emit_root_predicate};
+use super::middleware::emit_middleware;
+use super::minify::minify;
+use super::mock::emit_mock;
 use super::nodes::*;
+use super::sax::emit_sax;
+use super::strip::emit_strip;
+use super::suggest::emit_suggest_helpers;
+use super::types::TIMESTAMP_FN;
+use super::warnings::emit_warnings;
 use super::writer::{escape_js, CodeWriter};
-use crate::ast::{CompiledSchema, Node};
+use crate::ast::{CompiledSchema, Node, SchemaBundle, TypeKeyword};
+use crate::coerce::coerce_plan;
+use crate::compiler::escape_pointer;
+use crate::defaults::defaults_plan;
+use crate::deprecation::warning_plan;
+use crate::docs::function_doc;
+use crate::enums::{enum_constants, EnumConstant};
+use crate::inline::inlined;
+use crate::messages::Message;
+use crate::options::{EmitOptions, JsModuleFormat, JsRuntime};
+use crate::recursion::recursive;
+use crate::shape::shape;
+use crate::source_map;
+use crate::strip::strip_plan;
+use crate::subschemas::sub_schemas;
+use crate::suggest::suggest_sites;
 
 /// Emit a complete ES2020 module from a compiled schema.
 pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a complete module from a compiled schema with the given options.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let roots = [("validate".to_string(), &schema.root)];
+    let constants = if opts.enum_constants {
+        enum_constants(&[("", &schema.root)], &schema.definitions)
+    } else {
+        Vec::new()
+    };
+    emit_module(&roots, &schema.definitions, &constants, Some(schema), opts)
+}
+
+/// Emit one module for a bundle: each definition function is generated once
+/// and every root gets its own exported `validate<Name>` entry point,
+/// numbered from 2 when two names camel-case alike (`user-id`, `user_id`).
+/// Bundles carry no metadata, so the options that read it (`self_test`,
+/// `validate_at`, `coerce` and the like) add nothing.
+pub fn emit_bundle(bundle: &SchemaBundle, opts: &EmitOptions) -> String {
+    let names = definition_entry_names(bundle.roots.iter().map(|(name, _)| name), &[]);
+    let roots: Vec<(String, &Node)> = names
+        .into_iter()
+        .zip(&bundle.roots)
+        .map(|((entry, _), (_, node))| (entry, node))
+        .collect();
+    let constants = if opts.enum_constants {
+        let named: Vec<(&str, &Node)> = bundle
+            .roots
+            .iter()
+            .map(|(name, node)| (name.as_str(), node))
+            .collect();
+        enum_constants(&named, &bundle.definitions)
+    } else {
+        Vec::new()
+    };
+    emit_module(&roots, &bundle.definitions, &constants, None, opts)
+}
+
+/// `docs` supplies `metadata.description` for JSDoc comments; bundles
+/// carry no metadata and pass `None`.
+fn emit_module(
+    roots: &[(String, &Node)],
+    definitions: &BTreeMap<String, Node>,
+    constants: &[EnumConstant],
+    docs: Option<&CompiledSchema>,
+    opts: &EmitOptions,
+) -> String {
     let mut w = CodeWriter::new();
+    let format = opts.js_format();
+
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("js") {
+            w.line(&format!("// {line}"));
+        }
+    }
+    if opts.js_runtime != JsRuntime::Generic {
+        w.line(&format!("// Runtime: {}", opts.js_runtime.as_str()));
+    }
+    match format {
+        JsModuleFormat::Esm => {}
+        JsModuleFormat::CommonJs => {
+            w.line("\"use strict\";");
+            w.line("");
+        }
+        JsModuleFormat::Iife => w.open("(function ()"),
+        JsModuleFormat::Bare => {}
+    }
+
+    let uses_timestamp = roots.iter().any(|(_, root)| node_uses_timestamp(root))
+        || definitions.values().any(node_uses_timestamp);
+    if uses_timestamp {
+        emit_timestamp_helper(&mut w, false);
+        w.line("");
+    }
+
+    let nodes = roots
+        .iter()
+        .map(|(_, root)| *root)
+        .chain(definitions.values());
+    let mut hoisted = Hoisted::plan(nodes, opts.dedup);
+    let root_nodes = roots.iter().map(|(_, root)| *root);
+    hoisted.inline = inlined(root_nodes, definitions, opts.inline_threshold)
+        .into_iter()
+        .map(|name| (name.to_string(), definitions[name].clone()))
+        .collect();
+    let hoisted = Rc::new(hoisted);
+    if !hoisted.is_empty() {
+        hoisted.emit(&mut w);
+        w.line("");
+    }
+
+    if opts.messages {
+        emit_describe_helper(&mut w);
+        w.line("");
+    }
+
+    if opts.path_segments {
+        emit_segments_helper(&mut w);
+        w.line("");
+    }
+
+    for c in constants {
+        let items: Vec<String> = c
+            .values
+            .iter()
+            .map(|v| format!("\"{}\"", escape_js(v)))
+            .collect();
+        let decl = if format == JsModuleFormat::Esm {
+            "export const"
+        } else {
+            "const"
+        };
+        w.line(&format!(
+            "{decl} {} = Object.freeze([{}]);",
+            c.name,
+            items.join(", ")
+        ));
+    }
+    if !constants.is_empty() {
+        w.line("");
+    }
+
+    let suggestions = match docs {
+        Some(schema) if opts.suggestions => {
+            emit_suggest_helpers(&mut w, &suggest_sites(schema));
+            w.line("");
+            true
+        }
+        _ => false,
+    };
 
-    // Emit one function per definition
-    for (name, node) in &schema.definitions {
+    let recursive = recursive(definitions);
+    if !recursive.is_empty() {
+        w.line(&format!("let {DEPTH_VAR} = 0;"));
+        w.line("");
+    }
+
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    let limit_var = limit.map(|_| LIMIT_VAR.to_string());
+    let limit_param = limit.map_or(String::new(), |n| format!(", {LIMIT_VAR} = {n}"));
+
+    // Emit one function per definition; inlined ones only when the
+    // self-test or an exported definition entry point calls them
+    for (name, node) in definitions {
+        if hoisted.inline.contains_key(name) && !opts.self_test && !opts.js_export_definitions {
+            continue;
+        }
         let fn_name = def_fn_name(name);
-        w.open(&format!("function {fn_name}(v, e, p, sp)"));
-        let ctx = EmitContext::definition();
-        emit_node(&mut w, &ctx, node, None);
+        if let Some(schema) = docs {
+            emit_jsdoc(
+                &mut w,
+                &function_doc(
+                    schema,
+                    &format!("/definitions/{}", escape_pointer(name)),
+                    node,
+                ),
+            );
+        }
+        let limit_arg = limit_var
+            .as_ref()
+            .map_or(String::new(), |l| format!(", {l}"));
+        w.open(&format!("function {fn_name}(v, e, p, sp{limit_arg})"));
+        let ctx = EmitContext::definition()
+            .with_messages(opts.messages)
+            .with_hoisted(hoisted.clone());
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            emit_depth_enter(&mut w, &ctx);
+        }
+        let ctx = ctx
+            .with_stop(stops.then(|| definition_stop(guarded)))
+            .with_limit(limit_var.clone());
+        emit_node(&mut w, &ctx, node, opts, None);
+        if guarded {
+            emit_depth_exit(&mut w);
+        }
+        w.close();
+        w.line("");
+    }
+
+    // Emit one function per shared subschema; each use passes its own paths
+    for (i, node) in hoisted.shapes.iter().enumerate() {
+        let limit_arg = limit_var
+            .as_ref()
+            .map_or(String::new(), |l| format!(", {l}"));
+        w.open(&format!("function shape_{i}(v, e, p, sp{limit_arg})"));
+        let ctx = EmitContext::definition()
+            .with_messages(opts.messages)
+            .with_hoisted(hoisted.clone())
+            .with_stop(stops.then(|| definition_stop(false)))
+            .with_limit(limit_var.clone());
+        // A shared function's lines map to the first use of its subschema
+        if marks_nodes(opts) {
+            w.line(&source_map::open(node));
+        }
+        emit_node_inline(&mut w, &ctx, node, opts, None);
+        if marks_nodes(opts) {
+            w.line(source_map::close());
+        }
+        w.close();
+        w.line("");
+    }
+
+    // Emit the exported entry points
+    for (i, (fn_name, root)) in roots.iter().enumerate() {
+        if i > 0 {
+            w.line("");
+        }
+        if let Some(schema) = docs {
+            emit_jsdoc(&mut w, &function_doc(schema, "", root));
+        }
+        if format == JsModuleFormat::Esm {
+            w.open(&format!("export function {fn_name}(instance{limit_param})"));
+        } else {
+            w.open(&format!("function {fn_name}(instance{limit_param})"));
+        }
+        w.line("const e = [];");
+        let ret = return_errors(opts, suggestions, "instance");
+        let root_ctx = EmitContext::root()
+            .with_messages(opts.messages)
+            .with_hoisted(hoisted.clone())
+            .with_stop(stops.then(|| ret.clone()))
+            .with_limit(limit_var.clone());
+        emit_node(&mut w, &root_ctx, root, opts, None);
+        w.line(&ret);
         w.close();
+    }
+
+    let definition_entries = if opts.js_export_definitions {
+        let taken: Vec<&str> = roots
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(["validateAt"])
+            .collect();
+        definition_entry_names(definitions.keys(), &taken)
+    } else {
+        Vec::new()
+    };
+    for (fn_name, def) in &definition_entries {
         w.line("");
+        emit_definition_entry(&mut w, fn_name, def, opts, suggestions, format);
     }
 
-    // Emit the exported validate() entry point
-    w.open("export function validate(instance)");
+    // The predicates: validateOrder's is isValidOrder
+    let predicates: Vec<String> = if opts.is_valid {
+        roots
+            .iter()
+            .map(|(name, _)| name.replacen("validate", "isValid", 1))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if opts.is_valid {
+        w.line("");
+        emit_definition_predicates(&mut w, definitions, opts, &hoisted, false);
+        for (i, (name, (_, root))) in predicates.iter().zip(roots).enumerate() {
+            if i > 0 {
+                w.line("");
+            }
+            w.line("/** Whether `instance` is valid; stops at the first failure and builds no errors. */");
+            if format == JsModuleFormat::Esm {
+                w.open(&format!("export function {name}(instance)"));
+            } else {
+                w.open(&format!("function {name}(instance)"));
+            }
+            emit_root_predicate(&mut w, "instance", root, opts, &hoisted);
+            w.close();
+        }
+    }
+
+    let self_test = match docs {
+        Some(schema) if opts.self_test => {
+            w.line("");
+            emit_self_test(&mut w, schema, format);
+            true
+        }
+        _ => false,
+    };
+
+    let warnings = match docs {
+        Some(schema) if opts.deprecation_warnings => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_warnings(&mut w, &warning_plan(schema), export);
+            true
+        }
+        _ => false,
+    };
+
+    let validate_at = match docs {
+        Some(schema) if opts.validate_at => {
+            w.line("");
+            emit_validate_at(&mut w, schema, opts, format, &hoisted);
+            true
+        }
+        _ => false,
+    };
+
+    let coerce = match docs {
+        Some(schema) if opts.coerce => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_coerce(&mut w, &coerce_plan(schema), export);
+            true
+        }
+        _ => false,
+    };
+
+    let strip = match docs {
+        Some(schema) if opts.strip_additional => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_strip(&mut w, &strip_plan(schema), export);
+            true
+        }
+        _ => false,
+    };
+
+    let defaults = match docs {
+        Some(schema) if opts.apply_defaults => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_defaults(&mut w, &defaults_plan(schema), export);
+            true
+        }
+        _ => false,
+    };
+
+    let sax = match docs {
+        Some(schema) if opts.js_sax => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_sax(&mut w, schema, opts, export, &hoisted);
+            true
+        }
+        _ => false,
+    };
+
+    let middleware = match docs {
+        Some(_) if opts.js_middleware => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_middleware(&mut w, coerce, export);
+            true
+        }
+        _ => false,
+    };
+
+    let mock = match docs {
+        Some(schema) if opts.mock => {
+            w.line("");
+            let export = format == JsModuleFormat::Esm;
+            emit_mock(&mut w, schema, export);
+            true
+        }
+        _ => false,
+    };
+
+    let shape = match docs {
+        Some(schema) if opts.shape => {
+            w.line("");
+            w.line("/** Object keys, enum values and discriminator tags by instance path, for form and error UIs. */");
+            let decl = if format == JsModuleFormat::Esm {
+                "export const"
+            } else {
+                "const"
+            };
+            w.line(&format!("{decl} SHAPE = {};", shape(schema)));
+            true
+        }
+        _ => false,
+    };
+
+    let exports: Vec<&str> = roots
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain(definition_entries.iter().map(|(name, _)| name.as_str()))
+        .chain(predicates.iter().map(String::as_str))
+        .chain(self_test.then_some("selfTest"))
+        .chain(warnings.then_some("warnings"))
+        .chain(validate_at.then_some("validateAt"))
+        .chain(coerce.then_some("coerce"))
+        .chain(strip.then_some("strip"))
+        .chain(defaults.then_some("applyDefaults"))
+        .chain(sax.then_some("createSaxValidator"))
+        .chain(middleware.then_some("expressValidator"))
+        .chain(middleware.then_some("fastifyValidator"))
+        .chain(middleware.then_some("fastifyValidatorCompiler"))
+        .chain(mock.then_some("mock"))
+        .chain(shape.then_some("SHAPE"))
+        .chain(constants.iter().map(|c| c.name.as_str()))
+        .collect();
+    let list = exports.join(", ");
+    match format {
+        JsModuleFormat::Esm => {}
+        JsModuleFormat::CommonJs => {
+            w.line("");
+            w.line(&format!("module.exports = {{ {list} }};"));
+        }
+        JsModuleFormat::Iife => {
+            w.line("");
+            w.line(&format!("globalThis.JtdValidator = {{ {list} }};"));
+            w.close_with(")();");
+        }
+        JsModuleFormat::Bare => {}
+    }
+
+    if opts.js_minify {
+        return minify(&w.finish(), &exports);
+    }
+    w.finish()
+}
+
+/// `validateAddress(instance)` for the definition `address`: its function
+/// called from the top, with the schema path starting at the definition.
+fn emit_definition_entry(
+    w: &mut CodeWriter,
+    fn_name: &str,
+    def: &str,
+    opts: &EmitOptions,
+    suggestions: bool,
+    format: JsModuleFormat,
+) {
+    let sp = format!("/definitions/{}", escape_pointer(def));
+    w.line(&format!(
+        "/** Validate `instance` against the definition at {}. */",
+        escape_js(&sp)
+    ));
+    let limit = opts.error_limit();
+    let limit_param = limit.map_or(String::new(), |n| format!(", {LIMIT_VAR} = {n}"));
+    let limit_arg = limit.map_or(String::new(), |_| format!(", {LIMIT_VAR}"));
+    if format == JsModuleFormat::Esm {
+        w.open(&format!("export function {fn_name}(instance{limit_param})"));
+    } else {
+        w.open(&format!("function {fn_name}(instance{limit_param})"));
+    }
     w.line("const e = [];");
-    let root_ctx = EmitContext::root();
-    emit_node(&mut w, &root_ctx, &schema.root, None);
-    w.line("return e;");
+    w.line(&format!(
+        "{}(instance, e, \"\", \"{}\"{limit_arg});",
+        def_fn_name(def),
+        escape_js(&sp)
+    ));
+    w.line(&return_errors(opts, suggestions, "instance"));
     w.close();
+}
 
-    w.finish()
+/// `validateAt(pointer, value)`: validate a fragment against one sub-schema.
+/// Each branch is the same check `validate` inlines at that path, with the
+/// schema path starting at `pointer`; unknown pointers throw.
+fn emit_validate_at(
+    w: &mut CodeWriter,
+    schema: &CompiledSchema,
+    opts: &EmitOptions,
+    format: JsModuleFormat,
+    hoisted: &Rc<Hoisted>,
+) {
+    w.line("/** Validate `value` against the sub-schema at `pointer`, e.g. \"/properties/address\". */");
+    let limit = opts.error_limit();
+    let limit_param = limit.map_or(String::new(), |n| format!(", {LIMIT_VAR} = {n}"));
+    if format == JsModuleFormat::Esm {
+        w.open(&format!(
+            "export function validateAt(pointer, value{limit_param})"
+        ));
+    } else {
+        w.open(&format!("function validateAt(pointer, value{limit_param})"));
+    }
+    w.line("const e = [];");
+    let ret = return_errors(opts, opts.suggestions, "value");
+    let stops = opts.fail_fast || limit.is_some();
+    let limit_var = limit.map(|_| LIMIT_VAR.to_string());
+    let ctx = EmitContext::at_pointer()
+        .with_messages(opts.messages)
+        .with_hoisted(hoisted.clone())
+        .with_stop(stops.then(|| ret.clone()))
+        .with_limit(limit_var.clone());
+    let limit_arg = limit_var.map_or(String::new(), |l| format!(", {l}"));
+    w.open("switch (pointer)");
+    w.line("case \"\":");
+    w.line(&format!("  return validate(value{limit_arg});"));
+    for sub in sub_schemas(schema).iter().skip(1) {
+        w.open(&format!("case \"{}\":", escape_js(&sub.pointer)));
+        emit_node(w, &ctx, sub.node, opts, sub.tag);
+        w.line("break;");
+        w.close();
+    }
+    w.line("default:");
+    w.line("  throw new RangeError(\"validateAt: no sub-schema at \" + JSON.stringify(pointer));");
+    w.close();
+    w.line(&ret);
+    w.close();
+}
+
+/// The statement returning the errors `e` about `instance` from an entry
+/// point: with their suggestions, then with their paths as segments.
+fn return_errors(opts: &EmitOptions, suggestions: bool, instance: &str) -> String {
+    let mut errors = "e".to_string();
+    if suggestions {
+        errors = format!("addSuggestions({instance}, {errors})");
+    }
+    if opts.path_segments {
+        errors = format!("{SEGMENTS_FN}({errors})");
+    }
+    format!("return {errors};")
+}
+
+/// `selfTest()`: validate each `metadata.examples` entry (root and
+/// definitions) and return `{schemaPath, example, errors}` for failures.
+fn emit_self_test(w: &mut CodeWriter, schema: &CompiledSchema, format: JsModuleFormat) {
+    w.line("/** Validate the schema's metadata.examples; returns the examples that fail. */");
+    if format == JsModuleFormat::Esm {
+        w.open("export function selfTest()");
+    } else {
+        w.open("function selfTest()");
+    }
+    w.line("const failures = [];");
+    w.line("let e;");
+    for (sp, def, examples) in schema.example_sets() {
+        for (i, example) in examples.iter().enumerate() {
+            let json = serde_json::to_string(example).unwrap_or_default();
+            match def {
+                None => w.line(&format!("e = validate({json});")),
+                Some(name) => {
+                    w.line("e = [];");
+                    w.line(&format!(
+                        "{}({json}, e, \"\", \"{}\");",
+                        def_fn_name(name),
+                        escape_js(&sp)
+                    ));
+                }
+            }
+            w.line(&format!(
+                "if (e.length > 0) failures.push({{schemaPath: \"{}\", example: {i}, errors: e}});",
+                escape_js(&sp)
+            ));
+        }
+    }
+    w.line("return failures;");
+    w.close();
+}
+
+/// Write `lines` as a JSDoc block; nothing when there are none.
+pub fn emit_jsdoc(w: &mut CodeWriter, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    w.line("/**");
+    for line in lines {
+        if line.is_empty() {
+            w.line(" *");
+        } else {
+            w.line(&format!(" * {}", line.replace("*/", "*\\/")));
+        }
+    }
+    w.line(" */");
+}
+
+/// Whether `node` checks a timestamp, so its module needs
+/// [`emit_timestamp_helper`].
+pub fn node_uses_timestamp(node: &Node) -> bool {
+    match node {
+        Node::Type { type_kw } => *type_kw == TypeKeyword::Timestamp,
+        Node::Nullable { inner } => node_uses_timestamp(inner),
+        Node::Elements { schema } | Node::Values { schema } => node_uses_timestamp(schema),
+        Node::Properties {
+            required, optional, ..
+        } => required
+            .values()
+            .chain(optional.values())
+            .any(node_uses_timestamp),
+        Node::Discriminator { mapping, .. } => mapping.values().any(node_uses_timestamp),
+        _ => false,
+    }
+}
+
+/// The [`TIMESTAMP_FN`] helper, a port of [`crate::timestamp::is_rfc3339`]
+/// that checks every field itself instead of trusting `Date.parse`, whose
+/// leniency varies between engines. `typed` annotates it for TypeScript.
+pub fn emit_timestamp_helper(w: &mut CodeWriter, typed: bool) {
+    w.open(&if typed {
+        format!("function {TIMESTAMP_FN}(s: unknown): boolean")
+    } else {
+        format!("function {TIMESTAMP_FN}(s)")
+    });
+    w.line("if (typeof s !== \"string\") return false;");
+    w.line("const m = /^(\\d{4})-(\\d{2})-(\\d{2})[Tt](\\d{2}):(\\d{2}):(\\d{2})(\\.\\d+)?([Zz]|[+-](\\d{2}):(\\d{2}))$/.exec(s);");
+    w.line("if (m === null) return false;");
+    w.line("const y = +m[1], mo = +m[2], d = +m[3];");
+    w.line("if (mo < 1 || mo > 12 || d < 1) return false;");
+    w.line("const leap = y % 4 === 0 && (y % 100 !== 0 || y % 400 === 0);");
+    w.line("const dim = [31, leap ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][mo - 1];");
+    w.line("if (d > dim) return false;");
+    w.line("if (+m[4] > 23 || +m[5] > 59 || +m[6] > 60) return false;");
+    w.line("if (m[9] !== undefined && (+m[9] > 23 || +m[10] > 59)) return false;");
+    w.line("return true;");
+    w.close();
+}
+
+/// Recursively emit validation code for one AST node: a call to its
+/// shared function when `--dedup` found it recurring, else its checks.
+/// With [`EmitOptions::source_map`] the code is wrapped in markers.
+pub fn emit_node(
+    w: &mut CodeWriter,
+    ctx: &EmitContext,
+    node: &Node,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
+    if !marks_nodes(opts) {
+        return emit_node_checks(w, ctx, node, opts, discrim_tag);
+    }
+    w.line(&source_map::open(node));
+    emit_node_checks(w, ctx, node, opts, discrim_tag);
+    w.line(source_map::close());
+}
+
+/// Whether to write source-map markers, which minifying would not keep on
+/// lines of their own.
+fn marks_nodes(opts: &EmitOptions) -> bool {
+    opts.source_map && !opts.js_minify
+}
+
+/// [`emit_node`] without the source-map markers.
+fn emit_node_checks(
+    w: &mut CodeWriter,
+    ctx: &EmitContext,
+    node: &Node,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
+    // Discriminator variants skip their tag, so are never shared; shared
+    // functions record errors, which predicates do not
+    let shape = match discrim_tag {
+        None if !ctx.predicate => ctx.hoisted.shapes.index(node),
+        _ => None,
+    };
+    let Some(i) = shape else {
+        return emit_node_inline(w, ctx, node, opts, discrim_tag);
+    };
+    let limit = ctx
+        .limit
+        .as_ref()
+        .map_or(String::new(), |l| format!(", {l}"));
+    w.line(&format!(
+        "shape_{i}({}, {}, {}, {}{limit});",
+        ctx.val, ctx.err, ctx.ip, ctx.sp
+    ));
+    if let Some(check) = ctx.check_stop() {
+        w.line(&check);
+    }
 }
 
-/// Recursively emit validation code for one AST node.
+/// Emit the checks of one AST node in place.
 /// This is the dispatcher that connects all the per-node emitters.
-fn emit_node(w: &mut CodeWriter, ctx: &EmitContext, node: &Node, discrim_tag: Option<&str>) {
+fn emit_node_inline(
+    w: &mut CodeWriter,
+    ctx: &EmitContext,
+    node: &Node,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
     match node {
         Node::Empty => emit_empty(w, ctx),
 
@@ -42,24 +706,27 @@ fn emit_node(w: &mut CodeWriter, ctx: &EmitContext, node: &Node, discrim_tag: Op
 
         Node::Enum { values } => emit_enum(w, ctx, values),
 
-        Node::Ref { name } => emit_ref(w, ctx, name),
+        Node::Ref { name } => match ctx.hoisted.inline.get(name) {
+            Some(body) => emit_node(w, &ctx.inlined_ref(name), body, opts, None),
+            None => emit_ref(w, ctx, name),
+        },
 
         Node::Nullable { inner } => {
             let is_inner_empty = matches!(inner.as_ref(), Node::Empty);
             emit_nullable(w, ctx, is_inner_empty, |w, ctx| {
-                emit_node(w, ctx, inner, None);
+                emit_node(w, ctx, inner, opts, None);
             });
         }
 
         Node::Elements { schema } => {
             emit_elements(w, ctx, |w, ctx| {
-                emit_node(w, ctx, schema, None);
+                emit_node(w, ctx, schema, opts, None);
             });
         }
 
         Node::Values { schema } => {
             emit_values(w, ctx, |w, ctx| {
-                emit_node(w, ctx, schema, None);
+                emit_node(w, ctx, schema, opts, None);
             });
         }
 
@@ -68,11 +735,11 @@ fn emit_node(w: &mut CodeWriter, ctx: &EmitContext, node: &Node, discrim_tag: Op
             optional,
             additional,
         } => {
-            emit_properties_node(w, ctx, required, optional, *additional, discrim_tag);
+            emit_properties_node(w, ctx, required, optional, *additional, opts, discrim_tag);
         }
 
         Node::Discriminator { tag, mapping } => {
-            emit_discriminator_node(w, ctx, tag, mapping);
+            emit_discriminator_node(w, ctx, tag, mapping, opts);
         }
     }
 }
@@ -88,6 +755,7 @@ fn emit_properties_node(
     required: &BTreeMap<String, Node>,
     optional: &BTreeMap<String, Node>,
     additional: bool,
+    opts: &EmitOptions,
     discrim_tag: Option<&str>,
 ) {
     // Object type guard -- per test suite, schema path points to the form keyword
@@ -100,7 +768,7 @@ fn emit_properties_node(
         "if ({val} === null || typeof {val} !== \"object\" || Array.isArray({val}))",
         val = ctx.val
     ));
-    w.line(&ctx.push_error(guard_sp));
+    w.line(&ctx.push_error(guard_sp, Message::NotObject));
     w.close_open("else");
 
     // Required properties
@@ -109,11 +777,14 @@ fn emit_properties_node(
         w.line(&format!(
             "if (!(\"{escaped}\" in {})) {}",
             ctx.val,
-            ctx.push_error(&format!("/properties/{escaped}"))
+            ctx.push_error(
+                &format!("/properties/{}", escape_js(&escape_pointer(key))),
+                Message::MissingProperty(key)
+            )
         ));
         w.open("else");
         let child_ctx = ctx.required_prop(key);
-        emit_node(w, &child_ctx, node, None);
+        emit_node(w, &child_ctx, node, opts, None);
         w.close();
     }
 
@@ -122,7 +793,7 @@ fn emit_properties_node(
         let escaped = escape_js(key);
         w.open(&format!("if (\"{escaped}\" in {})", ctx.val));
         let child_ctx = ctx.optional_prop(key);
-        emit_node(w, &child_ctx, node, None);
+        emit_node(w, &child_ctx, node, opts, None);
         w.close();
     }
 
@@ -143,21 +814,18 @@ fn emit_properties_node(
         }
 
         if known.is_empty() {
-            w.line(&format!(
-                "{}.push({{instancePath: {} + \"/\" + {k_var}, schemaPath: {}}});",
-                ctx.err, ctx.ip, ctx.sp
-            ));
+            let ip = format!("{} + \"/\" + {}", ctx.ip, pointer_segment(k_var));
+            w.line(&ctx.push(&ip, &ctx.sp, Message::UnknownProperty(k_var)));
         } else {
             let conds: Vec<String> = known
                 .iter()
                 .map(|k| format!("{k_var} !== \"{}\"", escape_js(k)))
                 .collect();
+            let ip = format!("{} + \"/\" + {}", ctx.ip, pointer_segment(k_var));
             w.line(&format!(
-                "if ({}) {}.push({{instancePath: {} + \"/\" + {k_var}, schemaPath: {}}});",
+                "if ({}) {}",
                 conds.join(" && "),
-                ctx.err,
-                ctx.ip,
-                ctx.sp
+                ctx.push(&ip, &ctx.sp, Message::UnknownProperty(k_var))
             ));
         }
 
@@ -173,26 +841,28 @@ fn emit_discriminator_node(
     ctx: &EmitContext,
     tag: &str,
     mapping: &BTreeMap<String, Node>,
+    opts: &EmitOptions,
 ) {
     let escaped_tag = escape_js(tag);
+    let tag_ip = format!("/{}", escape_js(&escape_pointer(tag)));
 
     // Step 1: not an object -- per test suite, error points to "/discriminator"
     w.open(&format!(
         "if ({val} === null || typeof {val} !== \"object\" || Array.isArray({val}))",
         val = ctx.val
     ));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::NotObject));
 
     // Step 2: tag missing -- per test suite, error points to "/discriminator"
     w.close_open(&format!("else if (!(\"{escaped_tag}\" in {}))", ctx.val));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::MissingTag(tag)));
 
     // Step 3: tag not string
     w.close_open(&format!(
         "else if (typeof {}[\"{escaped_tag}\"] !== \"string\")",
         ctx.val
     ));
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/discriminator"));
+    w.line(&ctx.push_error_at(&tag_ip, "/discriminator", Message::TagNotString(tag)));
 
     // Step 4: dispatch per variant
     for (variant_key, variant_node) in mapping {
@@ -203,12 +873,12 @@ fn emit_discriminator_node(
         ));
         let variant_ctx = ctx.discrim_variant(variant_key);
         // The variant node must be a Properties node; emit with tag exclusion
-        emit_node(w, &variant_ctx, variant_node, Some(tag));
+        emit_node(w, &variant_ctx, variant_node, opts, Some(tag));
     }
 
     // Step 5: unknown tag value
     w.close_open("else");
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/mapping"));
+    w.line(&ctx.push_error_at(&tag_ip, "/mapping", Message::UnknownTag(tag)));
     w.close();
 }
 
@@ -288,4 +958,557 @@ mod tests {
         // No definition functions (schema has no definitions)
         assert!(!code.contains("function validate_"));
     }
+
+    fn emit_runtime(schema: serde_json::Value, runtime: JsRuntime) -> String {
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            js_runtime: runtime,
+            ..EmitOptions::default()
+        };
+        emit_with_options(&compiled, &opts)
+    }
+
+    #[test]
+    fn test_generic_runtime_matches_emit() {
+        let schema = json!({"type": "timestamp"});
+        let compiled = compiler::compile(&schema).unwrap();
+        assert_eq!(emit(&compiled), emit_runtime(schema, JsRuntime::Generic));
+    }
+
+    #[test]
+    fn test_node_runtime_is_commonjs() {
+        let code = emit_runtime(json!({"type": "string"}), JsRuntime::Node);
+        assert!(code.starts_with("// Runtime: Node.js\n\"use strict\";"));
+        assert!(code.contains("\nfunction validate(instance) {"));
+        assert!(!code.contains("export "));
+        assert!(code.ends_with("module.exports = { validate };\n"));
+    }
+
+    #[test]
+    fn test_browser_runtime_is_iife() {
+        let code = emit_runtime(json!({"type": "string"}), JsRuntime::Browser);
+        assert!(code.contains("(function () {\n  function validate(instance) {"));
+        assert!(code.contains("  globalThis.JtdValidator = { validate };"));
+        assert!(code.ends_with("})();\n"));
+    }
+
+    #[test]
+    fn test_module_format_overrides_runtime() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        let opts = EmitOptions {
+            js_runtime: JsRuntime::Browser,
+            js_module_format: Some(JsModuleFormat::CommonJs),
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("\"use strict\";\n\nfunction validate(instance) {"));
+        assert!(code.ends_with("module.exports = { validate };\n"));
+        let bare = EmitOptions {
+            js_module_format: Some(JsModuleFormat::Bare),
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &bare);
+        assert!(code.starts_with("function validate(instance) {"));
+        assert!(!code.contains("export "));
+        assert!(!code.contains("module.exports"));
+        assert!(!code.contains("globalThis"));
+    }
+
+    #[test]
+    fn test_export_definitions() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {
+                "address": {"properties": {"city": {"type": "string"}}},
+                "zip": {"type": "string"}
+            },
+            "properties": {"home": {"ref": "address"}, "code": {"ref": "zip"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            js_export_definitions: true,
+            inline_threshold: 1,
+            js_module_format: Some(JsModuleFormat::CommonJs),
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(concat!(
+            "/** Validate `instance` against the definition at /definitions/address. */\n",
+            "function validateAddress(instance) {\n",
+            "  const e = [];\n",
+            "  validate_address(instance, e, \"\", \"/definitions/address\");\n",
+            "  return e;\n",
+            "}\n"
+        )));
+        // Inlined at its use, but still a function for its entry point
+        assert!(code.contains("function validate_zip(v, e, p, sp)"));
+        assert!(code.ends_with("module.exports = { validate, validateAddress, validateZip };\n"));
+    }
+
+    #[test]
+    fn test_timestamps_avoid_date_parse() {
+        let code = emit_runtime(
+            json!({"properties": {"at": {"type": "timestamp"}}}),
+            JsRuntime::Generic,
+        );
+        assert!(code.contains("export function validate(instance)"));
+        assert_eq!(code.matches("function isRfc3339(s) {").count(), 1);
+        assert!(code.contains("if (!isRfc3339(instance[\"at\"]))"));
+        assert!(!code.contains("Date.parse"));
+    }
+
+    #[test]
+    fn test_timestamp_helper_omitted_when_unused() {
+        let code = emit_runtime(json!({"type": "string"}), JsRuntime::Generic);
+        assert!(!code.contains("isRfc3339"));
+    }
+
+    fn compile_order_bundle() -> SchemaBundle {
+        let shared =
+            json!({"definitions": {"address": {"properties": {"city": {"type": "string"}}}}});
+        let schemas = vec![
+            (
+                "order".to_string(),
+                json!({"properties": {"shipTo": {"ref": "address"}}}),
+            ),
+            (
+                "customer-profile".to_string(),
+                json!({"properties": {"home": {"ref": "address"}}}),
+            ),
+        ];
+        compiler::compile_bundle(&shared, &schemas).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_shares_definition_functions() {
+        let code = emit_bundle(&compile_order_bundle(), &EmitOptions::default());
+        assert_eq!(code.matches("function validate_address(").count(), 1);
+        assert!(code.contains("export function validateOrder(instance)"));
+        assert!(code.contains("export function validateCustomerProfile(instance)"));
+        assert!(!code.contains("export function validate(instance)"));
+    }
+
+    #[test]
+    fn test_bundle_numbers_colliding_entry_points() {
+        let schemas = vec![
+            ("user-id".to_string(), json!({"type": "string"})),
+            ("user_id".to_string(), json!({"type": "uint32"})),
+        ];
+        let bundle = compiler::compile_bundle(&json!({}), &schemas).unwrap();
+        let code = emit_bundle(&bundle, &EmitOptions::default());
+        assert!(code.contains("export function validateUserId(instance)"));
+        assert!(code.contains("export function validateUserId2(instance)"));
+    }
+
+    #[test]
+    fn test_bundle_commonjs_exports_every_entry_point() {
+        let opts = EmitOptions {
+            js_runtime: JsRuntime::Node,
+            ..EmitOptions::default()
+        };
+        let code = emit_bundle(&compile_order_bundle(), &opts);
+        assert!(code.ends_with("module.exports = { validateOrder, validateCustomerProfile };\n"));
+    }
+
+    #[test]
+    fn test_enum_constants() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["OPEN", "CLOSED"]}}
+        }))
+        .unwrap();
+        assert!(!emit(&compiled).contains("STATUS_VALUES"));
+
+        let opts = EmitOptions {
+            enum_constants: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(
+            code.contains("export const STATUS_VALUES = Object.freeze([\"OPEN\", \"CLOSED\"]);")
+        );
+
+        let opts = EmitOptions {
+            js_runtime: JsRuntime::Node,
+            ..opts
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("\nconst STATUS_VALUES = "));
+        assert!(code.ends_with("module.exports = { validate, STATUS_VALUES };\n"));
+    }
+
+    #[test]
+    fn test_descriptions_become_jsdoc() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"description": "A customer"},
+            "properties": {"name": {"type": "string", "metadata": {"description": "Full name"}}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains(
+            "/**\n * A customer\n *\n * Fields:\n *   /name: Full name\n */\nexport function validate("
+        ));
+    }
+
+    #[test]
+    fn test_self_test() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"examples": ["ok"]},
+            "definitions": {"id": {"type": "string", "metadata": {"examples": [5]}}},
+            "ref": "id"
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            self_test: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function selfTest()"));
+        assert!(code.contains("e = validate(\"ok\");"));
+        assert!(code.contains("validate_id(5, e, \"\", \"/definitions/id\");"));
+        assert!(!emit(&compiled).contains("selfTest"));
+    }
+
+    #[test]
+    fn test_deprecation_warnings() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"item": {"optionalProperties": {"code": {"metadata": {"deprecated": true}}}}},
+            "properties": {"items": {"elements": {"ref": "item"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            deprecation_warnings: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function warnings(instance)"));
+        assert!(code.contains("function warn_item(v, w, p)"));
+        assert!(code.contains(
+            "w.push({instancePath: p + \"/code\", schemaPath: \"/definitions/item/optionalProperties/code\"});"
+        ));
+        assert!(!emit(&compiled).contains("warnings"));
+    }
+
+    #[test]
+    fn test_validate_at() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"address": {"properties": {"zip": {"type": "string"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            validate_at: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function validateAt(pointer, value)"));
+        assert!(code.contains("case \"/properties/address/properties/zip\": {"));
+        assert!(code.contains("schemaPath: pointer + \"/type\""));
+        assert!(!emit(&compiled).contains("validateAt"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["active", "pending"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "\"/properties/status/enum\": {key: false, candidates: [\"active\", \"pending\"]},"
+        ));
+        assert!(code.contains("\"\": {key: true, candidates: [\"status\"]},"));
+        assert!(code.contains("return addSuggestions(instance, e);"));
+        assert!(!emit(&compiled).contains("addSuggestions"));
+    }
+
+    #[test]
+    fn test_coerce() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"page": {"type": "uint32"}, "q": {"type": "string"}},
+            "optionalProperties": {"debug": {"type": "boolean"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            coerce: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function coerce(instance)"));
+        assert!(code.contains("v[\"page\"] = coerceNumber(v[\"page\"], true);"));
+        assert!(code.contains("v[\"debug\"] = coerceBoolean(v[\"debug\"]);"));
+        assert!(!code.contains("v[\"q\"]"));
+        assert!(!emit(&compiled).contains("coerce"));
+    }
+
+    #[test]
+    fn test_strip_additional() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {
+                "b": {"properties": {"c": {"properties": {}}}, "additionalProperties": true}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            strip_additional: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function strip(instance)"));
+        assert!(code.contains("if (k0 !== \"a\" && k0 !== \"b\") delete v[k0];"));
+        assert!(code.contains("v[\"b\"] = {...v[\"b\"]};"));
+        assert!(!emit(&compiled).contains("strip"));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"q": {"type": "string"}},
+            "optionalProperties": {
+                "page": {"type": "uint32", "metadata": {"default": 1}},
+                "tags": {"elements": {"type": "string"}, "metadata": {"default": ["new"]}}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            apply_defaults: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function applyDefaults(instance)"));
+        assert!(code.contains("if (!(\"page\" in v)) v[\"page\"] = 1;"));
+        assert!(code.contains("if (!(\"tags\" in v)) v[\"tags\"] = [\"new\"];"));
+        assert!(!emit(&compiled).contains("applyDefaults"));
+    }
+
+    #[test]
+    fn test_sax_states() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "elements": {"values": {"ref": "id", "nullable": true}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            js_sax: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function createSaxValidator()"));
+        // The definition's leaf check is shared through the alias
+        assert!(code.contains("function sax_0(v, e, p)"));
+        assert!(code.contains("  {k: \"alias\", nullable: true, inner: 0},"));
+        assert!(code.contains("{k: \"elements\", sp: \"\", guard: \"/elements\", child: 2}"));
+        assert!(code.contains("const SAX_ROOT = 1;"));
+        assert!(!emit(&compiled).contains("createSaxValidator"));
+    }
+
+    #[test]
+    fn test_banner() {
+        let schema = json!({"type": "string"});
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            js_runtime: JsRuntime::Node,
+            banner: Some(crate::options::Banner::new(
+                &schema,
+                vec!["--runtime".into(), "node".into()],
+            )),
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        let header: Vec<&str> = code.lines().take(5).collect();
+        assert!(header[0].starts_with("// Generator: jtd-codegen "));
+        assert!(header[1].starts_with("// Schema: sha256:"));
+        assert_eq!(header[2], "// Options: --runtime node");
+        assert_eq!(header[3], "// Runtime: Node.js");
+        assert_eq!(header[4], "\"use strict\";");
+        assert!(!emit(&compiled).contains("sha256"));
+    }
+
+    #[test]
+    fn test_middleware() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"page": {"type": "uint32"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            js_middleware: true,
+            js_runtime: JsRuntime::Node,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function expressValidator(part = \"body\")"));
+        assert!(code.contains("const value = req[part];"));
+        assert!(code.contains("if (errors.length > 0) return reply.code(400).send(errors);"));
+        assert!(code.ends_with(
+            "module.exports = { validate, expressValidator, fastifyValidator, fastifyValidatorCompiler };\n"
+        ));
+
+        let opts = EmitOptions {
+            js_middleware: true,
+            coerce: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("const value = coerce(req[part]);"));
+        assert!(!emit(&compiled).contains("expressValidator"));
+    }
+
+    #[test]
+    fn test_mock() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"head": {"ref": "node"}, "tags": {"elements": {"type": "string"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            mock: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("export function mock(seed)"));
+        assert!(code.contains("function mock_node(r, d)"));
+        assert!(code.contains("v1[\"head\"] = mock_node(r, d + 1);"));
+        assert!(!emit(&compiled).contains("mock"));
+    }
+
+    #[test]
+    fn test_shape() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["new", "done"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            shape: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(&format!(
+            "export const SHAPE = {};",
+            crate::shape::shape(&compiled)
+        )));
+        assert!(code.contains(r#""enums":[{"path":"/status""#));
+        let opts = EmitOptions {
+            js_runtime: JsRuntime::Node,
+            ..opts
+        };
+        assert!(
+            emit_with_options(&compiled, &opts).contains("module.exports = { validate, SHAPE };")
+        );
+    }
+
+    #[test]
+    fn test_recursive_definitions_are_depth_limited() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {
+                "node": {"optionalProperties": {"next": {"ref": "node"}}},
+                "id": {"type": "string"}
+            },
+            "properties": {"id": {"ref": "id"}, "head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("let refDepth = 0;\n"));
+        assert!(code.contains(
+            "function validate_node(v, e, p, sp) {\n  if (refDepth >= 256) {\n    e.push({instancePath: p, schemaPath: sp});\n    return;\n  }\n  refDepth++;\n"
+        ));
+        assert!(code.contains("  refDepth--;\n}\n"));
+        assert!(code.contains("function validate_id(v, e, p, sp) {\n  if (typeof v"));
+        assert!(
+            !emit(&compiler::compile(&json!({"type": "string"})).unwrap()).contains("refDepth")
+        );
+    }
+
+    #[test]
+    fn test_messages() {
+        let compiled = compiler::compile(&json!({
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"n": {"enum": ["{x}"]}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            messages: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function describeValue(v) {\n"));
+        assert!(code.contains(
+            "message: \"unknown discriminator 'kind' value '\" + instance[\"kind\"] + \"'\"});"
+        ));
+        assert!(code.contains(
+            "message: \"expected one of '{x}', got \" + describeValue(instance[\"n\"])});"
+        ));
+        assert!(code.contains("message: \"unknown property '\" + k + \"'\"});"));
+        assert!(!emit(&compiled).contains("message"));
+    }
+
+    #[test]
+    fn test_path_segments() {
+        let compiled = compiler::compile(&json!({"values": {"type": "string"}})).unwrap();
+        let opts = EmitOptions {
+            path_segments: true,
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function pathSegments(e) {\n"));
+        assert!(code.contains("return pathSegments(addSuggestions(instance, e));"));
+        assert!(!emit(&compiled).contains("pathSegments"));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let point = json!({"properties": {"x": {"type": "float64"}, "y": {"type": "float64"}}});
+        let compiled = compiler::compile(&json!({
+            "properties": {"from": point, "to": point}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            dedup: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function shape_0(v, e, p, sp) {\n"));
+        assert!(code.contains(
+            "shape_0(instance[\"from\"], e, \"\" + \"/from\", \"\" + \"/properties/from\");"
+        ));
+        assert_eq!(code.matches("\"/x\"").count(), 1);
+        assert!(!emit(&compiled).contains("shape_"));
+    }
+
+    #[test]
+    fn test_inline_threshold() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {
+                "id": {"type": "string"},
+                "money": {"properties": {"cents": {"type": "int32"}}}
+            },
+            "properties": {
+                "a": {"ref": "id"},
+                "b": {"ref": "id"},
+                "price": {"ref": "money"},
+                "cost": {"ref": "money"}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            inline_threshold: 1,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(!code.contains("validate_id"));
+        assert!(code.contains(
+            "if (typeof instance[\"a\"] !== \"string\") e.push({instancePath: \"\" + \"/a\", schemaPath: \"/definitions/id\" + \"/type\"});"
+        ));
+        assert_eq!(code.matches("validate_money(").count(), 3);
+        let code = emit_with_options(
+            &compiled,
+            &EmitOptions {
+                inline_threshold: 2,
+                ..EmitOptions::default()
+            },
+        );
+        assert!(!code.contains("validate_money"));
+    }
 }
@@ -0,0 +1,157 @@
+/// Module-level helpers for checks a schema repeats: an enum whose values
+/// recur becomes one `Set`, so large schemas do not repeat the same value
+/// lists at every use. With `--dedup`
+/// the subschemas that recur are shared too, as functions `shape_{i}`;
+/// with `--inline-threshold` small definitions are checked in place.
+use std::collections::BTreeMap;
+
+use super::writer::{escape_js, CodeWriter};
+use crate::ast::Node;
+use crate::dedup::Shapes;
+
+/// What the checks of one module share.
+#[derive(Debug, Default)]
+pub struct Hoisted {
+    /// Each recurring enum's sorted values; the one at `i` is the constant
+    /// `enumSet{i}`.
+    enum_sets: Vec<Vec<String>>,
+    /// The recurring subschemas, each checked by the function `shape_{i}`.
+    pub shapes: Shapes,
+    /// The definitions checked in place of a call to their function.
+    pub inline: BTreeMap<String, Node>,
+}
+
+impl Hoisted {
+    /// Plan the helpers for validators checking `nodes`. With `dedup`
+    /// recurring subschemas are shared.
+    pub fn plan<'a>(nodes: impl IntoIterator<Item = &'a Node>, dedup: bool) -> Self {
+        let nodes: Vec<&Node> = nodes.into_iter().collect();
+        let mut counts = Counts::default();
+        for node in &nodes {
+            counts.walk(node);
+        }
+        let enum_sets = counts
+            .enum_order
+            .into_iter()
+            .filter(|values| counts.enums[values] > 1)
+            .collect();
+        Self {
+            enum_sets,
+            shapes: if dedup {
+                Shapes::find(nodes)
+            } else {
+                Shapes::default()
+            },
+            inline: BTreeMap::new(),
+        }
+    }
+
+    /// No helper function or constant to emit ahead of the validators.
+    pub fn is_empty(&self) -> bool {
+        self.enum_sets.is_empty()
+    }
+
+    /// The constant holding `values`, if the enum recurs.
+    pub fn enum_set(&self, values: &[String]) -> Option<String> {
+        let key = set_key(values);
+        let i = self.enum_sets.iter().position(|values| *values == key)?;
+        Some(format!("enumSet{i}"))
+    }
+
+    /// The constants, in the order planned.
+    pub fn emit(&self, w: &mut CodeWriter) {
+        for (i, values) in self.enum_sets.iter().enumerate() {
+            let items: Vec<String> = values
+                .iter()
+                .map(|v| format!("\"{}\"", escape_js(v)))
+                .collect();
+            w.line(&format!(
+                "const enumSet{i} = new Set([{}]);",
+                items.join(",")
+            ));
+        }
+    }
+}
+
+/// Enums are the same set whatever order they list their values in.
+fn set_key(values: &[String]) -> Vec<String> {
+    let mut key = values.to_vec();
+    key.sort();
+    key.dedup();
+    key
+}
+
+#[derive(Default)]
+struct Counts {
+    enums: BTreeMap<Vec<String>, usize>,
+    /// Distinct enums in the order first met, for stable names.
+    enum_order: Vec<Vec<String>>,
+}
+
+impl Counts {
+    fn walk(&mut self, node: &Node) {
+        match node {
+            Node::Enum { values } => {
+                let key = set_key(values);
+                let count = self.enums.entry(key.clone()).or_insert(0);
+                if *count == 0 {
+                    self.enum_order.push(key);
+                }
+                *count += 1;
+            }
+            Node::Nullable { inner } => self.walk(inner),
+            Node::Elements { schema } | Node::Values { schema } => self.walk(schema),
+            Node::Properties {
+                required, optional, ..
+            } => {
+                for child in required.values().chain(optional.values()) {
+                    self.walk(child);
+                }
+            }
+            Node::Discriminator { mapping, .. } => {
+                for variant in mapping.values() {
+                    self.walk(variant);
+                }
+            }
+            Node::Empty | Node::Ref { .. } | Node::Type { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_plan() {
+        let compiled = compiler::compile(&json!({
+            "properties": {
+                "c": {"enum": ["x", "y"]},
+                "d": {"enum": ["y", "x"]},
+                "e": {"enum": ["z"]}
+            }
+        }))
+        .unwrap();
+        let hoisted = Hoisted::plan([&compiled.root], false);
+        assert_eq!(
+            hoisted.enum_set(&["x".into(), "y".into()]),
+            Some("enumSet0".into())
+        );
+        assert_eq!(hoisted.enum_set(&["z".into()]), None);
+        let mut w = CodeWriter::new();
+        hoisted.emit(&mut w);
+        let code = w.finish();
+        assert!(code.contains("const enumSet0 = new Set([\"x\",\"y\"]);"));
+    }
+
+    #[test]
+    fn test_single_uses_stay_inline() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"enum": ["x"]}, "c": {"enum": ["y"]}}
+        }))
+        .unwrap();
+        assert!(Hoisted::plan([&compiled.root], false).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+/// `isValid(instance)`: the checks `validate` makes, returning false at the
+/// first failure instead of recording errors, so callers that only need a
+/// yes or no build no paths and allocate no error objects. Definitions get
+/// predicates `isValid_<name>(v)` beside their validating functions.
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use super::context::EmitContext;
+use super::emit::emit_node;
+use super::hoist::Hoisted;
+use super::nodes::{pred_fn_name, DEPTH_VAR};
+use super::writer::CodeWriter;
+use crate::ast::Node;
+use crate::options::EmitOptions;
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+
+/// One predicate per definition the checks call, annotated for TypeScript
+/// when `typed`. Recursive ones share the validators' depth count, and
+/// treat instances nested too deeply as invalid, as `validate` does.
+pub fn emit_definition_predicates(
+    w: &mut CodeWriter,
+    definitions: &BTreeMap<String, Node>,
+    opts: &EmitOptions,
+    hoisted: &Rc<Hoisted>,
+    typed: bool,
+) {
+    let recursive = recursive(definitions);
+    let signature = if typed { "(v: any): boolean" } else { "(v)" };
+    for (name, node) in definitions {
+        if hoisted.inline.contains_key(name) {
+            continue;
+        }
+        w.open(&format!("function {}{signature}", pred_fn_name(name)));
+        let guarded = recursive.contains(name.as_str());
+        let fail = if guarded {
+            w.line(&format!(
+                "if ({DEPTH_VAR} >= {MAX_REF_DEPTH}) return false;"
+            ));
+            w.line(&format!("{DEPTH_VAR}++;"));
+            format!("{DEPTH_VAR}--; return false;")
+        } else {
+            "return false;".to_string()
+        };
+        let ctx = EmitContext::definition()
+            .with_hoisted(hoisted.clone())
+            .predicate(&fail);
+        emit_node(w, &ctx, node, opts, None);
+        if guarded {
+            w.line(&format!("{DEPTH_VAR}--;"));
+        }
+        w.line("return true;");
+        w.close();
+        w.line("");
+    }
+}
+
+/// The body of a root predicate, checking the value in `val`: the caller
+/// opens the function.
+pub fn emit_root_predicate(
+    w: &mut CodeWriter,
+    val: &str,
+    root: &Node,
+    opts: &EmitOptions,
+    hoisted: &Rc<Hoisted>,
+) {
+    let ctx = EmitContext {
+        val: val.to_string(),
+        ..EmitContext::root()
+    }
+    .with_hoisted(hoisted.clone())
+    .predicate("return false;");
+    emit_node(w, &ctx, root, opts, None);
+    w.line("return true;");
+}
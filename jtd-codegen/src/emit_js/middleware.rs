@@ -0,0 +1,73 @@
+/// Express and Fastify wrappers around `validate`, so a Node service
+/// checks a request part with one import. Each takes the part to check
+/// (`"body"`, `"query"`, `"params"`, ...) and answers an invalid request
+/// with 400 and the error array. When `coerce` is generated too, the
+/// wrappers validate its copy of the part and hand that on in place of
+/// the original, since query strings and path parameters arrive as strings.
+use super::writer::CodeWriter;
+
+/// Emit `expressValidator`, `fastifyValidator` and
+/// `fastifyValidatorCompiler`.
+pub(super) fn emit_middleware(w: &mut CodeWriter, coerce: bool, export: bool) {
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    let read = |w: &mut CodeWriter, value: &str| {
+        if coerce {
+            w.line(&format!("const value = coerce({value});"));
+        } else {
+            w.line(&format!("const value = {value};"));
+        }
+    };
+
+    w.line("/** Express middleware answering 400 with the errors when `req[part]` is invalid. */");
+    w.open(&format!("{decl} expressValidator(part = \"body\")"));
+    w.open("return function (req, res, next)");
+    read(w, "req[part]");
+    w.line("const errors = validate(value);");
+    w.line("if (errors.length > 0) return res.status(400).json(errors);");
+    replace(w, coerce, "req");
+    w.line("next();");
+    w.close_with(";");
+    w.close();
+    w.line("");
+
+    w.line("/** Fastify preValidation hook answering 400 with the errors when `request[part]` is invalid. */");
+    w.open(&format!("{decl} fastifyValidator(part = \"body\")"));
+    w.open("return async function (request, reply)");
+    read(w, "request[part]");
+    w.line("const errors = validate(value);");
+    w.line("if (errors.length > 0) return reply.code(400).send(errors);");
+    replace(w, coerce, "request");
+    w.close_with(";");
+    w.close();
+    w.line("");
+
+    w.line("/**");
+    w.line(" * Fastify validatorCompiler checking every schema part a route declares against this");
+    w.line(" * schema. Failures reach the error handler with the errors as `error.validation`.");
+    w.line(" */");
+    w.open(&format!("{decl} fastifyValidatorCompiler()"));
+    w.open("return function (data)");
+    read(w, "data");
+    w.line("const errors = validate(value);");
+    w.line("if (errors.length === 0) return { value };");
+    w.line("const error = new Error(\"request does not match the schema\");");
+    w.line("error.statusCode = 400;");
+    w.line("error.validation = errors;");
+    w.line("return { error };");
+    w.close_with(";");
+    w.close();
+}
+
+/// Hand the coerced copy on as `<req>[part]`. Express 5 and Fastify expose
+/// `query` through getters, which an own property shadows.
+fn replace(w: &mut CodeWriter, coerce: bool, req: &str) {
+    if coerce {
+        w.line(&format!(
+            "Object.defineProperty({req}, part, {{ value, writable: true, enumerable: true, configurable: true }});"
+        ));
+    }
+}
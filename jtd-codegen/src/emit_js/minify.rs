@@ -0,0 +1,550 @@
+/// `--minify`: the module rewritten for bundle size. Comments and
+/// indentation go, line breaks stay only where dropping them would change
+/// how semicolons are inserted, string concatenations such as
+/// `"" + "/kids"` fold to one literal, and the internal functions and
+/// state (`validate_<name>`, `isValid_<name>`, `shape_{i}`, `refDepth`, ...)
+/// get the shortest names the module does not already use. Exported names
+/// and the leading `//` banner are kept, so the module behaves as the
+/// readable one does.
+use std::collections::{BTreeSet, HashMap};
+
+/// Prefixes of the per-definition functions of each mode.
+const INTERNAL_PREFIXES: &[&str] = &[
+    "validate_",
+    "isValid_",
+    "coerce_",
+    "defaults_",
+    "mock_",
+    "strip_",
+    "warn_",
+];
+
+/// Prefixes of the numbered helpers: shared subschemas, SAX leaf checks
+/// and hoisted enum sets.
+const NUMBERED_PREFIXES: &[&str] = &["shape_", "sax_", "enumSet"];
+
+/// Words after which a `/` starts a regular expression, not a division.
+const REGEX_AFTER: &[&str] = &[
+    "return",
+    "typeof",
+    "case",
+    "do",
+    "else",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "instanceof",
+    "yield",
+    "await",
+];
+
+/// Two-letter words a short name must not be.
+const RESERVED_SHORT: &[&str] = &["as", "do", "if", "in", "of"];
+
+/// Punctuators, longest first so the first match is the longest.
+const PUNCTUATORS: &[&str] = &[
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "**", "<<", ">>",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Word,
+    Number,
+    /// A string or template literal.
+    Str,
+    Regex,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    /// Whether a line break separated it from the token before.
+    newline_before: bool,
+}
+
+impl Token {
+    fn is(&self, punct: &str) -> bool {
+        self.kind == Kind::Punct && self.text == punct
+    }
+}
+
+/// Minify the generated module `src`, keeping the names in `exports`.
+pub fn minify(src: &str, exports: &[&str]) -> String {
+    let (banner, rest) = split_banner(src);
+    let tokens = fold_strings(tokenize(rest));
+    let tokens = rename(tokens, exports);
+
+    let mut out = banner.to_string();
+    let mut prev: Option<&Token> = None;
+    for tok in &tokens {
+        if let Some(p) = prev {
+            if tok.newline_before && asi_sensitive(p, tok) {
+                out.push('\n');
+            } else if needs_space(p, tok) {
+                out.push(' ');
+            }
+        }
+        out.push_str(&tok.text);
+        prev = Some(tok);
+    }
+    if !tokens.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// The leading `//` lines, kept as they are, and the code after them.
+fn split_banner(src: &str) -> (&str, &str) {
+    let mut end = 0;
+    for line in src.split_inclusive('\n') {
+        if !line.starts_with("//") {
+            break;
+        }
+        end += line.len();
+    }
+    src.split_at(end)
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+fn tokenize(src: &str) -> Vec<Token> {
+    let bytes = src.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut newline = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        let kind = match b {
+            b'\n' | b'\r' => {
+                newline = true;
+                i += 1;
+                continue;
+            }
+            b' ' | b'\t' => {
+                i += 1;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    newline |= bytes[i] == b'\n';
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'"' | b'\'' | b'`' => {
+                i = skip_quoted(bytes, i + 1, b);
+                Kind::Str
+            }
+            b'/' if regex_allowed(tokens.last()) => {
+                i = skip_regex(bytes, i + 1);
+                while i < bytes.len() && is_word_byte(bytes[i]) {
+                    i += 1;
+                }
+                Kind::Regex
+            }
+            b'0'..=b'9' => {
+                i = skip_number(bytes, i);
+                Kind::Number
+            }
+            b'.' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                i = skip_number(bytes, i);
+                Kind::Number
+            }
+            b if is_word_byte(b) => {
+                while i < bytes.len() && is_word_byte(bytes[i]) {
+                    i += 1;
+                }
+                Kind::Word
+            }
+            _ => {
+                let rest = &src[i..];
+                i += PUNCTUATORS
+                    .iter()
+                    .find(|p| rest.starts_with(**p))
+                    .map_or(1, |p| p.len());
+                Kind::Punct
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: src[start..i].to_string(),
+            newline_before: newline,
+        });
+        newline = false;
+    }
+    tokens
+}
+
+/// The index just past the literal closed by `quote`, from `i` inside it.
+fn skip_quoted(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// The index just past the closing `/` of a regular expression.
+fn skip_regex(bytes: &[u8], mut i: usize) -> usize {
+    let mut class = false;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => class = true,
+            b']' => class = false,
+            b'/' if !class => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn skip_number(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        let b = bytes[i];
+        let exponent_sign =
+            (b == b'+' || b == b'-') && matches!(bytes[i - 1], b'e' | b'E') && !is_hex(bytes, i);
+        if is_word_byte(b) || b == b'.' || exponent_sign {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+/// Whether the number ending before `i` is hexadecimal, where `e` is a digit.
+fn is_hex(bytes: &[u8], i: usize) -> bool {
+    let start = bytes[..i]
+        .iter()
+        .rposition(|b| !is_word_byte(*b) && *b != b'.')
+        .map_or(0, |p| p + 1);
+    matches!(&bytes[start..i], [b'0', b'x' | b'X', ..])
+}
+
+/// Whether a `/` after `prev` starts a regular expression.
+fn regex_allowed(prev: Option<&Token>) -> bool {
+    match prev {
+        None => true,
+        Some(t) => match t.kind {
+            Kind::Punct => !matches!(t.text.as_str(), ")" | "]" | "}"),
+            Kind::Word => REGEX_AFTER.contains(&t.text.as_str()),
+            _ => false,
+        },
+    }
+}
+
+/// Whether the line break between `prev` and `next` may end a statement,
+/// so that joining the lines could change the program.
+fn asi_sensitive(prev: &Token, next: &Token) -> bool {
+    let ends = match prev.kind {
+        Kind::Punct => matches!(prev.text.as_str(), ")" | "]" | "++" | "--"),
+        _ => true,
+    };
+    if prev.is("}") {
+        // A block ends without a semicolon; only what could continue an
+        // expression keeps the break.
+        return match next.kind {
+            Kind::Punct => matches!(
+                next.text.as_str(),
+                "(" | "[" | "+" | "-" | "++" | "--" | "/"
+            ),
+            Kind::Regex => true,
+            Kind::Str => next.text.starts_with('`'),
+            _ => false,
+        };
+    }
+    let starts = match next.kind {
+        Kind::Punct => matches!(
+            next.text.as_str(),
+            "(" | "[" | "{" | "+" | "-" | "++" | "--" | "!" | "~"
+        ),
+        _ => true,
+    };
+    ends && starts
+}
+
+/// Whether `prev` and `next` written together would read as other tokens.
+fn needs_space(prev: &Token, next: &Token) -> bool {
+    let (p, n) = (prev.text.as_bytes(), next.text.as_bytes());
+    let (last, first) = (p[p.len() - 1], n[0]);
+    (is_word_byte(last) && is_word_byte(first))
+        || (prev.kind == Kind::Number && first == b'.')
+        || (prev.kind == Kind::Regex && is_word_byte(first))
+        || (last == b'+' && first == b'+')
+        || (last == b'-' && first == b'-')
+        || (prev.kind == Kind::Punct && last == b'/' && matches!(first, b'/' | b'*'))
+}
+
+/// Fold `"a" + "b"` into `"ab"` where the operands of the `+` can be
+/// nothing but the two literals.
+fn fold_strings(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(tok) = iter.next() {
+        out.push(tok);
+        loop {
+            let n = out.len();
+            if n < 3 || !out[n - 2].is("+") {
+                break;
+            }
+            let (left, right) = (&out[n - 3], &out[n - 1]);
+            let before = n.checked_sub(4).map(|i| &out[i]);
+            let foldable = is_plain_string(left)
+                && is_plain_string(right)
+                && left.text.as_bytes()[0] == right.text.as_bytes()[0]
+                && !ends_with_nul_escape(left, right)
+                && before.is_some_and(binds_looser_before)
+                && iter.peek().is_none_or(binds_looser_after);
+            if !foldable {
+                break;
+            }
+            let right = out.pop().unwrap();
+            out.pop();
+            let left = out.last_mut().unwrap();
+            left.text.pop();
+            left.text.push_str(&right.text[1..]);
+        }
+    }
+    out
+}
+
+fn is_plain_string(t: &Token) -> bool {
+    t.kind == Kind::Str && !t.text.starts_with('`')
+}
+
+/// Whether joining would turn `"\0" + "1"` into the octal escape `"\01"`.
+fn ends_with_nul_escape(left: &Token, right: &Token) -> bool {
+    let body = &left.text.as_bytes()[..left.text.len() - 1];
+    let slashes = body
+        .iter()
+        .rev()
+        .skip(1)
+        .take_while(|b| **b == b'\\')
+        .count();
+    body.ends_with(b"0")
+        && slashes % 2 == 1
+        && right.text.as_bytes().get(1).is_some_and(u8::is_ascii_digit)
+}
+
+/// Whether the token before a `"a" + "b"` leaves `"a"` to the `+`.
+fn binds_looser_before(t: &Token) -> bool {
+    match t.kind {
+        Kind::Punct => matches!(
+            t.text.as_str(),
+            "+" | "("
+                | ","
+                | ":"
+                | "="
+                | "?"
+                | "["
+                | "{"
+                | ";"
+                | "==="
+                | "!=="
+                | "=="
+                | "!="
+                | "&&"
+                | "||"
+                | "??"
+                | "=>"
+        ),
+        Kind::Word => t.text == "return",
+        _ => false,
+    }
+}
+
+/// Whether the token after a `"a" + "b"` leaves `"b"` to the `+`.
+fn binds_looser_after(t: &Token) -> bool {
+    t.kind == Kind::Punct
+        && matches!(
+            t.text.as_str(),
+            "+" | ","
+                | ")"
+                | "]"
+                | "}"
+                | ";"
+                | ":"
+                | "?"
+                | "==="
+                | "!=="
+                | "=="
+                | "!="
+                | "&&"
+                | "||"
+                | "??"
+        )
+}
+
+fn is_internal(name: &str) -> bool {
+    name == "refDepth"
+        || INTERNAL_PREFIXES
+            .iter()
+            .any(|p| name.len() > p.len() && name.starts_with(p))
+        || NUMBERED_PREFIXES.iter().any(|p| {
+            name.strip_prefix(p)
+                .is_some_and(|i| !i.is_empty() && i.bytes().all(|b| b.is_ascii_digit()))
+        })
+}
+
+/// Give the internal names short ones, in order of first use. Names also
+/// used as properties, and the exported ones, are left alone.
+fn rename(mut tokens: Vec<Token>, exports: &[&str]) -> Vec<Token> {
+    let used: BTreeSet<&str> = tokens
+        .iter()
+        .filter(|t| t.kind == Kind::Word)
+        .map(|t| t.text.as_str())
+        .collect();
+    let properties: BTreeSet<&str> = tokens
+        .windows(2)
+        .filter(|w| w[0].is(".") || w[0].is("?."))
+        .map(|w| w[1].text.as_str())
+        .collect();
+
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut next = 0;
+    for tok in &tokens {
+        let name = tok.text.as_str();
+        if tok.kind != Kind::Word
+            || names.contains_key(name)
+            || !is_internal(name)
+            || exports.contains(&name)
+            || properties.contains(name)
+        {
+            continue;
+        }
+        let short = loop {
+            let candidate = short_name(next);
+            next += 1;
+            if !used.contains(candidate.as_str()) && !RESERVED_SHORT.contains(&candidate.as_str()) {
+                break candidate;
+            }
+        };
+        names.insert(name.to_string(), short);
+    }
+
+    for tok in &mut tokens {
+        if tok.kind == Kind::Word {
+            if let Some(short) = names.get(&tok.text) {
+                tok.text = short.clone();
+            }
+        }
+    }
+    tokens
+}
+
+/// The `n`th name of `a`..`Z`, `aa`, `ab`, ...
+fn short_name(mut n: usize) -> String {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut name = vec![FIRST[n % FIRST.len()]];
+    n /= FIRST.len();
+    while n > 0 {
+        n -= 1;
+        name.push(FIRST[n % FIRST.len()]);
+        n /= FIRST.len();
+    }
+    String::from_utf8(name).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_comments_and_indentation() {
+        let src = "/** Doc. */\nfunction f(v) {\n  // note\n  return v + 1;\n}\n";
+        assert_eq!(minify(src, &["f"]), "function f(v){return v+1;}\n");
+    }
+
+    #[test]
+    fn test_keeps_banner() {
+        let src = "// Generator: jtd-codegen\n\"use strict\";\n";
+        assert_eq!(
+            minify(src, &[]),
+            "// Generator: jtd-codegen\n\"use strict\";\n"
+        );
+    }
+
+    #[test]
+    fn test_keeps_regex_literals() {
+        let src =
+            "if (!/^[/\\]]+$/.test(k)) x = k.replace(/~/g, \"~0\").replace(/\\//g, \"~1\");\n";
+        assert_eq!(
+            minify(src, &[]),
+            "if(!/^[/\\]]+$/.test(k))x=k.replace(/~/g,\"~0\").replace(/\\//g,\"~1\");\n"
+        );
+        assert_eq!(minify("return a / b / c;\n", &[]), "return a/b/c;\n");
+    }
+
+    #[test]
+    fn test_folds_adjacent_strings() {
+        let src =
+            "e.push({instancePath: \"\" + \"/a\" + \"/b\", schemaPath: sp + \"/x\" + \"/y\"});\n";
+        assert_eq!(
+            minify(src, &[]),
+            "e.push({instancePath:\"/a/b\",schemaPath:sp+\"/x/y\"});\n"
+        );
+        // Neither operand may belong to a tighter operator
+        assert_eq!(
+            minify("x = \"a\" + \"b\".length;\n", &[]),
+            "x=\"a\"+\"b\".length;\n"
+        );
+        assert_eq!(
+            minify("x = y * \"2\" + \"3\";\n", &[]),
+            "x=y*\"2\"+\"3\";\n"
+        );
+    }
+
+    #[test]
+    fn test_renames_internal_names() {
+        let src = "let refDepth = 0;\nfunction validate_node(v) { refDepth++; }\nexport function validate(i) { validate_node(i); }\n";
+        assert_eq!(
+            minify(src, &["validate"]),
+            "let a=0;function b(v){a++;}export function validate(i){b(i);}\n"
+        );
+    }
+
+    #[test]
+    fn test_short_names_avoid_used_words() {
+        let src = "function validate_x(a, b) { return a(b); }\n";
+        assert_eq!(minify(src, &[]), "function c(a,b){return a(b);}\n");
+    }
+
+    #[test]
+    fn test_keeps_line_breaks_asi_needs() {
+        assert_eq!(minify("a = b\n(c)\n", &[]), "a=b\n(c)\n");
+        assert_eq!(minify("return\nx;\n", &[]), "return\nx;\n");
+        assert_eq!(minify("}\nelse {\n}\n", &[]), "}else{}\n");
+        assert_eq!(minify("i++\n+j;\n", &[]), "i++\n+j;\n");
+        assert_eq!(minify("a = b\n  ? c\n  : d;\n", &[]), "a=b?c:d;\n");
+    }
+
+    #[test]
+    fn test_separates_merging_tokens() {
+        assert_eq!(minify("a = b + +c - -d;\n", &[]), "a=b+ +c- -d;\n");
+        assert_eq!(minify("typeof v === \"x\";\n", &[]), "typeof v===\"x\";\n");
+        assert_eq!(minify("x = 1 .toString();\n", &[]), "x=1 .toString();\n");
+    }
+}
@@ -0,0 +1,174 @@
+/// `mock(seed)`: a random instance of the schema, built as described in
+/// [`crate::mock`] with a mulberry32 generator, so tests can fabricate
+/// valid fixtures. One `mock_<name>(r, d)` per definition; `d` is the ref
+/// depth.
+use super::nodes::def_fn_name;
+use super::writer::{escape_js, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::mock::{int_range, MAX_DEPTH, MAX_ITEMS, MAX_STRING};
+
+/// Emit the generator helpers, one function per definition and `mock`.
+pub(super) fn emit_mock(w: &mut CodeWriter, schema: &CompiledSchema, export: bool) {
+    for line in HELPERS.lines() {
+        w.line(&line.replace("{MAX_STRING}", &MAX_STRING.to_string()));
+    }
+    w.line("");
+    for (name, node) in &schema.definitions {
+        w.open(&format!("function {}(r, d)", mock_fn_name(name)));
+        let v = emit_value(w, node, &mut 0);
+        w.line(&format!("return {v};"));
+        w.close();
+        w.line("");
+    }
+    w.line("/** A random valid instance; the same seed gives the same instance. */");
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    w.open(&format!("{decl} mock(seed)"));
+    w.line("const r = mockRng(seed);");
+    w.line("const d = 0;");
+    let v = emit_value(w, &schema.root, &mut 0);
+    w.line(&format!("return {v};"));
+    w.close();
+}
+
+const HELPERS: &str = r#"// mulberry32; each call returns an integer in [lo, hi]
+function mockRng(seed) {
+  let a = seed >>> 0;
+  return function (lo, hi) {
+    a = (a + 0x6d2b79f5) >>> 0;
+    let t = a;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return lo + Math.floor((((t ^ (t >>> 14)) >>> 0) / 4294967296) * (hi - lo + 1));
+  };
+}
+
+function mockString(r) {
+  let s = "";
+  for (let n = r(0, {MAX_STRING}); n > 0; n--) s += String.fromCharCode(97 + r(0, 25));
+  return s;
+}
+
+function mockTimestamp(r) {
+  const pad = (n) => String(n).padStart(2, "0");
+  const date = r(2000, 2030) + "-" + pad(r(1, 12)) + "-" + pad(r(1, 28));
+  return date + "T" + pad(r(0, 23)) + ":" + pad(r(0, 59)) + ":" + pad(r(0, 59)) + "Z";
+}"#;
+
+fn mock_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "mock_", 1)
+}
+
+/// Emit the statements building a value for `node`; returns the expression
+/// holding it.
+fn emit_value(w: &mut CodeWriter, node: &Node, n: &mut usize) -> String {
+    match node {
+        Node::Empty => "null".to_string(),
+        Node::Ref { name } => format!("{}(r, d + 1)", mock_fn_name(name)),
+        Node::Type { type_kw } => match (type_kw, int_range(*type_kw)) {
+            (_, Some((lo, hi))) => format!("r({lo}, {hi})"),
+            (TypeKeyword::Boolean, _) => "r(0, 1) === 1".to_string(),
+            (TypeKeyword::String, _) => "mockString(r)".to_string(),
+            (TypeKeyword::Timestamp, _) => "mockTimestamp(r)".to_string(),
+            _ => "r(-1000000, 1000000) / 1000".to_string(),
+        },
+        Node::Enum { values } => {
+            let items: Vec<String> = values
+                .iter()
+                .map(|v| format!("\"{}\"", escape_js(v)))
+                .collect();
+            format!("[{}][r(0, {})]", items.join(", "), values.len() - 1)
+        }
+        Node::Elements { schema } => {
+            let v = fresh(n);
+            w.line(&format!("const {v} = [];"));
+            w.open(&format!(
+                "if (d < {MAX_DEPTH}) for (let i = r(0, {MAX_ITEMS}); i > 0; i--)"
+            ));
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{v}.push({item});"));
+            w.close();
+            v
+        }
+        Node::Values { schema } => {
+            let v = fresh(n);
+            w.line(&format!("const {v} = {{}};"));
+            w.open(&format!(
+                "if (d < {MAX_DEPTH}) for (let i = r(0, {MAX_ITEMS}); i > 0; i--)"
+            ));
+            w.line("const k = mockString(r);");
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{v}[k] = {item};"));
+            w.close();
+            v
+        }
+        Node::Properties { .. } => {
+            let v = fresh(n);
+            w.line(&format!("const {v} = {{}};"));
+            emit_properties(w, node, &v, n);
+            v
+        }
+        Node::Discriminator { tag, mapping } => {
+            let v = fresh(n);
+            w.line(&format!("const {v} = {{}};"));
+            w.open(&format!("switch (r(0, {}))", mapping.len() - 1));
+            for (i, (value, variant)) in mapping.iter().enumerate() {
+                w.open(&format!("case {i}:"));
+                set(w, &v, tag, &format!("\"{}\"", escape_js(value)));
+                emit_properties(w, variant, &v, n);
+                w.line("break;");
+                w.close();
+            }
+            w.close();
+            v
+        }
+        Node::Nullable { inner } => {
+            let v = fresh(n);
+            w.line(&format!("let {v} = null;"));
+            w.open(&format!("if (d < {MAX_DEPTH} && r(0, 3) !== 0)"));
+            let inner = emit_value(w, inner, n);
+            w.line(&format!("{v} = {inner};"));
+            w.close();
+            v
+        }
+    }
+}
+
+/// Add the properties of the Properties node `node` to the object `v`.
+fn emit_properties(w: &mut CodeWriter, node: &Node, v: &str, n: &mut usize) {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return;
+    };
+    for (key, child) in required {
+        let value = emit_value(w, child, n);
+        set(w, v, key, &value);
+    }
+    for (key, child) in optional {
+        w.open(&format!("if (d < {MAX_DEPTH} && r(0, 1) === 1)"));
+        let value = emit_value(w, child, n);
+        set(w, v, key, &value);
+        w.close();
+    }
+}
+
+fn set(w: &mut CodeWriter, v: &str, key: &str, value: &str) {
+    if key == "__proto__" {
+        // Plain assignment would set the prototype instead
+        w.line(&format!(
+            "Object.defineProperty({v}, \"__proto__\", {{value: {value}, enumerable: true, writable: true, configurable: true}});"
+        ));
+    } else {
+        w.line(&format!("{v}[\"{}\"] = {value};", escape_js(key)));
+    }
+}
+
+fn fresh(n: &mut usize) -> String {
+    *n += 1;
+    format!("v{n}")
+}
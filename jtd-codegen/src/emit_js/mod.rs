@@ -1,12 +1,31 @@
 /// JavaScript ESM2020 emitter — built incrementally.
+mod coerce;
 mod context;
+mod defaults;
 mod emit;
+mod hoist;
+mod is_valid;
+mod middleware;
+mod minify;
+mod mock;
 mod nodes;
+mod sax;
+mod strip;
+mod suggest;
 mod types;
+mod warnings;
 mod writer;
 
 pub use context::EmitContext;
-pub use emit::emit;
-pub use nodes::{def_fn_name, emit_empty, emit_enum, emit_nullable, emit_ref, emit_type};
+pub use emit::{
+    emit, emit_bundle, emit_jsdoc, emit_node, emit_timestamp_helper, emit_with_options,
+    node_uses_timestamp,
+};
+pub use is_valid::{emit_definition_predicates, emit_root_predicate};
+pub use nodes::{
+    bundle_fn_name, def_fn_name, definition_entry_names, definition_stop, emit_depth_enter,
+    emit_depth_exit, emit_empty, emit_enum, emit_nullable, emit_ref, emit_type, DEPTH_VAR,
+    LIMIT_VAR,
+};
 pub use types::type_condition;
 pub use writer::CodeWriter;
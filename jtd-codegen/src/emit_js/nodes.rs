@@ -4,12 +4,15 @@
 ///
 /// These are the composable building blocks. Each is independently testable
 /// by feeding it a tiny AST fragment and checking the CodeWriter output.
-use super::context::EmitContext;
+use super::context::{pointer_segment, EmitContext};
 use super::types::type_condition;
 use super::writer::{escape_js, CodeWriter};
 use crate::ast::TypeKeyword;
+use crate::compiler::escape_pointer;
+use crate::messages::Message;
+use crate::recursion::MAX_REF_DEPTH;
 
-type FieldEmitter = (&'static str, &'static dyn Fn(&mut CodeWriter, &EmitContext));
+type FieldEmitter<'a> = (&'a str, &'a dyn Fn(&mut CodeWriter, &EmitContext));
 
 // ── Empty ──────────────────────────────────────────────────────────────
 
@@ -23,20 +26,26 @@ pub fn emit_empty(_w: &mut CodeWriter, _ctx: &EmitContext) {
 /// Type form: inline type check.
 pub fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
     let cond = type_condition(type_kw, &ctx.val);
-    let err_stmt = ctx.push_error("/type");
+    let err_stmt = ctx.push_error("/type", Message::Type(type_kw));
     w.line(&format!("if ({cond}) {err_stmt}"));
 }
 
 // ── Enum ───────────────────────────────────────────────────────────────
 
-/// Enum form: string type guard + set membership.
+/// Enum form: string type guard + set membership, against the module's
+/// `Set` when the values recur.
 pub fn emit_enum(w: &mut CodeWriter, ctx: &EmitContext, values: &[String]) {
+    if let Some(set) = ctx.hoisted.enum_set(values) {
+        let err_stmt = ctx.push_error("/enum", Message::Enum(values));
+        w.line(&format!("if (!{set}.has({})) {err_stmt}", ctx.val));
+        return;
+    }
     let items: Vec<String> = values
         .iter()
         .map(|v| format!("\"{}\"", escape_js(v)))
         .collect();
     let arr = items.join(",");
-    let err_stmt = ctx.push_error("/enum");
+    let err_stmt = ctx.push_error("/enum", Message::Enum(values));
     w.line(&format!(
         "if (typeof {val} !== \"string\" || ![{arr}].includes({val})) {err_stmt}",
         val = ctx.val,
@@ -45,16 +54,89 @@ pub fn emit_enum(w: &mut CodeWriter, ctx: &EmitContext, values: &[String]) {
 
 // ── Ref ────────────────────────────────────────────────────────────────
 
-/// Ref form: call the generated definition function.
+/// Ref form: call the generated definition function, or in a predicate
+/// its predicate.
 /// The schema path is always the absolute path `/definitions/<name>` regardless
 /// of call depth -- recursive refs must not accumulate path prefixes.
 pub fn emit_ref(w: &mut CodeWriter, ctx: &EmitContext, def_name: &str) {
+    if ctx.predicate {
+        let fn_name = pred_fn_name(def_name);
+        w.line(&format!("if (!{fn_name}({})) {}", ctx.val, ctx.fail()));
+        return;
+    }
     let fn_name = def_fn_name(def_name);
-    let escaped = super::writer::escape_js(def_name);
+    let escaped = escape_js(&escape_pointer(def_name));
+    let limit = ctx
+        .limit
+        .as_ref()
+        .map_or(String::new(), |l| format!(", {l}"));
     w.line(&format!(
-        "{fn_name}({}, {}, {}, \"/definitions/{escaped}\");",
+        "{fn_name}({}, {}, {}, \"/definitions/{escaped}\"{limit});",
         ctx.val, ctx.err, ctx.ip
     ));
+    if let Some(check) = ctx.check_stop() {
+        w.line(&check);
+    }
+}
+
+// ── Depth guard ────────────────────────────────────────────────────────
+
+/// Module-level count of the recursive definition calls in progress.
+pub const DEPTH_VAR: &str = "refDepth";
+
+/// Start of a recursive definition's function: from `MAX_REF_DEPTH` nested
+/// calls on, report the value at the definition's schema path instead of
+/// descending, so deep instances cannot exhaust the stack.
+pub fn emit_depth_enter(w: &mut CodeWriter, ctx: &EmitContext) {
+    w.open(&format!("if ({DEPTH_VAR} >= {MAX_REF_DEPTH})"));
+    w.line(&ctx.push_error("", Message::TooDeep));
+    w.line("return;");
+    w.close();
+    w.line(&format!("{DEPTH_VAR}++;"));
+}
+
+/// End of a recursive definition's function, after [`emit_depth_enter`].
+pub fn emit_depth_exit(w: &mut CodeWriter) {
+    w.line(&format!("{DEPTH_VAR}--;"));
+}
+
+/// Module-level helper describing a value in messages: its JSON type, or
+/// the number itself.
+pub const DESCRIBE_FN: &str = "describeValue";
+
+/// The [`DESCRIBE_FN`] helper, with `--messages`.
+pub fn emit_describe_helper(w: &mut CodeWriter) {
+    w.open(&format!("function {DESCRIBE_FN}(v)"));
+    w.line("if (typeof v === \"number\") return String(v);");
+    w.line("return v === null ? \"null\" : Array.isArray(v) ? \"array\" : typeof v;");
+    w.close();
+}
+
+/// Module-level helper turning the errors' JSON Pointers into arrays of
+/// segments before the entry points return them.
+pub const SEGMENTS_FN: &str = "pathSegments";
+
+/// The [`SEGMENTS_FN`] helper, with `--path-segments`. Other members of
+/// the errors (`message`, `suggestion`) are kept.
+pub fn emit_segments_helper(w: &mut CodeWriter) {
+    w.open(&format!("function {SEGMENTS_FN}(e)"));
+    w.line("const split = (p) => p === \"\" ? [] : p.slice(1).split(\"/\").map((s) => s.replace(/~1/g, \"/\").replace(/~0/g, \"~\"));");
+    w.line("return e.map((x) => Object.assign({}, x, {instancePath: split(x.instancePath), schemaPath: split(x.schemaPath)}));");
+    w.close();
+}
+
+/// Parameter of the entry points and definition functions holding the
+/// error limit, with `--max-errors`.
+pub const LIMIT_VAR: &str = "maxErrors";
+
+/// The early exit of a definition's function, undoing
+/// [`emit_depth_enter`] in recursive ones.
+pub fn definition_stop(guarded: bool) -> String {
+    if guarded {
+        format!("{DEPTH_VAR}--; return;")
+    } else {
+        "return;".to_string()
+    }
 }
 
 /// Sanitize a definition name into a valid JS function name.
@@ -62,7 +144,7 @@ pub fn def_fn_name(name: &str) -> String {
     let safe: String = name
         .chars()
         .map(|c| {
-            if c.is_alphanumeric() || c == '_' {
+            if c.is_ascii_alphanumeric() || c == '_' {
                 c
             } else {
                 '_'
@@ -72,6 +154,52 @@ pub fn def_fn_name(name: &str) -> String {
     format!("validate_{safe}")
 }
 
+/// Name of a definition's predicate in the `isValid` functions.
+pub fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "isValid_", 1)
+}
+
+/// Name of a bundle entry point: `order-item` becomes `validateOrderItem`.
+pub fn bundle_fn_name(name: &str) -> String {
+    let mut out = String::from("validate");
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            if upper {
+                out.extend(c.to_uppercase());
+            } else {
+                out.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    out
+}
+
+/// Names of the exported entry points for definitions, or a bundle's
+/// schemas, in order: `address` becomes `validateAddress`, numbered from 2
+/// when that is in `taken` or an earlier name's. Never `validate_...`,
+/// which the definition functions use.
+pub fn definition_entry_names<'a>(
+    definitions: impl Iterator<Item = &'a String>,
+    taken: &[&str],
+) -> Vec<(String, &'a str)> {
+    let mut names: Vec<(String, &str)> = Vec::new();
+    for def in definitions {
+        let base = bundle_fn_name(def);
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken.contains(&name.as_str()) || names.iter().any(|(used, _)| *used == name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+        names.push((name, def));
+    }
+    names
+}
+
 // ── Nullable ───────────────────────────────────────────────────────────
 
 /// Nullable modifier: emit `if (val !== null) { <inner> }`.
@@ -101,7 +229,7 @@ pub fn emit_elements(
     emit_inner: impl FnOnce(&mut CodeWriter, &EmitContext),
 ) {
     // Per test suite: type guard error points to "/elements"
-    let err_stmt = ctx.push_error("/elements");
+    let err_stmt = ctx.push_error("/elements", Message::NotArray);
     w.open(&format!("if (!Array.isArray({}))", ctx.val));
     w.line(&err_stmt);
     w.close_open("else");
@@ -127,7 +255,7 @@ pub fn emit_values(
     emit_inner: impl FnOnce(&mut CodeWriter, &EmitContext),
 ) {
     // Per test suite: type guard error points to "/values"
-    let err_stmt = ctx.push_error("/values");
+    let err_stmt = ctx.push_error("/values", Message::NotObject);
     w.open(&format!(
         "if ({val} === null || typeof {val} !== \"object\" || Array.isArray({val}))",
         val = ctx.val
@@ -156,8 +284,8 @@ pub fn emit_values(
 pub fn emit_properties(
     w: &mut CodeWriter,
     ctx: &EmitContext,
-    required: &[FieldEmitter],
-    optional: &[FieldEmitter],
+    required: &[FieldEmitter<'_>],
+    optional: &[FieldEmitter<'_>],
     additional: bool,
     discrim_tag: Option<&str>,
 ) {
@@ -171,7 +299,7 @@ pub fn emit_properties(
         "if ({val} === null || typeof {val} !== \"object\" || Array.isArray({val}))",
         val = ctx.val
     ));
-    w.line(&ctx.push_error(guard_sp));
+    w.line(&ctx.push_error(guard_sp, Message::NotObject));
     w.close_open("else");
 
     // Required properties
@@ -181,7 +309,10 @@ pub fn emit_properties(
         w.line(&format!(
             "if (!(\"{escaped}\" in {})) {}",
             ctx.val,
-            ctx.push_error(&format!("/properties/{escaped}"))
+            ctx.push_error(
+                &format!("/properties/{}", escape_js(&escape_pointer(key))),
+                Message::MissingProperty(key)
+            )
         ));
         w.open("else");
         let child_ctx = ctx.required_prop(key);
@@ -215,21 +346,18 @@ pub fn emit_properties(
         }
 
         if known.is_empty() {
-            w.line(&format!(
-                "{}.push({{instancePath: {} + \"/\" + {k_var}, schemaPath: {}}});",
-                ctx.err, ctx.ip, ctx.sp
-            ));
+            let ip = format!("{} + \"/\" + {}", ctx.ip, pointer_segment(k_var));
+            w.line(&ctx.push(&ip, &ctx.sp, Message::UnknownProperty(k_var)));
         } else {
             let conds: Vec<String> = known
                 .iter()
                 .map(|k| format!("{k_var} !== \"{}\"", escape_js(k)))
                 .collect();
+            let ip = format!("{} + \"/\" + {}", ctx.ip, pointer_segment(k_var));
             w.line(&format!(
-                "if ({}) {}.push({{instancePath: {} + \"/\" + {k_var}, schemaPath: {}}});",
+                "if ({}) {}",
                 conds.join(" && "),
-                ctx.err,
-                ctx.ip,
-                ctx.sp
+                ctx.push(&ip, &ctx.sp, Message::UnknownProperty(k_var))
             ));
         }
 
@@ -250,27 +378,28 @@ pub fn emit_discriminator(
     w: &mut CodeWriter,
     ctx: &EmitContext,
     tag: &str,
-    variants: &[FieldEmitter],
+    variants: &[FieldEmitter<'_>],
 ) {
     let escaped_tag = escape_js(tag);
+    let tag_ip = format!("/{}", escape_js(&escape_pointer(tag)));
 
     // Step 1: not an object -- error points to "/discriminator"
     w.open(&format!(
         "if ({val} === null || typeof {val} !== \"object\" || Array.isArray({val}))",
         val = ctx.val
     ));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::NotObject));
 
     // Step 2: tag missing -- error points to "/discriminator"
     w.close_open(&format!("else if (!(\"{escaped_tag}\" in {}))", ctx.val));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::MissingTag(tag)));
 
     // Step 3: tag not a string
     w.close_open(&format!(
         "else if (typeof {}[\"{escaped_tag}\"] !== \"string\")",
         ctx.val
     ));
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/discriminator"));
+    w.line(&ctx.push_error_at(&tag_ip, "/discriminator", Message::TagNotString(tag)));
 
     // Step 4: dispatch to each variant
     for &(variant_key, ref emit_variant) in variants {
@@ -285,7 +414,7 @@ pub fn emit_discriminator(
 
     // Step 5: unknown tag value
     w.close_open("else");
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/mapping"));
+    w.line(&ctx.push_error_at(&tag_ip, "/mapping", Message::UnknownTag(tag)));
     w.close();
 }
 
@@ -311,7 +440,7 @@ mod tests {
 
     #[test]
     fn test_emit_empty_produces_nothing() {
-        let code = emit_to_string(|w, ctx| emit_empty(w, ctx));
+        let code = emit_to_string(emit_empty);
         assert_eq!(code, "");
     }
 
@@ -375,6 +504,29 @@ mod tests {
         assert_eq!(def_fn_name("foo.bar"), "validate_foo_bar");
     }
 
+    #[test]
+    fn test_bundle_fn_name() {
+        assert_eq!(bundle_fn_name("order"), "validateOrder");
+        assert_eq!(bundle_fn_name("order-item"), "validateOrderItem");
+        assert_eq!(bundle_fn_name("customer_v2"), "validateCustomerV2");
+    }
+
+    #[test]
+    fn test_definition_entry_names_avoid_collisions() {
+        let defs = ["address", "Address", "at", ""].map(String::from);
+        let names = definition_entry_names(defs.iter(), &["validate", "validateAt"]);
+        let names: Vec<&str> = names.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "validateAddress",
+                "validateAddress2",
+                "validateAt2",
+                "validate2"
+            ]
+        );
+    }
+
     #[test]
     fn test_emit_nullable_wraps_inner() {
         let code = emit_to_string(|w, ctx| {
@@ -0,0 +1,351 @@
+/// `createSaxValidator()`: a validator driven by parse events (`openObject`,
+/// `key`, `closeObject`, `openArray`, `closeArray`, `value`, `end`) instead
+/// of a parsed document, for streaming parsers such as clarinet.
+///
+/// The schema becomes a table of states. Properties, values and elements
+/// forms are followed event by event, so their objects and arrays are never
+/// built; only leaf values and discriminator objects (whose tag may come
+/// last) are collected and then checked by a generated `sax_N` function.
+/// The errors are those `validate` reports, though not always in the same
+/// order.
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use super::context::EmitContext;
+use super::emit::emit_node;
+use super::hoist::Hoisted;
+use super::nodes::SEGMENTS_FN;
+use super::writer::{escape_js, CodeWriter};
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+use crate::options::EmitOptions;
+
+enum State<'a> {
+    /// Accept anything: the empty form.
+    Any,
+    /// Collect the value and hand it to `sax_N`.
+    Leaf(usize),
+    /// Follow `inner`, first accepting null when `nullable`.
+    Alias {
+        nullable: bool,
+        inner: usize,
+    },
+    Elements {
+        sp: String,
+        child: usize,
+    },
+    Values {
+        sp: String,
+        child: usize,
+    },
+    Properties {
+        sp: String,
+        required: Vec<(&'a str, usize)>,
+        optional: Vec<(&'a str, usize)>,
+        additional: bool,
+    },
+}
+
+struct Table<'a> {
+    states: Vec<Option<State<'a>>>,
+    definitions: BTreeMap<&'a str, usize>,
+    /// Schema path and node of each `sax_N` check.
+    leaves: Vec<(String, &'a Node)>,
+}
+
+impl<'a> Table<'a> {
+    fn new(schema: &'a CompiledSchema) -> (Table<'a>, usize) {
+        let mut table = Table {
+            states: Vec::new(),
+            definitions: BTreeMap::new(),
+            leaves: Vec::new(),
+        };
+        // Reserved up front so refs (including recursive ones) resolve
+        for name in schema.definitions.keys() {
+            let id = table.reserve();
+            table.definitions.insert(name, id);
+        }
+        for (name, node) in &schema.definitions {
+            let id = table.definitions[name.as_str()];
+            table.fill(id, node, format!("/definitions/{}", escape_pointer(name)));
+        }
+        let root = table.state(&schema.root, String::new());
+        (table, root)
+    }
+
+    fn reserve(&mut self) -> usize {
+        self.states.push(None);
+        self.states.len() - 1
+    }
+
+    /// The state for `node`; refs reuse their definition's.
+    fn state(&mut self, node: &'a Node, sp: String) -> usize {
+        if let Node::Ref { name } = node {
+            return self.definitions[name.as_str()];
+        }
+        let id = self.reserve();
+        self.fill(id, node, sp);
+        id
+    }
+
+    fn fill(&mut self, id: usize, node: &'a Node, sp: String) {
+        let state = match node {
+            Node::Empty => State::Any,
+            Node::Ref { name } => State::Alias {
+                nullable: false,
+                inner: self.definitions[name.as_str()],
+            },
+            Node::Nullable { inner } => State::Alias {
+                nullable: true,
+                inner: self.state(inner, sp),
+            },
+            Node::Type { .. } | Node::Enum { .. } | Node::Discriminator { .. } => {
+                self.leaves.push((sp, node));
+                State::Leaf(self.leaves.len() - 1)
+            }
+            Node::Elements { schema } => State::Elements {
+                child: self.state(schema, format!("{sp}/elements")),
+                sp,
+            },
+            Node::Values { schema } => State::Values {
+                child: self.state(schema, format!("{sp}/values")),
+                sp,
+            },
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let required = required
+                    .iter()
+                    .map(|(k, n)| {
+                        (
+                            k.as_str(),
+                            self.state(n, format!("{sp}/properties/{}", escape_pointer(k))),
+                        )
+                    })
+                    .collect();
+                let optional = optional
+                    .iter()
+                    .map(|(k, n)| {
+                        let child =
+                            self.state(n, format!("{sp}/optionalProperties/{}", escape_pointer(k)));
+                        (k.as_str(), child)
+                    })
+                    .collect();
+                State::Properties {
+                    sp,
+                    required,
+                    optional,
+                    additional: *additional,
+                }
+            }
+        };
+        self.states[id] = Some(state);
+    }
+}
+
+/// Emit the leaf checks, the state table and `createSaxValidator`.
+pub(super) fn emit_sax(
+    w: &mut CodeWriter,
+    schema: &CompiledSchema,
+    opts: &EmitOptions,
+    export: bool,
+    hoisted: &Rc<Hoisted>,
+) {
+    let (table, root) = Table::new(schema);
+    for (i, (sp, node)) in table.leaves.iter().enumerate() {
+        w.open(&format!("function sax_{i}(v, e, p)"));
+        let ctx = EmitContext::sax_leaf(sp).with_hoisted(hoisted.clone());
+        emit_node(w, &ctx, node, opts, None);
+        w.close();
+        w.line("");
+    }
+
+    w.line("const SAX_STATES = [");
+    for state in table.states.iter().flatten() {
+        w.line(&format!("  {},", state_literal(state)));
+    }
+    w.line("];");
+    w.line(&format!("const SAX_ROOT = {root};"));
+    w.line("");
+    for line in RUNTIME.lines() {
+        let line = match line.strip_prefix("function createSaxValidator") {
+            Some(rest) if export => format!("export function createSaxValidator{rest}"),
+            _ if opts.path_segments && line == "      return e;" => {
+                format!("      return {SEGMENTS_FN}(e);")
+            }
+            _ => line.to_string(),
+        };
+        w.line(&line);
+    }
+}
+
+fn state_literal(state: &State) -> String {
+    let map = |entries: &[(&str, usize)]| {
+        let items: Vec<String> = entries
+            .iter()
+            .map(|(k, id)| format!("[\"{}\", {id}]", escape_js(k)))
+            .collect();
+        format!("new Map([{}])", items.join(", "))
+    };
+    match state {
+        State::Any => "{k: \"any\"}".to_string(),
+        State::Leaf(i) => format!("{{k: \"leaf\", check: sax_{i}}}"),
+        State::Alias { nullable, inner } => {
+            format!("{{k: \"alias\", nullable: {nullable}, inner: {inner}}}")
+        }
+        State::Elements { sp, child } => format!(
+            "{{k: \"elements\", sp: \"{}\", guard: \"/elements\", child: {child}}}",
+            escape_js(sp)
+        ),
+        State::Values { sp, child } => format!(
+            "{{k: \"values\", sp: \"{}\", guard: \"/values\", child: {child}}}",
+            escape_js(sp)
+        ),
+        State::Properties {
+            sp,
+            required,
+            optional,
+            additional,
+        } => {
+            // Same guard keyword as `validate` reports for non-objects
+            let guard = if required.is_empty() {
+                "/optionalProperties"
+            } else {
+                "/properties"
+            };
+            format!(
+                "{{k: \"properties\", sp: \"{}\", guard: \"{guard}\", req: {}, opt: {}, additional: {additional}}}",
+                escape_js(sp),
+                map(required),
+                map(optional)
+            )
+        }
+    }
+}
+
+/// The event-driven walker over `SAX_STATES`. Stack frames are `a`/`o` for
+/// an array or object being followed, `b` for a value being built for a
+/// leaf check and `x` for one being skipped.
+const RUNTIME: &str = r#"/**
+ * A validator fed parse events instead of a parsed document. Call
+ * openObject(), key(name), closeObject(), openArray(), closeArray() and
+ * value(scalar) in document order, then end() for the errors.
+ */
+function createSaxValidator() {
+  const e = [];
+  const stack = [];
+  let started = false;
+  // The state for a value, or null when it is a null a nullable accepts
+  function resolve(id, isNull) {
+    let s = SAX_STATES[id];
+    while (s.k === "alias") {
+      if (s.nullable && isNull) return null;
+      s = SAX_STATES[s.inner];
+    }
+    return s;
+  }
+  // A key as a JSON Pointer segment
+  function segment(k) {
+    return k.replace(/~/g, "~0").replace(/\//g, "~1");
+  }
+  // [state id, instance path] of the next value; id -1 skips it
+  function next() {
+    const top = stack[stack.length - 1];
+    if (top === undefined) {
+      if (started) throw new Error("sax: more than one top-level value");
+      started = true;
+      return [SAX_ROOT, ""];
+    }
+    if (top.t === "a") return [top.s.child, top.p + "/" + top.i++];
+    if (top.key === null) throw new Error("sax: object value without a key");
+    const p = top.p + "/" + segment(top.key);
+    top.key = null;
+    return [top.child, p];
+  }
+  function add(top, v) {
+    if (Array.isArray(top.v)) top.v.push(v);
+    else if (top.key === "__proto__") Object.defineProperty(top.v, top.key, {value: v, enumerable: true, writable: true, configurable: true});
+    else top.v[top.key] = v;
+  }
+  function open(isArray) {
+    const top = stack[stack.length - 1];
+    if (top !== undefined && top.t === "x") {
+      stack.push({t: "x"});
+      return;
+    }
+    if (top !== undefined && top.t === "b") {
+      const v = isArray ? [] : {};
+      add(top, v);
+      stack.push({t: "b", v, key: null});
+      return;
+    }
+    const [id, p] = next();
+    const s = id < 0 ? null : resolve(id, false);
+    if (s === null || s.k === "any") {
+      stack.push({t: "x"});
+    } else if (s.k === "leaf") {
+      stack.push({t: "b", v: isArray ? [] : {}, key: null, check: s.check, p});
+    } else if (isArray === (s.k === "elements")) {
+      stack.push({t: isArray ? "a" : "o", s, p, i: 0, key: null, child: -1, seen: new Set()});
+    } else {
+      e.push({instancePath: p, schemaPath: s.sp + s.guard});
+      stack.push({t: "x"});
+    }
+  }
+  function close() {
+    const f = stack.pop();
+    if (f === undefined) throw new Error("sax: close without open");
+    if (f.t === "o" && f.s.k === "properties") {
+      for (const k of f.s.req.keys()) {
+        if (!f.seen.has(k)) e.push({instancePath: f.p, schemaPath: f.s.sp + "/properties/" + segment(k)});
+      }
+    } else if (f.t === "b" && f.check !== undefined) {
+      f.check(f.v, e, f.p);
+    }
+  }
+  return {
+    openObject() { open(false); },
+    openArray() { open(true); },
+    closeObject() { close(); },
+    closeArray() { close(); },
+    key(k) {
+      const top = stack[stack.length - 1];
+      if (top === undefined || top.t === "a") throw new Error("sax: key outside an object");
+      if (top.t === "x") return;
+      top.key = k;
+      if (top.t !== "o") return;
+      const s = top.s;
+      if (s.k === "values") {
+        top.child = s.child;
+        return;
+      }
+      top.seen.add(k);
+      let child = s.req.get(k);
+      if (child === undefined) child = s.opt.get(k);
+      if (child === undefined) {
+        if (!s.additional) e.push({instancePath: top.p + "/" + segment(k), schemaPath: s.sp});
+        child = -1;
+      }
+      top.child = child;
+    },
+    value(v) {
+      const top = stack[stack.length - 1];
+      if (top !== undefined && top.t === "x") return;
+      if (top !== undefined && top.t === "b") {
+        add(top, v);
+        return;
+      }
+      const [id, p] = next();
+      const s = id < 0 ? null : resolve(id, v === null);
+      if (s === null || s.k === "any") return;
+      if (s.k === "leaf") s.check(v, e, p);
+      else e.push({instancePath: p, schemaPath: s.sp + s.guard});
+    },
+    end() {
+      if (!started || stack.length > 0) throw new Error("sax: incomplete document");
+      return e;
+    },
+  };
+}"#;
@@ -0,0 +1,110 @@
+/// `strip(instance)`: returns a copy of `instance` without the keys that
+/// closed Properties schemas do not declare, following a [`StripPlan`].
+/// Objects and arrays on the way are shallow copies, so the caller's
+/// document is never modified.
+use super::nodes::def_fn_name;
+use super::writer::{escape_js, CodeWriter};
+use crate::strip::{Shape, Strip, StripPlan};
+
+const IS_OBJECT: &str = "typeof {v} === \"object\" && {v} !== null && !Array.isArray({v})";
+
+/// Emit the definition strippers and the `strip` entry point.
+pub(super) fn emit_strip(w: &mut CodeWriter, plan: &StripPlan, export: bool) {
+    for (name, s) in &plan.definitions {
+        w.open(&format!("function {}(v)", strip_fn_name(name)));
+        emit_value(w, s, "v", 0);
+        w.line("return v;");
+        w.close();
+        w.line("");
+    }
+    w.line("/** A copy of `instance` without the properties the schema does not declare. */");
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    w.open(&format!("{decl} strip(instance)"));
+    w.line("let v = instance;");
+    if let Some(s) = &plan.root {
+        emit_value(w, s, "v", 0);
+    }
+    w.line("return v;");
+    w.close();
+}
+
+fn strip_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "strip_", 1)
+}
+
+fn is_object(v: &str) -> String {
+    IS_OBJECT.replace("{v}", v)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, s: &Strip, v: &str, depth: usize) {
+    match s {
+        Strip::Object(shape) => {
+            w.open(&format!("if ({})", is_object(v)));
+            emit_shape(w, shape, v, depth);
+            w.close();
+        }
+        Strip::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if (Array.isArray({v}))"));
+            w.line(&format!("{v} = [...{v}];"));
+            w.open(&format!("for (let {i} = 0; {i} < {v}.length; {i}++)"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Strip::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if ({})", is_object(v)));
+            w.line(&format!("{v} = {{...{v}}};"));
+            w.open(&format!("for (const {k} in {v})"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.close();
+            w.close();
+        }
+        Strip::Ref(name) => w.line(&format!("{v} = {}({v});", strip_fn_name(name))),
+        Strip::Tagged { tag, variants } => {
+            w.open(&format!("if ({})", is_object(v)));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] === \"{}\"", escape_js(tag), escape_js(value));
+                if i == 0 {
+                    w.open(&format!("if ({cond})"));
+                } else {
+                    w.close_open(&format!("else if ({cond})"));
+                }
+                emit_shape(w, shape, v, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+/// Copy the object at `v`, drop unknown keys, then recurse into the rest.
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize) {
+    w.line(&format!("{v} = {{...{v}}};"));
+    if let Some(known) = &shape.known {
+        let k = format!("k{depth}");
+        w.open(&format!("for (const {k} in {v})"));
+        if known.is_empty() {
+            w.line(&format!("delete {v}[{k}];"));
+        } else {
+            let conds: Vec<String> = known
+                .iter()
+                .map(|key| format!("{k} !== \"{}\"", escape_js(key)))
+                .collect();
+            w.line(&format!("if ({}) delete {v}[{k}];", conds.join(" && ")));
+        }
+        w.close();
+    }
+    for (key, s) in &shape.props {
+        let key = escape_js(key);
+        w.open(&format!("if (\"{key}\" in {v})"));
+        emit_value(w, s, &format!("{v}[\"{key}\"]"), depth);
+        w.close();
+    }
+}
@@ -0,0 +1,74 @@
+/// Helpers behind the `suggestions` option: a table of allowed names per
+/// schema path and `addSuggestions(instance, e)`, which sets `suggestion` on
+/// the errors it can match. See [`crate::suggest`] for the rules.
+use super::writer::{escape_js, CodeWriter};
+use crate::suggest::SuggestSite;
+
+pub(super) fn emit_suggest_helpers(w: &mut CodeWriter, sites: &[SuggestSite]) {
+    w.open("const SUGGESTIONS =");
+    for site in sites {
+        let candidates: Vec<String> = site
+            .candidates
+            .iter()
+            .map(|c| format!("\"{}\"", escape_js(c)))
+            .collect();
+        w.line(&format!(
+            "\"{}\": {{key: {}, candidates: [{}]}},",
+            escape_js(&site.schema_path),
+            site.key,
+            candidates.join(", ")
+        ));
+    }
+    w.close_with(";");
+    w.line("");
+    w.open("function editDistance(a, b)");
+    w.line("const d = [];");
+    w.line("for (let i = 0; i <= a.length; i++) d.push([i]);");
+    w.line("for (let j = 1; j <= b.length; j++) d[0][j] = j;");
+    w.open("for (let i = 1; i <= a.length; i++)");
+    w.open("for (let j = 1; j <= b.length; j++)");
+    w.line("const cost = a[i - 1] === b[j - 1] ? 0 : 1;");
+    w.line("d[i][j] = Math.min(d[i - 1][j] + 1, d[i][j - 1] + 1, d[i - 1][j - 1] + cost);");
+    w.open("if (i > 1 && j > 1 && a[i - 1] === b[j - 2] && a[i - 2] === b[j - 1])");
+    w.line("d[i][j] = Math.min(d[i][j], d[i - 2][j - 2] + 1);");
+    w.close();
+    w.close();
+    w.close();
+    w.line("return d[a.length][b.length];");
+    w.close();
+    w.line("");
+    w.open("function didYouMean(word, candidates)");
+    w.line("let best;");
+    w.line("let bestD = Math.max(1, Math.floor(word.length / 3)) + 1;");
+    w.open("for (const c of candidates)");
+    w.line("const d = editDistance(word, c);");
+    w.open("if (d < bestD)");
+    w.line("best = c;");
+    w.line("bestD = d;");
+    w.close();
+    w.close();
+    w.line("return best;");
+    w.close();
+    w.line("");
+    w.open("function valueAt(v, path)");
+    w.open("for (const seg of path.split(\"/\").slice(1))");
+    w.line("if (v === null || typeof v !== \"object\") return undefined;");
+    w.line("v = v[seg];");
+    w.close();
+    w.line("return v;");
+    w.close();
+    w.line("");
+    w.open("function addSuggestions(instance, e)");
+    w.open("for (const err of e)");
+    w.line("const site = SUGGESTIONS[err.schemaPath];");
+    w.line("if (site === undefined) continue;");
+    w.line("const word = site.key");
+    w.line("  ? err.instancePath.slice(err.instancePath.lastIndexOf(\"/\") + 1)");
+    w.line("  : valueAt(instance, err.instancePath);");
+    w.line("if (typeof word !== \"string\") continue;");
+    w.line("const suggestion = didYouMean(word, site.candidates);");
+    w.line("if (suggestion !== undefined) err.suggestion = suggestion;");
+    w.close();
+    w.line("return e;");
+    w.close();
+}
@@ -4,6 +4,10 @@
 /// These are the inlined expressions from Section 4 of the spec.
 use crate::ast::TypeKeyword;
 
+/// The module-level RFC 3339 check, emitted by `emit_timestamp_helper`
+/// whenever a schema has timestamps.
+pub const TIMESTAMP_FN: &str = "isRfc3339";
+
 /// Returns a JS expression (as a string) that evaluates to `true` when
 /// `val` does NOT satisfy the given type keyword.
 pub fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
@@ -14,14 +18,7 @@ pub fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
         TypeKeyword::String => {
             format!("typeof {val} !== \"string\"")
         }
-        TypeKeyword::Timestamp => {
-            // RFC 3339 regex + parse check with leap-second normalization
-            format!(
-                "typeof {val} !== \"string\" || \
-                 !/^\\d{{4}}-\\d{{2}}-\\d{{2}}[Tt]\\d{{2}}:\\d{{2}}:(\\d{{2}}|60)(\\.\\d+)?([Zz]|[+-]\\d{{2}}:\\d{{2}})$/.test({val}) || \
-                 Number.isNaN(Date.parse({val}.replace(/:60/, \":59\")))"
-            )
-        }
+        TypeKeyword::Timestamp => format!("!{TIMESTAMP_FN}({val})"),
         TypeKeyword::Float32 | TypeKeyword::Float64 => {
             format!("typeof {val} !== \"number\" || !Number.isFinite({val})")
         }
@@ -100,11 +97,9 @@ mod tests {
     }
 
     #[test]
-    fn test_timestamp_has_regex() {
+    fn test_timestamp_calls_helper() {
         let c = type_condition(TypeKeyword::Timestamp, "v");
-        assert!(c.contains("typeof v !== \"string\""));
-        assert!(c.contains(".test(v)"));
-        assert!(c.contains(":60"));
+        assert_eq!(c, "!isRfc3339(v)");
     }
 
     #[test]
@@ -0,0 +1,113 @@
+/// `warnings(instance)`: reports present properties whose schema is marked
+/// `metadata.deprecated`, following a [`WarningPlan`]. Separate from
+/// `validate` so the error path is unchanged.
+use super::context::pointer_segment;
+use super::nodes::def_fn_name;
+use super::writer::{escape_js, CodeWriter};
+use crate::compiler::escape_pointer;
+use crate::deprecation::{Prop, Walk, WarningPlan};
+
+const IS_OBJECT: &str = "typeof {v} === \"object\" && {v} !== null && !Array.isArray({v})";
+
+/// Emit the definition walkers and the `warnings` entry point.
+pub(super) fn emit_warnings(w: &mut CodeWriter, plan: &WarningPlan, export: bool) {
+    for (name, walk) in &plan.definitions {
+        w.open(&format!("function {}(v, w, p)", warn_fn_name(name)));
+        emit_walk(w, walk, "v", "p", 0);
+        w.close();
+        w.line("");
+    }
+    w.line("/** Deprecated properties present in `instance`, as {instancePath, schemaPath}. */");
+    let decl = if export {
+        "export function"
+    } else {
+        "function"
+    };
+    w.open(&format!("{decl} warnings(instance)"));
+    w.line("const w = [];");
+    if let Some(walk) = &plan.root {
+        emit_walk(w, walk, "instance", "\"\"", 0);
+    }
+    w.line("return w;");
+    w.close();
+}
+
+fn warn_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "warn_", 1)
+}
+
+fn is_object(v: &str) -> String {
+    IS_OBJECT.replace("{v}", v)
+}
+
+fn emit_walk(w: &mut CodeWriter, walk: &Walk, v: &str, p: &str, depth: usize) {
+    match walk {
+        Walk::Object(props) => {
+            w.open(&format!("if ({})", is_object(v)));
+            emit_props(w, props, v, p, depth);
+            w.close();
+        }
+        Walk::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if (Array.isArray({v}))"));
+            w.open(&format!("for (let {i} = 0; {i} < {v}.length; {i}++)"));
+            emit_walk(
+                w,
+                inner,
+                &format!("{v}[{i}]"),
+                &format!("{p} + \"/\" + {i}"),
+                depth + 1,
+            );
+            w.close();
+            w.close();
+        }
+        Walk::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if ({})", is_object(v)));
+            w.open(&format!("for (const {k} in {v})"));
+            emit_walk(
+                w,
+                inner,
+                &format!("{v}[{k}]"),
+                &format!("{p} + \"/\" + {}", pointer_segment(&k)),
+                depth + 1,
+            );
+            w.close();
+            w.close();
+        }
+        Walk::Ref(name) => w.line(&format!("{}({v}, w, {p});", warn_fn_name(name))),
+        Walk::Tagged { tag, variants } => {
+            w.open(&format!("if ({})", is_object(v)));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] === \"{}\"", escape_js(tag), escape_js(value));
+                if i == 0 {
+                    w.open(&format!("if ({cond})"));
+                } else {
+                    w.close_open(&format!("else if ({cond})"));
+                }
+                emit_props(w, props, v, p, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[Prop], v: &str, p: &str, depth: usize) {
+    for prop in props {
+        let key = escape_js(&prop.key);
+        let child_v = format!("{v}[\"{key}\"]");
+        let child_p = format!("{p} + \"/{}\"", escape_js(&escape_pointer(&prop.key)));
+        w.open(&format!("if (\"{key}\" in {v})"));
+        if prop.deprecated {
+            w.line(&format!(
+                "w.push({{instancePath: {child_p}, schemaPath: \"{}\"}});",
+                escape_js(&prop.schema_path)
+            ));
+        }
+        if let Some(child) = &prop.child {
+            emit_walk(w, child, &child_v, &child_p, depth);
+        }
+        w.close();
+    }
+}
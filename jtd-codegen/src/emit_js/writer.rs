@@ -18,9 +18,12 @@ impl CodeWriter {
         }
     }
 
-    /// Write a line at the current indentation level.
+    /// Write a line at the current indentation level; blank lines get no
+    /// indentation.
     pub fn line(&mut self, text: &str) {
-        self.write_indent();
+        if !text.is_empty() {
+            self.write_indent();
+        }
         self.buf.push_str(text);
         self.buf.push('\n');
     }
@@ -40,6 +43,15 @@ impl CodeWriter {
         self.buf.push_str("}\n");
     }
 
+    /// Close a block with trailing text after the brace, e.g. `})();`.
+    pub fn close_with(&mut self, suffix: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.write_indent();
+        self.buf.push('}');
+        self.buf.push_str(suffix);
+        self.buf.push('\n');
+    }
+
     /// Close with a continuation: `} else {`, `} else if (...) {`, etc.
     pub fn close_open(&mut self, text: &str) {
         self.depth = self.depth.saturating_sub(1);
@@ -76,6 +88,12 @@ pub fn escape_js(s: &str) -> String {
             '"' => out.push_str("\\\""),
             '\n' => out.push_str("\\n"),
             '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // Other control characters, and the line separators that end a
+            // line comment, as \uXXXX
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
             _ => out.push(c),
         }
     }
@@ -113,6 +131,15 @@ mod tests {
         assert_eq!(w.finish(), "if (a) {\n  x();\n} else {\n  y();\n}\n");
     }
 
+    #[test]
+    fn test_close_with() {
+        let mut w = CodeWriter::new();
+        w.open("(function ()");
+        w.line("x();");
+        w.close_with(")();");
+        assert_eq!(w.finish(), "(function () {\n  x();\n})();\n");
+    }
+
     #[test]
     fn test_nested() {
         let mut w = CodeWriter::new();
@@ -133,5 +160,7 @@ mod tests {
         assert_eq!(escape_js("a\"b"), "a\\\"b");
         assert_eq!(escape_js("a\\b"), "a\\\\b");
         assert_eq!(escape_js("a\nb"), "a\\nb");
+        assert_eq!(escape_js("a\0b\u{7f}"), "a\\u0000b\\u007f");
+        assert_eq!(escape_js("a\u{2028}b"), "a\\u2028b");
     }
 }
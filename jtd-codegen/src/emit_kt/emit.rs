@@ -0,0 +1,638 @@
+use super::writer::{kt_string, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::emit_js::def_fn_name;
+use crate::options::EmitOptions;
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::source_map;
+
+/// Emit a Kotlin file from a compiled schema.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a Kotlin file with the given options. The banner, `kt_package`,
+/// fail-fast, `max_errors` and `is_valid` apply.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut file = File {
+        source_map: opts.source_map,
+        ..Default::default()
+    };
+    let mut funcs = CodeWriter::new();
+
+    emit_kdoc(&mut funcs, &function_doc(schema, "", &schema.root));
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    file.limit = limit.is_some();
+    let limit_param = limit.map_or(String::new(), |n| format!(", maxErrors: Int = {n}"));
+    funcs.open(&format!(
+        "fun validate(instance: JsonElement{limit_param}): List<ValidationError>"
+    ));
+    funcs.line("val e = mutableListOf<ValidationError>()");
+    file.stop = stops.then_some("return e");
+    file.node(&mut funcs, &schema.root, "instance", "\"\"", "", None);
+    funcs.line("return e");
+    funcs.close();
+
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        let sp = format!("/definitions/{}", escape_pointer(name));
+        funcs.line("");
+        emit_kdoc(&mut funcs, &function_doc(schema, &sp, node));
+        funcs.open(&format!(
+            "private fun {}(v: JsonElement, e: MutableList<ValidationError>, p: String{})",
+            def_fn_name(name),
+            if file.limit { ", maxErrors: Int" } else { "" }
+        ));
+        file.vars = 0;
+        // From MAX_REF_DEPTH nested calls on, report the value rather than
+        // descend, so deep instances cannot overflow the stack
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            funcs.line("val depth = refDepth.get()");
+            funcs.open(&format!("if (depth >= {MAX_REF_DEPTH})"));
+            funcs.line(&format!("e.add(ValidationError(p, {}))", kt_string(&sp)));
+            funcs.line("return");
+            funcs.close();
+            funcs.line("refDepth.set(depth + 1)");
+        }
+        file.stop = if !stops {
+            None
+        } else if guarded {
+            Some("refDepth.set(depth); return")
+        } else {
+            Some("return")
+        };
+        file.node(&mut funcs, node, "v", "p", &sp, None);
+        if guarded {
+            funcs.line("refDepth.set(depth)");
+        }
+        funcs.close();
+    }
+
+    if opts.is_valid {
+        emit_is_valid(&mut file, &mut funcs, schema);
+    }
+
+    let mut w = CodeWriter::new();
+    w.line("// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
+    w.line("// This code is generated from a JSON Type Definition schema.");
+    w.line("// Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("kotlin") {
+            w.line(&format!("// {line}"));
+        }
+    }
+    // Definition functions keep their JSON names, and mapping variants
+    // repeat the object check their discriminator made
+    w.line("@file:Suppress(\"FunctionName\", \"USELESS_IS_CHECK\", \"unused\")");
+    w.line("");
+    if let Some(package) = &opts.kt_package {
+        w.line(&format!("package {package}"));
+        w.line("");
+    }
+    for class in [
+        "JsonArray",
+        "JsonElement",
+        "JsonNull",
+        "JsonObject",
+        "JsonPrimitive",
+    ] {
+        w.line(&format!("import kotlinx.serialization.json.{class}"));
+    }
+    w.line("");
+    w.line("/** A validation failure, as JSON Pointers into the instance and the schema. */");
+    w.line("data class ValidationError(val instancePath: String, val schemaPath: String)");
+    w.line("");
+    for (i, values) in file.enums.iter().enumerate() {
+        let values: Vec<String> = values.iter().map(|v| kt_string(v)).collect();
+        w.line(&format!(
+            "private val ENUM_{i} = setOf({})",
+            values.join(", ")
+        ));
+    }
+    if !file.enums.is_empty() {
+        w.line("");
+    }
+    if !recursive.is_empty() {
+        // Per thread, as threads may validate concurrently
+        w.line("/** Calls of recursive definitions in progress. */");
+        w.line("private val refDepth: ThreadLocal<Int> = ThreadLocal.withInitial { 0 }");
+        w.line("");
+    }
+    let mut out = w.finish();
+    out.push_str(&funcs.finish());
+    if file.numbers {
+        out.push_str(NUMBER_HELPERS);
+    }
+    if file.timestamps {
+        out.push_str(TIMESTAMP_HELPER);
+    }
+    out
+}
+
+/// `isValid(instance)`: the checks `validate` makes, returning false at the
+/// first failure without building errors, and a predicate per definition.
+/// Recursive ones share the validators' depth count.
+fn emit_is_valid(file: &mut File, funcs: &mut CodeWriter, schema: &CompiledSchema) {
+    file.predicate = true;
+    file.limit = false;
+    funcs.line("");
+    funcs.line(
+        "/** Whether `instance` is valid; stops at the first failure and builds no errors. */",
+    );
+    funcs.open("fun isValid(instance: JsonElement): Boolean");
+    file.vars = 0;
+    file.stop = Some("return false");
+    file.node(funcs, &schema.root, "instance", "\"\"", "", None);
+    funcs.line("return true");
+    funcs.close();
+
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        funcs.line("");
+        funcs.open(&format!(
+            "private fun {}(v: JsonElement): Boolean",
+            pred_fn_name(name)
+        ));
+        file.vars = 0;
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            funcs.line("val depth = refDepth.get()");
+            funcs.line(&format!("if (depth >= {MAX_REF_DEPTH}) return false"));
+            funcs.line("refDepth.set(depth + 1)");
+            file.stop = Some("refDepth.set(depth); return false");
+        } else {
+            file.stop = Some("return false");
+        }
+        file.node(funcs, node, "v", "\"\"", "", None);
+        if guarded {
+            funcs.line("refDepth.set(depth)");
+        }
+        funcs.line("return true");
+        funcs.close();
+    }
+}
+
+/// Name of a definition's predicate in `isValid`.
+fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "isValid_", 1)
+}
+
+/// `p`, a path expression, followed by `/` and the key in `k`, with `~` as
+/// `~0` and `/` as `~1`.
+fn join_key(p: &str, k: &str) -> String {
+    format!("{p} + \"/\" + {k}.replace(\"~\", \"~0\").replace(\"/\", \"~1\")")
+}
+
+/// The value of a JSON number, and its integer range check.
+const NUMBER_HELPERS: &str = r#"
+private fun number(v: JsonElement): Double? =
+    if (v is JsonPrimitive && !v.isString && v !is JsonNull) v.content.toDoubleOrNull() else null
+
+private fun isInteger(v: JsonElement, min: Double, max: Double): Boolean {
+    val n = number(v) ?: return false
+    return n == kotlin.math.floor(n) && n >= min && n <= max
+}
+"#;
+
+/// RFC 3339 with the checks of [`crate::timestamp::is_rfc3339`]: days per
+/// month, leap years and a leap second.
+const TIMESTAMP_HELPER: &str = r#"
+private val RFC3339 = Regex("""(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))""")
+
+private fun isTimestamp(v: JsonElement): Boolean {
+    if (v !is JsonPrimitive || !v.isString) return false
+    val g = RFC3339.matchEntire(v.content)?.groupValues ?: return false
+    val year = g[1].toInt()
+    val month = g[2].toInt()
+    val day = g[3].toInt()
+    if (month !in 1..12 || g[4].toInt() > 23 || g[5].toInt() > 59 || g[6].toInt() > 60) return false
+    val leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    val days = when (month) {
+        2 -> if (leap) 29 else 28
+        4, 6, 9, 11 -> 30
+        else -> 31
+    }
+    if (day < 1 || day > days) return false
+    return g[8].isEmpty() || (g[8].toInt() <= 23 && g[9].toInt() <= 59)
+}
+"#;
+
+/// KDoc from plain doc lines.
+fn emit_kdoc(w: &mut CodeWriter, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    w.line("/**");
+    for line in lines {
+        if line.is_empty() {
+            w.line(" *");
+        } else {
+            w.line(&format!(" * {}", line.replace("*/", "*\\/")));
+        }
+    }
+    w.line(" */");
+}
+
+#[derive(Default)]
+struct File {
+    /// The values of each enum, hoisted to `ENUM_<index>` sets.
+    enums: Vec<Vec<String>>,
+    numbers: bool,
+    timestamps: bool,
+    /// Locals used so far in the current function.
+    vars: usize,
+    /// Statement leaving the current function after an error, when
+    /// failing fast.
+    stop: Option<&'static str>,
+    /// Whether functions take `maxErrors` and only leave with `stop` once
+    /// there are that many errors.
+    limit: bool,
+    /// Whether the checks are those of an `isValid` predicate: a failure
+    /// runs `stop`, which returns false, without recording an error.
+    predicate: bool,
+    /// Wrap the checks of each node in source-map markers.
+    source_map: bool,
+}
+
+impl File {
+    fn var(&mut self, prefix: &str) -> String {
+        self.vars += 1;
+        format!("{prefix}{}", self.vars)
+    }
+
+    /// [`Self::node_checks`], in source-map markers when asked for.
+    fn node(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        if !self.source_map {
+            return self.node_checks(w, node, v, p, sp, tag);
+        }
+        w.line(&source_map::open(node));
+        self.node_checks(w, node, v, p, sp, tag);
+        w.line(source_map::close());
+    }
+
+    /// Validate the `JsonElement` in local `v`, whose instance path is the
+    /// Kotlin expression `p`, against `node` at schema path `sp`. `tag` is
+    /// a discriminator tag the properties of `node` may leave undeclared.
+    fn node_checks(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        let stop = self.stop;
+        let limit = self.limit;
+        let predicate = self.predicate;
+        // One or two statements on a line of their own
+        let add = move |p: &str, sp: &str| {
+            if let (true, Some(stop)) = (predicate, stop) {
+                return stop.to_string();
+            }
+            let add = format!("e.add(ValidationError({p}, {}))", kt_string(sp));
+            match stop {
+                Some(stop) if limit => format!("{add}; if (e.size >= maxErrors) {{ {stop} }}"),
+                Some(stop) => format!("{add}; {stop}"),
+                None => add,
+            }
+        };
+        // The same as the body of an `if` or `when` branch
+        let err = move |p: &str, sp: &str| match stop {
+            Some(_) => format!("{{ {} }}", add(p, sp)),
+            None => add(p, sp),
+        };
+        match node {
+            Node::Empty => {}
+
+            Node::Type { type_kw } => {
+                let check = self.type_check(*type_kw, v);
+                w.line(&format!(
+                    "if (!({check})) {}",
+                    err(p, &format!("{sp}/type"))
+                ));
+            }
+
+            Node::Enum { values } => {
+                let index = match self.enums.iter().position(|e| e == values) {
+                    Some(index) => index,
+                    None => {
+                        self.enums.push(values.clone());
+                        self.enums.len() - 1
+                    }
+                };
+                w.line(&format!(
+                    "if ({v} !is JsonPrimitive || !{v}.isString || {v}.content !in ENUM_{index}) {}",
+                    err(p, &format!("{sp}/enum"))
+                ));
+            }
+
+            Node::Ref { name } if predicate => {
+                let stop = stop.unwrap_or("return false");
+                w.line(&format!("if (!{}({v})) {{ {stop} }}", pred_fn_name(name)));
+            }
+
+            Node::Ref { name } => {
+                let limit_arg = if limit { ", maxErrors" } else { "" };
+                w.line(&format!("{}({v}, e, {p}{limit_arg})", def_fn_name(name)));
+                match stop {
+                    Some(stop) if limit => {
+                        w.line(&format!("if (e.size >= maxErrors) {{ {stop} }}"))
+                    }
+                    Some(stop) => w.line(&format!("if (e.isNotEmpty()) {{ {stop} }}")),
+                    None => {}
+                }
+            }
+
+            Node::Nullable { inner } => {
+                if matches!(**inner, Node::Empty) {
+                    return;
+                }
+                w.open(&format!("if ({v} !is JsonNull)"));
+                self.node(w, inner, v, p, sp, tag);
+                w.close();
+            }
+
+            Node::Elements { schema: item } => {
+                let item_sp = format!("{sp}/elements");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!("if ({v} !is JsonArray) {}", err(p, &item_sp)));
+                    return;
+                }
+                w.open(&format!("if ({v} !is JsonArray)"));
+                w.line(&add(p, &item_sp));
+                w.close_open("else");
+                let (i, x) = (self.var("i"), self.var("v"));
+                if predicate {
+                    w.open(&format!("for ({x} in {v})"));
+                } else {
+                    w.open(&format!("for (({i}, {x}) in {v}.withIndex())"));
+                }
+                self.node(w, item, &x, &format!("{p} + \"/\" + {i}"), &item_sp, None);
+                w.close();
+                w.close();
+            }
+
+            Node::Values { schema: item } => {
+                let item_sp = format!("{sp}/values");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!("if ({v} !is JsonObject) {}", err(p, &item_sp)));
+                    return;
+                }
+                w.open(&format!("if ({v} !is JsonObject)"));
+                w.line(&add(p, &item_sp));
+                w.close_open("else");
+                let (k, x) = (self.var("k"), self.var("v"));
+                if predicate {
+                    w.open(&format!("for ({x} in {v}.values)"));
+                } else {
+                    w.open(&format!("for (({k}, {x}) in {v})"));
+                }
+                self.node(w, item, &x, &join_key(p, &k), &item_sp, None);
+                w.close();
+                w.close();
+            }
+
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                w.open(&format!("if ({v} !is JsonObject)"));
+                w.line(&add(p, &format!("{sp}{guard}")));
+                w.close_open("else");
+                for (key, child) in required {
+                    let child_sp = format!("{sp}/properties/{}", escape_pointer(key));
+                    if matches!(child, Node::Empty) {
+                        w.line(&format!(
+                            "if (!{v}.containsKey({})) {}",
+                            kt_string(key),
+                            err(p, &child_sp)
+                        ));
+                        continue;
+                    }
+                    let x = self.var("v");
+                    w.line(&format!("val {x} = {v}[{}]", kt_string(key)));
+                    w.open(&format!("if ({x} == null)"));
+                    w.line(&add(p, &child_sp));
+                    w.close_open("else");
+                    let child_p =
+                        format!("{p} + {}", kt_string(&format!("/{}", escape_pointer(key))));
+                    self.node(w, child, &x, &child_p, &child_sp, None);
+                    w.close();
+                }
+                for (key, child) in optional {
+                    if matches!(child, Node::Empty) {
+                        continue;
+                    }
+                    let child_sp = format!("{sp}/optionalProperties/{}", escape_pointer(key));
+                    let x = self.var("v");
+                    w.line(&format!("val {x} = {v}[{}]", kt_string(key)));
+                    w.open(&format!("if ({x} != null)"));
+                    let child_p =
+                        format!("{p} + {}", kt_string(&format!("/{}", escape_pointer(key))));
+                    self.node(w, child, &x, &child_p, &child_sp, None);
+                    w.close();
+                }
+                if !additional {
+                    let k = self.var("k");
+                    let keys = required.keys().chain(optional.keys());
+                    let checks: Vec<String> = keys
+                        .map(String::as_str)
+                        .chain(tag)
+                        .map(|key| format!("{k} != {}", kt_string(key)))
+                        .collect();
+                    let error = err(&join_key(p, &k), sp);
+                    if checks.is_empty() {
+                        w.line(&format!("for ({k} in {v}.keys) {error}"));
+                    } else {
+                        w.line(&format!(
+                            "for ({k} in {v}.keys) if ({}) {error}",
+                            checks.join(" && ")
+                        ));
+                    }
+                }
+                w.close();
+            }
+
+            Node::Discriminator { tag, mapping } => {
+                let tag_sp = format!("{sp}/discriminator");
+                let tag_p = format!("{p} + {}", kt_string(&format!("/{}", escape_pointer(tag))));
+                w.open(&format!("if ({v} !is JsonObject)"));
+                w.line(&add(p, &tag_sp));
+                w.close_open("else");
+                let t = self.var("t");
+                w.line(&format!("val {t} = {v}[{}]", kt_string(tag)));
+                w.open(&format!("if ({t} == null)"));
+                w.line(&add(p, &tag_sp));
+                w.close_open(&format!("else if ({t} !is JsonPrimitive || !{t}.isString)"));
+                w.line(&add(&tag_p, &tag_sp));
+                w.close_open("else");
+                w.open(&format!("when ({t}.content)"));
+                for (value, variant) in mapping {
+                    w.open(&format!("{} ->", kt_string(value)));
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    self.node(w, variant, v, p, &variant_sp, Some(tag));
+                    w.close();
+                }
+                w.line(&format!(
+                    "else -> {}",
+                    err(&tag_p, &format!("{sp}/mapping"))
+                ));
+                w.close();
+                w.close();
+                w.close();
+            }
+        }
+    }
+
+    /// A Kotlin expression, true when the `JsonElement` in `v` has type
+    /// `type_kw`.
+    fn type_check(&mut self, type_kw: TypeKeyword, v: &str) -> String {
+        let mut int = |min: &str, max: &str| {
+            self.numbers = true;
+            format!("isInteger({v}, {min}, {max})")
+        };
+        match type_kw {
+            TypeKeyword::Boolean => format!(
+                "{v} is JsonPrimitive && !{v}.isString && ({v}.content == \"true\" || {v}.content == \"false\")"
+            ),
+            TypeKeyword::String => format!("{v} is JsonPrimitive && {v}.isString"),
+            TypeKeyword::Timestamp => {
+                self.timestamps = true;
+                format!("isTimestamp({v})")
+            }
+            TypeKeyword::Float32 | TypeKeyword::Float64 => {
+                self.numbers = true;
+                format!("number({v}) != null")
+            }
+            TypeKeyword::Int8 => int("-128.0", "127.0"),
+            TypeKeyword::Uint8 => int("0.0", "255.0"),
+            TypeKeyword::Int16 => int("-32768.0", "32767.0"),
+            TypeKeyword::Uint16 => int("0.0", "65535.0"),
+            TypeKeyword::Int32 => int("-2147483648.0", "2147483647.0"),
+            TypeKeyword::Uint32 => int("0.0", "4294967295.0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["a", "$b"]}}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let kt = emit(&compiled);
+        assert!(kt.contains("import kotlinx.serialization.json.JsonElement\n"));
+        assert!(kt.contains(
+            " * An order.\n */\nfun validate(instance: JsonElement): List<ValidationError> {"
+        ));
+        assert!(kt.contains("private val ENUM_0 = setOf(\"a\", \"\\$b\")"));
+        assert!(kt.contains("validate_id(v1, e, \"\" + \"/id\")"));
+        assert!(kt.contains(
+            "private fun validate_id(v: JsonElement, e: MutableList<ValidationError>, p: String) {"
+        ));
+        assert!(kt.contains(
+            "if (!(v is JsonPrimitive && v.isString)) e.add(ValidationError(p, \"/definitions/id/type\"))"
+        ));
+        // Helpers only come with the types that need them
+        assert!(!kt.contains("isTimestamp"));
+        assert!(!kt.contains("package "));
+
+        let opts = EmitOptions {
+            kt_package: Some("com.example.orders".into()),
+            ..Default::default()
+        };
+        let compiled = compiler::compile(&json!({"type": "timestamp"})).unwrap();
+        let kt = emit_with_options(&compiled, &opts);
+        assert!(kt.contains("\npackage com.example.orders\n"));
+        assert!(kt.contains("if (!(isTimestamp(instance))) e.add("));
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            fail_fast: true,
+            ..Default::default()
+        };
+        let kt = emit_with_options(&compiled, &opts);
+        assert!(kt.contains(
+            "if (!(v1 is JsonPrimitive && v1.isString)) { e.add(ValidationError(\"\" + \"/id\", \"/properties/id/type\")); return e }"
+        ));
+        assert!(kt.contains(
+            "e.add(ValidationError(p, \"/definitions/node/optionalProperties\")); refDepth.set(depth); return\n"
+        ));
+        assert!(kt.contains("if (e.isNotEmpty()) { refDepth.set(depth); return }"));
+    }
+
+    #[test]
+    fn test_max_errors() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            max_errors: Some(3),
+            ..Default::default()
+        };
+        let kt = emit_with_options(&compiled, &opts);
+        assert!(kt.contains("fun validate(instance: JsonElement, maxErrors: Int = 3)"));
+        assert!(kt.contains("p: String, maxErrors: Int)"));
+        assert!(kt.contains(
+            "{ e.add(ValidationError(\"\" + \"/id\", \"/properties/id/type\")); if (e.size >= maxErrors) { return e } }"
+        ));
+        assert!(kt.contains(", maxErrors)\n"));
+        assert!(kt.contains("if (e.size >= maxErrors) { refDepth.set(depth); return }"));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            is_valid: true,
+            max_errors: Some(3),
+            ..Default::default()
+        };
+        let kt = emit_with_options(&compiled, &opts);
+        assert!(kt.contains("fun isValid(instance: JsonElement): Boolean {"));
+        assert!(kt.contains("if (!(v1 is JsonPrimitive && v1.isString)) { return false }"));
+        assert!(kt.contains("private fun isValid_node(v: JsonElement): Boolean {"));
+        assert!(kt.contains("if (!isValid_node(v1)) { refDepth.set(depth); return false }"));
+        // Only the validators count errors
+        assert!(!kt.contains("isValid_node(v1, "));
+    }
+}
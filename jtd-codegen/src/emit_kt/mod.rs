@@ -0,0 +1,8 @@
+/// Kotlin emitter: validators over `kotlinx.serialization.json.JsonElement`,
+/// compiled ahead of time with the app, so Android clients check payloads
+/// without interpreting schemas at runtime. The file's only dependency is
+/// kotlinx-serialization-json.
+mod emit;
+mod writer;
+
+pub use emit::{emit, emit_with_options};
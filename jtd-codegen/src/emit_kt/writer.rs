@@ -0,0 +1,92 @@
+/// Indentation-aware string builder for emitting Kotlin.
+pub struct CodeWriter {
+    buf: String,
+    depth: usize,
+}
+
+impl Default for CodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeWriter {
+    pub fn new() -> Self {
+        Self {
+            buf: String::new(),
+            depth: 0,
+        }
+    }
+
+    /// Write a line at the current indentation level.
+    pub fn line(&mut self, text: &str) {
+        if text.is_empty() {
+            self.buf.push('\n');
+            return;
+        }
+        for _ in 0..self.depth {
+            self.buf.push_str("    ");
+        }
+        self.buf.push_str(text);
+        self.buf.push('\n');
+    }
+
+    /// Write `text {` and indent.
+    pub fn open(&mut self, text: &str) {
+        self.line(&format!("{text} {{"));
+        self.depth += 1;
+    }
+
+    /// Dedent and write `}`.
+    pub fn close(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line("}");
+    }
+
+    /// `} text {` between the arms of an `if`.
+    pub fn close_open(&mut self, text: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(&format!("}} {text} {{"));
+        self.depth += 1;
+    }
+
+    /// Consume and return the built string.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// `s` as a Kotlin string literal, quotes included. `$` is escaped so it
+/// never starts a template.
+pub fn kt_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '$' => out.push_str("\\$"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kt_string() {
+        assert_eq!(kt_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(kt_string("$x\n\u{0}é"), r#""\$x\n\u0000é""#);
+    }
+}
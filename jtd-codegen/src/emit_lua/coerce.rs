@@ -0,0 +1,129 @@
+/// `M.coerce(instance)`: converts string-typed booleans and numbers where
+/// the schema expects them, following a [`CoercePlan`]. Tables are updated in
+/// place (a deep copy would need to preserve the JSON library's array and
+/// null markers), and the converted instance is returned for chaining.
+use super::emit::def_fn_name;
+use super::writer::{escape_lua, CodeWriter};
+use crate::coerce::{Coerce, CoercePlan, Scalar};
+use crate::options::LuaDialect;
+
+/// Emit the conversion helpers, the definition coercers and the
+/// `M.coerce` entry point.
+pub(super) fn emit_coerce(w: &mut CodeWriter, plan: &CoercePlan, dialect: LuaDialect) {
+    w.open("local function coerce_boolean(x)");
+    w.line("if x == \"true\" then return true elseif x == \"false\" then return false end");
+    w.line("return x");
+    w.close("end");
+    w.line("");
+    w.line("-- JSON number syntax; tonumber alone also accepts hex, \"inf\" and spaces.");
+    w.open("local function is_json_number(s)");
+    w.line("local int, rest = s:match(\"^%-?([0-9]+)(.*)$\")");
+    w.line("if not int or (#int > 1 and int:sub(1, 1) == \"0\") then return false end");
+    w.line("local frac = rest:match(\"^%.([0-9]+)\")");
+    w.open("if frac then");
+    w.line("rest = rest:sub(#frac + 2)");
+    w.close_open("elseif rest:sub(1, 1) == \".\" then");
+    w.line("return false");
+    w.close("end");
+    w.line("return rest == \"\" or rest:match(\"^[eE][+-]?[0-9]+$\") ~= nil");
+    w.close("end");
+    w.line("");
+    w.open("local function coerce_number(x, integer)");
+    w.line("local n = x");
+    w.open("if type(x) == \"string\" then");
+    w.line("if not is_json_number(x) then return x end");
+    w.line("n = tonumber(x)");
+    w.close_open("elseif type(x) ~= \"number\" or not integer then");
+    w.line("return x");
+    w.close("end");
+    w.line("if n ~= n or n == math.huge or n == -math.huge then return x end");
+    w.open("if integer then");
+    w.line("if n ~= math.floor(n) then return x end");
+    if dialect.has_integer_subtype() {
+        w.line("return math.tointeger(n) or n");
+    } else {
+        w.line("return n");
+    }
+    w.close("end");
+    w.line("return n");
+    w.close("end");
+    w.line("");
+    // Declared up front so coercers can call ones defined after them.
+    if !plan.definitions.is_empty() {
+        let names: Vec<String> = plan.definitions.keys().map(|n| coerce_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    for (name, c) in &plan.definitions {
+        w.open(&format!("function {}(v)", coerce_fn_name(name)));
+        emit_value(w, c, "v", 0);
+        w.line("return v");
+        w.close("end");
+        w.line("");
+    }
+    w.line("--- Convert \"42\" / \"true\" style strings in `instance` (in place) where the schema expects numbers or booleans.");
+    w.open("function M.coerce(instance)");
+    if let Some(c) = &plan.root {
+        emit_value(w, c, "instance", 0);
+    }
+    w.line("return instance");
+    w.close("end");
+}
+
+fn coerce_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "coerce_", 1)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, c: &Coerce, v: &str, depth: usize) {
+    match c {
+        Coerce::Scalar(Scalar::Boolean) => w.line(&format!("{v} = coerce_boolean({v})")),
+        Coerce::Scalar(Scalar::Integer) => w.line(&format!("{v} = coerce_number({v}, true)")),
+        Coerce::Scalar(Scalar::Float) => w.line(&format!("{v} = coerce_number({v}, false)")),
+        Coerce::Ref(name) => w.line(&format!("{v} = {}({v})", coerce_fn_name(name))),
+        Coerce::Object(props) => {
+            w.open(&format!("if is_object({v}) then"));
+            emit_props(w, props, v, depth);
+            w.close("end");
+        }
+        Coerce::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if is_array({v}) then"));
+            w.open(&format!("for {i} = 1, #{v} do"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.close("end");
+            w.close("end");
+        }
+        Coerce::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if is_object({v}) then"));
+            w.open(&format!("for {k} in pairs({v}) do"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.close("end");
+            w.close("end");
+        }
+        Coerce::Tagged { tag, variants } => {
+            w.open(&format!("if is_object({v}) then"));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] == \"{}\"", escape_lua(tag), escape_lua(value));
+                if i == 0 {
+                    w.open(&format!("if {cond} then"));
+                } else {
+                    w.close_open(&format!("elseif {cond} then"));
+                }
+                emit_props(w, props, v, depth);
+            }
+            w.close("end");
+            w.close("end");
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[(String, Coerce)], v: &str, depth: usize) {
+    for (key, c) in props {
+        let child_v = format!("{v}[\"{}\"]", escape_lua(key));
+        w.open(&format!("if {child_v} ~= nil then"));
+        emit_value(w, c, &child_v, depth);
+        w.close("end");
+    }
+}
@@ -1,5 +1,8 @@
 /// EmitContext: the data threaded through each emit function.
+use super::emit::DESCRIBE_FN;
 use super::writer::escape_lua;
+use crate::compiler::escape_pointer;
+use crate::messages::{Message, Subject};
 
 #[derive(Clone)]
 pub struct EmitContext {
@@ -13,6 +16,18 @@ pub struct EmitContext {
     pub sp: String,
     /// Nesting depth
     pub depth: usize,
+    /// Statement leaving the function after an error, for fail-fast
+    /// validation: a `return`, or a `goto` to the function's exit in
+    /// dialects that have it. `None` collects every error.
+    pub stop: Option<String>,
+    /// The variable holding the error limit, with `--max-errors`: `stop`
+    /// then only runs once there are that many errors.
+    pub limit: Option<String>,
+    /// Whether errors carry a `message`, with `--messages`.
+    pub messages: bool,
+    /// Whether the checks are those of an `is_valid` predicate: a failure
+    /// runs `stop`, which returns false, without recording an error.
+    pub predicate: bool,
 }
 
 impl EmitContext {
@@ -23,6 +38,10 @@ impl EmitContext {
             ip: "\"\"".into(),
             sp: "\"\"".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
         }
     }
 
@@ -33,6 +52,25 @@ impl EmitContext {
             ip: "p".into(),
             sp: "sp".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
+        }
+    }
+
+    /// Context for an `M.validate_at` branch.
+    pub fn at_pointer() -> Self {
+        Self {
+            val: "value".into(),
+            err: "e".into(),
+            ip: "\"\"".into(),
+            sp: "pointer".into(),
+            depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
         }
     }
 
@@ -56,9 +94,17 @@ impl EmitContext {
         Self {
             val: format!("{}[\"{}\"]", self.val, escape_lua(key)),
             err: self.err.clone(),
-            ip: format!("{} .. \"/{}\"", self.ip, escape_lua(key)),
-            sp: format!("{} .. \"/properties/{}\"", self.sp, escape_lua(key)),
+            ip: format!("{} .. \"/{}\"", self.ip, escape_lua(&escape_pointer(key))),
+            sp: format!(
+                "{} .. \"/properties/{}\"",
+                self.sp,
+                escape_lua(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
         }
     }
 
@@ -66,9 +112,17 @@ impl EmitContext {
         Self {
             val: format!("{}[\"{}\"]", self.val, escape_lua(key)),
             err: self.err.clone(),
-            ip: format!("{} .. \"/{}\"", self.ip, escape_lua(key)),
-            sp: format!("{} .. \"/optionalProperties/{}\"", self.sp, escape_lua(key)),
+            ip: format!("{} .. \"/{}\"", self.ip, escape_lua(&escape_pointer(key))),
+            sp: format!(
+                "{} .. \"/optionalProperties/{}\"",
+                self.sp,
+                escape_lua(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
         }
     }
 
@@ -79,6 +133,10 @@ impl EmitContext {
             ip: format!("{} .. \"/\" .. ({} - 1)", self.ip, idx_var), // JTD paths are 0-based, Lua is 1-based
             sp: format!("{} .. \"/elements\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
         }
     }
 
@@ -86,9 +144,13 @@ impl EmitContext {
         Self {
             val: format!("{}[{}]", self.val, key_var),
             err: self.err.clone(),
-            ip: format!("{} .. \"/\" .. {}", self.ip, key_var),
+            ip: format!("{} .. \"/\" .. {}", self.ip, pointer_segment(key_var)),
             sp: format!("{} .. \"/values\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
         }
     }
 
@@ -97,24 +159,57 @@ impl EmitContext {
             val: self.val.clone(),
             err: self.err.clone(),
             ip: self.ip.clone(),
-            sp: format!("{} .. \"/mapping/{}\"", self.sp, escape_lua(variant_key)),
+            sp: format!(
+                "{} .. \"/mapping/{}\"",
+                self.sp,
+                escape_lua(&escape_pointer(variant_key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
         }
     }
 
-    pub fn push_error(&self, sp_suffix: &str) -> String {
+    /// This context, leaving the function with `stop` after an error.
+    pub fn with_stop(self, stop: Option<String>) -> Self {
+        Self { stop, ..self }
+    }
+
+    /// This context, leaving the function with `stop` once there are
+    /// `limit` errors rather than after any.
+    pub fn with_limit(self, limit: Option<String>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// This context, adding a `message` to each error when `messages`.
+    pub fn with_messages(self, messages: bool) -> Self {
+        Self { messages, ..self }
+    }
+
+    /// This context, for a predicate leaving with `fail` at the first
+    /// failure.
+    pub fn predicate(self, fail: &str) -> Self {
+        Self {
+            stop: Some(fail.to_string()),
+            limit: None,
+            messages: false,
+            predicate: true,
+            ..self
+        }
+    }
+
+    pub fn push_error(&self, sp_suffix: &str, msg: Message) -> String {
         let sp_expr = if sp_suffix.is_empty() {
             self.sp.clone()
         } else {
             format!("{} .. \"{}\"", self.sp, escape_lua(sp_suffix))
         };
-        format!(
-            "table.insert({}, {{instancePath = {}, schemaPath = {}}})",
-            self.err, self.ip, sp_expr
-        )
+        self.push(&self.ip, &sp_expr, msg)
     }
 
-    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str) -> String {
+    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str, msg: Message) -> String {
         let ip_expr = if ip_suffix.is_empty() {
             self.ip.clone()
         } else {
@@ -125,22 +220,97 @@ impl EmitContext {
         } else {
             format!("{} .. \"{}\"", self.sp, escape_lua(sp_suffix))
         };
-        format!(
-            "table.insert({}, {{instancePath = {}, schemaPath = {}}})",
-            self.err, ip_expr, sp_expr
-        )
+        self.push(&ip_expr, &sp_expr, msg)
     }
 
-    pub fn push_error_dynamic(&self, ip_expr_suffix: &str, sp_suffix: &str) -> String {
+    pub fn push_error_dynamic(
+        &self,
+        ip_expr_suffix: &str,
+        sp_suffix: &str,
+        msg: Message,
+    ) -> String {
         let ip_expr = format!("{} .. {}", self.ip, ip_expr_suffix);
         let sp_expr = if sp_suffix.is_empty() {
             self.sp.clone()
         } else {
             format!("{} .. \"{}\"", self.sp, escape_lua(sp_suffix))
         };
-        format!(
-            "table.insert({}, {{instancePath = {}, schemaPath = {}}})",
-            self.err, ip_expr, sp_expr
-        )
+        self.push(&ip_expr, &sp_expr, msg)
+    }
+
+    /// Push an error with the given path expressions, then leave the
+    /// function when failing fast or at the limit. A `return` stop is
+    /// wrapped in `do ... end` or `if ... end` so it may be followed by
+    /// more statements.
+    /// A predicate just leaves; see [`EmitContext::fail`].
+    pub fn push(&self, ip_expr: &str, sp_expr: &str, msg: Message) -> String {
+        if self.predicate {
+            return self.fail();
+        }
+        let message = if self.messages {
+            format!(", message = {}", self.message(msg))
+        } else {
+            String::new()
+        };
+        let push = format!(
+            "table.insert({}, {{instancePath = {ip_expr}, schemaPath = {sp_expr}{message}}})",
+            self.err
+        );
+        let Some(stop) = &self.stop else {
+            return push;
+        };
+        match &self.limit {
+            Some(limit) => format!("{push}; if #{} >= {limit} then {stop} end", self.err),
+            None => format!("{push}; {}", statement(stop)),
+        }
+    }
+
+    /// The statement a predicate leaves with at a failure, wrapped like
+    /// `stop` after a push.
+    pub fn fail(&self) -> String {
+        statement(self.stop.as_deref().unwrap_or("return false"))
+    }
+
+    /// The Lua expression for the message about `msg`.
+    pub fn message(&self, msg: Message) -> String {
+        let parts = msg.parts();
+        let text = format!("\"{}\"", escape_lua(&parts.text));
+        let subject = match parts.subject {
+            Subject::None => return text,
+            Subject::Value => format!("{DESCRIBE_FN}({})", self.val),
+            Subject::Member(key) => format!("{DESCRIBE_FN}({}[\"{}\"])", self.val, escape_lua(key)),
+            Subject::MemberString(key) => format!("{}[\"{}\"]", self.val, escape_lua(key)),
+            Subject::Variable(var) => var.to_string(),
+        };
+        if parts.end.is_empty() {
+            format!("{text} .. {subject}")
+        } else {
+            format!("{text} .. {subject} .. \"{}\"", escape_lua(parts.end))
+        }
+    }
+
+    /// After a call that may have pushed errors: leave the function when
+    /// failing fast and it did.
+    pub fn check_stop(&self) -> Option<String> {
+        let stop = self.stop.as_ref()?;
+        Some(match &self.limit {
+            Some(limit) => format!("if #{} >= {limit} then {stop} end", self.err),
+            None => format!("if #{} > 0 then {stop} end", self.err),
+        })
+    }
+}
+
+/// The Lua expression escaping the key in `key_var` as a JSON Pointer
+/// segment (RFC 6901), so keys with `/` or `~` give unambiguous paths.
+pub fn pointer_segment(key_var: &str) -> String {
+    format!("({key_var}:gsub(\"~\", \"~0\"):gsub(\"/\", \"~1\"))")
+}
+/// `stop` as a statement more may follow: `return` must end its block, so
+/// it goes in `do ... end`; `goto` may stand anywhere.
+fn statement(stop: &str) -> String {
+    if stop.starts_with("goto ") {
+        stop.to_string()
+    } else {
+        format!("do {stop} end")
     }
 }
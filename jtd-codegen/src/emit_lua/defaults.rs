@@ -0,0 +1,139 @@
+/// `M.apply_defaults(instance)`: sets absent optional properties to their
+/// `metadata.default`, following a [`DefaultsPlan`]. Tables are updated in
+/// place like `M.coerce`. Default tables carry the array/object marks the
+/// configured `--lua-empty-table` strategy looks for, and JSON null becomes
+/// the configured sentinel.
+use super::emit::{def_fn_name, null_expr};
+use super::writer::{escape_lua, CodeWriter};
+use crate::defaults::{DefaultsPlan, Fill, Shape};
+use crate::options::{EmitOptions, LuaEmptyTable};
+use serde_json::Value;
+
+/// Emit the definition fillers and the `M.apply_defaults` entry point.
+pub(super) fn emit_defaults(w: &mut CodeWriter, plan: &DefaultsPlan, opts: &EmitOptions) {
+    // Declared up front so fillers can call ones defined after them.
+    if !plan.definitions.is_empty() {
+        let names: Vec<String> = plan
+            .definitions
+            .keys()
+            .map(|n| defaults_fn_name(n))
+            .collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    for (name, fill) in &plan.definitions {
+        w.open(&format!("function {}(v)", defaults_fn_name(name)));
+        emit_value(w, fill, "v", 0, opts);
+        w.close("end");
+        w.line("");
+    }
+    w.line(
+        "--- Set absent optional properties in `instance` (in place) to their metadata.default.",
+    );
+    w.open("function M.apply_defaults(instance)");
+    if let Some(fill) = &plan.root {
+        emit_value(w, fill, "instance", 0, opts);
+    }
+    w.line("return instance");
+    w.close("end");
+}
+
+fn defaults_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "defaults_", 1)
+}
+
+/// A Lua expression building `value` the way the configured JSON library
+/// would decode it.
+pub(super) fn lua_literal(value: &Value, opts: &EmitOptions) -> String {
+    match value {
+        Value::Null => null_expr(&opts.lua_null()).unwrap_or_else(|| "nil".to_string()),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("\"{}\"", escape_lua(s)),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(|v| lua_literal(v, opts)).collect();
+            let table = format!("{{{}}}", items.join(", "));
+            match opts.lua_tables() {
+                LuaEmptyTable::Metatable => {
+                    format!("setmetatable({table}, {{__jsontype = \"array\"}})")
+                }
+                LuaEmptyTable::NField => {
+                    let sep = if items.is_empty() { "" } else { ", " };
+                    format!("{{{}{sep}n = {}}}", items.join(", "), items.len())
+                }
+                LuaEmptyTable::CjsonArrayMt => format!("setmetatable({table}, cjson_array_mt)"),
+                LuaEmptyTable::Lenient => table,
+            }
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("[\"{}\"] = {}", escape_lua(k), lua_literal(v, opts)))
+                .collect();
+            let table = format!("{{{}}}", entries.join(", "));
+            match opts.lua_tables() {
+                LuaEmptyTable::Metatable => {
+                    format!("setmetatable({table}, {{__jsontype = \"object\"}})")
+                }
+                _ => table,
+            }
+        }
+    }
+}
+
+fn emit_value(w: &mut CodeWriter, fill: &Fill, v: &str, depth: usize, opts: &EmitOptions) {
+    match fill {
+        Fill::Object(shape) => {
+            w.open(&format!("if is_object({v}) then"));
+            emit_shape(w, shape, v, depth, opts);
+            w.close("end");
+        }
+        Fill::Array(inner) => {
+            let (i, x) = (format!("i{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_array({v}) then"));
+            w.open(&format!("for {i}, {x} in ipairs({v}) do"));
+            emit_value(w, inner, &x, depth + 1, opts);
+            w.close("end");
+            w.close("end");
+        }
+        Fill::Map(inner) => {
+            let (k, x) = (format!("k{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_object({v}) then"));
+            w.open(&format!("for {k}, {x} in pairs({v}) do"));
+            emit_value(w, inner, &x, depth + 1, opts);
+            w.close("end");
+            w.close("end");
+        }
+        Fill::Ref(name) => w.line(&format!("{}({v})", defaults_fn_name(name))),
+        Fill::Tagged { tag, variants } => {
+            w.open(&format!("if is_object({v}) then"));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] == \"{}\"", escape_lua(tag), escape_lua(value));
+                if i == 0 {
+                    w.open(&format!("if {cond} then"));
+                } else {
+                    w.close_open(&format!("elseif {cond} then"));
+                }
+                emit_shape(w, shape, v, depth, opts);
+            }
+            w.close("end");
+            w.close("end");
+        }
+    }
+}
+
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize, opts: &EmitOptions) {
+    for (key, default) in &shape.defaults {
+        let child_v = format!("{v}[\"{}\"]", escape_lua(key));
+        w.line(&format!(
+            "if {child_v} == nil then {child_v} = {} end",
+            lua_literal(default, opts)
+        ));
+    }
+    for (key, fill) in &shape.props {
+        let child_v = format!("{v}[\"{}\"]", escape_lua(key));
+        w.open(&format!("if {child_v} ~= nil then"));
+        emit_value(w, fill, &child_v, depth, opts);
+        w.close("end");
+    }
+}
@@ -1,85 +1,446 @@
-use super::context::EmitContext;
+use super::coerce;
+use super::context::{pointer_segment, EmitContext};
+use super::defaults;
+use super::mock;
+use super::openresty;
+use super::strip;
+use super::suggest;
+use super::warnings;
 use super::writer::{escape_lua, CodeWriter};
 use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::coerce::coerce_plan;
+use crate::compiler::escape_pointer;
+use crate::defaults::defaults_plan;
+use crate::deprecation::warning_plan;
+use crate::docs::function_doc;
+use crate::enums::enum_constants;
+use crate::messages::Message;
+use crate::options::{EmitOptions, LuaDialect, LuaEmptyTable, LuaModuleFormat, LuaNullSentinel};
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::source_map;
+use crate::strip::strip_plan;
+use crate::subschemas::sub_schemas;
+use crate::suggest::suggest_sites;
 use std::collections::BTreeMap;
 
 /// Emit a complete Lua module from a compiled schema.
 pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a complete Lua module from a compiled schema with the given options.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
     let mut w = CodeWriter::new();
 
     w.line("-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
     w.line("-- This code is generated from a JSON Type Definition schema.");
     w.line("-- Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("lua") {
+            w.line(&format!("-- {line}"));
+        }
+    }
+    if opts.lua_dialect != LuaDialect::Lua51 {
+        w.line(&format!("-- Target: {}", opts.lua_dialect.as_str()));
+    }
+    if opts.lua_format == LuaModuleFormat::OpenResty {
+        w.line("-- Format: OpenResty module (no globals, cjson.safe, ngx helpers)");
+    }
     w.line("");
     w.line("local M = {}");
-    w.line("-- Generated validators require dkjson for null sentinel handling.");
-    w.line("-- Lua 5.1 cannot distinguish JSON null from table absence; dkjson.null");
-    w.line("-- provides a reliable sentinel that preserves JTD validation semantics.");
-    w.line("-- Future enhancement: Add --lua-null-sentinel <name> CLI flag to make");
-    w.line("-- this configurable for users who prefer other JSON libraries.");
-    w.line("local dkjson = require(\"dkjson\")");
+    if opts.lua_format == LuaModuleFormat::OpenResty {
+        openresty::emit_prelude(&mut w, opts);
+    }
+    emit_null_sentinel(&mut w, &opts.lua_null());
     w.line("");
 
-    // Helper: is_integer
-    w.open("local function is_integer(v)");
-    w.line("return type(v) == \"number\" and v == math.floor(v)");
-    w.close("end");
-
-    w.open("local function is_array(v)");
-    w.line("if v == dkjson.null then return false end");
-    w.line("if type(v) ~= \"table\" then return false end");
-    w.line("local mt = getmetatable(v)");
-    w.line("if mt and mt.__jsontype == \"object\" then return false end");
-    w.line("if mt and mt.__jsontype == \"array\" then return true end");
-    w.line("if #v > 0 then return true end");
-    w.line("return next(v) == nil");
-    w.close("end");
-
-    w.open("local function is_object(v)");
-    w.line("if v == dkjson.null then return false end");
-    w.line("if type(v) ~= \"table\" then return false end");
-    w.line("local mt = getmetatable(v)");
-    w.line("if mt and mt.__jsontype == \"array\" then return false end");
-    w.line("if mt and mt.__jsontype == \"object\" then return true end");
-    w.line("if #v > 0 then return false end");
-    w.line("return true");
-    w.close("end");
+    emit_is_integer_helper(&mut w, opts.lua_dialect);
 
+    emit_container_helpers(&mut w, opts);
     w.line("");
+    if opts.messages {
+        emit_describe_helper(&mut w, opts);
+        w.line("");
+    }
+    if opts.path_segments {
+        emit_segments_helper(&mut w);
+        w.line("");
+    }
 
     if needs_timestamp(&schema.root, &schema.definitions) {
         emit_timestamp_helper(&mut w);
     }
 
-    // Definitions
+    if opts.enum_constants {
+        let constants = enum_constants(&[("", &schema.root)], &schema.definitions);
+        for c in &constants {
+            let items: Vec<String> = c
+                .values
+                .iter()
+                .map(|v| format!("\"{}\"", escape_lua(v)))
+                .collect();
+            w.line(&format!("M.{} = {{ {} }}", c.name, items.join(", ")));
+        }
+        if !constants.is_empty() {
+            w.line("");
+        }
+    }
+
+    if opts.suggestions {
+        suggest::emit_suggest_helpers(&mut w, &suggest_sites(schema), opts.lua_dialect);
+        w.line("");
+    }
+
+    // Definitions, declared up front so validators can call ones defined
+    // after them
+    if !schema.definitions.is_empty() {
+        let names: Vec<String> = schema.definitions.keys().map(|n| def_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    let recursive = recursive(&schema.definitions);
+    if !recursive.is_empty() {
+        w.line("local ref_depth = 0");
+        w.line("");
+    }
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    let limit_var = limit.map(|_| LIMIT_VAR.to_string());
     for (name, node) in &schema.definitions {
         let fn_name = def_fn_name(name);
-        w.open(&format!("local function {fn_name}(v, e, p, sp)"));
-        let ctx = EmitContext::definition();
-        emit_node(&mut w, node, &ctx, None);
+        emit_doc(
+            &mut w,
+            &function_doc(
+                schema,
+                &format!("/definitions/{}", escape_pointer(name)),
+                node,
+            ),
+        );
+        let limit_arg = limit_var
+            .as_ref()
+            .map_or(String::new(), |l| format!(", {l}"));
+        w.open(&format!("function {fn_name}(v, e, p, sp{limit_arg})"));
+        let ctx = EmitContext::definition().with_messages(opts.messages);
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            emit_depth_enter(&mut w, &ctx);
+        }
+        let ctx = ctx
+            .with_stop(stops.then(|| exit_stop(opts).unwrap_or_else(|| definition_stop(guarded))))
+            .with_limit(limit_var.clone());
+        emit_exiting(&mut w, opts, |w| emit_node(w, node, &ctx, opts, None));
+        if guarded {
+            w.line("ref_depth = ref_depth - 1");
+        }
         w.close("end");
         w.line("");
     }
 
     // Root validate function
-    w.open("function M.validate(instance)");
+    emit_doc(&mut w, &function_doc(schema, "", &schema.root));
+    w.open(&format!(
+        "function M.validate(instance{})",
+        limit_param(opts)
+    ));
+    emit_limit_default(&mut w, opts);
     w.line("local e = {}");
-    let ctx = EmitContext::root();
-    emit_node(&mut w, &schema.root, &ctx, None);
-    w.line("return e");
+    let ret = return_errors(opts, "instance");
+    let ctx = EmitContext::root()
+        .with_messages(opts.messages)
+        .with_stop(stops.then(|| exit_stop(opts).unwrap_or_else(|| ret.clone())))
+        .with_limit(limit_var);
+    emit_exiting(&mut w, opts, |w| {
+        emit_node(w, &schema.root, &ctx, opts, None)
+    });
+    w.line(&ret);
     w.close("end");
-
     w.line("");
+
+    if opts.is_valid {
+        emit_is_valid(&mut w, schema, opts);
+        w.line("");
+    }
+
+    if opts.self_test {
+        emit_self_test(&mut w, schema, opts);
+        w.line("");
+    }
+
+    if opts.deprecation_warnings {
+        warnings::emit_warnings(&mut w, &warning_plan(schema));
+        w.line("");
+    }
+
+    if opts.validate_at {
+        emit_validate_at(&mut w, schema, opts);
+        w.line("");
+    }
+
+    if opts.coerce {
+        coerce::emit_coerce(&mut w, &coerce_plan(schema), opts.lua_dialect);
+        w.line("");
+    }
+
+    if opts.strip_additional {
+        strip::emit_strip(&mut w, &strip_plan(schema));
+        w.line("");
+    }
+
+    if opts.apply_defaults {
+        defaults::emit_defaults(&mut w, &defaults_plan(schema), opts);
+        w.line("");
+    }
+
+    if opts.mock {
+        mock::emit_mock(&mut w, schema, opts);
+        w.line("");
+    }
+
+    if opts.shape {
+        w.line("-- Object keys, enum values and discriminator tags by instance path,");
+        w.line("-- for form and error UIs.");
+        let shape = defaults::lua_literal(&crate::shape::shape(schema), opts);
+        w.line(&format!("M.SHAPE = {shape}"));
+        w.line("");
+    }
+
+    if opts.lua_format == LuaModuleFormat::OpenResty {
+        openresty::emit_handlers(&mut w);
+    }
+
     w.line("return M");
 
     w.finish()
 }
 
-fn def_fn_name(name: &str) -> String {
+/// Name of a definition's predicate in `M.is_valid`.
+fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "is_valid_", 1)
+}
+
+/// `M.is_valid(instance)`: the checks `M.validate` makes, returning false at
+/// the first failure without building errors, and a local predicate per
+/// definition. Recursive ones share the validators' depth count.
+fn emit_is_valid(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    if !schema.definitions.is_empty() {
+        let names: Vec<String> = schema.definitions.keys().map(|n| pred_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+    }
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        w.open(&format!("function {}(v)", pred_fn_name(name)));
+        let guarded = recursive.contains(name.as_str());
+        let fail = if guarded {
+            w.line(&format!(
+                "if ref_depth >= {MAX_REF_DEPTH} then return false end"
+            ));
+            w.line("ref_depth = ref_depth + 1");
+            "ref_depth = ref_depth - 1; return false"
+        } else {
+            "return false"
+        };
+        emit_node(
+            w,
+            node,
+            &EmitContext::definition().predicate(fail),
+            opts,
+            None,
+        );
+        if guarded {
+            w.line("ref_depth = ref_depth - 1");
+        }
+        w.line("return true");
+        w.close("end");
+    }
+    w.line("--- Whether `instance` is valid; stops at the first failure and builds no errors.");
+    w.open("function M.is_valid(instance)");
+    let ctx = EmitContext::root().predicate("return false");
+    emit_node(w, &schema.root, &ctx, opts, None);
+    w.line("return true");
+    w.close("end");
+}
+
+/// `M.validate_at(pointer, value)`: validate a fragment against one
+/// sub-schema, reporting schema paths from `pointer` on. Unknown pointers
+/// raise an error.
+fn emit_validate_at(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    w.line(
+        "--- Validate `value` against the sub-schema at `pointer`, e.g. \"/properties/address\".",
+    );
+    w.open(&format!(
+        "function M.validate_at(pointer, value{})",
+        limit_param(opts)
+    ));
+    let limit_var = opts.error_limit().map(|_| LIMIT_VAR.to_string());
+    let limit_arg = limit_var
+        .as_ref()
+        .map_or(String::new(), |l| format!(", {l}"));
+    w.open("if pointer == \"\" then");
+    w.line(&format!("return M.validate(value{limit_arg})"));
+    w.close("end");
+    emit_limit_default(w, opts);
+    w.line("local e = {}");
+    let ret = return_errors(opts, "value");
+    let ctx = EmitContext::at_pointer()
+        .with_messages(opts.messages)
+        .with_stop(
+            (opts.fail_fast || limit_var.is_some())
+                .then(|| exit_stop(opts).unwrap_or_else(|| ret.clone())),
+        )
+        .with_limit(limit_var);
+    let subs = sub_schemas(schema);
+    emit_exiting(w, opts, |w| {
+        for (i, sub) in subs.iter().skip(1).enumerate() {
+            let cond = format!("pointer == \"{}\" then", escape_lua(&sub.pointer));
+            if i == 0 {
+                w.open(&format!("if {cond}"));
+            } else {
+                w.close_open(&format!("elseif {cond}"));
+            }
+            emit_node(w, sub.node, &ctx, opts, sub.tag);
+        }
+        let unknown = "error(\"validate_at: no sub-schema at \" .. pointer)";
+        if subs.len() == 1 {
+            w.line(unknown);
+        } else {
+            w.close_open("else");
+            w.line(unknown);
+            w.close("end");
+        }
+    });
+    w.line(&ret);
+    w.close("end");
+}
+
+/// The final statement of an entry point validating `instance`: the
+/// errors with their suggestions, then with their paths as segments.
+fn return_errors(opts: &EmitOptions, instance: &str) -> String {
+    let mut errors = "e".to_string();
+    if opts.suggestions {
+        errors = format!("add_suggestions({instance}, {errors})");
+    }
+    if opts.path_segments {
+        errors = format!("{SEGMENTS_FN}({errors})");
+    }
+    format!("return {errors}")
+}
+
+/// The variable definitions and entry points take the error limit in.
+const LIMIT_VAR: &str = "max_errors";
+
+/// The optional error limit parameter of an entry point.
+fn limit_param(opts: &EmitOptions) -> String {
+    opts.error_limit()
+        .map_or(String::new(), |_| format!(", {LIMIT_VAR}"))
+}
+
+/// Default an entry point's error limit to `max_errors` when not given.
+fn emit_limit_default(w: &mut CodeWriter, opts: &EmitOptions) {
+    if let Some(n) = opts.error_limit() {
+        w.line(&format!("{LIMIT_VAR} = {LIMIT_VAR} or {n}"));
+    }
+}
+
+/// Label of the end of a validator, before its depth restore and return,
+/// which early exits jump to in dialects with `goto`.
+const EXIT_LABEL: &str = "done";
+
+/// The early exit when the dialect has `goto`: a jump to [`EXIT_LABEL`],
+/// so the function's tail is written once rather than at every error.
+fn exit_stop(opts: &EmitOptions) -> Option<String> {
+    (opts.lua_dialect.has_goto() && (opts.fail_fast || opts.error_limit().is_some()))
+        .then(|| format!("goto {EXIT_LABEL}"))
+}
+
+/// Emit a validator's checks with `body`, followed by [`EXIT_LABEL`] when
+/// [`exit_stop`] jumps there. The checks go in `do ... end`, as `goto` may
+/// not jump into the scope of their locals.
+fn emit_exiting(w: &mut CodeWriter, opts: &EmitOptions, body: impl FnOnce(&mut CodeWriter)) {
+    if exit_stop(opts).is_none() {
+        body(w);
+        return;
+    }
+    w.open("do");
+    body(w);
+    w.close("end");
+    w.line(&format!("::{EXIT_LABEL}::"));
+}
+
+/// How a definition's function leaves early when failing fast, restoring
+/// the depth counter of a recursive one.
+fn definition_stop(guarded: bool) -> String {
+    if guarded {
+        "ref_depth = ref_depth - 1; return".to_string()
+    } else {
+        "return".to_string()
+    }
+}
+
+/// `M.self_test([decode])`: validate each `metadata.examples` entry (root
+/// and definitions) and return `{schemaPath, example, errors}` for failures.
+/// Examples are embedded as JSON text and decoded with the same library the
+/// null sentinel comes from, so they look exactly like real input.
+fn emit_self_test(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    let sentinel = &opts.lua_null();
+    let limit_arg = opts
+        .error_limit()
+        .map_or(String::new(), |n| format!(", {n}"));
+    w.line("--- Validate the schema's metadata.examples; returns the examples that fail.");
+    w.line("--- `decode` turns JSON text into Lua values the way the host does.");
+    w.open("function M.self_test(decode)");
+    match sentinel {
+        LuaNullSentinel::Dkjson => {
+            w.line("decode = decode or function(s) return dkjson.decode(s, 1, dkjson.null) end")
+        }
+        LuaNullSentinel::Cjson => w.line("decode = decode or cjson.decode"),
+        LuaNullSentinel::Custom(_) | LuaNullSentinel::Nil => {
+            w.line("assert(decode, \"self_test needs a JSON decode function\")")
+        }
+    }
+    w.line("local failures = {}");
+    w.line("local e");
+    for (sp, def, examples) in schema.example_sets() {
+        for (i, example) in examples.iter().enumerate() {
+            let json = escape_lua(&serde_json::to_string(example).unwrap_or_default());
+            match def {
+                None => w.line(&format!("e = M.validate(decode(\"{json}\"))")),
+                Some(name) => {
+                    w.line("e = {}");
+                    w.line(&format!(
+                        "{}(decode(\"{json}\"), e, \"\", \"{}\"{limit_arg})",
+                        def_fn_name(name),
+                        escape_lua(&sp)
+                    ));
+                }
+            }
+            w.open("if #e > 0 then");
+            w.line(&format!(
+                "table.insert(failures, {{schemaPath = \"{}\", example = {i}, errors = e}})",
+                escape_lua(&sp)
+            ));
+            w.close("end");
+        }
+    }
+    w.line("return failures");
+    w.close("end");
+}
+
+/// LDoc-style `---` comment block.
+fn emit_doc(w: &mut CodeWriter, lines: &[String]) {
+    for line in lines {
+        if line.is_empty() {
+            w.line("---");
+        } else {
+            w.line(&format!("--- {line}"));
+        }
+    }
+}
+
+pub(super) fn def_fn_name(name: &str) -> String {
     let safe: String = name
         .chars()
         .map(|c| {
-            if c.is_alphanumeric() || c == '_' {
+            if c.is_ascii_alphanumeric() || c == '_' {
                 c
             } else {
                 '_'
@@ -89,6 +450,160 @@ fn def_fn_name(name: &str) -> String {
     format!("validate_{safe}")
 }
 
+/// Bind the JSON library's null sentinel. Missing keys read as `nil`;
+/// JSON null reads as the sentinel, which keeps the two distinguishable.
+fn emit_null_sentinel(w: &mut CodeWriter, sentinel: &LuaNullSentinel) {
+    match sentinel {
+        LuaNullSentinel::Dkjson => {
+            w.line("-- Generated validators require dkjson for null sentinel handling.");
+            w.line("-- Lua 5.1 cannot distinguish JSON null from table absence; dkjson.null");
+            w.line("-- provides a reliable sentinel that preserves JTD validation semantics.");
+            w.line("local dkjson = require(\"dkjson\")");
+        }
+        LuaNullSentinel::Cjson => {
+            w.line("-- JSON null is lua-cjson's cjson.null sentinel.");
+            w.line("local cjson = require(\"cjson\")");
+        }
+        LuaNullSentinel::Custom(expr) => {
+            w.line("-- JSON null is represented by a host-provided sentinel.");
+            w.line(&format!("local json_null = {expr}"));
+        }
+        LuaNullSentinel::Nil => {
+            w.line("-- JSON null decodes to nil: null values are indistinguishable from");
+            w.line("-- missing keys, so a null required property reports as missing.");
+        }
+    }
+}
+
+/// The Lua expression for JSON null, or `None` when null decodes to `nil`.
+pub(super) fn null_expr(sentinel: &LuaNullSentinel) -> Option<String> {
+    match sentinel {
+        LuaNullSentinel::Dkjson => Some("dkjson.null".into()),
+        LuaNullSentinel::Cjson => Some("cjson.null".into()),
+        LuaNullSentinel::Custom(_) => Some("json_null".into()),
+        LuaNullSentinel::Nil => None,
+    }
+}
+
+/// Emit `is_array`/`is_object`. An empty Lua table could be either `[]` or
+/// `{}`; the configured strategy decides how the two are told apart.
+fn emit_container_helpers(w: &mut CodeWriter, opts: &EmitOptions) {
+    let null = null_expr(&opts.lua_null());
+    let strategy = opts.lua_tables();
+
+    if strategy == LuaEmptyTable::CjsonArrayMt {
+        w.line("local cjson_array_mt = require(\"cjson\").array_mt");
+        w.line("local cjson_empty_array_mt = require(\"cjson\").empty_array_mt");
+    }
+
+    w.open("local function is_array(v)");
+    if let Some(null) = &null {
+        w.line(&format!("if v == {null} then return false end"));
+    }
+    w.line("if type(v) ~= \"table\" then return false end");
+    match strategy {
+        LuaEmptyTable::Metatable => {
+            w.line("local mt = getmetatable(v)");
+            w.line("if mt and mt.__jsontype == \"object\" then return false end");
+            w.line("if mt and mt.__jsontype == \"array\" then return true end");
+            w.line("if #v > 0 then return true end");
+            w.line("return next(v) == nil");
+        }
+        LuaEmptyTable::NField => {
+            w.line("return type(rawget(v, \"n\")) == \"number\"");
+        }
+        LuaEmptyTable::CjsonArrayMt => {
+            w.line("local mt = getmetatable(v)");
+            w.line("return mt ~= nil and (mt == cjson_array_mt or mt == cjson_empty_array_mt)");
+        }
+        LuaEmptyTable::Lenient => {
+            w.line("return #v > 0 or next(v) == nil");
+        }
+    }
+    w.close("end");
+
+    w.open("local function is_object(v)");
+    if let Some(null) = &null {
+        w.line(&format!("if v == {null} then return false end"));
+    }
+    w.line("if type(v) ~= \"table\" then return false end");
+    match strategy {
+        LuaEmptyTable::Metatable => {
+            w.line("local mt = getmetatable(v)");
+            w.line("if mt and mt.__jsontype == \"array\" then return false end");
+            w.line("if mt and mt.__jsontype == \"object\" then return true end");
+            w.line("if #v > 0 then return false end");
+            w.line("return true");
+        }
+        LuaEmptyTable::NField | LuaEmptyTable::CjsonArrayMt => {
+            w.line("return not is_array(v)");
+        }
+        LuaEmptyTable::Lenient => {
+            w.line("return #v == 0");
+        }
+    }
+    w.close("end");
+}
+
+/// Helper describing a value in messages: its JSON type, or the number
+/// itself.
+pub(super) const DESCRIBE_FN: &str = "describe";
+
+/// The [`DESCRIBE_FN`] helper, with `--messages`, after the container
+/// helpers it uses.
+fn emit_describe_helper(w: &mut CodeWriter, opts: &EmitOptions) {
+    w.open(&format!("local function {DESCRIBE_FN}(v)"));
+    match null_expr(&opts.lua_null()) {
+        Some(null) => w.line(&format!(
+            "if v == nil or v == {null} then return \"null\" end"
+        )),
+        None => w.line("if v == nil then return \"null\" end"),
+    }
+    w.line("local t = type(v)");
+    w.line("if t == \"number\" then return tostring(v) end");
+    w.line("if t ~= \"table\" then return t end");
+    w.line("if is_array(v) then return \"array\" end");
+    w.line("return \"object\"");
+    w.close("end");
+}
+
+/// Helper turning the errors' JSON Pointers into arrays of segments before
+/// the entry points return them.
+const SEGMENTS_FN: &str = "path_segments";
+
+/// The [`SEGMENTS_FN`] helper, with `--path-segments`. It rewrites the
+/// errors in place, keeping their other fields.
+fn emit_segments_helper(w: &mut CodeWriter) {
+    w.open(&format!("local function {SEGMENTS_FN}(e)"));
+    w.open("local function split(p)");
+    w.line("local out = {}");
+    w.open("for s in string.gmatch(p, \"/([^/]*)\") do");
+    w.line("table.insert(out, (s:gsub(\"~1\", \"/\"):gsub(\"~0\", \"~\")))");
+    w.close("end");
+    w.line("return out");
+    w.close("end");
+    w.open("for _, x in ipairs(e) do");
+    w.line("x.instancePath = split(x.instancePath)");
+    w.line("x.schemaPath = split(x.schemaPath)");
+    w.close("end");
+    w.line("return e");
+    w.close("end");
+}
+
+/// Integer check. Lua 5.3+ has an integer subtype, but JSON decoders may
+/// still hand back floats with a zero fraction (e.g. `1.0`), so both count.
+fn emit_is_integer_helper(w: &mut CodeWriter, dialect: LuaDialect) {
+    w.open("local function is_integer(v)");
+    if dialect.has_integer_subtype() {
+        w.line("local t = math.type(v)");
+        w.line("if t == \"integer\" then return true end");
+        w.line("return t == \"float\" and v == math.floor(v)");
+    } else {
+        w.line("return type(v) == \"number\" and v == math.floor(v)");
+    }
+    w.close("end");
+}
+
 fn needs_timestamp(root: &Node, defs: &BTreeMap<String, Node>) -> bool {
     node_uses_timestamp(root) || defs.values().any(node_uses_timestamp)
 }
@@ -109,47 +624,64 @@ fn node_uses_timestamp(node: &Node) -> bool {
     }
 }
 
+/// A port of [`crate::timestamp::is_rfc3339`]. The patterns spell out
+/// `[0-9]`, as what `%d` matches depends on the locale.
 fn emit_timestamp_helper(w: &mut CodeWriter) {
     w.open("local function is_rfc3339(s)");
     w.line("if type(s) ~= \"string\" then return false end");
-    w.line("-- Pattern: YYYY-MM-DDThh:mm:ss[.frac](Z|+/-hh:mm)");
-    // Lua patterns are limited. We do a loose check for structure.
-    // 4 digits - 2 digits - 2 digits T 2 digits : 2 digits : 2 digits
-    w.line("local y, m, d, t, h, min, s2, rest = s:match(\"^([0-9][0-9][0-9][0-9])%-([0-9][0-9])%-([0-9][0-9])([Tt])([0-9][0-9]):([0-9][0-9]):([0-9][0-9])(.*)$\")");
+    w.line("local y, mo, d, h, mi, sec, rest = s:match(\"^([0-9][0-9][0-9][0-9])%-([0-9][0-9])%-([0-9][0-9])[Tt]([0-9][0-9]):([0-9][0-9]):([0-9][0-9])(.*)$\")");
     w.line("if not y then return false end");
-    w.line("-- Validate component ranges");
-    w.line("local mn, dn, hn, minn, sn = tonumber(m), tonumber(d), tonumber(h), tonumber(min), tonumber(s2)");
-    w.line("if mn < 1 or mn > 12 then return false end");
-    w.line("if dn < 1 or dn > 31 then return false end");
-    w.line("if hn > 23 then return false end");
-    w.line("if minn > 59 then return false end");
-    w.line("if sn > 60 then return false end"); // RFC 3339 allows 60 for leap seconds
-    w.line("-- Optional fraction");
-    w.line("local off = rest");
-    w.open("if rest:sub(1, 1) == \".\" then");
-    w.line("local frac_end = rest:find(\"[^0-9]\", 2)");
-    w.open("if not frac_end then");
-    w.line("-- Just fraction, no offset?");
-    w.line("return false");
-    w.close("else");
-    w.line("off = rest:sub(frac_end)");
-    w.close("end");
+    w.line("y, mo, d = tonumber(y), tonumber(mo), tonumber(d)");
+    w.line("if mo < 1 or mo > 12 or d < 1 then return false end");
+    w.line("local leap = y % 4 == 0 and (y % 100 ~= 0 or y % 400 == 0)");
+    w.line("if d > ({31, leap and 29 or 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31})[mo] then return false end");
+    w.line("if tonumber(h) > 23 or tonumber(mi) > 59 or tonumber(sec) > 60 then return false end");
+    w.line("-- An optional fraction of at least one digit, then the offset");
+    w.line("local frac = rest:match(\"^%.[0-9]+\")");
+    w.line("if frac then rest = rest:sub(#frac + 1) end");
+    w.line("if rest == \"Z\" or rest == \"z\" then return true end");
+    w.line("local oh, om = rest:match(\"^[+-]([0-9][0-9]):([0-9][0-9])$\")");
+    w.line("return oh ~= nil and tonumber(oh) <= 23 and tonumber(om) <= 59");
     w.close("end");
+    w.line("");
+}
 
-    w.line("-- Offset: Z or z or +/-hh:mm");
-    w.line("if off == \"Z\" or off == \"z\" then return true end");
-    w.line("local oh, om = off:match(\"^[+-]([0-9][0-9]):([0-9][0-9])$\")");
-    w.open("if oh then");
-    w.line("local ohn, omn = tonumber(oh), tonumber(om)");
-    w.line("if ohn > 23 or omn > 59 then return false end");
-    w.line("return true");
-    w.close("end");
-    w.line("return false");
+/// Start of a recursive definition's function: from `MAX_REF_DEPTH` nested
+/// calls on, report the value at the definition's schema path instead of
+/// descending, so deep instances cannot exhaust the stack.
+fn emit_depth_enter(w: &mut CodeWriter, ctx: &EmitContext) {
+    w.open(&format!("if ref_depth >= {MAX_REF_DEPTH} then"));
+    w.line(&ctx.push_error("", Message::TooDeep));
+    w.line("return");
     w.close("end");
-    w.line("");
+    w.line("ref_depth = ref_depth + 1");
+}
+
+/// Emit the checks of one AST node, in source-map markers with
+/// [`EmitOptions::source_map`].
+fn emit_node(
+    w: &mut CodeWriter,
+    node: &Node,
+    ctx: &EmitContext,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
+    if !opts.source_map {
+        return emit_node_checks(w, node, ctx, opts, discrim_tag);
+    }
+    w.line(&source_map::open(node));
+    emit_node_checks(w, node, ctx, opts, discrim_tag);
+    w.line(source_map::close());
 }
 
-fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Option<&str>) {
+/// [`emit_node`] without the source-map markers.
+fn emit_node_checks(
+    w: &mut CodeWriter,
+    node: &Node,
+    ctx: &EmitContext,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
     match node {
         Node::Empty => {}
 
@@ -161,28 +693,48 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
                 .map(|v| format!("{} ~= \"{}\"", ctx.val, escape_lua(v)))
                 .collect();
             w.open(&format!("if {} then", conds.join(" and ")));
-            w.line(&ctx.push_error("/enum"));
+            w.line(&ctx.push_error("/enum", Message::Enum(values)));
+            w.close("end");
+        }
+
+        Node::Ref { name } if ctx.predicate => {
+            w.open(&format!("if not {}({}) then", pred_fn_name(name), ctx.val));
+            w.line(&ctx.fail());
             w.close("end");
         }
 
         Node::Ref { name } => {
             let fn_name = def_fn_name(name);
+            let limit_arg = ctx
+                .limit
+                .as_ref()
+                .map_or(String::new(), |l| format!(", {l}"));
             w.line(&format!(
-                "{}({}, {}, {}, \"/definitions/{}\")",
-                fn_name, ctx.val, ctx.err, ctx.ip, name
+                "{}({}, {}, {}, \"/definitions/{}\"{limit_arg})",
+                fn_name,
+                ctx.val,
+                ctx.err,
+                ctx.ip,
+                escape_lua(&escape_pointer(name))
             ));
+            if let Some(check) = ctx.check_stop() {
+                w.line(&check);
+            }
         }
 
         Node::Nullable { inner } => {
             if matches!(inner.as_ref(), Node::Empty) {
                 return;
             }
-            // Check for dkjson.null AND nil (just in case)
-            w.open(&format!(
-                "if {} ~= nil and {} ~= dkjson.null then",
-                ctx.val, ctx.val
-            ));
-            emit_node(w, inner, ctx, None);
+            // Check for the null sentinel AND nil (just in case)
+            match null_expr(&opts.lua_null()) {
+                Some(null) => w.open(&format!(
+                    "if {} ~= nil and {} ~= {null} then",
+                    ctx.val, ctx.val
+                )),
+                None => w.open(&format!("if {} ~= nil then", ctx.val)),
+            }
+            emit_node(w, inner, ctx, opts, None);
             w.close("end");
         }
 
@@ -191,15 +743,21 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
             w.open(&format!("if is_array({}) then", ctx.val));
             // In Lua, it's hard to distinguish array vs object strictly.
             // We assume table is valid.
-            w.open(&format!("for {}, elem in ipairs({}) do", idx, ctx.val));
+            if opts.lua_tables() == LuaEmptyTable::NField {
+                // `n` is authoritative: holes (JSON null as nil) still count.
+                w.open(&format!("for {} = 1, {}.n do", idx, ctx.val));
+                w.line(&format!("local elem = {}[{}]", ctx.val, idx));
+            } else {
+                w.open(&format!("for {}, elem in ipairs({}) do", idx, ctx.val));
+            }
             let child_ctx = ctx.element(&idx);
             // Redefine child val to be 'elem' for cleaner code, or use ctx val
             let mut inner_ctx = child_ctx.clone();
             inner_ctx.val = "elem".to_string(); // Optimization: use loop var
-            emit_node(w, schema, &inner_ctx, None);
+            emit_node(w, schema, &inner_ctx, opts, None);
             w.close("end");
             w.close_open("else");
-            w.line(&ctx.push_error("/elements"));
+            w.line(&ctx.push_error("/elements", Message::NotArray));
             w.close("end");
         }
 
@@ -210,10 +768,10 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
             let child_ctx = ctx.values_entry(&key);
             let mut inner_ctx = child_ctx.clone();
             inner_ctx.val = "val".to_string();
-            emit_node(w, schema, &inner_ctx, None);
+            emit_node(w, schema, &inner_ctx, opts, None);
             w.close("end");
             w.close_open("else");
-            w.line(&ctx.push_error("/values"));
+            w.line(&ctx.push_error("/values", Message::NotObject));
             w.close("end");
         }
 
@@ -240,23 +798,26 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
                     ctx.val,
                     escape_lua(key)
                 ));
-                w.line(&ctx.push_error(&format!("/properties/{}", escape_lua(key))));
+                w.line(&ctx.push_error(
+                    &format!("/properties/{}", escape_pointer(key)),
+                    Message::MissingProperty(key),
+                ));
                 w.close_open("else");
                 let child_ctx = ctx.required_prop(key);
-                emit_node(w, node, &child_ctx, None);
+                emit_node(w, node, &child_ctx, opts, None);
                 w.close("end");
             }
 
             for (key, node) in optional {
+                // A present key holding JSON null is still validated: only
+                // absence (nil) skips an optional property.
                 w.open(&format!(
-                    "if {}[\"{}\"] ~= nil and {}[\"{}\"] ~= dkjson.null then",
-                    ctx.val,
-                    escape_lua(key),
+                    "if {}[\"{}\"] ~= nil then",
                     ctx.val,
                     escape_lua(key)
                 ));
                 let child_ctx = ctx.optional_prop(key);
-                emit_node(w, node, &child_ctx, None);
+                emit_node(w, node, &child_ctx, opts, None);
                 w.close("end");
             }
 
@@ -276,21 +837,29 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
                 }
 
                 if known.is_empty() {
-                    w.line(&ctx.push_error_dynamic(&format!("\"/\" .. {}", k), ""));
+                    w.line(&ctx.push_error_dynamic(
+                        &format!("\"/\" .. {}", pointer_segment(&k)),
+                        "",
+                        Message::UnknownProperty(&k),
+                    ));
                 } else {
                     let conds: Vec<String> = known
                         .iter()
                         .map(|key| format!("{} ~= \"{}\"", k, escape_lua(key)))
                         .collect();
                     w.open(&format!("if {} then", conds.join(" and ")));
-                    w.line(&ctx.push_error_dynamic(&format!("\"/\" .. {}", k), ""));
+                    w.line(&ctx.push_error_dynamic(
+                        &format!("\"/\" .. {}", pointer_segment(&k)),
+                        "",
+                        Message::UnknownProperty(&k),
+                    ));
                     w.close("end");
                 }
                 w.close("end"); // for
             }
 
             w.close_open("else");
-            w.line(&ctx.push_error(guard_suffix));
+            w.line(&ctx.push_error(guard_suffix, Message::NotObject));
             w.close("end");
         }
 
@@ -326,12 +895,16 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
                     ));
                 }
                 let variant_ctx = ctx.discrim_variant(variant_key);
-                emit_node(w, variant_node, &variant_ctx, Some(tag));
+                emit_node(w, variant_node, &variant_ctx, opts, Some(tag));
             }
             if !first {
                 w.close_open("else");
                 // Unknown tag value
-                w.line(&ctx.push_error_at(&format!("/{}", escape_lua(tag)), "/mapping"));
+                w.line(&ctx.push_error_at(
+                    &format!("/{}", escape_pointer(tag)),
+                    "/mapping",
+                    Message::UnknownTag(tag),
+                ));
                 w.close("end");
             } else {
                 // Empty mapping? JTD spec says mapping can't be empty technically but handle it.
@@ -339,17 +912,21 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
 
             w.close_open("else");
             // Tag not string
-            w.line(&ctx.push_error_at(&format!("/{}", escape_lua(tag)), "/discriminator"));
+            w.line(&ctx.push_error_at(
+                &format!("/{}", escape_pointer(tag)),
+                "/discriminator",
+                Message::TagNotString(tag),
+            ));
             w.close("end");
 
             w.close_open("else");
             // Tag missing
-            w.line(&ctx.push_error("/discriminator"));
+            w.line(&ctx.push_error("/discriminator", Message::MissingTag(tag)));
             w.close("end");
 
             w.close_open("else");
             // Not object
-            w.line(&ctx.push_error("/discriminator"));
+            w.line(&ctx.push_error("/discriminator", Message::NotObject));
             w.close("end");
         }
     }
@@ -359,22 +936,22 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
     match type_kw {
         TypeKeyword::Boolean => {
             w.open(&format!("if type({}) ~= \"boolean\" then", ctx.val));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::String => {
             w.open(&format!("if type({}) ~= \"string\" then", ctx.val));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Timestamp => {
             w.open(&format!("if not is_rfc3339({}) then", ctx.val));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Float32 | TypeKeyword::Float64 => {
             w.open(&format!("if type({}) ~= \"number\" then", ctx.val));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Int8 => {
@@ -382,7 +959,7 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < -128 or {} > 127 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Uint8 => {
@@ -390,7 +967,7 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < 0 or {} > 255 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Int16 => {
@@ -398,7 +975,7 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < -32768 or {} > 32767 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Uint16 => {
@@ -406,7 +983,7 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < 0 or {} > 65535 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Int32 => {
@@ -414,7 +991,7 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < -2147483648 or {} > 2147483647 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
         TypeKeyword::Uint32 => {
@@ -423,8 +1000,452 @@ fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
                 "if not is_integer({}) or {} < 0 or {} > 4294967295 then",
                 ctx.val, ctx.val, ctx.val
             ));
-            w.line(&ctx.push_error("/type"));
+            w.line(&ctx.push_error("/type", Message::Type(type_kw)));
             w.close("end");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn emit_dialect(schema: serde_json::Value, dialect: LuaDialect) -> String {
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            lua_dialect: dialect,
+            ..EmitOptions::default()
+        };
+        emit_with_options(&compiled, &opts)
+    }
+
+    #[test]
+    fn test_default_is_lua51() {
+        let compiled = compiler::compile(&json!({"type": "uint8"})).unwrap();
+        assert_eq!(
+            emit(&compiled),
+            emit_dialect(json!({"type": "uint8"}), LuaDialect::Lua51)
+        );
+    }
+
+    #[test]
+    fn test_lua51_integer_check() {
+        let code = emit_dialect(json!({"type": "uint8"}), LuaDialect::Lua51);
+        assert!(code.contains("return type(v) == \"number\" and v == math.floor(v)"));
+        assert!(!code.contains("math.type"));
+        assert!(!code.contains("-- Target:"));
+    }
+
+    #[test]
+    fn test_lua54_uses_math_type() {
+        let code = emit_dialect(json!({"type": "int32"}), LuaDialect::Lua54);
+        assert!(code.contains("-- Target: Lua 5.4"));
+        assert!(code.contains("local t = math.type(v)"));
+        assert!(code.contains("return t == \"float\" and v == math.floor(v)"));
+    }
+
+    #[test]
+    fn test_lua54_exits_with_goto() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"type": "uint8"}},
+            "optionalProperties": {"b": {"enum": ["x", "y"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            fail_fast: true,
+            suggestions: true,
+            lua_dialect: LuaDialect::Lua54,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("schemaPath = \"\" .. \"/properties/a\"}); goto done\n"));
+        assert!(code.contains("  end\n  ::done::\n  return add_suggestions(instance, e)\n"));
+        assert!(!code.contains("do return"));
+        assert!(code.contains("math.max(1, #word // 3)"));
+
+        let code = emit_with_options(
+            &compiled,
+            &EmitOptions {
+                lua_dialect: LuaDialect::Lua51,
+                ..opts
+            },
+        );
+        assert!(code.contains("do return add_suggestions(instance, e) end"));
+        assert!(!code.contains("goto"));
+        assert!(code.contains("math.floor(#word / 3)"));
+    }
+
+    #[test]
+    fn test_luajit_keeps_lua51_numbers() {
+        let code = emit_dialect(json!({"type": "int32"}), LuaDialect::LuaJit);
+        assert!(code.contains("-- Target: LuaJIT"));
+        assert!(!code.contains("math.type"));
+    }
+
+    #[test]
+    fn test_timestamp_patterns_shared_by_dialects() {
+        let helper = |code: &str| {
+            let start = code.find("local function is_rfc3339(s)").unwrap();
+            code[start..start + code[start..].find("\nend\n").unwrap()].to_string()
+        };
+        let schema = json!({"type": "timestamp"});
+        let lua51 = helper(&emit_dialect(schema.clone(), LuaDialect::Lua51));
+        for dialect in [LuaDialect::Lua53, LuaDialect::Lua54, LuaDialect::LuaJit] {
+            assert_eq!(helper(&emit_dialect(schema.clone(), dialect)), lua51);
+        }
+    }
+
+    fn emit_sentinel(schema: serde_json::Value, sentinel: LuaNullSentinel) -> String {
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            lua_null_sentinel: Some(sentinel),
+            ..EmitOptions::default()
+        };
+        emit_with_options(&compiled, &opts)
+    }
+
+    #[test]
+    fn test_dkjson_sentinel_default() {
+        let code = emit_sentinel(
+            json!({"type": "string", "nullable": true}),
+            LuaNullSentinel::Dkjson,
+        );
+        assert!(code.contains("local dkjson = require(\"dkjson\")"));
+        assert!(code.contains("if instance ~= nil and instance ~= dkjson.null then"));
+    }
+
+    #[test]
+    fn test_cjson_sentinel() {
+        let code = emit_sentinel(
+            json!({"type": "string", "nullable": true}),
+            LuaNullSentinel::Cjson,
+        );
+        assert!(code.contains("local cjson = require(\"cjson\")"));
+        assert!(code.contains("instance ~= cjson.null"));
+        assert!(code.contains("if v == cjson.null then return false end"));
+        assert!(!code.contains("dkjson"));
+    }
+
+    #[test]
+    fn test_custom_sentinel() {
+        let code = emit_sentinel(
+            json!({"type": "string", "nullable": true}),
+            LuaNullSentinel::Custom("ngx.null".into()),
+        );
+        assert!(code.contains("local json_null = ngx.null"));
+        assert!(code.contains("instance ~= json_null"));
+    }
+
+    #[test]
+    fn test_nil_sentinel() {
+        let code = emit_sentinel(
+            json!({"type": "string", "nullable": true}),
+            LuaNullSentinel::Nil,
+        );
+        assert!(!code.contains("require("));
+        assert!(code.contains("if instance ~= nil then"));
+    }
+
+    #[test]
+    fn test_optional_null_value_is_validated() {
+        let code = emit_sentinel(
+            json!({"optionalProperties": {"a": {"type": "string"}}}),
+            LuaNullSentinel::Dkjson,
+        );
+        assert!(code.contains("if instance[\"a\"] ~= nil then"));
+        assert!(!code.contains("instance[\"a\"] ~= dkjson.null"));
+    }
+
+    fn emit_empty_table(schema: serde_json::Value, strategy: LuaEmptyTable) -> String {
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            lua_empty_table: Some(strategy),
+            ..EmitOptions::default()
+        };
+        emit_with_options(&compiled, &opts)
+    }
+
+    #[test]
+    fn test_metatable_strategy_default() {
+        let schema = json!({"elements": {"type": "string"}});
+        let code = emit_empty_table(schema.clone(), LuaEmptyTable::Metatable);
+        assert_eq!(code, emit(&compiler::compile(&schema).unwrap()));
+        assert!(code.contains("mt.__jsontype == \"array\""));
+    }
+
+    #[test]
+    fn test_n_field_strategy() {
+        let code = emit_empty_table(
+            json!({"elements": {"type": "string"}}),
+            LuaEmptyTable::NField,
+        );
+        assert!(code.contains("return type(rawget(v, \"n\")) == \"number\""));
+        assert!(code.contains("return not is_array(v)"));
+        assert!(code.contains("for i = 1, instance.n do"));
+        assert!(code.contains("local elem = instance[i]"));
+        assert!(!code.contains("ipairs"));
+    }
+
+    #[test]
+    fn test_cjson_array_mt_strategy() {
+        let code = emit_empty_table(
+            json!({"values": {"type": "string"}}),
+            LuaEmptyTable::CjsonArrayMt,
+        );
+        assert!(code.contains("local cjson_array_mt = require(\"cjson\").array_mt"));
+        assert!(code.contains("mt == cjson_array_mt or mt == cjson_empty_array_mt"));
+        assert!(!code.contains("__jsontype"));
+    }
+
+    #[test]
+    fn test_lenient_strategy() {
+        let code = emit_empty_table(
+            json!({"properties": {"a": {"type": "string"}}}),
+            LuaEmptyTable::Lenient,
+        );
+        assert!(code.contains("return #v > 0 or next(v) == nil"));
+        assert!(code.contains("return #v == 0"));
+        assert!(!code.contains("getmetatable"));
+    }
+
+    #[test]
+    fn test_openresty_format() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        let opts = EmitOptions {
+            lua_format: LuaModuleFormat::OpenResty,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("-- Format: OpenResty module"));
+        assert!(code.contains("local cjson = require(\"cjson\")"));
+        assert!(code.contains("decoder.decode_array_with_array_mt(true)"));
+        assert!(code.contains("local cjson_safe = require(\"cjson.safe\")"));
+        assert!(code.contains("function M.access()"));
+        assert!(code.trim_end().ends_with("return M"));
+        assert!(!code.contains("dkjson"));
+    }
+
+    #[test]
+    fn test_enum_constants() {
+        let compiled = compiler::compile(&json!({"enum": ["a", "b"]})).unwrap();
+        let opts = EmitOptions {
+            enum_constants: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("M.ROOT_VALUES = { \"a\", \"b\" }"));
+    }
+
+    #[test]
+    fn test_descriptions_become_ldoc_comments() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string", "metadata": {"description": "Order id"}}},
+            "ref": "id"
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("--- Order id\nfunction validate_id(v, e, p, sp)"));
+    }
+
+    #[test]
+    fn test_self_test() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"examples": ["ok"]},
+            "definitions": {"id": {"type": "string", "metadata": {"examples": [5]}}},
+            "ref": "id"
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            self_test: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.self_test(decode)"));
+        assert!(code.contains("dkjson.decode(s, 1, dkjson.null)"));
+        assert!(code.contains("e = M.validate(decode(\"\\\"ok\\\"\"))"));
+        assert!(code.contains("validate_id(decode(\"5\"), e, \"\", \"/definitions/id\")"));
+    }
+
+    #[test]
+    fn test_deprecation_warnings() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"item": {"optionalProperties": {"code": {"metadata": {"deprecated": true}}}}},
+            "properties": {"items": {"elements": {"ref": "item"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            deprecation_warnings: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.warnings(instance)"));
+        assert!(code.contains("local warn_item\n\nfunction warn_item(v, w, p)"));
+        assert!(code.contains("warn_item(x0, w, \"\" .. \"/items\" .. \"/\" .. (i0 - 1))"));
+    }
+
+    #[test]
+    fn test_validate_at() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"address": {"properties": {"zip": {"type": "string"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            validate_at: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.validate_at(pointer, value)"));
+        assert!(code.contains("elseif pointer == \"/properties/address/properties/zip\" then"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["active", "pending"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "[\"/properties/status/enum\"] = { key = false, candidates = { \"active\", \"pending\" } },"
+        ));
+        assert!(code.contains("return add_suggestions(instance, e)"));
+    }
+
+    #[test]
+    fn test_coerce() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"page": {"type": "uint32"}, "q": {"type": "string"}},
+            "optionalProperties": {"debug": {"type": "boolean"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            coerce: true,
+            lua_dialect: LuaDialect::Lua54,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.coerce(instance)"));
+        assert!(code.contains("instance[\"page\"] = coerce_number(instance[\"page\"], true)"));
+        assert!(code.contains("instance[\"debug\"] = coerce_boolean(instance[\"debug\"])"));
+        assert!(code.contains("return math.tointeger(n) or n"));
+        // Locale-independent digits, as in the timestamp helper
+        assert!(code.contains("local int, rest = s:match(\"^%-?([0-9]+)(.*)$\")"));
+        assert!(!code.contains("%d"));
+        assert!(!emit(&compiled).contains("coerce"));
+    }
+
+    #[test]
+    fn test_strip_additional() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {
+                "b": {"properties": {"c": {"properties": {}}}, "additionalProperties": true}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            strip_additional: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.strip(instance)"));
+        assert!(code.contains("if k0 ~= \"a\" and k0 ~= \"b\" then instance[k0] = nil end"));
+        assert!(!emit(&compiled).contains("strip"));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"q": {"type": "string"}},
+            "optionalProperties": {
+                "page": {"type": "uint32", "metadata": {"default": 1}},
+                "tags": {"elements": {"type": "string"}, "metadata": {"default": ["new"]}}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            apply_defaults: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("function M.apply_defaults(instance)"));
+        assert!(code.contains("if instance[\"page\"] == nil then instance[\"page\"] = 1 end"));
+        assert!(
+            code.contains("instance[\"tags\"] = setmetatable({\"new\"}, {__jsontype = \"array\"})")
+        );
+        assert!(!emit(&compiled).contains("apply_defaults"));
+    }
+
+    #[test]
+    fn test_mock() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"head": {"ref": "node"}, "tags": {"elements": {"type": "string"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            mock: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("local mock_node\n"));
+        assert!(code.contains("function M.mock(seed)"));
+        assert!(code.contains("local v2 = setmetatable({}, {__jsontype = \"array\"})"));
+        assert!(!emit(&compiled).contains("mock"));
+    }
+
+    #[test]
+    fn test_shape() {
+        let compiled = compiler::compile(&json!({
+            "optionalProperties": {"status": {"enum": ["new", "done"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            shape: true,
+            lua_empty_table: Some(LuaEmptyTable::Lenient),
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code
+            .contains(r#"M.SHAPE = {["discriminators"] = {}, ["enums"] = {{["path"] = "/status""#));
+    }
+
+    #[test]
+    fn test_messages() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"tags": {"values": {"type": "string"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            messages: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "local function describe(v)\n  if v == nil or v == dkjson.null then return \"null\" end\n"
+        ));
+        assert!(code.contains("message = \"expected string, got \" .. describe(val)})"));
+        assert!(code.contains("message = \"missing required property 'tags'\"})"));
+        assert!(code.contains("message = \"unknown property '\" .. k .. \"'\"})"));
+        assert!(!emit(&compiled).contains("describe"));
+    }
+
+    #[test]
+    fn test_path_segments() {
+        let compiled = compiler::compile(&json!({"values": {"type": "string"}})).unwrap();
+        let opts = EmitOptions {
+            path_segments: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("local function path_segments(e)\n"));
+        assert!(code.contains("return path_segments(e)"));
+        assert!(!emit(&compiled).contains("path_segments"));
+    }
+}
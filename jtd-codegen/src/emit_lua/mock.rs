@@ -0,0 +1,211 @@
+/// `M.mock(seed)`: a random instance of the schema, built as described in
+/// [`crate::mock`] with a Park-Miller generator whose arithmetic stays
+/// within 2^53, so Lua 5.1 doubles and 5.3+ integers agree. Tables
+/// carry the array/object marks the configured `--lua-empty-table`
+/// strategy looks for, and JSON null is the configured sentinel. One
+/// `mock_<name>(r, d)` per definition; `d` is the ref depth.
+use super::emit::{def_fn_name, null_expr};
+use super::writer::{escape_lua, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::mock::{int_range, MAX_DEPTH, MAX_ITEMS, MAX_STRING};
+use crate::options::{EmitOptions, LuaEmptyTable};
+
+const HELPERS: &str = r#"-- Each call of the returned function gives an integer in [lo, hi]
+local function mock_rng(seed)
+  local state = seed % 2147483646 + 1
+  local function next_state()
+    state = state * 16807 % 2147483647
+    return state
+  end
+  return function(lo, hi)
+    local x = next_state() % 2097152 * 2147483647 + next_state()
+    return lo + x % (hi - lo + 1)
+  end
+end
+
+local function mock_string(r)
+  local chars = {}
+  for i = 1, r(0, {MAX_STRING}) do
+    chars[i] = string.char(97 + r(0, 25))
+  end
+  return table.concat(chars)
+end
+
+local function mock_timestamp(r)
+  local date = string.format("%04d-%02d-%02d", r(2000, 2030), r(1, 12), r(1, 28))
+  return string.format("%sT%02d:%02d:%02dZ", date, r(0, 23), r(0, 59), r(0, 59))
+end"#;
+
+/// Emit the generator helpers, one function per definition and `M.mock`.
+pub(super) fn emit_mock(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    for line in HELPERS.lines() {
+        w.line(&line.replace("{MAX_STRING}", &MAX_STRING.to_string()));
+    }
+    w.line("");
+    // Declared up front so generators can call ones defined after them.
+    if !schema.definitions.is_empty() {
+        let names: Vec<String> = schema.definitions.keys().map(|n| mock_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    for (name, node) in &schema.definitions {
+        let mut g = Gen { opts, n: 0 };
+        w.open(&format!("function {}(r, d)", mock_fn_name(name)));
+        let v = g.value(w, node);
+        w.line(&format!("return {v}"));
+        w.close("end");
+        w.line("");
+    }
+    w.line("--- A random valid instance; the same seed gives the same instance.");
+    w.open("function M.mock(seed)");
+    w.line("local r = mock_rng(seed)");
+    w.line("local d = 0");
+    let v = Gen { opts, n: 0 }.value(w, &schema.root);
+    w.line(&format!("return {v}"));
+    w.close("end");
+}
+
+fn mock_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "mock_", 1)
+}
+
+struct Gen<'a> {
+    opts: &'a EmitOptions,
+    /// Last local variable number used.
+    n: usize,
+}
+
+impl Gen<'_> {
+    /// Emit the statements building a value for `node`; returns the
+    /// expression holding it.
+    fn value(&mut self, w: &mut CodeWriter, node: &Node) -> String {
+        match node {
+            Node::Empty => self.null(),
+            Node::Ref { name } => format!("{}(r, d + 1)", mock_fn_name(name)),
+            Node::Type { type_kw } => match (type_kw, int_range(*type_kw)) {
+                (_, Some((lo, hi))) => format!("r({lo}, {hi})"),
+                (TypeKeyword::Boolean, _) => "r(0, 1) == 1".to_string(),
+                (TypeKeyword::String, _) => "mock_string(r)".to_string(),
+                (TypeKeyword::Timestamp, _) => "mock_timestamp(r)".to_string(),
+                _ => "r(-1000000, 1000000) / 1000".to_string(),
+            },
+            Node::Enum { values } => {
+                let items: Vec<String> = values
+                    .iter()
+                    .map(|v| format!("\"{}\"", escape_lua(v)))
+                    .collect();
+                format!("({{{}}})[r(1, {})]", items.join(", "), values.len())
+            }
+            Node::Elements { schema } => {
+                let v = self.fresh();
+                w.line(&format!("local {v} = {}", self.array()));
+                w.open(&format!("if d < {MAX_DEPTH} then"));
+                w.open(&format!("for _ = 1, r(0, {MAX_ITEMS}) do"));
+                let item = self.value(w, schema);
+                w.line(&format!("{v}[#{v} + 1] = {item}"));
+                w.close("end");
+                w.close("end");
+                if self.opts.lua_tables() == LuaEmptyTable::NField {
+                    w.line(&format!("{v}.n = #{v}"));
+                }
+                v
+            }
+            Node::Values { schema } => {
+                let v = self.fresh();
+                w.line(&format!("local {v} = {}", self.object()));
+                w.open(&format!("if d < {MAX_DEPTH} then"));
+                w.open(&format!("for _ = 1, r(0, {MAX_ITEMS}) do"));
+                w.line("local k = mock_string(r)");
+                if self.opts.lua_tables() == LuaEmptyTable::NField {
+                    // A key "n" would make the table look like an array
+                    w.line("if k == \"n\" then k = \"n_\" end");
+                }
+                let item = self.value(w, schema);
+                w.line(&format!("{v}[k] = {item}"));
+                w.close("end");
+                w.close("end");
+                v
+            }
+            Node::Properties { .. } => {
+                let v = self.fresh();
+                w.line(&format!("local {v} = {}", self.object()));
+                self.properties(w, node, &v);
+                v
+            }
+            Node::Discriminator { tag, mapping } => {
+                let v = self.fresh();
+                w.line(&format!("local {v} = {}", self.object()));
+                w.line(&format!("local i = r(1, {})", mapping.len()));
+                for (i, (value, variant)) in mapping.iter().enumerate() {
+                    let cond = format!("i == {}", i + 1);
+                    if i == 0 {
+                        w.open(&format!("if {cond} then"));
+                    } else {
+                        w.close_open(&format!("elseif {cond} then"));
+                    }
+                    w.line(&format!(
+                        "{v}[\"{}\"] = \"{}\"",
+                        escape_lua(tag),
+                        escape_lua(value)
+                    ));
+                    self.properties(w, variant, &v);
+                }
+                w.close("end");
+                v
+            }
+            Node::Nullable { inner } => {
+                let v = self.fresh();
+                w.line(&format!("local {v} = {}", self.null()));
+                w.open(&format!("if d < {MAX_DEPTH} and r(0, 3) ~= 0 then"));
+                let inner = self.value(w, inner);
+                w.line(&format!("{v} = {inner}"));
+                w.close("end");
+                v
+            }
+        }
+    }
+
+    /// Add the properties of the Properties node `node` to the table `v`.
+    fn properties(&mut self, w: &mut CodeWriter, node: &Node, v: &str) {
+        let Node::Properties {
+            required, optional, ..
+        } = node
+        else {
+            return;
+        };
+        for (key, child) in required {
+            let value = self.value(w, child);
+            w.line(&format!("{v}[\"{}\"] = {value}", escape_lua(key)));
+        }
+        for (key, child) in optional {
+            w.open(&format!("if d < {MAX_DEPTH} and r(0, 1) == 1 then"));
+            let value = self.value(w, child);
+            w.line(&format!("{v}[\"{}\"] = {value}", escape_lua(key)));
+            w.close("end");
+        }
+    }
+
+    fn null(&self) -> String {
+        null_expr(&self.opts.lua_null()).unwrap_or_else(|| "nil".to_string())
+    }
+
+    fn array(&self) -> &'static str {
+        match self.opts.lua_tables() {
+            LuaEmptyTable::Metatable => "setmetatable({}, {__jsontype = \"array\"})",
+            LuaEmptyTable::CjsonArrayMt => "setmetatable({}, cjson_array_mt)",
+            LuaEmptyTable::NField | LuaEmptyTable::Lenient => "{}",
+        }
+    }
+
+    fn object(&self) -> &'static str {
+        match self.opts.lua_tables() {
+            LuaEmptyTable::Metatable => "setmetatable({}, {__jsontype = \"object\"})",
+            _ => "{}",
+        }
+    }
+
+    fn fresh(&mut self) -> String {
+        self.n += 1;
+        format!("v{}", self.n)
+    }
+}
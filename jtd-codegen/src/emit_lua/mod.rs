@@ -1,5 +1,12 @@
+mod coerce;
 mod context;
+mod defaults;
 mod emit;
+mod mock;
+mod openresty;
+mod strip;
+mod suggest;
+mod warnings;
 mod writer;
 
-pub use emit::emit;
+pub use emit::{emit, emit_with_options};
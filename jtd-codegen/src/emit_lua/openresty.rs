@@ -0,0 +1,109 @@
+/// OpenResty packaging for the Lua validator: localised globals, shared
+/// `cjson.safe` decoder and encoder instances, and `ngx` helpers for
+/// access/rewrite phase handlers.
+///
+/// The module itself stays a plain `require`-able table with no globals, so
+/// it is safe under `lua_code_cache on` and across worker reloads.
+use super::writer::CodeWriter;
+use crate::options::{EmitOptions, LuaEmptyTable};
+
+/// Emit after `local M = {}`: cache globals as upvalues, build a decoder
+/// configured to match the validator's empty-table strategy, and an
+/// encoder writing empty tables, which only path segments can be, as `[]`.
+pub(super) fn emit_prelude(w: &mut CodeWriter, opts: &EmitOptions) {
+    w.line(
+        "local type, pairs, ipairs, next, tonumber, tostring = type, pairs, ipairs, next, tonumber, tostring",
+    );
+    w.line("local getmetatable, rawget, math, io = getmetatable, rawget, math, io");
+    w.line("local ngx = ngx");
+    w.line("local cjson_safe = require(\"cjson.safe\")");
+    w.line("local decoder = cjson_safe.new()");
+    if opts.lua_tables() == LuaEmptyTable::CjsonArrayMt {
+        w.line("decoder.decode_array_with_array_mt(true)");
+    }
+    w.line("local encoder = cjson_safe.new()");
+    w.line("encoder.encode_empty_table_as_object(false)");
+}
+
+/// Emit after `M.validate`: request body decoding and an access handler.
+pub(super) fn emit_handlers(w: &mut CodeWriter) {
+    w.line("-- Read and validate the request body. Returns the decoded instance, or");
+    w.line("-- nil plus an error list (or a string when the body is not JSON).");
+    w.open("function M.check_request_body()");
+    w.line("ngx.req.read_body()");
+    w.line("local body = ngx.req.get_body_data()");
+    w.open("if not body then");
+    w.line("local path = ngx.req.get_body_file()");
+    w.open("if path then");
+    w.line("local f = io.open(path, \"rb\")");
+    w.open("if f then");
+    w.line("body = f:read(\"*a\")");
+    w.line("f:close()");
+    w.close("end");
+    w.close("end");
+    w.close("end");
+    w.line("if not body then return nil, \"empty request body\" end");
+    w.line("local instance, err = decoder.decode(body)");
+    w.line("if instance == nil then return nil, \"invalid JSON: \" .. tostring(err) end");
+    w.line("local errors = M.validate(instance)");
+    w.line("if #errors > 0 then return nil, errors end");
+    w.line("return instance");
+    w.close("end");
+    w.line("");
+
+    w.line("-- access_by_lua_block { require(\"validator\").access() }");
+    w.line("-- Rejects invalid bodies with 400 and the error array as JSON, and bodies");
+    w.line("-- that are not JSON with 400 and {\"error\": reason}.");
+    w.open("function M.access()");
+    w.line("local instance, err = M.check_request_body()");
+    w.line("if instance ~= nil then return end");
+    w.line("ngx.status = ngx.HTTP_BAD_REQUEST");
+    w.line("ngx.header[\"Content-Type\"] = \"application/json\"");
+    w.open("if type(err) == \"table\" then");
+    w.line("ngx.say(encoder.encode(err))");
+    w.close_open("else");
+    w.line("ngx.say(encoder.encode({ error = err }))");
+    w.close("end");
+    w.line("return ngx.exit(ngx.HTTP_BAD_REQUEST)");
+    w.close("end");
+    w.line("");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prelude_configures_array_mt() {
+        let mut w = CodeWriter::new();
+        let opts = EmitOptions {
+            lua_empty_table: Some(LuaEmptyTable::CjsonArrayMt),
+            ..EmitOptions::default()
+        };
+        emit_prelude(&mut w, &opts);
+        let code = w.finish();
+        assert!(code.contains("local ngx = ngx"));
+        assert!(code.contains("local decoder = cjson_safe.new()"));
+        assert!(code.contains("decoder.decode_array_with_array_mt(true)"));
+        assert!(code.contains("encoder.encode_empty_table_as_object(false)"));
+    }
+
+    #[test]
+    fn test_prelude_without_array_mt() {
+        let mut w = CodeWriter::new();
+        emit_prelude(&mut w, &EmitOptions::default());
+        assert!(!w.finish().contains("decode_array_with_array_mt"));
+    }
+
+    #[test]
+    fn test_handlers() {
+        let mut w = CodeWriter::new();
+        emit_handlers(&mut w);
+        let code = w.finish();
+        assert!(code.contains("function M.check_request_body()"));
+        assert!(code.contains("local instance, err = decoder.decode(body)"));
+        assert!(code.contains("function M.access()"));
+        assert!(code.contains("ngx.say(encoder.encode(err))"));
+        assert!(code.contains("return ngx.exit(ngx.HTTP_BAD_REQUEST)"));
+    }
+}
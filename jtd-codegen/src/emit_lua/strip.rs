@@ -0,0 +1,103 @@
+/// `M.strip(instance)`: removes the keys that closed Properties schemas do
+/// not declare, following a [`StripPlan`]. As with `M.coerce`, tables are
+/// updated in place so the JSON library's object markers survive, and the
+/// instance is returned for chaining.
+use super::emit::def_fn_name;
+use super::writer::{escape_lua, CodeWriter};
+use crate::strip::{Shape, Strip, StripPlan};
+
+/// Emit the definition strippers and the `M.strip` entry point.
+pub(super) fn emit_strip(w: &mut CodeWriter, plan: &StripPlan) {
+    // Declared up front so strippers can call ones defined after them.
+    if !plan.definitions.is_empty() {
+        let names: Vec<String> = plan.definitions.keys().map(|n| strip_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    for (name, s) in &plan.definitions {
+        w.open(&format!("function {}(v)", strip_fn_name(name)));
+        emit_value(w, s, "v", 0);
+        w.close("end");
+        w.line("");
+    }
+    w.line("--- Remove the properties the schema does not declare from `instance` (in place).");
+    w.open("function M.strip(instance)");
+    if let Some(s) = &plan.root {
+        emit_value(w, s, "instance", 0);
+    }
+    w.line("return instance");
+    w.close("end");
+}
+
+fn strip_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "strip_", 1)
+}
+
+fn emit_value(w: &mut CodeWriter, s: &Strip, v: &str, depth: usize) {
+    match s {
+        Strip::Object(shape) => {
+            w.open(&format!("if is_object({v}) then"));
+            emit_shape(w, shape, v, depth);
+            w.close("end");
+        }
+        Strip::Array(inner) => {
+            let (i, x) = (format!("i{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_array({v}) then"));
+            w.open(&format!("for {i}, {x} in ipairs({v}) do"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close("end");
+            w.close("end");
+        }
+        Strip::Map(inner) => {
+            let (k, x) = (format!("k{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_object({v}) then"));
+            w.open(&format!("for {k}, {x} in pairs({v}) do"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close("end");
+            w.close("end");
+        }
+        Strip::Ref(name) => w.line(&format!("{}({v})", strip_fn_name(name))),
+        Strip::Tagged { tag, variants } => {
+            w.open(&format!("if is_object({v}) then"));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] == \"{}\"", escape_lua(tag), escape_lua(value));
+                if i == 0 {
+                    w.open(&format!("if {cond} then"));
+                } else {
+                    w.close_open(&format!("elseif {cond} then"));
+                }
+                emit_shape(w, shape, v, depth);
+            }
+            w.close("end");
+            w.close("end");
+        }
+    }
+}
+
+/// Drop unknown keys from the table `v`, then recurse. Clearing a field
+/// during `pairs` is allowed.
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize) {
+    if let Some(known) = &shape.known {
+        let k = format!("k{depth}");
+        w.open(&format!("for {k} in pairs({v}) do"));
+        if known.is_empty() {
+            w.line(&format!("{v}[{k}] = nil"));
+        } else {
+            let conds: Vec<String> = known
+                .iter()
+                .map(|key| format!("{k} ~= \"{}\"", escape_lua(key)))
+                .collect();
+            w.line(&format!(
+                "if {} then {v}[{k}] = nil end",
+                conds.join(" and ")
+            ));
+        }
+        w.close("end");
+    }
+    for (key, s) in &shape.props {
+        let child_v = format!("{v}[\"{}\"]", escape_lua(key));
+        w.open(&format!("if {child_v} ~= nil then"));
+        emit_value(w, s, &child_v, depth);
+        w.close("end");
+    }
+}
@@ -0,0 +1,94 @@
+/// Helpers behind the `suggestions` option: a table of allowed names per
+/// schema path and `add_suggestions(instance, e)`, which sets `suggestion` on
+/// the errors it can match. See [`crate::suggest`] for the rules; Lua
+/// strings are compared byte by byte.
+use super::writer::{escape_lua, CodeWriter};
+use crate::options::LuaDialect;
+use crate::suggest::SuggestSite;
+
+pub(super) fn emit_suggest_helpers(w: &mut CodeWriter, sites: &[SuggestSite], dialect: LuaDialect) {
+    w.open("local SUGGESTIONS = {");
+    for site in sites {
+        let candidates: Vec<String> = site
+            .candidates
+            .iter()
+            .map(|c| format!("\"{}\"", escape_lua(c)))
+            .collect();
+        w.line(&format!(
+            "[\"{}\"] = {{ key = {}, candidates = {{ {} }} }},",
+            escape_lua(&site.schema_path),
+            site.key,
+            candidates.join(", ")
+        ));
+    }
+    w.close("}");
+    w.line("");
+    w.open("local function edit_distance(a, b)");
+    w.line("local d = {}");
+    w.open("for i = 0, #a do");
+    w.line("d[i] = { [0] = i }");
+    w.close("end");
+    w.open("for j = 1, #b do");
+    w.line("d[0][j] = j");
+    w.close("end");
+    w.open("for i = 1, #a do");
+    w.open("for j = 1, #b do");
+    w.line("local cost = (a:byte(i) == b:byte(j)) and 0 or 1");
+    w.line("d[i][j] = math.min(d[i - 1][j] + 1, d[i][j - 1] + 1, d[i - 1][j - 1] + cost)");
+    w.open("if i > 1 and j > 1 and a:byte(i) == b:byte(j - 1) and a:byte(i - 1) == b:byte(j) then");
+    w.line("d[i][j] = math.min(d[i][j], d[i - 2][j - 2] + 1)");
+    w.close("end");
+    w.close("end");
+    w.close("end");
+    w.line("return d[#a][#b]");
+    w.close("end");
+    w.line("");
+    w.open("local function did_you_mean(word, candidates)");
+    w.line("local best");
+    if dialect.has_floor_division() {
+        w.line("local best_d = math.max(1, #word // 3) + 1");
+    } else {
+        w.line("local best_d = math.max(1, math.floor(#word / 3)) + 1");
+    }
+    w.open("for _, c in ipairs(candidates) do");
+    w.line("local d = edit_distance(word, c)");
+    w.open("if d < best_d then");
+    w.line("best, best_d = c, d");
+    w.close("end");
+    w.close("end");
+    w.line("return best");
+    w.close("end");
+    w.line("");
+    w.open("local function value_at(v, path)");
+    w.open("for seg in string.gmatch(path, \"/([^/]*)\") do");
+    w.open("if type(v) ~= \"table\" then");
+    w.line("return nil");
+    w.close("end");
+    w.open("if is_array(v) then");
+    w.line("local n = tonumber(seg)");
+    w.line("v = n and v[n + 1]");
+    w.close_open("else");
+    w.line("v = v[seg]");
+    w.close("end");
+    w.close("end");
+    w.line("return v");
+    w.close("end");
+    w.line("");
+    w.open("local function add_suggestions(instance, e)");
+    w.open("for _, err in ipairs(e) do");
+    w.line("local site = SUGGESTIONS[err.schemaPath]");
+    w.open("if site then");
+    w.line("local word");
+    w.open("if site.key then");
+    w.line("word = string.match(err.instancePath, \"([^/]*)$\")");
+    w.close_open("else");
+    w.line("word = value_at(instance, err.instancePath)");
+    w.close("end");
+    w.open("if type(word) == \"string\" then");
+    w.line("err.suggestion = did_you_mean(word, site.candidates)");
+    w.close("end");
+    w.close("end");
+    w.close("end");
+    w.line("return e");
+    w.close("end");
+}
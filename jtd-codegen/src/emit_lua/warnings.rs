@@ -0,0 +1,104 @@
+/// `M.warnings(instance)`: reports present properties whose schema is marked
+/// `metadata.deprecated`, following a [`WarningPlan`]. Reuses the module's
+/// `is_object`/`is_array` helpers, so it honours `--lua-empty-table`.
+use super::context::pointer_segment;
+use super::emit::def_fn_name;
+use super::writer::{escape_lua, CodeWriter};
+use crate::compiler::escape_pointer;
+use crate::deprecation::{Prop, Walk, WarningPlan};
+
+/// Emit the definition walkers and the `M.warnings` entry point.
+pub(super) fn emit_warnings(w: &mut CodeWriter, plan: &WarningPlan) {
+    // Declared up front so walkers can call ones defined after them.
+    if !plan.definitions.is_empty() {
+        let names: Vec<String> = plan.definitions.keys().map(|n| warn_fn_name(n)).collect();
+        w.line(&format!("local {}", names.join(", ")));
+        w.line("");
+    }
+    for (name, walk) in &plan.definitions {
+        w.open(&format!("function {}(v, w, p)", warn_fn_name(name)));
+        emit_walk(w, walk, "v", "p", 0);
+        w.close("end");
+        w.line("");
+    }
+    w.line("--- Deprecated properties present in `instance`, as {instancePath, schemaPath}.");
+    w.open("function M.warnings(instance)");
+    w.line("local w = {}");
+    if let Some(walk) = &plan.root {
+        emit_walk(w, walk, "instance", "\"\"", 0);
+    }
+    w.line("return w");
+    w.close("end");
+}
+
+fn warn_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "warn_", 1)
+}
+
+fn emit_walk(w: &mut CodeWriter, walk: &Walk, v: &str, p: &str, depth: usize) {
+    match walk {
+        Walk::Object(props) => {
+            w.open(&format!("if is_object({v}) then"));
+            emit_props(w, props, v, p, depth);
+            w.close("end");
+        }
+        Walk::Array(inner) => {
+            let (i, x) = (format!("i{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_array({v}) then"));
+            w.open(&format!("for {i}, {x} in ipairs({v}) do"));
+            // JTD paths are 0-based, Lua is 1-based
+            let child_p = format!("{p} .. \"/\" .. ({i} - 1)");
+            emit_walk(w, inner, &x, &child_p, depth + 1);
+            w.close("end");
+            w.close("end");
+        }
+        Walk::Map(inner) => {
+            let (k, x) = (format!("k{depth}"), format!("x{depth}"));
+            w.open(&format!("if is_object({v}) then"));
+            w.open(&format!("for {k}, {x} in pairs({v}) do"));
+            emit_walk(
+                w,
+                inner,
+                &x,
+                &format!("{p} .. \"/\" .. {}", pointer_segment(&k)),
+                depth + 1,
+            );
+            w.close("end");
+            w.close("end");
+        }
+        Walk::Ref(name) => w.line(&format!("{}({v}, w, {p})", warn_fn_name(name))),
+        Walk::Tagged { tag, variants } => {
+            w.open(&format!("if is_object({v}) then"));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{v}[\"{}\"] == \"{}\"", escape_lua(tag), escape_lua(value));
+                if i == 0 {
+                    w.open(&format!("if {cond} then"));
+                } else {
+                    w.close_open(&format!("elseif {cond} then"));
+                }
+                emit_props(w, props, v, p, depth);
+            }
+            w.close("end");
+            w.close("end");
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[Prop], v: &str, p: &str, depth: usize) {
+    for prop in props {
+        let key = escape_lua(&prop.key);
+        let child_v = format!("{v}[\"{key}\"]");
+        let child_p = format!("{p} .. \"/{}\"", escape_lua(&escape_pointer(&prop.key)));
+        w.open(&format!("if {child_v} ~= nil then"));
+        if prop.deprecated {
+            w.line(&format!(
+                "table.insert(w, {{instancePath = {child_p}, schemaPath = \"{}\"}})",
+                escape_lua(&prop.schema_path)
+            ));
+        }
+        if let Some(child) = &prop.child {
+            emit_walk(w, child, &child_v, &child_p, depth);
+        }
+        w.close("end");
+    }
+}
@@ -0,0 +1,120 @@
+/// `coerce(instance)`: returns a copy of `instance` with string-typed
+/// booleans and numbers converted where the schema expects them, following a
+/// [`CoercePlan`]. Dicts and lists on the way to a converted slot are shallow
+/// copies, so the caller's document is never modified.
+use super::emit::def_fn_name;
+use super::writer::{escape_py, CodeWriter};
+use crate::coerce::{Coerce, CoercePlan, Scalar};
+
+/// Emit the conversion helpers, the definition coercers and the `coerce`
+/// entry point. Needs `re`, which the module header imports.
+pub(super) fn emit_coerce(w: &mut CodeWriter, plan: &CoercePlan) {
+    w.line("_JSON_NUMBER = re.compile(r\"-?(0|[1-9][0-9]*)(\\.[0-9]+)?([eE][+-]?[0-9]+)?\\Z\")");
+    w.line("");
+    w.line("");
+    w.open("def _coerce_boolean(x: object) -> object");
+    w.line("return {\"true\": True, \"false\": False}.get(x, x) if isinstance(x, str) else x");
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.open("def _coerce_number(x: object, integer: bool) -> object");
+    w.open("if isinstance(x, str) and _JSON_NUMBER.match(x)");
+    w.line("n = float(x) if any(c in x for c in \".eE\") else int(x)");
+    w.close_open("elif isinstance(x, float) and integer");
+    w.line("n = x");
+    w.close_open("else");
+    w.line("return x");
+    w.dedent();
+    w.open("if integer and isinstance(n, float)");
+    w.line("return int(n) if n.is_integer() else x");
+    w.dedent();
+    w.line("return n if abs(n) < float(\"inf\") else x");
+    w.dedent();
+    w.line("");
+    w.line("");
+    for (name, c) in &plan.definitions {
+        w.open(&format!(
+            "def {}(v: object) -> object",
+            coerce_fn_name(name)
+        ));
+        emit_value(w, c, "v", 0);
+        w.line("return v");
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open("def coerce(instance: object) -> object");
+    w.line("\"\"\"A copy of instance with \"42\" / \"true\" style strings converted where the schema expects numbers or booleans.\"\"\"");
+    w.line("v = instance");
+    if let Some(c) = &plan.root {
+        emit_value(w, c, "v", 0);
+    }
+    w.line("return v");
+    w.dedent();
+}
+
+fn coerce_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "coerce_", 1)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, c: &Coerce, v: &str, depth: usize) {
+    match c {
+        Coerce::Scalar(Scalar::Boolean) => w.line(&format!("{v} = _coerce_boolean({v})")),
+        Coerce::Scalar(Scalar::Integer) => w.line(&format!("{v} = _coerce_number({v}, True)")),
+        Coerce::Scalar(Scalar::Float) => w.line(&format!("{v} = _coerce_number({v}, False)")),
+        Coerce::Ref(name) => w.line(&format!("{v} = {}({v})", coerce_fn_name(name))),
+        Coerce::Object(props) => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.line(&format!("{v} = dict({v})"));
+            emit_props(w, props, v, depth);
+            w.dedent();
+        }
+        Coerce::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if isinstance({v}, list)"));
+            w.line(&format!("{v} = list({v})"));
+            w.open(&format!("for {i} in range(len({v}))"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Coerce::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.line(&format!("{v} = dict({v})"));
+            w.open(&format!("for {k} in {v}"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Coerce::Tagged { tag, variants } => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.line(&format!("{v} = dict({v})"));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!(
+                    "{v}.get(\"{}\") == \"{}\"",
+                    escape_py(tag),
+                    escape_py(value)
+                );
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("elif {cond}"));
+                }
+                emit_props(w, props, v, depth);
+            }
+            w.dedent();
+            w.dedent();
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[(String, Coerce)], v: &str, depth: usize) {
+    for (key, c) in props {
+        let key = escape_py(key);
+        w.open(&format!("if \"{key}\" in {v}"));
+        emit_value(w, c, &format!("{v}[\"{key}\"]"), depth);
+        w.dedent();
+    }
+}
@@ -3,7 +3,11 @@
 /// Tracks the Python expressions for the current value, error list,
 /// instance path, and schema path. Each descent into a child node
 /// produces a new context via pure methods -- no mutation.
+use super::emit::DESCRIBE_FN;
 use super::writer::escape_py;
+use crate::compiler::escape_pointer;
+use crate::messages::{Message, Subject};
+use crate::options::PyNumbers;
 
 #[derive(Clone)]
 pub struct EmitContext {
@@ -17,6 +21,21 @@ pub struct EmitContext {
     pub sp: String,
     /// Nesting depth for generating unique loop variable names.
     pub depth: usize,
+    /// Statement leaving the function after an error, for fail-fast
+    /// validation; `None` collects every error.
+    pub stop: Option<String>,
+    /// The variable holding the error limit, with `--max-errors`: `stop`
+    /// then only runs once there are that many errors.
+    pub limit: Option<String>,
+    /// Whether errors carry a `"message"`, with `--messages`.
+    pub messages: bool,
+    /// Whether the checks are those of an `is_valid` predicate: a failure
+    /// runs `stop`, which returns `False`, without recording an error.
+    pub predicate: bool,
+    /// How numbers are checked, with `--py-numbers`.
+    pub numbers: PyNumbers,
+    /// Whether each node's checks are wrapped in source-map markers.
+    pub source_map: bool,
 }
 
 impl EmitContext {
@@ -28,6 +47,12 @@ impl EmitContext {
             ip: "\"\"".into(),
             sp: "\"\"".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
+            numbers: PyNumbers::Loose,
+            source_map: false,
         }
     }
 
@@ -39,6 +64,30 @@ impl EmitContext {
             ip: "p".into(),
             sp: "sp".into(),
             depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
+            numbers: PyNumbers::Loose,
+            source_map: false,
+        }
+    }
+
+    /// Context for a `validateAt` branch: the fragment is `value` and the
+    /// schema path starts at the requested `pointer`.
+    pub fn at_pointer() -> Self {
+        Self {
+            val: "value".into(),
+            err: "e".into(),
+            ip: "\"\"".into(),
+            sp: "pointer".into(),
+            depth: 0,
+            stop: None,
+            limit: None,
+            messages: false,
+            predicate: false,
+            numbers: PyNumbers::Loose,
+            source_map: false,
         }
     }
 
@@ -65,9 +114,19 @@ impl EmitContext {
         Self {
             val: format!("{}[\"{}\"]", self.val, escape_py(key)),
             err: self.err.clone(),
-            ip: format!("{} + \"/{}\"", self.ip, escape_py(key)),
-            sp: format!("{} + \"/properties/{}\"", self.sp, escape_py(key)),
+            ip: format!("{} + \"/{}\"", self.ip, escape_py(&escape_pointer(key))),
+            sp: format!(
+                "{} + \"/properties/{}\"",
+                self.sp,
+                escape_py(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
+            numbers: self.numbers,
+            source_map: self.source_map,
         }
     }
 
@@ -76,9 +135,19 @@ impl EmitContext {
         Self {
             val: format!("{}[\"{}\"]", self.val, escape_py(key)),
             err: self.err.clone(),
-            ip: format!("{} + \"/{}\"", self.ip, escape_py(key)),
-            sp: format!("{} + \"/optionalProperties/{}\"", self.sp, escape_py(key)),
+            ip: format!("{} + \"/{}\"", self.ip, escape_py(&escape_pointer(key))),
+            sp: format!(
+                "{} + \"/optionalProperties/{}\"",
+                self.sp,
+                escape_py(&escape_pointer(key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
+            numbers: self.numbers,
+            source_map: self.source_map,
         }
     }
 
@@ -90,6 +159,12 @@ impl EmitContext {
             ip: format!("{} + \"/\" + str({})", self.ip, idx_var),
             sp: format!("{} + \"/elements\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
+            numbers: self.numbers,
+            source_map: self.source_map,
         }
     }
 
@@ -98,9 +173,15 @@ impl EmitContext {
         Self {
             val: format!("{}[{}]", self.val, key_var),
             err: self.err.clone(),
-            ip: format!("{} + \"/\" + {}", self.ip, key_var),
+            ip: format!("{} + \"/\" + {}", self.ip, pointer_segment(key_var)),
             sp: format!("{} + \"/values\"", self.sp),
             depth: self.depth + 1,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
+            numbers: self.numbers,
+            source_map: self.source_map,
         }
     }
 
@@ -110,27 +191,72 @@ impl EmitContext {
             val: self.val.clone(),
             err: self.err.clone(),
             ip: self.ip.clone(),
-            sp: format!("{} + \"/mapping/{}\"", self.sp, escape_py(variant_key)),
+            sp: format!(
+                "{} + \"/mapping/{}\"",
+                self.sp,
+                escape_py(&escape_pointer(variant_key))
+            ),
             depth: self.depth,
+            stop: self.stop.clone(),
+            limit: self.limit.clone(),
+            messages: self.messages,
+            predicate: self.predicate,
+            numbers: self.numbers,
+            source_map: self.source_map,
         }
     }
 
-    /// Push an error with the given schema path suffix.
+    /// This context, leaving the function with `stop` after an error.
+    pub fn with_stop(self, stop: Option<String>) -> Self {
+        Self { stop, ..self }
+    }
+
+    /// This context, leaving the function with `stop` once there are
+    /// `limit` errors rather than after any.
+    pub fn with_limit(self, limit: Option<String>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// This context, adding a `"message"` to each error when `messages`.
+    pub fn with_messages(self, messages: bool) -> Self {
+        Self { messages, ..self }
+    }
+
+    /// This context, checking numbers as `numbers` says.
+    pub fn with_numbers(self, numbers: PyNumbers) -> Self {
+        Self { numbers, ..self }
+    }
+
+    /// This context, marking each node's checks for a source map.
+    pub fn with_source_map(self, source_map: bool) -> Self {
+        Self { source_map, ..self }
+    }
+
+    /// This context, for a predicate leaving with `fail` at the first
+    /// failure.
+    pub fn predicate(self, fail: &str) -> Self {
+        Self {
+            stop: Some(fail.to_string()),
+            limit: None,
+            messages: false,
+            predicate: true,
+            ..self
+        }
+    }
+
+    /// Push an error with the given schema path suffix, about `msg`.
     /// Returns the Python statement string.
-    pub fn push_error(&self, sp_suffix: &str) -> String {
+    pub fn push_error(&self, sp_suffix: &str, msg: Message) -> String {
         let sp_expr = if sp_suffix.is_empty() {
             self.sp.clone()
         } else {
             format!("{} + \"{}\"", self.sp, sp_suffix)
         };
-        format!(
-            "{}.append({{\"instancePath\": {}, \"schemaPath\": {}}})",
-            self.err, self.ip, sp_expr
-        )
+        self.push(&self.ip, &sp_expr, msg)
     }
 
     /// Push an error with a custom instance path suffix and schema path suffix.
-    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str) -> String {
+    pub fn push_error_at(&self, ip_suffix: &str, sp_suffix: &str, msg: Message) -> String {
         let ip_expr = if ip_suffix.is_empty() {
             self.ip.clone()
         } else {
@@ -141,30 +267,96 @@ impl EmitContext {
         } else {
             format!("{} + \"{}\"", self.sp, sp_suffix)
         };
-        format!(
-            "{}.append({{\"instancePath\": {}, \"schemaPath\": {}}})",
-            self.err, ip_expr, sp_expr
-        )
+        self.push(&ip_expr, &sp_expr, msg)
     }
 
     /// Push an error with a dynamic instance path expression.
-    pub fn push_error_dynamic(&self, ip_expr_suffix: &str, sp_suffix: &str) -> String {
+    pub fn push_error_dynamic(
+        &self,
+        ip_expr_suffix: &str,
+        sp_suffix: &str,
+        msg: Message,
+    ) -> String {
         let ip_expr = format!("{} + {}", self.ip, ip_expr_suffix);
         let sp_expr = if sp_suffix.is_empty() {
             self.sp.clone()
         } else {
             format!("{} + \"{}\"", self.sp, sp_suffix)
         };
-        format!(
-            "{}.append({{\"instancePath\": {}, \"schemaPath\": {}}})",
-            self.err, ip_expr, sp_expr
-        )
+        self.push(&ip_expr, &sp_expr, msg)
+    }
+
+    /// Push an error with the given path expressions, then leave the
+    /// function when failing fast or at the limit: one line, or with a
+    /// limit two, the push and then the `if`. A predicate just leaves.
+    pub fn push(&self, ip_expr: &str, sp_expr: &str, msg: Message) -> String {
+        if self.predicate {
+            return self.fail();
+        }
+        let message = if self.messages {
+            format!(", \"message\": {}", self.message(msg))
+        } else {
+            String::new()
+        };
+        let push = format!(
+            "{}.append({{\"instancePath\": {ip_expr}, \"schemaPath\": {sp_expr}{message}}})",
+            self.err
+        );
+        let Some(stop) = &self.stop else {
+            return push;
+        };
+        match &self.limit {
+            Some(limit) => format!("{push}\nif len({}) >= {limit}: {stop}", self.err),
+            None => format!("{push}; {stop}"),
+        }
+    }
+
+    /// The Python expression for the message about `msg`.
+    pub fn message(&self, msg: Message) -> String {
+        let parts = msg.parts();
+        let text = format!("\"{}\"", escape_py(&parts.text));
+        let subject = match parts.subject {
+            Subject::None => return text,
+            Subject::Value => format!("{DESCRIBE_FN}({})", self.val),
+            Subject::Member(key) => format!("{DESCRIBE_FN}({}[\"{}\"])", self.val, escape_py(key)),
+            Subject::MemberString(key) => format!("{}[\"{}\"]", self.val, escape_py(key)),
+            Subject::Variable(var) => var.to_string(),
+        };
+        if parts.end.is_empty() {
+            format!("{text} + {subject}")
+        } else {
+            format!("{text} + {subject} + \"{}\"", escape_py(parts.end))
+        }
+    }
+
+    /// The statement a predicate runs on failure.
+    pub fn fail(&self) -> String {
+        self.stop
+            .clone()
+            .unwrap_or_else(|| "return False".to_string())
+    }
+
+    /// After a call that may have pushed errors: leave the function when
+    /// failing fast and it did.
+    pub fn check_stop(&self) -> Option<String> {
+        let stop = self.stop.as_ref()?;
+        Some(match &self.limit {
+            Some(limit) => format!("if len({}) >= {limit}: {stop}", self.err),
+            None => format!("if {}: {stop}", self.err),
+        })
     }
 }
 
+/// The Python expression escaping the key in `key_var` as a JSON Pointer
+/// segment (RFC 6901), so keys with `/` or `~` give unambiguous paths.
+pub fn pointer_segment(key_var: &str) -> String {
+    format!("{key_var}.replace(\"~\", \"~0\").replace(\"/\", \"~1\")")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::TypeKeyword;
 
     #[test]
     fn test_root_context() {
@@ -212,14 +404,17 @@ mod tests {
         let ctx = EmitContext::definition();
         let child = ctx.values_entry("k");
         assert_eq!(child.val, "v[k]");
-        assert_eq!(child.ip, "p + \"/\" + k");
+        assert_eq!(
+            child.ip,
+            "p + \"/\" + k.replace(\"~\", \"~0\").replace(\"/\", \"~1\")"
+        );
         assert_eq!(child.sp, "sp + \"/values\"");
     }
 
     #[test]
     fn test_push_error_no_suffix() {
         let ctx = EmitContext::root();
-        let stmt = ctx.push_error("");
+        let stmt = ctx.push_error("", Message::TooDeep);
         assert_eq!(
             stmt,
             "e.append({\"instancePath\": \"\", \"schemaPath\": \"\"})"
@@ -229,7 +424,7 @@ mod tests {
     #[test]
     fn test_push_error_with_suffix() {
         let ctx = EmitContext::root();
-        let stmt = ctx.push_error("/type");
+        let stmt = ctx.push_error("/type", Message::Type(TypeKeyword::String));
         assert_eq!(
             stmt,
             "e.append({\"instancePath\": \"\", \"schemaPath\": \"\" + \"/type\"})"
@@ -239,7 +434,11 @@ mod tests {
     #[test]
     fn test_push_error_at() {
         let ctx = EmitContext::definition();
-        let stmt = ctx.push_error_at("/name", "/properties/name");
+        let stmt = ctx.push_error_at(
+            "/name",
+            "/properties/name",
+            Message::MissingProperty("name"),
+        );
         assert_eq!(
             stmt,
             "e.append({\"instancePath\": p + \"/name\", \"schemaPath\": sp + \"/properties/name\"})"
@@ -249,13 +448,70 @@ mod tests {
     #[test]
     fn test_push_error_dynamic() {
         let ctx = EmitContext::root();
-        let stmt = ctx.push_error_dynamic("\"/\" + k", "");
+        let stmt = ctx.push_error_dynamic("\"/\" + k", "", Message::UnknownProperty("k"));
         assert_eq!(
             stmt,
             "e.append({\"instancePath\": \"\" + \"/\" + k, \"schemaPath\": \"\"})"
         );
     }
 
+    #[test]
+    fn test_push_error_message() {
+        let ctx = EmitContext::definition().with_messages(true);
+        assert_eq!(
+            ctx.push_error("/type", Message::Type(TypeKeyword::Uint8)),
+            "e.append({\"instancePath\": p, \"schemaPath\": sp + \"/type\", \"message\": \"expected uint8, got \" + _describe(v)})"
+        );
+        assert_eq!(
+            ctx.message(Message::TagNotString("kind")),
+            "\"discriminator 'kind' must be a string, got \" + _describe(v[\"kind\"])"
+        );
+    }
+
+    /// Keys are string literals in the generated code: whatever they hold,
+    /// each literal must decode back to the key (or its pointer segment).
+    #[test]
+    fn test_hostile_keys() {
+        let keys = [
+            "q\"uote",
+            "back\\slash",
+            "it's",
+            "new\nline",
+            "tab\tcr\r",
+            "nul\0",
+            "\u{1b}[0m",
+            "\u{2028}",
+            "${x}",
+            "`tick`",
+            "#{y}",
+            "]]",
+            "\\\"",
+            "a/b~c",
+        ];
+        let literal = |s: &str| serde_json::from_str::<String>(s).unwrap();
+        for key in keys {
+            let ctx = EmitContext::definition().required_prop(key);
+            let val = ctx
+                .val
+                .strip_prefix("v[")
+                .unwrap()
+                .strip_suffix(']')
+                .unwrap();
+            assert_eq!(literal(val), key);
+            let ip = ctx.ip.strip_prefix("p + ").unwrap();
+            assert_eq!(literal(ip), format!("/{}", escape_pointer(key)));
+            let ctx = EmitContext::definition().optional_prop(key);
+            let sp = ctx.sp.strip_prefix("sp + ").unwrap();
+            assert_eq!(
+                literal(sp),
+                format!("/optionalProperties/{}", escape_pointer(key))
+            );
+            let ctx = EmitContext::definition().discrim_variant(key);
+            let sp = ctx.sp.strip_prefix("sp + ").unwrap();
+            assert_eq!(literal(sp), format!("/mapping/{}", escape_pointer(key)));
+        }
+    }
+
     #[test]
     fn test_nested_descent() {
         let root = EmitContext::root();
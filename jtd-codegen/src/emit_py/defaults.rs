@@ -0,0 +1,136 @@
+/// `apply_defaults(instance)`: returns a copy of `instance` with absent
+/// optional properties set to their `metadata.default`, following a
+/// [`DefaultsPlan`]. Dicts and lists on the way are shallow copies and
+/// defaults are written as literals, so nothing is shared with the caller's
+/// document or between calls.
+use super::emit::def_fn_name;
+use super::writer::{escape_py, CodeWriter};
+use crate::defaults::{DefaultsPlan, Fill, Shape};
+use serde_json::Value;
+
+/// Emit the definition fillers and the `apply_defaults` entry point.
+pub(super) fn emit_defaults(w: &mut CodeWriter, plan: &DefaultsPlan) {
+    for (name, fill) in &plan.definitions {
+        w.open(&format!(
+            "def {}(v: object) -> object",
+            defaults_fn_name(name)
+        ));
+        emit_value(w, fill, "v", 0);
+        w.line("return v");
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open("def apply_defaults(instance: object) -> object");
+    w.line("\"\"\"A copy of instance with absent optional properties set to their metadata.default.\"\"\"");
+    w.line("v = instance");
+    if let Some(fill) = &plan.root {
+        emit_value(w, fill, "v", 0);
+    }
+    w.line("return v");
+    w.dedent();
+}
+
+fn defaults_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "defaults_", 1)
+}
+
+/// A Python expression building `value` the way `json.loads` would.
+pub(super) fn py_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("\"{}\"", escape_py(s)),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(py_literal).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("\"{}\": {}", escape_py(k), py_literal(v)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, fill: &Fill, v: &str, depth: usize) {
+    match fill {
+        Fill::Object(shape) => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            emit_shape(w, shape, v, depth);
+            w.dedent();
+        }
+        Fill::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if isinstance({v}, list)"));
+            w.line(&format!("{v} = list({v})"));
+            w.open(&format!("for {i} in range(len({v}))"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Fill::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.line(&format!("{v} = dict({v})"));
+            w.open(&format!("for {k} in {v}"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Fill::Ref(name) => w.line(&format!("{v} = {}({v})", defaults_fn_name(name))),
+        Fill::Tagged { tag, variants } => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!(
+                    "{v}.get(\"{}\") == \"{}\"",
+                    escape_py(tag),
+                    escape_py(value)
+                );
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("elif {cond}"));
+                }
+                emit_shape(w, shape, v, depth);
+            }
+            w.dedent();
+            w.dedent();
+        }
+    }
+}
+
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize) {
+    w.line(&format!("{v} = dict({v})"));
+    for (key, default) in &shape.defaults {
+        let key = escape_py(key);
+        w.open(&format!("if \"{key}\" not in {v}"));
+        w.line(&format!("{v}[\"{key}\"] = {}", py_literal(default)));
+        w.dedent();
+    }
+    for (key, fill) in &shape.props {
+        let key = escape_py(key);
+        w.open(&format!("if \"{key}\" in {v}"));
+        emit_value(w, fill, &format!("{v}[\"{key}\"]"), depth);
+        w.dedent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_py_literal() {
+        assert_eq!(
+            py_literal(&json!({"a": [1, 2.5, null], "b": true, "c": "q\""})),
+            "{\"a\": [1, 2.5, None], \"b\": True, \"c\": \"q\\\"\"}"
+        );
+    }
+}
@@ -1,59 +1,280 @@
 /// Top-level composition: walks a CompiledSchema AST and produces
 /// a complete Python validation module by dispatching to per-node emitters.
-use super::context::EmitContext;
+use super::coerce::emit_coerce;
+use super::context::{pointer_segment, EmitContext};
+use super::defaults::{emit_defaults, py_literal};
+use super::mock::emit_mock;
+use super::strip::emit_strip;
+use super::suggest::emit_suggest_helpers;
+use super::warnings::emit_warnings;
 use super::writer::{escape_py, CodeWriter};
 use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::coerce::coerce_plan;
+use crate::compiler::escape_pointer;
+use crate::defaults::defaults_plan;
+use crate::deprecation::warning_plan;
+use crate::docs::function_doc;
+use crate::enums::enum_constants;
+use crate::messages::Message;
+use crate::options::{EmitOptions, PyNumbers};
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::shape::shape;
+use crate::source_map;
+use crate::strip::strip_plan;
+use crate::subschemas::sub_schemas;
+use crate::suggest::suggest_sites;
 use std::collections::BTreeMap;
 
 /// Emit a complete Python 3.13+ module from a compiled schema.
 pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a complete Python module from a compiled schema with the given options.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
     let mut w = CodeWriter::new();
 
     w.line("# fmt: off");
     w.line("# Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
     w.line("# Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("python") {
+            w.line(&format!("# {line}"));
+        }
+    }
 
-    if needs_timestamp(&schema.root, &schema.definitions) {
+    let timestamps = needs_timestamp(&schema.root, &schema.definitions);
+    if opts.mock {
+        w.line("import random");
+    }
+    if timestamps || opts.coerce {
         w.line("import re");
-        w.line("from datetime import datetime");
+    }
+    let recursive = recursive(&schema.definitions);
+    if !recursive.is_empty() {
+        w.line("import threading");
+    }
+    if timestamps {
         w.line("");
         emit_timestamp_helper(&mut w);
     }
 
     w.line("");
 
+    if opts.enum_constants {
+        let constants = enum_constants(&[("", &schema.root)], &schema.definitions);
+        for c in &constants {
+            let items: Vec<String> = c
+                .values
+                .iter()
+                .map(|v| format!("\"{}\"", escape_py(v)))
+                .collect();
+            w.line(&format!(
+                "{}: frozenset[str] = frozenset({{{}}})",
+                c.name,
+                items.join(", ")
+            ));
+        }
+        if !constants.is_empty() {
+            w.line("");
+        }
+    }
+
+    if opts.suggestions {
+        emit_suggest_helpers(&mut w, &suggest_sites(schema));
+        w.line("");
+        w.line("");
+    }
+
+    if !recursive.is_empty() {
+        // Per thread, as threads may validate concurrently
+        w.line("_ref_depth = threading.local()");
+        w.line("");
+        w.line("");
+    }
+
+    if opts.messages {
+        emit_describe_helper(&mut w);
+        w.line("");
+        w.line("");
+    }
+
+    if opts.path_segments {
+        emit_segments_helper(&mut w);
+        w.line("");
+        w.line("");
+    }
+
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    let limit_var = limit.map(|_| LIMIT_VAR.to_string());
+
     // Emit one function per definition
     for (name, node) in &schema.definitions {
         let fn_name = def_fn_name(name);
-        w.open(&format!("def {fn_name}(v, e, p, sp)"));
-        let ctx = EmitContext::definition();
+        let limit_arg = limit_var
+            .as_ref()
+            .map_or(String::new(), |l| format!(", {l}: int"));
+        w.open(&format!(
+            "def {fn_name}(v: object, e: {ERRORS}, p: str, sp: str{limit_arg}) -> None"
+        ));
+        emit_docstring(
+            &mut w,
+            &function_doc(
+                schema,
+                &format!("/definitions/{}", escape_pointer(name)),
+                node,
+            ),
+        );
+        let ctx = EmitContext::definition()
+            .with_messages(opts.messages)
+            .with_numbers(opts.py_numbers)
+            .with_source_map(opts.source_map);
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            emit_depth_enter(&mut w, &ctx);
+        }
+        let ctx = ctx
+            .with_stop(stops.then(|| definition_stop(guarded)))
+            .with_limit(limit_var.clone());
         if is_no_op(node) {
             w.line("pass");
         } else {
             emit_node(&mut w, node, &ctx, None);
         }
+        if guarded {
+            w.line("_ref_depth.n = depth");
+        }
         w.dedent();
         w.line("");
     }
 
     // Emit the exported validate() entry point
-    w.open("def validate(instance)");
-    w.line("e = []");
-    let root_ctx = EmitContext::root();
+    w.open(&format!(
+        "def validate(instance: object{}) -> {}",
+        limit_param(opts),
+        returned_errors(opts)
+    ));
+    emit_docstring(&mut w, &function_doc(schema, "", &schema.root));
+    w.line(&format!("e: {ERRORS} = []"));
+    let ret = return_errors(opts, "instance");
+    let root_ctx = EmitContext::root()
+        .with_messages(opts.messages)
+        .with_numbers(opts.py_numbers)
+        .with_source_map(opts.source_map)
+        .with_stop(stops.then(|| ret.clone()))
+        .with_limit(limit_var);
     emit_node(&mut w, &schema.root, &root_ctx, None);
-    w.line("return e");
+    w.line(&ret);
     w.dedent();
+
+    if opts.is_valid {
+        w.line("");
+        w.line("");
+        emit_is_valid(&mut w, schema, opts.py_numbers);
+    }
+    if opts.self_test {
+        w.line("");
+        w.line("");
+        emit_self_test(&mut w, schema, opts);
+    }
+    if opts.deprecation_warnings {
+        w.line("");
+        w.line("");
+        emit_warnings(&mut w, &warning_plan(schema));
+    }
+    if opts.validate_at {
+        w.line("");
+        w.line("");
+        emit_validate_at(&mut w, schema, opts);
+    }
+    if opts.coerce {
+        w.line("");
+        w.line("");
+        emit_coerce(&mut w, &coerce_plan(schema));
+    }
+    if opts.strip_additional {
+        w.line("");
+        w.line("");
+        emit_strip(&mut w, &strip_plan(schema));
+    }
+    if opts.apply_defaults {
+        w.line("");
+        w.line("");
+        emit_defaults(&mut w, &defaults_plan(schema));
+    }
+    if opts.mock {
+        w.line("");
+        w.line("");
+        emit_mock(&mut w, schema);
+    }
+    if opts.shape {
+        w.line("");
+        w.line("# Object keys, enum values and discriminator tags by instance path,");
+        w.line("# for form and error UIs.");
+        w.line(&format!(
+            "SHAPE: dict[str, object] = {}",
+            py_literal(&shape(schema))
+        ));
+    }
     w.line("# fmt: on");
 
     w.finish()
 }
 
+/// The type of the error lists the validators fill.
+pub(super) const ERRORS: &str = "list[dict[str, str]]";
+
+/// The type of the errors [`SEGMENTS_FN`] returns, paths as segments.
+const SEGMENTED_ERRORS: &str = "list[dict[str, str | list[str]]]";
+
+/// The type of the errors an entry point returns.
+fn returned_errors(opts: &EmitOptions) -> &'static str {
+    if opts.path_segments {
+        SEGMENTED_ERRORS
+    } else {
+        ERRORS
+    }
+}
+
+/// The final statement of an entry point validating `instance`: the
+/// errors with their suggestions, then with their paths as segments.
+fn return_errors(opts: &EmitOptions, instance: &str) -> String {
+    let mut errors = "e".to_string();
+    if opts.suggestions {
+        errors = format!("_add_suggestions({instance}, {errors})");
+    }
+    if opts.path_segments {
+        errors = format!("{SEGMENTS_FN}({errors})");
+    }
+    format!("return {errors}")
+}
+
+/// The variable definitions and entry points take the error limit in.
+const LIMIT_VAR: &str = "max_errors";
+
+/// The error limit parameter of an entry point, defaulting to `max_errors`.
+fn limit_param(opts: &EmitOptions) -> String {
+    opts.error_limit()
+        .map_or(String::new(), |n| format!(", {LIMIT_VAR}: int = {n}"))
+}
+
+/// How a definition's function leaves early when failing fast, restoring
+/// the depth counter of a recursive one.
+fn definition_stop(guarded: bool) -> String {
+    if guarded {
+        "_ref_depth.n = depth; return".to_string()
+    } else {
+        "return".to_string()
+    }
+}
+
 /// Sanitize a definition name into a valid Python function name.
-fn def_fn_name(name: &str) -> String {
+pub(super) fn def_fn_name(name: &str) -> String {
     let safe: String = name
         .chars()
         .map(|c| {
-            if c.is_alphanumeric() || c == '_' {
+            if c.is_ascii_alphanumeric() || c == '_' {
                 c
             } else {
                 '_'
@@ -63,6 +284,158 @@ fn def_fn_name(name: &str) -> String {
     format!("validate_{safe}")
 }
 
+/// Name of a definition's predicate in the `is_valid` functions.
+fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "is_valid_", 1)
+}
+
+/// `is_valid(instance)`: the checks `validate` makes, returning `False` at
+/// the first failure without building errors, and a predicate per
+/// definition. Recursive ones count depth as the validators do.
+fn emit_is_valid(w: &mut CodeWriter, schema: &CompiledSchema, numbers: PyNumbers) {
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        w.open(&format!("def {}(v: object) -> bool", pred_fn_name(name)));
+        let guarded = recursive.contains(name.as_str());
+        let fail = if guarded {
+            w.line("depth = getattr(_ref_depth, \"n\", 0)");
+            w.open(&format!("if depth >= {MAX_REF_DEPTH}"));
+            w.line("return False");
+            w.dedent();
+            w.line("_ref_depth.n = depth + 1");
+            "_ref_depth.n = depth; return False"
+        } else {
+            "return False"
+        };
+        let ctx = EmitContext::definition()
+            .with_numbers(numbers)
+            .predicate(fail);
+        emit_node(w, node, &ctx, None);
+        if guarded {
+            w.line("_ref_depth.n = depth");
+        }
+        w.line("return True");
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open("def is_valid(instance: object) -> bool");
+    w.line(
+        "\"\"\"Whether instance is valid; stops at the first failure and builds no errors.\"\"\"",
+    );
+    emit_node(
+        w,
+        &schema.root,
+        &EmitContext::root()
+            .with_numbers(numbers)
+            .predicate("return False"),
+        None,
+    );
+    w.line("return True");
+    w.dedent();
+}
+
+/// `validate_at(pointer, value)`: validate a fragment against one
+/// sub-schema, reporting schema paths from `pointer` on. Unknown pointers
+/// raise `KeyError`.
+fn emit_validate_at(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    w.open(&format!(
+        "def validate_at(pointer: str, value: object{}) -> {}",
+        limit_param(opts),
+        returned_errors(opts)
+    ));
+    w.line("\"\"\"Validate value against the sub-schema at pointer, e.g. \"/properties/address\".\"\"\"");
+    w.line(&format!("e: {ERRORS} = []"));
+    let ret = return_errors(opts, "value");
+    let limit_var = opts.error_limit().map(|_| LIMIT_VAR.to_string());
+    let ctx = EmitContext::at_pointer()
+        .with_messages(opts.messages)
+        .with_numbers(opts.py_numbers)
+        .with_source_map(opts.source_map)
+        .with_stop((opts.fail_fast || limit_var.is_some()).then(|| ret.clone()))
+        .with_limit(limit_var.clone());
+    let limit_arg = limit_var.map_or(String::new(), |l| format!(", {l}"));
+    w.open("if pointer == \"\"");
+    w.line(&format!("return validate(value{limit_arg})"));
+    for sub in sub_schemas(schema).iter().skip(1) {
+        w.close_open(&format!("elif pointer == \"{}\"", escape_py(&sub.pointer)));
+        if is_no_op(sub.node) {
+            w.line("pass");
+        } else {
+            emit_node(w, sub.node, &ctx, sub.tag);
+        }
+    }
+    w.close_open("else");
+    w.line("raise KeyError(pointer)");
+    w.dedent();
+    w.line(&ret);
+    w.dedent();
+}
+
+/// `self_test()`: validate each `metadata.examples` entry (root and
+/// definitions) and return `{schemaPath, example, errors}` for failures.
+/// Definition examples collect their errors in `d`, as with
+/// `--path-segments` `validate` returns another type.
+fn emit_self_test(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    let limit_arg = opts
+        .error_limit()
+        .map_or(String::new(), |n| format!(", {n}"));
+    w.open("def self_test() -> list[dict[str, object]]");
+    w.line("\"\"\"Validate the schema's metadata.examples; returns the examples that fail.\"\"\"");
+    w.line("import json");
+    w.line("failures: list[dict[str, object]] = []");
+    let mut declared = false;
+    for (sp, def, examples) in schema.example_sets() {
+        for (i, example) in examples.iter().enumerate() {
+            let json = escape_py(&serde_json::to_string(example).unwrap_or_default());
+            let errors = match def {
+                None => {
+                    w.line(&format!("e = validate(json.loads(\"{json}\"))"));
+                    "e"
+                }
+                Some(name) => {
+                    if declared {
+                        w.line("d = []");
+                    } else {
+                        w.line(&format!("d: {ERRORS} = []"));
+                        declared = true;
+                    }
+                    w.line(&format!(
+                        "{}(json.loads(\"{json}\"), d, \"\", \"{}\"{limit_arg})",
+                        def_fn_name(name),
+                        escape_py(&sp)
+                    ));
+                    "d"
+                }
+            };
+            w.open(&format!("if {errors}"));
+            w.line(&format!(
+                "failures.append({{\"schemaPath\": \"{}\", \"example\": {i}, \"errors\": {errors}}})",
+                escape_py(&sp)
+            ));
+            w.dedent();
+        }
+    }
+    w.line("return failures");
+    w.dedent();
+}
+
+/// Write `lines` as the function docstring; nothing when there are none.
+fn emit_docstring(w: &mut CodeWriter, lines: &[String]) {
+    let escape = |l: &str| l.replace('\\', "\\\\").replace('"', "\\\"");
+    match lines {
+        [] => {}
+        [only] => w.line(&format!("\"\"\"{}\"\"\"", escape(only))),
+        [first, rest @ ..] => {
+            w.line(&format!("\"\"\"{}", escape(first)));
+            for line in rest {
+                w.line(&escape(line));
+            }
+            w.line("\"\"\"");
+        }
+    }
+}
+
 /// Check if an AST node produces no validation output.
 fn is_no_op(node: &Node) -> bool {
     match node {
@@ -92,30 +465,101 @@ fn node_uses_timestamp(node: &Node) -> bool {
     }
 }
 
+/// A port of [`crate::timestamp::is_rfc3339`]. `[0-9]` and `\Z` keep the
+/// pattern to ASCII digits and the end of the string, where `\d` and `$`
+/// would take other scripts' digits and a trailing newline.
 fn emit_timestamp_helper(w: &mut CodeWriter) {
-    w.line(r#"_TS_RE = re.compile(r'^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:(\d{2}|60)(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$')"#);
+    w.line(r#"_TS_RE = re.compile(r"([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))\Z")"#);
     w.line("");
-    w.open("def _is_rfc3339(s)");
-    w.open("if not isinstance(s, str) or not _TS_RE.match(s)");
+    w.open("def _is_rfc3339(s: object) -> bool");
+    w.open("if not isinstance(s, str)");
     w.line("return False");
     w.dedent();
-    w.open("try");
-    w.line("n = s.replace(\"t\", \"T\", 1)");
-    w.open("if n.endswith(\"z\")");
-    w.line("n = n[:-1] + \"Z\"");
+    w.line("m = _TS_RE.match(s)");
+    w.open("if m is None");
+    w.line("return False");
     w.dedent();
-    w.line("n = n.replace(\":60\", \":59\", 1)");
-    w.line("datetime.fromisoformat(n)");
-    w.line("return True");
-    w.close_open("except (ValueError, OverflowError)");
+    w.line("y, mo, d = int(m.group(1)), int(m.group(2)), int(m.group(3))");
+    w.open("if mo < 1 or mo > 12 or d < 1");
+    w.line("return False");
+    w.dedent();
+    w.line("leap = y % 4 == 0 and (y % 100 != 0 or y % 400 == 0)");
+    w.open("if d > (31, 29 if leap else 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31)[mo - 1]");
+    w.line("return False");
+    w.dedent();
+    w.open("if int(m.group(4)) > 23 or int(m.group(5)) > 59 or int(m.group(6)) > 60");
     w.line("return False");
     w.dedent();
+    w.line("return m.group(7) is None or (int(m.group(7)) <= 23 and int(m.group(8)) <= 59)");
     w.dedent();
     w.line("");
 }
 
+/// Helper describing a value in messages: its JSON type, or the number
+/// itself.
+pub(super) const DESCRIBE_FN: &str = "_describe";
+
+/// The [`DESCRIBE_FN`] helper, with `--messages`. Booleans are ints in
+/// Python, so they are told apart first.
+fn emit_describe_helper(w: &mut CodeWriter) {
+    w.open(&format!("def {DESCRIBE_FN}(v: object) -> str"));
+    w.open("if v is None");
+    w.line("return \"null\"");
+    w.close_open("elif isinstance(v, bool)");
+    w.line("return \"boolean\"");
+    w.close_open("elif isinstance(v, (int, float))");
+    w.line("return str(v)");
+    w.close_open("elif isinstance(v, str)");
+    w.line("return \"string\"");
+    w.close_open("elif isinstance(v, list)");
+    w.line("return \"array\"");
+    w.dedent();
+    w.line("return \"object\"");
+    w.dedent();
+}
+
+/// Helper turning the errors' JSON Pointers into lists of segments before
+/// the entry points return them.
+const SEGMENTS_FN: &str = "_path_segments";
+
+/// The [`SEGMENTS_FN`] helper, with `--path-segments`. Other keys of the
+/// errors (`message`, `suggestion`) are kept.
+fn emit_segments_helper(w: &mut CodeWriter) {
+    w.open(&format!(
+        "def {SEGMENTS_FN}(e: {ERRORS}) -> {SEGMENTED_ERRORS}"
+    ));
+    w.open("def split(p: str) -> list[str]");
+    w.line("return [s.replace(\"~1\", \"/\").replace(\"~0\", \"~\") for s in p.split(\"/\")[1:]]");
+    w.dedent();
+    w.line("return [{**x, \"instancePath\": split(x[\"instancePath\"]), \"schemaPath\": split(x[\"schemaPath\"])} for x in e]");
+    w.dedent();
+}
+
+/// Start of a recursive definition's function: from `MAX_REF_DEPTH` nested
+/// calls on, report the value at the definition's schema path instead of
+/// descending, so deep instances cannot exhaust the stack. The function
+/// ends by restoring `_ref_depth.n = depth`.
+fn emit_depth_enter(w: &mut CodeWriter, ctx: &EmitContext) {
+    w.line("depth = getattr(_ref_depth, \"n\", 0)");
+    w.open(&format!("if depth >= {MAX_REF_DEPTH}"));
+    w.line(&ctx.push_error("", Message::TooDeep));
+    w.line("return");
+    w.dedent();
+    w.line("_ref_depth.n = depth + 1");
+}
+
 /// Recursively emit validation code for one AST node.
 fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Option<&str>) {
+    if !ctx.source_map {
+        return emit_node_checks(w, node, ctx, discrim_tag);
+    }
+    w.line(&source_map::open(node));
+    emit_node_checks(w, node, ctx, discrim_tag);
+    w.line(source_map::close());
+}
+
+/// [`emit_node`] without the source-map markers.
+fn emit_node_checks(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Option<&str>) {
     match node {
         Node::Empty => {}
 
@@ -131,17 +575,30 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
                 "if not isinstance({val}, str) or {val} not in {set_literal}",
                 val = ctx.val,
             ));
-            w.line(&ctx.push_error("/enum"));
+            w.line(&ctx.push_error("/enum", Message::Enum(values)));
+            w.dedent();
+        }
+
+        Node::Ref { name } if ctx.predicate => {
+            w.open(&format!("if not {}({})", pred_fn_name(name), ctx.val));
+            w.line(&ctx.fail());
             w.dedent();
         }
 
         Node::Ref { name } => {
             let fn_name = def_fn_name(name);
-            let escaped = escape_py(name);
+            let escaped = escape_py(&escape_pointer(name));
+            let limit_arg = ctx
+                .limit
+                .as_ref()
+                .map_or(String::new(), |l| format!(", {l}"));
             w.line(&format!(
-                "{fn_name}({}, {}, {}, \"/definitions/{escaped}\")",
+                "{fn_name}({}, {}, {}, \"/definitions/{escaped}\"{limit_arg})",
                 ctx.val, ctx.err, ctx.ip
             ));
+            if let Some(check) = ctx.check_stop() {
+                w.line(&check);
+            }
         }
 
         Node::Nullable { inner } => {
@@ -177,15 +634,16 @@ fn emit_node(w: &mut CodeWriter, node: &Node, ctx: &EmitContext, discrim_tag: Op
 
 /// Emit a type check.
 fn emit_type(w: &mut CodeWriter, ctx: &EmitContext, type_kw: TypeKeyword) {
-    let cond = type_condition(type_kw, &ctx.val);
+    let cond = type_condition(type_kw, &ctx.val, ctx.numbers);
     w.open(&format!("if {cond}"));
-    w.line(&ctx.push_error("/type"));
+    w.line(&ctx.push_error("/type", Message::Type(type_kw)));
     w.dedent();
 }
 
 /// Returns a Python expression that evaluates to `true` when `val`
-/// does NOT satisfy the given type keyword.
-fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
+/// does NOT satisfy the given type keyword. `bool` is a subclass of `int`,
+/// so the numeric types rule it out explicitly.
+fn type_condition(type_kw: TypeKeyword, val: &str, numbers: PyNumbers) -> String {
     match type_kw {
         TypeKeyword::Boolean => {
             format!("not isinstance({val}, bool)")
@@ -196,27 +654,37 @@ fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
         TypeKeyword::Timestamp => {
             format!("not _is_rfc3339({val})")
         }
-        TypeKeyword::Float32 | TypeKeyword::Float64 => {
-            format!("not isinstance({val}, (int, float)) or isinstance({val}, bool)")
-        }
-        TypeKeyword::Int8 => int_cond(val, -128, 127),
-        TypeKeyword::Uint8 => int_cond(val, 0, 255),
-        TypeKeyword::Int16 => int_cond(val, -32768, 32767),
-        TypeKeyword::Uint16 => int_cond(val, 0, 65535),
-        TypeKeyword::Int32 => int_cond(val, -2_147_483_648, 2_147_483_647),
-        TypeKeyword::Uint32 => int_cond(val, 0, 4_294_967_295),
+        TypeKeyword::Float32 | TypeKeyword::Float64 => match numbers {
+            PyNumbers::Loose => {
+                format!("not isinstance({val}, (int, float)) or isinstance({val}, bool)")
+            }
+            PyNumbers::Strict => format!(
+                "not isinstance({val}, (int, float)) or isinstance({val}, bool) or not abs({val}) < float(\"inf\")"
+            ),
+        },
+        TypeKeyword::Int8 => int_cond(val, -128, 127, numbers),
+        TypeKeyword::Uint8 => int_cond(val, 0, 255, numbers),
+        TypeKeyword::Int16 => int_cond(val, -32768, 32767, numbers),
+        TypeKeyword::Uint16 => int_cond(val, 0, 65535, numbers),
+        TypeKeyword::Int32 => int_cond(val, -2_147_483_648, 2_147_483_647, numbers),
+        TypeKeyword::Uint32 => int_cond(val, 0, 4_294_967_295, numbers),
     }
 }
 
-fn int_cond(val: &str, min: i64, max: i64) -> String {
-    format!(
-        "not isinstance({val}, (int, float)) or isinstance({val}, bool) or {val} % 1 != 0 or {val} < {min} or {val} > {max}"
-    )
+fn int_cond(val: &str, min: i64, max: i64, numbers: PyNumbers) -> String {
+    match numbers {
+        PyNumbers::Loose => format!(
+            "not isinstance({val}, (int, float)) or isinstance({val}, bool) or {val} % 1 != 0 or {val} < {min} or {val} > {max}"
+        ),
+        PyNumbers::Strict => format!(
+            "not isinstance({val}, int) or isinstance({val}, bool) or {val} < {min} or {val} > {max}"
+        ),
+    }
 }
 
 /// Elements form: array type guard + loop with inner check.
 fn emit_elements(w: &mut CodeWriter, ctx: &EmitContext, schema: &Node) {
-    let err_stmt = ctx.push_error("/elements");
+    let err_stmt = ctx.push_error("/elements", Message::NotArray);
     w.open(&format!("if not isinstance({}, list)", ctx.val));
     w.line(&err_stmt);
     w.close_open("else");
@@ -235,7 +703,7 @@ fn emit_elements(w: &mut CodeWriter, ctx: &EmitContext, schema: &Node) {
 
 /// Values form: object type guard + for-in loop with inner check.
 fn emit_values(w: &mut CodeWriter, ctx: &EmitContext, schema: &Node) {
-    let err_stmt = ctx.push_error("/values");
+    let err_stmt = ctx.push_error("/values", Message::NotObject);
     w.open(&format!("if not isinstance({}, dict)", ctx.val));
     w.line(&err_stmt);
     w.close_open("else");
@@ -269,7 +737,7 @@ fn emit_properties(
         "/optionalProperties"
     };
     w.open(&format!("if not isinstance({}, dict)", ctx.val));
-    w.line(&ctx.push_error(guard_sp));
+    w.line(&ctx.push_error(guard_sp, Message::NotObject));
     w.close_open("else");
 
     let mut has_content = false;
@@ -279,7 +747,10 @@ fn emit_properties(
         has_content = true;
         let escaped = escape_py(key);
         w.open(&format!("if \"{}\" not in {}", escaped, ctx.val));
-        w.line(&ctx.push_error(&format!("/properties/{escaped}")));
+        w.line(&ctx.push_error(
+            &format!("/properties/{}", escape_py(&escape_pointer(key))),
+            Message::MissingProperty(key),
+        ));
         if !is_no_op(node) {
             w.close_open("else");
             let child_ctx = ctx.required_prop(key);
@@ -318,14 +789,22 @@ fn emit_properties(
         }
 
         if known.is_empty() {
-            w.line(&ctx.push_error_dynamic(&format!("\"/\" + {k_var}"), ""));
+            w.line(&ctx.push_error_dynamic(
+                &format!("\"/\" + {}", pointer_segment(k_var)),
+                "",
+                Message::UnknownProperty(k_var),
+            ));
         } else {
             let conds: Vec<String> = known
                 .iter()
                 .map(|k| format!("{k_var} != \"{}\"", escape_py(k)))
                 .collect();
             w.open(&format!("if {}", conds.join(" and ")));
-            w.line(&ctx.push_error_dynamic(&format!("\"/\" + {k_var}"), ""));
+            w.line(&ctx.push_error_dynamic(
+                &format!("\"/\" + {}", pointer_segment(k_var)),
+                "",
+                Message::UnknownProperty(k_var),
+            ));
             w.dedent();
         }
 
@@ -347,21 +826,22 @@ fn emit_discriminator(
     mapping: &BTreeMap<String, Node>,
 ) {
     let escaped_tag = escape_py(tag);
+    let tag_ip = format!("/{}", escape_py(&escape_pointer(tag)));
 
     // Step 1: not an object
     w.open(&format!("if not isinstance({}, dict)", ctx.val));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::NotObject));
 
     // Step 2: tag missing
     w.close_open(&format!("elif \"{}\" not in {}", escaped_tag, ctx.val));
-    w.line(&ctx.push_error("/discriminator"));
+    w.line(&ctx.push_error("/discriminator", Message::MissingTag(tag)));
 
     // Step 3: tag not string
     w.close_open(&format!(
         "elif not isinstance({}[\"{}\"], str)",
         ctx.val, escaped_tag
     ));
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/discriminator"));
+    w.line(&ctx.push_error_at(&tag_ip, "/discriminator", Message::TagNotString(tag)));
 
     // Step 4: dispatch per variant
     for (variant_key, variant_node) in mapping {
@@ -376,7 +856,7 @@ fn emit_discriminator(
 
     // Step 5: unknown tag value
     w.close_open("else");
-    w.line(&ctx.push_error_at(&format!("/{escaped_tag}"), "/mapping"));
+    w.line(&ctx.push_error_at(&tag_ip, "/mapping", Message::UnknownTag(tag)));
     w.dedent();
 }
 
@@ -391,8 +871,8 @@ mod tests {
         let schema = json!({});
         let compiled = compiler::compile(&schema).unwrap();
         let code = emit(&compiled);
-        assert!(code.contains("def validate(instance)"));
-        assert!(code.contains("e = []"));
+        assert!(code.contains("def validate(instance: object) -> list[dict[str, str]]:"));
+        assert!(code.contains("e: list[dict[str, str]] = []"));
         assert!(code.contains("return e"));
         // No type checks for empty schema
         assert!(!code.contains("isinstance"));
@@ -436,6 +916,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_emit_strict_numbers() {
+        let schema = json!({"properties": {"n": {"type": "uint8"}, "x": {"type": "float32"}}});
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            py_numbers: PyNumbers::Strict,
+            is_valid: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        let n = "instance[\"n\"]";
+        let int_check =
+            format!("not isinstance({n}, int) or isinstance({n}, bool) or {n} < 0 or {n} > 255");
+        // In validate and in is_valid
+        assert_eq!(code.matches(&int_check).count(), 2);
+        assert!(!code.contains("% 1"));
+        assert_eq!(
+            code.matches("or not abs(instance[\"x\"]) < float(\"inf\")")
+                .count(),
+            2
+        );
+    }
+
     #[test]
     fn test_emit_enum() {
         let schema = json!({"enum": ["a", "b", "c"]});
@@ -454,7 +957,9 @@ mod tests {
         let compiled = compiler::compile(&schema).unwrap();
         let code = emit(&compiled);
         // Definition function
-        assert!(code.contains("def validate_addr(v, e, p, sp)"));
+        assert!(code.contains(
+            "def validate_addr(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:"
+        ));
         assert!(code.contains("not isinstance(v, str)"));
         // Root calls it
         assert!(code.contains("validate_addr(instance, e, \"\", \"/definitions/addr\")"));
@@ -559,8 +1064,9 @@ mod tests {
         let compiled = compiler::compile(&schema).unwrap();
         let code = emit(&compiled);
         assert!(code.contains("import re"));
-        assert!(code.contains("from datetime import datetime"));
         assert!(code.contains("_is_rfc3339"));
+        // Platform date parsing differs from the other targets
+        assert!(!code.contains("datetime"));
     }
 
     #[test]
@@ -580,4 +1086,255 @@ mod tests {
         assert!(code.starts_with("# fmt: off\n"));
         assert!(code.contains("# fmt: on"));
     }
+
+    #[test]
+    fn test_enum_constants() {
+        let schema = json!({"properties": {"status": {"enum": ["OPEN", "CLOSED"]}}});
+        let compiled = compiler::compile(&schema).unwrap();
+        assert!(!emit(&compiled).contains("STATUS_VALUES"));
+        let opts = EmitOptions {
+            enum_constants: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("STATUS_VALUES: frozenset[str] = frozenset({\"OPEN\", \"CLOSED\"})"));
+    }
+
+    #[test]
+    fn test_descriptions_become_docstring() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"description": "A customer"},
+            "properties": {"name": {"type": "string", "metadata": {"description": "Full name"}}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains(
+            "def validate(instance: object) -> list[dict[str, str]]:\n    \"\"\"A customer\n\n    Fields:\n      /name: Full name\n    \"\"\"\n"
+        ));
+        let quoted =
+            compiler::compile(&json!({"metadata": {"description": "say \"hi\""}})).unwrap();
+        assert!(emit(&quoted).contains("\"\"\"say \\\"hi\\\"\"\"\""));
+    }
+
+    #[test]
+    fn test_self_test() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"examples": ["ok"]},
+            "definitions": {"id": {"type": "string", "metadata": {"examples": [5]}}},
+            "ref": "id"
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            self_test: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def self_test() -> list[dict[str, object]]:"));
+        assert!(code.contains("e = validate(json.loads(\"\\\"ok\\\"\"))"));
+        assert!(code.contains("validate_id(json.loads(\"5\"), d, \"\", \"/definitions/id\")"));
+    }
+
+    #[test]
+    fn test_deprecation_warnings() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"item": {"optionalProperties": {"code": {"metadata": {"deprecated": true}}}}},
+            "properties": {"items": {"elements": {"ref": "item"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            deprecation_warnings: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def warnings(instance: object) -> list[dict[str, str]]:"));
+        assert!(code.contains("for i0, x0 in enumerate(instance[\"items\"]):"));
+        assert!(code.contains("warn_item(x0, w, \"\" + \"/items\" + \"/\" + str(i0))"));
+    }
+
+    #[test]
+    fn test_validate_at() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"address": {"properties": {"zip": {"type": "string"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            validate_at: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(
+            code.contains("def validate_at(pointer: str, value: object) -> list[dict[str, str]]:")
+        );
+        assert!(code.contains("elif pointer == \"/properties/address\":"));
+        assert!(code.contains("raise KeyError(pointer)"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["active", "pending"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("\"/properties/status/enum\": (False, (\"active\", \"pending\",)),"));
+        assert!(code.contains("return _add_suggestions(instance, e)"));
+    }
+
+    #[test]
+    fn test_coerce() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"page": {"type": "uint32"}, "q": {"type": "string"}},
+            "optionalProperties": {"debug": {"type": "boolean"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            coerce: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("import re\n"));
+        assert!(code.contains("def coerce(instance: object) -> object:"));
+        assert!(code.contains("v[\"page\"] = _coerce_number(v[\"page\"], True)"));
+        assert!(code.contains("v[\"debug\"] = _coerce_boolean(v[\"debug\"])"));
+        assert!(!emit(&compiled).contains("coerce"));
+    }
+
+    #[test]
+    fn test_strip_additional() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {
+                "b": {"properties": {"c": {"properties": {}}}, "additionalProperties": true}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            strip_additional: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def strip(instance: object) -> object:"));
+        assert!(code.contains("v = {k0: x for k0, x in v.items() if k0 in {\"a\", \"b\"}}"));
+        assert!(code.contains("v[\"b\"] = dict(v[\"b\"])"));
+        assert!(!emit(&compiled).contains("strip"));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"q": {"type": "string"}},
+            "optionalProperties": {
+                "page": {"type": "uint32", "metadata": {"default": 1}},
+                "tags": {"elements": {"type": "string"}, "metadata": {"default": ["new"]}}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            apply_defaults: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def apply_defaults(instance: object) -> object:"));
+        assert!(code.contains("if \"page\" not in v:\n            v[\"page\"] = 1\n"));
+        assert!(code.contains("v[\"tags\"] = [\"new\"]"));
+        assert!(!emit(&compiled).contains("apply_defaults"));
+    }
+
+    #[test]
+    fn test_mock() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"head": {"ref": "node"}, "tags": {"elements": {"type": "string"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            mock: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def mock(seed: int) -> object:"));
+        assert!(code.contains("def _mock_node(r: random.Random, d: int) -> object:"));
+        assert!(code.contains("v1[\"head\"] = _mock_node(r, d + 1)"));
+        assert!(!emit(&compiled).contains("mock"));
+    }
+
+    #[test]
+    fn test_shape() {
+        let compiled = compiler::compile(&json!({
+            "optionalProperties": {"status": {"enum": ["new", "done"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            shape: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            r#"SHAPE: dict[str, object] = {"discriminators": [], "enums": [{"path": "/status""#
+        ));
+        assert!(code.contains(r#""additional": False"#));
+    }
+
+    #[test]
+    fn test_recursive_definitions_are_depth_limited() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {
+                "node": {"optionalProperties": {"next": {"ref": "node"}}},
+                "id": {"type": "string"}
+            },
+            "properties": {"id": {"ref": "id"}, "head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("import threading\n"));
+        assert!(code.contains("_ref_depth = threading.local()\n"));
+        assert!(code.contains(
+            "def validate_node(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:\n    depth = getattr(_ref_depth, \"n\", 0)\n    if depth >= 256:\n"
+        ));
+        assert!(code.contains("    _ref_depth.n = depth\n"));
+        assert!(code.contains("def validate_id(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:\n    if not isinstance(v, str)"));
+    }
+
+    #[test]
+    fn test_messages() {
+        let compiled = compiler::compile(&json!({
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"n": {"type": "uint8"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            messages: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("def _describe(v: object) -> str:\n    if v is None:\n"));
+        assert!(code.contains(
+            "\"message\": \"discriminator 'kind' must be a string, got \" + _describe(instance[\"kind\"])})"
+        ));
+        assert!(
+            code.contains("\"message\": \"expected uint8, got \" + _describe(instance[\"n\"])})")
+        );
+        assert!(code.contains("\"message\": \"missing discriminator 'kind'\"})"));
+        assert!(!emit(&compiled).contains("message"));
+    }
+
+    #[test]
+    fn test_path_segments() {
+        let compiled = compiler::compile(&json!({"values": {"type": "string"}})).unwrap();
+        let opts = EmitOptions {
+            path_segments: true,
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "def _path_segments(e: list[dict[str, str]]) -> list[dict[str, str | list[str]]]:\n"
+        ));
+        assert!(code.contains("return _path_segments(_add_suggestions(instance, e))"));
+        assert!(!emit(&compiled).contains("_path_segments"));
+    }
 }
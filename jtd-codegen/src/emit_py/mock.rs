@@ -0,0 +1,153 @@
+/// `mock(seed)`: a random instance of the schema, built as described in
+/// [`crate::mock`] with a `random.Random(seed)`, so tests can fabricate
+/// valid fixtures. One `_mock_<name>(r, d)` per definition; `d` is the ref
+/// depth.
+use super::emit::def_fn_name;
+use super::writer::{escape_py, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::mock::{int_range, MAX_DEPTH, MAX_ITEMS, MAX_STRING};
+
+/// Emit the generator helpers, one function per definition and `mock`.
+pub(super) fn emit_mock(w: &mut CodeWriter, schema: &CompiledSchema) {
+    w.open("def _mock_string(r: random.Random) -> str");
+    w.line(&format!(
+        "return \"\".join(chr(97 + r.randint(0, 25)) for _ in range(r.randint(0, {MAX_STRING})))"
+    ));
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.open("def _mock_timestamp(r: random.Random) -> str");
+    w.line("date = f\"{r.randint(2000, 2030)}-{r.randint(1, 12):02}-{r.randint(1, 28):02}\"");
+    w.line("return f\"{date}T{r.randint(0, 23):02}:{r.randint(0, 59):02}:{r.randint(0, 59):02}Z\"");
+    w.dedent();
+    w.line("");
+    w.line("");
+    for (name, node) in &schema.definitions {
+        w.open(&format!(
+            "def {}(r: random.Random, d: int) -> object",
+            mock_fn_name(name)
+        ));
+        let v = emit_value(w, node, &mut 0);
+        w.line(&format!("return {v}"));
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open("def mock(seed: int) -> object");
+    w.line("\"\"\"A random valid instance; the same seed gives the same instance.\"\"\"");
+    w.line("r = random.Random(seed)");
+    w.line("d = 0");
+    let v = emit_value(w, &schema.root, &mut 0);
+    w.line(&format!("return {v}"));
+    w.dedent();
+}
+
+fn mock_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "_mock_", 1)
+}
+
+/// Emit the statements building a value for `node`; returns the expression
+/// holding it.
+fn emit_value(w: &mut CodeWriter, node: &Node, n: &mut usize) -> String {
+    match node {
+        Node::Empty => "None".to_string(),
+        Node::Ref { name } => format!("{}(r, d + 1)", mock_fn_name(name)),
+        Node::Type { type_kw } => match (type_kw, int_range(*type_kw)) {
+            (_, Some((lo, hi))) => format!("r.randint({lo}, {hi})"),
+            (TypeKeyword::Boolean, _) => "r.randint(0, 1) == 1".to_string(),
+            (TypeKeyword::String, _) => "_mock_string(r)".to_string(),
+            (TypeKeyword::Timestamp, _) => "_mock_timestamp(r)".to_string(),
+            _ => "r.randint(-1000000, 1000000) / 1000".to_string(),
+        },
+        Node::Enum { values } => {
+            let items: Vec<String> = values
+                .iter()
+                .map(|v| format!("\"{}\"", escape_py(v)))
+                .collect();
+            format!("r.choice([{}])", items.join(", "))
+        }
+        Node::Elements { schema } => {
+            let v = fresh(n);
+            w.line(&format!("{v}: list[object] = []"));
+            w.open(&format!("if d < {MAX_DEPTH}"));
+            w.open(&format!("for _ in range(r.randint(0, {MAX_ITEMS}))"));
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{v}.append({item})"));
+            w.dedent();
+            w.dedent();
+            v
+        }
+        Node::Values { schema } => {
+            let v = fresh(n);
+            w.line(&format!("{v}: dict[str, object] = {{}}"));
+            w.open(&format!("if d < {MAX_DEPTH}"));
+            w.open(&format!("for _ in range(r.randint(0, {MAX_ITEMS}))"));
+            w.line("k = _mock_string(r)");
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{v}[k] = {item}"));
+            w.dedent();
+            w.dedent();
+            v
+        }
+        Node::Properties { .. } => {
+            let v = fresh(n);
+            w.line(&format!("{v}: dict[str, object] = {{}}"));
+            emit_properties(w, node, &v, n);
+            v
+        }
+        Node::Discriminator { tag, mapping } => {
+            let v = fresh(n);
+            w.line(&format!("{v}: dict[str, object] = {{}}"));
+            w.line(&format!("i = r.randint(0, {})", mapping.len() - 1));
+            for (i, (value, variant)) in mapping.iter().enumerate() {
+                if i == 0 {
+                    w.open("if i == 0");
+                } else {
+                    w.close_open(&format!("elif i == {i}"));
+                }
+                w.line(&format!(
+                    "{v}[\"{}\"] = \"{}\"",
+                    escape_py(tag),
+                    escape_py(value)
+                ));
+                emit_properties(w, variant, &v, n);
+            }
+            w.dedent();
+            v
+        }
+        Node::Nullable { inner } => {
+            let v = fresh(n);
+            w.line(&format!("{v}: object = None"));
+            w.open(&format!("if d < {MAX_DEPTH} and r.randint(0, 3) != 0"));
+            let inner = emit_value(w, inner, n);
+            w.line(&format!("{v} = {inner}"));
+            w.dedent();
+            v
+        }
+    }
+}
+
+/// Add the properties of the Properties node `node` to the dict `v`.
+fn emit_properties(w: &mut CodeWriter, node: &Node, v: &str, n: &mut usize) {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return;
+    };
+    for (key, child) in required {
+        let value = emit_value(w, child, n);
+        w.line(&format!("{v}[\"{}\"] = {value}", escape_py(key)));
+    }
+    for (key, child) in optional {
+        w.open(&format!("if d < {MAX_DEPTH} and r.randint(0, 1) == 1"));
+        let value = emit_value(w, child, n);
+        w.line(&format!("{v}[\"{}\"] = {value}", escape_py(key)));
+        w.dedent();
+    }
+}
+
+fn fresh(n: &mut usize) -> String {
+    *n += 1;
+    format!("v{n}")
+}
@@ -1,6 +1,14 @@
 /// Python 3.13+ emitter — generates standalone validation modules.
+mod coerce;
 mod context;
+mod defaults;
 mod emit;
+mod mock;
+mod pytest;
+mod strip;
+mod suggest;
+mod warnings;
 mod writer;
 
-pub use emit::emit;
+pub use emit::{emit, emit_with_options};
+pub use pytest::{emit_pytest, example_cases, suite_cases, PyTestCase};
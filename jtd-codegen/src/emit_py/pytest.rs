@@ -0,0 +1,236 @@
+/// Companion pytest module: emits a `test_validator.py` that imports the
+/// generated validator and checks it against a list of known cases.
+///
+/// Cases come from `metadata.examples` on the root schema (each example is
+/// expected to be valid) and, optionally, from a file in the upstream suite
+/// format (`{name: {schema, instance, errors}}`).
+use super::writer::{escape_py, CodeWriter};
+use crate::compiler::escape_pointer;
+use serde_json::Value;
+
+/// One regression case: an instance plus the exact errors it must produce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyTestCase {
+    /// pytest id for the case
+    pub name: String,
+    /// The JSON instance to validate
+    pub instance: Value,
+    /// Expected (instancePath, schemaPath) pairs; empty when valid
+    pub errors: Vec<(String, String)>,
+}
+
+/// Collect `metadata.examples` from the root schema as valid cases.
+pub fn example_cases(schema: &Value) -> Vec<PyTestCase> {
+    let examples = schema
+        .get("metadata")
+        .and_then(|m| m.get("examples"))
+        .and_then(|e| e.as_array());
+    let Some(examples) = examples else {
+        return Vec::new();
+    };
+    examples
+        .iter()
+        .enumerate()
+        .map(|(i, instance)| PyTestCase {
+            name: format!("example_{i}"),
+            instance: instance.clone(),
+            errors: Vec::new(),
+        })
+        .collect()
+}
+
+/// Collect cases from a suite-format object. Entries that carry a `schema`
+/// member are only kept when it matches `schema`, so a full upstream
+/// `validation.json` can be passed and sampled for the relevant cases.
+pub fn suite_cases(suite: &Value, schema: &Value) -> Vec<PyTestCase> {
+    let Some(entries) = suite.as_object() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|(_, case)| case.get("schema").is_none_or(|s| s == schema))
+        .filter_map(|(name, case)| {
+            let instance = case.get("instance")?.clone();
+            let errors = case
+                .get("errors")
+                .and_then(|e| e.as_array())
+                .map(|arr| arr.iter().filter_map(error_pair).collect())
+                .unwrap_or_default();
+            Some(PyTestCase {
+                name: name.clone(),
+                instance,
+                errors,
+            })
+        })
+        .collect()
+}
+
+/// Convert one suite error (segment arrays or pointer strings) into a pair.
+fn error_pair(err: &Value) -> Option<(String, String)> {
+    Some((
+        path_to_pointer(err.get("instancePath")?)?,
+        path_to_pointer(err.get("schemaPath")?)?,
+    ))
+}
+
+fn path_to_pointer(path: &Value) -> Option<String> {
+    match path {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(segments) => segments
+            .iter()
+            .map(|s| s.as_str().map(|s| format!("/{}", escape_pointer(s))))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Emit a pytest module exercising `validate` from `module` against `cases`.
+pub fn emit_pytest(module: &str, cases: &[PyTestCase]) -> String {
+    let mut w = CodeWriter::new();
+
+    w.line("# fmt: off");
+    w.line("# Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
+    w.line("# Do not edit manually.");
+    w.line("import json");
+    w.line("");
+    w.line("import pytest");
+    w.line("");
+    w.line(&format!("from {module} import validate"));
+    w.line("");
+
+    w.line("CASES = [");
+    for case in cases {
+        let instance_json = serde_json::to_string(&case.instance).unwrap_or_default();
+        let errors: Vec<String> = case
+            .errors
+            .iter()
+            .map(|(ip, sp)| format!("(\"{}\", \"{}\")", escape_py(ip), escape_py(sp)))
+            .collect();
+        w.line(&format!(
+            "    (\"{}\", \"{}\", [{}]),",
+            escape_py(&case.name),
+            escape_py(&instance_json),
+            errors.join(", ")
+        ));
+    }
+    w.line("]");
+    w.line("");
+    w.line("");
+
+    // Validators generated with --path-segments return lists of segments.
+    w.open("def _pointer(path)");
+    w.open("if isinstance(path, str)");
+    w.line("return path");
+    w.dedent();
+    w.line(
+        "return \"\".join(\"/\" + s.replace(\"~\", \"~0\").replace(\"/\", \"~1\") for s in path)",
+    );
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.line("@pytest.mark.parametrize(");
+    w.line("    \"instance_json, expected\",");
+    w.line("    [pytest.param(i, x, id=n) for n, i, x in CASES],");
+    w.line(")");
+    w.open("def test_validate(instance_json, expected)");
+    w.line("errors = validate(json.loads(instance_json))");
+    w.line("actual = sorted((_pointer(err[\"instancePath\"]), _pointer(err[\"schemaPath\"])) for err in errors)");
+    w.line("assert actual == sorted(expected)");
+    w.dedent();
+    w.line("# fmt: on");
+
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_example_cases_from_metadata() {
+        let schema = json!({
+            "type": "string",
+            "metadata": {"examples": ["a", "b"]}
+        });
+        let cases = example_cases(&schema);
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].name, "example_0");
+        assert_eq!(cases[1].instance, json!("b"));
+        assert!(cases[1].errors.is_empty());
+    }
+
+    #[test]
+    fn test_example_cases_without_metadata() {
+        assert!(example_cases(&json!({"type": "string"})).is_empty());
+    }
+
+    #[test]
+    fn test_suite_cases_filters_by_schema() {
+        let schema = json!({"type": "string"});
+        let suite = json!({
+            "string ok": {"schema": {"type": "string"}, "instance": "x", "errors": []},
+            "string bad": {
+                "schema": {"type": "string"},
+                "instance": 1,
+                "errors": [{"instancePath": [], "schemaPath": ["type"]}]
+            },
+            "other": {"schema": {"type": "boolean"}, "instance": true, "errors": []},
+            "no schema": {"instance": "y", "errors": []}
+        });
+        let cases = suite_cases(&suite, &schema);
+        let names: Vec<&str> = cases.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["no schema", "string bad", "string ok"]);
+        assert_eq!(cases[1].errors, vec![("".into(), "/type".into())]);
+    }
+
+    #[test]
+    fn test_suite_cases_escape_hostile_keys() {
+        let schema = json!({"values": {"type": "string"}});
+        let suite = json!({
+            "hostile": {
+                "schema": schema,
+                "instance": {"a/b~c": 1, "q\"\n": 2},
+                "errors": [
+                    {"instancePath": ["a/b~c"], "schemaPath": ["values", "type"]},
+                    {"instancePath": ["q\"\n"], "schemaPath": ["values", "type"]}
+                ]
+            }
+        });
+        let cases = suite_cases(&suite, &schema);
+        assert_eq!(
+            cases[0].errors,
+            vec![
+                ("/a~1b~0c".into(), "/values/type".into()),
+                ("/q\"\n".into(), "/values/type".into()),
+            ]
+        );
+        let code = emit_pytest("v", &cases);
+        assert!(code.contains("(\"/a~1b~0c\", \"/values/type\")"));
+        assert!(code.contains("(\"/q\\\"\\n\", \"/values/type\")"));
+    }
+
+    #[test]
+    fn test_emit_pytest_structure() {
+        let cases = vec![
+            PyTestCase {
+                name: "ok".into(),
+                instance: json!({"name": "Alice"}),
+                errors: vec![],
+            },
+            PyTestCase {
+                name: "bad".into(),
+                instance: json!({}),
+                errors: vec![("".into(), "/properties/name".into())],
+            },
+        ];
+        let code = emit_pytest("user_validator", &cases);
+        assert!(code.starts_with("# fmt: off\n"));
+        assert!(code.contains("import pytest"));
+        assert!(code.contains("from user_validator import validate"));
+        assert!(code.contains("(\"ok\", \"{\\\"name\\\":\\\"Alice\\\"}\", []),"));
+        assert!(code.contains("[(\"\", \"/properties/name\")]"));
+        assert!(code.contains("def test_validate(instance_json, expected):"));
+        assert!(code.contains("# fmt: on"));
+    }
+}
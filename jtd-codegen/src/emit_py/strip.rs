@@ -0,0 +1,104 @@
+/// `strip(instance)`: returns a copy of `instance` without the keys that
+/// closed Properties schemas do not declare, following a [`StripPlan`].
+/// Dicts and lists on the way are shallow copies, so the caller's document
+/// is never modified.
+use super::emit::def_fn_name;
+use super::writer::{escape_py, CodeWriter};
+use crate::strip::{Shape, Strip, StripPlan};
+
+/// Emit the definition strippers and the `strip` entry point.
+pub(super) fn emit_strip(w: &mut CodeWriter, plan: &StripPlan) {
+    for (name, s) in &plan.definitions {
+        w.open(&format!("def {}(v: object) -> object", strip_fn_name(name)));
+        emit_value(w, s, "v", 0);
+        w.line("return v");
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open("def strip(instance: object) -> object");
+    w.line("\"\"\"A copy of instance without the properties the schema does not declare.\"\"\"");
+    w.line("v = instance");
+    if let Some(s) = &plan.root {
+        emit_value(w, s, "v", 0);
+    }
+    w.line("return v");
+    w.dedent();
+}
+
+fn strip_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "strip_", 1)
+}
+
+/// Statements that replace the value at the assignable expression `v`.
+fn emit_value(w: &mut CodeWriter, s: &Strip, v: &str, depth: usize) {
+    match s {
+        Strip::Object(shape) => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            emit_shape(w, shape, v, depth);
+            w.dedent();
+        }
+        Strip::Array(inner) => {
+            let i = format!("i{depth}");
+            w.open(&format!("if isinstance({v}, list)"));
+            w.line(&format!("{v} = list({v})"));
+            w.open(&format!("for {i} in range(len({v}))"));
+            emit_value(w, inner, &format!("{v}[{i}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Strip::Map(inner) => {
+            let k = format!("k{depth}");
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.line(&format!("{v} = dict({v})"));
+            w.open(&format!("for {k} in {v}"));
+            emit_value(w, inner, &format!("{v}[{k}]"), depth + 1);
+            w.dedent();
+            w.dedent();
+        }
+        Strip::Ref(name) => w.line(&format!("{v} = {}({v})", strip_fn_name(name))),
+        Strip::Tagged { tag, variants } => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!(
+                    "{v}.get(\"{}\") == \"{}\"",
+                    escape_py(tag),
+                    escape_py(value)
+                );
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("elif {cond}"));
+                }
+                emit_shape(w, shape, v, depth);
+            }
+            w.dedent();
+            w.dedent();
+        }
+    }
+}
+
+/// Copy the dict at `v` without unknown keys, then recurse into the rest.
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, v: &str, depth: usize) {
+    match &shape.known {
+        None => w.line(&format!("{v} = dict({v})")),
+        Some(known) if known.is_empty() => w.line(&format!("{v} = {{}}")),
+        Some(known) => {
+            let k = format!("k{depth}");
+            let keys: Vec<String> = known
+                .iter()
+                .map(|key| format!("\"{}\"", escape_py(key)))
+                .collect();
+            w.line(&format!(
+                "{v} = {{{k}: x for {k}, x in {v}.items() if {k} in {{{}}}}}",
+                keys.join(", ")
+            ));
+        }
+    }
+    for (key, s) in &shape.props {
+        let key = escape_py(key);
+        w.open(&format!("if \"{key}\" in {v}"));
+        emit_value(w, s, &format!("{v}[\"{key}\"]"), depth);
+        w.dedent();
+    }
+}
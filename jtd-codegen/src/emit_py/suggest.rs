@@ -0,0 +1,89 @@
+/// Helpers behind the `suggestions` option: a table of allowed names per
+/// schema path and `_add_suggestions(instance, e)`, which sets `suggestion`
+/// on the errors it can match. See [`crate::suggest`] for the rules.
+use super::emit::ERRORS;
+use super::writer::{escape_py, CodeWriter};
+use crate::suggest::SuggestSite;
+
+pub(super) fn emit_suggest_helpers(w: &mut CodeWriter, sites: &[SuggestSite]) {
+    w.line("_SUGGESTIONS: dict[str, tuple[bool, tuple[str, ...]]] = {");
+    for site in sites {
+        let candidates: Vec<String> = site
+            .candidates
+            .iter()
+            .map(|c| format!("\"{}\"", escape_py(c)))
+            .collect();
+        w.line(&format!(
+            "    \"{}\": ({}, ({},)),",
+            escape_py(&site.schema_path),
+            if site.key { "True" } else { "False" },
+            candidates.join(", ")
+        ));
+    }
+    w.line("}");
+    w.line("");
+    w.line("");
+    w.open("def _edit_distance(a: str, b: str) -> int");
+    w.line("d = [[i] + [0] * len(b) for i in range(len(a) + 1)]");
+    w.line("d[0] = list(range(len(b) + 1))");
+    w.open("for i in range(1, len(a) + 1)");
+    w.open("for j in range(1, len(b) + 1)");
+    w.line("cost = 0 if a[i - 1] == b[j - 1] else 1");
+    w.line("d[i][j] = min(d[i - 1][j] + 1, d[i][j - 1] + 1, d[i - 1][j - 1] + cost)");
+    w.open("if i > 1 and j > 1 and a[i - 1] == b[j - 2] and a[i - 2] == b[j - 1]");
+    w.line("d[i][j] = min(d[i][j], d[i - 2][j - 2] + 1)");
+    w.dedent();
+    w.dedent();
+    w.dedent();
+    w.line("return d[len(a)][len(b)]");
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.open("def _did_you_mean(word: str, candidates: tuple[str, ...]) -> str | None");
+    w.line("best: str | None = None");
+    w.line("best_d = max(1, len(word) // 3) + 1");
+    w.open("for c in candidates");
+    w.line("d = _edit_distance(word, c)");
+    w.open("if d < best_d");
+    w.line("best, best_d = c, d");
+    w.dedent();
+    w.dedent();
+    w.line("return best");
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.open("def _value_at(v: object, path: str) -> object");
+    w.open("for seg in path.split(\"/\")[1:]");
+    w.open("if isinstance(v, dict)");
+    w.line("v = v.get(seg)");
+    w.close_open("elif isinstance(v, list) and seg.isdigit() and int(seg) < len(v)");
+    w.line("v = v[int(seg)]");
+    w.close_open("else");
+    w.line("return None");
+    w.dedent();
+    w.dedent();
+    w.line("return v");
+    w.dedent();
+    w.line("");
+    w.line("");
+    w.open(&format!(
+        "def _add_suggestions(instance: object, e: {ERRORS}) -> {ERRORS}"
+    ));
+    w.open("for err in e");
+    w.line("site = _SUGGESTIONS.get(err[\"schemaPath\"])");
+    w.open("if site is None");
+    w.line("continue");
+    w.dedent();
+    w.line("key, candidates = site");
+    w.line("ip = err[\"instancePath\"]");
+    w.line("word = ip[ip.rfind(\"/\") + 1:] if key else _value_at(instance, ip)");
+    w.open("if isinstance(word, str)");
+    w.line("suggestion = _did_you_mean(word, candidates)");
+    w.open("if suggestion is not None");
+    w.line("err[\"suggestion\"] = suggestion");
+    w.dedent();
+    w.dedent();
+    w.dedent();
+    w.line("return e");
+    w.dedent();
+}
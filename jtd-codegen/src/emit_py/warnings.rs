@@ -0,0 +1,110 @@
+/// `warnings(instance)`: reports present properties whose schema is marked
+/// `metadata.deprecated`, following a [`WarningPlan`]. Kept apart from
+/// `validate` so the error list is unchanged.
+use super::context::pointer_segment;
+use super::emit::{def_fn_name, ERRORS};
+use super::writer::{escape_py, CodeWriter};
+use crate::compiler::escape_pointer;
+use crate::deprecation::{Prop, Walk, WarningPlan};
+
+/// Emit the definition walkers and the `warnings` entry point.
+pub(super) fn emit_warnings(w: &mut CodeWriter, plan: &WarningPlan) {
+    for (name, walk) in &plan.definitions {
+        w.open(&format!(
+            "def {}(v: object, w: {ERRORS}, p: str) -> None",
+            warn_fn_name(name)
+        ));
+        emit_walk(w, walk, "v", "p", 0);
+        w.dedent();
+        w.line("");
+        w.line("");
+    }
+    w.open(&format!("def warnings(instance: object) -> {ERRORS}"));
+    w.line("\"\"\"Deprecated properties present in instance, as {instancePath, schemaPath}.\"\"\"");
+    w.line(&format!("w: {ERRORS} = []"));
+    if let Some(walk) = &plan.root {
+        emit_walk(w, walk, "instance", "\"\"", 0);
+    }
+    w.line("return w");
+    w.dedent();
+}
+
+fn warn_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "warn_", 1)
+}
+
+fn emit_walk(w: &mut CodeWriter, walk: &Walk, v: &str, p: &str, depth: usize) {
+    match walk {
+        Walk::Object(props) => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            emit_props(w, props, v, p, depth);
+            w.dedent();
+        }
+        Walk::Array(inner) => {
+            let (i, item) = (format!("i{depth}"), format!("x{depth}"));
+            w.open(&format!("if isinstance({v}, list)"));
+            w.open(&format!("for {i}, {item} in enumerate({v})"));
+            emit_walk(
+                w,
+                inner,
+                &item,
+                &format!("{p} + \"/\" + str({i})"),
+                depth + 1,
+            );
+            w.dedent();
+            w.dedent();
+        }
+        Walk::Map(inner) => {
+            let (k, item) = (format!("k{depth}"), format!("x{depth}"));
+            w.open(&format!("if isinstance({v}, dict)"));
+            w.open(&format!("for {k}, {item} in {v}.items()"));
+            emit_walk(
+                w,
+                inner,
+                &item,
+                &format!("{p} + \"/\" + {}", pointer_segment(&k)),
+                depth + 1,
+            );
+            w.dedent();
+            w.dedent();
+        }
+        Walk::Ref(name) => w.line(&format!("{}({v}, w, {p})", warn_fn_name(name))),
+        Walk::Tagged { tag, variants } => {
+            w.open(&format!("if isinstance({v}, dict)"));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!(
+                    "{v}.get(\"{}\") == \"{}\"",
+                    escape_py(tag),
+                    escape_py(value)
+                );
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("elif {cond}"));
+                }
+                emit_props(w, props, v, p, depth);
+            }
+            w.dedent();
+            w.dedent();
+        }
+    }
+}
+
+fn emit_props(w: &mut CodeWriter, props: &[Prop], v: &str, p: &str, depth: usize) {
+    for prop in props {
+        let key = escape_py(&prop.key);
+        let child_v = format!("{v}[\"{key}\"]");
+        let child_p = format!("{p} + \"/{}\"", escape_py(&escape_pointer(&prop.key)));
+        w.open(&format!("if \"{key}\" in {v}"));
+        if prop.deprecated {
+            w.line(&format!(
+                "w.append({{\"instancePath\": {child_p}, \"schemaPath\": \"{}\"}})",
+                escape_py(&prop.schema_path)
+            ));
+        }
+        if let Some(child) = &prop.child {
+            emit_walk(w, child, &child_v, &child_p, depth);
+        }
+        w.dedent();
+    }
+}
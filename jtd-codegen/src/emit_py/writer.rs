@@ -19,11 +19,17 @@ impl CodeWriter {
         }
     }
 
-    /// Write a line at the current indentation level.
+    /// Write a line at the current indentation level. Empty lines get no
+    /// indentation, so there is no trailing whitespace; text of several
+    /// lines has each indented.
     pub fn line(&mut self, text: &str) {
-        self.write_indent();
-        self.buf.push_str(text);
-        self.buf.push('\n');
+        for line in text.split('\n') {
+            if !line.is_empty() {
+                self.write_indent();
+            }
+            self.buf.push_str(line);
+            self.buf.push('\n');
+        }
     }
 
     /// Open a block: write `text:` and increase indent.
@@ -73,6 +79,8 @@ pub fn escape_py(s: &str) -> String {
             '\n' => out.push_str("\\n"),
             '\r' => out.push_str("\\r"),
             '\t' => out.push_str("\\t"),
+            // Other control characters (a NUL cannot appear in source at all)
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
             _ => out.push(c),
         }
     }
@@ -90,6 +98,28 @@ mod tests {
         assert_eq!(w.finish(), "x = 1\n");
     }
 
+    #[test]
+    fn test_blank_line_has_no_indent() {
+        let mut w = CodeWriter::new();
+        w.open("def f()");
+        w.line("");
+        w.line("pass");
+        w.dedent();
+        assert_eq!(w.finish(), "def f():\n\n    pass\n");
+    }
+
+    #[test]
+    fn test_lines_each_indented() {
+        let mut w = CodeWriter::new();
+        w.open("if x");
+        w.line("e.append(1)\nif len(e) >= 2: return e");
+        w.dedent();
+        assert_eq!(
+            w.finish(),
+            "if x:\n    e.append(1)\n    if len(e) >= 2: return e\n"
+        );
+    }
+
     #[test]
     fn test_open_dedent() {
         let mut w = CodeWriter::new();
@@ -144,5 +174,6 @@ mod tests {
         assert_eq!(escape_py("a\\b"), "a\\\\b");
         assert_eq!(escape_py("a\nb"), "a\\nb");
         assert_eq!(escape_py("a\tb"), "a\\tb");
+        assert_eq!(escape_py("a\0b\u{1b}"), "a\\u0000b\\u001b");
     }
 }
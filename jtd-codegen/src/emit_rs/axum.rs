@@ -0,0 +1,57 @@
+/// `ValidatedJson<T>`: an Axum 0.8 extractor that parses the body as JSON,
+/// checks it with `validate` and only then deserializes it into `T`.
+/// Invalid bodies are rejected with 422 and the error array as
+/// `[{"instancePath", "schemaPath"}]`, plus `"message"` with `--messages`;
+/// malformed JSON gets Axum's own `Json` rejection. Generated for the
+/// serde_json backend only.
+use crate::emit_js::CodeWriter;
+
+const EXTRACTOR: &str = r#"/// Axum extractor for a JSON body that `validate` accepts, deserialized
+/// into `T` (the raw `Value` by default). Rejects invalid bodies with 422
+/// and the errors as `[{"instancePath": ..., "schemaPath": ...}]`.
+pub struct ValidatedJson<T = Value>(pub T);
+
+impl<S, T> axum::extract::FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: serde::de::DeserializeOwned,
+{
+    type Rejection = axum::response::Response;
+
+    async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+        use axum::response::IntoResponse;
+
+        let axum::Json(value) = axum::Json::<Value>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let errors = validate(&value);
+        if !errors.is_empty() {
+            let body: Vec<Value> = errors
+                .into_iter()
+                .map(|e| serde_json::json!({"instancePath": e.instance_path, "schemaPath": e.schema_path}))
+                .collect();
+            let status = axum::http::StatusCode::UNPROCESSABLE_ENTITY;
+            return Err((status, axum::Json(body)).into_response());
+        }
+        serde_json::from_value(value).map(ValidatedJson).map_err(|e| {
+            let status = axum::http::StatusCode::UNPROCESSABLE_ENTITY;
+            (status, e.to_string()).into_response()
+        })
+    }
+}"#;
+
+/// Emit the `ValidatedJson` extractor, whose errors have a `message` too
+/// with `messages`.
+pub(super) fn emit_axum(w: &mut CodeWriter, messages: bool) {
+    let extractor = if messages {
+        EXTRACTOR.replace(
+            r#""schemaPath": e.schema_path}"#,
+            r#""schemaPath": e.schema_path, "message": e.message}"#,
+        )
+    } else {
+        EXTRACTOR.to_string()
+    };
+    for line in extractor.lines() {
+        w.line(line);
+    }
+}
@@ -0,0 +1,157 @@
+/// `coerce(instance)`: converts string-typed booleans and numbers in place
+/// where the schema expects them, following a [`CoercePlan`]. Working on
+/// `&mut Value` rather than returning a new value keeps one signature for
+/// serde_json and simd-json (whose `BorrowedValue` carries a lifetime); the
+/// generated code only uses `as_object_mut`, `as_array_mut`, `get_mut` and
+/// `Value::from`, which both provide.
+use super::emit::def_fn_name;
+use crate::coerce::{Coerce, CoercePlan, Scalar};
+use crate::emit_js::CodeWriter;
+
+/// Emit the conversion helpers, the definition coercers and the `coerce`
+/// entry point.
+pub(super) fn emit_coerce(w: &mut CodeWriter, plan: &CoercePlan) {
+    if plan.uses(Scalar::Boolean) {
+        emit_boolean_helper(w);
+    }
+    if plan.uses(Scalar::Integer) || plan.uses(Scalar::Float) {
+        emit_number_helpers(w);
+    }
+    for (name, c) in &plan.definitions {
+        w.open(&format!("fn {}(v: &mut Value)", coerce_fn_name(name)));
+        emit_value(w, c, "v", 0);
+        w.close();
+        w.line("");
+    }
+    w.line("/// Convert `\"42\"` / `\"true\"` style strings in `instance` where the schema expects numbers or booleans.");
+    match &plan.root {
+        Some(c) => {
+            w.open("pub fn coerce(instance: &mut Value)");
+            emit_value(w, c, "instance", 0);
+            w.close();
+        }
+        None => w.line("pub fn coerce(_instance: &mut Value) {}"),
+    }
+}
+
+fn emit_boolean_helper(w: &mut CodeWriter) {
+    w.open("fn coerce_boolean(v: &mut Value)");
+    w.open("let b = match v.as_str()");
+    w.line("Some(\"true\") => true,");
+    w.line("Some(\"false\") => false,");
+    w.line("_ => return,");
+    w.close_with(";");
+    w.line("*v = Value::from(b);");
+    w.close();
+    w.line("");
+}
+
+fn emit_number_helpers(w: &mut CodeWriter) {
+    w.line("/// JSON number syntax: `-?(0|[1-9][0-9]*)(\\.[0-9]+)?([eE][+-]?[0-9]+)?`.");
+    w.open("fn is_json_number(s: &str) -> bool");
+    w.line("let digits = |b: &[u8]| b.iter().take_while(|c| c.is_ascii_digit()).count();");
+    w.line("let b = s.strip_prefix('-').unwrap_or(s).as_bytes();");
+    w.line("let int = digits(b);");
+    w.open("if int == 0 || (int > 1 && b[0] == b'0')");
+    w.line("return false;");
+    w.close();
+    w.line("let mut rest = &b[int..];");
+    w.open("if let Some(frac) = rest.strip_prefix(b\".\")");
+    w.line("let n = digits(frac);");
+    w.open("if n == 0");
+    w.line("return false;");
+    w.close();
+    w.line("rest = &frac[n..];");
+    w.close();
+    w.open("if let [b'e' | b'E', exp @ ..] = rest");
+    w.line(
+        "let exp = exp.strip_prefix(b\"+\").or_else(|| exp.strip_prefix(b\"-\")).unwrap_or(exp);",
+    );
+    w.line("return !exp.is_empty() && digits(exp) == exp.len();");
+    w.close();
+    w.line("rest.is_empty()");
+    w.close();
+    w.line("");
+    w.open("fn coerce_number(v: &mut Value, integer: bool)");
+    w.open("let n = match v.as_str()");
+    w.line("Some(s) if is_json_number(s) => s.parse::<f64>().unwrap_or(f64::NAN),");
+    w.line("Some(_) => return,");
+    w.line("None if integer && v.as_i64().is_none() && v.as_u64().is_none() => match v.as_f64() {");
+    w.line("    Some(n) => n,");
+    w.line("    None => return,");
+    w.line("},");
+    w.line("None => return,");
+    w.close_with(";");
+    w.open("if integer");
+    w.open("if n.fract() == 0.0 && n.abs() <= 9_007_199_254_740_992.0");
+    w.line("*v = Value::from(n as i64);");
+    w.close();
+    w.close_open("else if n.is_finite()");
+    w.line("*v = Value::from(n);");
+    w.close();
+    w.close();
+    w.line("");
+}
+
+fn coerce_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "coerce_", 1)
+}
+
+/// `v` is a `&mut Value` expression.
+fn emit_value(w: &mut CodeWriter, c: &Coerce, v: &str, depth: usize) {
+    match c {
+        Coerce::Scalar(Scalar::Boolean) => w.line(&format!("coerce_boolean({v});")),
+        Coerce::Scalar(Scalar::Integer) => w.line(&format!("coerce_number({v}, true);")),
+        Coerce::Scalar(Scalar::Float) => w.line(&format!("coerce_number({v}, false);")),
+        Coerce::Ref(name) => w.line(&format!("{}({v});", coerce_fn_name(name))),
+        Coerce::Object(props) => {
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            emit_props(w, props, depth);
+            w.close();
+        }
+        Coerce::Array(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(arr) = {v}.as_array_mut()"));
+            w.open(&format!("for {x} in arr.iter_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Coerce::Map(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.open(&format!("for {x} in obj.values_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Coerce::Tagged { tag, variants } => {
+            let t = format!("tag{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.line(&format!(
+                "let {t} = obj.get({tag:?}).and_then(|t| t.as_str()).map(str::to_owned);"
+            ));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{t}.as_deref() == Some({value:?})");
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("else if {cond}"));
+                }
+                emit_props(w, props, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+/// Properties of the object bound to `obj`.
+fn emit_props(w: &mut CodeWriter, props: &[(String, Coerce)], depth: usize) {
+    for (key, c) in props {
+        let x = format!("x{depth}");
+        w.open(&format!("if let Some({x}) = obj.get_mut({key:?})"));
+        emit_value(w, c, &x, depth + 1);
+        w.close();
+    }
+}
@@ -0,0 +1,124 @@
+/// `apply_defaults(instance)`: sets absent optional properties to their
+/// `metadata.default` in place, following a [`DefaultsPlan`]. Defaults are
+/// built with `Value::from` and `Value::from_iter`, which serde_json and
+/// simd-json both implement.
+use super::emit::def_fn_name;
+use crate::defaults::{DefaultsPlan, Fill, Shape};
+use crate::emit_js::CodeWriter;
+use serde_json::Value;
+
+/// Emit the definition fillers and the `apply_defaults` entry point.
+pub(super) fn emit_defaults(w: &mut CodeWriter, plan: &DefaultsPlan) {
+    for (name, fill) in &plan.definitions {
+        w.open(&format!("fn {}(v: &mut Value)", defaults_fn_name(name)));
+        emit_value(w, fill, "v", 0);
+        w.close();
+        w.line("");
+    }
+    w.line("/// Set absent optional properties in `instance` to their metadata.default.");
+    match &plan.root {
+        Some(fill) => {
+            w.open("pub fn apply_defaults(instance: &mut Value)");
+            emit_value(w, fill, "instance", 0);
+            w.close();
+        }
+        None => w.line("pub fn apply_defaults(_instance: &mut Value) {}"),
+    }
+}
+
+fn defaults_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "defaults_", 1)
+}
+
+/// A Rust expression building `value` as a `Value`.
+fn rs_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "Value::from(())".to_string(),
+        Value::Bool(b) => format!("Value::from({b})"),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => format!("Value::from({i}_i64)"),
+            (None, Some(u)) => format!("Value::from({u}_u64)"),
+            _ => format!("Value::from({:?}_f64)", n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => format!("Value::from({s:?})"),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(rs_literal).collect();
+            format!("Value::from(vec![{}] as Vec<Value>)", items.join(", "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("({k:?}, {})", rs_literal(v)))
+                .collect();
+            format!(
+                "Value::from_iter([{}] as [(&str, Value); {}])",
+                entries.join(", "),
+                entries.len()
+            )
+        }
+    }
+}
+
+/// `v` is a `&mut Value` expression.
+fn emit_value(w: &mut CodeWriter, fill: &Fill, v: &str, depth: usize) {
+    match fill {
+        Fill::Object(shape) => {
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            emit_shape(w, shape, depth);
+            w.close();
+        }
+        Fill::Array(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(arr) = {v}.as_array_mut()"));
+            w.open(&format!("for {x} in arr.iter_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Fill::Map(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.open(&format!("for {x} in obj.values_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Fill::Ref(name) => w.line(&format!("{}({v});", defaults_fn_name(name))),
+        Fill::Tagged { tag, variants } => {
+            let t = format!("tag{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.line(&format!(
+                "let {t} = obj.get({tag:?}).and_then(|t| t.as_str()).map(str::to_owned);"
+            ));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{t}.as_deref() == Some({value:?})");
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("else if {cond}"));
+                }
+                emit_shape(w, shape, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+/// Fill the object bound to `obj`, then recurse.
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, depth: usize) {
+    for (key, default) in &shape.defaults {
+        w.open(&format!("if !obj.contains_key({key:?})"));
+        w.line(&format!(
+            "obj.insert({key:?}.into(), {});",
+            rs_literal(default)
+        ));
+        w.close();
+    }
+    for (key, fill) in &shape.props {
+        let x = format!("x{depth}");
+        w.open(&format!("if let Some({x}) = obj.get_mut({key:?})"));
+        emit_value(w, fill, &x, depth + 1);
+        w.close();
+    }
+}
@@ -0,0 +1,65 @@
+/// `Validated<T>`: a serde `Deserialize` wrapper that reads a `Value`,
+/// checks it with `validate` and only then deserializes it into `T`, and
+/// `deserialize_validated(json)`, which parses and validates a string in
+/// one call. Generated for the serde_json backend only.
+use crate::emit_js::CodeWriter;
+
+const WRAPPER: &str = r#"/// A value that `validate` accepts, deserialized into `T` (the raw `Value`
+/// by default). Deserializing an invalid value fails with its first error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validated<T = Value>(pub T);
+
+impl<T> Validated<T> {
+    /// The validated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> core::ops::Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'de, T: serde::de::DeserializeOwned> serde::Deserialize<'de> for Validated<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let value = <Value as serde::Deserialize>::deserialize(deserializer)?;
+        let errors = validate(&value);
+        match errors.len() {
+            0 => serde_json::from_value(value).map(Validated).map_err(D::Error::custom),
+            1 => Err(D::Error::custom(&errors[0])),
+            n => Err(D::Error::custom(format_args!("{} (and {} more)", errors[0], n - 1))),
+        }
+    }
+}"#;
+
+/// Emit `Validated` and `deserialize_validated`; the error for malformed
+/// JSON carries the parser's message too with `messages`.
+pub(super) fn emit_deserialize(w: &mut CodeWriter, messages: bool) {
+    for line in WRAPPER.lines() {
+        w.line(line);
+    }
+    w.line("");
+    w.line("/// Parse `json` and validate it. Malformed JSON is one error at the root");
+    w.line("/// with an empty schema path.");
+    w.open("pub fn deserialize_validated(json: &str) -> Result<Value, Vec<ValidationError>>");
+    if messages {
+        w.open("let value: Value = serde_json::from_str(json).map_err(|e|");
+        w.line("vec![ValidationError { instance_path: String::new(), schema_path: String::new(), message: e.to_string() }]");
+        w.close_with(")?;");
+    } else {
+        w.open("let value: Value = serde_json::from_str(json).map_err(|_|");
+        w.line(
+            "vec![ValidationError { instance_path: String::new(), schema_path: String::new() }]",
+        );
+        w.close_with(")?;");
+    }
+    w.line("let errors = validate(&value);");
+    w.line("if errors.is_empty() { Ok(value) } else { Err(errors) }");
+    w.close();
+}
@@ -1,38 +1,162 @@
+use super::axum::emit_axum;
+use super::coerce::emit_coerce;
+use super::defaults::emit_defaults;
+use super::deserialize::emit_deserialize;
+use super::mock::emit_mock;
+use super::smoke::emit_smoke_tests;
+use super::strip::emit_strip;
+use super::structs::emit_types;
+use super::suggest::emit_suggest_helpers;
 use super::types;
+use super::warnings::emit_warnings;
 /// Top-level Rust code emitter. Generates a standalone Rust module
 /// that validates serde_json::Value instances against a compiled JTD schema.
 use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::coerce::coerce_plan;
+use crate::compiler::escape_pointer;
+use crate::defaults::defaults_plan;
+use crate::deprecation::warning_plan;
+use crate::docs::function_doc;
 use crate::emit_js::CodeWriter;
+use crate::enums::enum_constants;
+use crate::messages::{Message, Subject};
+use crate::options::{EmitOptions, RsBackend};
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::shape::shape;
+use crate::source_map;
+use crate::strip::strip_plan;
+use crate::subschemas::sub_schemas;
+use crate::suggest::suggest_sites;
 
 /// Emit a complete Rust source file from a compiled schema.
 pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a complete Rust source file from a compiled schema with the given options.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
     let mut w = CodeWriter::new();
 
     w.line("// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
     w.line("// This code is generated from a JSON Type Definition schema.");
     w.line("// Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("rust") {
+            w.line(&format!("// {line}"));
+        }
+    }
     w.line("");
-    w.line("use serde_json::Value;");
+    if opts.rs_no_std {
+        w.line("extern crate alloc;");
+        w.line("");
+        // What the std prelude would bring in, for whichever the code uses
+        w.line("#[allow(unused_imports)]");
+        w.line("use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec, vec::Vec};");
+    }
+    match opts.rs_backend {
+        RsBackend::SerdeJson => w.line("use serde_json::Value;"),
+        RsBackend::SimdJson => {
+            w.line("use simd_json::prelude::*;");
+            w.line("use simd_json::BorrowedValue as Value;");
+        }
+    }
     w.line("");
 
+    emit_pointer_type(&mut w, opts);
+    emit_error_type(&mut w, opts);
     if needs_timestamp(&schema.root, &schema.definitions) {
         emit_timestamp_helper(&mut w);
     }
+    if opts.messages && needs_describe(&schema.root, &schema.definitions) {
+        emit_describe_helper(&mut w, opts.rs_backend);
+    }
+
+    if opts.enum_constants {
+        let constants = enum_constants(&[("", &schema.root)], &schema.definitions);
+        for c in &constants {
+            let items: Vec<String> = c.values.iter().map(|v| format!("{v:?}")).collect();
+            w.line(&format!(
+                "pub const {}: &[&str] = &[{}];",
+                c.name,
+                items.join(", ")
+            ));
+        }
+        if !constants.is_empty() {
+            w.line("");
+        }
+    }
+
+    let recursive = recursive(&schema.definitions);
+    if !recursive.is_empty() && opts.rs_no_std {
+        // No threads to keep it per thread without std
+        w.line("static REF_DEPTH: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);");
+        w.line("");
+    } else if !recursive.is_empty() {
+        // Per thread, as threads may validate concurrently
+        w.open("thread_local!");
+        w.line("static REF_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };");
+        w.close();
+        w.line("");
+    }
 
     for (name, node) in &schema.definitions {
         let fn_name = def_fn_name(name);
+        emit_doc(
+            &mut w,
+            &function_doc(
+                schema,
+                &format!("/definitions/{}", escape_pointer(name)),
+                node,
+            ),
+        );
+        let block = checks_block(opts, &[node]);
         w.open(&format!(
-            "fn {fn_name}(v: &Value, e: &mut Vec<(String, String)>, p: &str, sp: &str)"
+            "fn {fn_name}(v: &Value, e: &mut Vec<{ERROR}>, p: {POINTER}<'_>, sp: {POINTER}<'_>{})",
+            limit_param(opts, block)
         ));
-        emit_node(&mut w, node, "v", "p", "sp", "e", 0, None);
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            emit_depth_enter(&mut w, opts);
+        }
+        if block {
+            w.open("'checks:");
+        }
+        emit_node(&mut w, node, "v", "p", "sp", "e", 0, opts, None);
+        if block {
+            w.close();
+        }
+        if guarded {
+            w.line(&set_ref_depth(opts, "depth"));
+        }
         w.close();
         w.line("");
     }
 
-    w.open("pub fn validate(instance: &Value) -> Vec<(String, String)>");
-    w.line("let mut e: Vec<(String, String)> = Vec::new();");
-    w.line("let p = \"\";");
-    w.line("let sp = \"\";");
+    emit_doc(&mut w, &function_doc(schema, "", &schema.root));
+    let block = checks_block(opts, &[&schema.root]);
+    if let Some(n) = opts.error_limit() {
+        w.open(&format!(
+            "pub fn validate(instance: &Value) -> Vec<{ERROR}>"
+        ));
+        w.line(&format!("validate_with_limit(instance, {n})"));
+        w.close();
+        w.line("");
+        w.line("/// Validate `instance`, returning once there are `max_errors` errors.");
+        w.open(&format!(
+            "pub fn validate_with_limit(instance: &Value{}) -> Vec<{ERROR}>",
+            limit_param(opts, block)
+        ));
+    } else {
+        w.open(&format!(
+            "pub fn validate(instance: &Value) -> Vec<{ERROR}>"
+        ));
+    }
+    w.line(&format!("let mut e: Vec<{ERROR}> = Vec::new();"));
+    w.line(&format!("let p = {POINTER}::Base(\"\");"));
+    w.line(&format!("let sp = {POINTER}::Base(\"\");"));
+    if block {
+        w.open("'checks:");
+    }
     emit_node(
         &mut w,
         &schema.root,
@@ -41,19 +165,248 @@ pub fn emit(schema: &CompiledSchema) -> String {
         "sp",
         "&mut e",
         0,
+        opts,
         None,
     );
+    if block {
+        w.close();
+    }
     w.line("e");
     w.close();
 
+    if opts.is_valid {
+        w.line("");
+        emit_is_valid(&mut w, schema, opts);
+    }
+
+    if opts.self_test {
+        w.line("");
+        emit_self_test(&mut w, schema, opts);
+    }
+
+    if opts.deprecation_warnings {
+        w.line("");
+        emit_warnings(&mut w, &warning_plan(schema));
+    }
+
+    if opts.validate_at {
+        w.line("");
+        emit_validate_at(&mut w, schema, opts);
+    }
+
+    if opts.suggestions {
+        w.line("");
+        emit_suggest_helpers(&mut w, &suggest_sites(schema));
+    }
+
+    if opts.coerce {
+        w.line("");
+        emit_coerce(&mut w, &coerce_plan(schema));
+    }
+
+    if opts.strip_additional {
+        w.line("");
+        emit_strip(&mut w, &strip_plan(schema));
+    }
+
+    if opts.apply_defaults {
+        w.line("");
+        emit_defaults(&mut w, &defaults_plan(schema));
+    }
+
+    if opts.mock {
+        w.line("");
+        emit_mock(&mut w, schema, opts.rs_backend);
+    }
+
+    if opts.shape {
+        w.line("");
+        w.line("/// Object keys, enum values and discriminator tags by instance path, as");
+        w.line("/// JSON for form and error UIs.");
+        let json = shape(schema).to_string();
+        w.line(&format!("pub const SHAPE_JSON: &str = {json:?};"));
+    }
+
+    if opts.rs_axum && opts.rs_backend == RsBackend::SerdeJson {
+        w.line("");
+        emit_axum(&mut w, opts.messages);
+    }
+
+    if opts.rs_deserialize && opts.rs_backend == RsBackend::SerdeJson {
+        w.line("");
+        emit_deserialize(&mut w, opts.messages);
+    }
+
+    if opts.rs_types {
+        w.line("");
+        for line in emit_types(schema).lines() {
+            w.line(line);
+        }
+    }
+
+    if opts.rs_tests {
+        w.line("");
+        emit_smoke_tests(&mut w, schema, opts);
+    }
+
     w.finish()
 }
 
-fn def_fn_name(name: &str) -> String {
+/// `is_valid(instance)`: the checks `validate` makes, returning false at the
+/// first failure without building errors or paths, and a predicate per
+/// definition. Recursive ones share the validators' depth count.
+fn emit_is_valid(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    let recursive = recursive(&schema.definitions);
+    for (name, node) in &schema.definitions {
+        w.open(&format!("fn {}(v: &Value) -> bool", pred_fn_name(name)));
+        if recursive.contains(name.as_str()) {
+            w.line(&format!("let depth = {};", get_ref_depth(opts)));
+            w.open(&format!("if depth >= {MAX_REF_DEPTH}"));
+            w.line("return false;");
+            w.close();
+            w.line(&set_ref_depth(opts, "depth + 1"));
+            emit_predicate_checks(w, node, "v", opts, "let valid = ", ";");
+            w.line(&set_ref_depth(opts, "depth"));
+            w.line("valid");
+        } else {
+            emit_predicate_checks(w, node, "v", opts, "", "");
+        }
+        w.close();
+        w.line("");
+    }
+    w.line("/// Whether `instance` is valid; stops at the first failure and builds no errors.");
+    w.open("pub fn is_valid(instance: &Value) -> bool");
+    emit_predicate_checks(w, &schema.root, "instance", opts, "", "");
+    w.close();
+}
+
+/// A predicate's checks on `val`, evaluating to whether it passed: `true`,
+/// or a `'checks` block a failure leaves with false. `lead` starts the
+/// first line, e.g. `let valid =`, and `end` closes the last.
+fn emit_predicate_checks(
+    w: &mut CodeWriter,
+    node: &Node,
+    val: &str,
+    opts: &EmitOptions,
+    lead: &str,
+    end: &str,
+) {
+    if !can_fail(node) {
+        w.line(&format!("{lead}true{end}"));
+        return;
+    }
+    w.open(&format!("{lead}'checks:"));
+    emit_node(w, node, val, "p", "sp", PREDICATE, 0, opts, None);
+    w.line("true");
+    w.close_with(end);
+}
+
+/// `validate_at(pointer, value)`: validate a fragment against one
+/// sub-schema, reporting schema paths from `pointer` on. `None` when no
+/// sub-schema lives at `pointer`.
+fn emit_validate_at(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    w.line("/// Validate `value` against the sub-schema at `pointer`, e.g. `/properties/address`.");
+    w.open(&format!(
+        "pub fn validate_at(pointer: &str, value: &Value) -> Option<Vec<{ERROR}>>"
+    ));
+    w.line(&format!("let mut e: Vec<{ERROR}> = Vec::new();"));
+    w.line(&format!("let p = {POINTER}::Base(\"\");"));
+    w.line(&format!("let sp = {POINTER}::Base(pointer);"));
+    let subs = sub_schemas(schema);
+    let nodes: Vec<&Node> = subs.iter().skip(1).map(|sub| sub.node).collect();
+    let block = checks_block(opts, &nodes);
+    if let Some(n) = opts.error_limit().filter(|_| block) {
+        w.line(&format!("let {LIMIT_VAR} = {n};"));
+    }
+    if block {
+        w.open("'checks:");
+    }
+    w.open("match pointer");
+    w.line("\"\" => return Some(validate(value)),");
+    for sub in subs.iter().skip(1) {
+        w.open(&format!("{:?} =>", sub.pointer));
+        emit_node(w, sub.node, "value", "p", "sp", "&mut e", 0, opts, sub.tag);
+        w.close();
+    }
+    w.line("_ => return None,");
+    w.close();
+    if block {
+        w.close();
+    }
+    w.line("Some(e)");
+    w.close();
+}
+
+/// `self_test()`: validate each `metadata.examples` entry (root and
+/// definitions) and return `(schema path, example index, errors)` for failures.
+fn emit_self_test(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    let limit_arg = opts
+        .error_limit()
+        .map_or(String::new(), |n| format!(", {n}"));
+    w.line("/// Validate the schema's metadata.examples; returns the examples that fail.");
+    w.line("#[allow(clippy::type_complexity)]");
+    w.open(&format!(
+        "pub fn self_test() -> Vec<(String, usize, Vec<{ERROR}>)>"
+    ));
+    w.line("let mut failures = Vec::new();");
+    for (sp, def, examples) in schema.example_sets() {
+        for (i, example) in examples.iter().enumerate() {
+            let json = raw_str(&serde_json::to_string(example).unwrap_or_default());
+            match opts.rs_backend {
+                RsBackend::SerdeJson => {
+                    w.line(&format!(
+                        "let example: Value = serde_json::from_str({json}).expect(\"example is JSON\");"
+                    ));
+                }
+                RsBackend::SimdJson => {
+                    w.line(&format!("let mut bytes = {json}.as_bytes().to_vec();"));
+                    w.line("let example = simd_json::to_borrowed_value(&mut bytes).expect(\"example is JSON\");");
+                }
+            }
+            match def {
+                None => w.line("let e = validate(&example);"),
+                Some(name) => {
+                    w.line("let mut e = Vec::new();");
+                    w.line(&format!(
+                        "{}(&example, &mut e, {POINTER}::Base(\"\"), {POINTER}::Base({sp:?}){limit_arg});",
+                        def_fn_name(name)
+                    ));
+                }
+            }
+            w.open("if !e.is_empty()");
+            w.line(&format!("failures.push(({sp:?}.to_string(), {i}, e));"));
+            w.close();
+        }
+    }
+    w.line("failures");
+    w.close();
+}
+
+/// A raw string literal holding `s`, with enough `#`s to never close early.
+pub(super) fn raw_str(s: &str) -> String {
+    let mut hashes = 0;
+    while s.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let h = "#".repeat(hashes);
+    format!("r{h}\"{s}\"{h}")
+}
+
+fn emit_doc(w: &mut CodeWriter, lines: &[String]) {
+    for line in lines {
+        if line.is_empty() {
+            w.line("///");
+        } else {
+            w.line(&format!("/// {line}"));
+        }
+    }
+}
+
+pub(super) fn def_fn_name(name: &str) -> String {
     let safe: String = name
         .chars()
         .map(|c| {
-            if c.is_alphanumeric() || c == '_' {
+            if c.is_ascii_alphanumeric() || c == '_' {
                 c
             } else {
                 '_'
@@ -63,6 +416,11 @@ fn def_fn_name(name: &str) -> String {
     format!("validate_{safe}")
 }
 
+/// Name of a definition's predicate in `is_valid`.
+fn pred_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "is_valid_", 1)
+}
+
 fn needs_timestamp(root: &Node, defs: &std::collections::BTreeMap<String, Node>) -> bool {
     node_uses_timestamp(root) || defs.values().any(node_uses_timestamp)
 }
@@ -83,34 +441,376 @@ fn node_uses_timestamp(node: &Node) -> bool {
     }
 }
 
+/// RFC 3339 checks by hand, so generated code needs no crate beyond
+/// serde_json. Matches [`crate::timestamp::is_rfc3339`]: days per month,
+/// leap years and a leap second.
 fn emit_timestamp_helper(w: &mut CodeWriter) {
     w.open("fn is_rfc3339(s: &str) -> bool");
-    w.line("use std::sync::OnceLock;");
-    w.line("static RE: OnceLock<regex::Regex> = OnceLock::new();");
-    w.line("let re = RE.get_or_init(|| regex::Regex::new(r\"^\\d{4}-\\d{2}-\\d{2}[Tt]\\d{2}:\\d{2}:(\\d{2}|60)(\\.\\d+)?([Zz]|[+-]\\d{2}:\\d{2})$\").unwrap());");
-    w.line("if !re.is_match(s) { return false; }");
-    w.line("let normalized = s.replace(\":60\", \":59\");");
-    w.line("chrono::DateTime::parse_from_rfc3339(&normalized).is_ok()");
+    w.line("let b = s.as_bytes();");
+    w.open("let digits = |at: usize, n: usize| -> Option<u32>");
+    w.line("b.get(at..at + n)?");
+    w.line("  .iter()");
+    w.line(
+        "  .try_fold(0u32, |acc, c| c.is_ascii_digit().then(|| acc * 10 + u32::from(c - b'0')))",
+    );
+    w.close_with(";");
+    w.line("let sep = |i: usize, options: &[u8]| b.get(i).is_some_and(|c| options.contains(c));");
+    w.open("if !(sep(4, b\"-\") && sep(7, b\"-\") && sep(10, b\"Tt\") && sep(13, b\":\") && sep(16, b\":\"))");
+    w.line("return false;");
+    w.close();
+    w.open("let (Some(year), Some(month), Some(day)) = (digits(0, 4), digits(5, 2), digits(8, 2)) else");
+    w.line("return false;");
+    w.close_with(";");
+    w.open("let (Some(hour), Some(minute), Some(second)) = (digits(11, 2), digits(14, 2), digits(17, 2)) else");
+    w.line("return false;");
+    w.close_with(";");
+    w.line("let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));");
+    w.open("let days = match month");
+    w.line("2 => 28 + u32::from(leap),");
+    w.line("4 | 6 | 9 | 11 => 30,");
+    w.line("_ => 31,");
+    w.close_with(";");
+    // Second 60 is a leap second
+    w.open("if !(1..=12).contains(&month) || day < 1 || day > days || hour > 23 || minute > 59 || second > 60");
+    w.line("return false;");
+    w.close();
+    w.line("let mut rest = &b[19..];");
+    w.open("if let Some(frac) = rest.strip_prefix(b\".\")");
+    w.line("let n = frac.iter().take_while(|c| c.is_ascii_digit()).count();");
+    w.open("if n == 0");
+    w.line("return false;");
+    w.close();
+    w.line("rest = &frac[n..];");
+    w.close();
+    w.open("match rest");
+    w.line("[b'Z' | b'z'] => true,");
+    w.open("[b'+' | b'-', ..] if rest.len() == 6 && rest[3] == b':' =>");
+    w.line("let b = rest;");
+    w.line("let two = |at: usize| (b[at].is_ascii_digit() && b[at + 1].is_ascii_digit()).then(|| u32::from(b[at] - b'0') * 10 + u32::from(b[at + 1] - b'0'));");
+    w.line("matches!((two(1), two(4)), (Some(h), Some(m)) if h <= 23 && m <= 59)");
+    w.close();
+    w.line("_ => false,");
+    w.close();
+    w.close();
+    w.line("");
+}
+
+/// The type of the instance and schema paths validation carries.
+const POINTER: &str = "LazyPointer";
+
+/// Paths as a chain of segments borrowed from the caller's, formatted only
+/// when an error reports them: descending into a property or element then
+/// costs no allocation, where formatting a new string at every step would.
+fn emit_pointer_type(w: &mut CodeWriter, opts: &EmitOptions) {
+    let fmt = if opts.rs_no_std {
+        "core::fmt"
+    } else {
+        "std::fmt"
+    };
+    w.line("/// A JSON Pointer built only when an error reports it: each descent");
+    w.line("/// links a segment onto its parent instead of formatting a new string.");
+    w.line("#[derive(Clone, Copy)]");
+    // Schemas without values or elements never link a key or an index
+    w.line("#[allow(dead_code)]");
+    w.open(&format!("enum {POINTER}<'a>"));
+    w.line("/// A whole pointer, e.g. `\"\"` or `\"/definitions/node\"`.");
+    w.line("Base(&'a str),");
+    w.line("/// The parent followed by already escaped text, e.g. `\"/properties/id\"`.");
+    w.line(&format!("Lit(&'a {POINTER}<'a>, &'static str),"));
+    w.line("/// The parent followed by an object key, escaped per RFC 6901.");
+    w.line(&format!("Key(&'a {POINTER}<'a>, &'a str),"));
+    w.line("/// The parent followed by an array index.");
+    w.line(&format!("Index(&'a {POINTER}<'a>, usize),"));
+    w.close();
+    w.line("");
+    w.open(&format!("impl {fmt}::Display for {POINTER}<'_>"));
+    w.open(&format!(
+        "fn fmt(&self, f: &mut {fmt}::Formatter<'_>) -> {fmt}::Result"
+    ));
+    w.open("match self");
+    w.line(&format!("{POINTER}::Base(s) => f.write_str(s),"));
+    w.line(&format!(
+        "{POINTER}::Lit(p, s) => write!(f, \"{{p}}{{s}}\"),"
+    ));
+    w.line(&format!(
+        "{POINTER}::Key(p, k) => write!(f, \"{{p}}/{{}}\", {}),",
+        pointer_segment("k")
+    ));
+    w.line(&format!(
+        "{POINTER}::Index(p, i) => write!(f, \"{{p}}/{{i}}\"),"
+    ));
+    w.close();
+    w.close();
+    w.close();
+    w.line("");
+}
+
+/// Whether messages describe a value anywhere: any node but the empty form
+/// and refs can fail on one.
+fn needs_describe(root: &Node, defs: &std::collections::BTreeMap<String, Node>) -> bool {
+    fn describes(node: &Node) -> bool {
+        match node {
+            Node::Empty | Node::Ref { .. } => false,
+            Node::Nullable { inner } => describes(inner),
+            _ => true,
+        }
+    }
+    describes(root) || defs.values().any(describes)
+}
+
+/// Helper describing a value in messages: its JSON type, or the number
+/// itself.
+const DESCRIBE_FN: &str = "describe";
+
+fn emit_describe_helper(w: &mut CodeWriter, backend: RsBackend) {
+    w.open(&format!("fn {DESCRIBE_FN}(v: &Value) -> String"));
+    match backend {
+        RsBackend::SerdeJson => {
+            w.open("match v");
+            w.line("Value::Null => \"null\".to_string(),");
+            w.line("Value::Bool(_) => \"boolean\".to_string(),");
+            w.line("Value::Number(n) => n.to_string(),");
+            w.line("Value::String(_) => \"string\".to_string(),");
+            w.line("Value::Array(_) => \"array\".to_string(),");
+            w.line("Value::Object(_) => \"object\".to_string(),");
+            w.close();
+        }
+        RsBackend::SimdJson => {
+            w.open("let kind = if v.is_null()");
+            w.line("\"null\"");
+            w.close_open("else if v.is_bool()");
+            w.line("\"boolean\"");
+            w.close_open("else if v.is_str()");
+            w.line("\"string\"");
+            w.close_open("else if v.is_array()");
+            w.line("\"array\"");
+            w.close_open("else if v.is_object()");
+            w.line("\"object\"");
+            w.close_open("else");
+            w.line("return v.to_string();");
+            w.close_with(";");
+            w.line("kind.to_string()");
+        }
+    }
+    w.close();
+    w.line("");
+}
+
+/// The element type of the error vectors.
+const ERROR: &str = "ValidationError";
+
+/// `ValidationError`: the instance and schema paths, and the message with
+/// `--messages`, as public fields. `into_tuple` gives the tuple validators
+/// returned before it.
+fn emit_error_type(w: &mut CodeWriter, opts: &EmitOptions) {
+    let krate = if opts.rs_no_std { "core" } else { "std" };
+    w.line("/// A validation error: the value at `instance_path` fails the schema");
+    w.line("/// keyword at `schema_path`, both JSON Pointers.");
+    w.line("#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]");
+    w.open(&format!("pub struct {ERROR}"));
+    w.line("/// Where the value is in the instance, e.g. `/items/0`.");
+    w.line("pub instance_path: String,");
+    w.line("/// The keyword it fails, e.g. `/properties/items/elements/type`.");
+    w.line("pub schema_path: String,");
+    if opts.messages {
+        w.line("/// What is wrong, e.g. `expected uint8, got \"x\"`.");
+        w.line("pub message: String,");
+    }
     w.close();
     w.line("");
+    w.open(&format!("impl {ERROR}"));
+    if opts.messages {
+        w.line("/// The error as an `(instance path, schema path, message)` tuple.");
+        w.open("pub fn into_tuple(self) -> (String, String, String)");
+        w.line("(self.instance_path, self.schema_path, self.message)");
+    } else {
+        w.line("/// The error as an `(instance path, schema path)` tuple.");
+        w.open("pub fn into_tuple(self) -> (String, String)");
+        w.line("(self.instance_path, self.schema_path)");
+    }
+    w.close();
+    w.close();
+    w.line("");
+    w.open(&format!("impl {krate}::fmt::Display for {ERROR}"));
+    w.open(&format!(
+        "fn fmt(&self, f: &mut {krate}::fmt::Formatter<'_>) -> {krate}::fmt::Result"
+    ));
+    if opts.messages {
+        w.line("write!(f, \"value at {:?}: {}\", self.instance_path, self.message)");
+    } else {
+        w.line("write!(f, \"value at {:?} fails {:?}\", self.instance_path, self.schema_path)");
+    }
+    w.close();
+    w.close();
+    w.line("");
+    w.line(&format!("impl {krate}::error::Error for {ERROR} {{}}"));
+    w.line("");
+}
+
+/// The Rust expression for the message about `msg`, for the value `val`,
+/// or `None` without `--messages`.
+fn message(opts: &EmitOptions, val: &str, msg: Message) -> Option<String> {
+    if !opts.messages {
+        return None;
+    }
+    let parts = msg.parts();
+    let subject = match parts.subject {
+        Subject::None => return Some(format!("{:?}.to_string()", parts.text)),
+        Subject::Value => format!("{DESCRIBE_FN}({val})"),
+        Subject::Member(key) => {
+            format!("{val}.get({key:?}).map_or(String::new(), {DESCRIBE_FN})")
+        }
+        Subject::MemberString(key) => {
+            format!("{val}.get({key:?}).and_then(|t| t.as_str()).unwrap_or_default()")
+        }
+        Subject::Variable(var) => var.to_string(),
+    };
+    let literal = |s: &str| s.replace('{', "{{").replace('}', "}}");
+    let fmt = format!("{}{{}}{}", literal(&parts.text), literal(parts.end));
+    Some(format!("format!({fmt:?}, {subject})"))
+}
+
+/// Start of a recursive definition's function: from `MAX_REF_DEPTH` nested
+/// calls on, report the value at the definition's schema path instead of
+/// descending, so deep instances cannot overflow the stack. The function
+/// ends by restoring `REF_DEPTH` to `depth`.
+fn emit_depth_enter(w: &mut CodeWriter, opts: &EmitOptions) {
+    w.line(&format!("let depth = {};", get_ref_depth(opts)));
+    w.open(&format!("if depth >= {MAX_REF_DEPTH}"));
+    let message = message(opts, "v", Message::TooDeep);
+    w.line(&push_err(
+        "e",
+        &ip_str("p"),
+        &sp_str("sp"),
+        message.as_deref(),
+    ));
+    w.line("return;");
+    w.close();
+    w.line(&set_ref_depth(opts, "depth + 1"));
+}
+
+/// The expression reading `REF_DEPTH`: a thread local, or with
+/// `rs_no_std` an atomic.
+fn get_ref_depth(opts: &EmitOptions) -> &'static str {
+    if opts.rs_no_std {
+        "REF_DEPTH.load(core::sync::atomic::Ordering::Relaxed)"
+    } else {
+        "REF_DEPTH.with(|d| d.get())"
+    }
+}
+
+/// The statement setting `REF_DEPTH` to `value`.
+fn set_ref_depth(opts: &EmitOptions, value: &str) -> String {
+    if opts.rs_no_std {
+        format!("REF_DEPTH.store({value}, core::sync::atomic::Ordering::Relaxed);")
+    } else {
+        format!("REF_DEPTH.with(|d| d.set({value}));")
+    }
 }
 
 /// Helper: generate a push_error statement.
 /// `err` is the error vec expression (may include `&mut ` prefix),
 /// `ip_expr` builds the instancePath, `sp_expr` builds the schemaPath.
-fn push_err(err: &str, ip_expr: &str, sp_expr: &str) -> String {
+fn push_err(err: &str, ip_expr: &str, sp_expr: &str, message: Option<&str>) -> String {
     let vec_name = err.strip_prefix("&mut ").unwrap_or(err);
-    format!("{vec_name}.push(({ip_expr}, {sp_expr}));")
+    match message {
+        Some(message) => format!(
+            "{vec_name}.push({ERROR} {{ instance_path: {ip_expr}, schema_path: {sp_expr}, message: {message} }});"
+        ),
+        None => format!(
+            "{vec_name}.push({ERROR} {{ instance_path: {ip_expr}, schema_path: {sp_expr} }});"
+        ),
+    }
+}
+
+/// An error push in validation code about `msg` for the value `val`, which
+/// leaves the `'checks` block when failing fast or at the error limit.
+fn report(
+    opts: &EmitOptions,
+    err: &str,
+    ip_expr: &str,
+    sp_expr: &str,
+    val: &str,
+    msg: Message,
+) -> String {
+    if err == PREDICATE {
+        return "break 'checks false;".to_string();
+    }
+    let message = message(opts, val, msg);
+    let push = push_err(err, ip_expr, sp_expr, message.as_deref());
+    if opts.error_limit().is_some() {
+        let vec_name = err.strip_prefix("&mut ").unwrap_or(err);
+        format!("{push} if {vec_name}.len() >= {LIMIT_VAR} {{ break 'checks; }}")
+    } else if opts.fail_fast {
+        format!("{push} break 'checks;")
+    } else {
+        push
+    }
 }
 
-/// `ip` and `sp` are always Rust variable names of type `&str`.
-/// To build "ip + /foo" we emit `format!("{{ip}}/foo")`.
+/// The `err` of an `is_valid` predicate's checks: there are no errors to
+/// record or paths to build, and a failure leaves `'checks` with false.
+const PREDICATE: &str = "";
+
+/// The variable definitions and `validate_with_limit` take the error
+/// limit in.
+const LIMIT_VAR: &str = "max_errors";
+
+/// The error limit parameter of a function, `_`-prefixed when it has no
+/// `'checks` block to use it in.
+fn limit_param(opts: &EmitOptions, block: bool) -> String {
+    match opts.error_limit() {
+        Some(_) if block => format!(", {LIMIT_VAR}: usize"),
+        Some(_) => format!(", _{LIMIT_VAR}: usize"),
+        None => String::new(),
+    }
+}
+
+/// Whether validation code for `nodes` goes in a block labelled `'checks`,
+/// as it does when failing fast or limiting errors unless no node can
+/// fail (the label would go unused).
+fn checks_block(opts: &EmitOptions, nodes: &[&Node]) -> bool {
+    (opts.fail_fast || opts.error_limit().is_some()) && nodes.iter().any(|node| can_fail(node))
+}
+
+/// Whether any value can fail `node`'s checks.
+fn can_fail(node: &Node) -> bool {
+    match node {
+        Node::Empty => false,
+        Node::Nullable { inner } => !matches!(inner.as_ref(), Node::Empty),
+        _ => true,
+    }
+}
+
+/// `ip` and `sp` are always Rust variable names of type `LazyPointer`,
+/// which formats as the pointer. Errors build "ip + /foo" with
+/// `format!("{{ip}}/foo")`; descents link a segment with [`descend`].
 fn ip_str(ip: &str) -> String {
     format!("{ip}.to_string()")
 }
 
 fn ip_with(ip: &str, suffix: &str) -> String {
-    format!("format!(\"{{{}}}{}\")", ip, suffix)
+    format!("format!(\"{{{}}}{}\")", ip, format_literal(suffix))
+}
+
+/// `s` as literal text inside a `format!` string: escaped for the string
+/// literal, with braces doubled.
+fn format_literal(s: &str) -> String {
+    s.replace('{', "{{")
+        .replace('}', "}}")
+        .escape_debug()
+        .to_string()
+}
+
+/// The Rust expression escaping the key in `kv` as a JSON Pointer segment
+/// (RFC 6901), so keys with `/` or `~` give unambiguous paths.
+pub(super) fn pointer_segment(kv: &str) -> String {
+    format!("{kv}.replace('~', \"~0\").replace('/', \"~1\")")
+}
+
+/// The path `path` followed by the escaped `suffix`, for descending.
+fn descend(path: &str, suffix: &str) -> String {
+    format!("{POINTER}::Lit(&{path}, {suffix:?})")
 }
 
 fn sp_str(sp: &str) -> String {
@@ -118,9 +818,11 @@ fn sp_str(sp: &str) -> String {
 }
 
 fn sp_with(sp: &str, suffix: &str) -> String {
-    format!("format!(\"{{{}}}{}\")", sp, suffix)
+    format!("format!(\"{{{}}}{}\")", sp, format_literal(suffix))
 }
 
+/// Emit the checks of one AST node, in source-map markers with
+/// [`EmitOptions::source_map`].
 #[allow(clippy::too_many_arguments)]
 fn emit_node(
     w: &mut CodeWriter,
@@ -130,34 +832,112 @@ fn emit_node(
     sp: &str,
     err: &str,
     depth: usize,
+    opts: &EmitOptions,
+    discrim_tag: Option<&str>,
+) {
+    if !opts.source_map {
+        return emit_node_checks(w, node, val, ip, sp, err, depth, opts, discrim_tag);
+    }
+    w.line(&source_map::open(node));
+    emit_node_checks(w, node, val, ip, sp, err, depth, opts, discrim_tag);
+    w.line(source_map::close());
+}
+
+/// [`emit_node`] without the source-map markers.
+#[allow(clippy::too_many_arguments)]
+fn emit_node_checks(
+    w: &mut CodeWriter,
+    node: &Node,
+    val: &str,
+    ip: &str,
+    sp: &str,
+    err: &str,
+    depth: usize,
+    opts: &EmitOptions,
     discrim_tag: Option<&str>,
 ) {
     match node {
         Node::Empty => {}
 
         Node::Type { type_kw } => {
-            let cond = types::type_condition(*type_kw, val);
+            let cond = if opts.rs_no_std {
+                types::type_condition_no_std(*type_kw, val)
+            } else {
+                types::type_condition_for(opts.rs_backend, *type_kw, val)
+            };
             w.open(&format!("if {cond}"));
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/type")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/type"),
+                val,
+                Message::Type(*type_kw),
+            ));
+            w.close();
+        }
+
+        Node::Enum { values } if opts.rs_phf => {
+            // Scoped to the enclosing block, which holds no other node
+            w.line(&format!(
+                "static VALUES: phf::Set<&'static str> = {};",
+                phf_set(values.iter().map(String::as_str))
+            ));
+            w.open(&format!(
+                "if !{val}.as_str().map_or(false, |s| VALUES.contains(s))"
+            ));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/enum"),
+                val,
+                Message::Enum(values),
+            ));
             w.close();
         }
 
         Node::Enum { values } => {
-            let items: Vec<String> = values.iter().map(|v| format!("\"{}\"", v)).collect();
+            let items: Vec<String> = values.iter().map(|v| format!("{v:?}")).collect();
             let arr = items.join(", ");
             w.open(&format!(
                 "if !{val}.as_str().map_or(false, |s| [{arr}].contains(&s))"
             ));
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/enum")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/enum"),
+                val,
+                Message::Enum(values),
+            ));
+            w.close();
+        }
+
+        Node::Ref { name } if err == PREDICATE => {
+            w.open(&format!("if !{}({val})", pred_fn_name(name)));
+            w.line("break 'checks false;");
             w.close();
         }
 
         Node::Ref { name } => {
             let fn_name = def_fn_name(name);
-            // Borrow ip in case it's a String variable (e.g. ip_e0)
+            let limit_arg = match opts.error_limit() {
+                Some(_) => format!(", {LIMIT_VAR}"),
+                None => String::new(),
+            };
             w.line(&format!(
-                "{fn_name}({val}, {err}, &{ip}, &format!(\"/definitions/{name}\"));"
+                "{fn_name}({val}, {err}, {ip}, {POINTER}::Base({:?}){limit_arg});",
+                format!("/definitions/{}", escape_pointer(name))
             ));
+            let vec_name = err.strip_prefix("&mut ").unwrap_or(err);
+            if opts.error_limit().is_some() {
+                w.line(&format!(
+                    "if {vec_name}.len() >= {LIMIT_VAR} {{ break 'checks; }}"
+                ));
+            } else if opts.fail_fast {
+                w.line(&format!("if !{vec_name}.is_empty() {{ break 'checks; }}"));
+            }
         }
 
         Node::Nullable { inner } => {
@@ -165,19 +945,23 @@ fn emit_node(
                 return;
             }
             w.open(&format!("if !{val}.is_null()"));
-            emit_node(w, inner, val, ip, sp, err, depth, None);
+            emit_node(w, inner, val, ip, sp, err, depth, opts, None);
             w.close();
         }
 
         Node::Elements { schema } => {
             let iv = idx_var(depth);
             w.open(&format!("if let Some(arr) = {val}.as_array()"));
-            w.open(&format!("for ({iv}, elem) in arr.iter().enumerate()"));
             // Build child ip/sp variable names
             let child_ip = format!("ip_e{depth}");
             let child_sp = format!("sp_e{depth}");
-            w.line(&format!("let {child_ip} = format!(\"{{{ip}}}/{{{iv}}}\");"));
-            w.line(&format!("let {child_sp} = format!(\"{{{sp}}}/elements\");"));
+            if err == PREDICATE {
+                w.open("for elem in arr.iter()");
+            } else {
+                w.open(&format!("for ({iv}, elem) in arr.iter().enumerate()"));
+                w.line(&format!("let {child_ip} = {POINTER}::Index(&{ip}, {iv});"));
+                w.line(&format!("let {child_sp} = {};", descend(sp, "/elements")));
+            }
             emit_node(
                 w,
                 schema,
@@ -186,26 +970,55 @@ fn emit_node(
                 &child_sp,
                 err,
                 depth + 1,
+                opts,
                 None,
             );
             w.close(); // for
             w.close_open("else");
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/elements")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/elements"),
+                val,
+                Message::NotArray,
+            ));
             w.close();
         }
 
         Node::Values { schema } => {
             let kv = key_var(depth);
             w.open(&format!("if let Some(obj) = {val}.as_object()"));
-            w.open(&format!("for ({kv}, vv) in obj"));
             let child_ip = format!("ip_v{depth}");
             let child_sp = format!("sp_v{depth}");
-            w.line(&format!("let {child_ip} = format!(\"{{{ip}}}/{{{kv}}}\");"));
-            w.line(&format!("let {child_sp} = format!(\"{{{sp}}}/values\");"));
-            emit_node(w, schema, "vv", &child_ip, &child_sp, err, depth + 1, None);
+            if err == PREDICATE {
+                w.open("for vv in obj.values()");
+            } else {
+                w.open(&format!("for ({kv}, vv) in obj"));
+                w.line(&format!("let {child_ip} = {POINTER}::Key(&{ip}, {kv});"));
+                w.line(&format!("let {child_sp} = {};", descend(sp, "/values")));
+            }
+            emit_node(
+                w,
+                schema,
+                "vv",
+                &child_ip,
+                &child_sp,
+                err,
+                depth + 1,
+                opts,
+                None,
+            );
             w.close(); // for
             w.close_open("else");
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/values")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/values"),
+                val,
+                Message::NotObject,
+            ));
             w.close();
         }
 
@@ -221,33 +1034,55 @@ fn emit_node(
             };
             w.open(&format!("if let Some(obj) = {val}.as_object()"));
 
-            for (key, child_node) in required {
-                let child_ip = format!("ip_p_{key}");
-                let child_sp = format!("sp_p_{key}");
-                w.open(&format!("if let Some(pv) = obj.get(\"{key}\")"));
-                w.line(&format!("let {child_ip} = format!(\"{{{ip}}}/{key}\");"));
-                w.line(&format!(
-                    "let {child_sp} = format!(\"{{{sp}}}/properties/{key}\");"
-                ));
-                emit_node(w, child_node, "pv", &child_ip, &child_sp, err, depth, None);
+            // Path variables are numbered: keys need not be identifiers
+            for (i, (key, child_node)) in required.iter().enumerate() {
+                let segment = escape_pointer(key);
+                let child_ip = format!("ip_p{i}");
+                let child_sp = format!("sp_p{i}");
+                w.open(&format!("if let Some(pv) = obj.get({key:?})"));
+                if err != PREDICATE {
+                    w.line(&format!(
+                        "let {child_ip} = {};",
+                        descend(ip, &format!("/{segment}"))
+                    ));
+                    w.line(&format!(
+                        "let {child_sp} = {};",
+                        descend(sp, &format!("/properties/{segment}"))
+                    ));
+                }
+                emit_node(
+                    w, child_node, "pv", &child_ip, &child_sp, err, depth, opts, None,
+                );
                 w.close_open("else");
-                w.line(&push_err(
+                w.line(&report(
+                    opts,
                     err,
                     &ip_str(ip),
-                    &sp_with(sp, &format!("/properties/{key}")),
+                    &sp_with(sp, &format!("/properties/{}", escape_pointer(key))),
+                    val,
+                    Message::MissingProperty(key),
                 ));
                 w.close();
             }
 
-            for (key, child_node) in optional {
-                let child_ip = format!("ip_o_{key}");
-                let child_sp = format!("sp_o_{key}");
-                w.open(&format!("if let Some(pv) = obj.get(\"{key}\")"));
-                w.line(&format!("let {child_ip} = format!(\"{{{ip}}}/{key}\");"));
-                w.line(&format!(
-                    "let {child_sp} = format!(\"{{{sp}}}/optionalProperties/{key}\");"
-                ));
-                emit_node(w, child_node, "pv", &child_ip, &child_sp, err, depth, None);
+            for (i, (key, child_node)) in optional.iter().enumerate() {
+                let segment = escape_pointer(key);
+                let child_ip = format!("ip_o{i}");
+                let child_sp = format!("sp_o{i}");
+                w.open(&format!("if let Some(pv) = obj.get({key:?})"));
+                if err != PREDICATE {
+                    w.line(&format!(
+                        "let {child_ip} = {};",
+                        descend(ip, &format!("/{segment}"))
+                    ));
+                    w.line(&format!(
+                        "let {child_sp} = {};",
+                        descend(sp, &format!("/optionalProperties/{segment}"))
+                    ));
+                }
+                emit_node(
+                    w, child_node, "pv", &child_ip, &child_sp, err, depth, opts, None,
+                );
                 w.close();
             }
 
@@ -265,21 +1100,40 @@ fn emit_node(
                     known.push(key);
                 }
                 if known.is_empty() {
-                    w.line(&push_err(
+                    w.line(&report(
+                        opts,
                         err,
-                        &format!("format!(\"{{{ip}}}/{{{kv}}}\")"),
+                        &format!("format!(\"{{{ip}}}/{{}}\", {})", pointer_segment(&kv)),
                         &sp_str(sp),
+                        val,
+                        Message::UnknownProperty(&kv),
                     ));
                 } else {
-                    let conds: Vec<String> = known
-                        .iter()
-                        .map(|k| format!("{kv}.as_str() != \"{k}\""))
-                        .collect();
-                    w.open(&format!("if {}", conds.join(" && ")));
-                    w.line(&push_err(
+                    // serde_json keys are `String`, simd-json keys `Cow<str>`
+                    let key_str = match opts.rs_backend {
+                        RsBackend::SerdeJson => format!("{kv}.as_str()"),
+                        RsBackend::SimdJson => format!("&**{kv}"),
+                    };
+                    if opts.rs_phf {
+                        w.line(&format!(
+                            "static KNOWN: phf::Set<&'static str> = {};",
+                            phf_set(known.iter().copied())
+                        ));
+                        w.open(&format!("if !KNOWN.contains({key_str})"));
+                    } else {
+                        let conds: Vec<String> = known
+                            .iter()
+                            .map(|k| format!("{key_str} != {k:?}"))
+                            .collect();
+                        w.open(&format!("if {}", conds.join(" && ")));
+                    }
+                    w.line(&report(
+                        opts,
                         err,
-                        &format!("format!(\"{{{ip}}}/{{{kv}}}\")"),
+                        &format!("format!(\"{{{ip}}}/{{}}\", {})", pointer_segment(&kv)),
                         &sp_str(sp),
+                        val,
+                        Message::UnknownProperty(&kv),
                     ));
                     w.close();
                 }
@@ -287,54 +1141,107 @@ fn emit_node(
             }
 
             w.close_open("else");
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, guard_suffix)));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, guard_suffix),
+                val,
+                Message::NotObject,
+            ));
             w.close();
         }
 
         Node::Discriminator { tag, mapping } => {
             w.open(&format!("if let Some(obj) = {val}.as_object()"));
-            w.open(&format!("if let Some(tag_val) = obj.get(\"{tag}\")"));
+            w.open(&format!("if let Some(tag_val) = obj.get({tag:?})"));
             w.open("if let Some(tag_str) = tag_val.as_str()");
-            w.open("match tag_str");
-
-            for (variant_key, variant_node) in mapping {
-                let vsp = format!("sp_m_{variant_key}");
-                w.open(&format!("\"{variant_key}\" =>"));
+            if opts.rs_phf {
+                let entries: Vec<String> = mapping
+                    .keys()
+                    .enumerate()
+                    .map(|(i, k)| format!("{k:?} => {i}usize"))
+                    .collect();
                 w.line(&format!(
-                    "let {vsp} = format!(\"{{{sp}}}/mapping/{variant_key}\");"
+                    "static VARIANTS: phf::Map<&'static str, usize> = phf::phf_map! {{ {} }};",
+                    entries.join(", ")
                 ));
-                emit_node(w, variant_node, val, ip, &vsp, err, depth, Some(tag));
+                w.open("match VARIANTS.get(tag_str).copied()");
+            } else {
+                w.open("match tag_str");
+            }
+
+            for (i, (variant_key, variant_node)) in mapping.iter().enumerate() {
+                let vsp = format!("sp_m{i}");
+                if opts.rs_phf {
+                    w.open(&format!("Some({i}) =>"));
+                } else {
+                    w.open(&format!("{variant_key:?} =>"));
+                }
+                if err != PREDICATE {
+                    w.line(&format!(
+                        "let {vsp} = {};",
+                        descend(sp, &format!("/mapping/{}", escape_pointer(variant_key)))
+                    ));
+                }
+                emit_node(w, variant_node, val, ip, &vsp, err, depth, opts, Some(tag));
                 w.close();
             }
 
             w.open("_ =>");
-            w.line(&push_err(
+            w.line(&report(
+                opts,
                 err,
-                &ip_with(ip, &format!("/{tag}")),
+                &ip_with(ip, &format!("/{}", escape_pointer(tag))),
                 &sp_with(sp, "/mapping"),
+                val,
+                Message::UnknownTag(tag),
             ));
             w.close(); // _
             w.close(); // match
 
             w.close_open("else");
-            w.line(&push_err(
+            w.line(&report(
+                opts,
                 err,
-                &ip_with(ip, &format!("/{tag}")),
+                &ip_with(ip, &format!("/{}", escape_pointer(tag))),
                 &sp_with(sp, "/discriminator"),
+                val,
+                Message::TagNotString(tag),
             ));
             w.close(); // tag not string
 
             w.close_open("else");
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/discriminator")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/discriminator"),
+                val,
+                Message::MissingTag(tag),
+            ));
             w.close(); // tag missing
 
             w.close_open("else");
-            w.line(&push_err(err, &ip_str(ip), &sp_with(sp, "/discriminator")));
+            w.line(&report(
+                opts,
+                err,
+                &ip_str(ip),
+                &sp_with(sp, "/discriminator"),
+                val,
+                Message::NotObject,
+            ));
             w.close(); // not object
         }
     }
 }
 
+/// A `phf::phf_set!` invocation holding `items`.
+fn phf_set<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    let items: Vec<String> = items.map(|s| format!("{s:?}")).collect();
+    format!("phf::phf_set! {{ {} }}", items.join(", "))
+}
+
 fn idx_var(depth: usize) -> String {
     if depth == 0 {
         "i".into()
@@ -397,4 +1304,504 @@ mod tests {
         assert!(code.contains("obj.get(\"name\")"));
         assert!(code.contains("/properties/name"));
     }
+
+    #[test]
+    fn test_emit_simd_json_backend() {
+        let schema = json!({
+            "properties": {"name": {"type": "string"}, "age": {"type": "uint8"}}
+        });
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            rs_backend: RsBackend::SimdJson,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("use simd_json::BorrowedValue as Value;"));
+        assert!(!code.contains("serde_json"));
+        assert!(code.contains("is_str()"));
+        assert!(code.contains("cast_f64()"));
+        assert!(code.contains("&**k != \"age\""));
+    }
+
+    #[test]
+    fn test_enum_constants() {
+        let schema = json!({"definitions": {"status": {"enum": ["OPEN", "CLOSED"]}}});
+        let compiled = compiler::compile(&schema).unwrap();
+        let opts = EmitOptions {
+            enum_constants: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub const STATUS_VALUES: &[&str] = &[\"OPEN\", \"CLOSED\"];"));
+    }
+
+    #[test]
+    fn test_descriptions_become_doc_comments() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"description": "A customer"},
+            "properties": {"name": {"type": "string", "metadata": {"description": "Full name"}}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains(
+            "/// A customer\n///\n/// Fields:\n///   /name: Full name\npub fn validate("
+        ));
+    }
+
+    #[test]
+    fn test_self_test() {
+        let compiled = compiler::compile(&json!({
+            "metadata": {"examples": ["ok"]},
+            "definitions": {"id": {"type": "string", "metadata": {"examples": [5]}}},
+            "ref": "id"
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            self_test: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn self_test() -> Vec<(String, usize, Vec<ValidationError>)>"));
+        assert!(code.contains("serde_json::from_str(r#\"\"ok\"\"#)"));
+        assert!(code.contains("validate_id(&example, &mut e, LazyPointer::Base(\"\"), LazyPointer::Base(\"/definitions/id\"));"));
+    }
+
+    #[test]
+    fn test_deprecation_warnings() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"item": {"optionalProperties": {"code": {"metadata": {"deprecated": true}}}}},
+            "properties": {"items": {"elements": {"ref": "item"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            deprecation_warnings: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn warnings(instance: &Value) -> Vec<(String, String)>"));
+        assert!(code.contains("if obj.contains_key(\"code\")"));
+        assert!(code.contains("warn_item(x1, &mut w, &p1);"));
+    }
+
+    #[test]
+    fn test_validate_at() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"address": {"properties": {"zip": {"type": "string"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            validate_at: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "pub fn validate_at(pointer: &str, value: &Value) -> Option<Vec<ValidationError>>"
+        ));
+        assert!(code.contains("\"/properties/address/properties/zip\" => {"));
+        assert!(code.contains("_ => return None,"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["active", "pending"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            suggestions: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("(\"/properties/status/enum\", false, &[\"active\", \"pending\"]),"));
+        assert!(code.contains(
+            "pub fn validate_with_suggestions(instance: &Value) -> Vec<(ValidationError, Option<&'static str>)>"
+        ));
+    }
+
+    #[test]
+    fn test_rs_phf() {
+        let compiled = compiler::compile(&json!({
+            "properties": {
+                "status": {"enum": ["on", "off"]},
+                "ev": {"discriminator": "kind", "mapping": {"a": {"properties": {}}}}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            rs_phf: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "static VALUES: phf::Set<&'static str> = phf::phf_set! { \"on\", \"off\" };"
+        ));
+        assert!(code.contains("VALUES.contains(s)"));
+        assert!(code.contains("phf::phf_map! { \"a\" => 0usize }"));
+        assert!(code.contains("Some(0) =>"));
+        assert!(code.contains("phf::phf_set! { \"ev\", \"status\" }"));
+        assert!(code.contains("!KNOWN.contains(k.as_str())"));
+        assert!(!emit(&compiled).contains("phf"));
+    }
+
+    #[test]
+    fn test_raw_str() {
+        assert_eq!(raw_str("abc"), "r\"abc\"");
+        assert_eq!(raw_str("\"a\""), "r#\"\"a\"\"#");
+        assert_eq!(raw_str("\"#"), "r##\"\"#\"##");
+    }
+
+    #[test]
+    fn test_coerce() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"page": {"type": "uint32"}, "q": {"type": "string"}},
+            "optionalProperties": {"debug": {"type": "boolean"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            coerce: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn coerce(instance: &mut Value)"));
+        assert!(code.contains("if let Some(x0) = obj.get_mut(\"page\")"));
+        assert!(code.contains("coerce_number(x0, true);"));
+        assert!(code.contains("coerce_boolean(x0);"));
+        assert!(!emit(&compiled).contains("coerce"));
+    }
+
+    #[test]
+    fn test_strip_additional() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {
+                "b": {"properties": {"c": {"properties": {}}}, "additionalProperties": true}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            strip_additional: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn strip(instance: &mut Value)"));
+        assert!(code.contains("obj.retain(|k, _| matches!(&**k, \"a\" | \"b\"));"));
+        assert!(!code.contains("fn strip_"));
+        assert!(!emit(&compiled).contains("strip"));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"q": {"type": "string"}},
+            "optionalProperties": {
+                "page": {"type": "uint32", "metadata": {"default": 1}},
+                "tags": {"elements": {"type": "string"}, "metadata": {"default": ["new"]}}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            apply_defaults: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn apply_defaults(instance: &mut Value)"));
+        assert!(code.contains("obj.insert(\"page\".into(), Value::from(1_i64));"));
+        assert!(code.contains(
+            "obj.insert(\"tags\".into(), Value::from(vec![Value::from(\"new\")] as Vec<Value>));"
+        ));
+        assert!(!emit(&compiled).contains("apply_defaults"));
+    }
+
+    #[test]
+    fn test_rs_types() {
+        let compiled =
+            compiler::compile(&json!({"properties": {"id": {"type": "uint32"}}})).unwrap();
+        let opts = EmitOptions {
+            rs_types: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("\npub struct Root {\n    pub id: u32,\n}\n"));
+        assert!(!emit(&compiled).contains("pub struct Root"));
+    }
+
+    #[test]
+    fn test_axum_extractor() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        let opts = EmitOptions {
+            rs_axum: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub struct ValidatedJson<T = Value>(pub T);"));
+        assert!(code.contains("impl<S, T> axum::extract::FromRequest<S> for ValidatedJson<T>"));
+        assert!(!emit(&compiled).contains("ValidatedJson"));
+        let simd = EmitOptions {
+            rs_backend: RsBackend::SimdJson,
+            ..opts
+        };
+        assert!(!emit_with_options(&compiled, &simd).contains("ValidatedJson"));
+    }
+
+    #[test]
+    fn test_deserialize_wrapper() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        let opts = EmitOptions {
+            rs_deserialize: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub struct Validated<T = Value>(pub T);"));
+        assert!(code.contains(
+            "impl<'de, T: serde::de::DeserializeOwned> serde::Deserialize<'de> for Validated<T>"
+        ));
+        assert!(code.contains(
+            "pub fn deserialize_validated(json: &str) -> Result<Value, Vec<ValidationError>> {"
+        ));
+        assert!(!emit(&compiled).contains("Validated<"));
+        let messages = EmitOptions {
+            messages: true,
+            ..opts.clone()
+        };
+        assert!(emit_with_options(&compiled, &messages).contains("message: e.to_string() }]"));
+        let simd = EmitOptions {
+            rs_backend: RsBackend::SimdJson,
+            ..opts
+        };
+        assert!(!emit_with_options(&compiled, &simd).contains("deserialize_validated"));
+    }
+
+    #[test]
+    fn test_smoke_tests() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"id": {"type": "uint8"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            rs_tests: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("#[cfg(test)]\nmod tests {\n  use super::*;\n"));
+        assert!(code.contains("  fn valid() {\n    check(r#\"{\"id\":0}\"#, &[]);\n  }\n"));
+        assert!(code.contains(
+            "  /// wrong type at \"/id\"\n  #[test]\n  fn wrong_type_4() {\n    \
+             check(r#\"{\"id\":0.5}\"#, &[(\"/id\", \"/properties/id/type\")]);\n"
+        ));
+        assert!(!emit(&compiled).contains("#[cfg(test)]"));
+    }
+
+    #[test]
+    fn test_mock() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"head": {"ref": "node"}, "tags": {"elements": {"type": "string"}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            mock: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("pub fn mock(seed: u64) -> Value"));
+        assert!(code.contains("fn mock_node(r: &mut MockRng, d: usize) -> Value"));
+        assert!(code.contains("let v2 = mock_node(r, d + 1);"));
+        assert!(!emit(&compiled).contains("mock"));
+    }
+
+    #[test]
+    fn test_shape() {
+        let compiled = compiler::compile(&json!({
+            "optionalProperties": {"status": {"enum": ["new", "done"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            shape: true,
+            ..EmitOptions::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        let json = crate::shape::shape(&compiled).to_string();
+        assert!(code.contains(&format!("pub const SHAPE_JSON: &str = {json:?};")));
+    }
+
+    #[test]
+    fn test_recursive_definitions_are_depth_limited() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {
+                "node": {"optionalProperties": {"next": {"ref": "node"}}},
+                "id": {"type": "string"}
+            },
+            "properties": {"id": {"ref": "id"}, "head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("thread_local! {\n  static REF_DEPTH"));
+        assert!(code.contains(
+            "fn validate_node(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {\n  let depth = REF_DEPTH.with(|d| d.get());\n  if depth >= 256 {\n    e.push(ValidationError { instance_path: p.to_string(), schema_path: sp.to_string() });\n    return;\n  }\n"
+        ));
+        assert!(code.contains("  REF_DEPTH.with(|d| d.set(depth));\n}\n"));
+        assert_eq!(code.matches("REF_DEPTH.with(|d| d.get())").count(), 1);
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            fail_fast: true,
+            ..Default::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("  REF_DEPTH.with(|d| d.set(depth + 1));\n  'checks: {\n"));
+        assert!(code.contains("if !e.is_empty() { break 'checks; }"));
+        assert!(
+            code.contains("e.push(ValidationError { instance_path: p.to_string(), schema_path: format!(\"{sp}/properties\") }); break 'checks;")
+        );
+        // Nothing to fail, no label
+        let compiled = compiler::compile(&json!({})).unwrap();
+        assert!(!emit_with_options(&compiled, &opts).contains("'checks"));
+    }
+
+    #[test]
+    fn test_max_errors() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}, "any": {}},
+            "properties": {"head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            max_errors: Some(5),
+            ..Default::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains(
+            "pub fn validate(instance: &Value) -> Vec<ValidationError> {\n  validate_with_limit(instance, 5)\n}"
+        ));
+        assert!(code.contains("pub fn validate_with_limit(instance: &Value, max_errors: usize)"));
+        assert!(code.contains("sp: LazyPointer<'_>, _max_errors: usize)"));
+        assert!(code.contains("LazyPointer::Base(\"/definitions/node\"), max_errors);"));
+        assert!(code.contains("if e.len() >= max_errors { break 'checks; }"));
+        assert!(code.contains(
+            "e.push(ValidationError { instance_path: p.to_string(), schema_path: format!(\"{sp}/properties\") }); if e.len() >= max_errors { break 'checks; }"
+        ));
+        // Failing fast wins
+        let opts = EmitOptions {
+            fail_fast: true,
+            ..opts
+        };
+        assert!(!emit_with_options(&compiled, &opts).contains("max_errors"));
+    }
+
+    #[test]
+    fn test_messages() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"n": {"enum": ["{x}"]}, "head": {"ref": "node"}}}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            messages: true,
+            ..Default::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("fn describe(v: &Value) -> String {\n  match v {\n"));
+        assert!(code.contains("pub fn validate(instance: &Value) -> Vec<ValidationError>"));
+        assert!(code.contains("  pub message: String,\n}"));
+        assert!(code.contains("pub fn into_tuple(self) -> (String, String, String)"));
+        assert!(code.contains(
+            "e.push(ValidationError { instance_path: p.to_string(), schema_path: sp.to_string(), message: \"refs nested more than 256 deep\".to_string() });"
+        ));
+        assert!(code.contains("format!(\"expected one of '{{x}}', got {}\", describe(pv))"));
+        assert!(code.contains(
+            "format!(\"unknown discriminator 'kind' value '{}'\", instance.get(\"kind\").and_then(|t| t.as_str()).unwrap_or_default())"
+        ));
+        assert!(code.contains("format!(\"unknown property '{}'\", k)"));
+        assert!(!emit(&compiled).contains("describe"));
+    }
+
+    #[test]
+    fn test_emit_bench_validator_is_current() {
+        // benches/rs_paths.rs measures this output; regenerate it on change
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../../benches/rs_paths/order.json")).unwrap();
+        let compiled = compiler::compile(&schema).unwrap();
+        assert_eq!(
+            emit(&compiled),
+            include_str!("../../benches/rs_paths/lazy.rs")
+        );
+    }
+
+    #[test]
+    fn test_emit_paths_descend_without_formatting() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"a/b": {"elements": {"values": {"type": "string"}}}}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("let ip_p0 = LazyPointer::Lit(&p, \"/a~1b\");"));
+        assert!(code.contains("let sp_p0 = LazyPointer::Lit(&sp, \"/properties/a~1b\");"));
+        assert!(code.contains("let ip_e0 = LazyPointer::Index(&ip_p0, i);"));
+        assert!(code.contains("LazyPointer::Key(&ip_e0, "));
+        assert!(!code.contains("let ip_p0 = format!"));
+    }
+
+    #[test]
+    fn test_emit_timestamp_needs_only_serde_json() {
+        let compiled = compiler::compile(&json!({"type": "timestamp"})).unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("fn is_rfc3339(s: &str) -> bool {"));
+        assert!(code.contains("2 => 28 + u32::from(leap),"));
+        assert!(!code.contains("regex::"));
+        assert!(!code.contains("chrono::"));
+    }
+
+    #[test]
+    fn test_emit_no_std() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"properties": {"next": {"ref": "node", "nullable": true}}}},
+            "properties": {"n": {"type": "uint8"}, "head": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            rs_no_std: true,
+            ..Default::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("extern crate alloc;\n"));
+        assert!(code.contains(
+            "use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec, vec::Vec};"
+        ));
+        assert!(code.contains("impl core::fmt::Display for LazyPointer<'_>"));
+        assert!(code.contains("static REF_DEPTH: core::sync::atomic::AtomicUsize"));
+        assert!(code.contains("let depth = REF_DEPTH.load(core::sync::atomic::Ordering::Relaxed);"));
+        assert!(code.contains("n == n as i64 as f64"));
+        assert!(!code.contains("std::"));
+        assert!(!code.contains("fract()"));
+    }
+
+    #[test]
+    fn test_emit_error_type() {
+        let compiled = compiler::compile(&json!({"type": "string"})).unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains(
+            "pub struct ValidationError {\n  /// Where the value is in the instance, e.g. `/items/0`.\n  pub instance_path: String,\n"
+        ));
+        assert!(!code.contains("pub message: String"));
+        assert!(code.contains(
+            "pub fn into_tuple(self) -> (String, String) {\n    (self.instance_path, self.schema_path)\n  }"
+        ));
+        assert!(code.contains("impl std::fmt::Display for ValidationError {"));
+        assert!(code.contains("impl std::error::Error for ValidationError {}"));
+        assert!(code.contains("pub fn validate(instance: &Value) -> Vec<ValidationError>"));
+        let opts = EmitOptions {
+            rs_no_std: true,
+            ..Default::default()
+        };
+        let code = emit_with_options(&compiled, &opts);
+        assert!(code.contains("impl core::error::Error for ValidationError {}"));
+    }
 }
@@ -0,0 +1,225 @@
+/// `mock(seed)`: a random instance of the schema, built as described in
+/// [`crate::mock`] with the same SplitMix64 generator, so it returns what
+/// `jtd_codegen::mock::mock` does for the seed. Values are built with
+/// `Value::from` and `Value::from_iter`, which serde_json and simd-json
+/// both implement. One `mock_<name>(r, d)` per definition; `d` is the ref
+/// depth.
+use super::emit::def_fn_name;
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::emit_js::CodeWriter;
+use crate::mock::{int_range, MAX_DEPTH, MAX_ITEMS, MAX_STRING};
+use crate::options::RsBackend;
+
+const RNG: &str = r#"/// SplitMix64, the generator behind `mock`.
+struct MockRng(u64);
+
+impl MockRng {
+    fn int(&mut self, lo: i64, hi: i64) -> i64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        lo + (z % (hi - lo + 1) as u64) as i64
+    }
+
+    #[allow(dead_code)]
+    fn string(&mut self) -> String {
+        let len = self.int(0, {MAX_STRING});
+        (0..len).map(|_| (b'a' + self.int(0, 25) as u8) as char).collect()
+    }
+
+    #[allow(dead_code)]
+    fn timestamp(&mut self) -> String {
+        let (y, mo, d) = (self.int(2000, 2030), self.int(1, 12), self.int(1, 28));
+        let (h, mi, s) = (self.int(0, 23), self.int(0, 59), self.int(0, 59));
+        format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}Z")
+    }
+}"#;
+
+/// Emit the generator, one function per definition and `mock`.
+pub(super) fn emit_mock(w: &mut CodeWriter, schema: &CompiledSchema, backend: RsBackend) {
+    let value = match backend {
+        RsBackend::SerdeJson => "Value",
+        RsBackend::SimdJson => "Value<'static>",
+    };
+    for line in RNG.lines() {
+        w.line(&line.replace("{MAX_STRING}", &MAX_STRING.to_string()));
+    }
+    w.line("");
+    for (name, node) in &schema.definitions {
+        let (r, d) = params(node);
+        w.open(&format!(
+            "fn {}({r}: &mut MockRng, {d}: usize) -> {value}",
+            mock_fn_name(name)
+        ));
+        let v = emit_value(w, node, &mut 0);
+        w.line(&v);
+        w.close();
+        w.line("");
+    }
+    w.line("/// A random valid instance; the same seed gives the same instance.");
+    w.open(&format!("pub fn mock(seed: u64) -> {value}"));
+    let (r, d) = params(&schema.root);
+    w.line(&format!("let {r} = &mut MockRng(seed);"));
+    w.line(&format!("let {d}: usize = 0;"));
+    let v = emit_value(w, &schema.root, &mut 0);
+    w.line(&v);
+    w.close();
+}
+
+fn mock_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "mock_", 1)
+}
+
+/// Names for the generator and depth, `_`-prefixed when `node`'s function
+/// does not use them.
+fn params(node: &Node) -> (&'static str, &'static str) {
+    let r = if uses_rng(node) { "r" } else { "_r" };
+    let d = if uses_depth(node) { "d" } else { "_d" };
+    (r, d)
+}
+
+/// True when building `node` draws from the generator.
+fn uses_rng(node: &Node) -> bool {
+    match node {
+        Node::Empty => false,
+        Node::Properties {
+            required, optional, ..
+        } => !optional.is_empty() || required.values().any(uses_rng),
+        _ => true,
+    }
+}
+
+/// True when building `node` reads the depth, without following refs.
+fn uses_depth(node: &Node) -> bool {
+    match node {
+        Node::Ref { .. } | Node::Elements { .. } | Node::Values { .. } | Node::Nullable { .. } => {
+            true
+        }
+        Node::Properties {
+            required, optional, ..
+        } => !optional.is_empty() || required.values().any(uses_depth),
+        Node::Discriminator { mapping, .. } => mapping.values().any(uses_depth),
+        Node::Empty | Node::Type { .. } | Node::Enum { .. } => false,
+    }
+}
+
+/// Emit the statements building a value for `node` into a new variable;
+/// returns its name. Binding every value as soon as it is built keeps the
+/// generator calls in the same order as [`crate::mock::mock`].
+fn emit_value(w: &mut CodeWriter, node: &Node, n: &mut usize) -> String {
+    let v = fresh(n);
+    match node {
+        Node::Empty => w.line(&format!("let {v} = Value::from(());")),
+        Node::Ref { name } => {
+            w.line(&format!("let {v} = {}(r, d + 1);", mock_fn_name(name)));
+        }
+        Node::Type { type_kw } => {
+            let expr = match (type_kw, int_range(*type_kw)) {
+                (_, Some((lo, hi))) => format!("r.int({lo}, {hi})"),
+                (TypeKeyword::Boolean, _) => "r.int(0, 1) == 1".to_string(),
+                (TypeKeyword::String, _) => "r.string()".to_string(),
+                (TypeKeyword::Timestamp, _) => "r.timestamp()".to_string(),
+                _ => "r.int(-1_000_000, 1_000_000) as f64 / 1000.0".to_string(),
+            };
+            w.line(&format!("let {v} = Value::from({expr});"));
+        }
+        Node::Enum { values } => {
+            w.line(&format!(
+                "let {v} = Value::from({values:?}[r.int(0, {}) as usize]);",
+                values.len() - 1
+            ));
+        }
+        Node::Elements { schema } => {
+            let items = format!("{v}_items");
+            w.line(&format!("let mut {items} = Vec::new();"));
+            w.open(&format!("if d < {MAX_DEPTH}"));
+            w.open(&format!("for _ in 0..r.int(0, {MAX_ITEMS})"));
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{items}.push({item});"));
+            w.close();
+            w.close();
+            w.line(&format!("let {v} = Value::from({items});"));
+        }
+        Node::Values { schema } => {
+            let entries = format!("{v}_entries");
+            w.line(&format!("let mut {entries} = Vec::new();"));
+            w.open(&format!("if d < {MAX_DEPTH}"));
+            w.open(&format!("for _ in 0..r.int(0, {MAX_ITEMS})"));
+            w.line("let k = r.string();");
+            let item = emit_value(w, schema, n);
+            w.line(&format!("{entries}.push((k, {item}));"));
+            w.close();
+            w.close();
+            w.line(&format!("let {v} = Value::from_iter({entries});"));
+        }
+        Node::Properties { .. } => {
+            let fields = emit_properties(w, node, Vec::new(), &v, n);
+            w.line(&format!("let {v} = Value::from_iter({fields});"));
+        }
+        Node::Discriminator { tag, mapping } => {
+            w.open(&format!("let {v} = match r.int(0, {})", mapping.len() - 1));
+            for (i, (value, variant)) in mapping.iter().enumerate() {
+                let arm = if i + 1 == mapping.len() {
+                    "_".to_string()
+                } else {
+                    i.to_string()
+                };
+                w.open(&format!("{arm} =>"));
+                let tag_field = format!("({tag:?}.to_string(), Value::from({value:?}))");
+                let fields = emit_properties(w, variant, vec![tag_field], &v, n);
+                w.line(&format!("Value::from_iter({fields})"));
+                w.close();
+            }
+            w.close_with(";");
+        }
+        Node::Nullable { inner } => {
+            w.line(&format!("let mut {v} = Value::from(());"));
+            w.open(&format!("if d < {MAX_DEPTH} && r.int(0, 3) != 0"));
+            let inner = emit_value(w, inner, n);
+            w.line(&format!("{v} = {inner};"));
+            w.close();
+        }
+    }
+    v
+}
+
+/// Emit the fields of the Properties node `node` after `fields` (already
+/// built `(String, Value)` expressions); returns the variable holding them.
+fn emit_properties(
+    w: &mut CodeWriter,
+    node: &Node,
+    mut fields: Vec<String>,
+    v: &str,
+    n: &mut usize,
+) -> String {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        unreachable!("mapping variants are Properties forms");
+    };
+    for (key, child) in required {
+        let value = emit_value(w, child, n);
+        fields.push(format!("({key:?}.to_string(), {value})"));
+    }
+    let var = format!("{v}_fields");
+    let binding = if optional.is_empty() { "" } else { "mut " };
+    w.line(&format!(
+        "let {binding}{var}: Vec<(String, Value)> = vec![{}];",
+        fields.join(", ")
+    ));
+    for (key, child) in optional {
+        w.open(&format!("if d < {MAX_DEPTH} && r.int(0, 1) == 1"));
+        let value = emit_value(w, child, n);
+        w.line(&format!("{var}.push(({key:?}.to_string(), {value}));"));
+        w.close();
+    }
+    var
+}
+
+fn fresh(n: &mut usize) -> String {
+    *n += 1;
+    format!("v{n}")
+}
@@ -1,6 +1,17 @@
 /// Rust code emitter — generates standalone serde_json::Value validators.
+mod axum;
+mod coerce;
 mod context;
+mod defaults;
+mod deserialize;
 mod emit;
+mod mock;
+mod smoke;
+mod strip;
+mod structs;
+mod suggest;
 mod types;
+mod warnings;
 
-pub use emit::emit;
+pub use emit::{emit, emit_with_options};
+pub use structs::emit_types;
@@ -0,0 +1,66 @@
+/// The `#[cfg(test)]` module `--rs-tests` appends: the skeleton instance
+/// and one violation per form from [`crate::smoke::cases`], each checked
+/// against the errors the interpreter reports.
+use crate::ast::CompiledSchema;
+use crate::emit_js::CodeWriter;
+use crate::options::{EmitOptions, RsBackend};
+use crate::smoke::cases;
+
+use super::emit::raw_str;
+
+pub(super) fn emit_smoke_tests(w: &mut CodeWriter, schema: &CompiledSchema, opts: &EmitOptions) {
+    w.line("#[cfg(test)]");
+    w.open("mod tests");
+    w.line("use super::*;");
+    w.line("");
+    w.line("/// Validate `json` and compare the errors with `expected`.");
+    w.open("fn check(json: &str, expected: &[(&str, &str)])");
+    match opts.rs_backend {
+        RsBackend::SerdeJson => {
+            w.line("let instance: Value = serde_json::from_str(json).expect(\"case is JSON\");");
+        }
+        RsBackend::SimdJson => {
+            w.line("let mut bytes = json.as_bytes().to_vec();");
+            w.line(
+                "let instance = simd_json::to_borrowed_value(&mut bytes).expect(\"case is JSON\");",
+            );
+        }
+    }
+    w.line("let errors: Vec<(String, String)> = validate(&instance)");
+    w.line("  .into_iter()");
+    w.line("  .map(|e| (e.instance_path, e.schema_path))");
+    w.line("  .collect();");
+    w.line("let expected: Vec<(String, String)> = expected");
+    w.line("  .iter()");
+    w.line("  .map(|(i, s)| (i.to_string(), s.to_string()))");
+    w.line("  .collect();");
+    w.line("assert_eq!(errors, expected, \"{json}\");");
+    w.close();
+
+    let limit = opts.error_limit().unwrap_or(usize::MAX);
+    for (i, case) in cases(schema).iter().enumerate() {
+        w.line("");
+        if case.kind == "valid" {
+            w.line("#[test]");
+            w.open("fn valid()");
+        } else {
+            w.line(&format!(
+                "/// {} at {:?}",
+                case.kind.replace('_', " "),
+                case.instance_path
+            ));
+            w.line("#[test]");
+            w.open(&format!("fn {}_{i}()", case.kind));
+        }
+        let json = raw_str(&case.instance.to_string());
+        let expected: Vec<String> = case
+            .errors
+            .iter()
+            .take(limit)
+            .map(|(ip, sp)| format!("({ip:?}, {sp:?})"))
+            .collect();
+        w.line(&format!("check({json}, &[{}]);", expected.join(", ")));
+        w.close();
+    }
+    w.close();
+}
@@ -0,0 +1,98 @@
+/// `strip(instance)`: removes, in place, the keys that closed Properties
+/// schemas do not declare, following a [`StripPlan`]. Like `coerce` it takes
+/// `&mut Value` so one signature serves serde_json and simd-json; both maps
+/// provide `retain`.
+use super::emit::def_fn_name;
+use crate::emit_js::CodeWriter;
+use crate::strip::{Shape, Strip, StripPlan};
+
+/// Emit the definition strippers and the `strip` entry point.
+pub(super) fn emit_strip(w: &mut CodeWriter, plan: &StripPlan) {
+    for (name, s) in &plan.definitions {
+        w.open(&format!("fn {}(v: &mut Value)", strip_fn_name(name)));
+        emit_value(w, s, "v", 0);
+        w.close();
+        w.line("");
+    }
+    w.line("/// Remove the properties the schema does not declare from `instance`.");
+    match &plan.root {
+        Some(s) => {
+            w.open("pub fn strip(instance: &mut Value)");
+            emit_value(w, s, "instance", 0);
+            w.close();
+        }
+        None => w.line("pub fn strip(_instance: &mut Value) {}"),
+    }
+}
+
+fn strip_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "strip_", 1)
+}
+
+/// `v` is a `&mut Value` expression.
+fn emit_value(w: &mut CodeWriter, s: &Strip, v: &str, depth: usize) {
+    match s {
+        Strip::Object(shape) => {
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            emit_shape(w, shape, depth);
+            w.close();
+        }
+        Strip::Array(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(arr) = {v}.as_array_mut()"));
+            w.open(&format!("for {x} in arr.iter_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Strip::Map(inner) => {
+            let x = format!("x{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.open(&format!("for {x} in obj.values_mut()"));
+            emit_value(w, inner, &x, depth + 1);
+            w.close();
+            w.close();
+        }
+        Strip::Ref(name) => w.line(&format!("{}({v});", strip_fn_name(name))),
+        Strip::Tagged { tag, variants } => {
+            let t = format!("tag{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object_mut()"));
+            w.line(&format!(
+                "let {t} = obj.get({tag:?}).and_then(|t| t.as_str()).map(str::to_owned);"
+            ));
+            for (i, (value, shape)) in variants.iter().enumerate() {
+                let cond = format!("{t}.as_deref() == Some({value:?})");
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("else if {cond}"));
+                }
+                emit_shape(w, shape, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+/// Drop unknown keys from the object bound to `obj`, then recurse.
+fn emit_shape(w: &mut CodeWriter, shape: &Shape, depth: usize) {
+    match &shape.known {
+        None => {}
+        Some(known) if known.is_empty() => w.line("obj.clear();"),
+        Some(known) => {
+            let keys: Vec<String> = known.iter().map(|k| format!("{k:?}")).collect();
+            // `&**k` is a `&str` for serde_json's `String` and simd-json's `Cow<str>` keys
+            w.line(&format!(
+                "obj.retain(|k, _| matches!(&**k, {}));",
+                keys.join(" | ")
+            ));
+        }
+    }
+    for (key, s) in &shape.props {
+        let x = format!("x{depth}");
+        w.open(&format!("if let Some({x}) = obj.get_mut({key:?})"));
+        emit_value(w, s, &x, depth + 1);
+        w.close();
+    }
+}
@@ -0,0 +1,513 @@
+/// Rust types for a schema, with serde derives, following the conventions
+/// of the JTD project's own Rust codegen:
+///
+/// - Properties become structs and enums `enum`s with a variant per value;
+///   a discriminator is an internally tagged enum (`#[serde(tag = ...)]`)
+///   over one struct per variant.
+/// - Elements are `Vec<T>`, values `HashMap<String, T>`, the empty form
+///   `serde_json::Value` and timestamps `chrono::DateTime<FixedOffset>`.
+/// - Nullable and optional fields are `Option<Box<T>>` for generated types
+///   (which may be recursive) and `Option<T>` otherwise; optional ones are
+///   skipped when `None`.
+/// - Every definition is a type named after it (`order_item` becomes
+///   `OrderItem`), type aliases for other forms; the root is `Root` (an
+///   `Option` alias over `RootValue` when nullable). Anonymous types are
+///   named from their parent and key.
+///
+/// Paths are fully qualified, so the items can follow a generated
+/// validator in the same file.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::graphql::pascal;
+
+const DERIVE: &str = "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]";
+
+/// Names the validator file already uses.
+const RESERVED: [&str; 6] = [
+    "LazyPointer",
+    "Root",
+    "Validated",
+    "ValidatedJson",
+    "ValidationError",
+    "Value",
+];
+
+/// Rust types for `schema`: one per definition, then the root's.
+pub fn emit_types(schema: &CompiledSchema) -> String {
+    let mut types = Types {
+        schema,
+        defs: BTreeMap::new(),
+        used: RESERVED.iter().map(|s| s.to_string()).collect(),
+        newtypes: BTreeSet::new(),
+        items: Vec::new(),
+    };
+    for name in schema.definitions.keys() {
+        let type_name = types.name(name);
+        if alias_cycle(schema, name) {
+            types.newtypes.insert(type_name.clone());
+        }
+        types.defs.insert(name, type_name);
+    }
+    for (name, node) in &schema.definitions {
+        let type_name = types.defs[name.as_str()].clone();
+        let path = format!("/definitions/{}", escape_pointer(name));
+        types.item(&type_name, strip_nullable(node), &path);
+    }
+    types.used.remove("Root");
+    let root = types.name("Root");
+    types.item(&root, &schema.root, "");
+    types.items.join("\n")
+}
+
+/// The Rust type of a value, and whether it admits null.
+struct TypeRef {
+    ty: String,
+    nullable: bool,
+    /// A type generated here, boxed inside `Option` so recursion works.
+    generated: bool,
+}
+
+struct Types<'a> {
+    schema: &'a CompiledSchema,
+    /// Type names of the definitions.
+    defs: BTreeMap<&'a str, String>,
+    /// Type names taken so far.
+    used: BTreeSet<String>,
+    /// Definitions that would be recursive type aliases, emitted as
+    /// transparent newtypes instead.
+    newtypes: BTreeSet<String>,
+    /// Item definitions, parents before the types named after them.
+    items: Vec<String>,
+}
+
+impl<'a> Types<'a> {
+    /// A fresh type name from `hint`.
+    fn name(&mut self, hint: &str) -> String {
+        let mut base = pascal(hint);
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert(0, 'T');
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.used.contains(&name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+        self.used.insert(name.clone());
+        name
+    }
+
+    /// The type of `node` at `path`; anonymous types are named from `hint`.
+    fn type_ref(&mut self, node: &'a Node, path: &str, hint: &str) -> TypeRef {
+        let plain = |ty: &str| TypeRef {
+            ty: ty.to_string(),
+            nullable: false,
+            generated: false,
+        };
+        match node {
+            Node::Nullable { inner } => TypeRef {
+                nullable: true,
+                ..self.type_ref(inner, path, hint)
+            },
+            // Value has a null of its own
+            Node::Empty => plain("serde_json::Value"),
+            Node::Ref { name } => TypeRef {
+                ty: self.defs[name.as_str()].clone(),
+                nullable: matches!(self.schema.definitions[name], Node::Nullable { .. }),
+                generated: true,
+            },
+            Node::Type { type_kw } => plain(match type_kw {
+                TypeKeyword::Boolean => "bool",
+                TypeKeyword::String => "String",
+                TypeKeyword::Timestamp => "chrono::DateTime<chrono::FixedOffset>",
+                TypeKeyword::Float32 => "f32",
+                TypeKeyword::Float64 => "f64",
+                TypeKeyword::Int8 => "i8",
+                TypeKeyword::Uint8 => "u8",
+                TypeKeyword::Int16 => "i16",
+                TypeKeyword::Uint16 => "u16",
+                TypeKeyword::Int32 => "i32",
+                TypeKeyword::Uint32 => "u32",
+            }),
+            Node::Elements { schema: item } => {
+                let item = self.type_ref(item, &format!("{path}/elements"), &format!("{hint}Item"));
+                plain(&format!("Vec<{}>", option(&item, false)))
+            }
+            Node::Values { schema: item } => {
+                let item = self.type_ref(item, &format!("{path}/values"), &format!("{hint}Value"));
+                plain(&format!(
+                    "std::collections::HashMap<String, {}>",
+                    option(&item, false)
+                ))
+            }
+            node => {
+                let ty = self.name(hint);
+                self.item(&ty, node, path);
+                TypeRef {
+                    ty,
+                    nullable: false,
+                    generated: true,
+                }
+            }
+        }
+    }
+
+    /// Emit item `name` for `node` at `path`: a struct, an enum or an alias.
+    fn item(&mut self, name: &str, node: &'a Node, path: &str) {
+        let slot = self.items.len();
+        self.items.push(String::new());
+        let mut out = doc(self.schema.description(path), "");
+        match node {
+            Node::Properties { .. } => {
+                out.push_str(&format!("{DERIVE}\npub struct {name} {{\n"));
+                out.push_str(&self.fields(name, node, path));
+                out.push_str("}\n");
+            }
+            Node::Enum { values } => {
+                out.push_str(&format!("{DERIVE}\npub enum {name} {{\n"));
+                let mut seen = BTreeSet::new();
+                for value in values {
+                    let mut variant = pascal(value);
+                    if !variant.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        variant.insert(0, 'V');
+                    }
+                    while !seen.insert(variant.clone()) {
+                        variant.push('_');
+                    }
+                    out.push_str(&format!(
+                        "    #[serde(rename = {value:?})]\n    {variant},\n"
+                    ));
+                }
+                out.push_str("}\n");
+            }
+            Node::Discriminator { tag, mapping } => {
+                out.push_str(&format!(
+                    "{DERIVE}\n#[serde(tag = {tag:?})]\npub enum {name} {{\n"
+                ));
+                let mut seen = BTreeSet::new();
+                for (value, variant) in mapping {
+                    let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                    let struct_name = self.name(&format!("{name}_{value}"));
+                    let mut variant_name = pascal(value);
+                    if !variant_name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        variant_name.insert(0, 'V');
+                    }
+                    while !seen.insert(variant_name.clone()) {
+                        variant_name.push('_');
+                    }
+                    out.push_str(&doc(self.schema.description(&variant_path), "    "));
+                    out.push_str(&format!(
+                        "    #[serde(rename = {value:?})]\n    {variant_name}({struct_name}),\n"
+                    ));
+                    let slot = self.items.len();
+                    self.items.push(String::new());
+                    let mut block = format!("{DERIVE}\npub struct {struct_name} {{\n");
+                    block.push_str(&self.fields(&struct_name, variant, &variant_path));
+                    block.push_str("}\n");
+                    self.items[slot] = block;
+                }
+                out.push_str("}\n");
+            }
+            node if self.newtypes.contains(name) => {
+                let target = self.type_ref(node, path, name);
+                let mut ty = option(&target, false);
+                if target.generated && !target.nullable {
+                    ty = format!("Box<{ty}>");
+                }
+                out.push_str(&format!(
+                    "{DERIVE}\n#[serde(transparent)]\npub struct {name}(pub {ty});\n"
+                ));
+            }
+            node => {
+                // A nullable root aliases `Option` of the type it names
+                let hint = match node {
+                    Node::Nullable { .. } => format!("{name}Value"),
+                    _ => name.to_string(),
+                };
+                let target = self.type_ref(node, path, &hint);
+                out.push_str(&format!("pub type {name} = {};\n", option(&target, false)));
+            }
+        }
+        self.items[slot] = out;
+    }
+
+    /// The fields of the Properties form `node` of struct `parent`.
+    fn fields(&mut self, parent: &str, node: &'a Node, path: &str) -> String {
+        let Node::Properties {
+            required, optional, ..
+        } = node
+        else {
+            return String::new();
+        };
+        let mut out = String::new();
+        let props = required
+            .iter()
+            .map(|(key, child)| (key, child, true))
+            .chain(optional.iter().map(|(key, child)| (key, child, false)));
+        let mut seen = BTreeSet::new();
+        for (key, child, is_required) in props {
+            let child_path = if is_required {
+                format!("{path}/properties/{}", escape_pointer(key))
+            } else {
+                format!("{path}/optionalProperties/{}", escape_pointer(key))
+            };
+            let field = self.type_ref(child, &child_path, &format!("{parent}_{key}"));
+            let mut field_name = field_name(key);
+            while !seen.insert(field_name.clone()) {
+                field_name.push('_');
+            }
+            out.push_str(&doc(self.schema.description(&child_path), "    "));
+            if field_name != *key {
+                out.push_str(&format!("    #[serde(rename = {key:?})]\n"));
+            }
+            if !is_required {
+                out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            }
+            out.push_str(&format!(
+                "    pub {field_name}: {},\n",
+                option(&field, !is_required)
+            ));
+        }
+        out
+    }
+}
+
+/// `t` as written in a field, wrapped in `Option` when null or absence
+/// must be representable.
+fn option(t: &TypeRef, optional: bool) -> String {
+    if !t.nullable && !optional {
+        return t.ty.clone();
+    }
+    if t.generated {
+        format!("Option<Box<{}>>", t.ty)
+    } else {
+        format!("Option<{}>", t.ty)
+    }
+}
+
+/// True when definition `name` reaches itself through forms that would be
+/// type aliases (refs, elements, values), which Rust cannot expand.
+fn alias_cycle(schema: &CompiledSchema, name: &str) -> bool {
+    fn visit<'a>(
+        schema: &'a CompiledSchema,
+        node: &'a Node,
+        start: &str,
+        seen: &mut BTreeSet<&'a str>,
+    ) -> bool {
+        match node {
+            Node::Nullable { inner } => visit(schema, inner, start, seen),
+            Node::Elements { schema: item } | Node::Values { schema: item } => {
+                visit(schema, item, start, seen)
+            }
+            Node::Ref { name } if name == start => true,
+            Node::Ref { name } => {
+                seen.insert(name) && visit(schema, &schema.definitions[name], start, seen)
+            }
+            _ => false,
+        }
+    }
+    let node = &schema.definitions[name];
+    !matches!(
+        strip_nullable(node),
+        Node::Properties { .. } | Node::Discriminator { .. } | Node::Enum { .. }
+    ) && visit(schema, node, name, &mut BTreeSet::new())
+}
+
+fn strip_nullable(node: &Node) -> &Node {
+    match node {
+        Node::Nullable { inner } => inner,
+        node => node,
+    }
+}
+
+/// `firstName` or `first-name` as `first_name`, with `_` after keywords.
+fn field_name(key: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.insert(0, '_');
+    }
+    // A lone `_` (a key with no ASCII letters or digits) is not a field name
+    if out == "_" || is_keyword(&out) {
+        out.push('_');
+    }
+    out
+}
+
+fn is_keyword(s: &str) -> bool {
+    matches!(
+        s,
+        "as" | "async"
+            | "await"
+            | "box"
+            | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "gen"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "try"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "yield"
+    )
+}
+
+/// `desc` as `///` lines indented by `indent`, or nothing.
+fn doc(desc: Option<&str>, indent: &str) -> String {
+    desc.map(|desc| {
+        desc.lines()
+            .map(|line| format!("{indent}/// {line}").trim_end().to_string() + "\n")
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit_types() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree_node": {
+                    "properties": {"value": {"type": "uint8"}},
+                    "optionalProperties": {"next": {"ref": "tree_node"}}
+                },
+                "id": {"type": "string"}
+            },
+            "properties": {
+                "id": {"ref": "id", "metadata": {"description": "The order."}},
+                "type": {"enum": ["new", "in-progress"], "nullable": true},
+                "at": {"type": "timestamp"},
+                "extra": {"values": {}},
+                "tags": {"elements": {"type": "string", "nullable": true}},
+                "event": {
+                    "discriminator": "kind",
+                    "mapping": {"user-created": {"properties": {"userName": {"type": "string"}}}}
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            emit_types(&schema),
+            r#"pub type Id = String;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TreeNode {
+    pub value: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<Box<TreeNode>>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Root {
+    pub at: chrono::DateTime<chrono::FixedOffset>,
+    pub event: RootEvent,
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+    /// The order.
+    pub id: Id,
+    pub tags: Vec<Option<String>>,
+    #[serde(rename = "type")]
+    pub type_: Option<Box<RootType>>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind")]
+pub enum RootEvent {
+    #[serde(rename = "user-created")]
+    UserCreated(RootEventUserCreated),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RootEventUserCreated {
+    #[serde(rename = "userName")]
+    pub user_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RootType {
+    #[serde(rename = "new")]
+    New,
+    #[serde(rename = "in-progress")]
+    InProgress,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_alias() {
+        let schema = compile(&json!({
+            "definitions": {"list": {"elements": {"ref": "list"}}},
+            "ref": "list"
+        }))
+        .unwrap();
+        let types = emit_types(&schema);
+        assert!(types.contains("#[serde(transparent)]\npub struct List(pub Vec<List>);\n"));
+        assert!(types.ends_with("pub type Root = List;\n"));
+    }
+
+    #[test]
+    fn test_nullable_root() {
+        let schema = compile(&json!({"properties": {}, "nullable": true})).unwrap();
+        let types = emit_types(&schema);
+        assert!(types.starts_with("pub type Root = Option<Box<RootValue>>;\n"));
+        assert!(types.contains("pub struct RootValue {\n}\n"));
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("firstName"), "first_name");
+        assert_eq!(field_name("first-name"), "first_name");
+        assert_eq!(field_name("2fa"), "_2fa");
+        assert_eq!(field_name("match"), "match_");
+        assert_eq!(field_name("é☃"), "__");
+    }
+}
@@ -0,0 +1,79 @@
+/// Helpers behind the `suggestions` option. Errors stay `ValidationError`s;
+/// `suggestion(instance, instance_path, schema_path)` looks one up and
+/// `validate_with_suggestions` pairs every error with its suggestion.
+/// See [`crate::suggest`] for the rules.
+use crate::emit_js::CodeWriter;
+use crate::suggest::SuggestSite;
+
+pub(super) fn emit_suggest_helpers(w: &mut CodeWriter, sites: &[SuggestSite]) {
+    w.line("const SUGGESTIONS: &[(&str, bool, &[&str])] = &[");
+    for site in sites {
+        w.line(&format!(
+            "    ({:?}, {}, &{:?}),",
+            site.schema_path, site.key, site.candidates
+        ));
+    }
+    w.line("];");
+    w.line("");
+    w.open("fn edit_distance(a: &str, b: &str) -> usize");
+    w.line("let a: Vec<char> = a.chars().collect();");
+    w.line("let b: Vec<char> = b.chars().collect();");
+    w.line("let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];");
+    w.open("for (i, row) in d.iter_mut().enumerate()");
+    w.line("row[0] = i;");
+    w.close();
+    w.open("for (j, cell) in d[0].iter_mut().enumerate()");
+    w.line("*cell = j;");
+    w.close();
+    w.open("for i in 1..=a.len()");
+    w.open("for j in 1..=b.len()");
+    w.line("let cost = usize::from(a[i - 1] != b[j - 1]);");
+    w.line("d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);");
+    w.open("if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1]");
+    w.line("d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);");
+    w.close();
+    w.close();
+    w.close();
+    w.line("d[a.len()][b.len()]");
+    w.close();
+    w.line("");
+    w.line("/// The closest allowed name for an error reported by `validate(instance)`:");
+    w.line("/// unknown enum values, discriminator tags and additional properties.");
+    w.open(
+        "pub fn suggestion(instance: &Value, instance_path: &str, schema_path: &str) -> Option<&'static str>",
+    );
+    w.line("let (_, key, candidates) = SUGGESTIONS.iter().find(|(sp, _, _)| *sp == schema_path)?;");
+    w.open("let word: &str = if *key");
+    w.line("&instance_path[instance_path.rfind('/').map_or(0, |i| i + 1)..]");
+    w.close_open("else");
+    w.line("let mut v = instance;");
+    w.open("for seg in instance_path.split('/').skip(1)");
+    w.open("v = match v.as_array()");
+    w.line("Some(arr) => arr.get(seg.parse::<usize>().ok()?)?,");
+    w.line("None => v.as_object()?.get(seg)?,");
+    w.close_with(";");
+    w.close();
+    w.line("v.as_str()?");
+    w.close_with(";");
+    w.line("let mut best = None;");
+    w.line("let mut best_d = (word.chars().count() / 3).max(1) + 1;");
+    w.open("for c in candidates.iter()");
+    w.line("let d = edit_distance(word, c);");
+    w.open("if d < best_d");
+    w.line("best = Some(*c);");
+    w.line("best_d = d;");
+    w.close();
+    w.close();
+    w.line("best");
+    w.close();
+    w.line("");
+    w.line("/// `validate`, with the suggestion (if any) for each error.");
+    w.open("pub fn validate_with_suggestions(instance: &Value) -> Vec<(ValidationError, Option<&'static str>)>");
+    w.line("let mut out = Vec::new();");
+    w.open("for err in validate(instance)");
+    w.line("let s = suggestion(instance, &err.instance_path, &err.schema_path);");
+    w.line("out.push((err, s));");
+    w.close();
+    w.line("out");
+    w.close();
+}
@@ -1,14 +1,31 @@
 /// Pure function: TypeKeyword -> Rust condition string that is TRUE when
 /// the value FAILS the type check against serde_json::Value.
 use crate::ast::TypeKeyword;
+use crate::options::RsBackend;
 
 /// Returns a Rust expression that evaluates to `true` when
 /// `val` (a `&serde_json::Value`) does NOT satisfy the given type keyword.
+#[allow(dead_code)]
 pub fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
+    type_condition_for(RsBackend::SerdeJson, type_kw, val)
+}
+
+/// As [`type_condition`], for the value type of `backend`. simd-json names
+/// the predicates `is_bool`/`is_str`, and its `as_f64` only accepts floats,
+/// so `cast_f64` is used to read integers too.
+pub fn type_condition_for(backend: RsBackend, type_kw: TypeKeyword, val: &str) -> String {
+    let simd = backend == RsBackend::SimdJson;
+    let to_f64 = if simd { "cast_f64" } else { "as_f64" };
     match type_kw {
+        TypeKeyword::Boolean if simd => {
+            format!("!{val}.is_bool()")
+        }
         TypeKeyword::Boolean => {
             format!("!{val}.is_boolean()")
         }
+        TypeKeyword::String if simd => {
+            format!("!{val}.is_str()")
+        }
         TypeKeyword::String => {
             format!("!{val}.is_string()")
         }
@@ -18,20 +35,38 @@ pub fn type_condition(type_kw: TypeKeyword, val: &str) -> String {
         }
         TypeKeyword::Float32 | TypeKeyword::Float64 => {
             // Any finite JSON number
-            format!("!{val}.as_f64().map_or(false, |n| n.is_finite())")
+            format!("!{val}.{to_f64}().map_or(false, |n| n.is_finite())")
         }
-        TypeKeyword::Int8 => int_cond(val, -128, 127),
-        TypeKeyword::Uint8 => int_cond(val, 0, 255),
-        TypeKeyword::Int16 => int_cond(val, -32768, 32767),
-        TypeKeyword::Uint16 => int_cond(val, 0, 65535),
-        TypeKeyword::Int32 => int_cond(val, -2_147_483_648, 2_147_483_647),
-        TypeKeyword::Uint32 => int_cond(val, 0, 4_294_967_295),
+        TypeKeyword::Int8 => int_cond(val, to_f64, -128, 127),
+        TypeKeyword::Uint8 => int_cond(val, to_f64, 0, 255),
+        TypeKeyword::Int16 => int_cond(val, to_f64, -32768, 32767),
+        TypeKeyword::Uint16 => int_cond(val, to_f64, 0, 65535),
+        TypeKeyword::Int32 => int_cond(val, to_f64, -2_147_483_648, 2_147_483_647),
+        TypeKeyword::Uint32 => int_cond(val, to_f64, 0, 4_294_967_295),
     }
 }
 
-fn int_cond(val: &str, min: i64, max: i64) -> String {
+/// As [`type_condition`] for `#![no_std]` code, where `f64::fract` is not
+/// available: a number in range is an integer when it survives a round trip
+/// through `i64`.
+pub fn type_condition_no_std(type_kw: TypeKeyword, val: &str) -> String {
+    let (min, max): (i64, i64) = match type_kw {
+        TypeKeyword::Int8 => (-128, 127),
+        TypeKeyword::Uint8 => (0, 255),
+        TypeKeyword::Int16 => (-32768, 32767),
+        TypeKeyword::Uint16 => (0, 65535),
+        TypeKeyword::Int32 => (-2_147_483_648, 2_147_483_647),
+        TypeKeyword::Uint32 => (0, 4_294_967_295),
+        _ => return type_condition(type_kw, val),
+    };
     format!(
-        "!{val}.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= {min}_f64 && n <= {max}_f64)"
+        "!{val}.as_f64().map_or(false, |n| n >= {min}_f64 && n <= {max}_f64 && n == n as i64 as f64)"
+    )
+}
+
+fn int_cond(val: &str, to_f64: &str, min: i64, max: i64) -> String {
+    format!(
+        "!{val}.{to_f64}().map_or(false, |n| n.fract() == 0.0 && n >= {min}_f64 && n <= {max}_f64)"
     )
 }
 
@@ -45,6 +80,17 @@ pub fn needs_timestamp_helper(type_kw: TypeKeyword) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_no_std_integers_avoid_fract() {
+        let c = type_condition_no_std(TypeKeyword::Uint8, "v");
+        assert!(c.contains("n == n as i64 as f64"));
+        assert!(!c.contains("fract"));
+        assert_eq!(
+            type_condition_no_std(TypeKeyword::String, "v"),
+            type_condition(TypeKeyword::String, "v")
+        );
+    }
+
     #[test]
     fn test_boolean() {
         let c = type_condition(TypeKeyword::Boolean, "v");
@@ -86,6 +132,21 @@ mod tests {
         assert!(c.contains("2147483647"));
     }
 
+    #[test]
+    fn test_simd_json_names() {
+        let simd = RsBackend::SimdJson;
+        assert_eq!(
+            type_condition_for(simd, TypeKeyword::Boolean, "v"),
+            "!v.is_bool()"
+        );
+        assert_eq!(
+            type_condition_for(simd, TypeKeyword::String, "v"),
+            "!v.is_str()"
+        );
+        assert!(type_condition_for(simd, TypeKeyword::Uint8, "v").contains("v.cast_f64()"));
+        assert!(type_condition_for(simd, TypeKeyword::Float64, "v").contains("v.cast_f64()"));
+    }
+
     #[test]
     fn test_timestamp() {
         let c = type_condition(TypeKeyword::Timestamp, "v");
@@ -0,0 +1,133 @@
+/// `warnings(instance)`: reports present properties whose schema is marked
+/// `metadata.deprecated`, following a [`WarningPlan`]. The generated code only
+/// uses `as_object`, `as_array`, `as_str` and `get`, so it compiles against
+/// both serde_json and simd-json values.
+use super::emit::{def_fn_name, pointer_segment};
+use crate::compiler::escape_pointer;
+use crate::deprecation::{Prop, Walk, WarningPlan};
+use crate::emit_js::CodeWriter;
+
+/// Emit the definition walkers and the `warnings` entry point.
+pub(super) fn emit_warnings(w: &mut CodeWriter, plan: &WarningPlan) {
+    for (name, walk) in &plan.definitions {
+        w.open(&format!(
+            "fn {}(v: &Value, w: &mut Vec<(String, String)>, p: &str)",
+            warn_fn_name(name)
+        ));
+        emit_walk(w, walk, "v", "p", "w", 0);
+        w.close();
+        w.line("");
+    }
+    w.line("/// Deprecated properties present in `instance`, as (instance path, schema path).");
+    w.open("pub fn warnings(instance: &Value) -> Vec<(String, String)>");
+    w.line("let mut w: Vec<(String, String)> = Vec::new();");
+    if let Some(walk) = &plan.root {
+        w.line("let p = \"\";");
+        emit_walk(w, walk, "instance", "p", "&mut w", 0);
+    }
+    w.line("w");
+    w.close();
+}
+
+fn warn_fn_name(name: &str) -> String {
+    def_fn_name(name).replacen("validate_", "warn_", 1)
+}
+
+/// `v` is a `&Value` expression, `p` a `&str`/`String` path variable and
+/// `out` the `&mut Vec` to pass on to definition walkers.
+fn emit_walk(w: &mut CodeWriter, walk: &Walk, v: &str, p: &str, out: &str, depth: usize) {
+    match walk {
+        Walk::Object(props) => {
+            w.open(&format!("if let Some(obj) = {v}.as_object()"));
+            emit_props(w, props, p, out, depth);
+            w.close();
+        }
+        Walk::Array(inner) => {
+            let (i, x, cp) = (
+                format!("i{depth}"),
+                format!("x{depth}"),
+                format!("p{depth}"),
+            );
+            w.open(&format!("if let Some(arr) = {v}.as_array()"));
+            w.open(&format!("for ({i}, {x}) in arr.iter().enumerate()"));
+            w.line(&format!("let {cp} = format!(\"{{}}/{{}}\", {p}, {i});"));
+            emit_walk(w, inner, x.as_str(), &cp, out, depth + 1);
+            w.close();
+            w.close();
+        }
+        Walk::Map(inner) => {
+            let (k, x, cp) = (
+                format!("k{depth}"),
+                format!("x{depth}"),
+                format!("p{depth}"),
+            );
+            w.open(&format!("if let Some(obj) = {v}.as_object()"));
+            w.open(&format!("for ({k}, {x}) in obj"));
+            w.line(&format!(
+                "let {cp} = format!(\"{{}}/{{}}\", {p}, {});",
+                pointer_segment(&k)
+            ));
+            emit_walk(w, inner, x.as_str(), &cp, out, depth + 1);
+            w.close();
+            w.close();
+        }
+        Walk::Ref(name) => {
+            // `p` itself is a `&str`; the other path variables are `String`s.
+            let arg = if p == "p" {
+                p.to_string()
+            } else {
+                format!("&{p}")
+            };
+            w.line(&format!("{}({v}, {out}, {arg});", warn_fn_name(name)));
+        }
+        Walk::Tagged { tag, variants } => {
+            let t = format!("tag{depth}");
+            w.open(&format!("if let Some(obj) = {v}.as_object()"));
+            w.line(&format!(
+                "let {t} = obj.get({tag:?}).and_then(|t| t.as_str());"
+            ));
+            for (i, (value, props)) in variants.iter().enumerate() {
+                let cond = format!("{t} == Some({value:?})");
+                if i == 0 {
+                    w.open(&format!("if {cond}"));
+                } else {
+                    w.close_open(&format!("else if {cond}"));
+                }
+                emit_props(w, props, p, out, depth);
+            }
+            w.close();
+            w.close();
+        }
+    }
+}
+
+/// Properties of the object bound to `obj`.
+fn emit_props(w: &mut CodeWriter, props: &[Prop], p: &str, out: &str, depth: usize) {
+    for prop in props {
+        let key = &prop.key;
+        let (x, cp) = (format!("x{depth}"), format!("p{depth}"));
+        match &prop.child {
+            Some(_) => w.open(&format!("if let Some({x}) = obj.get({key:?})")),
+            None => w.open(&format!("if obj.contains_key({key:?})")),
+        }
+        w.line(&format!(
+            "let {cp} = format!(\"{{}}/{{}}\", {p}, {:?});",
+            escape_pointer(key)
+        ));
+        if prop.deprecated {
+            let ip = if prop.child.is_some() {
+                format!("{cp}.clone()")
+            } else {
+                cp.clone()
+            };
+            w.line(&format!(
+                "w.push(({ip}, {:?}.to_string()));",
+                prop.schema_path
+            ));
+        }
+        if let Some(child) = &prop.child {
+            emit_walk(w, child, &x, &cp, out, depth + 1);
+        }
+        w.close();
+    }
+}
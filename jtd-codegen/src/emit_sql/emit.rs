@@ -0,0 +1,511 @@
+use super::writer::{dollar_quote, sql_string, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::emit_js::def_fn_name;
+use crate::mock::int_range;
+use crate::options::EmitOptions;
+use crate::source_map;
+
+/// Emit a PostgreSQL script from a compiled schema.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a PostgreSQL script with the given options. The banner,
+/// `sql_prefix`, `sql_check` and fail-fast apply.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let prefix = opts.sql_prefix.as_deref().unwrap_or("jtd");
+    let mut file = File {
+        prefix,
+        fail_fast: opts.fail_fast,
+        source_map: opts.source_map,
+        ..Default::default()
+    };
+
+    let mut funcs = String::new();
+    for (name, node) in &schema.definitions {
+        let sp = format!("/definitions/{}", escape_pointer(name));
+        let body = file.body(node, "v", "p", &sp);
+        funcs.push_str(&function(
+            &function_doc(schema, &sp, node),
+            &format!("{prefix}_{}(v jsonb, p text)", def_fn_name(name)),
+            &body,
+        ));
+        funcs.push('\n');
+    }
+    let body = file.body(&schema.root, "instance", "''", "");
+    funcs.push_str(&function(
+        &function_doc(schema, "", &schema.root),
+        &format!("{prefix}_validate(instance jsonb)"),
+        &body,
+    ));
+
+    let mut out = String::new();
+    out.push_str("-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)\n");
+    out.push_str("-- This code is generated from a JSON Type Definition schema.\n");
+    out.push_str("-- Do not edit manually.\n");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("sql") {
+            out.push_str(&format!("-- {line}\n"));
+        }
+    }
+    out.push('\n');
+    if file.integers {
+        out.push_str(&INTEGER_HELPER.replace("{prefix}", prefix));
+        out.push('\n');
+    }
+    if file.timestamps {
+        out.push_str(&TIMESTAMP_HELPER.replace("{prefix}", prefix));
+        out.push('\n');
+    }
+    out.push_str(&funcs);
+    out.push_str(&format!(
+        "\n-- True when instance has no validation errors; usable in CHECK constraints.\n\
+         CREATE OR REPLACE FUNCTION {prefix}_is_valid(instance jsonb)\n\
+         RETURNS boolean\n\
+         LANGUAGE sql IMMUTABLE AS $$\n    \
+         SELECT NOT EXISTS (SELECT 1 FROM {prefix}_validate(instance))\n\
+         $$;\n"
+    ));
+    if let Some(check) = &opts.sql_check {
+        let (table, column) = check.rsplit_once('.').unwrap_or((check, "doc"));
+        let name = format!("{}_{column}_jtd", table.rsplit('.').next().unwrap_or(table));
+        out.push_str(&format!(
+            "\nALTER TABLE {table} ADD CONSTRAINT {name} CHECK ({prefix}_is_valid({column}));\n"
+        ));
+    }
+    out
+}
+
+/// A JSON number that is an integer within `[lo, hi]`. `CASE` keeps the
+/// cast from running on other types.
+const INTEGER_HELPER: &str = r#"CREATE OR REPLACE FUNCTION {prefix}_is_integer(v jsonb, lo numeric, hi numeric)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT CASE WHEN jsonb_typeof(v) = 'number'
+        THEN v::numeric % 1 = 0 AND v::numeric BETWEEN lo AND hi
+        ELSE false
+    END
+$$;
+"#;
+
+/// RFC 3339 with the checks of [`crate::timestamp::is_rfc3339`]: days per
+/// month, leap years and a leap second.
+const TIMESTAMP_HELPER: &str = r#"CREATE OR REPLACE FUNCTION {prefix}_is_timestamp(v jsonb)
+RETURNS boolean
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    g text[];
+    days integer;
+BEGIN
+    IF jsonb_typeof(v) <> 'string' THEN
+        RETURN false;
+    END IF;
+    g := regexp_match(v #>> '{}',
+        '^([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))$');
+    IF g IS NULL THEN
+        RETURN false;
+    END IF;
+    IF g[2]::int NOT BETWEEN 1 AND 12 OR g[4]::int > 23 OR g[5]::int > 59 OR g[6]::int > 60 THEN
+        RETURN false;
+    END IF;
+    days := CASE
+        WHEN g[2]::int = 2 AND g[1]::int % 4 = 0 AND (g[1]::int % 100 <> 0 OR g[1]::int % 400 = 0) THEN 29
+        WHEN g[2]::int = 2 THEN 28
+        WHEN g[2]::int IN (4, 6, 9, 11) THEN 30
+        ELSE 31
+    END;
+    IF g[3]::int NOT BETWEEN 1 AND days THEN
+        RETURN false;
+    END IF;
+    RETURN g[8] IS NULL OR (g[8]::int <= 23 AND g[9]::int <= 59);
+END;
+$$;
+"#;
+
+/// A function returning error rows, with `doc` as leading comments.
+fn function(doc: &[String], signature: &str, body: &str) -> String {
+    let mut out = String::new();
+    for line in doc {
+        if line.is_empty() {
+            out.push_str("--\n");
+        } else {
+            out.push_str(&format!("-- {line}\n"));
+        }
+    }
+    out.push_str(&format!(
+        "CREATE OR REPLACE FUNCTION {signature}\n\
+         RETURNS TABLE (instance_path text, schema_path text)\n\
+         LANGUAGE plpgsql IMMUTABLE AS {};\n",
+        dollar_quote(body)
+    ));
+    out
+}
+
+/// `p`, a text expression, followed by the literal segment `/key`.
+fn join(p: &str, key: &str) -> String {
+    let segment = sql_string(&format!("/{}", escape_pointer(key)));
+    if p == "''" {
+        segment
+    } else {
+        format!("{p} || {segment}")
+    }
+}
+
+/// `p` followed by `/` and the value of the variable `var`.
+fn join_var(p: &str, var: &str) -> String {
+    if p == "''" {
+        format!("'/' || {var}")
+    } else {
+        format!("{p} || '/' || {var}")
+    }
+}
+
+/// `p` followed by `/` and the key in the text variable `var`, with `~` as
+/// `~0` and `/` as `~1`.
+fn join_key(p: &str, var: &str) -> String {
+    join_var(p, &format!("replace(replace({var}, '~', '~0'), '/', '~1')"))
+}
+
+#[derive(Default)]
+struct File<'a> {
+    prefix: &'a str,
+    /// Return after the first error row.
+    fail_fast: bool,
+    integers: bool,
+    timestamps: bool,
+    /// Locals used so far in the current function.
+    vars: usize,
+    /// `DECLARE` entries of the current function.
+    locals: Vec<String>,
+    /// Wrap the checks of each node in source-map markers.
+    source_map: bool,
+}
+
+impl File<'_> {
+    /// Return the error row `(p, sp)`, then leave the function when
+    /// failing fast.
+    fn err(&self, p: &str, sp: &str) -> String {
+        let row = format!("RETURN QUERY VALUES ({p}, {});", sql_string(sp));
+        if self.fail_fast {
+            format!("{row} RETURN;")
+        } else {
+            row
+        }
+    }
+
+    fn var(&mut self, prefix: &str, ty: &str) -> String {
+        self.vars += 1;
+        let name = format!("{prefix}{}", self.vars);
+        self.locals.push(format!("{name} {ty};"));
+        name
+    }
+
+    /// The function body checking `node` on the `jsonb` in `v`.
+    fn body(&mut self, node: &Node, v: &str, p: &str, sp: &str) -> String {
+        self.vars = 0;
+        self.locals.clear();
+        let mut w = CodeWriter::new();
+        w.open("BEGIN");
+        self.node(&mut w, node, v, p, sp, None);
+        w.close("END;");
+        let mut out = String::new();
+        if !self.locals.is_empty() {
+            out.push_str("DECLARE\n");
+            for local in &self.locals {
+                out.push_str(&format!("    {local}\n"));
+            }
+        }
+        out.push_str(&w.finish());
+        out
+    }
+
+    /// [`Self::node_checks`], in source-map markers when asked for.
+    fn node(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        if !self.source_map {
+            return self.node_checks(w, node, v, p, sp, tag);
+        }
+        w.line(&source_map::open(node));
+        self.node_checks(w, node, v, p, sp, tag);
+        w.line(source_map::close());
+    }
+
+    /// Validate the `jsonb` in variable `v`, whose instance path is the
+    /// text expression `p`, against `node` at schema path `sp`. `tag` is a
+    /// discriminator tag the properties of `node` may leave undeclared.
+    fn node_checks(
+        &mut self,
+        w: &mut CodeWriter,
+        node: &Node,
+        v: &str,
+        p: &str,
+        sp: &str,
+        tag: Option<&str>,
+    ) {
+        match node {
+            Node::Empty => {}
+
+            Node::Type { type_kw } => {
+                let check = self.type_check(*type_kw, v);
+                w.line(&format!(
+                    "IF {check} THEN {} END IF;",
+                    self.err(p, &format!("{sp}/type"))
+                ));
+            }
+
+            Node::Enum { values } => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| sql_string(&serde_json::Value::from(value.as_str()).to_string()))
+                    .collect();
+                w.line(&format!(
+                    "IF {v} NOT IN ({}) THEN {} END IF;",
+                    values.join(", "),
+                    self.err(p, &format!("{sp}/enum"))
+                ));
+            }
+
+            Node::Ref { name } => {
+                w.line(&format!(
+                    "RETURN QUERY SELECT * FROM {}_{}({v}, {p});",
+                    self.prefix,
+                    def_fn_name(name)
+                ));
+                if self.fail_fast {
+                    w.line("IF FOUND THEN RETURN; END IF;");
+                }
+            }
+
+            Node::Nullable { inner } => {
+                if matches!(**inner, Node::Empty) {
+                    return;
+                }
+                w.open(&format!("IF jsonb_typeof({v}) <> 'null' THEN"));
+                self.node(w, inner, v, p, sp, tag);
+                w.close("END IF;");
+            }
+
+            Node::Elements { schema: item } => {
+                let item_sp = format!("{sp}/elements");
+                let guard = format!("jsonb_typeof({v}) <> 'array'");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!(
+                        "IF {guard} THEN {} END IF;",
+                        self.err(p, &item_sp)
+                    ));
+                    return;
+                }
+                w.open(&format!("IF {guard} THEN"));
+                w.line(&self.err(p, &item_sp));
+                w.close_open("ELSE");
+                // The loop variable of an integer FOR needs no declaration
+                self.vars += 1;
+                let i = format!("i{}", self.vars);
+                let x = self.var("v", "jsonb");
+                w.open(&format!("FOR {i} IN 0 .. jsonb_array_length({v}) - 1 LOOP"));
+                w.line(&format!("{x} := {v} -> {i};"));
+                self.node(w, item, &x, &join_var(p, &i), &item_sp, None);
+                w.close("END LOOP;");
+                w.close("END IF;");
+            }
+
+            Node::Values { schema: item } => {
+                let item_sp = format!("{sp}/values");
+                let guard = format!("jsonb_typeof({v}) <> 'object'");
+                if matches!(**item, Node::Empty) {
+                    w.line(&format!(
+                        "IF {guard} THEN {} END IF;",
+                        self.err(p, &item_sp)
+                    ));
+                    return;
+                }
+                w.open(&format!("IF {guard} THEN"));
+                w.line(&self.err(p, &item_sp));
+                w.close_open("ELSE");
+                let (k, x) = (self.var("k", "text"), self.var("v", "jsonb"));
+                w.open(&format!(
+                    "FOR {k}, {x} IN SELECT key, value FROM jsonb_each({v}) LOOP"
+                ));
+                self.node(w, item, &x, &join_key(p, &k), &item_sp, None);
+                w.close("END LOOP;");
+                w.close("END IF;");
+            }
+
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                w.open(&format!("IF jsonb_typeof({v}) <> 'object' THEN"));
+                w.line(&self.err(p, &format!("{sp}{guard}")));
+                w.close_open("ELSE");
+                for (key, child) in required {
+                    let child_sp = format!("{sp}/properties/{}", escape_pointer(key));
+                    let missing = format!("NOT {v} ? {}", sql_string(key));
+                    if matches!(child, Node::Empty) {
+                        w.line(&format!(
+                            "IF {missing} THEN {} END IF;",
+                            self.err(p, &child_sp)
+                        ));
+                        continue;
+                    }
+                    w.open(&format!("IF {missing} THEN"));
+                    w.line(&self.err(p, &child_sp));
+                    w.close_open("ELSE");
+                    let x = self.var("v", "jsonb");
+                    w.line(&format!("{x} := {v} -> {};", sql_string(key)));
+                    self.node(w, child, &x, &join(p, key), &child_sp, None);
+                    w.close("END IF;");
+                }
+                for (key, child) in optional {
+                    if matches!(child, Node::Empty) {
+                        continue;
+                    }
+                    let child_sp = format!("{sp}/optionalProperties/{}", escape_pointer(key));
+                    w.open(&format!("IF {v} ? {} THEN", sql_string(key)));
+                    let x = self.var("v", "jsonb");
+                    w.line(&format!("{x} := {v} -> {};", sql_string(key)));
+                    self.node(w, child, &x, &join(p, key), &child_sp, None);
+                    w.close("END IF;");
+                }
+                if !additional {
+                    let known: Vec<String> = required
+                        .keys()
+                        .chain(optional.keys())
+                        .map(String::as_str)
+                        .chain(tag)
+                        .map(sql_string)
+                        .collect();
+                    let filter = if known.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" WHERE k NOT IN ({})", known.join(", "))
+                    };
+                    w.line(&format!(
+                        "RETURN QUERY SELECT {}, {} FROM jsonb_object_keys({v}) AS k{filter};",
+                        join_key(p, "k"),
+                        sql_string(sp)
+                    ));
+                }
+                w.close("END IF;");
+            }
+
+            Node::Discriminator { tag, mapping } => {
+                let tag_sp = format!("{sp}/discriminator");
+                let tag_p = join(p, tag);
+                let t = sql_string(tag);
+                w.open(&format!("IF jsonb_typeof({v}) <> 'object' THEN"));
+                w.line(&self.err(p, &tag_sp));
+                w.close_open(&format!("ELSIF NOT {v} ? {t} THEN"));
+                w.line(&self.err(p, &tag_sp));
+                w.close_open(&format!("ELSIF jsonb_typeof({v} -> {t}) <> 'string' THEN"));
+                w.line(&self.err(&tag_p, &tag_sp));
+                w.close_open("ELSE");
+                let mapping_err = self.err(&tag_p, &format!("{sp}/mapping"));
+                // PL/pgSQL has no CASE without a WHEN
+                if mapping.is_empty() {
+                    w.line(&mapping_err);
+                    w.close("END IF;");
+                    return;
+                }
+                w.line(&format!("CASE {v} ->> {t}"));
+                for (i, (value, variant)) in mapping.iter().enumerate() {
+                    let when = format!("WHEN {} THEN", sql_string(value));
+                    if i == 0 {
+                        w.open(&when);
+                    } else {
+                        w.close_open(&when);
+                    }
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    self.node(w, variant, v, p, &variant_sp, Some(tag));
+                }
+                w.close_open("ELSE");
+                w.line(&mapping_err);
+                w.close("END CASE;");
+                w.close("END IF;");
+            }
+        }
+    }
+
+    /// A boolean SQL expression, true when the `jsonb` in `v` does not
+    /// have type `type_kw`.
+    fn type_check(&mut self, type_kw: TypeKeyword, v: &str) -> String {
+        if let Some((lo, hi)) = int_range(type_kw) {
+            self.integers = true;
+            return format!("NOT {}_is_integer({v}, {lo}, {hi})", self.prefix);
+        }
+        match type_kw {
+            TypeKeyword::Boolean => format!("jsonb_typeof({v}) <> 'boolean'"),
+            TypeKeyword::Timestamp => {
+                self.timestamps = true;
+                format!("NOT {}_is_timestamp({v})", self.prefix)
+            }
+            TypeKeyword::Float32 | TypeKeyword::Float64 => {
+                format!("jsonb_typeof({v}) <> 'number'")
+            }
+            _ => format!("jsonb_typeof({v}) <> 'string'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["a", "it's"]}}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let sql = emit(&compiled);
+        assert!(sql.contains(
+            "-- An order.\nCREATE OR REPLACE FUNCTION jtd_validate(instance jsonb)\n\
+             RETURNS TABLE (instance_path text, schema_path text)\n"
+        ));
+        assert!(sql.contains("CREATE OR REPLACE FUNCTION jtd_validate_id(v jsonb, p text)"));
+        assert!(sql.contains("RETURN QUERY SELECT * FROM jtd_validate_id(v1, '/id');"));
+        assert!(sql.contains("IF v4 NOT IN ('\"a\"', '\"it''s\"') THEN"));
+        assert!(sql.contains("FOR i3 IN 0 .. jsonb_array_length(v2) - 1 LOOP"));
+        assert!(sql.contains(
+            "RETURN QUERY SELECT '/' || replace(replace(k, '~', '~0'), '/', '~1'), '' \
+             FROM jsonb_object_keys(instance) AS k WHERE k NOT IN ('id', 'tags');"
+        ));
+        assert!(sql.contains("SELECT NOT EXISTS (SELECT 1 FROM jtd_validate(instance))"));
+        // Helpers only come with the types that need them
+        assert!(!sql.contains("is_timestamp"));
+        assert!(!sql.contains("ALTER TABLE"));
+
+        let opts = EmitOptions {
+            sql_prefix: Some("orders.jtd".into()),
+            sql_check: Some("orders.order.doc".into()),
+            ..Default::default()
+        };
+        let compiled = compiler::compile(&json!({"type": "uint8"})).unwrap();
+        let sql = emit_with_options(&compiled, &opts);
+        assert!(sql.contains("CREATE OR REPLACE FUNCTION orders.jtd_is_integer("));
+        assert!(sql.contains("IF NOT orders.jtd_is_integer(instance, 0, 255) THEN"));
+        assert!(sql.ends_with(
+            "ALTER TABLE orders.order ADD CONSTRAINT order_doc_jtd \
+             CHECK (orders.jtd_is_valid(doc));\n"
+        ));
+    }
+}
@@ -0,0 +1,9 @@
+/// PostgreSQL emitter: PL/pgSQL functions validating a `jsonb` value, so a
+/// table can enforce the contract with `CHECK (jtd_is_valid(doc))` at the
+/// storage layer. `<prefix>_validate(jsonb)` returns the errors as rows of
+/// `(instance_path, schema_path)`; `<prefix>_is_valid(jsonb)` wraps it for
+/// constraints. All functions are `IMMUTABLE`, as `CHECK` requires.
+mod emit;
+mod writer;
+
+pub use emit::{emit, emit_with_options};
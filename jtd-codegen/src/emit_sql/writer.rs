@@ -0,0 +1,92 @@
+/// Indentation-aware string builder for emitting PL/pgSQL, whose blocks
+/// close with a keyword (`END IF;`, `END LOOP;`) rather than a brace.
+pub struct CodeWriter {
+    buf: String,
+    depth: usize,
+}
+
+impl Default for CodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeWriter {
+    pub fn new() -> Self {
+        Self {
+            buf: String::new(),
+            depth: 0,
+        }
+    }
+
+    /// Write a line at the current indentation level.
+    pub fn line(&mut self, text: &str) {
+        if text.is_empty() {
+            self.buf.push('\n');
+            return;
+        }
+        for _ in 0..self.depth {
+            self.buf.push_str("    ");
+        }
+        self.buf.push_str(text);
+        self.buf.push('\n');
+    }
+
+    /// Write `text` and indent.
+    pub fn open(&mut self, text: &str) {
+        self.line(text);
+        self.depth += 1;
+    }
+
+    /// Dedent and write `text`.
+    pub fn close(&mut self, text: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(text);
+    }
+
+    /// `text` between the arms of an `IF` or `CASE`.
+    pub fn close_open(&mut self, text: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(text);
+        self.depth += 1;
+    }
+
+    /// Consume and return the built string.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// `s` as a standard-conforming SQL string literal, quotes included.
+pub fn sql_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// `body` between dollar quotes whose tag it does not contain, so a schema
+/// string can never end the function body early.
+pub fn dollar_quote(body: &str) -> String {
+    let mut tag = "$$".to_string();
+    let mut n = 0;
+    while body.contains(&tag) {
+        tag = format!("$jtd{n}$");
+        n += 1;
+    }
+    format!("{tag}\n{body}{tag}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_string() {
+        assert_eq!(sql_string("it's"), "'it''s'");
+        assert_eq!(sql_string("a\\b"), "'a\\b'");
+    }
+
+    #[test]
+    fn test_dollar_quote() {
+        assert_eq!(dollar_quote("BEGIN\n"), "$$\nBEGIN\n$$");
+        assert_eq!(dollar_quote("'$$'\n"), "$jtd0$\n'$$'\n$jtd0$");
+    }
+}
@@ -0,0 +1,219 @@
+/// A `.d.ts` for the JavaScript emitter's module: the data types from
+/// [`emit_types`], `ValidationError`, and a declaration for every function
+/// and constant the options make the module export, so TypeScript code can
+/// import the plain JavaScript validator.
+use super::types::emit_types;
+use crate::ast::CompiledSchema;
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::emit_js::{definition_entry_names, emit_jsdoc, CodeWriter};
+use crate::enums::enum_constants;
+use crate::options::EmitOptions;
+
+/// Declarations for `emit_js::emit_with_options(schema, opts)`. IIFE
+/// builds (`--runtime browser`) export a global rather than a module and
+/// have no declaration file.
+pub fn emit_declarations(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut w = CodeWriter::new();
+
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("d.ts") {
+            w.line(&format!("// {line}"));
+        }
+    }
+
+    if opts.path_segments {
+        w.line(
+            "/** A validation failure, as the path segments into the instance and the schema. */",
+        );
+        w.open("export interface ValidationError");
+        w.line("instancePath: string[];");
+        w.line("schemaPath: string[];");
+    } else {
+        w.line("/** A validation failure, as JSON Pointers into the instance and the schema. */");
+        w.open("export interface ValidationError");
+        w.line("instancePath: string;");
+        w.line("schemaPath: string;");
+    }
+    if opts.messages {
+        w.line("/** What was wrong, such as \"expected uint8, got string\"; not from `createSaxValidator`. */");
+        w.line("message?: string;");
+    }
+    if opts.suggestions {
+        w.line("/** The closest allowed name, for unknown enum values, tags and properties. */");
+        w.line("suggestion?: string;");
+    }
+    w.close();
+    w.line("");
+
+    for line in emit_types(schema).lines() {
+        w.line(line);
+    }
+    w.line("");
+
+    emit_jsdoc(&mut w, &function_doc(schema, "", &schema.root));
+    w.line("export declare function validate(instance: unknown): ValidationError[];");
+    if opts.js_export_definitions {
+        let names = definition_entry_names(schema.definitions.keys(), &["validate", "validateAt"]);
+        for (name, def) in names {
+            w.line(&format!(
+                "/** Validate `instance` against the definition at /definitions/{}. */",
+                escape_pointer(def)
+            ));
+            w.line(&format!(
+                "export declare function {name}(instance: unknown): ValidationError[];"
+            ));
+        }
+    }
+
+    if opts.is_valid {
+        w.line(
+            "/** Whether `instance` is valid; stops at the first failure and builds no errors. */",
+        );
+        w.line("export declare function isValid(instance: unknown): instance is Root;");
+    }
+    if opts.enum_constants {
+        let constants = enum_constants(&[("", &schema.root)], &schema.definitions);
+        for c in &constants {
+            let values: Vec<String> = c
+                .values
+                .iter()
+                .map(|v| serde_json::to_string(v).expect("strings serialize"))
+                .collect();
+            w.line(&format!(
+                "export declare const {}: readonly [{}];",
+                c.name,
+                values.join(", ")
+            ));
+        }
+    }
+    if opts.self_test {
+        w.line("/** Validate the schema's metadata.examples; returns the examples that fail. */");
+        w.line("export declare function selfTest(): { schemaPath: string; example: number; errors: ValidationError[] }[];");
+    }
+    if opts.deprecation_warnings {
+        w.line("/** Deprecated properties present in `instance`. */");
+        w.line("export declare function warnings(instance: unknown): ValidationError[];");
+    }
+    if opts.validate_at {
+        w.line("/** Validate `value` against the sub-schema at `pointer`, e.g. \"/properties/address\". */");
+        w.line("export declare function validateAt(pointer: string, value: unknown): ValidationError[];");
+    }
+    if opts.coerce {
+        w.line("/** A copy of `instance` with \"42\" / \"true\" style strings converted where the schema expects numbers or booleans. */");
+        w.line("export declare function coerce(instance: unknown): unknown;");
+    }
+    if opts.strip_additional {
+        w.line("/** A copy of `instance` without the properties the schema does not declare. */");
+        w.line("export declare function strip(instance: unknown): unknown;");
+    }
+    if opts.apply_defaults {
+        w.line("/** A copy of `instance` with absent optional properties set to their metadata.default. */");
+        w.line("export declare function applyDefaults(instance: unknown): unknown;");
+    }
+    if opts.js_sax {
+        w.line("/** A validator fed parse events; `end()` returns the errors. */");
+        w.open("export declare function createSaxValidator():");
+        w.line("openObject(): void;");
+        w.line("openArray(): void;");
+        w.line("closeObject(): void;");
+        w.line("closeArray(): void;");
+        w.line("key(k: string): void;");
+        w.line("value(v: unknown): void;");
+        w.line("end(): ValidationError[];");
+        w.close_with(";");
+    }
+    if opts.js_middleware {
+        // The frameworks' own request types are not dependencies here
+        w.line(
+            "/** Express middleware answering 400 with the errors when `req[part]` is invalid. */",
+        );
+        w.line("export declare function expressValidator(part?: string): (req: any, res: any, next: () => void) => void;");
+        w.line("/** Fastify preValidation hook answering 400 with the errors when `request[part]` is invalid. */");
+        w.line("export declare function fastifyValidator(part?: string): (request: any, reply: any) => Promise<void>;");
+        w.line("/** Fastify validatorCompiler checking every schema part a route declares. */");
+        w.line("export declare function fastifyValidatorCompiler(): (data: unknown) => { value: unknown } | { error: Error };");
+    }
+    if opts.mock {
+        w.line("/** A random valid instance; the same seed gives the same instance. */");
+        w.line("export declare function mock(seed: number): Root;");
+    }
+    if opts.shape {
+        w.line("/** Object keys, enum values and discriminator tags by instance path, for form and error UIs. */");
+        w.line("export declare const SHAPE: { readonly [instancePath: string]: unknown };");
+    }
+
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit_declarations() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["OPEN", "CLOSED"]}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let code = emit_declarations(&compiled, &EmitOptions::default());
+        assert!(code.starts_with("/** A validation failure, as JSON Pointers into the instance and the schema. */\nexport interface ValidationError {\n  instancePath: string;\n  schemaPath: string;\n}\n"));
+        assert!(
+            code.contains("/** An order. */\nexport interface Root {\n  status: RootStatus;\n}\n")
+        );
+        assert!(code.contains("export type RootStatus = \"OPEN\" | \"CLOSED\";\n"));
+        assert!(code.ends_with(
+            " * An order.\n */\nexport declare function validate(instance: unknown): ValidationError[];\n"
+        ));
+        assert!(!code.contains("STATUS_VALUES"));
+    }
+
+    #[test]
+    fn test_emit_declarations_options() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"status": {"enum": ["OPEN", "CLOSED"]}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            enum_constants: true,
+            suggestions: true,
+            js_sax: true,
+            mock: true,
+            is_valid: true,
+            ..Default::default()
+        };
+        let code = emit_declarations(&compiled, &opts);
+        assert!(code.contains("  suggestion?: string;\n}"));
+        assert!(
+            code.contains("export declare const STATUS_VALUES: readonly [\"OPEN\", \"CLOSED\"];")
+        );
+        assert!(
+            code.contains("export declare function createSaxValidator(): {\n  openObject(): void;")
+        );
+        assert!(code.contains("  end(): ValidationError[];\n};\n"));
+        assert!(code.contains("export declare function mock(seed: number): Root;"));
+        assert!(
+            code.contains("export declare function isValid(instance: unknown): instance is Root;")
+        );
+        assert!(!code.contains("coerce"));
+    }
+
+    #[test]
+    fn test_emit_declarations_definition_entry_points() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"address": {"type": "string"}, "at": {"type": "string"}},
+            "ref": "address"
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            js_export_definitions: true,
+            ..Default::default()
+        };
+        let code = emit_declarations(&compiled, &opts);
+        assert!(code.contains("/** Validate `instance` against the definition at /definitions/address. */\nexport declare function validateAddress(instance: unknown): ValidationError[];\n"));
+        assert!(code.contains("export declare function validateAt2(instance: unknown)"));
+    }
+}
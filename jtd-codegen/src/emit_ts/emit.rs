@@ -0,0 +1,145 @@
+use std::rc::Rc;
+
+use crate::ast::CompiledSchema;
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::emit_js::{
+    def_fn_name, definition_stop, emit_definition_predicates, emit_depth_enter, emit_depth_exit,
+    emit_jsdoc, emit_node, emit_root_predicate, emit_timestamp_helper, node_uses_timestamp,
+    CodeWriter, EmitContext, DEPTH_VAR, LIMIT_VAR,
+};
+use crate::options::EmitOptions;
+use crate::recursion::recursive;
+
+/// Emit a TypeScript module from a compiled schema.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a TypeScript module with the given options. Only the banner,
+/// `fail_fast`, `max_errors` and `is_valid` apply; the JavaScript-only helpers
+/// (`--coerce`, `--sax` and so on) are not generated.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut w = CodeWriter::new();
+
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("ts") {
+            w.line(&format!("// {line}"));
+        }
+    }
+
+    w.line("/** A validation failure, as JSON Pointers into the instance and the schema. */");
+    w.open("export interface ValidationError");
+    w.line("instancePath: string;");
+    w.line("schemaPath: string;");
+    w.close();
+    w.line("");
+
+    if node_uses_timestamp(&schema.root) || schema.definitions.values().any(node_uses_timestamp) {
+        emit_timestamp_helper(&mut w, true);
+        w.line("");
+    }
+
+    let recursive = recursive(&schema.definitions);
+    if !recursive.is_empty() {
+        w.line(&format!("let {DEPTH_VAR} = 0;"));
+        w.line("");
+    }
+
+    let limit = opts.error_limit();
+    let stops = opts.fail_fast || limit.is_some();
+    let limit_var = limit.map(|_| LIMIT_VAR.to_string());
+
+    // Checks index freely into values whose shape they are establishing,
+    // so inside the module the instance is `any`
+    for (name, node) in &schema.definitions {
+        emit_jsdoc(
+            &mut w,
+            &function_doc(
+                schema,
+                &format!("/definitions/{}", escape_pointer(name)),
+                node,
+            ),
+        );
+        w.open(&format!(
+            "function {}(v: any, e: ValidationError[], p: string, sp: string{}): void",
+            def_fn_name(name),
+            limit_var
+                .as_ref()
+                .map_or(String::new(), |l| format!(", {l}: number"))
+        ));
+        let ctx = EmitContext::definition();
+        let guarded = recursive.contains(name.as_str());
+        if guarded {
+            emit_depth_enter(&mut w, &ctx);
+        }
+        let ctx = ctx
+            .with_stop(stops.then(|| definition_stop(guarded)))
+            .with_limit(limit_var.clone());
+        emit_node(&mut w, &ctx, node, opts, None);
+        if guarded {
+            emit_depth_exit(&mut w);
+        }
+        w.close();
+        w.line("");
+    }
+
+    emit_jsdoc(&mut w, &function_doc(schema, "", &schema.root));
+    let limit_param = limit.map_or(String::new(), |n| format!(", {LIMIT_VAR}: number = {n}"));
+    w.open(&format!(
+        "export function validate(instance: unknown{limit_param}): ValidationError[]"
+    ));
+    w.line("const e: ValidationError[] = [];");
+    w.line("const v: any = instance;");
+    let ctx = EmitContext {
+        val: "v".into(),
+        stop: stops.then(|| "return e;".to_string()),
+        ..EmitContext::root()
+    }
+    .with_limit(limit_var);
+    emit_node(&mut w, &ctx, &schema.root, opts, None);
+    w.line("return e;");
+    w.close();
+
+    if opts.is_valid {
+        w.line("");
+        let hoisted = Rc::default();
+        emit_definition_predicates(&mut w, &schema.definitions, opts, &hoisted, true);
+        w.line(
+            "/** Whether `instance` is valid; stops at the first failure and builds no errors. */",
+        );
+        w.open("export function isValid(instance: unknown): boolean");
+        w.line("const v: any = instance;");
+        emit_root_predicate(&mut w, "v", &schema.root, opts, &hoisted);
+        w.close();
+    }
+
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let code = emit(&compiled);
+        assert!(code.contains("export interface ValidationError {\n  instancePath: string;"));
+        assert!(code.contains(
+            "function validate_id(v: any, e: ValidationError[], p: string, sp: string): void {"
+        ));
+        assert!(code.contains(
+            " * An order.\n */\nexport function validate(instance: unknown): ValidationError[] {"
+        ));
+        assert!(code.contains("validate_id(v[\"id\"], e, \"\" + \"/id\", \"/definitions/id\");"));
+        assert!(code.ends_with("  return e;\n}\n"));
+    }
+}
@@ -0,0 +1,11 @@
+/// TypeScript emitter: the JavaScript emitter's checks, typed. Entry points
+/// take `unknown` and return `ValidationError[]`; the module passes
+/// `tsc --strict`. `emit_types` declares the data's types and
+/// `emit_declarations` a `.d.ts` for the JavaScript module.
+mod declarations;
+mod emit;
+mod types;
+
+pub use declarations::emit_declarations;
+pub use emit::{emit, emit_with_options};
+pub use types::emit_types;
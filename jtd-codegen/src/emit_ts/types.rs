@@ -0,0 +1,369 @@
+/// TypeScript types for a schema's data, following the conventions of the
+/// JTD project's own TypeScript codegen:
+///
+/// - Properties become interfaces, optional properties `key?: T`; a
+///   discriminator is a union of one interface per variant, each with the
+///   tag as a string literal, so `switch (x.tag)` narrows it.
+/// - Enums are unions of string literals, elements `T[]`, values
+///   `{ [key: string]: T }`, timestamps `string` and the empty form
+///   `unknown`. Nullable adds `| null`.
+/// - Every definition is a type named after it (`order_item` becomes
+///   `OrderItem`); the root is `Root`. Anonymous interfaces and enums are
+///   named from their parent and key.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::graphql::pascal;
+use crate::recursion::ref_cycles;
+
+/// Names the validator module already declares.
+const RESERVED: [&str; 2] = ["Root", "ValidationError"];
+
+/// TypeScript declarations for `schema`: one type per definition, then the
+/// root's, all exported.
+pub fn emit_types(schema: &CompiledSchema) -> String {
+    let mut types = Types {
+        schema,
+        defs: BTreeMap::new(),
+        used: RESERVED.iter().map(|s| s.to_string()).collect(),
+        items: Vec::new(),
+    };
+    for name in schema.definitions.keys() {
+        let type_name = types.name(name);
+        types.defs.insert(name, type_name);
+    }
+    // `type A = B; type B = A | null` names no type at all
+    let cyclic: BTreeSet<&str> = ref_cycles(&schema.definitions)
+        .into_iter()
+        .flatten()
+        .collect();
+    for (name, node) in &schema.definitions {
+        let type_name = types.defs[name.as_str()].clone();
+        let path = format!("/definitions/{}", escape_pointer(name));
+        // Refs to a nullable definition add the `| null`
+        let node = match node {
+            Node::Nullable { inner } => inner,
+            node => node,
+        };
+        if cyclic.contains(name.as_str()) {
+            let slot = types.reserve();
+            types.items[slot] = format!(
+                "{}export type {type_name} = unknown;\n",
+                doc(schema.description(&path), "")
+            );
+        } else {
+            types.item(&type_name, node, &path);
+        }
+    }
+    types.used.remove("Root");
+    let root = types.name("Root");
+    types.item(&root, &schema.root, "");
+    types.items.join("\n")
+}
+
+struct Types<'a> {
+    schema: &'a CompiledSchema,
+    /// Type names of the definitions.
+    defs: BTreeMap<&'a str, String>,
+    /// Type names taken so far.
+    used: BTreeSet<String>,
+    /// Declarations, parents before the types named after them.
+    items: Vec<String>,
+}
+
+impl<'a> Types<'a> {
+    /// A fresh type name from `hint`.
+    fn name(&mut self, hint: &str) -> String {
+        let mut base = pascal(hint);
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert(0, 'T');
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.used.contains(&name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+        self.used.insert(name.clone());
+        name
+    }
+
+    /// A slot in `items` for a declaration filled in later, so it comes
+    /// before the declarations it names.
+    fn reserve(&mut self) -> usize {
+        self.items.push(String::new());
+        self.items.len() - 1
+    }
+
+    /// The type of `node` at `path`; anonymous types are named from `hint`.
+    fn type_ref(&mut self, node: &'a Node, path: &str, hint: &str) -> String {
+        match node {
+            Node::Nullable { inner } => {
+                let inner = self.type_ref(inner, path, hint);
+                if inner == "unknown" || inner.ends_with(" | null") {
+                    inner
+                } else {
+                    format!("{inner} | null")
+                }
+            }
+            Node::Empty => "unknown".to_string(),
+            Node::Ref { name } => {
+                let ty = self.defs[name.as_str()].clone();
+                match &self.schema.definitions[name] {
+                    Node::Nullable { .. } => format!("{ty} | null"),
+                    _ => ty,
+                }
+            }
+            Node::Type { type_kw } => match type_kw {
+                TypeKeyword::Boolean => "boolean",
+                TypeKeyword::String | TypeKeyword::Timestamp => "string",
+                _ => "number",
+            }
+            .to_string(),
+            Node::Elements { schema: item } => {
+                let item = self.type_ref(item, &format!("{path}/elements"), &format!("{hint}Item"));
+                if item.contains(' ') {
+                    format!("({item})[]")
+                } else {
+                    format!("{item}[]")
+                }
+            }
+            Node::Values { schema: item } => {
+                let item = self.type_ref(item, &format!("{path}/values"), &format!("{hint}Value"));
+                format!("{{ [key: string]: {item} }}")
+            }
+            node => {
+                let ty = self.name(hint);
+                self.item(&ty, node, path);
+                ty
+            }
+        }
+    }
+
+    /// Emit declaration `name` for `node` at `path`: an interface, a union
+    /// or an alias.
+    fn item(&mut self, name: &str, node: &'a Node, path: &str) {
+        let slot = self.reserve();
+        let mut out = doc(self.schema.description(path), "");
+        match node {
+            Node::Properties { .. } => {
+                out.push_str(&format!("export interface {name} {{\n"));
+                out.push_str(&self.fields(name, node, path, None));
+                out.push_str("}\n");
+            }
+            Node::Enum { values } => {
+                let values: Vec<String> = values.iter().map(|v| literal(v)).collect();
+                out.push_str(&format!("export type {name} = {};\n", values.join(" | ")));
+            }
+            Node::Discriminator { tag, mapping } => {
+                let variants: Vec<String> = mapping
+                    .keys()
+                    .map(|value| self.name(&format!("{name}_{value}")))
+                    .collect();
+                let union = if variants.is_empty() {
+                    "never".to_string()
+                } else {
+                    variants.join(" | ")
+                };
+                out.push_str(&format!("export type {name} = {union};\n"));
+                for ((value, variant), variant_name) in mapping.iter().zip(&variants) {
+                    let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                    let slot = self.reserve();
+                    let mut block = doc(self.schema.description(&variant_path), "");
+                    block.push_str(&format!("export interface {variant_name} {{\n"));
+                    block.push_str(&format!("  {}: {};\n", key(tag), literal(value)));
+                    block.push_str(&self.fields(variant_name, variant, &variant_path, Some(tag)));
+                    block.push_str("}\n");
+                    self.items[slot] = block;
+                }
+            }
+            node => {
+                // A nullable root names its object or enum `RootValue`
+                let hint = match node {
+                    Node::Nullable { .. } => format!("{name}Value"),
+                    _ => name.to_string(),
+                };
+                let target = self.type_ref(node, path, &hint);
+                out.push_str(&format!("export type {name} = {target};\n"));
+            }
+        }
+        self.items[slot] = out;
+    }
+
+    /// The members of the Properties form `node` of interface `parent`;
+    /// `tag` is left out, as the variant declares it.
+    fn fields(&mut self, parent: &str, node: &'a Node, path: &str, tag: Option<&str>) -> String {
+        let Node::Properties {
+            required, optional, ..
+        } = node
+        else {
+            return String::new();
+        };
+        let mut out = String::new();
+        let props = required
+            .iter()
+            .map(|(key, child)| (key, child, true))
+            .chain(optional.iter().map(|(key, child)| (key, child, false)));
+        for (name, child, is_required) in props {
+            if Some(name.as_str()) == tag {
+                continue;
+            }
+            let child_path = if is_required {
+                format!("{path}/properties/{}", escape_pointer(name))
+            } else {
+                format!("{path}/optionalProperties/{}", escape_pointer(name))
+            };
+            let ty = self.type_ref(child, &child_path, &format!("{parent}_{name}"));
+            let mark = if is_required { "" } else { "?" };
+            out.push_str(&doc(self.schema.description(&child_path), "  "));
+            out.push_str(&format!("  {}{mark}: {ty};\n", key(name)));
+        }
+        out
+    }
+}
+
+/// `s` as a string literal type.
+fn literal(s: &str) -> String {
+    serde_json::to_string(s).expect("strings serialize")
+}
+
+/// `name` as a property key: bare when it is an identifier, quoted
+/// otherwise.
+fn key(name: &str) -> String {
+    let ident = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if ident {
+        name.to_string()
+    } else {
+        literal(name)
+    }
+}
+
+/// `desc` as a JSDoc block indented by `indent`, or nothing.
+fn doc(desc: Option<&str>, indent: &str) -> String {
+    let Some(desc) = desc else {
+        return String::new();
+    };
+    let desc = desc.replace("*/", "*\\/");
+    let mut lines = desc.lines();
+    match (lines.next(), lines.next()) {
+        (Some(line), None) => format!("{indent}/** {line} */\n"),
+        _ => {
+            let mut out = format!("{indent}/**\n");
+            for line in desc.lines() {
+                out.push_str(format!("{indent} * {line}").trim_end());
+                out.push('\n');
+            }
+            out.push_str(&format!("{indent} */\n"));
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit_types() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree_node": {
+                    "properties": {"value": {"type": "uint8"}},
+                    "optionalProperties": {"next": {"ref": "tree_node"}}
+                },
+                "id": {"type": "string", "nullable": true}
+            },
+            "properties": {
+                "id": {"ref": "id", "metadata": {"description": "The order."}},
+                "type": {"enum": ["new", "in-progress"], "nullable": true},
+                "at": {"type": "timestamp"},
+                "extra": {"values": {}},
+                "tags": {"elements": {"type": "string", "nullable": true}},
+                "event": {
+                    "discriminator": "kind",
+                    "mapping": {
+                        "user-created": {"properties": {"user-name": {"type": "string"}}},
+                        "deleted": {"properties": {}}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            emit_types(&schema),
+            r#"export type Id = string;
+
+export interface TreeNode {
+  value: number;
+  next?: TreeNode;
+}
+
+export interface Root {
+  at: string;
+  event: RootEvent;
+  extra: { [key: string]: unknown };
+  /** The order. */
+  id: Id | null;
+  tags: (string | null)[];
+  type: RootType | null;
+}
+
+export type RootEvent = RootEventDeleted | RootEventUserCreated;
+
+export interface RootEventDeleted {
+  kind: "deleted";
+}
+
+export interface RootEventUserCreated {
+  kind: "user-created";
+  "user-name": string;
+}
+
+export type RootType = "new" | "in-progress";
+"#
+        );
+    }
+
+    #[test]
+    fn test_nullable_root() {
+        let schema = compile(&json!({
+            "properties": {"a": {"elements": {"ref": "root"}}},
+            "definitions": {"root": {"values": {"ref": "root"}}},
+            "nullable": true
+        }))
+        .unwrap();
+        assert_eq!(
+            emit_types(&schema),
+            "export type Root2 = { [key: string]: Root2 };\n\n\
+             export type Root = RootValue | null;\n\n\
+             export interface RootValue {\n  a: Root2[];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_alias_cycle() {
+        let schema = compile(&json!({
+            "definitions": {"a": {"ref": "b"}, "b": {"ref": "a", "nullable": true}},
+            "ref": "a"
+        }))
+        .unwrap();
+        assert_eq!(
+            emit_types(&schema),
+            "export type A = unknown;\n\nexport type B = unknown;\n\nexport type Root = A;\n"
+        );
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key("firstName"), "firstName");
+        assert_eq!(key("$ref"), "$ref");
+        assert_eq!(key("first-name"), "\"first-name\"");
+        assert_eq!(key("1st"), "\"1st\"");
+    }
+}
@@ -0,0 +1,458 @@
+/// Walks a CompiledSchema and writes one exported TypeBox schema, and its
+/// `Static` type, per definition and for the root.
+///
+/// TypeBox builds schemas from constants, so definitions are emitted
+/// before the ones that use them. A definition that reaches itself
+/// through refs becomes a `Type.Recursive`; the other definitions of its
+/// cycle are inlined into it as nested `Type.Recursive`s, since a
+/// constant cannot refer to one declared after it.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::graphql::pascal;
+use crate::mock::int_range;
+use crate::options::EmitOptions;
+use crate::recursion::reaches;
+use crate::source_map;
+
+/// Names the module imports or reserves.
+const RESERVED: [&str; 3] = ["Root", "Static", "Type"];
+
+/// Emit a TypeBox module from a compiled schema.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a TypeBox module with the given options. Only the banner applies.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut out = String::new();
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("typebox") {
+            out.push_str(&format!("// {line}\n"));
+        }
+    }
+    out.push_str("import { Type, type Static } from \"@sinclair/typebox\";\n");
+
+    let mut used: BTreeSet<String> = RESERVED.iter().map(|s| s.to_string()).collect();
+    let mut names = BTreeMap::new();
+    for name in schema.definitions.keys() {
+        names.insert(name.as_str(), fresh_name(&mut used, name));
+    }
+    let mut module = Module {
+        schema,
+        reaches: reaches(&schema.definitions),
+        names,
+        emitted: BTreeSet::new(),
+        self_refs: BTreeSet::new(),
+        items: Vec::new(),
+        source_map: opts.source_map,
+    };
+    for name in schema.definitions.keys() {
+        module.definition(name);
+    }
+    let root = module.expr(&schema.root, "", 0, &mut Vec::new());
+    module.push_item(
+        &schema.root,
+        "Root",
+        &function_doc(schema, "", &schema.root),
+        root,
+    );
+
+    for item in &module.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out
+}
+
+struct Module<'a> {
+    schema: &'a CompiledSchema,
+    /// The definitions each definition reaches through refs.
+    reaches: BTreeMap<&'a str, BTreeSet<&'a str>>,
+    /// Constant names of the definitions.
+    names: BTreeMap<&'a str, String>,
+    /// Definitions whose constant is written.
+    emitted: BTreeSet<&'a str>,
+    /// Recursive definitions referred to through their parameter.
+    self_refs: BTreeSet<&'a str>,
+    /// Constant declarations, dependencies first.
+    items: Vec<String>,
+    /// Wrap the lines of each node in source-map markers.
+    source_map: bool,
+}
+
+impl<'a> Module<'a> {
+    /// Write the constant for definition `name`, after those it uses.
+    fn definition(&mut self, name: &'a str) {
+        if !self.emitted.insert(name) {
+            return;
+        }
+        let expr = self.inline(name, 0, &mut Vec::new());
+        let path = format!("/definitions/{}", escape_pointer(name));
+        let node = &self.schema.definitions[name];
+        let doc = function_doc(self.schema, &path, node);
+        let const_name = self.names[name].clone();
+        self.push_item(node, &const_name, &doc, expr);
+    }
+
+    /// `code` in source-map markers for `node`, when asked for.
+    fn mark(&self, node: &Node, code: String) -> String {
+        if self.source_map {
+            source_map::wrap(node, &code)
+        } else {
+            code
+        }
+    }
+
+    /// Declare constant and type `name` for `node`.
+    fn push_item(&mut self, node: &Node, name: &str, doc: &[String], expr: String) {
+        let mut item = String::new();
+        if !doc.is_empty() {
+            item.push_str("/**\n");
+            for line in doc {
+                let line = line.replace("*/", "*\\/");
+                item.push_str(format!(" * {line}").trim_end());
+                item.push('\n');
+            }
+            item.push_str(" */\n");
+        }
+        item.push_str(&format!("export const {name} = {expr};\n"));
+        item.push_str(&format!("export type {name} = Static<typeof {name}>;\n"));
+        self.items.push(self.mark(node, item));
+    }
+
+    /// The schema of definition `name` written in place: a `Type.Recursive`
+    /// when it refers to itself. `stack` holds the recursive definitions
+    /// being written around it.
+    fn inline(&mut self, name: &'a str, depth: usize, stack: &mut Vec<&'a str>) -> String {
+        let node = &self.schema.definitions[name];
+        let path = format!("/definitions/{}", escape_pointer(name));
+        if !self.reaches[name].contains(name) {
+            return self.expr(node, &path, depth, stack);
+        }
+        stack.push(name);
+        let body = self.expr(node, &path, depth + 1, stack);
+        stack.pop();
+        if !self.self_refs.remove(name) {
+            // Only the definitions around it recurse through this one
+            return self.expr(node, &path, depth, stack);
+        }
+        let param = self_name(&self.names[name]);
+        format!(
+            "Type.Recursive(({param}) =>\n{}{body}\n{})",
+            indent(depth + 1),
+            indent(depth)
+        )
+    }
+
+    /// The TypeBox expression for `node` at schema path `path`, written at
+    /// indent level `depth`.
+    fn expr(
+        &mut self,
+        node: &'a Node,
+        path: &str,
+        depth: usize,
+        stack: &mut Vec<&'a str>,
+    ) -> String {
+        match node {
+            Node::Empty => "Type.Unknown()".to_string(),
+            Node::Ref { name } => {
+                let name = name.as_str();
+                if stack.contains(&name) {
+                    self.self_refs.insert(name);
+                    return self_name(&self.names[name]);
+                }
+                // Part of a cycle being written: it cannot be a constant
+                if stack.iter().any(|s| self.reaches[name].contains(s)) {
+                    return self.inline(name, depth, stack);
+                }
+                self.definition(name);
+                self.names[name].clone()
+            }
+            Node::Type { type_kw } => type_expr(*type_kw),
+            Node::Enum { values } => {
+                let literals: Vec<String> = values.iter().map(|v| literal(v)).collect();
+                union(&literals, depth)
+            }
+            Node::Elements { schema: item } => {
+                let item = self.expr(item, &format!("{path}/elements"), depth, stack);
+                format!("Type.Array({item})")
+            }
+            Node::Values { schema: item } => {
+                let item = self.expr(item, &format!("{path}/values"), depth, stack);
+                format!("Type.Record(Type.String(), {item})")
+            }
+            Node::Properties { .. } => self.object(node, path, None, depth, stack),
+            Node::Discriminator { mapping, .. } if mapping.is_empty() => "Type.Never()".into(),
+            Node::Discriminator { tag, mapping } => {
+                let variants: Vec<String> = mapping
+                    .iter()
+                    .map(|(value, variant)| {
+                        let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                        let tag = (tag.as_str(), value.as_str());
+                        self.object(variant, &variant_path, Some(tag), depth + 1, stack)
+                    })
+                    .collect();
+                union(&variants, depth)
+            }
+            Node::Nullable { inner } => match inner.as_ref() {
+                Node::Empty => "Type.Unknown()".to_string(),
+                Node::Enum { values } => {
+                    let mut members: Vec<String> = values.iter().map(|v| literal(v)).collect();
+                    members.push("Type.Null()".into());
+                    union(&members, depth)
+                }
+                inner => {
+                    let inner = self.expr(inner, path, depth + 1, stack);
+                    union(&[inner, "Type.Null()".into()], depth)
+                }
+            },
+        }
+    }
+
+    /// `Type.Object` for the Properties node `node`, with a discriminator
+    /// `tag` pinned to its value first when given. Closed unless the
+    /// schema allows additional properties.
+    fn object(
+        &mut self,
+        node: &'a Node,
+        path: &str,
+        tag: Option<(&str, &str)>,
+        depth: usize,
+        stack: &mut Vec<&'a str>,
+    ) -> String {
+        let Node::Properties {
+            required,
+            optional,
+            additional,
+        } = node
+        else {
+            return "Type.Unknown()".to_string();
+        };
+        let mut fields = Vec::new();
+        if let Some((tag, value)) = tag {
+            fields.push(format!(
+                "{}{}: {},\n",
+                indent(depth + 1),
+                key(tag),
+                literal(value)
+            ));
+        }
+        let props = required
+            .iter()
+            .map(|(key, child)| (key, child, true))
+            .chain(optional.iter().map(|(key, child)| (key, child, false)));
+        for (name, child, is_required) in props {
+            let child_path = if is_required {
+                format!("{path}/properties/{}", escape_pointer(name))
+            } else {
+                format!("{path}/optionalProperties/{}", escape_pointer(name))
+            };
+            let mut field = String::new();
+            if let Some(desc) = self.schema.description(&child_path) {
+                let desc = desc.replace("*/", "*\\/").replace('\n', " ");
+                field.push_str(&format!("/** {desc} */\n{}", indent(depth + 1)));
+            }
+            let ty = self.expr(child, &child_path, depth + 1, stack);
+            if is_required {
+                field.push_str(&format!("{}: {ty}", key(name)));
+            } else {
+                field.push_str(&format!("{}: Type.Optional({ty})", key(name)));
+            }
+            fields.push(self.mark(child, format!("{}{field},\n", indent(depth + 1))));
+        }
+        let options = if *additional {
+            ""
+        } else {
+            ", { additionalProperties: false }"
+        };
+        if fields.is_empty() {
+            return format!("Type.Object({{}}{options})");
+        }
+        let mut out = String::from("Type.Object({\n");
+        for field in fields {
+            out.push_str(&field);
+        }
+        out.push_str(&format!("{}}}{options})", indent(depth)));
+        out
+    }
+}
+
+fn type_expr(type_kw: TypeKeyword) -> String {
+    if let Some((lo, hi)) = int_range(type_kw) {
+        return format!("Type.Integer({{ minimum: {lo}, maximum: {hi} }})");
+    }
+    match type_kw {
+        TypeKeyword::Boolean => "Type.Boolean()",
+        TypeKeyword::Timestamp => "Type.String({ format: \"date-time\" })",
+        TypeKeyword::Float32 | TypeKeyword::Float64 => "Type.Number()",
+        _ => "Type.String()",
+    }
+    .to_string()
+}
+
+/// `Type.Union` of `members`, one per line when any spans several.
+fn union(members: &[String], depth: usize) -> String {
+    if members.iter().all(|m| !m.contains('\n')) {
+        return format!("Type.Union([{}])", members.join(", "));
+    }
+    let mut out = String::from("Type.Union([\n");
+    for member in members {
+        out.push_str(&format!("{}{member},\n", indent(depth + 1)));
+    }
+    out.push_str(&format!("{}])", indent(depth)));
+    out
+}
+
+/// A fresh constant name from `hint`.
+fn fresh_name(used: &mut BTreeSet<String>, hint: &str) -> String {
+    let mut base = pascal(hint);
+    if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        base.insert(0, 'T');
+    }
+    let mut name = base.clone();
+    let mut n = 2;
+    while used.contains(&name) {
+        name = format!("{base}{n}");
+        n += 1;
+    }
+    used.insert(name.clone());
+    name
+}
+
+/// The `Type.Recursive` parameter standing for constant `name`; constant
+/// names are PascalCase, so it cannot clash with one.
+fn self_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let first = chars.next().map(|c| c.to_ascii_lowercase());
+    first.into_iter().chain(chars).collect::<String>() + "Self"
+}
+
+fn literal(s: &str) -> String {
+    format!(
+        "Type.Literal({})",
+        serde_json::to_string(s).expect("strings serialize")
+    )
+}
+
+/// `name` as an object key: bare when it is an identifier, quoted
+/// otherwise.
+fn key(name: &str) -> String {
+    let ident = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if ident {
+        name.to_string()
+    } else {
+        serde_json::to_string(name).expect("strings serialize")
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let schema = compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {
+                "id": {"ref": "id", "metadata": {"description": "The order."}},
+                "qty": {"type": "uint8"},
+                "status": {"enum": ["new", "done"], "nullable": true}
+            },
+            "optionalProperties": {"first-name": {"type": "string"}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        assert_eq!(
+            emit(&schema),
+            r#"import { Type, type Static } from "@sinclair/typebox";
+
+export const Id = Type.String();
+export type Id = Static<typeof Id>;
+
+/**
+ * An order.
+ *
+ * Fields:
+ *   /id: The order.
+ */
+export const Root = Type.Object({
+  /** The order. */
+  id: Id,
+  qty: Type.Integer({ minimum: 0, maximum: 255 }),
+  status: Type.Union([Type.Literal("new"), Type.Literal("done"), Type.Null()]),
+  "first-name": Type.Optional(Type.String()),
+}, { additionalProperties: false });
+export type Root = Static<typeof Root>;
+"#
+        );
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let schema = compile(&json!({
+            "discriminator": "kind",
+            "mapping": {
+                "a": {"properties": {"x": {"type": "timestamp"}}},
+                "b": {"properties": {}, "additionalProperties": true}
+            }
+        }))
+        .unwrap();
+        assert!(emit(&schema).contains(
+            r#"export const Root = Type.Union([
+  Type.Object({
+    kind: Type.Literal("a"),
+    x: Type.String({ format: "date-time" }),
+  }, { additionalProperties: false }),
+  Type.Object({
+    kind: Type.Literal("b"),
+  }),
+]);"#
+        ));
+    }
+
+    #[test]
+    fn test_recursion() {
+        let schema = compile(&json!({
+            "definitions": {
+                "leaf": {"type": "string"},
+                "tree": {"properties": {"kids": {"elements": {"ref": "tree"}}, "leaf": {"ref": "leaf"}}},
+                "a": {"properties": {"b": {"ref": "b", "nullable": true}}},
+                "b": {"elements": {"ref": "a"}}
+            },
+            "ref": "tree"
+        }))
+        .unwrap();
+        let code = emit(&schema);
+        // Dependencies first; each cycle member is self-contained
+        let leaf = code.find("export const Leaf =").unwrap();
+        let tree = code.find("export const Tree =").unwrap();
+        assert!(leaf < tree);
+        assert!(code.contains(
+            "export const Tree = Type.Recursive((treeSelf) =>\n  Type.Object({\n    kids: Type.Array(treeSelf),\n    leaf: Leaf,\n  }, { additionalProperties: false })\n);"
+        ));
+        assert!(code.contains(
+            "export const A = Type.Recursive((aSelf) =>\n  Type.Object({\n    b: Type.Union([Type.Array(aSelf), Type.Null()]),\n  }, { additionalProperties: false })\n);"
+        ));
+        assert!(code.contains("export const B = Type.Recursive((bSelf) =>\n  Type.Array(Type.Object({\n    b: Type.Union([bSelf, Type.Null()]),\n  }, { additionalProperties: false }))\n);"));
+        assert!(
+            code.ends_with("export const Root = Tree;\nexport type Root = Static<typeof Root>;\n")
+        );
+    }
+
+    #[test]
+    fn test_self_name() {
+        assert_eq!(self_name("OrderItem"), "orderItemSelf");
+    }
+}
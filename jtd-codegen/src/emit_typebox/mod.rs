@@ -0,0 +1,7 @@
+/// TypeBox emitter: `Type.Object`/`Type.Union` schemas with their `Static`
+/// types, for Fastify's TypeBox type provider and other JSON Schema
+/// validators. Each definition and the root (`Root`) is an exported
+/// constant of the same name as its type.
+mod emit;
+
+pub use emit::{emit, emit_with_options};
@@ -0,0 +1,695 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::runtime::{RUNTIME, TIMESTAMP};
+use super::writer::{comment, wat_string, CodeWriter};
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::docs::function_doc;
+use crate::options::EmitOptions;
+use crate::recursion::{recursive, MAX_REF_DEPTH};
+use crate::source_map;
+
+/// Token kinds, as written by the host.
+const NULL: u32 = 0;
+const NUMBER: u32 = 3;
+const STRING: u32 = 4;
+const ARRAY: u32 = 5;
+const OBJECT: u32 = 6;
+
+const PAGE: usize = 65536;
+
+/// The end of a call of a recursive definition.
+const DEPTH_LEAVE: &str = "(global.set $depth (i32.sub (global.get $depth) (i32.const 1)))";
+
+/// Emit a WAT module validating token buffers against `schema`.
+pub fn emit(schema: &CompiledSchema) -> String {
+    emit_with_options(schema, &EmitOptions::default())
+}
+
+/// Emit a WAT module with the given options. Only the banner, fail-fast,
+/// `max_errors` and `is_valid` apply; the limit is fixed when generating.
+pub fn emit_with_options(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut module = Module {
+        defs: schema
+            .definitions
+            .keys()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect(),
+        data: Vec::new(),
+        strings: HashMap::new(),
+        paths: Vec::new(),
+        path_ids: HashMap::new(),
+        timestamps: false,
+        fail_fast: opts.fail_fast,
+        max_errors: opts.error_limit(),
+        source_map: opts.source_map,
+    };
+
+    let recursive = recursive(&schema.definitions);
+    let root_doc = function_doc(schema, "", &schema.root);
+    let mut funcs = vec![module.func("$root", "root", &root_doc, &schema.root, "", false)];
+    for (name, node) in &schema.definitions {
+        let id = format!("$d{}", module.defs[name.as_str()]);
+        let sp = format!("/definitions/{}", escape_pointer(name));
+        let doc = function_doc(schema, &sp, node);
+        let guarded = recursive.contains(name.as_str());
+        funcs.push(module.func(&id, &comment(&sp), &doc, node, &sp, guarded));
+    }
+    if opts.is_valid {
+        funcs.push(module.predicate("$is_root", "root", &schema.root, false));
+        for (name, node) in &schema.definitions {
+            let id = format!("$is_d{}", module.defs[name.as_str()]);
+            let note = comment(&format!("/definitions/{}", escape_pointer(name)));
+            let guarded = recursive.contains(name.as_str());
+            funcs.push(module.predicate(&id, &note, node, guarded));
+        }
+    }
+
+    // The schema path table: (address, length) of each path, by id
+    while !module.data.len().is_multiple_of(4) {
+        module.data.push(0);
+    }
+    let table = module.data.len();
+    for &(ptr, len) in &module.paths {
+        module.data.extend(ptr.to_le_bytes());
+        module.data.extend(len.to_le_bytes());
+    }
+    let heap_base = module.data.len().div_ceil(16) * 16;
+
+    let mut w = CodeWriter::new();
+    w.line(";; Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)");
+    w.line(";; This code is generated from a JSON Type Definition schema.");
+    w.line(";; Do not edit manually.");
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("wat") {
+            w.line(&format!(";; {line}"));
+        }
+    }
+    w.open("(module");
+    w.line(&format!(
+        "(memory (export \"memory\") {})",
+        heap_base / PAGE + 1
+    ));
+    w.line(";; Where the host may write tokens and strings");
+    w.line(&format!(
+        "(global (export \"heap_base\") i32 (i32.const {heap_base}))"
+    ));
+    w.line(";; (address, length) of each schema path, indexed by error records");
+    w.line(&format!(
+        "(global (export \"schema_paths\") i32 (i32.const {table}))"
+    ));
+    w.line(&format!(
+        "(global (export \"schema_path_count\") i32 (i32.const {}))",
+        module.paths.len()
+    ));
+    w.line("(global $tokens (mut i32) (i32.const 0))");
+    w.line("(global $errors (mut i32) (i32.const 0))");
+    w.line("(global $cap (mut i32) (i32.const 0))");
+    w.line("(global $count (mut i32) (i32.const 0))");
+    if !recursive.is_empty() {
+        w.line(";; Calls of recursive definitions in progress");
+        w.line("(global $depth (mut i32) (i32.const 0))");
+    }
+    if !module.data.is_empty() {
+        w.line(&format!(
+            "(data (i32.const 0) {})",
+            wat_string(&module.data)
+        ));
+    }
+    let mut out = w.finish();
+    out.push_str(RUNTIME);
+    if module.timestamps {
+        out.push_str(TIMESTAMP);
+    }
+
+    let mut w = CodeWriter::with_depth(1);
+    w.line(";; Validate the token buffer at $tokens, writing up to $cap error records");
+    w.line(";; of (token index, schema path id) to $errors; returns the error count");
+    w.open("(func (export \"validate\") (param $tokens i32) (param $errors i32) (param $cap i32) (result i32)");
+    w.line("(global.set $tokens (local.get $tokens))");
+    w.line("(global.set $errors (local.get $errors))");
+    w.line("(global.set $cap (local.get $cap))");
+    w.line("(global.set $count (i32.const 0))");
+    if !recursive.is_empty() {
+        // After a trap the count is stale
+        w.line("(global.set $depth (i32.const 0))");
+    }
+    w.line("(call $root (i32.const 0))");
+    w.line("(global.get $count))");
+    if opts.is_valid {
+        w.line(";; 1 when the token buffer at $tokens is valid, 0 at the first failure;");
+        w.line(";; no error records are written");
+        w.open("(func (export \"is_valid\") (param $tokens i32) (result i32)");
+        w.line("(global.set $tokens (local.get $tokens))");
+        if !recursive.is_empty() {
+            w.line("(global.set $depth (i32.const 0))");
+        }
+        w.line("(call $is_root (i32.const 0)))");
+    }
+    out.push_str(&w.finish());
+    for f in funcs {
+        out.push_str(&f);
+    }
+    out.push_str(")\n");
+    out
+}
+
+struct Module<'a> {
+    /// Index of each definition's function, `$d<index>`.
+    defs: BTreeMap<&'a str, usize>,
+    /// The data segment: interned strings, then the schema path table.
+    data: Vec<u8>,
+    strings: HashMap<String, (u32, u32)>,
+    /// `(address, length)` of each schema path, by id.
+    paths: Vec<(u32, u32)>,
+    path_ids: HashMap<String, u32>,
+    timestamps: bool,
+    /// Return after the first error.
+    fail_fast: bool,
+    /// Return once there are this many errors.
+    max_errors: Option<usize>,
+    /// Wrap the checks of each node in source-map markers.
+    source_map: bool,
+}
+
+/// A function body under construction.
+struct Func {
+    w: CodeWriter,
+    locals: usize,
+    labels: usize,
+    /// Instructions leaving the function after an error, when failing fast.
+    stop: Vec<&'static str>,
+    /// Whether this is a predicate for `is_valid`: a failure runs `stop`,
+    /// which returns 0, without recording an error.
+    predicate: bool,
+}
+
+impl Func {
+    fn local(&mut self) -> String {
+        self.locals += 1;
+        format!("$l{}", self.locals)
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!("$b{}", self.labels)
+    }
+}
+
+impl<'a> Module<'a> {
+    /// `(address, length)` of `s` in the data segment.
+    fn string(&mut self, s: &str) -> (u32, u32) {
+        if let Some(&at) = self.strings.get(s) {
+            return at;
+        }
+        let at = (self.data.len() as u32, s.len() as u32);
+        self.data.extend(s.as_bytes());
+        self.strings.insert(s.to_string(), at);
+        at
+    }
+
+    fn path_id(&mut self, sp: &str) -> u32 {
+        if let Some(&id) = self.path_ids.get(sp) {
+            return id;
+        }
+        let at = self.string(sp);
+        let id = self.paths.len() as u32;
+        self.paths.push(at);
+        self.path_ids.insert(sp.to_string(), id);
+        id
+    }
+
+    /// The function `id` validating the token in `$t` against `node`,
+    /// documented with `doc` from `metadata.description`. `guarded`
+    /// functions, those of recursive definitions, count how deeply they
+    /// nest.
+    fn func(
+        &mut self,
+        id: &str,
+        note: &str,
+        doc: &[String],
+        node: &'a Node,
+        sp: &str,
+        guarded: bool,
+    ) -> String {
+        let mut f = Func {
+            w: CodeWriter::with_depth(2),
+            locals: 0,
+            labels: 0,
+            stop: Vec::new(),
+            predicate: false,
+        };
+        // From MAX_REF_DEPTH nested calls on, report the token rather than
+        // descend, so deep instances cannot exhaust the stack
+        if guarded {
+            f.w.line(&format!(
+                "(i32.ge_u (global.get $depth) (i32.const {MAX_REF_DEPTH}))"
+            ));
+            f.w.open("if");
+            self.err(&mut f, "$t", sp);
+            f.w.line("return");
+            f.w.close();
+            f.w.line("(global.set $depth (i32.add (global.get $depth) (i32.const 1)))");
+        }
+        if self.fail_fast || self.max_errors.is_some() {
+            if guarded {
+                f.stop.push(DEPTH_LEAVE);
+            }
+            f.stop.push("return");
+        }
+        self.node(&mut f, node, "$t", sp, None);
+        if guarded {
+            f.w.line(DEPTH_LEAVE);
+        }
+
+        let mut w = CodeWriter::with_depth(1);
+        w.line(&format!(";; {note}"));
+        for line in doc {
+            w.line(format!(";; {line}").trim_end());
+        }
+        w.open(&format!("(func {id} (param $t i32)"));
+        for i in 1..=f.locals {
+            w.line(&format!("(local $l{i} i32)"));
+        }
+        let mut out = w.finish();
+        out.push_str(&f.w.finish());
+        out.push_str("  )\n");
+        out
+    }
+
+    /// The predicate `id` for `is_valid`, returning 1 when the token in `$t`
+    /// is valid against `node` and 0 at the first failure. `guarded` ones
+    /// share the validators' depth count.
+    fn predicate(&mut self, id: &str, note: &str, node: &'a Node, guarded: bool) -> String {
+        let mut f = Func {
+            w: CodeWriter::with_depth(2),
+            locals: 0,
+            labels: 0,
+            stop: Vec::new(),
+            predicate: true,
+        };
+        if guarded {
+            f.w.line(&format!(
+                "(i32.ge_u (global.get $depth) (i32.const {MAX_REF_DEPTH}))"
+            ));
+            f.w.open("if");
+            f.w.line("(return (i32.const 0))");
+            f.w.close();
+            f.w.line("(global.set $depth (i32.add (global.get $depth) (i32.const 1)))");
+            f.stop.push(DEPTH_LEAVE);
+        }
+        f.stop.push("(return (i32.const 0))");
+        self.node(&mut f, node, "$t", "", None);
+        if guarded {
+            f.w.line(DEPTH_LEAVE);
+        }
+        f.w.line("(i32.const 1)");
+
+        let mut w = CodeWriter::with_depth(1);
+        w.line(&format!(";; {note}"));
+        w.open(&format!("(func {id} (param $t i32) (result i32)"));
+        for i in 1..=f.locals {
+            w.line(&format!("(local $l{i} i32)"));
+        }
+        let mut out = w.finish();
+        out.push_str(&f.w.finish());
+        out.push_str("  )\n");
+        out
+    }
+
+    fn err(&mut self, f: &mut Func, t: &str, sp: &str) {
+        if f.predicate {
+            for line in &f.stop {
+                f.w.line(line);
+            }
+            return;
+        }
+        let id = self.path_id(sp);
+        f.w.line(&format!(
+            "(call $err (local.get {t}) (i32.const {id}))  ;; {}",
+            comment(sp)
+        ));
+        match self.max_errors {
+            Some(_) if !f.stop.is_empty() => self.emit_stop(f),
+            _ => {
+                for line in &f.stop {
+                    f.w.line(line);
+                }
+            }
+        }
+    }
+
+    /// `if <stop> end` once the error count reaches `max_errors`, or any
+    /// error was reported when failing fast.
+    fn emit_stop(&self, f: &mut Func) {
+        match self.max_errors {
+            Some(n) => {
+                f.w.line(&format!("(i32.ge_u (global.get $count) (i32.const {n}))"))
+            }
+            None => f.w.line("(global.get $count)"),
+        }
+        f.w.open("if");
+        for line in &f.stop {
+            f.w.line(line);
+        }
+        f.w.close();
+    }
+
+    /// `(i32.ne (call $kind t) (i32.const kind)) if <error> br <exit> end`.
+    fn guard_kind(&mut self, f: &mut Func, t: &str, kind: u32, sp: &str, exit: &str) {
+        f.w.line(&format!(
+            "(i32.ne (call $kind (local.get {t})) (i32.const {kind}))"
+        ));
+        f.w.open("if");
+        self.err(f, t, sp);
+        f.w.line(&format!("br {exit}"));
+        f.w.close();
+    }
+
+    /// 1 when the token in `t` is the string `s`.
+    fn eq(&mut self, t: &str, s: &str) -> String {
+        let (ptr, len) = self.string(s);
+        format!("(call $eq (local.get {t}) (i32.const {ptr}) (i32.const {len}))")
+    }
+
+    /// The value token of member `s` of the object in `t`, or -1.
+    fn find(&mut self, t: &str, s: &str) -> String {
+        let (ptr, len) = self.string(s);
+        format!("(call $find (local.get {t}) (i32.const {ptr}) (i32.const {len}))")
+    }
+
+    /// [`Self::node_checks`], in source-map markers when asked for.
+    fn node(&mut self, f: &mut Func, node: &'a Node, t: &str, sp: &str, tag: Option<&str>) {
+        if !self.source_map {
+            return self.node_checks(f, node, t, sp, tag);
+        }
+        f.w.line(&source_map::open(node));
+        self.node_checks(f, node, t, sp, tag);
+        f.w.line(source_map::close());
+    }
+
+    /// Validate the token whose index is in local `t` against `node` at
+    /// schema path `sp`. `tag` is a discriminator tag the properties of
+    /// `node` may leave undeclared.
+    fn node_checks(&mut self, f: &mut Func, node: &'a Node, t: &str, sp: &str, tag: Option<&str>) {
+        match node {
+            Node::Empty => {}
+
+            Node::Type { type_kw } => {
+                let check = self.type_check(*type_kw, t);
+                f.w.line(&format!("(i32.eqz {check})"));
+                f.w.open("if");
+                self.err(f, t, &format!("{sp}/type"));
+                f.w.close();
+            }
+
+            Node::Enum { values } => {
+                let done = f.label();
+                f.w.open(&format!("block {done}"));
+                for value in values {
+                    let eq = self.eq(t, value);
+                    f.w.line(&format!("(br_if {done} {eq})  ;; {}", comment(value)));
+                }
+                self.err(f, t, &format!("{sp}/enum"));
+                f.w.close();
+            }
+
+            Node::Ref { name } if f.predicate => {
+                let index = self.defs[name.as_str()];
+                f.w.line(&format!("(i32.eqz (call $is_d{index} (local.get {t})))"));
+                f.w.open("if");
+                for line in &f.stop {
+                    f.w.line(line);
+                }
+                f.w.close();
+            }
+
+            Node::Ref { name } => {
+                let index = self.defs[name.as_str()];
+                f.w.line(&format!("(call $d{index} (local.get {t}))"));
+                if !f.stop.is_empty() {
+                    self.emit_stop(f);
+                }
+            }
+
+            Node::Nullable { inner } => {
+                f.w.line(&format!(
+                    "(i32.ne (call $kind (local.get {t})) (i32.const {NULL}))"
+                ));
+                f.w.open("if");
+                self.node(f, inner, t, sp, tag);
+                f.w.close();
+            }
+
+            Node::Elements { schema: item } => {
+                let item_sp = format!("{sp}/elements");
+                if matches!(**item, Node::Empty) {
+                    f.w.line(&format!(
+                        "(i32.ne (call $kind (local.get {t})) (i32.const {ARRAY}))"
+                    ));
+                    f.w.open("if");
+                    self.err(f, t, &item_sp);
+                    f.w.close();
+                    return;
+                }
+                let done = f.label();
+                f.w.open(&format!("block {done}"));
+                self.guard_kind(f, t, ARRAY, &item_sp, &done);
+                let (left, c) = (f.local(), f.local());
+                f.w.line(&format!("(local.set {left} (call $len (local.get {t})))"));
+                f.w.line(&format!(
+                    "(local.set {c} (i32.add (local.get {t}) (i32.const 1)))"
+                ));
+                let each = f.label();
+                f.w.open(&format!("loop {each}"));
+                f.w.line(&format!("(br_if {done} (i32.eqz (local.get {left})))"));
+                self.node(f, item, &c, &item_sp, None);
+                f.w.line(&format!("(local.set {c} (call $next (local.get {c})))"));
+                f.w.line(&format!(
+                    "(local.set {left} (i32.sub (local.get {left}) (i32.const 1)))"
+                ));
+                f.w.line(&format!("br {each}"));
+                f.w.close();
+                f.w.close();
+            }
+
+            Node::Values { schema: item } => {
+                let item_sp = format!("{sp}/values");
+                if matches!(**item, Node::Empty) {
+                    f.w.line(&format!(
+                        "(i32.ne (call $kind (local.get {t})) (i32.const {OBJECT}))"
+                    ));
+                    f.w.open("if");
+                    self.err(f, t, &item_sp);
+                    f.w.close();
+                    return;
+                }
+                let done = f.label();
+                f.w.open(&format!("block {done}"));
+                self.guard_kind(f, t, OBJECT, &item_sp, &done);
+                let (left, k, v) = (f.local(), f.local(), f.local());
+                f.w.line(&format!("(local.set {left} (call $len (local.get {t})))"));
+                f.w.line(&format!(
+                    "(local.set {k} (i32.add (local.get {t}) (i32.const 1)))"
+                ));
+                let each = f.label();
+                f.w.open(&format!("loop {each}"));
+                f.w.line(&format!("(br_if {done} (i32.eqz (local.get {left})))"));
+                f.w.line(&format!(
+                    "(local.set {v} (i32.add (local.get {k}) (i32.const 1)))"
+                ));
+                self.node(f, item, &v, &item_sp, None);
+                f.w.line(&format!("(local.set {k} (call $next (local.get {v})))"));
+                f.w.line(&format!(
+                    "(local.set {left} (i32.sub (local.get {left}) (i32.const 1)))"
+                ));
+                f.w.line(&format!("br {each}"));
+                f.w.close();
+                f.w.close();
+            }
+
+            Node::Properties {
+                required,
+                optional,
+                additional,
+            } => {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                let done = f.label();
+                f.w.open(&format!("block {done}"));
+                self.guard_kind(f, t, OBJECT, &format!("{sp}{guard}"), &done);
+                for (key, child) in required {
+                    let child_sp = format!("{sp}/properties/{}", escape_pointer(key));
+                    let v = f.local();
+                    let find = self.find(t, key);
+                    f.w.line(&format!("(local.set {v} {find})  ;; {}", comment(key)));
+                    f.w.line(&format!("(i32.lt_s (local.get {v}) (i32.const 0))"));
+                    f.w.open("if");
+                    self.err(f, t, &child_sp);
+                    if !matches!(child, Node::Empty) {
+                        f.w.close_open("else");
+                        self.node(f, child, &v, &child_sp, None);
+                    }
+                    f.w.close();
+                }
+                for (key, child) in optional {
+                    if matches!(child, Node::Empty) {
+                        continue;
+                    }
+                    let child_sp = format!("{sp}/optionalProperties/{}", escape_pointer(key));
+                    let v = f.local();
+                    let find = self.find(t, key);
+                    f.w.line(&format!("(local.set {v} {find})  ;; {}", comment(key)));
+                    f.w.line(&format!("(i32.ge_s (local.get {v}) (i32.const 0))"));
+                    f.w.open("if");
+                    self.node(f, child, &v, &child_sp, None);
+                    f.w.close();
+                }
+                if !additional {
+                    let (left, k) = (f.local(), f.local());
+                    f.w.line(&format!("(local.set {left} (call $len (local.get {t})))"));
+                    f.w.line(&format!(
+                        "(local.set {k} (i32.add (local.get {t}) (i32.const 1)))"
+                    ));
+                    let each = f.label();
+                    f.w.open(&format!("loop {each}"));
+                    f.w.line(&format!("(br_if {done} (i32.eqz (local.get {left})))"));
+                    let known = f.label();
+                    f.w.open(&format!("block {known}"));
+                    let keys = required.keys().chain(optional.keys());
+                    for key in keys.map(String::as_str).chain(tag) {
+                        let eq = self.eq(&k, key);
+                        f.w.line(&format!("(br_if {known} {eq})  ;; {}", comment(key)));
+                    }
+                    self.err(f, &k, sp);
+                    f.w.close();
+                    f.w.line(&format!(
+                        "(local.set {k} (call $next (i32.add (local.get {k}) (i32.const 1))))"
+                    ));
+                    f.w.line(&format!(
+                        "(local.set {left} (i32.sub (local.get {left}) (i32.const 1)))"
+                    ));
+                    f.w.line(&format!("br {each}"));
+                    f.w.close();
+                }
+                f.w.close();
+            }
+
+            Node::Discriminator { tag, mapping } => {
+                let tag_sp = format!("{sp}/discriminator");
+                let done = f.label();
+                f.w.open(&format!("block {done}"));
+                self.guard_kind(f, t, OBJECT, &tag_sp, &done);
+                let v = f.local();
+                let find = self.find(t, tag);
+                f.w.line(&format!("(local.set {v} {find})  ;; {}", comment(tag)));
+                f.w.line(&format!("(i32.lt_s (local.get {v}) (i32.const 0))"));
+                f.w.open("if");
+                self.err(f, t, &tag_sp);
+                f.w.line(&format!("br {done}"));
+                f.w.close();
+                self.guard_kind(f, &v, STRING, &tag_sp, &done);
+                for (value, variant) in mapping {
+                    let eq = self.eq(&v, value);
+                    f.w.line(&format!("{eq}  ;; {}", comment(value)));
+                    f.w.open("if");
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(value));
+                    self.node(f, variant, t, &variant_sp, Some(tag));
+                    f.w.line(&format!("br {done}"));
+                    f.w.close();
+                }
+                self.err(f, &v, &format!("{sp}/mapping"));
+                f.w.close();
+            }
+        }
+    }
+
+    /// An i32 expression, 1 when the token in `t` has type `type_kw`.
+    fn type_check(&mut self, type_kw: TypeKeyword, t: &str) -> String {
+        let kind = format!("(call $kind (local.get {t}))");
+        let int = |min: f64, max: f64| {
+            format!("(call $int (local.get {t}) (f64.const {min}) (f64.const {max}))")
+        };
+        match type_kw {
+            TypeKeyword::Boolean => {
+                format!("(i32.le_u (i32.sub {kind} (i32.const 1)) (i32.const 1))")
+            }
+            TypeKeyword::String => format!("(i32.eq {kind} (i32.const {STRING}))"),
+            TypeKeyword::Timestamp => {
+                self.timestamps = true;
+                format!("(call $timestamp (local.get {t}))")
+            }
+            TypeKeyword::Float32 | TypeKeyword::Float64 => {
+                format!("(i32.eq {kind} (i32.const {NUMBER}))")
+            }
+            TypeKeyword::Int8 => int(-128.0, 127.0),
+            TypeKeyword::Uint8 => int(0.0, 255.0),
+            TypeKeyword::Int16 => int(-32768.0, 32767.0),
+            TypeKeyword::Uint16 => int(0.0, 65535.0),
+            TypeKeyword::Int32 => int(-2_147_483_648.0, 2_147_483_647.0),
+            TypeKeyword::Uint32 => int(0.0, 4_294_967_295.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"id": {"ref": "id"}, "tags": {"elements": {"enum": ["a", "b"]}}}
+        }))
+        .unwrap();
+        let wat = emit(&compiled);
+        assert!(wat.contains("(func (export \"validate\")"));
+        assert!(wat.contains(";; \"/definitions/id\"\n  (func $d0 (param $t i32)"));
+        assert!(wat.contains("(call $d0 (local.get $l1))"));
+        assert!(wat.contains(";; \"/properties/tags/elements/enum\""));
+        // The RFC 3339 helpers only come with timestamps
+        assert!(!wat.contains("$timestamp"));
+        let compiled = compiler::compile(&json!({"type": "timestamp"})).unwrap();
+        assert!(emit(&compiled).contains("(call $timestamp (local.get $t))"));
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let compiled = compiler::compile(&json!({
+            "properties": {"id": {"type": "string", "metadata": {"description": "The id."}}},
+            "metadata": {"description": "An order."}
+        }))
+        .unwrap();
+        let wat = emit(&compiled);
+        assert!(wat.contains(
+            ";; root\n  ;; An order.\n  ;;\n  ;; Fields:\n  ;;   /id: The id.\n  (func $root (param $t i32)"
+        ));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"node": {"optionalProperties": {"next": {"ref": "node"}}}},
+            "properties": {"id": {"type": "string"}, "n": {"ref": "node"}}
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            is_valid: true,
+            ..Default::default()
+        };
+        let wat = emit_with_options(&compiled, &opts);
+        assert!(wat.contains("(func (export \"is_valid\") (param $tokens i32) (result i32)"));
+        assert!(wat.contains("(func $is_d0 (param $t i32) (result i32)"));
+        assert!(wat.contains("(i32.eqz (call $is_d0 (local.get $l2)))"));
+        // Predicates add no schema paths
+        let paths = |wat: &str| wat.matches("(call $err").count();
+        assert_eq!(paths(&wat), paths(&emit(&compiled)));
+    }
+}
@@ -0,0 +1,38 @@
+/// WebAssembly emitter: a standalone module per schema, in WAT or (with
+/// the `wasm` feature) assembled to `.wasm`, so hosts without a Rust
+/// toolchain get a validator of a few kilobytes.
+///
+/// The module parses no JSON. The host writes the instance as a token
+/// buffer into the exported `memory`, at or above the exported global
+/// `heap_base`, growing the memory as needed. Each token is 16 bytes,
+/// little-endian, in document order:
+///
+/// | offset | field                                                        |
+/// |--------|--------------------------------------------------------------|
+/// | 0      | kind (i32): 0 null, 1 false, 2 true, 3 number, 4 string, 5 array, 6 object |
+/// | 4      | i32: a string's UTF-8 byte length; an array's element count; an object's member count |
+/// | 8      | f64 for a number; for a string, the i32 address of its (unescaped) bytes; for an array or object, the i32 index of the first token after it |
+///
+/// An array's elements follow it; an object's members follow it as a key
+/// string token then the value's tokens. `validate(tokens, errors, cap)`
+/// checks the value whose first token is at address `tokens`, writes up
+/// to `cap` 8-byte error records of `(token index, schema path id)` at
+/// `errors`, and returns the number of errors, which may exceed `cap`.
+///
+/// The token index is where the error is: the value, or for an
+/// additional property its key token, whose instance path is the
+/// member's. Schema path `id` is entry `id` of the `(address, length)`
+/// table at the exported global `schema_paths`. [`tape`] is a host for
+/// Rust callers.
+mod emit;
+mod runtime;
+pub mod tape;
+mod writer;
+
+pub use emit::{emit, emit_with_options};
+
+/// Assemble WAT from [`emit`] into a `.wasm` binary.
+#[cfg(feature = "wasm")]
+pub fn assemble(wat: &str) -> Vec<u8> {
+    wat::parse_str(wat).expect("generated WAT is well formed")
+}
@@ -0,0 +1,184 @@
+/// Helper functions every generated module carries, in WAT. They read the
+/// token buffer described in [`crate::emit_wasm`] and record errors.
+pub const RUNTIME: &str = r#"  ;; Address of token $t
+  (func $tok (param $t i32) (result i32)
+    (i32.add (global.get $tokens) (i32.shl (local.get $t) (i32.const 4))))
+  (func $kind (param $t i32) (result i32)
+    (i32.load (call $tok (local.get $t))))
+  ;; Byte length of a string, member or element count of a container
+  (func $len (param $t i32) (result i32)
+    (i32.load offset=4 (call $tok (local.get $t))))
+  ;; Address of a string's bytes, or the end index of a container
+  (func $ptr (param $t i32) (result i32)
+    (i32.load offset=8 (call $tok (local.get $t))))
+  (func $num (param $t i32) (result f64)
+    (f64.load offset=8 (call $tok (local.get $t))))
+  ;; The token after $t and everything inside it
+  (func $next (param $t i32) (result i32)
+    (if (result i32) (i32.ge_u (call $kind (local.get $t)) (i32.const 5))
+      (then (call $ptr (local.get $t)))
+      (else (i32.add (local.get $t) (i32.const 1)))))
+  ;; Record an error at token $t with schema path $sp, counting past capacity
+  (func $err (param $t i32) (param $sp i32)
+    (local $at i32)
+    (if (i32.lt_u (global.get $count) (global.get $cap))
+      (then
+        (local.set $at
+          (i32.add (global.get $errors) (i32.shl (global.get $count) (i32.const 3))))
+        (i32.store (local.get $at) (local.get $t))
+        (i32.store offset=4 (local.get $at) (local.get $sp))))
+    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+  ;; 1 when token $t is the string of the $n bytes at $p
+  (func $eq (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $q i32)
+    (local $i i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (if (i32.ne (call $len (local.get $t)) (local.get $n))
+      (then (return (i32.const 0))))
+    (local.set $q (call $ptr (local.get $t)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+        (if (i32.ne (i32.load8_u (i32.add (local.get $p) (local.get $i)))
+                    (i32.load8_u (i32.add (local.get $q) (local.get $i))))
+          (then (return (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $each)))
+    (i32.const 1))
+  ;; The value token of the member of object $t named by the $n bytes at $p,
+  ;; or -1
+  (func $find (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $left i32)
+    (local $k i32)
+    (local.set $left (call $len (local.get $t)))
+    (local.set $k (i32.add (local.get $t) (i32.const 1)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $left)))
+        (if (call $eq (local.get $k) (local.get $p) (local.get $n))
+          (then (return (i32.add (local.get $k) (i32.const 1)))))
+        (local.set $k (call $next (i32.add (local.get $k) (i32.const 1))))
+        (local.set $left (i32.sub (local.get $left) (i32.const 1)))
+        (br $each)))
+    (i32.const -1))
+  ;; 1 when token $t is a number with no fractional part in [$min, $max]
+  (func $int (param $t i32) (param $min f64) (param $max f64) (result i32)
+    (local $x f64)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 3))
+      (then (return (i32.const 0))))
+    (local.set $x (call $num (local.get $t)))
+    (i32.and
+      (f64.eq (f64.floor (local.get $x)) (local.get $x))
+      (i32.and (f64.ge (local.get $x) (local.get $min))
+               (f64.le (local.get $x) (local.get $max)))))
+"#;
+
+/// RFC 3339 checks, carried only by modules with timestamps. Matches
+/// [`crate::timestamp::is_rfc3339`]: days per month, leap years and a leap
+/// second.
+pub const TIMESTAMP: &str = r#"  ;; The value of the $n ASCII digits at $p, or -1
+  (func $digits (param $p i32) (param $n i32) (result i32)
+    (local $v i32)
+    (local $c i32)
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $c (i32.sub (i32.load8_u (local.get $p)) (i32.const 48)))
+        (if (i32.gt_u (local.get $c) (i32.const 9))
+          (then (return (i32.const -1))))
+        (local.set $v (i32.add (i32.mul (local.get $v) (i32.const 10)) (local.get $c)))
+        (local.set $p (i32.add (local.get $p) (i32.const 1)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $each)))
+    (local.get $v))
+  ;; 1 when token $t is a string holding an RFC 3339 date-time
+  (func $timestamp (param $t i32) (result i32)
+    (local $p i32)
+    (local $n i32)
+    (local $y i32)
+    (local $m i32)
+    (local $days i32)
+    (local $i i32)
+    (local $c i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (local.set $p (call $ptr (local.get $t)))
+    (local.set $n (call $len (local.get $t)))
+    ;; YYYY-MM-DDThh:mm:ssZ is the shortest form
+    (if (i32.lt_u (local.get $n) (i32.const 20))
+      (then (return (i32.const 0))))
+    (if (i32.or
+          (i32.or (i32.ne (i32.load8_u offset=4 (local.get $p)) (i32.const 45))
+                  (i32.ne (i32.load8_u offset=7 (local.get $p)) (i32.const 45)))
+          (i32.or
+            (i32.ne (i32.or (i32.load8_u offset=10 (local.get $p)) (i32.const 32))
+                    (i32.const 116))
+            (i32.or (i32.ne (i32.load8_u offset=13 (local.get $p)) (i32.const 58))
+                    (i32.ne (i32.load8_u offset=16 (local.get $p)) (i32.const 58)))))
+      (then (return (i32.const 0))))
+    (local.set $y (call $digits (local.get $p) (i32.const 4)))
+    (local.set $m (call $digits (i32.add (local.get $p) (i32.const 5)) (i32.const 2)))
+    (if (i32.or (i32.lt_s (local.get $y) (i32.const 0))
+                (i32.gt_u (i32.sub (local.get $m) (i32.const 1)) (i32.const 11)))
+      (then (return (i32.const 0))))
+    ;; 31 days in months 1, 3, 5, 7, 8, 10 and 12
+    (local.set $days
+      (i32.add (i32.const 30)
+        (i32.and (i32.add (local.get $m) (i32.shr_u (local.get $m) (i32.const 3)))
+                 (i32.const 1))))
+    (if (i32.eq (local.get $m) (i32.const 2))
+      (then
+        (local.set $days
+          (i32.add (i32.const 28)
+            (i32.and
+              (i32.eqz (i32.rem_u (local.get $y) (i32.const 4)))
+              (i32.or (i32.ne (i32.rem_u (local.get $y) (i32.const 100)) (i32.const 0))
+                      (i32.eqz (i32.rem_u (local.get $y) (i32.const 400)))))))))
+    ;; Unsigned comparisons also reject -1 from $digits
+    (if (i32.or
+          (i32.ge_u
+            (i32.sub (call $digits (i32.add (local.get $p) (i32.const 8)) (i32.const 2))
+                     (i32.const 1))
+            (local.get $days))
+          (i32.or
+            (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 11)) (i32.const 2))
+                      (i32.const 23))
+            (i32.or
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 14)) (i32.const 2))
+                        (i32.const 59))
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 17)) (i32.const 2))
+                        (i32.const 60)))))
+      (then (return (i32.const 0))))
+    (local.set $i (i32.const 19))
+    (if (i32.eq (i32.load8_u offset=19 (local.get $p)) (i32.const 46))
+      (then
+        (local.set $i (i32.const 20))
+        (block $done
+          (loop $each
+            (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+            (local.set $c
+              (i32.sub (i32.load8_u (i32.add (local.get $p) (local.get $i))) (i32.const 48)))
+            (br_if $done (i32.gt_u (local.get $c) (i32.const 9)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $each)))
+        (if (i32.eq (local.get $i) (i32.const 20))
+          (then (return (i32.const 0))))))
+    (local.set $p (i32.add (local.get $p) (local.get $i)))
+    (local.set $n (i32.sub (local.get $n) (local.get $i)))
+    (if (i32.eq (local.get $n) (i32.const 1))
+      (then (return (i32.eq (i32.or (i32.load8_u (local.get $p)) (i32.const 32))
+                            (i32.const 122)))))
+    (if (i32.ne (local.get $n) (i32.const 6))
+      (then (return (i32.const 0))))
+    (local.set $c (i32.load8_u (local.get $p)))
+    (i32.and
+      (i32.and
+        (i32.or (i32.eq (local.get $c) (i32.const 43)) (i32.eq (local.get $c) (i32.const 45)))
+        (i32.eq (i32.load8_u offset=3 (local.get $p)) (i32.const 58)))
+      (i32.and
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 1)) (i32.const 2))
+                  (i32.const 23))
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 4)) (i32.const 2))
+                  (i32.const 59)))))
+"#;
@@ -0,0 +1,145 @@
+/// Host side of the token buffer for Rust callers: lay out a parsed value
+/// for `validate` and turn its error records back into paths.
+use serde_json::Value;
+
+use crate::compiler::escape_pointer;
+
+const TOKEN: u32 = 16;
+
+/// A value laid out as tokens, for writing into module memory at `base`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tape {
+    /// The address the bytes are laid out for; pass it to `validate`.
+    pub base: u32,
+    /// The tokens, then the string bytes they point to.
+    pub bytes: Vec<u8>,
+    /// The instance path of each token; a key token has its member's.
+    pub paths: Vec<String>,
+}
+
+/// Lay out `value` for memory at `base` (16-byte aligned, at or above the
+/// module's `heap_base`).
+pub fn encode(value: &Value, base: u32) -> Tape {
+    let count = tokens(value);
+    let mut tape = Tape {
+        base,
+        bytes: Vec::with_capacity((count * TOKEN) as usize),
+        paths: Vec::with_capacity(count as usize),
+    };
+    let mut strings = Vec::new();
+    let strings_at = base + count * TOKEN;
+    write(value, "", &mut tape, &mut strings, strings_at);
+    tape.bytes.extend(strings);
+    tape
+}
+
+/// `(instancePath, schemaPath)` of each error record `validate` wrote to
+/// `errors` in `memory`; `count` is its result and `table` the module's
+/// `schema_paths`.
+pub fn errors(
+    tape: &Tape,
+    memory: &[u8],
+    errors: u32,
+    count: u32,
+    table: u32,
+) -> Vec<(String, String)> {
+    let word = |at: u32| {
+        let at = at as usize;
+        u32::from_le_bytes(memory[at..at + 4].try_into().expect("4 bytes"))
+    };
+    (0..count)
+        .map(|i| {
+            let record = errors + i * 8;
+            let (token, id) = (word(record), word(record + 4));
+            let (ptr, len) = (word(table + id * 8), word(table + id * 8 + 4));
+            let sp = &memory[ptr as usize..(ptr + len) as usize];
+            (
+                tape.paths[token as usize].clone(),
+                String::from_utf8_lossy(sp).into_owned(),
+            )
+        })
+        .collect()
+}
+
+fn tokens(value: &Value) -> u32 {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(tokens).sum::<u32>(),
+        Value::Object(members) => 1 + members.values().map(|v| 1 + tokens(v)).sum::<u32>(),
+        _ => 1,
+    }
+}
+
+fn token(tape: &mut Tape, path: &str, kind: u32, len: u32, payload: [u8; 8]) -> usize {
+    let at = tape.bytes.len();
+    tape.bytes.extend(kind.to_le_bytes());
+    tape.bytes.extend(len.to_le_bytes());
+    tape.bytes.extend(payload);
+    tape.paths.push(path.to_string());
+    at
+}
+
+fn string(tape: &mut Tape, path: &str, s: &str, strings: &mut Vec<u8>, strings_at: u32) {
+    let ptr = strings_at + strings.len() as u32;
+    strings.extend(s.as_bytes());
+    let mut payload = [0; 8];
+    payload[..4].copy_from_slice(&ptr.to_le_bytes());
+    token(tape, path, 4, s.len() as u32, payload);
+}
+
+fn write(value: &Value, path: &str, tape: &mut Tape, strings: &mut Vec<u8>, strings_at: u32) {
+    match value {
+        Value::Null => {
+            token(tape, path, 0, 0, [0; 8]);
+        }
+        Value::Bool(b) => {
+            token(tape, path, 1 + u32::from(*b), 0, [0; 8]);
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            token(tape, path, 3, 0, n.to_le_bytes());
+        }
+        Value::String(s) => string(tape, path, s, strings, strings_at),
+        Value::Array(items) => {
+            let at = token(tape, path, 5, items.len() as u32, [0; 8]);
+            for (i, item) in items.iter().enumerate() {
+                write(item, &format!("{path}/{i}"), tape, strings, strings_at);
+            }
+            end(tape, at);
+        }
+        Value::Object(members) => {
+            let at = token(tape, path, 6, members.len() as u32, [0; 8]);
+            for (key, member) in members {
+                let member_path = format!("{path}/{}", escape_pointer(key));
+                string(tape, &member_path, key, strings, strings_at);
+                write(member, &member_path, tape, strings, strings_at);
+            }
+            end(tape, at);
+        }
+    }
+}
+
+/// Point the container token at `at` past its last descendant.
+fn end(tape: &mut Tape, at: usize) {
+    let next = tape.paths.len() as u32;
+    tape.bytes[at + 8..at + 12].copy_from_slice(&next.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_encode() {
+        let tape = encode(&json!({"a": [true, "xy"], "b": null}), 64);
+        assert_eq!(tape.paths, ["", "/a", "/a", "/a/0", "/a/1", "/b", "/b"]);
+        let word = |at: usize| u32::from_le_bytes(tape.bytes[at..at + 4].try_into().unwrap());
+        // The object: 2 members, ending after the last token
+        assert_eq!((word(0), word(4), word(8)), (6, 2, 7));
+        // The array ends at the "b" key
+        assert_eq!((word(32), word(36), word(40)), (5, 2, 5));
+        // "xy" is stored after the 7 tokens and the keys before it
+        assert_eq!((word(64), word(68), word(72)), (4, 2, 64 + 7 * 16 + 1));
+        assert_eq!(&tape.bytes[7 * 16..], b"axyb");
+    }
+}
@@ -0,0 +1,92 @@
+/// Indentation-aware string builder for emitting WebAssembly text.
+pub struct CodeWriter {
+    buf: String,
+    depth: usize,
+}
+
+impl Default for CodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeWriter {
+    pub fn new() -> Self {
+        Self {
+            buf: String::new(),
+            depth: 0,
+        }
+    }
+
+    /// A writer whose lines start `depth` levels in.
+    pub fn with_depth(depth: usize) -> Self {
+        Self {
+            buf: String::new(),
+            depth,
+        }
+    }
+
+    /// Write a line at the current indentation level.
+    pub fn line(&mut self, text: &str) {
+        for _ in 0..self.depth {
+            self.buf.push_str("  ");
+        }
+        self.buf.push_str(text);
+        self.buf.push('\n');
+    }
+
+    /// Open a block: write `text` (`block $b`, `loop $l`, `if`) and indent.
+    pub fn open(&mut self, text: &str) {
+        self.line(text);
+        self.depth += 1;
+    }
+
+    /// Close a block with `end`.
+    pub fn close(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line("end");
+    }
+
+    /// `else` between the arms of an `if`.
+    pub fn close_open(&mut self, text: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(text);
+        self.depth += 1;
+    }
+
+    /// Consume and return the built string.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// `bytes` as a WAT string literal, quotes included.
+pub fn wat_string(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('"');
+    for &b in bytes {
+        match b {
+            b'"' | b'\\' => out.push_str(&format!("\\{}", b as char)),
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\{b:02x}")),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `s` made safe for a `;;` comment, which runs to the end of the line.
+pub fn comment(s: &str) -> String {
+    format!("{s:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wat_string() {
+        assert_eq!(wat_string(b"a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(wat_string("é\n".as_bytes()), r#""\c3\a9\0a""#);
+    }
+}
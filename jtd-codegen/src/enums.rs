@@ -0,0 +1,185 @@
+/// Named value lists for enum forms, used by the `enum_constants` option.
+/// Each enum is named after the definition or property that holds it
+/// (property `status` -> `STATUS_VALUES`); when two enums would share a name
+/// but differ in values, both fall back to their full path.
+use std::collections::BTreeMap;
+
+use crate::ast::Node;
+
+/// One exported constant: a SCREAMING_SNAKE name and the allowed values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumConstant {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Collect the enum constants for a module. `roots` pairs each entry point
+/// with a name; an empty name means the single unnamed root.
+pub fn enum_constants(
+    roots: &[(&str, &Node)],
+    definitions: &BTreeMap<String, Node>,
+) -> Vec<EnumConstant> {
+    let mut found: Vec<(Vec<String>, Vec<String>)> = Vec::new();
+    for (name, node) in definitions {
+        collect(node, &mut vec![name.clone()], &mut found);
+    }
+    for (name, node) in roots {
+        let mut path = if name.is_empty() {
+            vec![]
+        } else {
+            vec![name.to_string()]
+        };
+        collect(node, &mut path, &mut found);
+    }
+
+    let short = |path: &[String]| const_name(path.last().map_or("root", String::as_str));
+    let mut out: Vec<EnumConstant> = Vec::new();
+    for (path, values) in &found {
+        let clash = found
+            .iter()
+            .any(|(p, v)| short(p) == short(path) && v != values);
+        let name = if clash {
+            const_name(&path.join("_"))
+        } else {
+            short(path)
+        };
+        if !out.iter().any(|c| c.name == name) {
+            out.push(EnumConstant {
+                name,
+                values: values.clone(),
+            });
+        }
+    }
+    out
+}
+
+fn collect(node: &Node, path: &mut Vec<String>, found: &mut Vec<(Vec<String>, Vec<String>)>) {
+    match node {
+        Node::Enum { values } => found.push((path.clone(), values.clone())),
+        Node::Nullable { inner } => collect(inner, path, found),
+        Node::Elements { schema } | Node::Values { schema } => collect(schema, path, found),
+        Node::Properties {
+            required, optional, ..
+        } => {
+            for (key, child) in required.iter().chain(optional) {
+                path.push(key.clone());
+                collect(child, path, found);
+                path.pop();
+            }
+        }
+        Node::Discriminator { mapping, .. } => {
+            for variant in mapping.values() {
+                collect(variant, path, found);
+            }
+        }
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } => {}
+    }
+}
+
+/// `orderStatus` / `order-status` -> `ORDER_STATUS_VALUES`.
+fn const_name(raw: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in raw.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    let trimmed = out.trim_end_matches('_');
+    let base = if trimmed.is_empty() { "ENUM" } else { trimmed };
+    if base.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{base}_VALUES")
+    } else {
+        format!("{base}_VALUES")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn constants(schema: serde_json::Value) -> Vec<(String, Vec<String>)> {
+        let c = compiler::compile(&schema).unwrap();
+        enum_constants(&[("", &c.root)], &c.definitions)
+            .into_iter()
+            .map(|e| (e.name, e.values))
+            .collect()
+    }
+
+    #[test]
+    fn test_names_from_definitions_and_properties() {
+        let got = constants(json!({
+            "definitions": {"orderStatus": {"enum": ["OPEN", "CLOSED"]}},
+            "properties": {
+                "status": {"ref": "orderStatus"},
+                "tags": {"elements": {"enum": ["a", "b"]}},
+                "level": {"enum": ["LOW"], "nullable": true}
+            }
+        }));
+        assert_eq!(
+            got,
+            vec![
+                (
+                    "ORDER_STATUS_VALUES".into(),
+                    vec!["OPEN".into(), "CLOSED".into()]
+                ),
+                ("LEVEL_VALUES".into(), vec!["LOW".into()]),
+                ("TAGS_VALUES".into(), vec!["a".into(), "b".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_enum() {
+        assert_eq!(
+            constants(json!({"enum": ["x"]})),
+            vec![("ROOT_VALUES".into(), vec!["x".into()])]
+        );
+    }
+
+    #[test]
+    fn test_clashing_names_use_full_path() {
+        let got = constants(json!({
+            "properties": {
+                "a": {"properties": {"kind": {"enum": ["x"]}}},
+                "b": {"properties": {"kind": {"enum": ["y"]}}},
+                "c": {"properties": {"kind": {"enum": ["x"]}}}
+            }
+        }));
+        let names: Vec<&str> = got.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["A_KIND_VALUES", "B_KIND_VALUES", "C_KIND_VALUES"]
+        );
+    }
+
+    #[test]
+    fn test_identical_enums_share_one_constant() {
+        let got = constants(json!({
+            "properties": {
+                "a": {"properties": {"kind": {"enum": ["x"]}}},
+                "b": {"properties": {"kind": {"enum": ["x"]}}}
+            }
+        }));
+        assert_eq!(got, vec![("KIND_VALUES".into(), vec!["x".into()])]);
+    }
+
+    #[test]
+    fn test_const_name() {
+        assert_eq!(const_name("order-item"), "ORDER_ITEM_VALUES");
+        assert_eq!(const_name("HTTPCode"), "HTTPCODE_VALUES");
+        assert_eq!(const_name("2fa"), "_2FA_VALUES");
+        assert_eq!(const_name("$"), "ENUM_VALUES");
+    }
+}
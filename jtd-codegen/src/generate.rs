@@ -0,0 +1,266 @@
+/// One-call facade over parse + compile + emit, for build scripts that just
+/// want source text for a target without touching the module layout.
+use crate::ast::CompiledSchema;
+use crate::compiler::{self, CompileError};
+use crate::options::EmitOptions;
+use crate::source_map::{self, SourceMap};
+
+/// Code generation targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A BigQuery table schema; see [`crate::bigquery`].
+    BigQuery,
+    /// A self-contained C99 file with its own JSON parser; see
+    /// [`crate::emit_c`].
+    C,
+    /// A Dart library over `jsonDecode` output; see [`crate::emit_dart`].
+    Dart,
+    /// GraphQL SDL types rather than a validator; see
+    /// [`crate::graphql`].
+    GraphQl,
+    /// A dependency-free JavaScript module, ESM unless the runtime or
+    /// `js_module_format` says otherwise; see [`crate::emit_js`].
+    JavaScript,
+    /// A JSON Forms data and UI schema rather than a validator; see
+    /// [`crate::jsonforms`].
+    JsonForms,
+    /// Kotlin over kotlinx.serialization; see [`crate::emit_kt`].
+    Kotlin,
+    /// A Lua module for the chosen dialect and JSON library; see
+    /// [`crate::emit_lua`].
+    Lua,
+    /// A MongoDB `$jsonSchema` validator; see [`crate::mongodb`].
+    MongoDb,
+    /// A proto3 IDL file rather than a validator; see
+    /// [`crate::protobuf`].
+    Protobuf,
+    /// A Python module over `json.loads` output; see [`crate::emit_py`].
+    Python,
+    /// A Rust module over `serde_json::Value` or another backend; see
+    /// [`crate::emit_rs`].
+    Rust,
+    /// PostgreSQL functions validating `jsonb`; see [`crate::emit_sql`].
+    Sql,
+    /// TypeBox schemas and their static types; see
+    /// [`crate::emit_typebox`].
+    TypeBox,
+    /// A TypeScript module with the data's types; see [`crate::emit_ts`].
+    TypeScript,
+    /// A WebAssembly text module; see [`crate::emit_wasm`].
+    Wat,
+}
+
+impl Target {
+    /// Every target, in the order of their CLI names.
+    pub const ALL: [Target; 16] = [
+        Target::BigQuery,
+        Target::C,
+        Target::Dart,
+        Target::GraphQl,
+        Target::JavaScript,
+        Target::JsonForms,
+        Target::Kotlin,
+        Target::Lua,
+        Target::MongoDb,
+        Target::Protobuf,
+        Target::Python,
+        Target::Rust,
+        Target::Sql,
+        Target::TypeBox,
+        Target::TypeScript,
+        Target::Wat,
+    ];
+
+    /// Parse a CLI target name (`bigquery`, `c`, `dart`, `graphql`, `js`,
+    /// `jsonforms`, `kotlin`/`kt`, `lua`, `mongodb`, `proto`/`protobuf`,
+    /// `python`/`py`, `rust`/`rs`, `sql`/`postgres`, `typebox`,
+    /// `ts`/`typescript`, `wat`).
+    pub fn parse(s: &str) -> Option<Target> {
+        match s {
+            "bigquery" => Some(Target::BigQuery),
+            "c" => Some(Target::C),
+            "dart" => Some(Target::Dart),
+            "graphql" => Some(Target::GraphQl),
+            "js" | "javascript" => Some(Target::JavaScript),
+            "jsonforms" => Some(Target::JsonForms),
+            "kotlin" | "kt" => Some(Target::Kotlin),
+            "lua" => Some(Target::Lua),
+            "mongodb" => Some(Target::MongoDb),
+            "proto" | "protobuf" => Some(Target::Protobuf),
+            "python" | "py" => Some(Target::Python),
+            "rust" | "rs" => Some(Target::Rust),
+            "sql" | "postgres" => Some(Target::Sql),
+            "typebox" => Some(Target::TypeBox),
+            "ts" | "typescript" => Some(Target::TypeScript),
+            "wat" => Some(Target::Wat),
+            _ => None,
+        }
+    }
+
+    /// The canonical CLI name, as recorded in banners and manifests.
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::BigQuery => "bigquery",
+            Target::C => "c",
+            Target::Dart => "dart",
+            Target::GraphQl => "graphql",
+            Target::JavaScript => "js",
+            Target::JsonForms => "jsonforms",
+            Target::Kotlin => "kotlin",
+            Target::Lua => "lua",
+            Target::MongoDb => "mongodb",
+            Target::Protobuf => "proto",
+            Target::Python => "python",
+            Target::Rust => "rust",
+            Target::Sql => "sql",
+            Target::TypeBox => "typebox",
+            Target::TypeScript => "ts",
+            Target::Wat => "wat",
+        }
+    }
+
+    /// The usual file extension of the output, without the dot. JavaScript
+    /// is `mjs`, the default module format's; [`crate::build`] picks `cjs` or
+    /// `js` for the others.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Target::BigQuery | Target::JsonForms | Target::MongoDb => "json",
+            Target::C => "c",
+            Target::Dart => "dart",
+            Target::GraphQl => "graphql",
+            Target::JavaScript => "mjs",
+            Target::Kotlin => "kt",
+            Target::Lua => "lua",
+            Target::Protobuf => "proto",
+            Target::Python => "py",
+            Target::Rust => "rs",
+            Target::Sql => "sql",
+            Target::TypeBox | Target::TypeScript => "ts",
+            Target::Wat => "wat",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GenerateError {
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid JTD schema: {0}")]
+    Compile(#[from] CompileError),
+}
+
+/// Parse `schema_json`, compile it, and emit a validator for `target`.
+pub fn generate(
+    schema_json: &str,
+    target: Target,
+    opts: &EmitOptions,
+) -> Result<String, GenerateError> {
+    let schema: serde_json::Value = serde_json::from_str(schema_json)?;
+    let compiled = compiler::compile(&schema)?;
+    Ok(emit(&compiled, target, opts))
+}
+
+/// Emit an already compiled schema for `target`.
+pub fn emit(compiled: &CompiledSchema, target: Target, opts: &EmitOptions) -> String {
+    match target {
+        Target::BigQuery => crate::bigquery::emit(compiled),
+        Target::C => crate::emit_c::emit_with_options(compiled, opts),
+        Target::Dart => crate::emit_dart::emit_with_options(compiled, opts),
+        Target::GraphQl => crate::graphql::to_graphql(compiled, opts),
+        Target::JavaScript => crate::emit_js::emit_with_options(compiled, opts),
+        Target::JsonForms => crate::jsonforms::emit(compiled),
+        Target::Kotlin => crate::emit_kt::emit_with_options(compiled, opts),
+        Target::Lua => crate::emit_lua::emit_with_options(compiled, opts),
+        Target::MongoDb => crate::mongodb::emit(compiled, opts),
+        Target::Protobuf => crate::protobuf::to_proto(compiled, opts),
+        Target::Python => crate::emit_py::emit_with_options(compiled, opts),
+        Target::Rust => crate::emit_rs::emit_with_options(compiled, opts),
+        Target::Sql => crate::emit_sql::emit_with_options(compiled, opts),
+        Target::TypeBox => crate::emit_typebox::emit_with_options(compiled, opts),
+        Target::TypeScript => crate::emit_ts::emit_with_options(compiled, opts),
+        Target::Wat => crate::emit_wasm::emit_with_options(compiled, opts),
+    }
+}
+
+/// [`emit`], with the [`SourceMap`] of the output. The code is the same as
+/// `emit` returns. Targets whose output is JSON, and minified JavaScript,
+/// have no per-node lines, so their maps are empty.
+pub fn emit_with_source_map(
+    compiled: &CompiledSchema,
+    target: Target,
+    opts: &EmitOptions,
+) -> (String, SourceMap) {
+    let opts = EmitOptions {
+        source_map: true,
+        ..opts.clone()
+    };
+    source_map::extract(compiled, &emit(compiled, target, &opts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_parse() {
+        assert_eq!(Target::parse("js"), Some(Target::JavaScript));
+        assert_eq!(Target::parse("py"), Some(Target::Python));
+        assert_eq!(Target::parse("rs"), Some(Target::Rust));
+        assert_eq!(Target::parse("jsonforms"), Some(Target::JsonForms));
+        assert_eq!(Target::parse("mongodb"), Some(Target::MongoDb));
+        assert_eq!(Target::parse("bigquery"), Some(Target::BigQuery));
+        assert_eq!(Target::parse("go"), None);
+        for target in Target::ALL {
+            assert_eq!(Target::parse(target.name()), Some(target));
+        }
+    }
+
+    #[test]
+    fn test_generate_each_target() {
+        let schema = r#"{"properties": {"name": {"type": "string"}}}"#;
+        let opts = EmitOptions::default();
+        let js = generate(schema, Target::JavaScript, &opts).unwrap();
+        assert!(js.contains("export function validate(instance)"));
+        let lua = generate(schema, Target::Lua, &opts).unwrap();
+        assert!(lua.contains("function M.validate(instance)"));
+        let py = generate(schema, Target::Python, &opts).unwrap();
+        assert!(py.contains("def validate("));
+        let rs = generate(schema, Target::Rust, &opts).unwrap();
+        assert!(rs.contains("pub fn validate(instance: &Value)"));
+        let ts = generate(schema, Target::TypeScript, &opts).unwrap();
+        assert!(ts.contains("export function validate(instance: unknown): ValidationError[]"));
+        let kt = generate(schema, Target::Kotlin, &opts).unwrap();
+        assert!(kt.contains("fun validate(instance: JsonElement): List<ValidationError>"));
+        let dart = generate(schema, Target::Dart, &opts).unwrap();
+        assert!(dart.contains("List<ValidationError> validate(Object? instance) {"));
+        let sql = generate(schema, Target::Sql, &opts).unwrap();
+        assert!(sql.contains("CREATE OR REPLACE FUNCTION jtd_validate(instance jsonb)"));
+        let graphql = generate(schema, Target::GraphQl, &opts).unwrap();
+        assert!(graphql.contains("type Root {\n  name: String!\n}"));
+        let proto = generate(schema, Target::Protobuf, &opts).unwrap();
+        assert!(proto.contains("message Root {\n  string name = 1;\n}"));
+        let typebox = generate(schema, Target::TypeBox, &opts).unwrap();
+        assert!(typebox.contains("export const Root = Type.Object({\n  name: Type.String(),\n}"));
+        let c = generate(schema, Target::C, &opts).unwrap();
+        assert!(c.contains("size_t jtd_validate(const jtd_node *nodes"));
+        let wat = generate(schema, Target::Wat, &opts).unwrap();
+        assert!(wat.contains("(func (export \"validate\")"));
+    }
+
+    #[test]
+    fn test_generate_invalid_json() {
+        let err = generate("{", Target::Rust, &EmitOptions::default()).unwrap_err();
+        assert!(matches!(err, GenerateError::Json(_)));
+        assert!(err.to_string().starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_generate_invalid_schema() {
+        let err =
+            generate(r#"{"type": "nope"}"#, Target::Rust, &EmitOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            GenerateError::Compile(CompileError::UnknownType(_))
+        ));
+    }
+}
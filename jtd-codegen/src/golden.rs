@@ -0,0 +1,278 @@
+/// Golden snapshots: emitter output checked in beside the schemas it came
+/// from, so every change to generated code is a reviewable diff and a
+/// change nobody meant to make fails the build.
+///
+/// Each check emits twice and requires the same bytes, since output that
+/// varies from run to run (hash-map order, say) would make vendored files
+/// and `jtd-codegen verify` flap. The output is then compared with its
+/// snapshot, and a mismatch reports the unified diff from [`diff`].
+/// With `JTD_UPDATE_GOLDEN=1` ([`UPDATE_ENV`]) missing or stale snapshots
+/// are written instead.
+///
+/// `tests/golden_snapshots.rs` runs [`Golden::run`] over
+/// `tests/golden/schemas` for every [`Target`]. An emitter built on this
+/// crate passes its own emit function:
+///
+/// ```no_run
+/// use jtd_codegen::golden::Golden;
+///
+/// let golden = Golden::new("tests/golden/schemas", "tests/golden");
+/// let failures = golden.run("mine", "txt", |schema| format!("{:?}", schema.root));
+/// assert!(failures.is_empty(), "{}", Golden::report(&failures));
+/// ```
+use std::path::{Path, PathBuf};
+
+use crate::ast::CompiledSchema;
+use crate::compiler;
+use crate::generate::Target;
+use crate::options::EmitOptions;
+use crate::verify::diff;
+
+/// The environment variable that makes [`Golden::new`] write snapshots.
+pub const UPDATE_ENV: &str = "JTD_UPDATE_GOLDEN";
+
+#[derive(Debug, thiserror::Error)]
+pub enum GoldenError {
+    #[error("cannot read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{}: {message}", path.display())]
+    Schema { path: PathBuf, message: String },
+    #[error("cannot write {}: {source}", path.display())]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{} differs between two runs:\n{diff}", path.display())]
+    Nondeterministic { path: PathBuf, diff: String },
+    #[error("{} is missing; rerun with {UPDATE_ENV}=1 to write it", path.display())]
+    Missing { path: PathBuf },
+    #[error("{} is out of date; rerun with {UPDATE_ENV}=1 if the change is intended:\n{diff}", path.display())]
+    Changed { path: PathBuf, diff: String },
+}
+
+/// A directory of fixture schemas and the snapshots generated from them.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    /// Where the `*.json` schemas are.
+    pub schemas: PathBuf,
+    /// Where the snapshots are, one subdirectory per emitter.
+    pub snapshots: PathBuf,
+    /// Write snapshots rather than compare with them.
+    pub update: bool,
+}
+
+impl Golden {
+    /// Snapshots of the schemas in `schemas`, kept under `snapshots`,
+    /// updating them when [`UPDATE_ENV`] is set to anything but `0`.
+    pub fn new(schemas: impl Into<PathBuf>, snapshots: impl Into<PathBuf>) -> Golden {
+        Golden {
+            schemas: schemas.into(),
+            snapshots: snapshots.into(),
+            update: std::env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0"),
+        }
+    }
+
+    /// Check `emit`'s output for every schema against
+    /// `snapshots/<name>/<schema stem>.<extension>`, returning every
+    /// failure rather than stopping at the first.
+    pub fn run(
+        &self,
+        name: &str,
+        extension: &str,
+        emit: impl Fn(&CompiledSchema) -> String,
+    ) -> Vec<GoldenError> {
+        let schemas = match self.schema_files() {
+            Ok(schemas) => schemas,
+            Err(e) => return vec![e],
+        };
+        let mut failures = Vec::new();
+        for path in schemas {
+            let compiled = match load(&path) {
+                Ok(compiled) => compiled,
+                Err(e) => {
+                    failures.push(e);
+                    continue;
+                }
+            };
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let snapshot = self
+                .snapshots
+                .join(name)
+                .join(format!("{stem}.{extension}"));
+            if let Err(e) = check(&snapshot, || emit(&compiled), self.update) {
+                failures.push(e);
+            }
+        }
+        failures
+    }
+
+    /// [`Golden::run`] for each of `targets`, with `opts`, under the
+    /// targets' CLI names and file extensions.
+    pub fn run_targets(&self, targets: &[Target], opts: &EmitOptions) -> Vec<GoldenError> {
+        targets
+            .iter()
+            .flat_map(|&target| {
+                self.run(target.name(), target.extension(), |schema| {
+                    crate::generate::emit(schema, target, opts)
+                })
+            })
+            .collect()
+    }
+
+    /// The failures, one paragraph each, for an assertion message.
+    pub fn report(failures: &[GoldenError]) -> String {
+        let lines: Vec<String> = failures.iter().map(|e| e.to_string()).collect();
+        format!(
+            "{} golden snapshot(s) failed:\n\n{}",
+            failures.len(),
+            lines.join("\n")
+        )
+    }
+
+    /// The `*.json` files in `schemas`, sorted by name.
+    fn schema_files(&self) -> Result<Vec<PathBuf>, GoldenError> {
+        let read_error = |source| GoldenError::Read {
+            path: self.schemas.clone(),
+            source,
+        };
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.schemas).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Emit twice, require the same output, then compare it with the snapshot
+/// at `path`, or with `update` write it there when it differs.
+pub fn check(path: &Path, emit: impl Fn() -> String, update: bool) -> Result<(), GoldenError> {
+    let output = emit();
+    let again = emit();
+    if let Some(diff) = diff("first run", "second run", &output, &again) {
+        return Err(GoldenError::Nondeterministic {
+            path: path.to_path_buf(),
+            diff,
+        });
+    }
+
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(source) => {
+            return Err(GoldenError::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+    if expected.as_deref() == Some(output.as_str()) {
+        return Ok(());
+    }
+    if update {
+        let write_error = |source| GoldenError::Write {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(write_error)?;
+        }
+        return std::fs::write(path, output).map_err(write_error);
+    }
+    match expected {
+        None => Err(GoldenError::Missing {
+            path: path.to_path_buf(),
+        }),
+        Some(expected) => Err(GoldenError::Changed {
+            path: path.to_path_buf(),
+            diff: diff("snapshot", "output", &expected, &output).unwrap_or_default(),
+        }),
+    }
+}
+
+/// Read and compile the schema at `path`.
+fn load(path: &Path) -> Result<CompiledSchema, GoldenError> {
+    let text = std::fs::read_to_string(path).map_err(|source| GoldenError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let schema_error = |message: String| GoldenError::Schema {
+        path: path.to_path_buf(),
+        message,
+    };
+    let schema = serde_json::from_str(&text).map_err(|e| schema_error(e.to_string()))?;
+    compiler::compile(&schema).map_err(|e| schema_error(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_check_writes_then_compares() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("js").join("user.mjs");
+        let err = check(&path, || "a\n".into(), false).unwrap_err();
+        assert!(matches!(err, GoldenError::Missing { .. }));
+        assert!(err.to_string().contains("JTD_UPDATE_GOLDEN=1"));
+
+        check(&path, || "a\n".into(), true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\n");
+        check(&path, || "a\n".into(), false).unwrap();
+
+        let err = check(&path, || "b\n".into(), false).unwrap_err();
+        let GoldenError::Changed { diff, .. } = &err else {
+            panic!("{err}");
+        };
+        assert!(diff.contains("-a\n+b\n"));
+        // A failed comparison leaves the snapshot alone
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_check_rejects_nondeterministic_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("x.txt");
+        let runs = Cell::new(0);
+        let emit = || {
+            runs.set(runs.get() + 1);
+            format!("run {}\n", runs.get())
+        };
+        let err = check(&path, emit, true).unwrap_err();
+        assert!(matches!(err, GoldenError::Nondeterministic { .. }));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_run_reports_every_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let schemas = dir.path().join("schemas");
+        std::fs::create_dir(&schemas).unwrap();
+        std::fs::write(schemas.join("a.json"), r#"{"type": "string"}"#).unwrap();
+        std::fs::write(schemas.join("b.json"), r#"{"type": "nope"}"#).unwrap();
+        std::fs::write(schemas.join("notes.txt"), "not a schema").unwrap();
+        let mut golden = Golden::new(&schemas, dir.path());
+        golden.update = true;
+
+        let failures = golden.run_targets(&[Target::JavaScript], &EmitOptions::default());
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(failures[0], GoldenError::Schema { .. }));
+        assert!(Golden::report(&failures).starts_with("1 golden snapshot(s) failed:"));
+        assert!(dir.path().join("js").join("a.mjs").exists());
+
+        golden.update = false;
+        std::fs::remove_file(schemas.join("b.json")).unwrap();
+        assert!(golden
+            .run_targets(&[Target::JavaScript], &EmitOptions::default())
+            .is_empty());
+        let failures = golden.run_targets(&[Target::Python], &EmitOptions::default());
+        assert!(matches!(failures[..], [GoldenError::Missing { .. }]));
+    }
+}
@@ -0,0 +1,431 @@
+/// GraphQL SDL from JTD schemas, so an API's types can be derived from the
+/// same schemas its payloads are validated against.
+///
+/// - Properties forms become object types and discriminators unions of
+///   one object type per variant, each with the tag as a `String!` field.
+///   Enums whose values are all GraphQL names become enums; others are
+///   `String`s.
+/// - Definitions of those forms become types named after the definition
+///   (`order_item` becomes `OrderItem`), so refs (recursive ones too) name
+///   them; other definitions are inlined. Anonymous types are named from
+///   their parent and key, and a root of those forms is `Root`.
+/// - Required properties are non-null unless nullable. Integers up to
+///   `int32` are `Int`; `uint32`, beyond GraphQL's 32-bit `Int`, is
+///   `Float`. Timestamps use a `DateTime` scalar, and what GraphQL cannot
+///   type (the empty form, values, objects without properties) a `JSON`
+///   scalar.
+/// - Property names that are not GraphQL names have other characters
+///   replaced by `_`. `metadata.description` becomes the description.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::options::EmitOptions;
+use crate::source_map;
+
+/// The SDL for `schema`: scalars first, then one type per definition of
+/// a named form, then the root's. Of the options only the banner applies.
+pub fn to_graphql(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut sdl = Sdl {
+        schema,
+        defs: BTreeMap::new(),
+        used: [
+            "Boolean", "DateTime", "Float", "ID", "Int", "JSON", "String",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect(),
+        scalars: BTreeSet::new(),
+        types: Vec::new(),
+        source_map: opts.source_map,
+    };
+    for (name, node) in &schema.definitions {
+        if is_named(strip_nullable(node)) {
+            let type_name = sdl.name(name);
+            sdl.defs.insert(name, type_name);
+        }
+    }
+    for (name, node) in &schema.definitions {
+        if let Some(type_name) = sdl.defs.get(name.as_str()).cloned() {
+            sdl.named(
+                &type_name,
+                strip_nullable(node),
+                &format!("/definitions/{}", escape_pointer(name)),
+            );
+        }
+    }
+    if is_named(strip_nullable(&schema.root)) {
+        let type_name = sdl.name("Root");
+        sdl.named(&type_name, strip_nullable(&schema.root), "");
+    }
+
+    let mut blocks = Vec::new();
+    if sdl.scalars.contains("DateTime") {
+        blocks.push("\"\"\"An RFC 3339 date-time string.\"\"\"\nscalar DateTime\n".to_string());
+    }
+    if sdl.scalars.contains("JSON") {
+        blocks.push("\"\"\"Any JSON value.\"\"\"\nscalar JSON\n".to_string());
+    }
+    blocks.extend(sdl.types);
+    let mut out = String::new();
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("graphql") {
+            out.push_str(&format!("# {line}\n"));
+        }
+    }
+    out.push_str(&blocks.join("\n"));
+    out
+}
+
+struct Sdl<'a> {
+    schema: &'a CompiledSchema,
+    /// Type names of the definitions emitted as named types.
+    defs: BTreeMap<&'a str, String>,
+    /// Type names taken so far, built-in scalars included.
+    used: BTreeSet<String>,
+    /// Custom scalars referenced.
+    scalars: BTreeSet<&'static str>,
+    /// Type definitions, parents before the types named after them.
+    types: Vec<String>,
+    /// Wrap the lines of each node in source-map markers.
+    source_map: bool,
+}
+
+impl<'a> Sdl<'a> {
+    /// `code` in source-map markers for `node`, when asked for.
+    fn mark(&self, node: &Node, code: String) -> String {
+        if self.source_map {
+            source_map::wrap(node, &code)
+        } else {
+            code
+        }
+    }
+
+    /// A fresh type name from `hint`.
+    fn name(&mut self, hint: &str) -> String {
+        let mut base = pascal(hint);
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert(0, 'T');
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.used.contains(&name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+        self.used.insert(name.clone());
+        name
+    }
+
+    fn scalar(&mut self, name: &'static str) -> String {
+        self.scalars.insert(name);
+        name.to_string()
+    }
+
+    /// The type `node` at `path` has in a field or list, and whether it
+    /// admits null. Anonymous types are named from `hint`; `inlining`
+    /// holds the definitions being inlined on the way here.
+    fn type_of(
+        &mut self,
+        node: &'a Node,
+        path: &str,
+        hint: &str,
+        inlining: &mut Vec<&'a str>,
+    ) -> (String, bool) {
+        match node {
+            Node::Nullable { inner } => (self.type_of(inner, path, hint, inlining).0, true),
+            Node::Empty => (self.scalar("JSON"), true),
+            Node::Ref { name } => {
+                let target = &self.schema.definitions[name];
+                if let Some(type_name) = self.defs.get(name.as_str()) {
+                    return (type_name.clone(), matches!(target, Node::Nullable { .. }));
+                }
+                if inlining.contains(&name.as_str()) {
+                    return (self.scalar("JSON"), true);
+                }
+                inlining.push(name);
+                let out = self.type_of(
+                    target,
+                    &format!("/definitions/{}", escape_pointer(name)),
+                    name,
+                    inlining,
+                );
+                inlining.pop();
+                out
+            }
+            Node::Type { type_kw } => {
+                let t = match type_kw {
+                    TypeKeyword::Boolean => "Boolean".to_string(),
+                    TypeKeyword::String => "String".to_string(),
+                    TypeKeyword::Timestamp => self.scalar("DateTime"),
+                    TypeKeyword::Float32 | TypeKeyword::Float64 | TypeKeyword::Uint32 => {
+                        "Float".to_string()
+                    }
+                    _ => "Int".to_string(),
+                };
+                (t, false)
+            }
+            Node::Enum { .. } if !is_named(node) => ("String".to_string(), false),
+            Node::Elements { schema: item } => {
+                let item_hint = format!("{hint}Item");
+                let (t, nullable) =
+                    self.type_of(item, &format!("{path}/elements"), &item_hint, inlining);
+                let bang = if nullable { "" } else { "!" };
+                (format!("[{t}{bang}]"), false)
+            }
+            Node::Values { .. } => (self.scalar("JSON"), false),
+            node if !is_named(node) => (self.scalar("JSON"), false),
+            node => {
+                let type_name = self.name(hint);
+                self.named(&type_name, node, path);
+                (type_name, false)
+            }
+        }
+    }
+
+    /// Emit type `name` for the named form `node` at `path`.
+    fn named(&mut self, name: &str, node: &'a Node, path: &str) {
+        let slot = self.types.len();
+        self.types.push(String::new());
+        let mut out = description(self.schema.description(path), "");
+        match node {
+            Node::Enum { values } => {
+                out.push_str(&format!("enum {name} {{\n"));
+                for value in values {
+                    out.push_str(&format!("  {value}\n"));
+                }
+                out.push_str("}\n");
+            }
+            Node::Properties { .. } => {
+                out.push_str(&format!("type {name} {{\n"));
+                out.push_str(&self.fields(name, node, path));
+                out.push_str("}\n");
+            }
+            Node::Discriminator { tag, mapping } => {
+                let mut members = Vec::new();
+                for (value, variant) in mapping {
+                    let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                    let variant_name = self.name(&format!("{name}_{value}"));
+                    let slot = self.types.len();
+                    self.types.push(String::new());
+                    let mut block = description(self.schema.description(&variant_path), "");
+                    block.push_str(&format!("type {variant_name} {{\n"));
+                    block.push_str(&format!("  {}: String!\n", field_name(tag)));
+                    block.push_str(&self.fields(&variant_name, variant, &variant_path));
+                    block.push_str("}\n");
+                    self.types[slot] = self.mark(variant, block);
+                    members.push(variant_name);
+                }
+                out.push_str(&format!("union {name} = {}\n", members.join(" | ")));
+            }
+            _ => {}
+        }
+        self.types[slot] = self.mark(node, out);
+    }
+
+    /// The fields of the Properties form `node` of type `parent`.
+    fn fields(&mut self, parent: &str, node: &'a Node, path: &str) -> String {
+        let Node::Properties {
+            required, optional, ..
+        } = node
+        else {
+            return String::new();
+        };
+        let mut out = String::new();
+        let props = required
+            .iter()
+            .map(|(key, child)| (key, child, true))
+            .chain(optional.iter().map(|(key, child)| (key, child, false)));
+        for (key, child, is_required) in props {
+            let child_path = if is_required {
+                format!("{path}/properties/{}", escape_pointer(key))
+            } else {
+                format!("{path}/optionalProperties/{}", escape_pointer(key))
+            };
+            let hint = format!("{parent}_{key}");
+            let (t, nullable) = self.type_of(child, &child_path, &hint, &mut Vec::new());
+            let bang = if is_required && !nullable { "!" } else { "" };
+            let mut field = description(self.schema.description(&child_path), "  ");
+            field.push_str(&format!("  {}: {t}{bang}\n", field_name(key)));
+            out.push_str(&self.mark(child, field));
+        }
+        out
+    }
+}
+
+fn strip_nullable(node: &Node) -> &Node {
+    match node {
+        Node::Nullable { inner } => inner,
+        node => node,
+    }
+}
+
+/// Forms that become a named type: objects with at least one property
+/// (GraphQL has no empty types), discriminators with at least one variant
+/// and enums GraphQL can spell.
+fn is_named(node: &Node) -> bool {
+    match node {
+        Node::Properties {
+            required, optional, ..
+        } => !required.is_empty() || !optional.is_empty(),
+        Node::Discriminator { mapping, .. } => !mapping.is_empty(),
+        Node::Enum { values } => values
+            .iter()
+            .all(|v| is_name(v) && !matches!(v.as_str(), "true" | "false" | "null")),
+        _ => false,
+    }
+}
+
+/// `/[_A-Za-z][_0-9A-Za-z]*/`, a GraphQL name.
+fn is_name(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `order_item` or `order-item` as `OrderItem`.
+pub(crate) fn pascal(s: &str) -> String {
+    let mut out = String::new();
+    let mut upper = true;
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    out
+}
+
+/// `key` as a GraphQL name.
+fn field_name(key: &str) -> String {
+    let mut out: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !is_name(&out) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// A block string description, indented by `indent`, or nothing. It
+/// gets lines of its own when a quote or line break would run into the
+/// delimiters.
+fn description(desc: Option<&str>, indent: &str) -> String {
+    let Some(desc) = desc else {
+        return String::new();
+    };
+    let desc = desc.replace("\"\"\"", "\\\"\"\"");
+    if desc.contains('\n') || desc.starts_with('"') || desc.ends_with('"') {
+        let body: Vec<String> = desc.lines().map(|line| format!("{indent}{line}")).collect();
+        format!("{indent}\"\"\"\n{}\n{indent}\"\"\"\n", body.join("\n"))
+    } else {
+        format!("{indent}\"\"\"{desc}\"\"\"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_graphql() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree_node": {
+                    "properties": {"value": {"type": "uint32"}},
+                    "optionalProperties": {"children": {"elements": {"ref": "tree_node"}}}
+                },
+                "id": {"type": "string"}
+            },
+            "properties": {
+                "id": {"ref": "id"},
+                "root": {"ref": "tree_node", "metadata": {"description": "The \"\"\"top\"\"\"."}},
+                "status": {"enum": ["NEW", "DONE"], "nullable": true},
+                "size": {"enum": ["x-large"]},
+                "at": {"type": "timestamp"},
+                "extra": {"values": {"type": "int8"}},
+                "event": {
+                    "discriminator": "type",
+                    "mapping": {"user-created": {"properties": {"name": {"type": "string"}}}}
+                }
+            }
+        }))
+        .unwrap();
+        let sdl = to_graphql(&schema, &EmitOptions::default());
+        assert_eq!(
+            sdl,
+            r#""""An RFC 3339 date-time string."""
+scalar DateTime
+
+"""Any JSON value."""
+scalar JSON
+
+type TreeNode {
+  value: Float!
+  children: [TreeNode!]
+}
+
+type Root {
+  at: DateTime!
+  event: RootEvent!
+  extra: JSON!
+  id: String!
+  """The \"""top\"""."""
+  root: TreeNode!
+  size: String!
+  status: RootStatus
+}
+
+union RootEvent = RootEventUserCreated
+
+type RootEventUserCreated {
+  type: String!
+  name: String!
+}
+
+enum RootStatus {
+  NEW
+  DONE
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(pascal("order_item"), "OrderItem");
+        assert_eq!(field_name("first-name"), "first_name");
+        assert_eq!(field_name("2fa"), "_2fa");
+        let schema = compile(&json!({
+            "definitions": {"Root": {"enum": ["A"]}},
+            "properties": {"r": {"ref": "Root"}}
+        }))
+        .unwrap();
+        let sdl = to_graphql(&schema, &EmitOptions::default());
+        assert!(sdl.contains("enum Root {"));
+        assert!(sdl.contains("type Root2 {\n  r: Root!\n}"));
+    }
+
+    #[test]
+    fn test_banner() {
+        let schema = json!({"properties": {"id": {"type": "string"}}});
+        let opts = EmitOptions {
+            banner: Some(crate::options::Banner::new(&schema, Vec::new())),
+            ..EmitOptions::default()
+        };
+        let sdl = to_graphql(&compile(&schema).unwrap(), &opts);
+        let header: Vec<&str> = sdl.lines().take(4).collect();
+        assert!(header[0].starts_with("# Generator: jtd-codegen "));
+        assert!(header[1].starts_with("# Schema: sha256:"));
+        assert_eq!(header[2], "# Options: (none)");
+        assert_eq!(header[3], "type Root {");
+    }
+}
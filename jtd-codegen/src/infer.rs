@@ -0,0 +1,285 @@
+/// Schema inference: a best-effort JTD schema from sample instances, as a
+/// starting point to edit rather than a contract. `jtd-codegen infer
+/// samples.ndjson` writes the schema for every JSON value in the files.
+///
+/// - Objects use the properties form, with the keys present in every
+///   sample required and the others optional. Objects whose keys look like
+///   data (ids, dates, paths) or that have more than
+///   [`InferOptions::values_min_keys`] distinct keys use the values form.
+/// - Integers get the narrowest of `uint8`, `int8`, `uint16`, `int16`,
+///   `uint32` and `int32` holding every sample, other numbers `float64`.
+/// - Strings that are all RFC 3339 date-times are timestamps; strings with
+///   few distinct values, each seen at least twice, become an enum.
+/// - Values seen as `null` make their schema nullable. Values seen with
+///   several JSON types, or only as `null`, get the empty schema.
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::timestamp::is_rfc3339;
+
+/// Integer types by preference, with their bounds.
+const INT_TYPES: [(&str, i64, i64); 6] = [
+    ("uint8", 0, 255),
+    ("int8", -128, 127),
+    ("uint16", 0, 65_535),
+    ("int16", -32_768, 32_767),
+    ("uint32", 0, 4_294_967_295),
+    ("int32", -2_147_483_648, 2_147_483_647),
+];
+
+/// Thresholds for the inference heuristics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferOptions {
+    /// Most distinct values a string may take to become an enum; 0 infers
+    /// no enums.
+    pub enum_max_values: usize,
+    /// Distinct keys past which objects use the values form.
+    pub values_min_keys: usize,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        InferOptions {
+            enum_max_values: 8,
+            values_min_keys: 32,
+        }
+    }
+}
+
+/// The schema for `instances`.
+pub fn infer(instances: &[Value], opts: &InferOptions) -> Value {
+    let mut summary = Summary::default();
+    for instance in instances {
+        summary.add(instance, opts);
+    }
+    summary.schema(opts)
+}
+
+/// What the samples at one place in the instances looked like.
+#[derive(Default)]
+struct Summary {
+    nulls: usize,
+    booleans: usize,
+    /// Bounds of the integers seen.
+    ints: Option<(i64, i64)>,
+    /// Numbers with a fraction or outside `i64`.
+    floats: usize,
+    strings: usize,
+    /// Each distinct string and how often it was seen, until there are too
+    /// many for an enum.
+    string_values: Option<BTreeMap<String, usize>>,
+    non_timestamps: usize,
+    arrays: usize,
+    elements: Option<Box<Summary>>,
+    objects: usize,
+    /// Each key with the number of objects having it and their values.
+    properties: BTreeMap<String, (usize, Summary)>,
+    /// The values of every key, for the values form.
+    values: Option<Box<Summary>>,
+}
+
+impl Summary {
+    fn add(&mut self, value: &Value, opts: &InferOptions) {
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => {
+                    let (lo, hi) = self.ints.unwrap_or((n, n));
+                    self.ints = Some((lo.min(n), hi.max(n)));
+                }
+                None => self.floats += 1,
+            },
+            Value::String(s) => {
+                if self.strings == 0 {
+                    self.string_values = Some(BTreeMap::new());
+                }
+                self.strings += 1;
+                if !is_rfc3339(s) {
+                    self.non_timestamps += 1;
+                }
+                if let Some(seen) = &mut self.string_values {
+                    *seen.entry(s.clone()).or_default() += 1;
+                    if seen.len() > opts.enum_max_values {
+                        self.string_values = None;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                self.arrays += 1;
+                let elements = self.elements.get_or_insert_with(Box::default);
+                for item in items {
+                    elements.add(item, opts);
+                }
+            }
+            Value::Object(obj) => {
+                self.objects += 1;
+                let values = self.values.get_or_insert_with(Box::default);
+                for (key, v) in obj {
+                    let (count, summary) = self.properties.entry(key.clone()).or_default();
+                    *count += 1;
+                    summary.add(v, opts);
+                    values.add(v, opts);
+                }
+            }
+        }
+    }
+
+    fn schema(&self, opts: &InferOptions) -> Value {
+        let kinds = [
+            self.booleans,
+            self.ints.map_or(0, |_| 1) + self.floats,
+            self.strings,
+            self.arrays,
+            self.objects,
+        ];
+        if kinds.iter().filter(|&&n| n > 0).count() != 1 {
+            return json!({});
+        }
+        let mut schema = if self.booleans > 0 {
+            json!({"type": "boolean"})
+        } else if self.strings > 0 {
+            self.string_schema()
+        } else if self.arrays > 0 {
+            let elements = self.elements.as_ref().map_or(json!({}), |s| s.schema(opts));
+            json!({"elements": elements})
+        } else if self.objects > 0 {
+            self.object_schema(opts)
+        } else {
+            json!({"type": self.number_type()})
+        };
+        if self.nulls > 0 && schema.as_object().is_some_and(|s| !s.is_empty()) {
+            schema["nullable"] = true.into();
+        }
+        schema
+    }
+
+    fn number_type(&self) -> &'static str {
+        match self.ints {
+            Some((lo, hi)) if self.floats == 0 => INT_TYPES
+                .iter()
+                .find(|(_, min, max)| *min <= lo && hi <= *max)
+                .map_or("float64", |(name, ..)| name),
+            _ => "float64",
+        }
+    }
+
+    fn string_schema(&self) -> Value {
+        if self.non_timestamps == 0 {
+            return json!({"type": "timestamp"});
+        }
+        match &self.string_values {
+            Some(seen) if seen.values().all(|&n| n >= 2) => {
+                json!({"enum": seen.keys().collect::<Vec<_>>()})
+            }
+            _ => json!({"type": "string"}),
+        }
+    }
+
+    fn object_schema(&self, opts: &InferOptions) -> Value {
+        let data_keys =
+            !self.properties.is_empty() && self.properties.keys().all(|key| is_data_key(key));
+        if data_keys || self.properties.len() > opts.values_min_keys {
+            let values = self.values.as_ref().map_or(json!({}), |s| s.schema(opts));
+            return json!({"values": values});
+        }
+        let mut required = Map::new();
+        let mut optional = Map::new();
+        for (key, (count, summary)) in &self.properties {
+            let out = if *count == self.objects {
+                &mut required
+            } else {
+                &mut optional
+            };
+            out.insert(key.clone(), summary.schema(opts));
+        }
+        let mut schema = json!({"properties": required});
+        if !optional.is_empty() {
+            if required.is_empty() {
+                schema = json!({});
+            }
+            schema["optionalProperties"] = optional.into();
+        }
+        schema
+    }
+}
+
+/// Whether `key` looks like data rather than a field name: it starts with
+/// a digit or has characters identifiers do not.
+fn is_data_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_digit())
+        || key
+            .chars()
+            .any(|c| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '$'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use crate::interpret::validate;
+
+    #[test]
+    fn test_infer() {
+        let samples = vec![
+            json!({"id": 7, "name": "a", "status": "OPEN", "at": "2024-01-02T03:04:05Z", "tags": ["x"]}),
+            json!({"id": 300, "name": "b", "status": "OPEN", "at": "2024-01-03T00:00:00Z", "tags": [], "note": null}),
+            json!({"id": -2, "name": "c", "status": "CLOSED", "at": "2024-01-04T00:00:00+01:00", "tags": [], "note": "hi"}),
+            json!({"id": 4, "name": "d", "status": "CLOSED", "at": "2024-01-05T00:00:00Z", "tags": ["y"], "price": 1.5}),
+        ];
+        let schema = infer(&samples, &InferOptions::default());
+        assert_eq!(
+            schema,
+            json!({
+                "properties": {
+                    "at": {"type": "timestamp"},
+                    "id": {"type": "int16"},
+                    "name": {"type": "string"},
+                    "status": {"enum": ["CLOSED", "OPEN"]},
+                    "tags": {"elements": {"type": "string"}}
+                },
+                "optionalProperties": {
+                    "note": {"type": "string", "nullable": true},
+                    "price": {"type": "float64"}
+                }
+            })
+        );
+        let compiled = compile(&schema).unwrap();
+        for sample in &samples {
+            assert!(validate(&compiled, sample).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_infer_forms() {
+        let opts = InferOptions::default();
+        assert_eq!(
+            infer(&[json!({"2024-01-01": 3, "2024-01-02": 4})], &opts),
+            json!({"values": {"type": "uint8"}})
+        );
+        assert_eq!(infer(&[json!(1), json!("a")], &opts), json!({}));
+        assert_eq!(infer(&[json!(null)], &opts), json!({}));
+        assert_eq!(infer(&[json!([])], &opts), json!({"elements": {}}));
+        assert_eq!(
+            infer(&[json!({"a": 1}), json!({"b": 2})], &opts),
+            json!({"optionalProperties": {"a": {"type": "uint8"}, "b": {"type": "uint8"}}})
+        );
+        assert_eq!(
+            infer(&[json!(5_000_000_000i64), json!(true)], &opts),
+            json!({})
+        );
+        assert_eq!(
+            infer(&[json!(5_000_000_000i64)], &opts),
+            json!({"type": "float64"})
+        );
+        let no_enums = InferOptions {
+            enum_max_values: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            infer(&[json!("a"), json!("a")], &no_enums),
+            json!({"type": "string"})
+        );
+    }
+}
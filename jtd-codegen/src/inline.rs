@@ -0,0 +1,91 @@
+/// Which definitions to check in place instead of through a function.
+///
+/// Each definition normally becomes a function its refs call. A definition
+/// referenced only once, or whose body is a single type or enum check, is
+/// cheaper checked where it is used: the call costs more than the check,
+/// and the function adds code. Its errors keep the schema path
+/// `/definitions/<name>/...`, as the function would report them.
+///
+/// Recursive definitions always keep their function, which bounds how deep
+/// they nest.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::Node;
+use crate::recursion::recursive;
+use crate::visit::{visit_node, Visitor};
+
+/// The definitions to inline among `definitions`, referenced from `roots`
+/// and each other: those referenced at most `threshold` times, and those
+/// whose body is a leaf. A `threshold` of 0 inlines none.
+pub fn inlined<'a>(
+    roots: impl IntoIterator<Item = &'a Node>,
+    definitions: &'a BTreeMap<String, Node>,
+    threshold: usize,
+) -> BTreeSet<&'a str> {
+    if threshold == 0 {
+        return BTreeSet::new();
+    }
+    let mut counts = RefCounts::default();
+    for node in roots.into_iter().chain(definitions.values()) {
+        visit_node(node, "", &mut counts);
+    }
+    let recursive = recursive(definitions);
+    definitions
+        .iter()
+        .map(|(name, node)| (name.as_str(), node))
+        .filter(|(name, _)| !recursive.contains(name))
+        .filter(|(name, node)| {
+            let uses = counts.0.get(name).copied().unwrap_or(0);
+            leaf(node) || (1..=threshold).contains(&uses)
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// A body no longer than the call to it.
+fn leaf(node: &Node) -> bool {
+    matches!(node, Node::Empty | Node::Type { .. } | Node::Enum { .. })
+}
+
+#[derive(Default)]
+struct RefCounts<'a>(BTreeMap<&'a str, usize>);
+
+impl<'a> Visitor<'a> for RefCounts<'a> {
+    fn enter(&mut self, node: &'a Node, _schema_path: &str) {
+        if let Node::Ref { name } = node {
+            *self.0.entry(name).or_insert(0) += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_inlined() {
+        let schema = compile(&json!({
+            "definitions": {
+                "id": {"type": "string"},
+                "address": {"properties": {"zip": {"ref": "id"}}},
+                "money": {"properties": {"cents": {"type": "int32"}}},
+                "tree": {"elements": {"ref": "tree"}},
+                "unused": {"values": {"type": "string"}}
+            },
+            "properties": {
+                "id": {"ref": "id"},
+                "home": {"ref": "address"},
+                "price": {"ref": "money"},
+                "cost": {"ref": "money"},
+                "tree": {"ref": "tree"}
+            }
+        }))
+        .unwrap();
+        let inline = |threshold| inlined([&schema.root], &schema.definitions, threshold);
+        assert_eq!(inline(1), BTreeSet::from(["address", "id"]));
+        assert_eq!(inline(2), BTreeSet::from(["address", "id", "money"]));
+        assert!(inline(0).is_empty());
+    }
+}
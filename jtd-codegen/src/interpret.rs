@@ -0,0 +1,468 @@
+/// AST interpreter: validates a `serde_json::Value` against a compiled schema
+/// without generating code. Produces the same `(instancePath, schemaPath)`
+/// pairs as the generated validators, so bindings (Ruby, UniFFI) can offer
+/// `validate` without a compile step.
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::defaults;
+use crate::timestamp::is_rfc3339;
+use serde_json::Value;
+
+/// Validate `instance` and return every error as `(instancePath, schemaPath)`.
+pub fn validate(schema: &CompiledSchema, instance: &Value) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    validate_node(schema, &schema.root, instance, "", "", None, &mut errors);
+    errors
+}
+
+/// A copy of `instance` with each absent optional property that has a
+/// `metadata.default` filled in, as the generated `applyDefaults` does.
+pub fn apply_defaults(schema: &CompiledSchema, instance: &Value) -> Value {
+    let mut out = instance.clone();
+    defaults::apply(&defaults::defaults_plan(schema), &mut out);
+    out
+}
+
+/// Patch `previous` (the errors `validate` returned for an earlier version of
+/// the instance) after the values at `changed` instance paths were edited,
+/// added or removed. Only the object or array holding each change is
+/// validated again, so an editor can revalidate on every keystroke.
+///
+/// The result holds the same errors as a full `validate(schema, instance)`,
+/// but errors outside the changed sub-trees keep their earlier position and
+/// the re-checked ones are appended, so compare them as a set.
+pub fn revalidate(
+    schema: &CompiledSchema,
+    instance: &Value,
+    previous: &[(String, String)],
+    changed: &[&str],
+) -> Vec<(String, String)> {
+    let mut units: Vec<Unit> = Vec::new();
+    for path in changed {
+        let unit = locate(schema, instance, parent(path));
+        if units.iter().any(|u| within(&unit.ip, &u.ip)) {
+            continue;
+        }
+        units.retain(|u| !within(&u.ip, &unit.ip));
+        units.push(unit);
+    }
+
+    let mut errors: Vec<(String, String)> = previous
+        .iter()
+        .filter(|(ip, _)| !units.iter().any(|u| within(ip, &u.ip)))
+        .cloned()
+        .collect();
+    for u in &units {
+        validate_node(schema, u.node, u.value, &u.ip, &u.sp, u.tag, &mut errors);
+    }
+    errors
+}
+
+/// A node of the instance together with the schema that validates it.
+struct Unit<'a> {
+    ip: String,
+    sp: String,
+    node: &'a Node,
+    value: &'a Value,
+    tag: Option<&'a str>,
+}
+
+/// The instance path of the container holding `path`.
+fn parent(path: &str) -> &str {
+    path.rfind('/').map_or("", |i| &path[..i])
+}
+
+/// True when `ip` is `base` or lies below it.
+fn within(ip: &str, base: &str) -> bool {
+    ip.strip_prefix(base)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Follow `path` down the instance and the schema together, stopping at the
+/// deepest node both still have. Anything below a ref, nullable or
+/// discriminator is resolved from the instance, as `validate` would.
+fn locate<'a>(schema: &'a CompiledSchema, instance: &'a Value, path: &str) -> Unit<'a> {
+    let mut unit = Unit {
+        ip: String::new(),
+        sp: String::new(),
+        node: &schema.root,
+        value: instance,
+        tag: None,
+    };
+    for segment in path.split('/').skip(1) {
+        match step(schema, &unit, segment) {
+            Some(next) => unit = next,
+            None => break,
+        }
+    }
+    unit
+}
+
+fn step<'a>(schema: &'a CompiledSchema, unit: &Unit<'a>, segment: &str) -> Option<Unit<'a>> {
+    let (mut node, mut sp) = (unit.node, unit.sp.clone());
+    loop {
+        match node {
+            Node::Ref { name } => {
+                node = schema.definitions.get(name)?;
+                sp = format!("/definitions/{}", escape_pointer(name));
+            }
+            Node::Nullable { inner } if !unit.value.is_null() => node = inner,
+            Node::Discriminator { tag, mapping } => {
+                let tag_str = unit.value.get(tag)?.as_str()?;
+                node = mapping.get(tag_str)?;
+                sp = format!("{sp}/mapping/{}", escape_pointer(tag_str));
+            }
+            _ => break,
+        }
+    }
+    let ip = format!("{}/{segment}", unit.ip);
+    let key = crate::lsp::spans::unescape_segment(segment);
+    let (node, sp, value) = match node {
+        Node::Elements { schema: elem } => {
+            let item = unit.value.as_array()?.get(segment.parse::<usize>().ok()?)?;
+            (elem.as_ref(), format!("{sp}/elements"), item)
+        }
+        Node::Values { schema: value } => {
+            let item = unit.value.as_object()?.get(&key)?;
+            (value.as_ref(), format!("{sp}/values"), item)
+        }
+        Node::Properties {
+            required, optional, ..
+        } => {
+            let item = unit.value.as_object()?.get(&key)?;
+            match (required.get(&key), optional.get(&key)) {
+                (Some(child), _) => (child, format!("{sp}/properties/{segment}"), item),
+                (None, Some(child)) => (child, format!("{sp}/optionalProperties/{segment}"), item),
+                (None, None) => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(Unit {
+        ip,
+        sp,
+        node,
+        value,
+        tag: None,
+    })
+}
+
+fn validate_node(
+    schema: &CompiledSchema,
+    node: &Node,
+    v: &Value,
+    ip: &str,
+    sp: &str,
+    discrim_tag: Option<&str>,
+    errors: &mut Vec<(String, String)>,
+) {
+    match node {
+        Node::Empty => {}
+
+        Node::Type { type_kw } => {
+            if !type_ok(*type_kw, v) {
+                errors.push((ip.to_string(), format!("{sp}/type")));
+            }
+        }
+
+        Node::Enum { values } => {
+            let ok = v.as_str().is_some_and(|s| values.iter().any(|x| x == s));
+            if !ok {
+                errors.push((ip.to_string(), format!("{sp}/enum")));
+            }
+        }
+
+        Node::Ref { name } => {
+            if let Some(def) = schema.definitions.get(name) {
+                let def_sp = format!("/definitions/{}", escape_pointer(name));
+                validate_node(schema, def, v, ip, &def_sp, None, errors);
+            }
+        }
+
+        Node::Nullable { inner } => {
+            if !v.is_null() {
+                validate_node(schema, inner, v, ip, sp, None, errors);
+            }
+        }
+
+        Node::Elements { schema: elem } => match v.as_array() {
+            Some(arr) => {
+                let elem_sp = format!("{sp}/elements");
+                for (i, item) in arr.iter().enumerate() {
+                    let elem_ip = format!("{ip}/{i}");
+                    validate_node(schema, elem, item, &elem_ip, &elem_sp, None, errors);
+                }
+            }
+            None => errors.push((ip.to_string(), format!("{sp}/elements"))),
+        },
+
+        Node::Values { schema: value } => match v.as_object() {
+            Some(obj) => {
+                let value_sp = format!("{sp}/values");
+                for (k, item) in obj {
+                    let value_ip = format!("{ip}/{}", escape_pointer(k));
+                    validate_node(schema, value, item, &value_ip, &value_sp, None, errors);
+                }
+            }
+            None => errors.push((ip.to_string(), format!("{sp}/values"))),
+        },
+
+        Node::Properties {
+            required,
+            optional,
+            additional,
+        } => {
+            let Some(obj) = v.as_object() else {
+                let guard = if required.is_empty() {
+                    "/optionalProperties"
+                } else {
+                    "/properties"
+                };
+                errors.push((ip.to_string(), format!("{sp}{guard}")));
+                return;
+            };
+            for (key, child) in required {
+                match obj.get(key) {
+                    Some(pv) => {
+                        let seg = escape_pointer(key);
+                        let child_ip = format!("{ip}/{seg}");
+                        let child_sp = format!("{sp}/properties/{seg}");
+                        validate_node(schema, child, pv, &child_ip, &child_sp, None, errors);
+                    }
+                    None => errors.push((
+                        ip.to_string(),
+                        format!("{sp}/properties/{}", escape_pointer(key)),
+                    )),
+                }
+            }
+            for (key, child) in optional {
+                if let Some(pv) = obj.get(key) {
+                    let seg = escape_pointer(key);
+                    let child_ip = format!("{ip}/{seg}");
+                    let child_sp = format!("{sp}/optionalProperties/{seg}");
+                    validate_node(schema, child, pv, &child_ip, &child_sp, None, errors);
+                }
+            }
+            if !additional {
+                for key in obj.keys() {
+                    let known = required.contains_key(key)
+                        || optional.contains_key(key)
+                        || discrim_tag == Some(key.as_str());
+                    if !known {
+                        errors.push((format!("{ip}/{}", escape_pointer(key)), sp.to_string()));
+                    }
+                }
+            }
+        }
+
+        Node::Discriminator { tag, mapping } => {
+            let Some(obj) = v.as_object() else {
+                errors.push((ip.to_string(), format!("{sp}/discriminator")));
+                return;
+            };
+            let Some(tag_val) = obj.get(tag) else {
+                errors.push((ip.to_string(), format!("{sp}/discriminator")));
+                return;
+            };
+            let Some(tag_str) = tag_val.as_str() else {
+                errors.push((
+                    format!("{ip}/{}", escape_pointer(tag)),
+                    format!("{sp}/discriminator"),
+                ));
+                return;
+            };
+            match mapping.get(tag_str) {
+                Some(variant) => {
+                    let variant_sp = format!("{sp}/mapping/{}", escape_pointer(tag_str));
+                    validate_node(schema, variant, v, ip, &variant_sp, Some(tag), errors);
+                }
+                None => errors.push((
+                    format!("{ip}/{}", escape_pointer(tag)),
+                    format!("{sp}/mapping"),
+                )),
+            }
+        }
+    }
+}
+
+fn type_ok(type_kw: TypeKeyword, v: &Value) -> bool {
+    match type_kw {
+        TypeKeyword::Boolean => v.is_boolean(),
+        TypeKeyword::String => v.is_string(),
+        TypeKeyword::Timestamp => v.as_str().is_some_and(is_rfc3339),
+        TypeKeyword::Float32 | TypeKeyword::Float64 => v.as_f64().is_some_and(f64::is_finite),
+        TypeKeyword::Int8 => int_in(v, -128.0, 127.0),
+        TypeKeyword::Uint8 => int_in(v, 0.0, 255.0),
+        TypeKeyword::Int16 => int_in(v, -32768.0, 32767.0),
+        TypeKeyword::Uint16 => int_in(v, 0.0, 65535.0),
+        TypeKeyword::Int32 => int_in(v, -2_147_483_648.0, 2_147_483_647.0),
+        TypeKeyword::Uint32 => int_in(v, 0.0, 4_294_967_295.0),
+    }
+}
+
+fn int_in(v: &Value, min: f64, max: f64) -> bool {
+    v.as_f64()
+        .is_some_and(|n| n.fract() == 0.0 && n >= min && n <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn check(schema: Value, instance: Value) -> Vec<(String, String)> {
+        validate(&compiler::compile(&schema).unwrap(), &instance)
+    }
+
+    #[test]
+    fn test_worked_example() {
+        let schema = json!({
+            "properties": {
+                "name": {"type": "string"},
+                "tags": {"elements": {"type": "string"}}
+            },
+            "optionalProperties": {"age": {"type": "uint8"}}
+        });
+        assert!(check(schema.clone(), json!({"name": "a", "tags": []})).is_empty());
+        let errors = check(schema, json!({"tags": ["x", 1], "age": 300, "extra": true}));
+        assert_eq!(
+            errors,
+            vec![
+                ("".into(), "/properties/name".into()),
+                ("/tags/1".into(), "/properties/tags/elements/type".into()),
+                ("/age".into(), "/optionalProperties/age/type".into()),
+                ("/extra".into(), "".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ref_uses_absolute_schema_path() {
+        let schema = json!({
+            "definitions": {"id": {"type": "string"}},
+            "elements": {"ref": "id"}
+        });
+        assert_eq!(
+            check(schema, json!([1])),
+            vec![("/0".into(), "/definitions/id/type".into())]
+        );
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let schema = json!({
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"x": {"type": "string"}}}}
+        });
+        assert!(check(schema.clone(), json!({"kind": "a", "x": "1"})).is_empty());
+        assert_eq!(
+            check(schema.clone(), json!({"kind": "b"})),
+            vec![("/kind".into(), "/mapping".into())]
+        );
+        assert_eq!(
+            check(schema, json!({"kind": 1})),
+            vec![("/kind".into(), "/discriminator".into())]
+        );
+    }
+
+    fn sorted(mut errors: Vec<(String, String)>) -> Vec<(String, String)> {
+        errors.sort();
+        errors
+    }
+
+    #[test]
+    fn test_revalidate_matches_full_validation() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"item": {"properties": {"sku": {"type": "string"}, "qty": {"type": "uint8"}}}},
+            "properties": {
+                "name": {"type": "string"},
+                "items": {"elements": {"ref": "item"}},
+                "pet": {
+                    "discriminator": "kind",
+                    "mapping": {
+                        "cat": {"properties": {"lives": {"type": "uint8"}}},
+                        "dog": {"properties": {"good": {"type": "boolean"}}}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let before = json!({
+            "name": 1,
+            "items": [{"sku": "a", "qty": 1}, {"sku": 2, "qty": 1}],
+            "pet": {"kind": "cat", "lives": 9}
+        });
+        let previous = validate(&compiled, &before);
+        let edits: Vec<(&str, Value)> = vec![
+            (
+                "/items/1/sku",
+                json!({"name": 1, "items": [{"sku": "a", "qty": 1}, {"sku": "b", "qty": 1}], "pet": {"kind": "cat", "lives": 9}}),
+            ),
+            (
+                "/items/0/qty",
+                json!({"name": 1, "items": [{"sku": "a"}, {"sku": 2, "qty": 1}], "pet": {"kind": "cat", "lives": 9}}),
+            ),
+            (
+                "/items/0/extra",
+                json!({"name": 1, "items": [{"sku": "a", "qty": 1, "extra": 0}, {"sku": 2, "qty": 1}], "pet": {"kind": "cat", "lives": 9}}),
+            ),
+            (
+                "/pet/kind",
+                json!({"name": 1, "items": [{"sku": "a", "qty": 1}, {"sku": 2, "qty": 1}], "pet": {"kind": "dog", "lives": 9}}),
+            ),
+            (
+                "/items/1",
+                json!({"name": 1, "items": [{"sku": "a", "qty": 1}], "pet": {"kind": "cat", "lives": 9}}),
+            ),
+            (
+                "/name",
+                json!({"name": "x", "items": [{"sku": "a", "qty": 1}, {"sku": 2, "qty": 1}], "pet": {"kind": "cat", "lives": 9}}),
+            ),
+        ];
+        for (path, after) in edits {
+            assert_eq!(
+                sorted(revalidate(&compiled, &after, &previous, &[path])),
+                sorted(validate(&compiled, &after)),
+                "edit at {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_revalidate_merges_nested_changes() {
+        let compiled = compiler::compile(&json!({
+            "values": {"elements": {"type": "string"}}
+        }))
+        .unwrap();
+        let after = json!({"a": ["x", 1], "b": [2]});
+        let previous = vec![("/a/0".to_string(), "/values/elements/type".to_string())];
+        let errors = revalidate(&compiled, &after, &previous, &["/a/0", "/a/1", "/b/0", ""]);
+        assert_eq!(sorted(errors), sorted(validate(&compiled, &after)));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let compiled = compiler::compile(&json!({
+            "discriminator": "kind",
+            "mapping": {
+                "search": {
+                    "properties": {"q": {"type": "string"}},
+                    "optionalProperties": {
+                        "page": {"type": "uint32", "metadata": {"default": 1}},
+                        "sort": {"enum": ["asc", "desc"], "metadata": {"default": "asc"}}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let instance = json!({"kind": "search", "q": "jtd", "sort": "desc"});
+        let filled = apply_defaults(&compiled, &instance);
+        assert_eq!(
+            filled,
+            json!({"kind": "search", "q": "jtd", "page": 1, "sort": "desc"})
+        );
+        assert!(validate(&compiled, &filled).is_empty());
+        assert_eq!(apply_defaults(&compiled, &json!("x")), json!("x"));
+    }
+}
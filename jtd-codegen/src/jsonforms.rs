@@ -0,0 +1,313 @@
+/// JSON Forms documents: a JSON Schema (draft-07) data schema and a UI
+/// schema, so internal tools can render editing forms for the same schema
+/// the validators check. The same data schema also works with
+/// react-jsonschema-form.
+///
+/// - `metadata.label` becomes the data schema's `title` and the control's
+///   `label`; `metadata.description` and `metadata.default` carry over.
+/// - `metadata.enumLabels`, an object from enum value to label, turns an
+///   enum into `oneOf` `const`/`title` pairs so dropdowns show the labels.
+/// - Definitions become `definitions` reached by `$ref`; a discriminator
+///   becomes a `oneOf` whose variants fix the tag with `const`.
+/// - The UI schema is a vertical layout with a control per root property,
+///   and a group of controls for each inline nested object. Other forms
+///   get the renderer JSON Forms picks for their data schema.
+use serde_json::{json, Map, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::lsp::spans::escape_segment;
+
+/// `{"schema": ..., "uischema": ...}` for `schema`.
+pub fn jsonforms(schema: &CompiledSchema) -> Value {
+    let mut data = data_schema(schema, &schema.root, "");
+    if !schema.definitions.is_empty() {
+        let defs: Map<String, Value> = schema
+            .definitions
+            .iter()
+            .map(|(name, node)| {
+                let path = format!("/definitions/{}", escape_pointer(name));
+                (name.clone(), data_schema(schema, node, &path))
+            })
+            .collect();
+        data["definitions"] = Value::Object(defs);
+    }
+    let mut root = Map::new();
+    root.insert(
+        "$schema".into(),
+        "http://json-schema.org/draft-07/schema#".into(),
+    );
+    root.extend(data.as_object().cloned().unwrap_or_default());
+    let elements = ui_elements(schema, &schema.root, "", "#");
+    json!({
+        "schema": root,
+        "uischema": {"type": "VerticalLayout", "elements": elements},
+    })
+}
+
+/// The JSON Forms document as pretty-printed JSON, for `--target jsonforms`.
+pub fn emit(schema: &CompiledSchema) -> String {
+    let mut out = serde_json::to_string_pretty(&jsonforms(schema)).expect("JSON values serialize");
+    out.push('\n');
+    out
+}
+
+fn meta<'a>(schema: &'a CompiledSchema, path: &str, key: &str) -> Option<&'a Value> {
+    schema.metadata.get(path)?.get(key)
+}
+
+/// Data schema for `node`, whose JTD schema lives at `path`.
+fn data_schema(schema: &CompiledSchema, node: &Node, path: &str) -> Value {
+    let mut out = match node {
+        Node::Nullable { inner } => nullable(data_schema(schema, inner, path)),
+        _ => form(schema, node, path),
+    };
+    if let Some(label) = meta(schema, path, "label").and_then(Value::as_str) {
+        out["title"] = label.into();
+    }
+    if let Some(desc) = schema.description(path) {
+        out["description"] = desc.into();
+    }
+    if let Some(default) = schema.default_value(path) {
+        out["default"] = default.clone();
+    }
+    out
+}
+
+fn form(schema: &CompiledSchema, node: &Node, path: &str) -> Value {
+    match node {
+        Node::Empty | Node::Nullable { .. } => json!({}),
+        Node::Ref { name } => json!({"$ref": format!("#/definitions/{}", escape_segment(name))}),
+        Node::Type { type_kw } => type_schema(*type_kw),
+        Node::Enum { values } => {
+            let labels = meta(schema, path, "enumLabels").and_then(Value::as_object);
+            match labels {
+                Some(labels) => {
+                    let one_of: Vec<Value> = values
+                        .iter()
+                        .map(|v| {
+                            let title = labels.get(v).and_then(Value::as_str).unwrap_or(v);
+                            json!({"const": v, "title": title})
+                        })
+                        .collect();
+                    json!({"type": "string", "oneOf": one_of})
+                }
+                None => json!({"type": "string", "enum": values}),
+            }
+        }
+        Node::Elements { schema: item } => json!({
+            "type": "array",
+            "items": data_schema(schema, item, &format!("{path}/elements")),
+        }),
+        Node::Values { schema: item } => json!({
+            "type": "object",
+            "additionalProperties": data_schema(schema, item, &format!("{path}/values")),
+        }),
+        Node::Properties { .. } => object(schema, node, path, None),
+        Node::Discriminator { tag, mapping } => {
+            let one_of: Vec<Value> = mapping
+                .iter()
+                .map(|(value, variant)| {
+                    let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                    let mut out = object(schema, variant, &variant_path, Some((tag, value)));
+                    out["title"] = meta(schema, &variant_path, "label")
+                        .cloned()
+                        .unwrap_or_else(|| value.as_str().into());
+                    out
+                })
+                .collect();
+            json!({"type": "object", "oneOf": one_of})
+        }
+    }
+}
+
+fn type_schema(type_kw: TypeKeyword) -> Value {
+    let (lo, hi): (i64, i64) = match type_kw {
+        TypeKeyword::Boolean => return json!({"type": "boolean"}),
+        TypeKeyword::String => return json!({"type": "string"}),
+        TypeKeyword::Timestamp => return json!({"type": "string", "format": "date-time"}),
+        TypeKeyword::Float32 | TypeKeyword::Float64 => return json!({"type": "number"}),
+        TypeKeyword::Int8 => (i8::MIN.into(), i8::MAX.into()),
+        TypeKeyword::Uint8 => (0, u8::MAX.into()),
+        TypeKeyword::Int16 => (i16::MIN.into(), i16::MAX.into()),
+        TypeKeyword::Uint16 => (0, u16::MAX.into()),
+        TypeKeyword::Int32 => (i32::MIN.into(), i32::MAX.into()),
+        TypeKeyword::Uint32 => (0, u32::MAX.into()),
+    };
+    json!({"type": "integer", "minimum": lo, "maximum": hi})
+}
+
+/// Allow null as well: a second type where there is a single one, an
+/// `anyOf` otherwise (refs, `oneOf`s).
+fn nullable(mut inner: Value) -> Value {
+    if let Some(t) = inner
+        .get("type")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    {
+        if inner.get("oneOf").is_none() {
+            inner["type"] = json!([t, "null"]);
+            if let Some(values) = inner.get_mut("enum").and_then(Value::as_array_mut) {
+                values.push(Value::Null);
+            }
+            return inner;
+        }
+    }
+    if inner.as_object().is_some_and(Map::is_empty) {
+        return inner;
+    }
+    json!({"anyOf": [inner, {"type": "null"}]})
+}
+
+/// Object schema for the Properties node `node`, with a discriminator
+/// `tag` fixed to its value first when given.
+fn object(
+    schema: &CompiledSchema,
+    node: &Node,
+    path: &str,
+    tag: Option<(&String, &String)>,
+) -> Value {
+    let Node::Properties {
+        required,
+        optional,
+        additional,
+    } = node
+    else {
+        return json!({});
+    };
+    let mut props = Map::new();
+    let mut names = Vec::new();
+    if let Some((tag, value)) = tag {
+        props.insert(tag.clone(), json!({"type": "string", "const": value}));
+        names.push(tag.clone());
+    }
+    for (key, child) in required {
+        let child_path = format!("{path}/properties/{}", escape_pointer(key));
+        props.insert(key.clone(), data_schema(schema, child, &child_path));
+        names.push(key.clone());
+    }
+    for (key, child) in optional {
+        let child_path = format!("{path}/optionalProperties/{}", escape_pointer(key));
+        props.insert(key.clone(), data_schema(schema, child, &child_path));
+    }
+    json!({
+        "type": "object",
+        "properties": props,
+        "required": names,
+        "additionalProperties": additional,
+    })
+}
+
+/// UI elements for `node` at data scope `scope`: one control per property
+/// of an inline object, nested objects as groups, and a single control
+/// for anything else.
+fn ui_elements(schema: &CompiledSchema, node: &Node, path: &str, scope: &str) -> Vec<Value> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return vec![control(schema, path, scope)];
+    };
+    let required = required.iter().map(|(k, n)| (k, n, "properties"));
+    let optional = optional.iter().map(|(k, n)| (k, n, "optionalProperties"));
+    required
+        .chain(optional)
+        .map(|(key, child, keyword)| {
+            let child_path = format!("{path}/{keyword}/{}", escape_pointer(key));
+            let child_scope = format!("{scope}/properties/{}", escape_segment(key));
+            match child {
+                Node::Properties { .. } => {
+                    let label = meta(schema, &child_path, "label")
+                        .cloned()
+                        .unwrap_or_else(|| key.as_str().into());
+                    json!({
+                        "type": "Group",
+                        "label": label,
+                        "elements": ui_elements(schema, child, &child_path, &child_scope),
+                    })
+                }
+                _ => control(schema, &child_path, &child_scope),
+            }
+        })
+        .collect()
+}
+
+fn control(schema: &CompiledSchema, path: &str, scope: &str) -> Value {
+    let mut out = json!({"type": "Control", "scope": scope});
+    if let Some(label) = meta(schema, path, "label") {
+        out["label"] = label.clone();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+
+    #[test]
+    fn test_jsonforms() {
+        let schema = compile(&json!({
+            "definitions": {"money": {"properties": {"cents": {"type": "uint32"}}}},
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "metadata": {"label": "Full name", "description": "As on the card"}
+                },
+                "status": {
+                    "enum": ["new", "done"],
+                    "metadata": {"enumLabels": {"new": "New order"}}
+                },
+                "address": {"properties": {"city": {"type": "string"}}},
+                "total": {"ref": "money", "nullable": true},
+                "pet": {"discriminator": "kind", "mapping": {"cat": {"properties": {}}}}
+            },
+            "optionalProperties": {"at": {"type": "timestamp"}}
+        }))
+        .unwrap();
+        let forms = jsonforms(&schema);
+        let data = &forms["schema"];
+        assert_eq!(
+            data["required"],
+            json!(["address", "name", "pet", "status", "total"])
+        );
+        assert_eq!(data["additionalProperties"], json!(false));
+        assert_eq!(
+            data["properties"]["name"],
+            json!({"type": "string", "title": "Full name", "description": "As on the card"})
+        );
+        assert_eq!(
+            data["properties"]["status"]["oneOf"],
+            json!([{"const": "new", "title": "New order"}, {"const": "done", "title": "done"}])
+        );
+        assert_eq!(
+            data["properties"]["total"],
+            json!({"anyOf": [{"$ref": "#/definitions/money"}, {"type": "null"}]})
+        );
+        assert_eq!(
+            data["properties"]["at"],
+            json!({"type": "string", "format": "date-time"})
+        );
+        assert_eq!(
+            data["properties"]["pet"]["oneOf"][0]["properties"]["kind"],
+            json!({"type": "string", "const": "cat"})
+        );
+        assert_eq!(
+            data["definitions"]["money"]["properties"]["cents"],
+            json!({"type": "integer", "minimum": 0, "maximum": 4294967295_u32})
+        );
+
+        let elements = &forms["uischema"]["elements"];
+        assert_eq!(
+            elements[0],
+            json!({"type": "Group", "label": "address", "elements": [
+                {"type": "Control", "scope": "#/properties/address/properties/city"}
+            ]})
+        );
+        assert_eq!(
+            elements[1],
+            json!({"type": "Control", "scope": "#/properties/name", "label": "Full name"})
+        );
+        assert_eq!(elements[5]["scope"], json!("#/properties/at"));
+    }
+}
@@ -1,6 +1,58 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod ast;
+pub mod batch;
+pub mod bigquery;
+pub mod build;
+pub mod bundler;
+pub mod coerce;
 pub mod compiler;
+pub mod compose;
+pub mod convert;
+pub mod dedup;
+pub mod defaults;
+pub mod deprecation;
+pub mod diff;
+pub mod docs;
+pub mod emit_c;
+pub mod emit_dart;
 pub mod emit_js;
+pub mod emit_kt;
 pub mod emit_lua;
 pub mod emit_py;
 pub mod emit_rs;
+pub mod emit_sql;
+pub mod emit_ts;
+pub mod emit_typebox;
+pub mod emit_wasm;
+pub mod enums;
+pub mod generate;
+pub mod golden;
+pub mod graphql;
+pub mod infer;
+pub mod inline;
+pub mod interpret;
+pub mod jsonforms;
+pub mod lsp;
+pub mod manifest;
+pub mod messages;
+pub mod mock;
+pub mod mongodb;
+pub mod options;
+pub mod partial;
+pub mod protobuf;
+pub mod recursion;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod scaffold;
+pub mod shape;
+pub mod smoke;
+pub mod source_map;
+pub mod strip;
+pub mod subschemas;
+pub mod suggest;
+pub mod timestamp;
+pub mod verify;
+pub mod visit;
+
+pub use generate::{generate, GenerateError, Target};
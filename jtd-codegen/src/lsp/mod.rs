@@ -0,0 +1,609 @@
+/// A language server for JTD schema files (`*.jtd.json`), behind the
+/// `jtd-lsp` binary. It speaks LSP over stdio with full-document sync and
+/// offers:
+///
+/// - diagnostics: JSON syntax errors, [`compile_located`] errors underlined
+///   at the offending keyword, and unused definitions as warnings;
+/// - hover: the form of the schema under the cursor and its
+///   `metadata.description`;
+/// - go-to-definition from a `ref` value to its definition;
+/// - completion of schema keywords, type names and definition names.
+///
+/// [`Server::handle`] maps one incoming message to the messages to send
+/// back, so the protocol logic is testable without a client.
+pub mod spans;
+pub mod transport;
+
+use crate::compiler::compile_located;
+use crate::recursion::{ref_cycles, refs, warnings};
+use serde_json::{json, Value};
+use spans::{cursor_context, escape_segment, unescape_segment, Span, SpanIndex};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead, Write};
+
+const KEYWORDS: [&str; 13] = [
+    "definitions",
+    "ref",
+    "type",
+    "enum",
+    "elements",
+    "properties",
+    "optionalProperties",
+    "additionalProperties",
+    "values",
+    "discriminator",
+    "mapping",
+    "nullable",
+    "metadata",
+];
+
+const TYPES: [(&str, &str); 11] = [
+    ("boolean", "`true` or `false`"),
+    ("string", "a JSON string"),
+    ("timestamp", "an RFC 3339 timestamp string"),
+    ("int8", "an integer in -128..=127"),
+    ("uint8", "an integer in 0..=255"),
+    ("int16", "an integer in -32768..=32767"),
+    ("uint16", "an integer in 0..=65535"),
+    ("int32", "an integer in -2147483648..=2147483647"),
+    ("uint32", "an integer in 0..=4294967295"),
+    ("float32", "any JSON number"),
+    ("float64", "any JSON number"),
+];
+
+// LSP enum values used below.
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const KIND_VALUE: u8 = 12;
+const KIND_KEYWORD: u8 = 14;
+const KIND_REFERENCE: u8 = 18;
+
+struct Document {
+    text: String,
+    /// The most recent version of the text that parsed, for completions
+    /// while the current text is mid-edit.
+    last_good: Option<Value>,
+}
+
+/// Open documents and lifecycle state.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    exited: bool,
+}
+
+impl Server {
+    /// Whether the client has sent `exit`.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Handle one client message; returns the responses and notifications
+    /// to send.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Vec::new(); // a response to a server request; none are sent
+        };
+        let id = message.get("id").cloned();
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {"triggerCharacters": ["\""]}
+                },
+                "serverInfo": {"name": "jtd-lsp", "version": env!("CARGO_PKG_VERSION")}
+            }),
+            "shutdown" => Value::Null,
+            "exit" => {
+                self.exited = true;
+                return Vec::new();
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                return vec![self.update(uri, text.to_string())];
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                let Some(text) = changes.and_then(|c| c.last()?["text"].as_str()) else {
+                    return Vec::new();
+                };
+                return vec![self.update(uri, text.to_string())];
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish(uri, Vec::new())];
+            }
+            "textDocument/hover" | "textDocument/definition" | "textDocument/completion" => {
+                let Some(doc) = self.documents.get(uri) else {
+                    return respond(id, Value::Null);
+                };
+                let line = params["position"]["line"].as_u64().unwrap_or(0) as u32;
+                let character = params["position"]["character"].as_u64().unwrap_or(0) as u32;
+                let offset = spans::offset(&doc.text, line, character);
+                match method {
+                    "textDocument/hover" => hover(&doc.text, offset),
+                    "textDocument/definition" => definition(uri, &doc.text, offset),
+                    _ => Value::Array(completion(&doc.text, doc.last_good.as_ref(), offset)),
+                }
+            }
+            _ if id.is_some() => {
+                return vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32601, "message": format!("unsupported method {method}")}
+                })];
+            }
+            _ => return Vec::new(),
+        };
+        respond(id, result)
+    }
+
+    fn update(&mut self, uri: &str, text: String) -> Value {
+        let parsed = serde_json::from_str(&text).ok();
+        let doc = self.documents.entry(uri.to_string()).or_insert(Document {
+            text: String::new(),
+            last_good: None,
+        });
+        doc.text = text;
+        if parsed.is_some() {
+            doc.last_good = parsed;
+        }
+        publish(uri, diagnostics(&doc.text))
+    }
+}
+
+/// Serve LSP messages from `input` until the client sends `exit` or closes
+/// the stream.
+pub fn run<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = transport::read_message(&mut input)? {
+        for reply in server.handle(&message) {
+            transport::write_message(&mut output, &reply)?;
+        }
+        if server.exited() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn respond(id: Option<Value>, result: Value) -> Vec<Value> {
+    match id {
+        Some(id) => vec![json!({"jsonrpc": "2.0", "id": id, "result": result})],
+        None => Vec::new(),
+    }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics}
+    })
+}
+
+fn range(text: &str, (start, end): Span) -> Value {
+    let (l0, c0) = spans::position(text, start);
+    let (l1, c1) = spans::position(text, end);
+    json!({
+        "start": {"line": l0, "character": c0},
+        "end": {"line": l1, "character": c1}
+    })
+}
+
+fn diagnostic(text: &str, span: Span, severity: u8, message: String) -> Value {
+    json!({
+        "range": range(text, span),
+        "severity": severity,
+        "source": "jtd",
+        "message": message
+    })
+}
+
+/// Diagnostics for a whole document.
+pub fn diagnostics(text: &str) -> Vec<Value> {
+    let schema: Value = match serde_json::from_str(text) {
+        Ok(schema) => schema,
+        Err(e) => {
+            // serde_json reports 1-based lines and byte columns
+            let line_start: usize = text
+                .split_inclusive('\n')
+                .take(e.line().saturating_sub(1))
+                .map(str::len)
+                .sum();
+            let mut at = (line_start + e.column().saturating_sub(1)).min(text.len());
+            while !text.is_char_boundary(at) {
+                at -= 1;
+            }
+            let message = format!("invalid JSON: {e}");
+            return vec![diagnostic(text, (at, at), SEVERITY_ERROR, message)];
+        }
+    };
+    let index = SpanIndex::parse(text).unwrap_or_default();
+    let compiled = match compile_located(&schema) {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            let span = index.highlight(text, &e.path).unwrap_or((0, 0));
+            return vec![diagnostic(text, span, SEVERITY_ERROR, e.error.to_string())];
+        }
+    };
+
+    let mut used = BTreeSet::new();
+    refs(&compiled.root, &mut used);
+    for node in compiled.definitions.values() {
+        refs(node, &mut used);
+    }
+    let unused = compiled
+        .definitions
+        .keys()
+        .filter(|name| !used.contains(name.as_str()))
+        .map(|name| {
+            (
+                name.as_str(),
+                format!("definition '{name}' is never referenced"),
+            )
+        });
+    // Each ref cycle is flagged at its first definition
+    let cycles = ref_cycles(&compiled.definitions)
+        .into_iter()
+        .map(|cycle| cycle[0])
+        .zip(warnings(&compiled.definitions));
+    unused
+        .chain(cycles)
+        .filter_map(|(name, message)| {
+            let pointer = format!("/definitions/{}", escape_segment(name));
+            let span = index.get(&pointer)?.key?;
+            Some(diagnostic(text, span, SEVERITY_WARNING, message))
+        })
+        .collect()
+}
+
+/// Whether `pointer` names a schema rather than a keyword's value.
+fn is_schema(pointer: &str) -> bool {
+    let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+    let mut i = 0;
+    while i < segments.len() {
+        i += match segments[i] {
+            "definitions" if i == 0 => 2,
+            "properties" | "optionalProperties" | "mapping" => 2,
+            "elements" | "values" => 1,
+            _ => return false,
+        };
+    }
+    i == segments.len()
+}
+
+/// The nearest schema at or above `pointer`.
+fn enclosing_schema(pointer: &str) -> &str {
+    let mut p = pointer;
+    while !is_schema(p) {
+        p = &p[..p.rfind('/').unwrap_or(0)];
+    }
+    p
+}
+
+fn lookup<'a>(root: &'a Value, pointer: &str) -> Option<&'a Value> {
+    pointer
+        .split('/')
+        .skip(1)
+        .try_fold(root, |v, segment| v.get(unescape_segment(segment)))
+}
+
+fn code_list<'a>(items: impl IntoIterator<Item = &'a String>, sep: &str) -> String {
+    let items: Vec<String> = items.into_iter().map(|s| format!("`{s}`")).collect();
+    items.join(sep)
+}
+
+/// Markdown summary of `schema`: its form, nullability and description.
+fn describe(root: &Value, schema: &Value) -> String {
+    let keys = |v: &Value, k: &str| -> Vec<String> {
+        v.get(k)
+            .and_then(Value::as_object)
+            .map(|o| o.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let mut text = if let Some(name) = schema.get("ref").and_then(Value::as_str) {
+        let target = root.get("definitions").and_then(|d| d.get(name));
+        let about = target
+            .and_then(|t| t["metadata"]["description"].as_str())
+            .map(|d| format!(": {d}"))
+            .unwrap_or_default();
+        format!("**ref** `{name}`{about}")
+    } else if let Some(t) = schema.get("type").and_then(Value::as_str) {
+        let about = TYPES.iter().find(|(name, _)| *name == t).map(|(_, d)| *d);
+        format!("**type** `{t}`: {}", about.unwrap_or("unknown type"))
+    } else if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let values: Vec<String> = values
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(String::from)
+            .collect();
+        format!("**enum**: {}", code_list(&values, " | "))
+    } else if schema.get("elements").is_some() {
+        "**elements**: an array".to_string()
+    } else if schema.get("values").is_some() {
+        "**values**: an object with any keys".to_string()
+    } else if let Some(tag) = schema.get("discriminator").and_then(Value::as_str) {
+        let variants = keys(schema, "mapping");
+        format!("**discriminator** `{tag}`: {}", code_list(&variants, " | "))
+    } else if schema.get("properties").is_some() || schema.get("optionalProperties").is_some() {
+        let mut parts = Vec::new();
+        let required = keys(schema, "properties");
+        if !required.is_empty() {
+            parts.push(format!("required {}", code_list(&required, ", ")));
+        }
+        let optional = keys(schema, "optionalProperties");
+        if !optional.is_empty() {
+            parts.push(format!("optional {}", code_list(&optional, ", ")));
+        }
+        if schema["additionalProperties"] == Value::Bool(true) {
+            parts.push("other properties allowed".to_string());
+        }
+        format!("**properties**: {}", parts.join("; "))
+    } else {
+        "**empty**: any JSON value".to_string()
+    };
+    if schema["nullable"] == Value::Bool(true) {
+        text.push_str(", or `null`");
+    }
+    if let Some(description) = schema["metadata"]["description"].as_str() {
+        text.push_str("\n\n");
+        text.push_str(description);
+    }
+    text
+}
+
+/// Hover over any part of a schema: describe the schema that contains it.
+pub fn hover(text: &str, offset: usize) -> Value {
+    let (Some(index), Ok(root)) = (SpanIndex::parse(text), serde_json::from_str::<Value>(text))
+    else {
+        return Value::Null;
+    };
+    let Some(entry) = index.at(offset) else {
+        return Value::Null;
+    };
+    let pointer = enclosing_schema(&entry.pointer);
+    let Some(schema) = lookup(&root, pointer).filter(|s| s.is_object()) else {
+        return Value::Null;
+    };
+    let span = entry
+        .key
+        .filter(|(start, end)| *start <= offset && offset < *end)
+        .unwrap_or(entry.value);
+    json!({
+        "contents": {"kind": "markdown", "value": describe(&root, schema)},
+        "range": range(text, span)
+    })
+}
+
+/// From a `ref` value to the name of the definition it points at.
+pub fn definition(uri: &str, text: &str, offset: usize) -> Value {
+    let Some(index) = SpanIndex::parse(text) else {
+        return Value::Null;
+    };
+    let Some(entry) = index.at(offset) else {
+        return Value::Null;
+    };
+    let Some(schema) = entry.pointer.strip_suffix("/ref").filter(|s| is_schema(s)) else {
+        return Value::Null;
+    };
+    let root: Value = serde_json::from_str(text).unwrap_or_default();
+    let Some(name) = lookup(&root, schema).and_then(|s| s["ref"].as_str()) else {
+        return Value::Null;
+    };
+    let pointer = format!("/definitions/{}", escape_segment(name));
+    match index.get(&pointer) {
+        Some(target) => json!({
+            "uri": uri,
+            "range": range(text, target.key.unwrap_or(target.value))
+        }),
+        None => Value::Null,
+    }
+}
+
+/// Completion items at `offset`. `last_good` supplies definition names
+/// while the document does not parse.
+pub fn completion(text: &str, last_good: Option<&Value>, offset: usize) -> Vec<Value> {
+    let Some(ctx) = cursor_context(text, offset) else {
+        return Vec::new();
+    };
+    let quote = |s: &str| {
+        if ctx.in_string {
+            s.to_string()
+        } else {
+            format!("\"{s}\"")
+        }
+    };
+    let item = |label: &str, kind: u8, detail: &str| json!({"label": label, "kind": kind, "detail": detail, "insertText": quote(label)});
+    if !ctx.in_object || !is_schema(&ctx.container) {
+        return Vec::new();
+    }
+    if ctx.at_key {
+        return KEYWORDS
+            .iter()
+            .filter(|k| **k != "definitions" || ctx.container.is_empty())
+            .map(|k| item(k, KIND_KEYWORD, "JTD keyword"))
+            .collect();
+    }
+    match ctx.member.as_deref() {
+        Some("type") => TYPES
+            .iter()
+            .map(|(name, about)| item(name, KIND_VALUE, about))
+            .collect(),
+        Some("ref") => {
+            let names = last_good
+                .and_then(|root| root.get("definitions")?.as_object())
+                .map(|defs| defs.keys().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            names
+                .iter()
+                .map(|name| item(name, KIND_REFERENCE, "definition"))
+                .collect()
+        }
+        Some("nullable" | "additionalProperties") if !ctx.in_string => ["true", "false"]
+            .iter()
+            .map(|b| json!({"label": b, "kind": KIND_VALUE}))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r#"{
+  "definitions": {
+    "id": {"type": "string", "metadata": {"description": "Order id"}},
+    "spare": {}
+  },
+  "properties": {
+    "id": {"ref": "id"},
+    "qty": {"type": "uint8", "nullable": true}
+  }
+}"#;
+
+    fn pos(text: &str, needle: &str) -> usize {
+        text.find(needle).unwrap() + 1
+    }
+
+    #[test]
+    fn test_ref_cycle_diagnostic() {
+        let text = "{\n  \"definitions\": {\"a\": {\"ref\": \"a\"}},\n  \"ref\": \"a\"\n}";
+        let found = diagnostics(text);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0]["message"],
+            "definitions 'a' -> 'a' refer to each other with no data between them; no non-null instance is valid"
+        );
+        assert_eq!(
+            found[0]["range"]["start"],
+            json!({"line": 1, "character": 18})
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let warnings = diagnostics(DOC);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["severity"], SEVERITY_WARNING);
+        assert_eq!(
+            warnings[0]["message"],
+            "definition 'spare' is never referenced"
+        );
+        assert_eq!(
+            warnings[0]["range"]["start"],
+            json!({"line": 3, "character": 4})
+        );
+
+        let text = "{\n  \"properties\": {\"a\": {\"type\": \"nope\"}}\n}";
+        let errors = diagnostics(text);
+        assert_eq!(errors[0]["message"], "unknown type keyword: 'nope'");
+        assert_eq!(
+            errors[0]["range"],
+            json!({"start": {"line": 1, "character": 31}, "end": {"line": 1, "character": 37}})
+        );
+
+        let errors = diagnostics("{\n  \"a\": }");
+        assert!(errors[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JSON"));
+        assert_eq!(errors[0]["range"]["start"]["line"], 1);
+    }
+
+    #[test]
+    fn test_hover() {
+        let h = hover(DOC, pos(DOC, "\"uint8\""));
+        assert_eq!(
+            h["contents"]["value"],
+            "**type** `uint8`: an integer in 0..=255, or `null`"
+        );
+        let h = hover(DOC, pos(DOC, "{\"ref\""));
+        assert_eq!(h["contents"]["value"], "**ref** `id`: Order id");
+        let h = hover(DOC, pos(DOC, "\"properties\""));
+        assert_eq!(
+            h["contents"]["value"],
+            "**properties**: required `id`, `qty`"
+        );
+    }
+
+    #[test]
+    fn test_definition() {
+        let loc = definition("file:///a.jtd.json", DOC, pos(DOC, "\"id\"}"));
+        assert_eq!(loc["uri"], "file:///a.jtd.json");
+        assert_eq!(loc["range"]["start"], json!({"line": 2, "character": 4}));
+        assert_eq!(definition("u", DOC, pos(DOC, "\"uint8\"")), Value::Null);
+    }
+
+    #[test]
+    fn test_completion() {
+        let labels = |items: Vec<Value>| -> Vec<String> {
+            items
+                .iter()
+                .map(|i| i["label"].as_str().unwrap().to_string())
+                .collect()
+        };
+        let text = r#"{"properties": {"a": {"type": "u"#;
+        let items = completion(text, None, text.len());
+        assert!(labels(items.clone()).contains(&"uint32".to_string()));
+        assert_eq!(items[0]["insertText"], "boolean");
+
+        let text = r#"{"properties": {"a": {"#;
+        let items = labels(completion(text, None, text.len()));
+        assert!(items.contains(&"elements".to_string()));
+        assert!(!items.contains(&"definitions".to_string()));
+
+        let last_good: Value = serde_json::from_str(DOC).unwrap();
+        let text = r#"{"elements": {"ref": "#;
+        let items = completion(text, Some(&last_good), text.len());
+        assert_eq!(labels(items.clone()), ["id", "spare"]);
+        assert_eq!(items[0]["insertText"], "\"id\"");
+
+        let text = r#"{"properties": {"#;
+        assert!(completion(text, None, text.len()).is_empty());
+    }
+
+    #[test]
+    fn test_session() {
+        let mut server = Server::default();
+        let init = server
+            .handle(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}));
+        assert_eq!(init[0]["result"]["capabilities"]["hoverProvider"], true);
+        let open = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": "u", "languageId": "json", "version": 1, "text": "{\"type\": 1}"}}
+        }));
+        assert_eq!(open[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(
+            open[0]["params"]["diagnostics"][0]["message"],
+            "type must be a string"
+        );
+        let change = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {"textDocument": {"uri": "u", "version": 2}, "contentChanges": [{"text": "{}"}]}
+        }));
+        assert_eq!(change[0]["params"]["diagnostics"], json!([]));
+        let hover = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover",
+            "params": {"textDocument": {"uri": "u"}, "position": {"line": 0, "character": 0}}
+        }));
+        assert_eq!(
+            hover[0]["result"]["contents"]["value"],
+            "**empty**: any JSON value"
+        );
+        let unknown =
+            server.handle(&json!({"jsonrpc": "2.0", "id": 3, "method": "workspace/symbol"}));
+        assert_eq!(unknown[0]["error"]["code"], -32601);
+        assert!(server
+            .handle(&json!({"jsonrpc": "2.0", "method": "exit"}))
+            .is_empty());
+        assert!(server.exited());
+    }
+}
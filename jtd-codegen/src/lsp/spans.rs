@@ -0,0 +1,367 @@
+/// Source positions for a schema document. [`SpanIndex`] maps each JSON
+/// Pointer in a well-formed document to the byte ranges of its member key
+/// and value, so compiler paths can be turned back into editor ranges.
+/// [`cursor_context`] reads only the text before the cursor, so it works on
+/// the half-typed documents completion sees.
+///
+/// Byte range `start..end` in the document.
+pub type Span = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub pointer: String,
+    /// The quoted member name, for values inside an object.
+    pub key: Option<Span>,
+    pub value: Span,
+}
+
+/// Every value in a document, in document order (parents before children).
+#[derive(Debug, Default)]
+pub struct SpanIndex {
+    entries: Vec<Entry>,
+}
+
+impl SpanIndex {
+    /// Index `text`, or `None` if it is not well-formed JSON.
+    pub fn parse(text: &str) -> Option<SpanIndex> {
+        let mut p = Parser {
+            s: text.as_bytes(),
+            i: 0,
+            entries: Vec::new(),
+        };
+        p.value(String::new(), None)?;
+        p.ws();
+        (p.i == p.s.len()).then_some(SpanIndex { entries: p.entries })
+    }
+
+    pub fn get(&self, pointer: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.pointer == pointer)
+    }
+
+    /// The innermost value whose key or value range contains `offset`.
+    pub fn at(&self, offset: usize) -> Option<&Entry> {
+        let inside = |(start, end): Span| start <= offset && offset < end;
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| e.key.is_some_and(inside) || inside(e.value))
+    }
+
+    /// Range to underline for a problem at `pointer`: the member name for
+    /// objects and arrays, so the squiggle does not cover a whole block, and
+    /// the value itself for scalars.
+    pub fn highlight(&self, text: &str, pointer: &str) -> Option<Span> {
+        let entry = self.get(pointer)?;
+        let (start, end) = entry.value;
+        if matches!(text.as_bytes().get(start), Some(b'{' | b'[')) {
+            Some(entry.key.unwrap_or((start, start + 1)))
+        } else {
+            Some((start, end))
+        }
+    }
+}
+
+/// Escape a key for use as a JSON Pointer segment (RFC 6901).
+pub fn escape_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Undo [`escape_segment`].
+pub fn unescape_segment(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+    entries: Vec<Entry>,
+}
+
+impl Parser<'_> {
+    fn ws(&mut self) {
+        while matches!(self.s.get(self.i), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.i += 1;
+        }
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        self.ws();
+        let hit = self.s.get(self.i) == Some(&b);
+        if hit {
+            self.i += 1;
+        }
+        hit
+    }
+
+    fn value(&mut self, pointer: String, key: Option<Span>) -> Option<()> {
+        self.ws();
+        let start = self.i;
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            pointer: pointer.clone(),
+            key,
+            value: (start, start),
+        });
+        match *self.s.get(self.i)? {
+            b'{' => {
+                self.i += 1;
+                if !self.eat(b'}') {
+                    loop {
+                        self.ws();
+                        let key_start = self.i;
+                        let name = self.string()?;
+                        let key = (key_start, self.i);
+                        if !self.eat(b':') {
+                            return None;
+                        }
+                        let child = format!("{pointer}/{}", escape_segment(&name));
+                        self.value(child, Some(key))?;
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+            }
+            b'[' => {
+                self.i += 1;
+                if !self.eat(b']') {
+                    let mut n = 0;
+                    loop {
+                        self.value(format!("{pointer}/{n}"), None)?;
+                        n += 1;
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+            }
+            b'"' => {
+                self.string()?;
+            }
+            _ => {
+                while self
+                    .s
+                    .get(self.i)
+                    .is_some_and(|b| !b",]} \t\r\n".contains(b))
+                {
+                    self.i += 1;
+                }
+                serde_json::from_slice::<serde_json::Value>(&self.s[start..self.i]).ok()?;
+            }
+        }
+        self.entries[index].value.1 = self.i;
+        Some(())
+    }
+
+    /// A string starting at the cursor, decoded.
+    fn string(&mut self) -> Option<String> {
+        let start = self.i;
+        if self.s.get(self.i) != Some(&b'"') {
+            return None;
+        }
+        self.i += 1;
+        loop {
+            match *self.s.get(self.i)? {
+                b'\\' => self.i += 2,
+                b'"' => {
+                    self.i += 1;
+                    break;
+                }
+                _ => self.i += 1,
+            }
+        }
+        serde_json::from_slice(&self.s[start..self.i]).ok()
+    }
+}
+
+/// Where the cursor sits, judged from the text before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorContext {
+    /// Pointer of the innermost object or array around the cursor.
+    pub container: String,
+    /// Whether that container is an object.
+    pub in_object: bool,
+    /// In an object, whether the cursor is where a member name goes.
+    pub at_key: bool,
+    /// In an object, the member whose value the cursor is in.
+    pub member: Option<String>,
+    /// Whether the cursor is inside an unterminated string.
+    pub in_string: bool,
+}
+
+struct Frame {
+    pointer: String,
+    object: bool,
+    key: Option<String>,
+    index: usize,
+    after_colon: bool,
+}
+
+/// Scan `text[..offset]` and report the cursor's context, or `None` when it
+/// is outside any object or array.
+pub fn cursor_context(text: &str, offset: usize) -> Option<CursorContext> {
+    let s = &text.as_bytes()[..offset.min(text.len())];
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut i = 0;
+    let mut in_string = false;
+    while i < s.len() {
+        match s[i] {
+            open @ (b'{' | b'[') => {
+                let pointer = match stack.last() {
+                    None => String::new(),
+                    Some(f) if f.object => format!(
+                        "{}/{}",
+                        f.pointer,
+                        escape_segment(f.key.as_deref().unwrap_or(""))
+                    ),
+                    Some(f) => format!("{}/{}", f.pointer, f.index),
+                };
+                stack.push(Frame {
+                    pointer,
+                    object: open == b'{',
+                    key: None,
+                    index: 0,
+                    after_colon: false,
+                });
+            }
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b':' => {
+                if let Some(f) = stack.last_mut() {
+                    f.after_colon = true;
+                }
+            }
+            b',' => {
+                if let Some(f) = stack.last_mut() {
+                    if f.object {
+                        f.key = None;
+                        f.after_colon = false;
+                    } else {
+                        f.index += 1;
+                    }
+                }
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < s.len() && s[i] != b'"' {
+                    i += if s[i] == b'\\' { 2 } else { 1 };
+                }
+                if i >= s.len() {
+                    in_string = true;
+                    break;
+                }
+                if let Some(f) = stack.last_mut() {
+                    if f.object && !f.after_colon {
+                        f.key = serde_json::from_slice(&s[start..=i]).ok();
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    let f = stack.last()?;
+    Some(CursorContext {
+        container: f.pointer.clone(),
+        in_object: f.object,
+        at_key: f.object && !f.after_colon,
+        member: f.key.clone().filter(|_| f.after_colon),
+        in_string,
+    })
+}
+
+/// LSP position (zero-based line, UTF-16 column) of byte `offset`.
+pub fn position(text: &str, offset: usize) -> (u32, u32) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = before[line_start..].encode_utf16().count();
+    (line as u32, character as u32)
+}
+
+/// Byte offset of an LSP position, clamped to the line's end.
+pub fn offset(text: &str, line: u32, character: u32) -> usize {
+    let mut start = 0;
+    for _ in 0..line {
+        match text[start..].find('\n') {
+            Some(i) => start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[start..].char_indices() {
+        if units >= character as usize || c == '\n' {
+            return start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r#"{
+  "definitions": {"id": {"type": "string"}},
+  "properties": {"a/b": {"ref": "id"}, "xs": {"elements": {}}}
+}"#;
+
+    #[test]
+    fn test_index_pointers() {
+        let index = SpanIndex::parse(DOC).unwrap();
+        let entry = index.get("/properties/a~1b/ref").unwrap();
+        assert_eq!(&DOC[entry.value.0..entry.value.1], "\"id\"");
+        assert_eq!(&DOC[entry.key.unwrap().0..entry.key.unwrap().1], "\"ref\"");
+        let xs = index.get("/properties/xs").unwrap();
+        assert_eq!(&DOC[xs.value.0..xs.value.1], "{\"elements\": {}}");
+        assert_eq!(
+            index.highlight(DOC, "/properties/xs"),
+            xs.key,
+            "containers highlight their member name"
+        );
+        let at = DOC.find("string").unwrap();
+        assert_eq!(index.at(at).unwrap().pointer, "/definitions/id/type");
+        assert!(SpanIndex::parse("{\"a\": }").is_none());
+        assert!(SpanIndex::parse("[1, 2] 3").is_none());
+    }
+
+    #[test]
+    fn test_cursor_context() {
+        let text = r#"{"properties": {"a": {"type": "ui"#;
+        let ctx = cursor_context(text, text.len()).unwrap();
+        assert_eq!(ctx.container, "/properties/a");
+        assert_eq!(ctx.member.as_deref(), Some("type"));
+        assert!(ctx.in_string && !ctx.at_key);
+
+        let text = r#"{"elements": {"nullable": true, "#;
+        let ctx = cursor_context(text, text.len()).unwrap();
+        assert_eq!(ctx.container, "/elements");
+        assert!(ctx.at_key && !ctx.in_string);
+        assert_eq!(ctx.member, None);
+
+        let text = r#"{"enum": ["a", "#;
+        let ctx = cursor_context(text, text.len()).unwrap();
+        assert_eq!(ctx.container, "/enum");
+        assert!(!ctx.in_object);
+        assert_eq!(cursor_context("", 0), None);
+    }
+
+    #[test]
+    fn test_positions_count_utf16() {
+        let text = "{\n  \"😀\": 1}";
+        let one = text.find('1').unwrap();
+        assert_eq!(position(text, one), (1, 8));
+        assert_eq!(offset(text, 1, 8), one);
+        assert_eq!(offset(text, 0, 99), 1);
+        assert_eq!(offset(text, 9, 0), text.len());
+    }
+}
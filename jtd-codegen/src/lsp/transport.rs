@@ -0,0 +1,61 @@
+/// LSP base protocol framing: each JSON-RPC message is preceded by a
+/// `Content-Length` header and a blank line.
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Read the next message, or `None` at end of input.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, &json!({"id": 1, "method": "x"})).unwrap();
+        write_message(&mut buf, &json!({"method": "ü"})).unwrap();
+        let mut input = io::Cursor::new(buf);
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(json!({"id": 1, "method": "x"}))
+        );
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(json!({"method": "ü"}))
+        );
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+}
@@ -0,0 +1,89 @@
+/// Build-system manifests (`--manifest`): what a CLI run read and wrote,
+/// with content hashes, so Bazel, Nix or Gradle rules can declare the
+/// run's inputs and outputs and rerun it only when one changes.
+///
+/// ```json
+/// {"generator": "jtd-codegen 0.2.0", "artifacts": [{
+///   "target": "rust", "options": ["--target", "rust"], "schemaHash": "sha256:...",
+///   "inputs": [{"path": "order.json", "hash": "sha256:..."}],
+///   "output": {"path": "order.rs", "hash": "sha256:..."}}]}
+/// ```
+///
+/// `schemaHash` is the banner's hash of the schema after merging, `hash`
+/// the SHA-256 of a file's bytes. An output written to stdout has a null
+/// path.
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// One file a run wrote (or printed), and what it was generated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub target: String,
+    pub options: Vec<String>,
+    pub schema_hash: String,
+    /// `(path, checksum)` of each file read: schemas, `--extends` bases,
+    /// `--definitions` and so on.
+    pub inputs: Vec<(String, String)>,
+    /// Where the output went, `None` for stdout.
+    pub output: Option<String>,
+    pub output_hash: String,
+}
+
+/// `sha256:<hex>` of `bytes`.
+pub fn checksum(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256:{hex}")
+}
+
+/// The manifest document for `artifacts`.
+pub fn manifest(artifacts: &[Artifact]) -> Value {
+    let artifacts: Vec<Value> = artifacts
+        .iter()
+        .map(|a| {
+            let inputs: Vec<Value> = a
+                .inputs
+                .iter()
+                .map(|(path, hash)| json!({"path": path, "hash": hash}))
+                .collect();
+            json!({
+                "target": a.target,
+                "options": a.options,
+                "schemaHash": a.schema_hash,
+                "inputs": inputs,
+                "output": {"path": a.output, "hash": a.output_hash},
+            })
+        })
+        .collect();
+    json!({
+        "generator": format!("jtd-codegen {}", env!("CARGO_PKG_VERSION")),
+        "artifacts": artifacts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        assert_eq!(
+            checksum(b"abc"),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let doc = manifest(&[Artifact {
+            target: "rust".into(),
+            options: vec!["--target".into(), "rust".into()],
+            schema_hash: "sha256:00".into(),
+            inputs: vec![("order.json".into(), checksum(b"{}"))],
+            output: None,
+            output_hash: checksum(b"code"),
+        }]);
+        let artifact = &doc["artifacts"][0];
+        assert_eq!(artifact["target"], "rust");
+        assert_eq!(artifact["schemaHash"], "sha256:00");
+        assert_eq!(artifact["inputs"][0]["path"], "order.json");
+        assert_eq!(artifact["output"]["path"], Value::Null);
+        assert_eq!(artifact["output"]["hash"], checksum(b"code"));
+    }
+}
@@ -0,0 +1,146 @@
+/// Human-readable error messages, with `--messages`: each error also gets
+/// a `message` such as "expected uint8, got string", "missing required
+/// property 'name'" or "unknown property 'foo'". The wording lives here;
+/// each emitter spells the message as an expression in its language,
+/// joining [`Parts::text`] with the runtime part given by the subject.
+///
+/// Values are described by their JSON type, or by themselves for numbers,
+/// so that "expected uint8, got 300" says what was wrong.
+use crate::ast::TypeKeyword;
+use crate::recursion::MAX_REF_DEPTH;
+
+/// What a validation error is about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Message<'a> {
+    /// The value is not of the `type`.
+    Type(TypeKeyword),
+    /// The value is not one of the `enum` values.
+    Enum(&'a [String]),
+    /// The value of an `elements` form is not an array.
+    NotArray,
+    /// The value of a properties, `values` or discriminator form is not an
+    /// object.
+    NotObject,
+    /// The required property is missing.
+    MissingProperty(&'a str),
+    /// The object has a property the schema does not allow, whose name is
+    /// in the given variable of the generated code.
+    UnknownProperty(&'a str),
+    /// The discriminator tag is missing.
+    MissingTag(&'a str),
+    /// The discriminator tag is not a string.
+    TagNotString(&'a str),
+    /// The discriminator tag is a string the mapping does not have.
+    UnknownTag(&'a str),
+    /// Refs nest deeper than [`MAX_REF_DEPTH`].
+    TooDeep,
+}
+
+/// The runtime part of a message, after [`Parts::text`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Subject<'a> {
+    /// None: the text is the whole message.
+    None,
+    /// A description of the value being validated.
+    Value,
+    /// A description of the value's member of that name.
+    Member(&'a str),
+    /// The string in the value's member of that name.
+    MemberString(&'a str),
+    /// The string in the variable of that name.
+    Variable(&'a str),
+}
+
+/// A message as text, a runtime subject and more text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parts<'a> {
+    pub text: String,
+    pub subject: Subject<'a>,
+    pub end: &'static str,
+}
+
+impl<'a> Message<'a> {
+    pub fn parts(&self) -> Parts<'a> {
+        let (text, subject, end) = match *self {
+            Message::Type(type_kw) => (
+                format!("expected {}, got ", type_kw.as_str()),
+                Subject::Value,
+                "",
+            ),
+            Message::Enum(values) => {
+                let values: Vec<String> = values.iter().map(|v| format!("'{v}'")).collect();
+                (
+                    format!("expected one of {}, got ", values.join(", ")),
+                    Subject::Value,
+                    "",
+                )
+            }
+            Message::NotArray => ("expected array, got ".into(), Subject::Value, ""),
+            Message::NotObject => ("expected object, got ".into(), Subject::Value, ""),
+            Message::MissingProperty(key) => (
+                format!("missing required property '{key}'"),
+                Subject::None,
+                "",
+            ),
+            Message::UnknownProperty(var) => {
+                ("unknown property '".into(), Subject::Variable(var), "'")
+            }
+            Message::MissingTag(tag) => {
+                (format!("missing discriminator '{tag}'"), Subject::None, "")
+            }
+            Message::TagNotString(tag) => (
+                format!("discriminator '{tag}' must be a string, got "),
+                Subject::Member(tag),
+                "",
+            ),
+            Message::UnknownTag(tag) => (
+                format!("unknown discriminator '{tag}' value '"),
+                Subject::MemberString(tag),
+                "'",
+            ),
+            Message::TooDeep => (
+                format!("refs nested more than {MAX_REF_DEPTH} deep"),
+                Subject::None,
+                "",
+            ),
+        };
+        Parts { text, subject, end }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts() {
+        let parts = Message::Type(TypeKeyword::Uint8).parts();
+        assert_eq!(parts.text, "expected uint8, got ");
+        assert_eq!(parts.subject, Subject::Value);
+        let values = ["A".to_string(), "B".to_string()];
+        assert_eq!(
+            Message::Enum(&values).parts().text,
+            "expected one of 'A', 'B', got "
+        );
+        assert_eq!(
+            Message::MissingProperty("name").parts(),
+            Parts {
+                text: "missing required property 'name'".into(),
+                subject: Subject::None,
+                end: ""
+            }
+        );
+        assert_eq!(
+            Message::UnknownProperty("k").parts(),
+            Parts {
+                text: "unknown property '".into(),
+                subject: Subject::Variable("k"),
+                end: "'"
+            }
+        );
+        assert_eq!(
+            Message::UnknownTag("kind").parts().subject,
+            Subject::MemberString("kind")
+        );
+    }
+}
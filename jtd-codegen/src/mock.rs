@@ -0,0 +1,187 @@
+/// Random schema-valid instances for `mock(seed)`, emitted with the `mock`
+/// option, so contract and frontend tests can fabricate fixtures at runtime.
+/// Every target follows the recipe below with its own seeded generator, so
+/// a seed always gives the same instance for one target but not across
+/// targets. The generated Rust uses the same SplitMix64 generator as
+/// [`mock`], which makes them agree.
+///
+/// - Strings are up to [`MAX_STRING`] lowercase ASCII letters; timestamps
+///   are whole-second UTC times between 2000 and 2030; floats are
+///   thousandths in ±1000; integers cover their type's whole range.
+/// - Arrays and maps get up to [`MAX_ITEMS`] entries, optional properties
+///   are present half the time and nullable values are null a quarter of
+///   the time.
+/// - Each ref followed adds one to the depth. From [`MAX_DEPTH`] on, arrays
+///   and maps are empty, optional properties absent and nullable values
+///   null, so recursive schemas end. A ref cycle that needs none of these
+///   (every step required and non-nullable) has no finite instance at all.
+/// - The empty form gives null.
+use serde_json::{Map, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+
+/// Ref depth from which containers stay empty.
+pub const MAX_DEPTH: usize = 3;
+/// Most entries in a generated array or map.
+pub const MAX_ITEMS: i64 = 3;
+/// Longest generated string.
+pub const MAX_STRING: i64 = 8;
+
+/// Inclusive range of an integer type, `None` for the others.
+pub fn int_range(type_kw: TypeKeyword) -> Option<(i64, i64)> {
+    match type_kw {
+        TypeKeyword::Int8 => Some((i8::MIN.into(), i8::MAX.into())),
+        TypeKeyword::Uint8 => Some((0, u8::MAX.into())),
+        TypeKeyword::Int16 => Some((i16::MIN.into(), i16::MAX.into())),
+        TypeKeyword::Uint16 => Some((0, u16::MAX.into())),
+        TypeKeyword::Int32 => Some((i32::MIN.into(), i32::MAX.into())),
+        TypeKeyword::Uint32 => Some((0, u32::MAX.into())),
+        _ => None,
+    }
+}
+
+/// SplitMix64, as emitted in generated Rust.
+struct Rng(u64);
+
+impl Rng {
+    fn int(&mut self, lo: i64, hi: i64) -> i64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        lo + (z % (hi - lo + 1) as u64) as i64
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.int(0, MAX_STRING);
+        (0..len)
+            .map(|_| (b'a' + self.int(0, 25) as u8) as char)
+            .collect()
+    }
+
+    fn timestamp(&mut self) -> String {
+        let (y, mo, d) = (self.int(2000, 2030), self.int(1, 12), self.int(1, 28));
+        let (h, mi, s) = (self.int(0, 23), self.int(0, 59), self.int(0, 59));
+        format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}Z")
+    }
+}
+
+/// The instance `mock(seed)` generates for `schema` in Rust.
+pub fn mock(schema: &CompiledSchema, seed: u64) -> Value {
+    generate(schema, &schema.root, &mut Rng(seed), 0)
+}
+
+fn generate(schema: &CompiledSchema, node: &Node, r: &mut Rng, d: usize) -> Value {
+    let deep = d >= MAX_DEPTH;
+    match node {
+        Node::Empty => Value::Null,
+        Node::Ref { name } => generate(schema, &schema.definitions[name], r, d + 1),
+        Node::Type { type_kw } => match (type_kw, int_range(*type_kw)) {
+            (_, Some((lo, hi))) => r.int(lo, hi).into(),
+            (TypeKeyword::Boolean, _) => (r.int(0, 1) == 1).into(),
+            (TypeKeyword::String, _) => r.string().into(),
+            (TypeKeyword::Timestamp, _) => r.timestamp().into(),
+            _ => (r.int(-1_000_000, 1_000_000) as f64 / 1000.0).into(),
+        },
+        Node::Enum { values } => values[r.int(0, values.len() as i64 - 1) as usize]
+            .clone()
+            .into(),
+        Node::Elements { schema: item } => {
+            let n = if deep { 0 } else { r.int(0, MAX_ITEMS) };
+            (0..n).map(|_| generate(schema, item, r, d)).collect()
+        }
+        Node::Values { schema: item } => {
+            let n = if deep { 0 } else { r.int(0, MAX_ITEMS) };
+            let mut map = Map::new();
+            for _ in 0..n {
+                let key = r.string();
+                map.insert(key, generate(schema, item, r, d));
+            }
+            Value::Object(map)
+        }
+        Node::Properties { .. } => Value::Object(properties(schema, node, r, d, Map::new())),
+        Node::Discriminator { tag, mapping } => {
+            let i = r.int(0, mapping.len() as i64 - 1) as usize;
+            let (value, variant) = mapping.iter().nth(i).expect("mapping is non-empty");
+            let mut map = Map::new();
+            map.insert(tag.clone(), value.clone().into());
+            Value::Object(properties(schema, variant, r, d, map))
+        }
+        Node::Nullable { inner } => {
+            if deep || r.int(0, 3) == 0 {
+                Value::Null
+            } else {
+                generate(schema, inner, r, d)
+            }
+        }
+    }
+}
+
+/// `map` with the properties of the Properties node `node` added.
+fn properties(
+    schema: &CompiledSchema,
+    node: &Node,
+    r: &mut Rng,
+    d: usize,
+    mut map: Map<String, Value>,
+) -> Map<String, Value> {
+    let Node::Properties {
+        required, optional, ..
+    } = node
+    else {
+        return map;
+    };
+    for (key, child) in required {
+        map.insert(key.clone(), generate(schema, child, r, d));
+    }
+    for (key, child) in optional {
+        if d < MAX_DEPTH && r.int(0, 1) == 1 {
+            map.insert(key.clone(), generate(schema, child, r, d));
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use crate::interpret::validate;
+    use serde_json::json;
+
+    #[test]
+    fn test_mocks_are_valid() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree": {
+                    "properties": {"value": {"type": "int8"}},
+                    "optionalProperties": {
+                        "children": {"elements": {"ref": "tree"}},
+                        "parent": {"ref": "tree", "nullable": true}
+                    }
+                }
+            },
+            "properties": {
+                "id": {"type": "uint32"},
+                "at": {"type": "timestamp"},
+                "score": {"type": "float32", "nullable": true},
+                "status": {"enum": ["new", "done"]},
+                "labels": {"values": {"type": "string"}},
+                "tree": {"ref": "tree"},
+                "pet": {"discriminator": "kind", "mapping": {
+                    "cat": {"properties": {"lives": {"type": "uint8"}}},
+                    "dog": {"optionalProperties": {"good": {"type": "boolean"}}}
+                }},
+                "any": {}
+            }
+        }))
+        .unwrap();
+        for seed in 0..200 {
+            let instance = mock(&schema, seed);
+            assert_eq!(validate(&schema, &instance), vec![], "{instance}");
+        }
+        assert_eq!(mock(&schema, 7), mock(&schema, 7));
+        assert_ne!(mock(&schema, 7), mock(&schema, 8));
+    }
+}
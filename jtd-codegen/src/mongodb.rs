@@ -0,0 +1,264 @@
+/// MongoDB `$jsonSchema` validators, so a collection enforces the contract
+/// the application validators check. MongoDB's dialect has no `$ref` or
+/// `definitions`, so refs are inlined; a ref back into a definition
+/// already being inlined cannot be expressed and accepts anything there.
+///
+/// - Types use `bsonType`. Integer types are numbers with `multipleOf: 1`
+///   and the type's bounds, like JTD, which accepts `3.0` as a `uint8`.
+///   Timestamps may be stored as BSON dates or as RFC 3339 strings.
+/// - Properties forms stay closed. The root object (or each root
+///   discriminator variant) also admits `_id`, which every stored document
+///   has, unless the schema declares it.
+/// - A discriminator is a `oneOf` whose variants pin the tag with `enum`.
+/// - `metadata.label` becomes `title`; `metadata.description` carries over.
+use serde_json::{json, Map, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::mock::int_range;
+use crate::options::EmitOptions;
+
+/// RFC 3339 date-times, as in the generated validators (which also reject
+/// impossible dates).
+const TIMESTAMP_PATTERN: &str =
+    r"^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:(\d{2}|60)(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$";
+
+/// The `$jsonSchema` document for `schema`.
+pub fn json_schema(schema: &CompiledSchema) -> Value {
+    let mut out = convert(schema, &schema.root, "", &mut Vec::new());
+    let objects: Vec<&mut Value> = match out.get("oneOf") {
+        Some(_) => out["oneOf"].as_array_mut().into_iter().flatten().collect(),
+        None => vec![&mut out],
+    };
+    for object in objects {
+        if let Some(props) = object.get_mut("properties").and_then(Value::as_object_mut) {
+            props.entry("_id").or_insert_with(|| json!({}));
+        }
+    }
+    out
+}
+
+/// `{"$jsonSchema": ...}`, the collection's `validator` option.
+pub fn validator(schema: &CompiledSchema) -> Value {
+    json!({"$jsonSchema": json_schema(schema)})
+}
+
+/// The `collMod` command applying the validator to an existing collection,
+/// rejecting invalid writes.
+pub fn coll_mod(schema: &CompiledSchema, collection: &str) -> Value {
+    json!({
+        "collMod": collection,
+        "validator": validator(schema),
+        "validationLevel": "strict",
+        "validationAction": "error",
+    })
+}
+
+/// Pretty-printed output for `--target mongodb`: the `collMod` command for
+/// `opts.mongo_collection` when set, the validator document otherwise.
+pub fn emit(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let doc = match &opts.mongo_collection {
+        Some(collection) => coll_mod(schema, collection),
+        None => validator(schema),
+    };
+    let mut out = serde_json::to_string_pretty(&doc).expect("JSON values serialize");
+    out.push('\n');
+    out
+}
+
+/// `node` at schema path `path`; `inlining` holds the definitions being
+/// inlined on the way here.
+fn convert<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    inlining: &mut Vec<&'a str>,
+) -> Value {
+    let mut out = match node {
+        Node::Empty => json!({}),
+        Node::Ref { name } => {
+            if inlining.contains(&name.as_str()) {
+                return json!({});
+            }
+            inlining.push(name);
+            let target = &schema.definitions[name];
+            let out = convert(
+                schema,
+                target,
+                &format!("/definitions/{}", escape_pointer(name)),
+                inlining,
+            );
+            inlining.pop();
+            out
+        }
+        Node::Type { type_kw } => type_schema(*type_kw),
+        Node::Enum { values } => json!({"bsonType": "string", "enum": values}),
+        Node::Elements { schema: item } => json!({
+            "bsonType": "array",
+            "items": convert(schema, item, &format!("{path}/elements"), inlining),
+        }),
+        Node::Values { schema: item } => json!({
+            "bsonType": "object",
+            "additionalProperties": convert(schema, item, &format!("{path}/values"), inlining),
+        }),
+        Node::Properties { .. } => object(schema, node, path, None, inlining),
+        Node::Discriminator { tag, mapping } => {
+            let one_of: Vec<Value> = mapping
+                .iter()
+                .map(|(value, variant)| {
+                    let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                    object(schema, variant, &variant_path, Some((tag, value)), inlining)
+                })
+                .collect();
+            json!({"bsonType": "object", "oneOf": one_of})
+        }
+        Node::Nullable { inner } => nullable(convert(schema, inner, path, inlining)),
+    };
+    if let Some(meta) = schema.metadata.get(path) {
+        if let Some(label) = meta.get("label").and_then(Value::as_str) {
+            out["title"] = label.into();
+        }
+        if let Some(desc) = meta.get("description").and_then(Value::as_str) {
+            out["description"] = desc.into();
+        }
+    }
+    out
+}
+
+fn type_schema(type_kw: TypeKeyword) -> Value {
+    if let Some((lo, hi)) = int_range(type_kw) {
+        return json!({"bsonType": "number", "multipleOf": 1, "minimum": lo, "maximum": hi});
+    }
+    match type_kw {
+        TypeKeyword::Boolean => json!({"bsonType": "bool"}),
+        TypeKeyword::Timestamp => {
+            json!({"bsonType": ["date", "string"], "pattern": TIMESTAMP_PATTERN})
+        }
+        TypeKeyword::Float32 | TypeKeyword::Float64 => json!({"bsonType": "number"}),
+        _ => json!({"bsonType": "string"}),
+    }
+}
+
+/// Also admit null: as one more `bsonType` (and enum member) where the
+/// schema has one, through `anyOf` otherwise.
+fn nullable(mut inner: Value) -> Value {
+    if inner.as_object().is_some_and(Map::is_empty) {
+        return inner;
+    }
+    if inner.get("oneOf").is_none() {
+        let types = match inner.get("bsonType") {
+            Some(Value::String(t)) => Some(vec![Value::String(t.clone())]),
+            Some(Value::Array(ts)) => Some(ts.clone()),
+            _ => None,
+        };
+        if let Some(mut types) = types {
+            types.push("null".into());
+            inner["bsonType"] = Value::Array(types);
+            if let Some(values) = inner.get_mut("enum").and_then(Value::as_array_mut) {
+                values.push(Value::Null);
+            }
+            return inner;
+        }
+    }
+    json!({"anyOf": [inner, {"bsonType": "null"}]})
+}
+
+/// A closed object for the Properties node `node`, with a discriminator
+/// `tag` pinned to its value when given.
+fn object<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    tag: Option<(&String, &String)>,
+    inlining: &mut Vec<&'a str>,
+) -> Value {
+    let Node::Properties {
+        required,
+        optional,
+        additional,
+    } = node
+    else {
+        return json!({});
+    };
+    let mut props = Map::new();
+    let mut names = Vec::new();
+    if let Some((tag, value)) = tag {
+        props.insert(tag.clone(), json!({"bsonType": "string", "enum": [value]}));
+        names.push(tag.clone());
+    }
+    for (key, child) in required {
+        let child_path = format!("{path}/properties/{}", escape_pointer(key));
+        props.insert(key.clone(), convert(schema, child, &child_path, inlining));
+        names.push(key.clone());
+    }
+    for (key, child) in optional {
+        let child_path = format!("{path}/optionalProperties/{}", escape_pointer(key));
+        props.insert(key.clone(), convert(schema, child, &child_path, inlining));
+    }
+    let mut out = json!({"bsonType": "object", "properties": props});
+    // MongoDB rejects an empty `required` array
+    if !names.is_empty() {
+        out["required"] = names.into();
+    }
+    if !additional {
+        out["additionalProperties"] = false.into();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+
+    #[test]
+    fn test_json_schema() {
+        let schema = compile(&json!({
+            "definitions": {
+                "node": {
+                    "properties": {"n": {"type": "uint8"}},
+                    "optionalProperties": {"next": {"ref": "node", "nullable": true}}
+                }
+            },
+            "properties": {
+                "status": {"enum": ["new", "done"], "nullable": true},
+                "at": {"type": "timestamp", "metadata": {"description": "Created"}},
+                "head": {"ref": "node"},
+                "pet": {"discriminator": "kind", "mapping": {"cat": {"properties": {}}}}
+            }
+        }))
+        .unwrap();
+        let doc = json_schema(&schema);
+        assert_eq!(doc["required"], json!(["at", "head", "pet", "status"]));
+        assert_eq!(doc["additionalProperties"], json!(false));
+        assert_eq!(doc["properties"]["_id"], json!({}));
+        assert_eq!(
+            doc["properties"]["status"],
+            json!({"bsonType": ["string", "null"], "enum": ["new", "done", null]})
+        );
+        assert_eq!(
+            doc["properties"]["at"]["bsonType"],
+            json!(["date", "string"])
+        );
+        assert_eq!(doc["properties"]["at"]["description"], json!("Created"));
+        let head = &doc["properties"]["head"];
+        assert_eq!(
+            head["properties"]["n"],
+            json!({"bsonType": "number", "multipleOf": 1, "minimum": 0, "maximum": 255})
+        );
+        assert_eq!(head["properties"]["next"], json!({}));
+        assert_eq!(
+            doc["properties"]["pet"]["oneOf"][0],
+            json!({
+                "bsonType": "object",
+                "properties": {"kind": {"bsonType": "string", "enum": ["cat"]}},
+                "required": ["kind"],
+                "additionalProperties": false
+            })
+        );
+
+        let cmd = coll_mod(&schema, "orders");
+        assert_eq!(cmd["collMod"], json!("orders"));
+        assert_eq!(cmd["validator"]["$jsonSchema"], doc);
+    }
+}
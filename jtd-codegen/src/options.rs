@@ -0,0 +1,650 @@
+/// Emitter options shared by every target.
+///
+/// Each emitter exposes `emit(schema)` for the default output and
+/// `emit_with_options(schema, &EmitOptions)` for configured output.
+/// Fields that only affect one target are prefixed with its name.
+/// `Default` reproduces the output of the plain `emit()` functions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmitOptions {
+    /// Lua language version the generated module must run on.
+    pub lua_dialect: LuaDialect,
+    /// How the host's JSON library represents JSON null in Lua, overriding
+    /// the module format's; see [`EmitOptions::lua_null`].
+    pub lua_null_sentinel: Option<LuaNullSentinel>,
+    /// How the Lua validator tells `[]` and `{}` apart, overriding the
+    /// module format's; see [`EmitOptions::lua_tables`].
+    pub lua_empty_table: Option<LuaEmptyTable>,
+    /// How the Lua module is packaged.
+    pub lua_format: LuaModuleFormat,
+    /// JavaScript environment the generated module is tuned for.
+    pub js_runtime: JsRuntime,
+    /// How the JavaScript module exposes its entry points, overriding the
+    /// runtime preset's; see [`EmitOptions::js_format`].
+    pub js_module_format: Option<JsModuleFormat>,
+    /// JSON value type the generated Rust validates.
+    pub rs_backend: RsBackend,
+    /// How the Python validator checks numbers.
+    pub py_numbers: PyNumbers,
+    /// Generate a `validate` that returns after the first error, which RFC
+    /// 8927 allows, instead of collecting them all.
+    pub fail_fast: bool,
+    /// Generate validators that stop collecting errors after this many,
+    /// which RFC 8927 also allows, to bound the work and memory hostile
+    /// instances cost. Where the language allows, entry points take the
+    /// limit as an optional last parameter defaulting to this one. Ignored
+    /// with `fail_fast`.
+    pub max_errors: Option<usize>,
+    /// Add a human-readable `message` to each error ("expected uint8, got
+    /// string") in the JavaScript, Python, Lua and Rust validators. Off by
+    /// default: RFC 8927 errors are just the two paths.
+    pub messages: bool,
+    /// Return each error's `instancePath` and `schemaPath` as arrays of
+    /// segments (`["items", "0"]`), as the upstream suite writes them,
+    /// instead of JSON Pointers, in the JavaScript, Python and Lua
+    /// validators. Consumers then never unescape `~0`/`~1`.
+    pub path_segments: bool,
+    /// Check each subschema that recurs in the schema (the same shape under
+    /// several properties) with one shared function in the JavaScript
+    /// validator, instead of repeating its checks at every use.
+    pub dedup: bool,
+    /// Check each definition referenced at most this many times, or whose
+    /// body is a single type or enum check, in place in the JavaScript
+    /// validator instead of through a function call. Recursive definitions
+    /// keep their function; 0, the default, inlines none.
+    pub inline_threshold: usize,
+    /// Export each enum's allowed values as a named constant
+    /// (`STATUS_VALUES`) alongside the validator.
+    pub enum_constants: bool,
+    /// Also emit `isValid(instance)` (`is_valid` where that is the
+    /// language's style), which makes the same checks as `validate` but
+    /// returns a boolean at the first failure, building no errors.
+    pub is_valid: bool,
+    /// Emit a `selfTest` function that validates every `metadata.examples`
+    /// entry and returns the ones that fail.
+    pub self_test: bool,
+    /// Emit a `warnings(instance)` function reporting properties whose
+    /// schema has `metadata.deprecated: true`, separately from errors.
+    pub deprecation_warnings: bool,
+    /// Emit a `validateAt(pointer, value)` entry point that validates a
+    /// fragment against the sub-schema at a schema path such as
+    /// `/properties/address`.
+    pub validate_at: bool,
+    /// Attach a `suggestion` (closest allowed name by edit distance) to
+    /// errors for unknown enum values, discriminator tags and additional
+    /// properties.
+    pub suggestions: bool,
+    /// Emit a `coerce(instance)` function that converts `"42"`/`"true"`
+    /// style strings (and, for integer types, numbers with a zero fraction)
+    /// where the schema expects numbers or booleans, for query strings and
+    /// form data.
+    pub coerce: bool,
+    /// Emit a `strip(instance)` function that removes the properties closed
+    /// Properties schemas do not declare, so gateways can drop extra fields
+    /// instead of rejecting them.
+    pub strip_additional: bool,
+    /// Emit an `applyDefaults(instance)` function that fills absent optional
+    /// properties with their `metadata.default`.
+    pub apply_defaults: bool,
+    /// Also emit `createSaxValidator()`, which validates from parse events
+    /// (`openObject`, `key`, `value`, ...) without building the document.
+    pub js_sax: bool,
+    /// Also emit `expressValidator(part)`, `fastifyValidator(part)` and
+    /// `fastifyValidatorCompiler`, which answer invalid requests with 400
+    /// and the error array.
+    pub js_middleware: bool,
+    /// Also export `validateAddress(instance)` and so on for each
+    /// definition, validating a fragment against it, with schema paths
+    /// starting at `/definitions/<name>`. Named as bundle entry points are;
+    /// see [`crate::emit_js::definition_entry_names`].
+    pub js_export_definitions: bool,
+    /// Minify the module: no comments or indentation, folded string
+    /// concatenations and short internal names. Exported names stay.
+    pub js_minify: bool,
+    /// Check enum values, known property names and discriminator tags
+    /// against `phf` static sets and maps. The generated Rust then needs
+    /// the `phf` crate with its `macros` feature.
+    pub rs_phf: bool,
+    /// Also emit `ValidatedJson<T>`, an Axum extractor that validates the
+    /// body before deserializing it. serde_json backend only.
+    pub rs_axum: bool,
+    /// Also emit `Validated<T>`, a serde `Deserialize` wrapper that rejects
+    /// values `validate` does not accept, and `deserialize_validated(json)`.
+    /// Needs the `serde` crate; serde_json backend only.
+    pub rs_deserialize: bool,
+    /// Also emit serde structs and enums for the schema's data, with the
+    /// root as `Root`; see [`crate::emit_rs::emit_types`]. They need the
+    /// `serde` crate with `derive`, and `chrono` for timestamps.
+    pub rs_types: bool,
+    /// Generate validators for `#![no_std]` crates, using only `core` and
+    /// `alloc`: the crate needs serde_json with `default-features = false`
+    /// and its `alloc` feature, and the file goes in a module of its own
+    /// (it declares `extern crate alloc`). serde_json backend only;
+    /// recursive definitions count their depth in a static rather than per
+    /// thread. The `rs_axum`, `rs_types` and `coerce` extras still need std.
+    pub rs_no_std: bool,
+    /// Append a `#[cfg(test)]` module checking the validator against a
+    /// skeleton instance and one violation per form; see [`crate::smoke`].
+    pub rs_tests: bool,
+    /// Package of the generated Kotlin file; `None` leaves it in the
+    /// default package.
+    pub kt_package: Option<String>,
+    /// Package of the generated `.proto` file; `None` declares none.
+    pub proto_package: Option<String>,
+    /// Prefix of the generated PostgreSQL function names, optionally
+    /// schema-qualified; `None` uses `jtd`.
+    pub sql_prefix: Option<String>,
+    /// `table.column` of a `jsonb` column to add a `CHECK` constraint to
+    /// after the functions; `None` emits the functions only.
+    pub sql_check: Option<String>,
+    /// Emit a `collMod` command applying the validator to this collection
+    /// instead of the bare validator document.
+    pub mongo_collection: Option<String>,
+    /// Emit a `mock(seed)` function returning a random instance of the
+    /// schema; see [`crate::mock`].
+    pub mock: bool,
+    /// Export `SHAPE`, a descriptor of the object keys, enum values and
+    /// discriminator tags at each path; see [`crate::shape`].
+    pub shape: bool,
+    /// Header comment lines recording the generator version, target,
+    /// schema hash and options, so a generated file can be traced back to
+    /// its inputs. `None` keeps the plain header.
+    pub banner: Option<Banner>,
+    /// Write marker lines around the code of each schema node, for
+    /// [`crate::source_map::extract`] to remove and turn into a source map.
+    /// Set by [`crate::generate::emit_with_source_map`]; the output is not
+    /// valid code until extracted.
+    pub source_map: bool,
+}
+
+impl EmitOptions {
+    /// The `max_errors` generated validators apply; `None` when failing
+    /// fast, which stops at the first error unconditionally.
+    pub fn error_limit(&self) -> Option<usize> {
+        self.max_errors.filter(|_| !self.fail_fast)
+    }
+
+    /// The module format the JavaScript validator uses: `js_module_format`
+    /// when set, else the one `js_runtime` implies.
+    pub fn js_format(&self) -> JsModuleFormat {
+        self.js_module_format
+            .unwrap_or_else(|| self.js_runtime.module_format())
+    }
+
+    /// The null sentinel the Lua validator uses: `lua_null_sentinel` when
+    /// set, else the one `lua_format` implies.
+    pub fn lua_null(&self) -> LuaNullSentinel {
+        self.lua_null_sentinel
+            .clone()
+            .unwrap_or_else(|| self.lua_format.null_sentinel())
+    }
+
+    /// The array convention the Lua validator uses: `lua_empty_table` when
+    /// set, else the one `lua_format` implies.
+    pub fn lua_tables(&self) -> LuaEmptyTable {
+        self.lua_empty_table
+            .unwrap_or_else(|| self.lua_format.empty_table())
+    }
+}
+
+/// What the banner records about a file's inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Banner {
+    /// `sha256:<hex>` of the schema, serialized compactly with sorted keys
+    /// so formatting changes do not change it.
+    pub schema_hash: String,
+    /// The command-line options the file was generated with.
+    pub options: Vec<String>,
+}
+
+impl Banner {
+    pub fn new(schema: &serde_json::Value, options: Vec<String>) -> Banner {
+        Banner {
+            schema_hash: crate::manifest::checksum(schema.to_string().as_bytes()),
+            options,
+        }
+    }
+
+    /// The banner text for `target`, one comment line each, without the
+    /// comment marker.
+    pub fn lines(&self, target: &str) -> Vec<String> {
+        let options = if self.options.is_empty() {
+            "(none)".to_string()
+        } else {
+            self.options.join(" ").replace(['\n', '\r'], " ")
+        };
+        vec![
+            format!(
+                "Generator: jtd-codegen {}, target {target}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            format!("Schema: {}", self.schema_hash),
+            format!("Options: {options}"),
+        ]
+    }
+}
+
+/// Lua language versions supported by the Lua emitter. A dialect changes
+/// integer checks, the division in suggestion helpers and how early exits
+/// are written; string checks use the same Lua patterns everywhere, since no
+/// dialect has regular expressions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LuaDialect {
+    /// Lua 5.1: numbers are doubles.
+    #[default]
+    Lua51,
+    /// Lua 5.3: integer/float number subtypes (`math.type`).
+    Lua53,
+    /// Lua 5.4: integer/float number subtypes, as 5.3.
+    Lua54,
+    /// LuaJIT 2.x: Lua 5.1 semantics plus `goto`.
+    LuaJit,
+}
+
+impl LuaDialect {
+    pub fn parse(s: &str) -> Option<LuaDialect> {
+        match s {
+            "5.1" | "lua51" => Some(LuaDialect::Lua51),
+            "5.3" | "lua53" => Some(LuaDialect::Lua53),
+            "5.4" | "lua54" => Some(LuaDialect::Lua54),
+            "luajit" | "jit" => Some(LuaDialect::LuaJit),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LuaDialect::Lua51 => "Lua 5.1",
+            LuaDialect::Lua53 => "Lua 5.3",
+            LuaDialect::Lua54 => "Lua 5.4",
+            LuaDialect::LuaJit => "LuaJIT",
+        }
+    }
+
+    /// True when numbers carry an integer/float subtype (`math.type`).
+    pub fn has_integer_subtype(&self) -> bool {
+        matches!(self, LuaDialect::Lua53 | LuaDialect::Lua54)
+    }
+
+    /// True when there is a floor division operator (`//`).
+    pub fn has_floor_division(&self) -> bool {
+        matches!(self, LuaDialect::Lua53 | LuaDialect::Lua54)
+    }
+
+    /// True when there is `goto`, which Lua 5.2 added and LuaJIT supports.
+    pub fn has_goto(&self) -> bool {
+        !matches!(self, LuaDialect::Lua51)
+    }
+}
+
+/// The value a Lua JSON library decodes JSON null to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LuaNullSentinel {
+    /// `dkjson.null` (requires the `dkjson` module).
+    #[default]
+    Dkjson,
+    /// `cjson.null` (lua-cjson, OpenResty).
+    Cjson,
+    /// A Lua expression evaluated once at module load, e.g. `ngx.null`.
+    Custom(String),
+    /// Null decodes to `nil` and cannot be told apart from a missing key.
+    Nil,
+}
+
+impl LuaNullSentinel {
+    /// Parse a CLI value: `dkjson`, `cjson`, `nil`, or any other Lua expression.
+    pub fn parse(s: &str) -> LuaNullSentinel {
+        match s {
+            "dkjson" => LuaNullSentinel::Dkjson,
+            "cjson" => LuaNullSentinel::Cjson,
+            "nil" => LuaNullSentinel::Nil,
+            expr => LuaNullSentinel::Custom(expr.to_string()),
+        }
+    }
+}
+
+/// How the Lua emitter distinguishes JSON arrays from objects. Lua has a
+/// single table type, so an empty `{}` is ambiguous without a convention
+/// shared with the JSON decoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LuaEmptyTable {
+    /// Honour a `__jsontype` metatable field (dkjson); untagged empty tables
+    /// are accepted as either.
+    #[default]
+    Metatable,
+    /// Arrays carry a numeric `n` field (`table.pack` convention); everything
+    /// else is an object.
+    NField,
+    /// Arrays carry lua-cjson's `array_mt`/`empty_array_mt` metatable
+    /// (`decode_array_with_array_mt`); everything else is an object.
+    CjsonArrayMt,
+    /// No marker: non-empty tables are classified by their border, empty
+    /// tables are accepted as either.
+    Lenient,
+}
+
+impl LuaEmptyTable {
+    pub fn parse(s: &str) -> Option<LuaEmptyTable> {
+        match s {
+            "metatable" => Some(LuaEmptyTable::Metatable),
+            "n-field" | "n" => Some(LuaEmptyTable::NField),
+            "cjson" => Some(LuaEmptyTable::CjsonArrayMt),
+            "lenient" => Some(LuaEmptyTable::Lenient),
+            _ => None,
+        }
+    }
+}
+
+/// Packaging of the generated Lua module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LuaModuleFormat {
+    /// A plain module returning `{ validate = ... }`.
+    #[default]
+    Plain,
+    /// An OpenResty module: globals cached as locals, a `cjson.safe`
+    /// decoder, and `check_request_body()`/`access()` for nginx phases.
+    OpenResty,
+}
+
+impl LuaModuleFormat {
+    pub fn parse(s: &str) -> Option<LuaModuleFormat> {
+        match s {
+            "plain" => Some(LuaModuleFormat::Plain),
+            "openresty" | "ngx" => Some(LuaModuleFormat::OpenResty),
+            _ => None,
+        }
+    }
+
+    /// The null sentinel of the JSON library the format ships with:
+    /// lua-cjson's for OpenResty, dkjson's otherwise.
+    pub fn null_sentinel(&self) -> LuaNullSentinel {
+        match self {
+            LuaModuleFormat::Plain => LuaNullSentinel::Dkjson,
+            LuaModuleFormat::OpenResty => LuaNullSentinel::Cjson,
+        }
+    }
+
+    /// The array marker of the JSON library the format ships with.
+    pub fn empty_table(&self) -> LuaEmptyTable {
+        match self {
+            LuaModuleFormat::Plain => LuaEmptyTable::Metatable,
+            LuaModuleFormat::OpenResty => LuaEmptyTable::CjsonArrayMt,
+        }
+    }
+}
+
+/// JavaScript runtime presets. A preset picks the default module format; the
+/// validator body is the same plain ES2015 for every runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsRuntime {
+    /// Plain ES module (`export function validate`), as emitted by `emit()`.
+    /// Deno and edge isolates such as Cloudflare Workers load it as is, so
+    /// `deno`, `edge` and `workers` parse to this preset.
+    #[default]
+    Generic,
+    /// Node.js: CommonJS (`module.exports`), loadable from `.js` without
+    /// `"type": "module"`.
+    Node,
+    /// Browser `<script>` tag: IIFE exposing `globalThis.JtdValidator`.
+    Browser,
+}
+
+/// How a generated JavaScript module exposes its entry points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsModuleFormat {
+    /// `export function validate`
+    Esm,
+    /// `module.exports = { validate }`
+    CommonJs,
+    /// `(function () { ... })();` assigning to a global
+    Iife,
+    /// Plain `function validate` declarations and nothing exported, for
+    /// embedded engines that evaluate the source and call functions by name.
+    Bare,
+}
+
+impl JsModuleFormat {
+    pub fn parse(s: &str) -> Option<JsModuleFormat> {
+        match s {
+            "esm" => Some(JsModuleFormat::Esm),
+            "cjs" | "commonjs" => Some(JsModuleFormat::CommonJs),
+            "iife" | "global" => Some(JsModuleFormat::Iife),
+            "bare" => Some(JsModuleFormat::Bare),
+            _ => None,
+        }
+    }
+}
+
+impl JsRuntime {
+    pub fn parse(s: &str) -> Option<JsRuntime> {
+        match s {
+            "generic" | "esm" | "deno" | "edge" | "workers" => Some(JsRuntime::Generic),
+            "node" => Some(JsRuntime::Node),
+            "browser" => Some(JsRuntime::Browser),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsRuntime::Generic => "generic",
+            JsRuntime::Node => "Node.js",
+            JsRuntime::Browser => "browser",
+        }
+    }
+
+    pub fn module_format(&self) -> JsModuleFormat {
+        match self {
+            JsRuntime::Node => JsModuleFormat::CommonJs,
+            JsRuntime::Browser => JsModuleFormat::Iife,
+            JsRuntime::Generic => JsModuleFormat::Esm,
+        }
+    }
+}
+
+/// How the Python emitter checks the numeric types. `True` and `False` are
+/// `int`s in Python, so both modes reject `bool` explicitly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PyNumbers {
+    /// RFC 8927's JSON view: a number with a zero fraction is an integer,
+    /// so `3.0` is a `uint8`. Anything `json.loads` returns as a number,
+    /// `NaN` and `Infinity` included, passes the float types.
+    #[default]
+    Loose,
+    /// Integer types need a Python `int`, rejecting `3.0`, and the float
+    /// types reject `NaN` and `Infinity`, which JSON cannot express, for
+    /// values built in Python rather than parsed from JSON.
+    Strict,
+}
+
+impl PyNumbers {
+    pub fn parse(s: &str) -> Option<PyNumbers> {
+        match s {
+            "loose" => Some(PyNumbers::Loose),
+            "strict" => Some(PyNumbers::Strict),
+            _ => None,
+        }
+    }
+}
+
+/// JSON value representation targeted by the Rust emitter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RsBackend {
+    /// `serde_json::Value`
+    #[default]
+    SerdeJson,
+    /// `simd_json::BorrowedValue`, via the `simd_json::prelude` traits.
+    SimdJson,
+}
+
+impl RsBackend {
+    pub fn parse(s: &str) -> Option<RsBackend> {
+        match s {
+            "serde_json" | "serde-json" => Some(RsBackend::SerdeJson),
+            "simd_json" | "simd-json" => Some(RsBackend::SimdJson),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options() {
+        let opts = EmitOptions::default();
+        assert_eq!(opts.lua_dialect, LuaDialect::Lua51);
+        assert_eq!(opts.lua_null_sentinel, None);
+        assert_eq!(opts.lua_null(), LuaNullSentinel::Dkjson);
+        assert_eq!(opts.lua_empty_table, None);
+        assert_eq!(opts.lua_tables(), LuaEmptyTable::Metatable);
+        assert_eq!(opts.lua_format, LuaModuleFormat::Plain);
+        assert_eq!(opts.js_runtime, JsRuntime::Generic);
+        assert_eq!(opts.js_module_format, None);
+        assert_eq!(opts.js_format(), JsModuleFormat::Esm);
+        assert_eq!(opts.rs_backend, RsBackend::SerdeJson);
+        assert_eq!(opts.py_numbers, PyNumbers::Loose);
+        assert!(!opts.enum_constants);
+        assert!(!opts.self_test);
+        assert!(!opts.deprecation_warnings);
+        assert!(!opts.validate_at);
+        assert!(!opts.is_valid);
+        assert!(!opts.suggestions);
+        assert!(!opts.coerce);
+        assert!(!opts.strip_additional);
+        assert!(!opts.apply_defaults);
+        assert!(!opts.js_sax);
+        assert!(!opts.js_middleware);
+        assert!(!opts.js_export_definitions);
+        assert!(!opts.js_minify);
+        assert!(!opts.rs_phf);
+        assert!(!opts.rs_axum);
+        assert!(!opts.rs_deserialize);
+        assert!(!opts.rs_tests);
+        assert!(!opts.rs_types);
+        assert!(!opts.rs_no_std);
+        assert_eq!(opts.kt_package, None);
+        assert_eq!(opts.proto_package, None);
+        assert_eq!(opts.sql_prefix, None);
+        assert_eq!(opts.sql_check, None);
+        assert_eq!(opts.mongo_collection, None);
+        assert!(!opts.mock);
+        assert!(!opts.shape);
+        assert_eq!(opts.banner, None);
+    }
+
+    #[test]
+    fn test_banner() {
+        let a = Banner::new(
+            &serde_json::json!({"type": "string", "nullable": true}),
+            vec![],
+        );
+        let b: serde_json::Value =
+            serde_json::from_str("{ \"nullable\": true,\n  \"type\": \"string\" }").unwrap();
+        assert_eq!(a.schema_hash, Banner::new(&b, vec![]).schema_hash);
+        assert_eq!(a.schema_hash.len(), "sha256:".len() + 64);
+        let banner = Banner::new(&b, vec!["--coerce".into(), "a\nb".into()]);
+        let lines = banner.lines("js");
+        assert!(lines[0].starts_with("Generator: jtd-codegen 0."));
+        assert!(lines[0].ends_with(", target js"));
+        assert_eq!(lines[2], "Options: --coerce a b");
+        assert_eq!(banner.lines("rust")[1], lines[1]);
+    }
+
+    #[test]
+    fn test_lua_null_sentinel_parse() {
+        assert_eq!(LuaNullSentinel::parse("dkjson"), LuaNullSentinel::Dkjson);
+        assert_eq!(LuaNullSentinel::parse("cjson"), LuaNullSentinel::Cjson);
+        assert_eq!(LuaNullSentinel::parse("nil"), LuaNullSentinel::Nil);
+        assert_eq!(
+            LuaNullSentinel::parse("ngx.null"),
+            LuaNullSentinel::Custom("ngx.null".into())
+        );
+    }
+
+    #[test]
+    fn test_py_numbers_parse() {
+        assert_eq!(PyNumbers::parse("loose"), Some(PyNumbers::Loose));
+        assert_eq!(PyNumbers::parse("strict"), Some(PyNumbers::Strict));
+        assert_eq!(PyNumbers::parse("exact"), None);
+    }
+
+    #[test]
+    fn test_lua_dialect_parse() {
+        assert_eq!(LuaDialect::parse("5.1"), Some(LuaDialect::Lua51));
+        assert_eq!(LuaDialect::parse("5.3"), Some(LuaDialect::Lua53));
+        assert_eq!(LuaDialect::parse("5.4"), Some(LuaDialect::Lua54));
+        assert_eq!(LuaDialect::parse("luajit"), Some(LuaDialect::LuaJit));
+        assert_eq!(LuaDialect::parse("5.2"), None);
+    }
+
+    #[test]
+    fn test_lua_empty_table_parse() {
+        assert_eq!(
+            LuaEmptyTable::parse("metatable"),
+            Some(LuaEmptyTable::Metatable)
+        );
+        assert_eq!(LuaEmptyTable::parse("n-field"), Some(LuaEmptyTable::NField));
+        assert_eq!(
+            LuaEmptyTable::parse("cjson"),
+            Some(LuaEmptyTable::CjsonArrayMt)
+        );
+        assert_eq!(
+            LuaEmptyTable::parse("lenient"),
+            Some(LuaEmptyTable::Lenient)
+        );
+        assert_eq!(LuaEmptyTable::parse("array"), None);
+    }
+
+    #[test]
+    fn test_lua_module_format_parse() {
+        assert_eq!(
+            LuaModuleFormat::parse("plain"),
+            Some(LuaModuleFormat::Plain)
+        );
+        assert_eq!(
+            LuaModuleFormat::parse("openresty"),
+            Some(LuaModuleFormat::OpenResty)
+        );
+        assert_eq!(LuaModuleFormat::parse("nginx"), None);
+    }
+
+    #[test]
+    fn test_openresty_defaults_to_cjson() {
+        let mut opts = EmitOptions {
+            lua_format: LuaModuleFormat::OpenResty,
+            ..Default::default()
+        };
+        assert_eq!(opts.lua_null(), LuaNullSentinel::Cjson);
+        assert_eq!(opts.lua_tables(), LuaEmptyTable::CjsonArrayMt);
+        opts.lua_null_sentinel = Some(LuaNullSentinel::Nil);
+        opts.lua_empty_table = Some(LuaEmptyTable::Lenient);
+        assert_eq!(opts.lua_null(), LuaNullSentinel::Nil);
+        assert_eq!(opts.lua_tables(), LuaEmptyTable::Lenient);
+    }
+
+    #[test]
+    fn test_js_runtime_presets() {
+        assert_eq!(JsRuntime::parse("node"), Some(JsRuntime::Node));
+        assert_eq!(JsRuntime::parse("bun"), None);
+        assert_eq!(JsRuntime::Generic.module_format(), JsModuleFormat::Esm);
+        assert_eq!(JsRuntime::Node.module_format(), JsModuleFormat::CommonJs);
+        assert_eq!(JsRuntime::Browser.module_format(), JsModuleFormat::Iife);
+        assert_eq!(JsRuntime::parse("deno"), Some(JsRuntime::Generic));
+        assert_eq!(JsRuntime::parse("workers"), Some(JsRuntime::Generic));
+    }
+
+    #[test]
+    fn test_rs_backend_parse() {
+        assert_eq!(RsBackend::parse("serde_json"), Some(RsBackend::SerdeJson));
+        assert_eq!(RsBackend::parse("simd-json"), Some(RsBackend::SimdJson));
+        assert_eq!(RsBackend::parse("sonic"), None);
+    }
+
+    #[test]
+    fn test_lua_dialect_capabilities() {
+        assert!(!LuaDialect::Lua51.has_integer_subtype());
+        assert!(!LuaDialect::LuaJit.has_integer_subtype());
+        assert!(LuaDialect::Lua54.has_integer_subtype());
+    }
+}
@@ -0,0 +1,159 @@
+/// Deep-partial schemas for PATCH payloads: every required property becomes
+/// optional, so a JSON merge patch body can be validated against the same
+/// schema as the full resource. Types, enums and closed objects are still
+/// enforced for whatever the patch does contain.
+///
+/// Without a depth every Properties form is relaxed, including those in
+/// definitions. With `Some(n)` only the first `n` levels of Properties forms
+/// are; deeper objects must be complete, as a patch that replaces a nested
+/// object wholesale would send them. Refs reached within that depth are
+/// inlined, relaxed to the remaining depth. Errors under a relaxed property
+/// report its schema path as `/optionalProperties/<name>`.
+use crate::ast::{CompiledSchema, Node};
+use std::collections::BTreeMap;
+
+/// The partial variant of `schema`, relaxed to `depth` levels (all when `None`).
+pub fn partial(schema: &CompiledSchema, depth: Option<usize>) -> CompiledSchema {
+    let defs = &schema.definitions;
+    let definitions = match depth {
+        None => defs
+            .iter()
+            .map(|(name, node)| (name.clone(), relax(node, None, defs, &mut Vec::new())))
+            .collect(),
+        Some(_) => defs.clone(),
+    };
+    CompiledSchema {
+        root: relax(&schema.root, depth, defs, &mut Vec::new()),
+        definitions,
+        metadata: schema.metadata.clone(),
+    }
+}
+
+/// `node` with its first `depth` levels of Properties forms relaxed. With no
+/// depth, refs are left alone since their definitions are relaxed too.
+/// Otherwise they are inlined; `inlining` holds the refs inlined since the
+/// last Properties level, so a ref cycle that never reaches one stays a ref
+/// instead of expanding forever.
+fn relax<'a>(
+    node: &'a Node,
+    depth: Option<usize>,
+    definitions: &'a BTreeMap<String, Node>,
+    inlining: &mut Vec<&'a str>,
+) -> Node {
+    if depth == Some(0) {
+        return node.clone();
+    }
+    match node {
+        Node::Ref { name } if depth.is_some() && !inlining.contains(&name.as_str()) => {
+            inlining.push(name);
+            let inlined = relax(&definitions[name], depth, definitions, inlining);
+            inlining.pop();
+            inlined
+        }
+        Node::Properties {
+            required,
+            optional,
+            additional,
+        } => {
+            let child_depth = depth.map(|d| d - 1);
+            let optional = required
+                .iter()
+                .chain(optional)
+                .map(|(key, child)| {
+                    let child = relax(child, child_depth, definitions, &mut Vec::new());
+                    (key.clone(), child)
+                })
+                .collect();
+            Node::Properties {
+                required: BTreeMap::new(),
+                optional,
+                additional: *additional,
+            }
+        }
+        Node::Discriminator { tag, mapping } => Node::Discriminator {
+            tag: tag.clone(),
+            mapping: mapping
+                .iter()
+                .map(|(key, variant)| (key.clone(), relax(variant, depth, definitions, inlining)))
+                .collect(),
+        },
+        Node::Elements { schema } => Node::Elements {
+            schema: Box::new(relax(schema, depth, definitions, inlining)),
+        },
+        Node::Values { schema } => Node::Values {
+            schema: Box::new(relax(schema, depth, definitions, inlining)),
+        },
+        Node::Nullable { inner } => Node::Nullable {
+            inner: Box::new(relax(inner, depth, definitions, inlining)),
+        },
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => node.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use crate::interpret::validate;
+    use serde_json::json;
+
+    fn schema() -> CompiledSchema {
+        compile(&json!({
+            "definitions": {
+                "address": {"properties": {"street": {"type": "string"}, "city": {"type": "string"}}},
+                "tree": {"properties": {"value": {"type": "int32"}}, "optionalProperties": {"children": {"elements": {"ref": "tree"}}}}
+            },
+            "properties": {
+                "name": {"type": "string"},
+                "address": {"ref": "address"},
+                "tree": {"ref": "tree"}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deep_partial() {
+        let relaxed = partial(&schema(), None);
+        assert!(validate(&relaxed, &json!({})).is_empty());
+        assert!(validate(&relaxed, &json!({"address": {"city": "Leeds"}})).is_empty());
+        assert!(validate(&relaxed, &json!({"tree": {"children": [{}]}})).is_empty());
+        // Present values are still checked, and objects stay closed
+        assert_eq!(
+            validate(&relaxed, &json!({"name": 1, "extra": true})),
+            vec![
+                (
+                    "/name".to_string(),
+                    "/optionalProperties/name/type".to_string()
+                ),
+                ("/extra".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partial_depth() {
+        let schema = schema();
+        let relaxed = partial(&schema, Some(1));
+        assert_eq!(relaxed.definitions, schema.definitions);
+        assert!(validate(&relaxed, &json!({"name": "Ann"})).is_empty());
+        // Nested objects must be complete below the depth
+        assert_eq!(
+            validate(&relaxed, &json!({"address": {"city": "Leeds"}})),
+            vec![(
+                "/address".to_string(),
+                "/definitions/address/properties/street".to_string()
+            )]
+        );
+
+        let relaxed = partial(&schema, Some(2));
+        assert!(validate(&relaxed, &json!({"tree": {"children": []}})).is_empty());
+        assert_eq!(
+            validate(&relaxed, &json!({"tree": {"children": [{}]}})),
+            vec![(
+                "/tree/children/0".to_string(),
+                "/definitions/tree/properties/value".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,503 @@
+/// proto3 IDL from JTD schemas, so protobuf services can share the JTD
+/// source of truth.
+///
+/// - Properties forms become messages, numbered in declaration order
+///   (required properties first). Keys that are not proto identifiers have
+///   other characters replaced by `_` and keep their JSON name through
+///   `json_name`. Optional and nullable scalars are `optional`.
+/// - A discriminator is a message with a `oneof` named after the tag and
+///   one variant message per mapping value; the tag itself is implied by
+///   the case set.
+/// - Enums get an `_UNSPECIFIED` zero value, then one value per JTD value,
+///   upper snake case and prefixed with the enum's name, as proto3 style
+///   requires (so their protobuf JSON spelling differs from the JTD one).
+/// - Definitions of those forms become top-level types named after the
+///   definition; others are inlined. Anonymous types are named from their
+///   parent and key, and a root of those forms is `Root`.
+/// - Elements are `repeated` and values `map<string, T>`. Timestamps are
+///   `google.protobuf.Timestamp`; what proto3 cannot type (the empty form,
+///   lists or maps inside lists or maps, recursive inlined refs) is
+///   `google.protobuf.Value`. `metadata.description` becomes a comment.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::graphql::pascal;
+use crate::options::EmitOptions;
+use crate::source_map;
+
+const TIMESTAMP: &str = "google.protobuf.Timestamp";
+const VALUE: &str = "google.protobuf.Value";
+
+/// The `.proto` file for `schema`, in `opts.proto_package` when set.
+pub fn to_proto(schema: &CompiledSchema, opts: &EmitOptions) -> String {
+    let mut proto = Proto {
+        schema,
+        defs: BTreeMap::new(),
+        used: BTreeSet::new(),
+        imports: BTreeSet::new(),
+        types: Vec::new(),
+        source_map: opts.source_map,
+    };
+    for (name, node) in &schema.definitions {
+        if is_named(strip_nullable(node)) {
+            let type_name = proto.name(name);
+            proto.defs.insert(name, type_name);
+        }
+    }
+    for (name, node) in &schema.definitions {
+        if let Some(type_name) = proto.defs.get(name.as_str()).cloned() {
+            proto.named(
+                &type_name,
+                strip_nullable(node),
+                &format!("/definitions/{}", escape_pointer(name)),
+            );
+        }
+    }
+    if is_named(strip_nullable(&schema.root)) {
+        let type_name = proto.name("Root");
+        proto.named(&type_name, strip_nullable(&schema.root), "");
+    }
+
+    let mut out = String::new();
+    if let Some(banner) = &opts.banner {
+        for line in banner.lines("proto") {
+            out.push_str(&format!("// {line}\n"));
+        }
+    }
+    out.push_str("syntax = \"proto3\";\n");
+    if let Some(package) = &opts.proto_package {
+        out.push_str(&format!("\npackage {package};\n"));
+    }
+    if !proto.imports.is_empty() {
+        out.push('\n');
+        for import in &proto.imports {
+            out.push_str(&format!("import \"{import}\";\n"));
+        }
+    }
+    for block in proto.types {
+        out.push('\n');
+        out.push_str(&block);
+    }
+    out
+}
+
+/// A field's type: `T`, `repeated T` or `map<string, T>`.
+enum Field {
+    Single { ty: String, message: bool },
+    Repeated(String),
+    Map(String),
+}
+
+struct Proto<'a> {
+    schema: &'a CompiledSchema,
+    /// Type names of the definitions emitted as named types.
+    defs: BTreeMap<&'a str, String>,
+    /// Type names taken so far.
+    used: BTreeSet<String>,
+    /// Well-known type files imported.
+    imports: BTreeSet<&'static str>,
+    /// Type definitions, parents before the types named after them.
+    types: Vec<String>,
+    /// Wrap the lines of each node in source-map markers.
+    source_map: bool,
+}
+
+impl<'a> Proto<'a> {
+    /// `code` in source-map markers for `node`, when asked for.
+    fn mark(&self, node: &Node, code: String) -> String {
+        if self.source_map {
+            source_map::wrap(node, &code)
+        } else {
+            code
+        }
+    }
+
+    /// A fresh type name from `hint`.
+    fn name(&mut self, hint: &str) -> String {
+        let mut base = pascal(hint);
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            base.insert(0, 'T');
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.used.contains(&name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+        self.used.insert(name.clone());
+        name
+    }
+
+    fn well_known(&mut self, ty: &'static str) -> Field {
+        self.imports.insert(match ty {
+            TIMESTAMP => "google/protobuf/timestamp.proto",
+            _ => "google/protobuf/struct.proto",
+        });
+        Field::Single {
+            ty: ty.to_string(),
+            message: true,
+        }
+    }
+
+    /// The field type of `node` at `path`, and whether it admits null.
+    /// Anonymous types are named from `hint`; `inlining` holds the
+    /// definitions being inlined on the way here.
+    fn field(
+        &mut self,
+        node: &'a Node,
+        path: &str,
+        hint: &str,
+        inlining: &mut Vec<&'a str>,
+    ) -> (Field, bool) {
+        match node {
+            Node::Nullable { inner } => (self.field(inner, path, hint, inlining).0, true),
+            Node::Empty => (self.well_known(VALUE), true),
+            Node::Ref { name } => {
+                let target = &self.schema.definitions[name];
+                if let Some(type_name) = self.defs.get(name.as_str()) {
+                    let message = !matches!(strip_nullable(target), Node::Enum { .. });
+                    let ty = type_name.clone();
+                    return (
+                        Field::Single { ty, message },
+                        matches!(target, Node::Nullable { .. }),
+                    );
+                }
+                if inlining.contains(&name.as_str()) {
+                    return (self.well_known(VALUE), true);
+                }
+                inlining.push(name);
+                let out = self.field(
+                    target,
+                    &format!("/definitions/{}", escape_pointer(name)),
+                    name,
+                    inlining,
+                );
+                inlining.pop();
+                out
+            }
+            Node::Type {
+                type_kw: TypeKeyword::Timestamp,
+            } => (self.well_known(TIMESTAMP), false),
+            Node::Type { type_kw } => {
+                let ty = match type_kw {
+                    TypeKeyword::Boolean => "bool",
+                    TypeKeyword::String => "string",
+                    TypeKeyword::Float32 => "float",
+                    TypeKeyword::Float64 => "double",
+                    TypeKeyword::Int8 | TypeKeyword::Int16 | TypeKeyword::Int32 => "int32",
+                    _ => "uint32",
+                };
+                let ty = ty.to_string();
+                (Field::Single { ty, message: false }, false)
+            }
+            Node::Elements { schema: item } | Node::Values { schema: item } => {
+                let item_path = match node {
+                    Node::Elements { .. } => format!("{path}/elements"),
+                    _ => format!("{path}/values"),
+                };
+                let item_hint = format!("{hint}Item");
+                let ty = match self.field(item, &item_path, &item_hint, inlining).0 {
+                    Field::Single { ty, .. } => ty,
+                    // No lists or maps of lists or maps
+                    _ => VALUE.to_string(),
+                };
+                if ty == VALUE {
+                    self.well_known(VALUE);
+                }
+                match node {
+                    Node::Elements { .. } => (Field::Repeated(ty), false),
+                    _ => (Field::Map(ty), false),
+                }
+            }
+            node => {
+                let type_name = self.name(hint);
+                self.named(&type_name, node, path);
+                let message = !matches!(node, Node::Enum { .. });
+                (
+                    Field::Single {
+                        ty: type_name,
+                        message,
+                    },
+                    false,
+                )
+            }
+        }
+    }
+
+    /// Emit type `name` for the named form `node` at `path`.
+    fn named(&mut self, name: &str, node: &'a Node, path: &str) {
+        let slot = self.types.len();
+        self.types.push(String::new());
+        let mut out = comment(self.schema.description(path), "");
+        match node {
+            Node::Enum { values } => {
+                let prefix = upper_snake(name);
+                out.push_str(&format!("enum {name} {{\n"));
+                out.push_str(&format!("  {prefix}_UNSPECIFIED = 0;\n"));
+                let mut seen = BTreeSet::from([format!("{prefix}_UNSPECIFIED")]);
+                for (i, value) in values.iter().enumerate() {
+                    let mut value_name = format!("{prefix}_{}", upper_snake(value));
+                    while !seen.insert(value_name.clone()) {
+                        value_name.push('_');
+                    }
+                    out.push_str(&format!("  {value_name} = {};\n", i + 1));
+                }
+                out.push_str("}\n");
+            }
+            Node::Properties { .. } => {
+                out.push_str(&format!("message {name} {{\n"));
+                out.push_str(&self.fields(name, node, path));
+                out.push_str("}\n");
+            }
+            Node::Discriminator { tag, mapping } => {
+                out.push_str(&format!("message {name} {{\n"));
+                if !mapping.is_empty() {
+                    out.push_str(&format!("  oneof {} {{\n", field_name(tag)));
+                    for (i, (value, variant)) in mapping.iter().enumerate() {
+                        let variant_path = format!("{path}/mapping/{}", escape_pointer(value));
+                        let variant_name = self.name(&format!("{name}_{value}"));
+                        let slot = self.types.len();
+                        self.types.push(String::new());
+                        let mut block = comment(self.schema.description(&variant_path), "");
+                        block.push_str(&format!("message {variant_name} {{\n"));
+                        block.push_str(&self.fields(&variant_name, variant, &variant_path));
+                        block.push_str("}\n");
+                        self.types[slot] = self.mark(variant, block);
+                        let line = format!(
+                            "    {variant_name} {} = {}{};\n",
+                            field_name(value),
+                            i + 1,
+                            json_name(value)
+                        );
+                        out.push_str(&self.mark(variant, line));
+                    }
+                    out.push_str("  }\n");
+                }
+                out.push_str("}\n");
+            }
+            _ => {}
+        }
+        self.types[slot] = self.mark(node, out);
+    }
+
+    /// The fields of the Properties form `node` of message `parent`.
+    fn fields(&mut self, parent: &str, node: &'a Node, path: &str) -> String {
+        let Node::Properties {
+            required, optional, ..
+        } = node
+        else {
+            return String::new();
+        };
+        let mut out = String::new();
+        let props = required
+            .iter()
+            .map(|(key, child)| (key, child, true))
+            .chain(optional.iter().map(|(key, child)| (key, child, false)));
+        for (i, (key, child, is_required)) in props.enumerate() {
+            let child_path = if is_required {
+                format!("{path}/properties/{}", escape_pointer(key))
+            } else {
+                format!("{path}/optionalProperties/{}", escape_pointer(key))
+            };
+            let hint = format!("{parent}_{key}");
+            let (field, nullable) = self.field(child, &child_path, &hint, &mut Vec::new());
+            let ty = match field {
+                Field::Single { ty, message } if !message && (!is_required || nullable) => {
+                    format!("optional {ty}")
+                }
+                Field::Single { ty, .. } => ty,
+                Field::Repeated(ty) => format!("repeated {ty}"),
+                Field::Map(ty) => format!("map<string, {ty}>"),
+            };
+            let mut line = comment(self.schema.description(&child_path), "  ");
+            line.push_str(&format!(
+                "  {ty} {} = {}{};\n",
+                field_name(key),
+                i + 1,
+                json_name(key)
+            ));
+            out.push_str(&self.mark(child, line));
+        }
+        out
+    }
+}
+
+fn strip_nullable(node: &Node) -> &Node {
+    match node {
+        Node::Nullable { inner } => inner,
+        node => node,
+    }
+}
+
+/// Forms that become a named type.
+fn is_named(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::Properties { .. } | Node::Discriminator { .. } | Node::Enum { .. }
+    )
+}
+
+/// `key` as a proto identifier.
+fn field_name(key: &str) -> String {
+    let mut out: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        out.insert(0, 'f');
+    }
+    out
+}
+
+/// A `json_name` option keeping `key` when its field was renamed.
+fn json_name(key: &str) -> String {
+    if field_name(key) == key {
+        return String::new();
+    }
+    format!(" [json_name = {}]", serde_json::Value::from(key))
+}
+
+/// `userCreated` or `user-created` as `USER_CREATED`.
+fn upper_snake(s: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    let out = out.trim_end_matches('_').to_string();
+    if out.is_empty() {
+        "VALUE".to_string()
+    } else {
+        out
+    }
+}
+
+/// `desc` as `//` comment lines indented by `indent`, or nothing.
+fn comment(desc: Option<&str>, indent: &str) -> String {
+    desc.map(|desc| {
+        desc.lines()
+            .map(|line| format!("{indent}// {line}\n").replace("// \n", "//\n"))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_proto() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree_node": {
+                    "properties": {"value": {"type": "uint8"}},
+                    "optionalProperties": {"children": {"elements": {"ref": "tree_node"}}}
+                }
+            },
+            "properties": {
+                "root": {"ref": "tree_node", "metadata": {"description": "The top."}},
+                "status": {"enum": ["new", "inProgress"], "nullable": true},
+                "at": {"type": "timestamp"},
+                "extra": {"values": {"elements": {"type": "int8"}}},
+                "first-name": {"type": "string"},
+                "event": {
+                    "discriminator": "type",
+                    "mapping": {"user-created": {"properties": {"name": {"type": "string"}}}}
+                }
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            proto_package: Some("example.orders".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_proto(&schema, &opts),
+            r#"syntax = "proto3";
+
+package example.orders;
+
+import "google/protobuf/struct.proto";
+import "google/protobuf/timestamp.proto";
+
+message TreeNode {
+  uint32 value = 1;
+  repeated TreeNode children = 2;
+}
+
+message Root {
+  google.protobuf.Timestamp at = 1;
+  RootEvent event = 2;
+  map<string, google.protobuf.Value> extra = 3;
+  string first_name = 4 [json_name = "first-name"];
+  // The top.
+  TreeNode root = 5;
+  optional RootStatus status = 6;
+}
+
+message RootEvent {
+  oneof type {
+    RootEventUserCreated user_created = 1 [json_name = "user-created"];
+  }
+}
+
+message RootEventUserCreated {
+  string name = 1;
+}
+
+enum RootStatus {
+  ROOT_STATUS_UNSPECIFIED = 0;
+  ROOT_STATUS_NEW = 1;
+  ROOT_STATUS_IN_PROGRESS = 2;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(upper_snake("userCreated"), "USER_CREATED");
+        assert_eq!(upper_snake("user-created"), "USER_CREATED");
+        assert_eq!(upper_snake("HTTP2"), "HTTP2");
+        assert_eq!(upper_snake("--"), "VALUE");
+        assert_eq!(field_name("2fa"), "f2fa");
+        let schema = compile(&json!({"enum": ["a", "A", "unspecified"]})).unwrap();
+        let proto = to_proto(&schema, &EmitOptions::default());
+        assert!(proto.starts_with("syntax = \"proto3\";\n\nenum Root {\n"));
+        assert!(proto.contains("  ROOT_A = 1;\n  ROOT_A_ = 2;\n  ROOT_UNSPECIFIED_ = 3;\n"));
+    }
+
+    #[test]
+    fn test_banner() {
+        let schema = json!({"enum": ["a"]});
+        let opts = EmitOptions {
+            banner: Some(crate::options::Banner::new(
+                &schema,
+                vec!["--target".into(), "proto".into()],
+            )),
+            ..EmitOptions::default()
+        };
+        let proto = to_proto(&compile(&schema).unwrap(), &opts);
+        let header: Vec<&str> = proto.lines().take(4).collect();
+        assert!(header[0].starts_with("// Generator: jtd-codegen "));
+        assert!(header[0].ends_with(", target proto"));
+        assert!(header[1].starts_with("// Schema: sha256:"));
+        assert_eq!(header[2], "// Options: --target proto");
+        assert_eq!(header[3], "syntax = \"proto3\";");
+    }
+}
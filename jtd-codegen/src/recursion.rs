@@ -0,0 +1,159 @@
+/// Recursion analysis over a schema's definitions.
+///
+/// A definition is *recursive* when it reaches itself through refs. The
+/// generated validators call one function per definition, so an instance
+/// nested as deep as the recursion allows would otherwise nest the calls as
+/// deep and can exhaust the host stack. The functions of recursive
+/// definitions therefore count how deep they are and, from
+/// [`MAX_REF_DEPTH`] on, report the value as an error at the definition's
+/// schema path (`/definitions/<name>`) instead of descending further.
+///
+/// A *ref cycle* is recursion with no data between the refs, e.g.
+/// `{"a": {"ref": "b"}, "b": {"ref": "a"}}`: validating a non-null value
+/// never reaches a type check. Such schemas compile, with a warning.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::Node;
+use crate::visit::{visit_node, Visitor};
+
+/// Calls of recursive definition functions a validation may nest.
+pub const MAX_REF_DEPTH: usize = 256;
+
+/// The definitions `node` refers to directly.
+pub fn refs<'a>(node: &'a Node, out: &mut BTreeSet<&'a str>) {
+    struct Refs<'a, 'o>(&'o mut BTreeSet<&'a str>);
+    impl<'a> Visitor<'a> for Refs<'a, '_> {
+        fn enter(&mut self, node: &'a Node, _schema_path: &str) {
+            if let Node::Ref { name } = node {
+                self.0.insert(name);
+            }
+        }
+    }
+    visit_node(node, "", &mut Refs(out));
+}
+
+/// The definitions each definition reaches through one or more refs.
+pub fn reaches(definitions: &BTreeMap<String, Node>) -> BTreeMap<&str, BTreeSet<&str>> {
+    let direct: BTreeMap<&str, BTreeSet<&str>> = definitions
+        .iter()
+        .map(|(name, node)| {
+            let mut out = BTreeSet::new();
+            refs(node, &mut out);
+            (name.as_str(), out)
+        })
+        .collect();
+    direct
+        .keys()
+        .map(|&name| {
+            let mut seen = BTreeSet::new();
+            let mut todo: Vec<&str> = direct[name].iter().copied().collect();
+            while let Some(next) = todo.pop() {
+                if seen.insert(next) {
+                    todo.extend(direct[next].iter().copied());
+                }
+            }
+            (name, seen)
+        })
+        .collect()
+}
+
+/// The definitions that reach themselves.
+pub fn recursive(definitions: &BTreeMap<String, Node>) -> BTreeSet<&str> {
+    reaches(definitions)
+        .into_iter()
+        .filter(|(name, reached)| reached.contains(name))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Each ref cycle, as the definitions along it starting from the first by
+/// name.
+pub fn ref_cycles(definitions: &BTreeMap<String, Node>) -> Vec<Vec<&str>> {
+    // A definition that is only a (nullable) ref has one successor
+    let next = |name: &str| match &definitions[name] {
+        Node::Ref { name } => Some(name.as_str()),
+        Node::Nullable { inner } => match inner.as_ref() {
+            Node::Ref { name } => Some(name.as_str()),
+            _ => None,
+        },
+        _ => None,
+    };
+    let mut cycles = Vec::new();
+    for start in definitions.keys() {
+        let mut path = vec![start.as_str()];
+        while let Some(name) = next(path[path.len() - 1]) {
+            if name == start {
+                cycles.push(path);
+                break;
+            }
+            // Cycles through a smaller name are reported from there
+            if name < start.as_str() || path.contains(&name) {
+                break;
+            }
+            path.push(name);
+        }
+    }
+    cycles
+}
+
+/// A warning for each ref cycle in `definitions`.
+pub fn warnings(definitions: &BTreeMap<String, Node>) -> Vec<String> {
+    ref_cycles(definitions)
+        .into_iter()
+        .map(|cycle| {
+            let mut names: Vec<String> = cycle.iter().map(|name| format!("'{name}'")).collect();
+            names.push(names[0].clone());
+            format!(
+                "definitions {} refer to each other with no data between them; no non-null instance is valid",
+                names.join(" -> ")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_recursive() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree": {"properties": {"children": {"elements": {"ref": "tree"}}}},
+                "a": {"values": {"ref": "b"}},
+                "b": {"optionalProperties": {"a": {"ref": "a"}}},
+                "leaf": {"type": "string"},
+                "uses_tree": {"ref": "tree"}
+            },
+            "ref": "uses_tree"
+        }))
+        .unwrap();
+        let recursive = recursive(&schema.definitions);
+        assert_eq!(recursive, BTreeSet::from(["a", "b", "tree"]));
+        assert!(ref_cycles(&schema.definitions).is_empty());
+    }
+
+    #[test]
+    fn test_ref_cycles() {
+        let schema = compile(&json!({
+            "definitions": {
+                "c": {"ref": "b"},
+                "b": {"ref": "a", "nullable": true},
+                "a": {"ref": "c"},
+                "self": {"ref": "self"},
+                "into": {"ref": "a"}
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            ref_cycles(&schema.definitions),
+            vec![vec!["a", "c", "b"], vec!["self"]]
+        );
+        assert_eq!(
+            warnings(&schema.definitions)[1],
+            "definitions 'self' -> 'self' refer to each other with no data between them; no non-null instance is valid"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+/// Client for an HTTP schema registry, so CI jobs and services can generate
+/// validators from schemas by name instead of vendoring every file.
+/// `GET {base}/{name}/{version}` must return the schema JSON.
+///
+/// Responses are cached on disk next to their `ETag` and revalidated with
+/// `If-None-Match`. A `sha256:<hex>` pin rejects any body with a different
+/// digest; a cached body that matches its pin is used without a request,
+/// since pinned content cannot change.
+pub use crate::manifest::checksum;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    #[error("invalid schema reference '{0}': expected name[@version]")]
+    BadReference(String),
+    #[error("invalid pin '{0}': expected sha256:<64 hex digits>")]
+    BadPin(String),
+    #[error("request for {url} failed: {message}")]
+    Http { url: String, message: String },
+    #[error("registry answered 304 Not Modified for {0} but nothing is cached")]
+    NotCached(String),
+    #[error("checksum mismatch for {name}: pinned {expected}, registry sent {actual}")]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("invalid JSON from registry: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("schema cache: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// `name[@version]`; the version defaults to `latest`. Names may have
+/// `/`-separated segments of letters, digits, `.`, `_` and `-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaRef {
+    pub name: String,
+    pub version: String,
+}
+
+impl SchemaRef {
+    pub fn parse(s: &str) -> Result<SchemaRef, RegistryError> {
+        let (name, version) = s.rsplit_once('@').unwrap_or((s, "latest"));
+        // Segments become cache paths, so `.` and `..` are refused too.
+        let valid = |part: &str| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        };
+        if !name.split('/').all(valid) || !valid(version) {
+            return Err(RegistryError::BadReference(s.to_string()));
+        }
+        Ok(SchemaRef {
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+/// What a conditional GET returned.
+pub enum Fetched {
+    Body { body: Vec<u8>, etag: Option<String> },
+    NotModified,
+}
+
+/// How the registry client talks HTTP; [`HttpTransport`] unless testing.
+pub trait Transport {
+    fn get(&self, url: &str, if_none_match: Option<&str>) -> Result<Fetched, RegistryError>;
+}
+
+/// Blocking HTTP(S) via ureq.
+pub struct HttpTransport;
+
+impl Transport for HttpTransport {
+    fn get(&self, url: &str, if_none_match: Option<&str>) -> Result<Fetched, RegistryError> {
+        let http = |message: String| RegistryError::Http {
+            url: url.to_string(),
+            message,
+        };
+        let mut request = ureq::get(url).set("Accept", "application/json");
+        if let Some(etag) = if_none_match {
+            request = request.set("If-None-Match", etag);
+        }
+        let response = request.call().map_err(|e| http(e.to_string()))?;
+        if response.status() == 304 {
+            return Ok(Fetched::NotModified);
+        }
+        let etag = response.header("ETag").map(str::to_owned);
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(|e| http(e.to_string()))?;
+        Ok(Fetched::Body { body, etag })
+    }
+}
+
+pub struct Registry<T: Transport = HttpTransport> {
+    base_url: String,
+    cache_dir: Option<PathBuf>,
+    transport: T,
+}
+
+impl Registry<HttpTransport> {
+    pub fn new(base_url: &str) -> Self {
+        Registry::with_transport(base_url, HttpTransport)
+    }
+}
+
+impl<T: Transport> Registry<T> {
+    pub fn with_transport(base_url: &str, transport: T) -> Self {
+        Registry {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cache_dir: None,
+            transport,
+        }
+    }
+
+    /// Cache responses under `dir` (created on first write).
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Fetch and parse a schema, checking it against `pin` when given.
+    pub fn fetch(
+        &self,
+        schema: &SchemaRef,
+        pin: Option<&str>,
+    ) -> Result<serde_json::Value, RegistryError> {
+        if let Some(pin) = pin {
+            let hex = pin.strip_prefix("sha256:").unwrap_or_default();
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(RegistryError::BadPin(pin.to_string()));
+            }
+        }
+        let url = format!("{}/{}/{}", self.base_url, schema.name, schema.version);
+        // Versions may hold dots (1.2.0), so append rather than replace an
+        // extension
+        let paths = self.cache_dir.as_ref().map(|dir| {
+            let dir = dir.join(&schema.name);
+            let version = &schema.version;
+            (
+                dir.join(format!("{version}.json")),
+                dir.join(format!("{version}.etag")),
+            )
+        });
+        let cached = paths
+            .as_ref()
+            .and_then(|(body, _)| std::fs::read(body).ok());
+
+        if let (Some(body), Some(pin)) = (&cached, pin) {
+            if checksum(body).eq_ignore_ascii_case(pin) {
+                return Ok(serde_json::from_slice(body)?);
+            }
+        }
+
+        let etag = match (&cached, &paths) {
+            (Some(_), Some((_, etag))) => std::fs::read_to_string(etag).ok(),
+            _ => None,
+        };
+        let body = match self.transport.get(&url, etag.as_deref())? {
+            Fetched::NotModified => {
+                let body = cached.ok_or(RegistryError::NotCached(url))?;
+                verify(schema, &body, pin)?;
+                body
+            }
+            Fetched::Body { body, etag } => {
+                verify(schema, &body, pin)?;
+                if let Some((body_path, etag_path)) = &paths {
+                    if let Some(parent) = body_path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(body_path, &body)?;
+                    match etag {
+                        Some(etag) => std::fs::write(etag_path, etag)?,
+                        None => {
+                            let _ = std::fs::remove_file(etag_path);
+                        }
+                    }
+                }
+                body
+            }
+        };
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+fn verify(schema: &SchemaRef, body: &[u8], pin: Option<&str>) -> Result<(), RegistryError> {
+    let Some(pin) = pin else {
+        return Ok(());
+    };
+    let actual = checksum(body);
+    if actual.eq_ignore_ascii_case(pin) {
+        Ok(())
+    } else {
+        Err(RegistryError::ChecksumMismatch {
+            name: format!("{}@{}", schema.name, schema.version),
+            expected: pin.to_string(),
+            actual,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Serves one body with ETag `"v1"`, answering 304 when asked for it.
+    struct Fake {
+        body: &'static str,
+        requests: RefCell<Vec<(String, Option<String>)>>,
+    }
+
+    impl Transport for &Fake {
+        fn get(&self, url: &str, if_none_match: Option<&str>) -> Result<Fetched, RegistryError> {
+            self.requests
+                .borrow_mut()
+                .push((url.to_string(), if_none_match.map(str::to_owned)));
+            if if_none_match == Some("\"v1\"") {
+                return Ok(Fetched::NotModified);
+            }
+            Ok(Fetched::Body {
+                body: self.body.as_bytes().to_vec(),
+                etag: Some("\"v1\"".to_string()),
+            })
+        }
+    }
+
+    fn fake() -> Fake {
+        Fake {
+            body: r#"{"type": "string"}"#,
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        let r = SchemaRef::parse("orders/order@3").unwrap();
+        assert_eq!((r.name.as_str(), r.version.as_str()), ("orders/order", "3"));
+        assert_eq!(SchemaRef::parse("user").unwrap().version, "latest");
+        assert!(SchemaRef::parse("../etc@1").is_err());
+        assert!(SchemaRef::parse("a//b").is_err());
+        assert!(SchemaRef::parse("a@").is_err());
+    }
+
+    #[test]
+    fn test_etag_revalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = fake();
+        let registry =
+            Registry::with_transport("https://r.example/", &server).cache_dir(dir.path());
+        let schema = SchemaRef::parse("user@2").unwrap();
+        assert_eq!(registry.fetch(&schema, None).unwrap()["type"], "string");
+        assert_eq!(registry.fetch(&schema, None).unwrap()["type"], "string");
+        let requests = server.requests.borrow();
+        assert_eq!(
+            *requests,
+            [
+                ("https://r.example/user/2".to_string(), None),
+                (
+                    "https://r.example/user/2".to_string(),
+                    Some("\"v1\"".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dotted_versions_cache_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = fake();
+        let registry = Registry::with_transport("https://r.example", &server).cache_dir(dir.path());
+        for version in ["1.2.0", "1.2.1"] {
+            let schema = SchemaRef::parse(&format!("user@{version}")).unwrap();
+            registry.fetch(&schema, None).unwrap();
+            assert!(dir.path().join(format!("user/{version}.json")).is_file());
+            assert!(dir.path().join(format!("user/{version}.etag")).is_file());
+        }
+        // 1.2.1 was not revalidated with the ETag cached for 1.2.0
+        let requests = server.requests.borrow();
+        assert_eq!(
+            requests[1],
+            ("https://r.example/user/1.2.1".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_pins() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = fake();
+        let registry = Registry::with_transport("https://r.example", &server).cache_dir(dir.path());
+        let schema = SchemaRef::parse("user").unwrap();
+        let good = checksum(server.body.as_bytes());
+        registry.fetch(&schema, Some(&good)).unwrap();
+        // Pinned and cached: no second request
+        registry.fetch(&schema, Some(&good)).unwrap();
+        assert_eq!(server.requests.borrow().len(), 1);
+
+        let wrong = format!("sha256:{}", "0".repeat(64));
+        let err = registry.fetch(&schema, Some(&wrong)).unwrap_err();
+        assert!(matches!(err, RegistryError::ChecksumMismatch { .. }));
+        assert!(matches!(
+            registry.fetch(&schema, Some("md5:abc")),
+            Err(RegistryError::BadPin(_))
+        ));
+    }
+}
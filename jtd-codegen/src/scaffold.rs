@@ -0,0 +1,292 @@
+/// Project templates for `jtd-codegen init`: a starter `schema.json`, the
+/// wiring that turns it into a validator, and a sample test, modeled on
+/// jtd-wasm-validator. The Rust templates generate the validator in
+/// `build.rs`; the JS template ships a generated `validator.mjs` plus an npm
+/// script to regenerate it.
+use crate::compiler;
+use crate::emit_js;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    /// Rust crate built with wasm-pack, exporting `validate(json)` to JS.
+    Wasm,
+    /// Rust library crate exporting `validate(&Value)`.
+    Rust,
+    /// npm package with a generated ES module.
+    Js,
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Option<Template> {
+        match s {
+            "wasm" => Some(Template::Wasm),
+            "rust" | "rs" => Some(Template::Rust),
+            "js" | "javascript" => Some(Template::Js),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScaffoldError {
+    #[error("{0} already exists; init does not overwrite files")]
+    Exists(PathBuf),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+const SCHEMA: &str = r#"{
+  "properties": {
+    "name": { "type": "string" },
+    "age": { "type": "uint8" },
+    "tags": { "elements": { "type": "string" } }
+  },
+  "optionalProperties": {
+    "email": { "type": "string" }
+  }
+}
+"#;
+
+const BUILD_RS: &str = r#"/// Build script: generates Rust validation code for schema.json via
+/// jtd-codegen into OUT_DIR, as schema.rs, for inclusion in lib.rs.
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    jtd_codegen::build::generate("schema.json", jtd_codegen::Target::Rust, &out_dir)
+        .unwrap_or_else(|e| panic!("{e}"));
+}
+"#;
+
+const GENERATED_MOD: &str = r#"/// Generated validator -- compiled from schema.json at build time.
+#[allow(clippy::all)]
+#[allow(unused_imports)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/schema.rs"));
+}
+"#;
+
+const RUST_LIB: &str = r#"
+pub use generated::ValidationError;
+
+/// Validate an instance against schema.json; empty when it is valid.
+pub fn validate(instance: &serde_json::Value) -> Vec<ValidationError> {
+    generated::validate(instance)
+}
+"#;
+
+const RUST_TEST: &str = r#"use serde_json::json;
+
+#[test]
+fn test_valid_instance() {
+    let errors = {crate}::validate(&json!({"name": "Bob", "age": 25, "tags": ["x"]}));
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn test_invalid_instance() {
+    let errors = {crate}::validate(&json!({"name": "Alice", "age": 300, "tags": []}));
+    assert_eq!(
+        errors,
+        vec![{crate}::ValidationError {
+            instance_path: "/age".to_string(),
+            schema_path: "/properties/age/type".to_string(),
+        }]
+    );
+}
+"#;
+
+const WASM_LIB: &str = r#"
+/// Validate a JSON string against schema.json. Returns an array of
+/// `{instancePath, schemaPath}` objects, empty when the instance is valid.
+#[wasm_bindgen]
+pub fn validate(instance_json: &str) -> Result<JsValue, JsError> {
+    let instance: serde_json::Value = serde_json::from_str(instance_json)
+        .map_err(|e| JsError::new(&format!("Invalid JSON: {e}")))?;
+    let arr = js_sys::Array::new();
+    for e in generated::validate(&instance) {
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(&obj, &"instancePath".into(), &e.instance_path.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"schemaPath".into(), &e.schema_path.into()).unwrap();
+        arr.push(&obj);
+    }
+    Ok(arr.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn test_generated_validator() {
+        assert!(super::generated::validate(&json!({"name": "Bob", "age": 25, "tags": []})).is_empty());
+        let errors = super::generated::validate(&json!({"name": "Alice", "age": 300, "tags": []}));
+        let errors: Vec<_> = errors.into_iter().map(|e| e.into_tuple()).collect();
+        assert_eq!(errors, vec![("/age".to_string(), "/properties/age/type".to_string())]);
+    }
+}
+"#;
+
+const JS_TEST: &str = r#"import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { validate } from '../validator.mjs';
+
+test('valid instance', () => {
+  assert.deepEqual(validate({ name: 'Bob', age: 25, tags: ['x'] }), []);
+});
+
+test('invalid instance', () => {
+  assert.deepEqual(validate({ name: 'Alice', age: 300, tags: [] }), [
+    { instancePath: '/age', schemaPath: '/properties/age/type' },
+  ]);
+});
+"#;
+
+/// The files of a new project called `name`, as (relative path, contents).
+pub fn files(template: Template, name: &str) -> Vec<(&'static str, String)> {
+    let package = package_name(name);
+    let codegen_version = env!("CARGO_PKG_VERSION");
+    let mut out = vec![("schema.json", SCHEMA.to_string())];
+    match template {
+        Template::Rust | Template::Wasm => {
+            let (lib, extra_deps, crate_type) = if template == Template::Wasm {
+                (
+                    format!("use wasm_bindgen::prelude::*;\n\n{GENERATED_MOD}{WASM_LIB}"),
+                    "wasm-bindgen = \"0.2\"\njs-sys = \"0.3\"\n",
+                    "\n[lib]\ncrate-type = [\"cdylib\", \"rlib\"]\n",
+                )
+            } else {
+                (format!("{GENERATED_MOD}{RUST_LIB}"), "", "")
+            };
+            out.push((
+                "Cargo.toml",
+                format!(
+                    "[package]\nname = \"{package}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n{crate_type}\n\
+                     [dependencies]\nserde_json = \"1\"\n{extra_deps}\n\
+                     [build-dependencies]\n\
+                     jtd-codegen = {{ version = \"{codegen_version}\", default-features = false }}\n"
+                ),
+            ));
+            out.push(("build.rs", BUILD_RS.to_string()));
+            out.push(("src/lib.rs", lib));
+            if template == Template::Rust {
+                let ident = package.replace('-', "_");
+                out.push(("tests/validate.rs", RUST_TEST.replace("{crate}", &ident)));
+            }
+            out.push((".gitignore", "/target\n/pkg\n".to_string()));
+        }
+        Template::Js => {
+            let schema = serde_json::from_str(SCHEMA).expect("template schema is JSON");
+            let compiled = compiler::compile(&schema).expect("template schema is valid JTD");
+            out.push((
+                "package.json",
+                format!(
+                    "{{\n  \"name\": \"{package}\",\n  \"version\": \"0.1.0\",\n  \"private\": true,\n  \
+                     \"type\": \"module\",\n  \"main\": \"validator.mjs\",\n  \"scripts\": {{\n    \
+                     \"generate\": \"jtd-codegen --target js schema.json > validator.mjs\",\n    \
+                     \"test\": \"node --test\"\n  }}\n}}\n"
+                ),
+            ));
+            out.push(("validator.mjs", emit_js::emit(&compiled)));
+            out.push(("test/validator.test.mjs", JS_TEST.to_string()));
+        }
+    }
+    out
+}
+
+/// Write the template's files under `dir` (created if needed), named after
+/// the directory. Nothing is written if any file already exists.
+pub fn init(dir: &Path, template: Template) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let name = std::path::absolute(dir)?
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("validator")
+        .to_string();
+    let files = files(template, &name);
+    if let Some((path, _)) = files.iter().find(|(path, _)| dir.join(path).exists()) {
+        return Err(ScaffoldError::Exists(dir.join(path)));
+    }
+    let mut written = Vec::new();
+    for (path, contents) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// A crate and npm package name: lowercase ASCII letters, digits and `-`.
+fn package_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('-');
+    match cleaned.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => cleaned.to_string(),
+        Some(_) => format!("validator-{cleaned}"),
+        None => "validator".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("My Validator"), "my-validator");
+        assert_eq!(package_name("2024_orders"), "validator-2024-orders");
+        assert_eq!(package_name("..."), "validator");
+    }
+
+    #[test]
+    fn test_templates() {
+        let rust = files(Template::Rust, "orders");
+        let paths: Vec<&str> = rust.iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            paths,
+            [
+                "schema.json",
+                "Cargo.toml",
+                "build.rs",
+                "src/lib.rs",
+                "tests/validate.rs",
+                ".gitignore"
+            ]
+        );
+        assert!(rust[1].1.contains("name = \"orders\""));
+        assert!(rust[4].1.contains("orders::validate("));
+
+        let wasm = files(Template::Wasm, "orders");
+        assert!(wasm[1].1.contains("crate-type = [\"cdylib\", \"rlib\"]"));
+        assert!(wasm[3].1.contains("#[wasm_bindgen]"));
+
+        let js = files(Template::Js, "orders");
+        assert!(js.iter().any(|(p, code)| *p == "validator.mjs"
+            && code.contains("export function validate(instance)")));
+    }
+
+    #[test]
+    fn test_init_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("orders");
+        let written = init(&project, Template::Js).unwrap();
+        assert_eq!(written.len(), 4);
+        assert!(std::fs::read_to_string(project.join("package.json"))
+            .unwrap()
+            .contains("\"name\": \"orders\""));
+        assert!(matches!(
+            init(&project, Template::Js),
+            Err(ScaffoldError::Exists(_))
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+/// A compact description of what a schema expects, exported by generated
+/// validators with the `shape` option (`SHAPE`), so form builders and
+/// error UIs can show hints without interpreting the schema at runtime.
+///
+/// ```json
+/// {"objects": [{"path": "", "schemaPath": "", "required": ["id"],
+///               "optional": ["note"], "additional": false}],
+///  "enums": [{"path": "/status", "schemaPath": "/properties/status",
+///             "values": ["new", "done"]}],
+///  "discriminators": [{"path": "/pet", "schemaPath": "/properties/pet",
+///                      "tag": "kind", "values": ["cat", "dog"]}]}
+/// ```
+///
+/// `path` is an instance path with `*` standing for any array index or
+/// map key; `schemaPath` is the schema location, as in validation errors.
+/// Each discriminator variant is an object entry at the discriminator's
+/// path with `"when": {tag: value}`; its keys leave out the tag. Refs are
+/// followed, except back into a definition already being followed, so a
+/// recursive structure is described down to its first repeat.
+use serde_json::{json, Value};
+
+use crate::ast::{CompiledSchema, Node};
+use crate::lsp::spans::escape_segment;
+
+#[derive(Default)]
+struct Shape {
+    objects: Vec<Value>,
+    enums: Vec<Value>,
+    discriminators: Vec<Value>,
+}
+
+/// The descriptor for `schema`.
+pub fn shape(schema: &CompiledSchema) -> Value {
+    let mut out = Shape::default();
+    walk(
+        schema,
+        &schema.root,
+        "",
+        "",
+        None,
+        &mut Vec::new(),
+        &mut out,
+    );
+    json!({
+        "objects": out.objects,
+        "enums": out.enums,
+        "discriminators": out.discriminators,
+    })
+}
+
+/// Record `node`, at instance path `path` and schema path `sp`, and what
+/// it contains. `when` is the tag and value selecting a variant.
+fn walk<'a>(
+    schema: &'a CompiledSchema,
+    node: &'a Node,
+    path: &str,
+    sp: &str,
+    when: Option<(&str, &str)>,
+    inlining: &mut Vec<&'a str>,
+    out: &mut Shape,
+) {
+    match node {
+        Node::Empty | Node::Type { .. } => {}
+        Node::Nullable { inner } => walk(schema, inner, path, sp, when, inlining, out),
+        Node::Ref { name } => {
+            if inlining.contains(&name.as_str()) {
+                return;
+            }
+            inlining.push(name);
+            let def_sp = format!("/definitions/{}", escape_segment(name));
+            walk(
+                schema,
+                &schema.definitions[name],
+                path,
+                &def_sp,
+                when,
+                inlining,
+                out,
+            );
+            inlining.pop();
+        }
+        Node::Enum { values } => {
+            out.enums
+                .push(json!({"path": path, "schemaPath": sp, "values": values}));
+        }
+        Node::Elements { schema: item } => {
+            let item_sp = format!("{sp}/elements");
+            walk(
+                schema,
+                item,
+                &format!("{path}/*"),
+                &item_sp,
+                None,
+                inlining,
+                out,
+            );
+        }
+        Node::Values { schema: item } => {
+            let item_sp = format!("{sp}/values");
+            walk(
+                schema,
+                item,
+                &format!("{path}/*"),
+                &item_sp,
+                None,
+                inlining,
+                out,
+            );
+        }
+        Node::Properties {
+            required,
+            optional,
+            additional,
+        } => {
+            let mut entry = json!({
+                "path": path,
+                "schemaPath": sp,
+                "required": required.keys().collect::<Vec<_>>(),
+                "optional": optional.keys().collect::<Vec<_>>(),
+                "additional": additional,
+            });
+            if let Some((tag, value)) = when {
+                entry["when"] = json!({ tag: value });
+            }
+            out.objects.push(entry);
+            let required = required.iter().map(|(k, n)| (k, n, "properties"));
+            let optional = optional.iter().map(|(k, n)| (k, n, "optionalProperties"));
+            for (key, child, keyword) in required.chain(optional) {
+                let key = escape_segment(key);
+                let child_path = format!("{path}/{key}");
+                let child_sp = format!("{sp}/{keyword}/{key}");
+                walk(schema, child, &child_path, &child_sp, None, inlining, out);
+            }
+        }
+        Node::Discriminator { tag, mapping } => {
+            out.discriminators.push(json!({
+                "path": path,
+                "schemaPath": sp,
+                "tag": tag,
+                "values": mapping.keys().collect::<Vec<_>>(),
+            }));
+            for (value, variant) in mapping {
+                let variant_sp = format!("{sp}/mapping/{}", escape_segment(value));
+                let when = Some((tag.as_str(), value.as_str()));
+                walk(schema, variant, path, &variant_sp, when, inlining, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+
+    #[test]
+    fn test_shape() {
+        let schema = compile(&json!({
+            "definitions": {
+                "node": {"properties": {"next": {"ref": "node", "nullable": true}}}
+            },
+            "properties": {
+                "tags": {"elements": {"enum": ["a", "b"]}},
+                "head": {"ref": "node"},
+                "pet": {"discriminator": "kind", "mapping": {
+                    "cat": {"properties": {"lives": {"type": "uint8"}}},
+                    "dog": {"properties": {}, "optionalProperties": {"a/b": {"values": {}}}}
+                }}
+            },
+            "optionalProperties": {"note": {"type": "string"}},
+            "additionalProperties": true
+        }))
+        .unwrap();
+        assert_eq!(
+            shape(&schema),
+            json!({
+                "objects": [
+                    {"path": "", "schemaPath": "", "required": ["head", "pet", "tags"],
+                     "optional": ["note"], "additional": true},
+                    {"path": "/head", "schemaPath": "/definitions/node", "required": ["next"],
+                     "optional": [], "additional": false},
+                    {"path": "/pet", "schemaPath": "/properties/pet/mapping/cat",
+                     "required": ["lives"], "optional": [], "additional": false,
+                     "when": {"kind": "cat"}},
+                    {"path": "/pet", "schemaPath": "/properties/pet/mapping/dog",
+                     "required": [], "optional": ["a/b"], "additional": false,
+                     "when": {"kind": "dog"}}
+                ],
+                "enums": [
+                    {"path": "/tags/*", "schemaPath": "/properties/tags/elements",
+                     "values": ["a", "b"]}
+                ],
+                "discriminators": [
+                    {"path": "/pet", "schemaPath": "/properties/pet", "tag": "kind",
+                     "values": ["cat", "dog"]}
+                ]
+            })
+        );
+    }
+}
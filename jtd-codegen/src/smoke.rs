@@ -0,0 +1,324 @@
+/// Smoke-test cases synthesized from a schema, for generated test modules:
+/// a skeleton instance (required properties only, one element or value per
+/// array or map, the first enum value and discriminator mapping, non-null
+/// nullables) and, for each form it reaches, a copy with one violation.
+/// Each case carries the errors the interpreter reports for it, so a
+/// generated validator is checked against the same answers.
+///
+/// Refs are followed to [`crate::mock::MAX_DEPTH`], and each definition's
+/// violations are generated at its first use only. A skeleton that cannot
+/// be valid (a ref cycle through required properties) still yields cases,
+/// with the errors it has.
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::ast::{CompiledSchema, Node, TypeKeyword};
+use crate::compiler::escape_pointer;
+use crate::interpret::validate;
+use crate::mock::MAX_DEPTH;
+
+/// One synthesized instance and the errors it should produce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeCase {
+    /// What was done to the skeleton: `valid` for the skeleton itself, or
+    /// `wrong_type`, `not_in_enum`, `not_an_array`, `not_an_object`,
+    /// `missing_property`, `additional_property`, `missing_tag` or
+    /// `unknown_tag`.
+    pub kind: &'static str,
+    /// Where in the instance, as a JSON Pointer.
+    pub instance_path: String,
+    pub instance: Value,
+    /// `(instancePath, schemaPath)` pairs, in the validators' order.
+    pub errors: Vec<(String, String)>,
+}
+
+/// How a violation changes the value at its path.
+enum Edit {
+    Replace(Value),
+    Remove(String),
+    Insert(String, Value),
+}
+
+/// The skeleton case first, then one case per violation.
+pub fn cases(schema: &CompiledSchema) -> Vec<SmokeCase> {
+    let skeleton = skeleton(schema, &schema.root, 0);
+    let mut edits = Vec::new();
+    let mut seen = BTreeSet::new();
+    walk(
+        schema,
+        &schema.root,
+        &skeleton,
+        "",
+        0,
+        &mut seen,
+        &mut edits,
+    );
+
+    let case = |kind, instance_path: String, instance: Value| SmokeCase {
+        kind,
+        errors: validate(schema, &instance),
+        instance_path,
+        instance,
+    };
+    let mut out = vec![case("valid", String::new(), skeleton.clone())];
+    for (kind, path, edit) in edits {
+        let mut instance = skeleton.clone();
+        let Some(target) = instance.pointer_mut(&path) else {
+            continue;
+        };
+        match edit {
+            Edit::Replace(v) => *target = v,
+            Edit::Remove(key) => {
+                target.as_object_mut().map(|o| o.remove(&key));
+            }
+            Edit::Insert(key, v) => {
+                target.as_object_mut().map(|o| o.insert(key, v));
+            }
+        }
+        out.push(case(kind, path, instance));
+    }
+    out
+}
+
+fn skeleton(schema: &CompiledSchema, node: &Node, d: usize) -> Value {
+    match node {
+        Node::Empty => Value::Null,
+        Node::Ref { name } if d < MAX_DEPTH => skeleton(schema, &schema.definitions[name], d + 1),
+        Node::Ref { .. } => Value::Null,
+        Node::Type { type_kw } => match type_kw {
+            TypeKeyword::Boolean => false.into(),
+            TypeKeyword::String => "".into(),
+            TypeKeyword::Timestamp => "1970-01-01T00:00:00Z".into(),
+            _ => 0.into(),
+        },
+        Node::Enum { values } => values[0].clone().into(),
+        Node::Elements { schema: item } => vec![skeleton(schema, item, d)].into(),
+        Node::Values { schema: item } => {
+            let mut map = Map::new();
+            map.insert("key".to_string(), skeleton(schema, item, d));
+            Value::Object(map)
+        }
+        Node::Properties { .. } => Value::Object(properties(schema, node, d, Map::new())),
+        Node::Discriminator { tag, mapping } => {
+            let mut map = Map::new();
+            // An empty mapping accepts nothing; any tag will do
+            let Some((value, variant)) = mapping.iter().next() else {
+                map.insert(tag.clone(), "".into());
+                return Value::Object(map);
+            };
+            map.insert(tag.clone(), value.clone().into());
+            Value::Object(properties(schema, variant, d, map))
+        }
+        Node::Nullable { inner } => skeleton(schema, inner, d),
+    }
+}
+
+/// `map` with the required properties of the Properties node `node` added.
+fn properties(
+    schema: &CompiledSchema,
+    node: &Node,
+    d: usize,
+    mut map: Map<String, Value>,
+) -> Map<String, Value> {
+    if let Node::Properties { required, .. } = node {
+        for (key, child) in required {
+            map.insert(key.clone(), skeleton(schema, child, d));
+        }
+    }
+    map
+}
+
+/// Collect the violations for `node`, whose skeleton `value` is at `path`.
+fn walk(
+    schema: &CompiledSchema,
+    node: &Node,
+    value: &Value,
+    path: &str,
+    d: usize,
+    seen: &mut BTreeSet<String>,
+    out: &mut Vec<(&'static str, String, Edit)>,
+) {
+    let at = |key: &str| format!("{path}/{}", escape_pointer(key));
+    match node {
+        Node::Empty => {}
+        Node::Ref { name } => {
+            if d < MAX_DEPTH && seen.insert(name.clone()) {
+                walk(
+                    schema,
+                    &schema.definitions[name],
+                    value,
+                    path,
+                    d + 1,
+                    seen,
+                    out,
+                );
+            }
+        }
+        Node::Type { type_kw } => {
+            let wrong = match type_kw {
+                TypeKeyword::Boolean => 0.into(),
+                TypeKeyword::String => false.into(),
+                TypeKeyword::Timestamp => "not a timestamp".into(),
+                TypeKeyword::Float32 | TypeKeyword::Float64 => "0".into(),
+                _ => 0.5.into(),
+            };
+            out.push(("wrong_type", path.to_string(), Edit::Replace(wrong)));
+        }
+        Node::Enum { values } => {
+            let wrong = unused(values.iter().map(String::as_str), "not_in_enum");
+            out.push(("not_in_enum", path.to_string(), Edit::Replace(wrong.into())));
+        }
+        Node::Elements { schema: item } => {
+            out.push((
+                "not_an_array",
+                path.to_string(),
+                Edit::Replace(Map::new().into()),
+            ));
+            walk(schema, item, &value[0], &format!("{path}/0"), d, seen, out);
+        }
+        Node::Values { schema: item } => {
+            out.push((
+                "not_an_object",
+                path.to_string(),
+                Edit::Replace(Vec::<Value>::new().into()),
+            ));
+            walk(schema, item, &value["key"], &at("key"), d, seen, out);
+        }
+        Node::Properties { .. } => {
+            out.push((
+                "not_an_object",
+                path.to_string(),
+                Edit::Replace(Vec::<Value>::new().into()),
+            ));
+            walk_properties(schema, node, value, path, None, d, seen, out);
+        }
+        Node::Discriminator { tag, mapping } => {
+            out.push((
+                "not_an_object",
+                path.to_string(),
+                Edit::Replace(Vec::<Value>::new().into()),
+            ));
+            out.push(("missing_tag", path.to_string(), Edit::Remove(tag.clone())));
+            let wrong = unused(mapping.keys().map(String::as_str), "unknown_tag");
+            out.push((
+                "unknown_tag",
+                path.to_string(),
+                Edit::Insert(tag.clone(), wrong.into()),
+            ));
+            if let Some(variant) = mapping.values().next() {
+                walk_properties(schema, variant, value, path, Some(tag), d, seen, out);
+            }
+        }
+        Node::Nullable { inner } => walk(schema, inner, value, path, d, seen, out),
+    }
+}
+
+/// The violations of a Properties node's members, not counting its own
+/// type: a missing required property, an additional one when they are not
+/// allowed, and whatever each required property's value can get wrong.
+#[allow(clippy::too_many_arguments)]
+fn walk_properties(
+    schema: &CompiledSchema,
+    node: &Node,
+    value: &Value,
+    path: &str,
+    tag: Option<&str>,
+    d: usize,
+    seen: &mut BTreeSet<String>,
+    out: &mut Vec<(&'static str, String, Edit)>,
+) {
+    let Node::Properties {
+        required,
+        optional,
+        additional,
+    } = node
+    else {
+        return;
+    };
+    if let Some(key) = required.keys().next() {
+        out.push((
+            "missing_property",
+            path.to_string(),
+            Edit::Remove(key.clone()),
+        ));
+    }
+    if !additional {
+        let known = required.keys().chain(optional.keys()).map(String::as_str);
+        let key = unused(known.chain(tag), "extra");
+        out.push((
+            "additional_property",
+            path.to_string(),
+            Edit::Insert(key, Value::Null),
+        ));
+    }
+    for (key, child) in required {
+        let child_path = format!("{path}/{}", escape_pointer(key));
+        walk(schema, child, &value[key], &child_path, d, seen, out);
+    }
+}
+
+/// `base`, with underscores appended until it is none of `taken`.
+fn unused<'a>(taken: impl Iterator<Item = &'a str> + Clone, base: &str) -> String {
+    let mut s = base.to_string();
+    while taken.clone().any(|t| t == s) {
+        s.push('_');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[test]
+    fn test_skeleton_is_valid_and_violations_are_not() {
+        let schema = compile(&json!({
+            "definitions": {
+                "tree": {
+                    "properties": {"value": {"type": "int8"}},
+                    "optionalProperties": {"children": {"elements": {"ref": "tree"}}}
+                }
+            },
+            "properties": {
+                "id": {"type": "uint32"},
+                "at": {"type": "timestamp"},
+                "score": {"type": "float32", "nullable": true},
+                "status": {"enum": ["new", "not_in_enum"]},
+                "labels": {"values": {"type": "string"}},
+                "tree": {"ref": "tree"},
+                "pet": {"discriminator": "kind", "mapping": {
+                    "cat": {"properties": {"lives": {"type": "uint8"}}}
+                }},
+                "any": {}
+            },
+            "additionalProperties": true
+        }))
+        .unwrap();
+        let cases = cases(&schema);
+        assert_eq!(cases[0].kind, "valid");
+        assert_eq!(cases[0].errors, vec![]);
+        assert_eq!(cases[0].instance["status"], "new");
+        assert_eq!(cases[0].instance["labels"], json!({"key": ""}));
+        for case in &cases[1..] {
+            assert!(!case.errors.is_empty(), "{case:?}");
+        }
+        let enum_case = cases.iter().find(|c| c.kind == "not_in_enum").unwrap();
+        assert_eq!(enum_case.instance["status"], "not_in_enum_");
+        assert_eq!(
+            enum_case.errors,
+            vec![("/status".to_string(), "/properties/status/enum".to_string())]
+        );
+        let tag_case = cases.iter().find(|c| c.kind == "unknown_tag").unwrap();
+        assert_eq!(tag_case.instance_path, "/pet");
+        // Root allows additional properties; the tree and pet variant do not
+        let extra: Vec<&str> = cases
+            .iter()
+            .filter(|c| c.kind == "additional_property")
+            .map(|c| c.instance_path.as_str())
+            .collect();
+        assert_eq!(extra, vec!["/pet", "/tree"]);
+    }
+}
@@ -0,0 +1,241 @@
+/// Source maps (`--source-map`): which lines of a generated file came from
+/// which part of the schema, so a failing check, or a compile error in
+/// generated Rust, can be traced back to the schema location.
+///
+/// ```json
+/// {"version": 1, "target": "js", "file": "order.mjs", "mappings": [
+///   {"lines": [31, 33], "schemaPath": "/properties/id"},
+///   {"lines": [34, 36], "schemaPath": "/properties/total"}]}
+/// ```
+///
+/// Lines are numbered from 1 and each range is inclusive. A line belongs to
+/// the innermost node whose code contains it, so the lines of a properties
+/// check are split around those of its children; headers, helpers and the
+/// bodies of functions shared under `dedup` belong to no one node and are
+/// left out.
+///
+/// With [`EmitOptions::source_map`](crate::options::EmitOptions::source_map)
+/// set, an emitter writes an [`open`] marker line before the code of each
+/// node and a [`close`] marker after it. [`extract`] removes the markers,
+/// leaving exactly the code emitted without the option. Markers name nodes
+/// by address, which [`visit`] resolves to schema paths, so emitters need
+/// not track static paths beside the path expressions they generate.
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::ast::{CompiledSchema, Node};
+use crate::generate::Target;
+use crate::visit::{visit, Visitor};
+
+/// Starts every marker line; generated code escapes control characters.
+const MARKER: char = '\u{1}';
+
+/// The lines of one node's code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// First and last line, from 1.
+    pub lines: (usize, usize),
+    pub schema_path: String,
+}
+
+/// The mappings of one generated file, in line order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// The schema path line `line` (from 1) came from, if any.
+    pub fn schema_path(&self, line: usize) -> Option<&str> {
+        self.mappings
+            .iter()
+            .find(|m| (m.lines.0..=m.lines.1).contains(&line))
+            .map(|m| m.schema_path.as_str())
+    }
+
+    /// The sidecar document for the output of `target`, written to `file`
+    /// (`None` for stdout).
+    pub fn to_json(&self, target: Target, file: Option<&str>) -> Value {
+        let mappings: Vec<Value> = self
+            .mappings
+            .iter()
+            .map(|m| json!({"lines": [m.lines.0, m.lines.1], "schemaPath": m.schema_path}))
+            .collect();
+        json!({
+            "version": 1,
+            "target": target.name(),
+            "file": file,
+            "mappings": mappings,
+        })
+    }
+}
+
+/// The marker line before the code of `node`.
+pub fn open(node: &Node) -> String {
+    format!("{MARKER}>{node:p}")
+}
+
+/// The marker line after the code of the node last opened.
+pub fn close() -> &'static str {
+    "\u{1}<"
+}
+
+/// `code`, whole lines, between the markers of `node`, for emitters that
+/// build each node's code as a string rather than through a writer.
+pub fn wrap(node: &Node, code: &str) -> String {
+    format!("{}\n{code}{}\n", open(node), close())
+}
+
+/// Remove the markers from `code`, emitted for `schema`, and map each
+/// remaining line to its node's schema path. A node that is not part of
+/// `schema` (one an emitter built itself) counts as part of its parent.
+pub fn extract(schema: &CompiledSchema, code: &str) -> (String, SourceMap) {
+    let mut paths = Paths::default();
+    visit(schema, &mut paths);
+
+    let mut out = String::with_capacity(code.len());
+    let mut map = SourceMap::default();
+    let mut open: Vec<Option<&str>> = Vec::new();
+    let mut line = 0;
+    for text in code.split_inclusive('\n') {
+        let marker = text.trim().strip_prefix(MARKER);
+        if let Some(address) = marker.and_then(|m| m.strip_prefix('>')) {
+            let path = paths.0.get(address).map(String::as_str);
+            open.push(path.or(open.last().copied().flatten()));
+            continue;
+        }
+        if marker == Some("<") {
+            open.pop();
+            continue;
+        }
+        out.push_str(text);
+        line += 1;
+        let Some(path) = open.last().copied().flatten() else {
+            continue;
+        };
+        match map.mappings.last_mut() {
+            Some(m) if m.lines.1 + 1 == line && m.schema_path == path => m.lines.1 = line,
+            _ => map.mappings.push(Mapping {
+                lines: (line, line),
+                schema_path: path.to_string(),
+            }),
+        }
+    }
+    (out, map)
+}
+
+/// Schema paths by node address, as [`open`] writes it.
+#[derive(Default)]
+struct Paths(HashMap<String, String>);
+
+impl Visitor<'_> for Paths {
+    fn enter(&mut self, node: &Node, schema_path: &str) {
+        self.0.insert(format!("{node:p}"), schema_path.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use crate::generate::emit_with_source_map;
+    use crate::options::EmitOptions;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract() {
+        let schema = compile(&json!({"properties": {"a": {"type": "string"}}})).unwrap();
+        let Node::Properties { required, .. } = &schema.root else {
+            unreachable!();
+        };
+        let code = format!(
+            "head\n{}\nif (obj) {{\n  {}\n  check a\n  {}\n}}\n{}\ntail\n",
+            open(&schema.root),
+            open(&required["a"]),
+            close(),
+            close()
+        );
+        let (out, map) = extract(&schema, &code);
+        assert_eq!(out, "head\nif (obj) {\n  check a\n}\ntail\n");
+        assert_eq!(map.schema_path(1), None);
+        assert_eq!(map.schema_path(2), Some(""));
+        assert_eq!(map.schema_path(3), Some("/properties/a"));
+        assert_eq!(map.schema_path(4), Some(""));
+        assert_eq!(map.schema_path(5), None);
+        assert_eq!(map.mappings.len(), 3);
+    }
+
+    #[test]
+    fn test_unknown_node_belongs_to_parent() {
+        let schema = compile(&json!({"elements": {}})).unwrap();
+        let built = Node::Empty;
+        let code = format!(
+            "{}\n{}\nx\n{}\n{}\n",
+            open(&schema.root),
+            open(&built),
+            close(),
+            close()
+        );
+        let (out, map) = extract(&schema, &code);
+        assert_eq!(out, "x\n");
+        assert_eq!(
+            map.mappings,
+            [Mapping {
+                lines: (1, 1),
+                schema_path: String::new()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let schema =
+            compile(&json!({"definitions": {"id": {"type": "string"}}, "ref": "id"})).unwrap();
+        let (code, map) = emit_with_source_map(&schema, Target::Python, &EmitOptions::default());
+        assert!(!code.contains(MARKER));
+        let doc = map.to_json(Target::Python, Some("id.py"));
+        assert_eq!(doc["target"], "python");
+        assert_eq!(doc["file"], "id.py");
+        let paths: Vec<&str> = doc["mappings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["schemaPath"].as_str().unwrap())
+            .collect();
+        assert!(paths.contains(&"/definitions/id"), "{paths:?}");
+    }
+
+    #[test]
+    fn test_dedup_and_escaped_definitions() {
+        let schema = compile(&json!({
+            "definitions": {"a/b~c": {"properties": {"x": {"type": "string"}}}},
+            "properties": {
+                "p": {"properties": {"x": {"type": "string"}}},
+                "q": {"properties": {"x": {"type": "string"}}},
+                "r": {"ref": "a/b~c"}
+            }
+        }))
+        .unwrap();
+        let opts = EmitOptions {
+            dedup: true,
+            ..EmitOptions::default()
+        };
+        let (code, map) = emit_with_source_map(&schema, Target::JavaScript, &opts);
+        let path_of = |needle: &str| {
+            let line = code.lines().position(|l| l.contains(needle)).unwrap() + 1;
+            map.schema_path(line)
+        };
+        // Each use of a shared function maps to its own path; the shared
+        // body belongs to no one node and is left out
+        assert_eq!(path_of("shape_0(instance[\"p\"]"), Some("/properties/p"));
+        assert_eq!(path_of("shape_0(instance[\"q\"]"), Some("/properties/q"));
+        assert_eq!(path_of("function shape_0("), None);
+        assert_eq!(path_of("typeof v[\"x\"] !== \"string\""), None);
+        assert_eq!(
+            path_of("  shape_0(v, e, p, sp);"),
+            Some("/definitions/a~1b~0c")
+        );
+        assert_eq!(path_of("validate_a_b_c(instance"), Some("/properties/r"));
+    }
+}
@@ -0,0 +1,169 @@
+/// Plan for the `strip(instance)` function emitted with the
+/// `strip_additional` option: which objects in an instance can carry keys
+/// the schema does not declare, and which keys they may keep.
+///
+/// Only Properties schemas with `additionalProperties: false` (the JTD
+/// default) lose keys; a discriminator variant also keeps its tag. Schemas
+/// that allow additional properties are walked for nested closed objects
+/// but keep their own unknown keys. Stripping never fixes anything else, so
+/// the result still needs `validate`.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{CompiledSchema, Node};
+
+/// What to strip for one schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Strip {
+    Object(Shape),
+    /// Every element of an array.
+    Array(Box<Strip>),
+    /// Every value of an object.
+    Map(Box<Strip>),
+    /// A definition's strip function (`strip_<name>`).
+    Ref(String),
+    /// An object whose shape depends on the string at `tag`.
+    Tagged {
+        tag: String,
+        variants: Vec<(String, Shape)>,
+    },
+}
+
+/// One Properties schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    /// The keys to keep, or `None` when additional properties are allowed.
+    pub known: Option<Vec<String>>,
+    /// Known properties with something to strip inside.
+    pub props: Vec<(String, Strip)>,
+}
+
+/// Plans for the root and for each definition with something to strip.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StripPlan {
+    pub root: Option<Strip>,
+    pub definitions: BTreeMap<String, Strip>,
+}
+
+/// Build the strip plan for `schema`.
+pub fn strip_plan(schema: &CompiledSchema) -> StripPlan {
+    // Definitions that reach a closed object, directly or via refs.
+    let mut live: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let before = live.len();
+        for (name, node) in &schema.definitions {
+            if !live.contains(name) && plan(node, &live).is_some() {
+                live.insert(name.clone());
+            }
+        }
+        if live.len() == before {
+            break;
+        }
+    }
+
+    let definitions = live
+        .iter()
+        .filter_map(|name| Some((name.clone(), plan(&schema.definitions[name], &live)?)))
+        .collect();
+    StripPlan {
+        root: plan(&schema.root, &live),
+        definitions,
+    }
+}
+
+fn plan(node: &Node, live: &BTreeSet<String>) -> Option<Strip> {
+    match node {
+        Node::Nullable { inner } => plan(inner, live),
+        Node::Ref { name } => live.contains(name).then(|| Strip::Ref(name.clone())),
+        Node::Elements { schema } => plan(schema, live).map(|s| Strip::Array(Box::new(s))),
+        Node::Values { schema } => plan(schema, live).map(|s| Strip::Map(Box::new(s))),
+        Node::Properties { .. } => shape(node, None, live).map(Strip::Object),
+        Node::Discriminator { tag, mapping } => {
+            let variants: Vec<(String, Shape)> = mapping
+                .iter()
+                .filter_map(|(value, variant)| {
+                    Some((value.clone(), shape(variant, Some(tag), live)?))
+                })
+                .collect();
+            (!variants.is_empty()).then(|| Strip::Tagged {
+                tag: tag.clone(),
+                variants,
+            })
+        }
+        Node::Empty | Node::Type { .. } | Node::Enum { .. } => None,
+    }
+}
+
+fn shape(node: &Node, tag: Option<&str>, live: &BTreeSet<String>) -> Option<Shape> {
+    let Node::Properties {
+        required,
+        optional,
+        additional,
+    } = node
+    else {
+        return None;
+    };
+    let known = (!additional).then(|| {
+        tag.into_iter()
+            .map(str::to_string)
+            .chain(required.keys().cloned())
+            .chain(optional.keys().cloned())
+            .collect()
+    });
+    let props: Vec<(String, Strip)> = required
+        .iter()
+        .chain(optional)
+        .filter_map(|(key, child)| Some((key.clone(), plan(child, live)?)))
+        .collect();
+    (known.is_some() || !props.is_empty()).then_some(Shape { known, props })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    fn plan_of(schema: serde_json::Value) -> StripPlan {
+        strip_plan(&compiler::compile(&schema).unwrap())
+    }
+
+    #[test]
+    fn test_open_schema_has_nothing_to_strip() {
+        let p = plan_of(json!({
+            "properties": {"a": {"type": "string"}},
+            "additionalProperties": true
+        }));
+        assert_eq!(p, StripPlan::default());
+    }
+
+    #[test]
+    fn test_closed_object_keeps_known_keys() {
+        let p = plan_of(json!({
+            "properties": {"a": {"type": "string"}},
+            "optionalProperties": {"b": {"elements": {"properties": {"c": {}}}}}
+        }));
+        let Some(Strip::Object(shape)) = p.root else {
+            panic!("expected object plan")
+        };
+        assert_eq!(shape.known, Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(shape.props.len(), 1);
+        assert_eq!(shape.props[0].0, "b");
+    }
+
+    #[test]
+    fn test_discriminator_keeps_tag() {
+        let p = plan_of(json!({
+            "definitions": {"open": {"properties": {}, "additionalProperties": true}},
+            "discriminator": "kind",
+            "mapping": {"a": {"properties": {"x": {"ref": "open"}}}}
+        }));
+        assert!(p.definitions.is_empty());
+        let Some(Strip::Tagged { variants, .. }) = p.root else {
+            panic!("expected tagged plan")
+        };
+        assert_eq!(
+            variants[0].1.known,
+            Some(vec!["kind".to_string(), "x".to_string()])
+        );
+    }
+}
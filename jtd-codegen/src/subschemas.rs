@@ -0,0 +1,145 @@
+/// Every sub-schema of a compiled schema, keyed by its schema path, for the
+/// `validateAt(pointer, value)` entry points emitted with the `validate_at`
+/// option. The pointer is the same path errors report as `schemaPath`, so a
+/// form can validate one field against `/properties/address` and get errors
+/// whose paths line up with a full `validate`.
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+
+/// One addressable sub-schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubSchema<'a> {
+    /// Schema path, `""` for the root.
+    pub pointer: String,
+    pub node: &'a Node,
+    /// Discriminator tag when `node` is a mapping variant; the tag property
+    /// is then allowed even though the variant does not list it.
+    pub tag: Option<&'a str>,
+}
+
+/// The root, every definition and every schema nested in them, in the
+/// order a reader would find them in the schema document.
+pub fn sub_schemas(schema: &CompiledSchema) -> Vec<SubSchema<'_>> {
+    let mut out = Vec::new();
+    collect(&schema.root, String::new(), None, &mut out);
+    for (name, node) in &schema.definitions {
+        collect(
+            node,
+            format!("/definitions/{}", escape_pointer(name)),
+            None,
+            &mut out,
+        );
+    }
+    out
+}
+
+fn collect<'a>(
+    node: &'a Node,
+    pointer: String,
+    tag: Option<&'a str>,
+    out: &mut Vec<SubSchema<'a>>,
+) {
+    out.push(SubSchema {
+        pointer: pointer.clone(),
+        node,
+        tag,
+    });
+    let node = match node {
+        Node::Nullable { inner } => inner,
+        other => other,
+    };
+    match node {
+        Node::Elements { schema } => collect(schema, format!("{pointer}/elements"), None, out),
+        Node::Values { schema } => collect(schema, format!("{pointer}/values"), None, out),
+        Node::Properties {
+            required, optional, ..
+        } => {
+            for (key, child) in required {
+                collect(
+                    child,
+                    format!("{pointer}/properties/{}", escape_pointer(key)),
+                    None,
+                    out,
+                );
+            }
+            for (key, child) in optional {
+                collect(
+                    child,
+                    format!("{pointer}/optionalProperties/{}", escape_pointer(key)),
+                    None,
+                    out,
+                );
+            }
+        }
+        Node::Discriminator { tag, mapping } => {
+            for (value, variant) in mapping {
+                collect(
+                    variant,
+                    format!("{pointer}/mapping/{}", escape_pointer(value)),
+                    Some(tag),
+                    out,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_pointers() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"addr": {"properties": {"zip": {"type": "string"}}}},
+            "properties": {
+                "address": {"ref": "addr"},
+                "tags": {"elements": {"type": "string"}, "nullable": true},
+                "pet": {"discriminator": "kind", "mapping": {"cat": {"properties": {}}}}
+            }
+        }))
+        .unwrap();
+        let subs = sub_schemas(&compiled);
+        let pointers: Vec<&str> = subs.iter().map(|s| s.pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "",
+                "/properties/address",
+                "/properties/pet",
+                "/properties/pet/mapping/cat",
+                "/properties/tags",
+                "/properties/tags/elements",
+                "/definitions/addr",
+                "/definitions/addr/properties/zip",
+            ]
+        );
+        assert_eq!(subs[3].tag, Some("kind"));
+    }
+
+    #[test]
+    fn test_pointers_are_escaped() {
+        let compiled = compiler::compile(&json!({
+            "definitions": {"a/b~c": {"properties": {"x/y": {"type": "string"}}}},
+            "discriminator": "t",
+            "mapping": {"m~n": {"properties": {}}}
+        }))
+        .unwrap();
+        let pointers: Vec<String> = sub_schemas(&compiled)
+            .into_iter()
+            .map(|s| s.pointer)
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "",
+                "/mapping/m~0n",
+                "/definitions/a~1b~0c",
+                "/definitions/a~1b~0c/properties/x~1y",
+            ]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+/// "Did you mean" data for the `suggestions` option: for each schema path
+/// that can report an unknown enum value, discriminator tag or additional
+/// property, the names that would have been accepted there.
+///
+/// The generated validators look an error's `schemaPath` up in this table,
+/// take the offending word (the string at `instancePath`, or the last path
+/// segment for an additional property) and attach the closest candidate as
+/// `suggestion`. [`did_you_mean`] is the reference for what every target's
+/// helper computes.
+use crate::ast::{CompiledSchema, Node};
+use crate::subschemas::sub_schemas;
+
+/// One schema path whose errors can carry a suggestion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestSite {
+    /// The `schemaPath` of the error.
+    pub schema_path: String,
+    /// The word is the last segment of `instancePath` (an object key)
+    /// rather than the string value found there.
+    pub key: bool,
+    pub candidates: Vec<String>,
+}
+
+/// Every enum, discriminator and closed Properties schema.
+pub fn suggest_sites(schema: &CompiledSchema) -> Vec<SuggestSite> {
+    let mut out = Vec::new();
+    for sub in sub_schemas(schema) {
+        let node = match sub.node {
+            Node::Nullable { inner } => inner,
+            other => other,
+        };
+        match node {
+            Node::Enum { values } => out.push(SuggestSite {
+                schema_path: format!("{}/enum", sub.pointer),
+                key: false,
+                candidates: values.clone(),
+            }),
+            Node::Discriminator { mapping, .. } => out.push(SuggestSite {
+                schema_path: format!("{}/mapping", sub.pointer),
+                key: false,
+                candidates: mapping.keys().cloned().collect(),
+            }),
+            Node::Properties {
+                required,
+                optional,
+                additional: false,
+            } if !(required.is_empty() && optional.is_empty()) => out.push(SuggestSite {
+                schema_path: sub.pointer.clone(),
+                key: true,
+                candidates: required.keys().chain(optional.keys()).cloned().collect(),
+            }),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Largest edit distance still worth suggesting for `word`: one edit per
+/// three characters, at least one.
+pub fn max_distance(word: &str) -> usize {
+    (word.chars().count() / 3).max(1)
+}
+
+/// The candidate closest to `word` (first one on ties), if within
+/// [`max_distance`].
+pub fn did_you_mean<'a>(word: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let mut best = None;
+    let mut best_d = max_distance(word) + 1;
+    for c in candidates {
+        let d = edit_distance(word, c);
+        if d < best_d {
+            best = Some(c.as_str());
+            best_d = d;
+        }
+    }
+    best
+}
+
+/// Optimal string alignment distance: Levenshtein plus adjacent
+/// transpositions, so `"nmae"` is one edit from `"name"`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use serde_json::json;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("name", "name"), 0);
+        assert_eq!(edit_distance("nmae", "name"), 1);
+        assert_eq!(edit_distance("colour", "color"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = vec!["active".to_string(), "pending".to_string()];
+        assert_eq!(did_you_mean("pendng", &candidates), Some("pending"));
+        assert_eq!(did_you_mean("Active", &candidates), Some("active"));
+        assert_eq!(did_you_mean("deleted", &candidates), None);
+    }
+
+    #[test]
+    fn test_sites() {
+        let compiled = compiler::compile(&json!({
+            "properties": {
+                "status": {"enum": ["active", "pending"]},
+                "pet": {"discriminator": "kind", "mapping": {"cat": {"properties": {"lives": {}}}}}
+            },
+            "optionalProperties": {"meta": {"values": {}}}
+        }))
+        .unwrap();
+        let sites = suggest_sites(&compiled);
+        let paths: Vec<(&str, bool)> = sites
+            .iter()
+            .map(|s| (s.schema_path.as_str(), s.key))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("", true),
+                ("/properties/pet/mapping", false),
+                ("/properties/pet/mapping/cat", true),
+                ("/properties/status/enum", false),
+            ]
+        );
+        assert_eq!(sites[0].candidates, vec!["pet", "status", "meta"]);
+    }
+}
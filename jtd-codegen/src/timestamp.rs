@@ -0,0 +1,112 @@
+/// Whether `s` is an RFC 3339 `date-time`, the `timestamp` type. Every
+/// target's generated check is a port of this function, and
+/// `tests/timestamps.json` holds the edge cases each validation suite runs
+/// them against.
+///
+/// - The shape is `YYYY-MM-DDThh:mm:ss[.frac](Z|+hh:mm|-hh:mm)` in ASCII
+///   digits, with `T` and `Z` in either case and at least one digit after a
+///   `.`. Nothing may follow the offset, not even a newline.
+/// - The month is 1 to 12 and the day at most the month's length, with
+///   Gregorian leap years counted back to year 0000.
+/// - The hour is at most 23, the minute 59 and the second 60. A leap second
+///   is accepted at any time of day: RFC 3339 leaves checking it against
+///   the table of leap seconds to the application.
+/// - An offset's hour is at most 23 and its minute 59.
+pub fn is_rfc3339(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| -> Option<u32> {
+        let part = b.get(range)?;
+        part.iter().try_fold(0u32, |acc, c| {
+            c.is_ascii_digit().then(|| acc * 10 + u32::from(c - b'0'))
+        })
+    };
+    let sep = |i: usize, options: &[u8]| b.get(i).is_some_and(|c| options.contains(c));
+
+    if !(sep(4, b"-") && sep(7, b"-") && sep(10, b"Tt") && sep(13, b":") && sep(16, b":")) {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) = (digits(0..4), digits(5..7), digits(8..10)) else {
+        return false;
+    };
+    let (Some(hour), Some(minute), Some(second)) = (digits(11..13), digits(14..16), digits(17..19))
+    else {
+        return false;
+    };
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return false;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+
+    let mut rest = &b[19..];
+    if let Some(frac) = rest.strip_prefix(b".") {
+        let n = frac.iter().take_while(|c| c.is_ascii_digit()).count();
+        if n == 0 {
+            return false;
+        }
+        rest = &frac[n..];
+    }
+    match rest {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => {
+            let two = |a: &u8, b: &u8| {
+                (a.is_ascii_digit() && b.is_ascii_digit())
+                    .then(|| u32::from(a - b'0') * 10 + u32::from(b - b'0'))
+            };
+            matches!((two(h1, h2), two(m1, m2)), (Some(h), Some(m)) if h <= 23 && m <= 59)
+        }
+        _ => false,
+    }
+}
+
+/// Days in `month` (1 to 12) of `year`, in the proleptic Gregorian calendar.
+pub fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_rfc3339() {
+        assert!(is_rfc3339("1985-04-12T23:20:50.52Z"));
+        assert!(is_rfc3339("1990-12-31T23:59:60Z"));
+        assert!(is_rfc3339("2024-02-29t00:00:00+05:30"));
+        assert!(!is_rfc3339("2023-02-29T00:00:00Z"));
+        assert!(!is_rfc3339("2024-13-45T99:99:99Z"));
+        assert!(!is_rfc3339("2024-01-01T00:00:00"));
+        assert!(!is_rfc3339("2024-01-01T00:00:00.Z"));
+        assert!(!is_rfc3339("2024-01-01T00:00:00+24:00"));
+    }
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(0, 2), 29);
+        assert_eq!(days_in_month(2023, 4), 30);
+        assert_eq!(days_in_month(2023, 12), 31);
+    }
+
+    /// The shared vectors are the reference's own cases too.
+    #[test]
+    fn test_shared_vectors() {
+        let cases: Value = serde_json::from_str(include_str!("../tests/timestamps.json")).unwrap();
+        for (name, case) in cases.as_object().unwrap() {
+            let s = case["instance"].as_str().unwrap();
+            let valid = case["errors"].as_array().unwrap().is_empty();
+            assert_eq!(is_rfc3339(s), valid, "{name}: {s:?}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/// Freshness checks for checked-in generated files: `jtd-codegen verify`
+/// regenerates in memory and compares with [`diff`], so CI can fail a
+/// merge whose vendored validator no longer matches its schema.
+///
+/// The diff is a unified diff (Myers' algorithm, three lines of context)
+/// of the checked-in file against the regenerated one.
+const CONTEXT: usize = 3;
+
+/// Edit distances past this many lines are not minimised; the differing
+/// middle is shown as removed and re-added, keeping memory bounded.
+const MAX_EDITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// A unified diff from `old` to `new`, or `None` when they are equal.
+pub fn diff(old_name: &str, new_name: &str, old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let script = edit_script(&a, &b);
+
+    let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+    let changes: Vec<usize> = (0..script.len())
+        .filter(|&i| script[i].0 != Op::Keep)
+        .collect();
+    if changes.is_empty() {
+        // Only line endings or a final newline differ
+        out.push_str("(files differ only in line endings)\n");
+        return Some(out);
+    }
+    let mut group_start = 0;
+    for g in 1..=changes.len() {
+        if g < changes.len() && changes[g] - changes[g - 1] <= 2 * CONTEXT {
+            continue;
+        }
+        let start = changes[group_start].saturating_sub(CONTEXT);
+        let end = (changes[g - 1] + CONTEXT + 1).min(script.len());
+        hunk(&mut out, &script, start, end);
+        group_start = g;
+    }
+    Some(out)
+}
+
+/// Append the hunk covering `script[start..end]`.
+fn hunk(out: &mut String, script: &[(Op, &str)], start: usize, end: usize) {
+    let count = |range: &[(Op, &str)], skip: Op| range.iter().filter(|(op, _)| *op != skip).count();
+    let old_before = count(&script[..start], Op::Insert);
+    let new_before = count(&script[..start], Op::Delete);
+    let old_len = count(&script[start..end], Op::Insert);
+    let new_len = count(&script[start..end], Op::Delete);
+    // An empty range is numbered after the line it follows
+    let from = |before: usize, len: usize| if len == 0 { before } else { before + 1 };
+    out.push_str(&format!(
+        "@@ -{},{old_len} +{},{new_len} @@\n",
+        from(old_before, old_len),
+        from(new_before, new_len)
+    ));
+    for (op, line) in &script[start..end] {
+        let mark = match op {
+            Op::Keep => ' ',
+            Op::Delete => '-',
+            Op::Insert => '+',
+        };
+        out.push(mark);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// The lines of `a` and `b` in order, each kept, deleted from `a` or
+/// inserted from `b`.
+fn edit_script<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut script: Vec<(Op, &str)> = a[..prefix].iter().map(|l| (Op::Keep, *l)).collect();
+    match myers(mid_a, mid_b) {
+        Some(middle) => script.extend(middle),
+        None => {
+            script.extend(mid_a.iter().map(|l| (Op::Delete, *l)));
+            script.extend(mid_b.iter().map(|l| (Op::Insert, *l)));
+        }
+    }
+    script.extend(a[a.len() - suffix..].iter().map(|l| (Op::Keep, *l)));
+    script
+}
+
+/// A shortest edit script, or `None` past [`MAX_EDITS`] edits. Keeps the
+/// furthest-reaching x of each diagonal per step for backtracking.
+fn myers<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(Op, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let at = |k: isize| (k + offset) as usize;
+
+    let mut found = None;
+    'search: for d in 0..=max {
+        trace.push(v[at(-d)..=at(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                found = Some(d);
+                break 'search;
+            }
+        }
+    }
+    found?;
+
+    let mut script = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = if d == 0 { 0 } else { get(prev_k) };
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            script.push((Op::Keep, a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                script.push((Op::Insert, b[prev_y as usize]));
+            } else {
+                script.push((Op::Delete, a[prev_x as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    script.reverse();
+    Some(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("a", "b", "x\ny\n", "x\ny\n"), None);
+
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        assert_eq!(
+            diff("gen.rs", "regenerated", old, new).unwrap(),
+            "--- gen.rs\n+++ regenerated\n\
+             @@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+
+        let script = edit_script(
+            &["a", "b", "c", "a", "b", "b", "a"],
+            &["c", "b", "a", "b", "a", "c"],
+        );
+        let edits = script.iter().filter(|(op, _)| *op != Op::Keep).count();
+        assert_eq!(edits, 5);
+        let rebuilt: Vec<&str> = script
+            .iter()
+            .filter(|(op, _)| *op != Op::Delete)
+            .map(|(_, l)| *l)
+            .collect();
+        assert_eq!(rebuilt, ["c", "b", "a", "b", "a", "c"]);
+    }
+}
@@ -0,0 +1,148 @@
+/// A walk over a compiled schema for code that only cares about some
+/// forms, such as linters, doc generators and custom emitters: implement
+/// [`Visitor`] and call [`visit`] instead of matching every [`Node`]
+/// variant and building schema paths by hand.
+///
+/// The walk covers the root and then each definition, depth first. Every
+/// node is entered before its children and left after them, with its schema
+/// path (`""` for the root, `/definitions/a/properties/b`, ...). A nullable
+/// node is entered first and its inner node next, both at the same path.
+/// Refs are not followed; each definition is walked once on its own.
+use crate::ast::{CompiledSchema, Node};
+use crate::compiler::escape_pointer;
+
+/// Callbacks for [`visit`], which may keep the nodes (lifetime `'a`) they
+/// are given. Both default to doing nothing.
+pub trait Visitor<'a> {
+    /// Called before the children of `node`.
+    fn enter(&mut self, _node: &'a Node, _schema_path: &str) {}
+
+    /// Called after the children of `node`.
+    fn leave(&mut self, _node: &'a Node, _schema_path: &str) {}
+}
+
+/// Walk `schema` with `visitor`.
+pub fn visit<'a>(schema: &'a CompiledSchema, visitor: &mut impl Visitor<'a>) {
+    visit_node(&schema.root, "", visitor);
+    for (name, node) in &schema.definitions {
+        visit_node(
+            node,
+            &format!("/definitions/{}", escape_pointer(name)),
+            visitor,
+        );
+    }
+}
+
+/// Walk `node`, at `schema_path`, and the nodes under it.
+pub fn visit_node<'a>(node: &'a Node, schema_path: &str, visitor: &mut impl Visitor<'a>) {
+    visitor.enter(node, schema_path);
+    match node {
+        Node::Nullable { inner } => visit_node(inner, schema_path, visitor),
+        Node::Elements { schema } => {
+            visit_node(schema, &format!("{schema_path}/elements"), visitor)
+        }
+        Node::Values { schema } => visit_node(schema, &format!("{schema_path}/values"), visitor),
+        Node::Properties {
+            required, optional, ..
+        } => {
+            for (key, child) in required {
+                visit_node(
+                    child,
+                    &format!("{schema_path}/properties/{}", escape_pointer(key)),
+                    visitor,
+                );
+            }
+            for (key, child) in optional {
+                let path = format!("{schema_path}/optionalProperties/{}", escape_pointer(key));
+                visit_node(child, &path, visitor);
+            }
+        }
+        Node::Discriminator { mapping, .. } => {
+            for (value, variant) in mapping {
+                visit_node(
+                    variant,
+                    &format!("{schema_path}/mapping/{}", escape_pointer(value)),
+                    visitor,
+                );
+            }
+        }
+        Node::Empty | Node::Ref { .. } | Node::Type { .. } | Node::Enum { .. } => {}
+    }
+    visitor.leave(node, schema_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Visitor<'_> for Trace {
+        fn enter(&mut self, node: &Node, schema_path: &str) {
+            let form = match node {
+                Node::Nullable { .. } => "nullable",
+                Node::Properties { .. } => "properties",
+                Node::Discriminator { .. } => "discriminator",
+                Node::Elements { .. } => "elements",
+                Node::Ref { .. } => "ref",
+                _ => "leaf",
+            };
+            self.0.push(format!("enter {form} {schema_path}"));
+        }
+
+        fn leave(&mut self, _node: &Node, schema_path: &str) {
+            self.0.push(format!("leave {schema_path}"));
+        }
+    }
+
+    #[test]
+    fn test_visit() {
+        let schema = compile(&json!({
+            "definitions": {"id": {"type": "string"}},
+            "properties": {"tags": {"elements": {"ref": "id"}, "nullable": true}},
+            "optionalProperties": {"v": {"discriminator": "k", "mapping": {"x": {"properties": {}}}}}
+        }))
+        .unwrap();
+        let mut trace = Trace::default();
+        visit(&schema, &mut trace);
+        assert_eq!(
+            trace.0,
+            vec![
+                "enter properties ",
+                "enter nullable /properties/tags",
+                "enter elements /properties/tags",
+                "enter ref /properties/tags/elements",
+                "leave /properties/tags/elements",
+                "leave /properties/tags",
+                "leave /properties/tags",
+                "enter discriminator /optionalProperties/v",
+                "enter properties /optionalProperties/v/mapping/x",
+                "leave /optionalProperties/v/mapping/x",
+                "leave /optionalProperties/v",
+                "leave ",
+                "enter leaf /definitions/id",
+                "leave /definitions/id",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_escapes_paths() {
+        let schema = compile(&json!({
+            "definitions": {"a/b~c": {"type": "string"}},
+            "optionalProperties": {"x/y": {"ref": "a/b~c"}}
+        }))
+        .unwrap();
+        let mut trace = Trace::default();
+        visit(&schema, &mut trace);
+        assert!(trace
+            .0
+            .contains(&"enter ref /optionalProperties/x~1y".to_string()));
+        assert!(trace
+            .0
+            .contains(&"enter leaf /definitions/a~1b~0c".to_string()));
+    }
+}
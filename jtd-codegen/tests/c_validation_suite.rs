@@ -0,0 +1,360 @@
+/// Integration test: generates C from each test case in the official JTD
+/// validation suite, compiles it with a small harness using the system C
+/// compiler (`CC`, default `cc`), and runs it on the instance.
+use jtd_codegen::options::EmitOptions;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+fn default_suite_path() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
+}
+
+fn segments_to_pointer(segments: &[Value]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
+    let arr = errors.as_array().expect("errors must be array");
+    arr.iter()
+        .map(|e| {
+            let ip = segments_to_pointer(e["instancePath"].as_array().unwrap());
+            let sp = segments_to_pointer(e["schemaPath"].as_array().unwrap());
+            (ip, sp)
+        })
+        .collect()
+}
+
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+/// All the expected errors, or with a `limit` (1 when failing fast) that
+/// many of them when there are more.
+fn errors_match(
+    actual: &[(String, String)],
+    expected: &BTreeSet<(String, String)>,
+    limit: Option<usize>,
+) -> bool {
+    if let Some(limit) = limit {
+        actual.len() == expected.len().min(limit) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<BTreeSet<_>>() == *expected
+    }
+}
+
+/// Reads an instance from stdin and prints one `instancePath\tschemaPath`
+/// line per error, with backslashes and control bytes as `\xHH`; exits 2
+/// when the instance does not parse, and 3 when `jtd_is_valid` disagrees
+/// with `jtd_validate` (with `JTD_TEST_IS_VALID`).
+const HARNESS: &str = r#"
+#include <stdio.h>
+
+static void put(const char *s, size_t n) {
+    for (size_t i = 0; i < n; i++) {
+        unsigned char b = (unsigned char)s[i];
+        if (b < 0x20 || b == '\\') {
+            printf("\\x%02x", b);
+        } else {
+            putchar(b);
+        }
+    }
+}
+
+int main(void) {
+    static char json[1 << 16];
+    static jtd_node nodes[4096];
+    jtd_error errors[64];
+    char path[1024];
+    size_t len = fread(json, 1, sizeof json, stdin);
+    if (jtd_parse(json, len, nodes, sizeof nodes / sizeof nodes[0]) == 0) {
+        return 2;
+    }
+    size_t count = jtd_validate(nodes, errors, 64);
+#ifdef JTD_TEST_IS_VALID
+    if (jtd_is_valid(nodes) != (count == 0)) {
+        return 3;
+    }
+#endif
+    for (size_t i = 0; i < count && i < 64; i++) {
+        size_t n = jtd_instance_path(nodes, errors[i].node, path, sizeof path);
+        put(path, n < sizeof path ? n : sizeof path - 1);
+        putchar('\t');
+        put(errors[i].schema_path, errors[i].schema_path_len);
+        putchar('\n');
+    }
+    return 0;
+}
+"#;
+
+fn compiler() -> String {
+    std::env::var("CC").unwrap_or_else(|_| "cc".to_string())
+}
+
+/// Compile `code` with the harness into `dir`, run it on the JSON text
+/// `instance` and return its errors.
+fn run(
+    dir: &Path,
+    name: &str,
+    code: &str,
+    instance: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let source = dir.join(format!("{name}.c"));
+    let binary = dir.join(name);
+    std::fs::write(&source, format!("{code}{HARNESS}")).unwrap();
+    let out = Command::new(compiler())
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-O1", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(format!("cc: {}", String::from_utf8_lossy(&out.stderr)));
+    }
+
+    let mut child = Command::new(&binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(instance.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(format!("harness exited with {}", out.status));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .map(|line| {
+            let (ip, sp) = line.split_once('\t').unwrap_or((line, ""));
+            (unescape(ip), unescape(sp))
+        })
+        .collect())
+}
+
+/// Undo the harness's `\xHH` escapes.
+fn unescape(s: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        match tail {
+            [b'x', hi, lo, tail @ ..] if b == b'\\' => {
+                let hex = [*hi, *lo];
+                let hex = std::str::from_utf8(&hex).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                rest = tail;
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).unwrap()
+}
+
+fn have_compiler() -> bool {
+    let found = Command::new(compiler())
+        .arg("--version")
+        .output()
+        .is_ok_and(|out| out.status.success());
+    if !found {
+        eprintln!("SKIP: no C compiler found, skipping C validation suite");
+    }
+    found
+}
+
+/// The bundled parser: escapes, surrogate pairs and malformed input.
+#[test]
+fn test_c_parser() {
+    if !have_compiler() {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let schema = serde_json::json!({"values": {"enum": ["\u{e9}\u{1f600}\n\"/"]}});
+    let code = jtd_codegen::emit_c::emit(&jtd_codegen::compiler::compile(&schema).unwrap());
+    let ok = r#" { "a\u0062" : "\u00e9\ud83d\ude00\n\"\/" , "c": "x" } "#;
+    assert_eq!(
+        run(dir.path(), "ok", &code, ok).unwrap(),
+        [("/c".to_string(), "/values/enum".to_string())]
+    );
+    for (i, bad) in ["[1,]", "{\"a\" 1}", "01", "\"\\x\"", "[1] 2", "tru", ""]
+        .iter()
+        .enumerate()
+    {
+        let err = run(dir.path(), &format!("bad{i}"), &code, bad).unwrap_err();
+        assert!(err.starts_with("harness exited"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn test_c_validation_suite() {
+    run_suite("default", &EmitOptions::default());
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_c_validation_suite_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    };
+    run_suite("fail-fast", &opts);
+}
+
+/// Validators with an error limit return no more errors than that.
+#[test]
+fn test_c_validation_suite_max_errors() {
+    let opts = EmitOptions {
+        max_errors: Some(2),
+        ..EmitOptions::default()
+    };
+    run_suite("max-errors", &opts);
+}
+
+/// `jtd_is_valid` agrees with `jtd_validate` on every case.
+#[test]
+fn test_c_validation_suite_is_valid() {
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("is-valid", &opts);
+}
+
+fn run_suite(label: &str, opts: &EmitOptions) {
+    if !have_compiler() {
+        return;
+    }
+
+    let suite = load_suite();
+    let dir = tempfile::tempdir().unwrap();
+    let mut cases = Vec::new();
+    let mut skipped = 0u32;
+    for (i, (name, case)) in suite.iter().enumerate() {
+        match jtd_codegen::compiler::compile(&case["schema"]) {
+            Ok(compiled) => cases.push((
+                i,
+                name,
+                format!(
+                    "{}{}",
+                    if opts.is_valid {
+                        "#define JTD_TEST_IS_VALID\n"
+                    } else {
+                        ""
+                    },
+                    jtd_codegen::emit_c::emit_with_options(&compiled, opts)
+                ),
+                &case["instance"],
+                normalize_errors(&case["errors"]),
+            )),
+            Err(_) => skipped += 1,
+        }
+    }
+
+    // One compiler run per case, so spread them over the cores
+    let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let chunk = cases.len().div_ceil(threads).max(1);
+    let failures: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = cases
+            .chunks(chunk)
+            .map(|chunk| {
+                let dir = dir.path();
+                scope.spawn(move || {
+                    let mut failures = Vec::new();
+                    for (i, name, code, instance, expected) in chunk {
+                        let instance = instance.to_string();
+                        match run(dir, &format!("case{i}"), code, &instance) {
+                            Ok(errors) => {
+                                if !errors_match(&errors, expected, error_limit(opts)) {
+                                    let actual = &errors;
+                                    failures.push(format!(
+                                        "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+                                    ));
+                                }
+                            }
+                            Err(e) => failures.push(format!("FAIL: {name}\n  {e}")),
+                        }
+                    }
+                    failures
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+
+    eprintln!("=== JTD Validation Suite (C, {label}) ===");
+    eprintln!("Passed:  {}", cases.len() - failures.len());
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} C {label} test cases failed",
+        failures.len()
+    );
+}
@@ -0,0 +1,457 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+#include <stddef.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Deepest nesting jtd_parse accepts; it recurses once per level. */
+#ifndef JTD_MAX_DEPTH
+#define JTD_MAX_DEPTH 64
+#endif
+
+typedef enum {
+    JTD_NULL,
+    JTD_FALSE,
+    JTD_TRUE,
+    JTD_NUMBER,
+    JTD_STRING,
+    JTD_ARRAY,
+    JTD_OBJECT
+} jtd_kind;
+
+/* A JSON value, one node of a flat array in document order. An array's
+ * elements follow it; an object's members follow it as a key string node
+ * then the value's nodes. */
+typedef struct {
+    jtd_kind kind;
+    /* A string's byte length, an array's element count or an object's
+     * member count */
+    size_t len;
+    /* The index of the first node after this one and everything inside it */
+    size_t next;
+    double number;
+    /* A string's decoded UTF-8 bytes, not NUL-terminated */
+    const char *string;
+} jtd_node;
+
+/* A validation failure: the node it is at (for an additional property, its
+ * key, whose instance path is the member's) and the schema path, with its
+ * length since keys may hold NUL. */
+typedef struct {
+    size_t node;
+    const char *schema_path;
+    size_t schema_path_len;
+} jtd_error;
+
+/* Parse the len bytes of JSON at json into at most cap nodes, decoding
+ * strings in place. Returns the node count, or 0 when the JSON is
+ * malformed, too deep or needs more nodes. */
+size_t jtd_parse(char *json, size_t len, jtd_node *nodes, size_t cap);
+
+/* Validate the parsed value at nodes[0], writing up to cap errors. Returns
+ * the error count, which may exceed cap. */
+size_t jtd_validate(const jtd_node *nodes, jtd_error *errors, size_t cap);
+
+/* Write the instance path (JSON Pointer) of nodes[node] to buf, truncated
+ * to cap - 1 bytes and NUL-terminated. Returns its full length. */
+size_t jtd_instance_path(const jtd_node *nodes, size_t node, char *buf, size_t cap);
+
+typedef struct {
+    char *p;
+    char *end;
+    jtd_node *nodes;
+    size_t count;
+    size_t cap;
+} jtd__parser;
+
+static void jtd__ws(jtd__parser *s) {
+    while (s->p < s->end && (*s->p == ' ' || *s->p == '\t' || *s->p == '\n' || *s->p == '\r')) {
+        s->p++;
+    }
+}
+
+static int jtd__literal(jtd__parser *s, const char *word, size_t n) {
+    if ((size_t)(s->end - s->p) < n || memcmp(s->p, word, n) != 0) {
+        return 0;
+    }
+    s->p += n;
+    return 1;
+}
+
+static int jtd__hex4(const char *p, unsigned long *out) {
+    unsigned long v = 0;
+    for (int i = 0; i < 4; i++) {
+        char ch = p[i];
+        v <<= 4;
+        if (ch >= '0' && ch <= '9') {
+            v |= (unsigned long)(ch - '0');
+        } else if (ch >= 'a' && ch <= 'f') {
+            v |= (unsigned long)(ch - 'a' + 10);
+        } else if (ch >= 'A' && ch <= 'F') {
+            v |= (unsigned long)(ch - 'A' + 10);
+        } else {
+            return 0;
+        }
+    }
+    *out = v;
+    return 1;
+}
+
+/* Decode the string after an opening quote into n, in place: the UTF-8 of
+ * an escape is never longer than the escape. */
+static int jtd__string(jtd__parser *s, jtd_node *n) {
+    char *out = s->p;
+    n->kind = JTD_STRING;
+    n->string = out;
+    n->number = 0;
+    while (s->p < s->end) {
+        unsigned char ch = (unsigned char)*s->p++;
+        if (ch == '"') {
+            n->len = (size_t)(out - n->string);
+            return 1;
+        }
+        if (ch < 0x20) {
+            return 0;
+        }
+        if (ch != '\\') {
+            *out++ = (char)ch;
+            continue;
+        }
+        if (s->p >= s->end) {
+            return 0;
+        }
+        unsigned long cp, low;
+        switch (*s->p++) {
+        case '"': *out++ = '"'; break;
+        case '\\': *out++ = '\\'; break;
+        case '/': *out++ = '/'; break;
+        case 'b': *out++ = '\b'; break;
+        case 'f': *out++ = '\f'; break;
+        case 'n': *out++ = '\n'; break;
+        case 'r': *out++ = '\r'; break;
+        case 't': *out++ = '\t'; break;
+        case 'u':
+            if (s->end - s->p < 4 || !jtd__hex4(s->p, &cp)) {
+                return 0;
+            }
+            s->p += 4;
+            if (cp >= 0xD800 && cp <= 0xDBFF && s->end - s->p >= 6 && s->p[0] == '\\'
+                && s->p[1] == 'u' && jtd__hex4(s->p + 2, &low) && low >= 0xDC00 && low <= 0xDFFF) {
+                cp = 0x10000 + ((cp - 0xD800) << 10) + (low - 0xDC00);
+                s->p += 6;
+            }
+            if (cp < 0x80) {
+                *out++ = (char)cp;
+            } else if (cp < 0x800) {
+                *out++ = (char)(0xC0 | (cp >> 6));
+                *out++ = (char)(0x80 | (cp & 0x3F));
+            } else if (cp < 0x10000) {
+                *out++ = (char)(0xE0 | (cp >> 12));
+                *out++ = (char)(0x80 | ((cp >> 6) & 0x3F));
+                *out++ = (char)(0x80 | (cp & 0x3F));
+            } else {
+                *out++ = (char)(0xF0 | (cp >> 18));
+                *out++ = (char)(0x80 | ((cp >> 12) & 0x3F));
+                *out++ = (char)(0x80 | ((cp >> 6) & 0x3F));
+                *out++ = (char)(0x80 | (cp & 0x3F));
+            }
+            break;
+        default:
+            return 0;
+        }
+    }
+    return 0;
+}
+
+static int jtd__digit(const jtd__parser *s) {
+    return s->p < s->end && *s->p >= '0' && *s->p <= '9';
+}
+
+/* A number, checked against the JSON grammar and converted with strtod
+ * (so in the C locale) from a copy of at most 320 characters. */
+static int jtd__number(jtd__parser *s, jtd_node *n) {
+    char text[321];
+    const char *start = s->p;
+    if (s->p < s->end && *s->p == '-') {
+        s->p++;
+    }
+    if (!jtd__digit(s)) {
+        return 0;
+    }
+    if (*s->p++ != '0') {
+        while (jtd__digit(s)) {
+            s->p++;
+        }
+    }
+    if (s->p < s->end && *s->p == '.') {
+        s->p++;
+        if (!jtd__digit(s)) {
+            return 0;
+        }
+        while (jtd__digit(s)) {
+            s->p++;
+        }
+    }
+    if (s->p < s->end && (*s->p == 'e' || *s->p == 'E')) {
+        s->p++;
+        if (s->p < s->end && (*s->p == '+' || *s->p == '-')) {
+            s->p++;
+        }
+        if (!jtd__digit(s)) {
+            return 0;
+        }
+        while (jtd__digit(s)) {
+            s->p++;
+        }
+    }
+    size_t len = (size_t)(s->p - start);
+    if (len >= sizeof text) {
+        return 0;
+    }
+    memcpy(text, start, len);
+    text[len] = '\0';
+    n->kind = JTD_NUMBER;
+    n->number = strtod(text, NULL);
+    return 1;
+}
+
+static int jtd__value(jtd__parser *s, int depth) {
+    jtd__ws(s);
+    if (s->p >= s->end || s->count >= s->cap) {
+        return 0;
+    }
+    size_t at = s->count++;
+    jtd_node *n = &s->nodes[at];
+    n->len = 0;
+    n->number = 0;
+    n->string = NULL;
+    char ch = *s->p;
+    if (ch == '"') {
+        s->p++;
+        if (!jtd__string(s, n)) {
+            return 0;
+        }
+    } else if (ch == '[' || ch == '{') {
+        char close = ch == '[' ? ']' : '}';
+        if (depth >= JTD_MAX_DEPTH) {
+            return 0;
+        }
+        s->p++;
+        n->kind = ch == '[' ? JTD_ARRAY : JTD_OBJECT;
+        jtd__ws(s);
+        if (s->p < s->end && *s->p == close) {
+            s->p++;
+        } else {
+            for (;;) {
+                if (n->kind == JTD_OBJECT) {
+                    jtd__ws(s);
+                    if (s->p >= s->end || *s->p != '"' || s->count >= s->cap) {
+                        return 0;
+                    }
+                    s->p++;
+                    jtd_node *key = &s->nodes[s->count++];
+                    key->next = s->count;
+                    if (!jtd__string(s, key)) {
+                        return 0;
+                    }
+                    jtd__ws(s);
+                    if (s->p >= s->end || *s->p++ != ':') {
+                        return 0;
+                    }
+                }
+                if (!jtd__value(s, depth + 1)) {
+                    return 0;
+                }
+                n->len++;
+                jtd__ws(s);
+                if (s->p < s->end && *s->p == ',') {
+                    s->p++;
+                } else if (s->p < s->end && *s->p == close) {
+                    s->p++;
+                    break;
+                } else {
+                    return 0;
+                }
+            }
+        }
+    } else if (jtd__literal(s, "null", 4)) {
+        n->kind = JTD_NULL;
+    } else if (jtd__literal(s, "false", 5)) {
+        n->kind = JTD_FALSE;
+    } else if (jtd__literal(s, "true", 4)) {
+        n->kind = JTD_TRUE;
+    } else if (!jtd__number(s, n)) {
+        return 0;
+    }
+    n->next = s->count;
+    return 1;
+}
+
+size_t jtd_parse(char *json, size_t len, jtd_node *nodes, size_t cap) {
+    jtd__parser s = {json, json + len, nodes, 0, cap};
+    if (!jtd__value(&s, 0)) {
+        return 0;
+    }
+    jtd__ws(&s);
+    return s.p == s.end ? s.count : 0;
+}
+
+static size_t jtd__append(char *buf, size_t cap, size_t at, const char *s, size_t n) {
+    for (size_t i = 0; i < n; i++, at++) {
+        if (at + 1 < cap) {
+            buf[at] = s[i];
+        }
+    }
+    return at;
+}
+
+/* Append key n as a JSON Pointer segment, with ~ as ~0 and / as ~1 */
+static size_t jtd__append_key(char *buf, size_t cap, size_t at, const jtd_node *n) {
+    for (size_t i = 0; i < n->len; i++) {
+        char ch = n->string[i];
+        if (ch == '~') {
+            at = jtd__append(buf, cap, at, "~0", 2);
+        } else if (ch == '/') {
+            at = jtd__append(buf, cap, at, "~1", 2);
+        } else {
+            at = jtd__append(buf, cap, at, &ch, 1);
+        }
+    }
+    return at;
+}
+
+size_t jtd_instance_path(const jtd_node *nodes, size_t node, char *buf, size_t cap) {
+    size_t at = 0, t = 0;
+    while (t != node) {
+        size_t c = t + 1;
+        if (nodes[t].kind == JTD_ARRAY) {
+            char digits[24];
+            size_t i = 0, n = sizeof digits;
+            while (nodes[c].next <= node) {
+                c = nodes[c].next;
+                i++;
+            }
+            do {
+                digits[--n] = (char)('0' + i % 10);
+                i /= 10;
+            } while (i > 0);
+            at = jtd__append(buf, cap, at, "/", 1);
+            at = jtd__append(buf, cap, at, digits + n, sizeof digits - n);
+            t = c;
+        } else {
+            while (nodes[c + 1].next <= node) {
+                c = nodes[c + 1].next;
+            }
+            at = jtd__append(buf, cap, at, "/", 1);
+            at = jtd__append_key(buf, cap, at, &nodes[c]);
+            t = c == node ? node : c + 1;
+        }
+    }
+    if (cap > 0) {
+        buf[at < cap ? at : cap - 1] = '\0';
+    }
+    return at;
+}
+
+#define JTD__NONE ((size_t)-1)
+
+/* The validation helpers are inline so that those a schema does not need
+ * raise no unused-function warnings. */
+
+typedef struct {
+    const jtd_node *nodes;
+    jtd_error *errors;
+    size_t cap;
+    size_t count;
+    /* Calls of recursive definitions in progress */
+    size_t depth;
+} jtd__ctx;
+
+/* Record an error at node t, counting past capacity */
+static inline void jtd__err(jtd__ctx *c, size_t t, const char *schema_path, size_t len) {
+    if (c->count < c->cap) {
+        c->errors[c->count].node = t;
+        c->errors[c->count].schema_path = schema_path;
+        c->errors[c->count].schema_path_len = len;
+    }
+    c->count++;
+}
+
+/* 1 when node n is the string of the len bytes at s */
+static inline int jtd__eq(const jtd_node *n, const char *s, size_t len) {
+    return n->kind == JTD_STRING && n->len == len && memcmp(n->string, s, len) == 0;
+}
+
+/* The value node of the member of object t named by the len bytes at s,
+ * or JTD__NONE */
+static inline size_t jtd__find(const jtd_node *nodes, size_t t, const char *s, size_t len) {
+    for (size_t k = t + 1; k < nodes[t].next; k = nodes[k + 1].next) {
+        if (jtd__eq(&nodes[k], s, len)) {
+            return k + 1;
+        }
+    }
+    return JTD__NONE;
+}
+
+/* 1 when node n is a number with no fractional part in [min, max] */
+static inline int jtd__int(const jtd_node *n, double min, double max) {
+    return n->kind == JTD_NUMBER && n->number >= min && n->number <= max
+        && (double)(long long)n->number == n->number;
+}
+
+static void jtd__d0(jtd__ctx *c, size_t t);
+
+// root
+static void jtd__root(jtd__ctx *c, size_t t) {
+    jtd__d0(c, t);
+}
+
+// "/definitions/node"
+static void jtd__d0(jtd__ctx *c, size_t t) {
+    if (c->depth >= 256) {
+        jtd__err(c, t, "/definitions/node", 17);
+        return;
+    }
+    c->depth++;
+    if (c->nodes[t].kind != JTD_OBJECT) {
+        jtd__err(c, t, "/definitions/node/properties", 28);
+    } else {
+        size_t v1 = jtd__find(c->nodes, t, "children", 8);
+        if (v1 == JTD__NONE) {
+            jtd__err(c, t, "/definitions/node/properties/children", 37);
+        } else {
+            if (c->nodes[v1].kind != JTD_ARRAY) {
+                jtd__err(c, v1, "/definitions/node/properties/children/elements", 46);
+            } else for (size_t v2 = v1 + 1; v2 < c->nodes[v1].next; v2 = c->nodes[v2].next) {
+                jtd__d0(c, v2);
+            }
+        }
+        size_t v3 = jtd__find(c->nodes, t, "value", 5);
+        if (v3 == JTD__NONE) {
+            jtd__err(c, t, "/definitions/node/properties/value", 34);
+        } else {
+            if (!jtd__int(&c->nodes[v3], -32768.0, 32767.0)) {
+                jtd__err(c, v3, "/definitions/node/properties/value/type", 39);
+            }
+        }
+        size_t v4 = jtd__find(c->nodes, t, "parent", 6);
+        if (v4 != JTD__NONE) {
+            if (c->nodes[v4].kind != JTD_NULL) {
+                jtd__d0(c, v4);
+            }
+        }
+        for (size_t k5 = t + 1; k5 < c->nodes[t].next; k5 = c->nodes[k5 + 1].next) {
+            if (!jtd__eq(&c->nodes[k5], "children", 8) && !jtd__eq(&c->nodes[k5], "value", 5) && !jtd__eq(&c->nodes[k5], "parent", 6)) {
+                jtd__err(c, k5, "/definitions/node", 17);
+            }
+        }
+    }
+    c->depth--;
+}
+
+size_t jtd_validate(const jtd_node *nodes, jtd_error *errors, size_t cap) {
+    jtd__ctx c = {nodes, errors, cap, 0, 0};
+    jtd__root(&c, 0);
+    return c.count;
+}
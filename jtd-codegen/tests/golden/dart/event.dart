@@ -0,0 +1,144 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+// ignore_for_file: non_constant_identifier_names, prefer_interpolation_to_compose_strings, unnecessary_type_check, unused_element
+
+/// A validation failure, as JSON Pointers into the instance and the schema.
+class ValidationError {
+  final String instancePath;
+  final String schemaPath;
+
+  const ValidationError(this.instancePath, this.schemaPath);
+
+  @override
+  String toString() => 'ValidationError($instancePath, $schemaPath)';
+}
+
+const _enum0 = {'EUR', 'GBP', 'USD'};
+
+List<ValidationError> validate(Object? instance) {
+  final e = <ValidationError>[];
+  if (instance is! Map<String, Object?>) {
+    e.add(ValidationError('', '/discriminator'));
+  } else if (!instance.containsKey('kind')) {
+    e.add(ValidationError('', '/discriminator'));
+  } else {
+    final t1 = instance['kind'];
+    if (t1 is! String) {
+      e.add(ValidationError('' + '/kind', '/discriminator'));
+    } else if (t1 == 'login') {
+      if (instance is! Map<String, Object?>) {
+        e.add(ValidationError('', '/mapping/login/properties'));
+      } else {
+        if (!instance.containsKey('actor')) {
+          e.add(ValidationError('', '/mapping/login/properties/actor'));
+        } else {
+          final v2 = instance['actor'];
+          _validate_actor(v2, e, '' + '/actor');
+        }
+        if (!instance.containsKey('at')) {
+          e.add(ValidationError('', '/mapping/login/properties/at'));
+        } else {
+          final v3 = instance['at'];
+          if (!_isTimestamp(v3)) e.add(ValidationError('' + '/at', '/mapping/login/properties/at/type'));
+        }
+        for (final k4 in instance.keys) {
+          if (k4 != 'actor' && k4 != 'at' && k4 != 'kind') e.add(ValidationError('' + '/' + k4.replaceAll('~', '~0').replaceAll('/', '~1'), '/mapping/login'));
+        }
+      }
+    } else if (t1 == 'transfer') {
+      if (instance is! Map<String, Object?>) {
+        e.add(ValidationError('', '/mapping/transfer/properties'));
+      } else {
+        if (!instance.containsKey('actor')) {
+          e.add(ValidationError('', '/mapping/transfer/properties/actor'));
+        } else {
+          final v5 = instance['actor'];
+          _validate_actor(v5, e, '' + '/actor');
+        }
+        if (!instance.containsKey('amount')) {
+          e.add(ValidationError('', '/mapping/transfer/properties/amount'));
+        } else {
+          final v6 = instance['amount'];
+          if (!_isInteger(v6, -2147483648, 2147483647)) e.add(ValidationError('' + '/amount', '/mapping/transfer/properties/amount/type'));
+        }
+        if (!instance.containsKey('currency')) {
+          e.add(ValidationError('', '/mapping/transfer/properties/currency'));
+        } else {
+          final v7 = instance['currency'];
+          if (v7 is! String || !_enum0.contains(v7)) e.add(ValidationError('' + '/currency', '/mapping/transfer/properties/currency/enum'));
+        }
+        if (instance.containsKey('note')) {
+          final v8 = instance['note'];
+          if (v8 is! String) e.add(ValidationError('' + '/note', '/mapping/transfer/optionalProperties/note/type'));
+        }
+        for (final k9 in instance.keys) {
+          if (k9 != 'actor' && k9 != 'amount' && k9 != 'currency' && k9 != 'note' && k9 != 'kind') e.add(ValidationError('' + '/' + k9.replaceAll('~', '~0').replaceAll('/', '~1'), '/mapping/transfer'));
+        }
+      }
+    } else {
+      e.add(ValidationError('' + '/kind', '/mapping'));
+    }
+  }
+  return e;
+}
+
+void _validate_actor(Object? v, List<ValidationError> e, String p) {
+  if (v is! Map<String, Object?>) {
+    e.add(ValidationError(p, '/definitions/actor/properties'));
+  } else {
+    if (!v.containsKey('name')) {
+      e.add(ValidationError(p, '/definitions/actor/properties/name'));
+    } else {
+      final v1 = v['name'];
+      if (v1 is! String) e.add(ValidationError(p + '/name', '/definitions/actor/properties/name/type'));
+    }
+  }
+}
+
+void _validate_login(Object? v, List<ValidationError> e, String p) {
+  if (v is! Map<String, Object?>) {
+    e.add(ValidationError(p, '/definitions/login/properties'));
+  } else {
+    if (!v.containsKey('actor')) {
+      e.add(ValidationError(p, '/definitions/login/properties/actor'));
+    } else {
+      final v1 = v['actor'];
+      _validate_actor(v1, e, p + '/actor');
+    }
+    if (!v.containsKey('at')) {
+      e.add(ValidationError(p, '/definitions/login/properties/at'));
+    } else {
+      final v2 = v['at'];
+      if (!_isTimestamp(v2)) e.add(ValidationError(p + '/at', '/definitions/login/properties/at/type'));
+    }
+    for (final k3 in v.keys) {
+      if (k3 != 'actor' && k3 != 'at') e.add(ValidationError(p + '/' + k3.replaceAll('~', '~0').replaceAll('/', '~1'), '/definitions/login'));
+    }
+  }
+}
+
+bool _isInteger(Object? v, num min, num max) =>
+    v is num && v % 1 == 0 && v >= min && v <= max;
+
+final _rfc3339 = RegExp(
+    r'^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))$');
+
+bool _isTimestamp(Object? v) {
+  if (v is! String) return false;
+  final m = _rfc3339.firstMatch(v);
+  if (m == null) return false;
+  final year = int.parse(m[1]!);
+  final month = int.parse(m[2]!);
+  final day = int.parse(m[3]!);
+  if (month < 1 || month > 12) return false;
+  if (int.parse(m[4]!) > 23 || int.parse(m[5]!) > 59 || int.parse(m[6]!) > 60) return false;
+  final leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+  final days = switch (month) {
+    2 => leap ? 29 : 28,
+    4 || 6 || 9 || 11 => 30,
+    _ => 31,
+  };
+  if (day < 1 || day > days) return false;
+  return m[8] == null || (int.parse(m[8]!) <= 23 && int.parse(m[9]!) <= 59);
+}
@@ -0,0 +1,68 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+// ignore_for_file: non_constant_identifier_names, prefer_interpolation_to_compose_strings, unnecessary_type_check, unused_element
+
+/// A validation failure, as JSON Pointers into the instance and the schema.
+class ValidationError {
+  final String instancePath;
+  final String schemaPath;
+
+  const ValidationError(this.instancePath, this.schemaPath);
+
+  @override
+  String toString() => 'ValidationError($instancePath, $schemaPath)';
+}
+
+/// Calls of recursive definitions in progress.
+var _refDepth = 0;
+
+List<ValidationError> validate(Object? instance) {
+  final e = <ValidationError>[];
+  _validate_node(instance, e, '');
+  return e;
+}
+
+void _validate_node(Object? v, List<ValidationError> e, String p) {
+  if (_refDepth >= 256) {
+    e.add(ValidationError(p, '/definitions/node'));
+    return;
+  }
+  _refDepth++;
+  if (v is! Map<String, Object?>) {
+    e.add(ValidationError(p, '/definitions/node/properties'));
+  } else {
+    if (!v.containsKey('children')) {
+      e.add(ValidationError(p, '/definitions/node/properties/children'));
+    } else {
+      final v1 = v['children'];
+      if (v1 is! List) {
+        e.add(ValidationError(p + '/children', '/definitions/node/properties/children/elements'));
+      } else {
+        for (var i2 = 0; i2 < v1.length; i2++) {
+          final v3 = v1[i2];
+          _validate_node(v3, e, p + '/children' + '/' + i2.toString());
+        }
+      }
+    }
+    if (!v.containsKey('value')) {
+      e.add(ValidationError(p, '/definitions/node/properties/value'));
+    } else {
+      final v4 = v['value'];
+      if (!_isInteger(v4, -32768, 32767)) e.add(ValidationError(p + '/value', '/definitions/node/properties/value/type'));
+    }
+    if (v.containsKey('parent')) {
+      final v5 = v['parent'];
+      if (v5 != null) {
+        _validate_node(v5, e, p + '/parent');
+      }
+    }
+    for (final k6 in v.keys) {
+      if (k6 != 'children' && k6 != 'value' && k6 != 'parent') e.add(ValidationError(p + '/' + k6.replaceAll('~', '~0').replaceAll('/', '~1'), '/definitions/node'));
+    }
+  }
+  _refDepth--;
+}
+
+bool _isInteger(Object? v, num min, num max) =>
+    v is num && v % 1 == 0 && v >= min && v <= max;
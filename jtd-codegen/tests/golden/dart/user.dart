@@ -0,0 +1,113 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+// ignore_for_file: non_constant_identifier_names, prefer_interpolation_to_compose_strings, unnecessary_type_check, unused_element
+
+/// A validation failure, as JSON Pointers into the instance and the schema.
+class ValidationError {
+  final String instancePath;
+  final String schemaPath;
+
+  const ValidationError(this.instancePath, this.schemaPath);
+
+  @override
+  String toString() => 'ValidationError($instancePath, $schemaPath)';
+}
+
+const _enum0 = {'admin', 'member', 'guest'};
+
+/// An account holder.
+List<ValidationError> validate(Object? instance) {
+  final e = <ValidationError>[];
+  if (instance is! Map<String, Object?>) {
+    e.add(ValidationError('', '/properties'));
+  } else {
+    if (!instance.containsKey('age')) {
+      e.add(ValidationError('', '/properties/age'));
+    } else {
+      final v1 = instance['age'];
+      if (!_isInteger(v1, 0, 255)) e.add(ValidationError('' + '/age', '/properties/age/type'));
+    }
+    if (!instance.containsKey('createdAt')) {
+      e.add(ValidationError('', '/properties/createdAt'));
+    } else {
+      final v2 = instance['createdAt'];
+      if (!_isTimestamp(v2)) e.add(ValidationError('' + '/createdAt', '/properties/createdAt/type'));
+    }
+    if (!instance.containsKey('id')) {
+      e.add(ValidationError('', '/properties/id'));
+    } else {
+      final v3 = instance['id'];
+      if (v3 is! String) e.add(ValidationError('' + '/id', '/properties/id/type'));
+    }
+    if (!instance.containsKey('role')) {
+      e.add(ValidationError('', '/properties/role'));
+    } else {
+      final v4 = instance['role'];
+      if (v4 is! String || !_enum0.contains(v4)) e.add(ValidationError('' + '/role', '/properties/role/enum'));
+    }
+    if (!instance.containsKey('tags')) {
+      e.add(ValidationError('', '/properties/tags'));
+    } else {
+      final v5 = instance['tags'];
+      if (v5 is! List) {
+        e.add(ValidationError('' + '/tags', '/properties/tags/elements'));
+      } else {
+        for (var i6 = 0; i6 < v5.length; i6++) {
+          final v7 = v5[i6];
+          if (v7 is! String) e.add(ValidationError('' + '/tags' + '/' + i6.toString(), '/properties/tags/elements/type'));
+        }
+      }
+    }
+    if (instance.containsKey('email')) {
+      final v8 = instance['email'];
+      if (v8 != null) {
+        if (v8 is! String) e.add(ValidationError('' + '/email', '/optionalProperties/email/type'));
+      }
+    }
+    if (instance.containsKey('preferences')) {
+      final v9 = instance['preferences'];
+      if (v9 is! Map<String, Object?>) {
+        e.add(ValidationError('' + '/preferences', '/optionalProperties/preferences/values'));
+      } else {
+        for (final k10 in v9.keys) {
+          final v11 = v9[k10];
+          if (v11 is! bool) e.add(ValidationError('' + '/preferences' + '/' + k10.replaceAll('~', '~0').replaceAll('/', '~1'), '/optionalProperties/preferences/values/type'));
+        }
+      }
+    }
+    if (instance.containsKey('score')) {
+      final v12 = instance['score'];
+      if (v12 is! num) e.add(ValidationError('' + '/score', '/optionalProperties/score/type'));
+    }
+    for (final k13 in instance.keys) {
+      if (k13 != 'age' && k13 != 'createdAt' && k13 != 'id' && k13 != 'role' && k13 != 'tags' && k13 != 'email' && k13 != 'preferences' && k13 != 'score') e.add(ValidationError('' + '/' + k13.replaceAll('~', '~0').replaceAll('/', '~1'), ''));
+    }
+  }
+  return e;
+}
+
+bool _isInteger(Object? v, num min, num max) =>
+    v is num && v % 1 == 0 && v >= min && v <= max;
+
+final _rfc3339 = RegExp(
+    r'^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))$');
+
+bool _isTimestamp(Object? v) {
+  if (v is! String) return false;
+  final m = _rfc3339.firstMatch(v);
+  if (m == null) return false;
+  final year = int.parse(m[1]!);
+  final month = int.parse(m[2]!);
+  final day = int.parse(m[3]!);
+  if (month < 1 || month > 12) return false;
+  if (int.parse(m[4]!) > 23 || int.parse(m[5]!) > 59 || int.parse(m[6]!) > 60) return false;
+  final leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+  final days = switch (month) {
+    2 => leap ? 29 : 28,
+    4 || 6 || 9 || 11 => 30,
+    _ => 31,
+  };
+  if (day < 1 || day > days) return false;
+  return m[8] == null || (int.parse(m[8]!) <= 23 && int.parse(m[9]!) <= 59);
+}
@@ -0,0 +1,71 @@
+function isRfc3339(s) {
+  if (typeof s !== "string") return false;
+  const m = /^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?([Zz]|[+-](\d{2}):(\d{2}))$/.exec(s);
+  if (m === null) return false;
+  const y = +m[1], mo = +m[2], d = +m[3];
+  if (mo < 1 || mo > 12 || d < 1) return false;
+  const leap = y % 4 === 0 && (y % 100 !== 0 || y % 400 === 0);
+  const dim = [31, leap ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][mo - 1];
+  if (d > dim) return false;
+  if (+m[4] > 23 || +m[5] > 59 || +m[6] > 60) return false;
+  if (m[9] !== undefined && (+m[9] > 23 || +m[10] > 59)) return false;
+  return true;
+}
+
+/**
+ * An account holder.
+ */
+export function validate(instance) {
+  const e = [];
+  if (instance === null || typeof instance !== "object" || Array.isArray(instance)) {
+    e.push({instancePath: "", schemaPath: "" + "/properties"});
+  } else {
+    if (!("age" in instance)) e.push({instancePath: "", schemaPath: "" + "/properties/age"});
+    else {
+      if (typeof instance["age"] !== "number" || !Number.isInteger(instance["age"]) || instance["age"] < 0 || instance["age"] > 255) e.push({instancePath: "" + "/age", schemaPath: "" + "/properties/age" + "/type"});
+    }
+    if (!("createdAt" in instance)) e.push({instancePath: "", schemaPath: "" + "/properties/createdAt"});
+    else {
+      if (!isRfc3339(instance["createdAt"])) e.push({instancePath: "" + "/createdAt", schemaPath: "" + "/properties/createdAt" + "/type"});
+    }
+    if (!("id" in instance)) e.push({instancePath: "", schemaPath: "" + "/properties/id"});
+    else {
+      if (typeof instance["id"] !== "string") e.push({instancePath: "" + "/id", schemaPath: "" + "/properties/id" + "/type"});
+    }
+    if (!("role" in instance)) e.push({instancePath: "", schemaPath: "" + "/properties/role"});
+    else {
+      if (typeof instance["role"] !== "string" || !["admin","member","guest"].includes(instance["role"])) e.push({instancePath: "" + "/role", schemaPath: "" + "/properties/role" + "/enum"});
+    }
+    if (!("tags" in instance)) e.push({instancePath: "", schemaPath: "" + "/properties/tags"});
+    else {
+      if (!Array.isArray(instance["tags"])) {
+        e.push({instancePath: "" + "/tags", schemaPath: "" + "/properties/tags" + "/elements"});
+      } else {
+        for (let i = 0; i < instance["tags"].length; i++) {
+          if (typeof instance["tags"][i] !== "string") e.push({instancePath: "" + "/tags" + "/" + i, schemaPath: "" + "/properties/tags" + "/elements" + "/type"});
+        }
+      }
+    }
+    if ("email" in instance) {
+      if (instance["email"] !== null) {
+        if (typeof instance["email"] !== "string") e.push({instancePath: "" + "/email", schemaPath: "" + "/optionalProperties/email" + "/type"});
+      }
+    }
+    if ("preferences" in instance) {
+      if (instance["preferences"] === null || typeof instance["preferences"] !== "object" || Array.isArray(instance["preferences"])) {
+        e.push({instancePath: "" + "/preferences", schemaPath: "" + "/optionalProperties/preferences" + "/values"});
+      } else {
+        for (const k in instance["preferences"]) {
+          if (typeof instance["preferences"][k] !== "boolean") e.push({instancePath: "" + "/preferences" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: "" + "/optionalProperties/preferences" + "/values" + "/type"});
+        }
+      }
+    }
+    if ("score" in instance) {
+      if (typeof instance["score"] !== "number" || !Number.isFinite(instance["score"])) e.push({instancePath: "" + "/score", schemaPath: "" + "/optionalProperties/score" + "/type"});
+    }
+    for (const k in instance) {
+      if (k !== "age" && k !== "createdAt" && k !== "id" && k !== "role" && k !== "tags" && k !== "email" && k !== "preferences" && k !== "score") e.push({instancePath: "" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: ""});
+    }
+  }
+  return e;
+}
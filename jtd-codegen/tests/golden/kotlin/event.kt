@@ -0,0 +1,142 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+@file:Suppress("FunctionName", "USELESS_IS_CHECK", "unused")
+
+import kotlinx.serialization.json.JsonArray
+import kotlinx.serialization.json.JsonElement
+import kotlinx.serialization.json.JsonNull
+import kotlinx.serialization.json.JsonObject
+import kotlinx.serialization.json.JsonPrimitive
+
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+data class ValidationError(val instancePath: String, val schemaPath: String)
+
+private val ENUM_0 = setOf("EUR", "GBP", "USD")
+
+fun validate(instance: JsonElement): List<ValidationError> {
+    val e = mutableListOf<ValidationError>()
+    if (instance !is JsonObject) {
+        e.add(ValidationError("", "/discriminator"))
+    } else {
+        val t1 = instance["kind"]
+        if (t1 == null) {
+            e.add(ValidationError("", "/discriminator"))
+        } else if (t1 !is JsonPrimitive || !t1.isString) {
+            e.add(ValidationError("" + "/kind", "/discriminator"))
+        } else {
+            when (t1.content) {
+                "login" -> {
+                    if (instance !is JsonObject) {
+                        e.add(ValidationError("", "/mapping/login/properties"))
+                    } else {
+                        val v2 = instance["actor"]
+                        if (v2 == null) {
+                            e.add(ValidationError("", "/mapping/login/properties/actor"))
+                        } else {
+                            validate_actor(v2, e, "" + "/actor")
+                        }
+                        val v3 = instance["at"]
+                        if (v3 == null) {
+                            e.add(ValidationError("", "/mapping/login/properties/at"))
+                        } else {
+                            if (!(isTimestamp(v3))) e.add(ValidationError("" + "/at", "/mapping/login/properties/at/type"))
+                        }
+                        for (k4 in instance.keys) if (k4 != "actor" && k4 != "at" && k4 != "kind") e.add(ValidationError("" + "/" + k4.replace("~", "~0").replace("/", "~1"), "/mapping/login"))
+                    }
+                }
+                "transfer" -> {
+                    if (instance !is JsonObject) {
+                        e.add(ValidationError("", "/mapping/transfer/properties"))
+                    } else {
+                        val v5 = instance["actor"]
+                        if (v5 == null) {
+                            e.add(ValidationError("", "/mapping/transfer/properties/actor"))
+                        } else {
+                            validate_actor(v5, e, "" + "/actor")
+                        }
+                        val v6 = instance["amount"]
+                        if (v6 == null) {
+                            e.add(ValidationError("", "/mapping/transfer/properties/amount"))
+                        } else {
+                            if (!(isInteger(v6, -2147483648.0, 2147483647.0))) e.add(ValidationError("" + "/amount", "/mapping/transfer/properties/amount/type"))
+                        }
+                        val v7 = instance["currency"]
+                        if (v7 == null) {
+                            e.add(ValidationError("", "/mapping/transfer/properties/currency"))
+                        } else {
+                            if (v7 !is JsonPrimitive || !v7.isString || v7.content !in ENUM_0) e.add(ValidationError("" + "/currency", "/mapping/transfer/properties/currency/enum"))
+                        }
+                        val v8 = instance["note"]
+                        if (v8 != null) {
+                            if (!(v8 is JsonPrimitive && v8.isString)) e.add(ValidationError("" + "/note", "/mapping/transfer/optionalProperties/note/type"))
+                        }
+                        for (k9 in instance.keys) if (k9 != "actor" && k9 != "amount" && k9 != "currency" && k9 != "note" && k9 != "kind") e.add(ValidationError("" + "/" + k9.replace("~", "~0").replace("/", "~1"), "/mapping/transfer"))
+                    }
+                }
+                else -> e.add(ValidationError("" + "/kind", "/mapping"))
+            }
+        }
+    }
+    return e
+}
+
+private fun validate_actor(v: JsonElement, e: MutableList<ValidationError>, p: String) {
+    if (v !is JsonObject) {
+        e.add(ValidationError(p, "/definitions/actor/properties"))
+    } else {
+        val v1 = v["name"]
+        if (v1 == null) {
+            e.add(ValidationError(p, "/definitions/actor/properties/name"))
+        } else {
+            if (!(v1 is JsonPrimitive && v1.isString)) e.add(ValidationError(p + "/name", "/definitions/actor/properties/name/type"))
+        }
+    }
+}
+
+private fun validate_login(v: JsonElement, e: MutableList<ValidationError>, p: String) {
+    if (v !is JsonObject) {
+        e.add(ValidationError(p, "/definitions/login/properties"))
+    } else {
+        val v1 = v["actor"]
+        if (v1 == null) {
+            e.add(ValidationError(p, "/definitions/login/properties/actor"))
+        } else {
+            validate_actor(v1, e, p + "/actor")
+        }
+        val v2 = v["at"]
+        if (v2 == null) {
+            e.add(ValidationError(p, "/definitions/login/properties/at"))
+        } else {
+            if (!(isTimestamp(v2))) e.add(ValidationError(p + "/at", "/definitions/login/properties/at/type"))
+        }
+        for (k3 in v.keys) if (k3 != "actor" && k3 != "at") e.add(ValidationError(p + "/" + k3.replace("~", "~0").replace("/", "~1"), "/definitions/login"))
+    }
+}
+
+private fun number(v: JsonElement): Double? =
+    if (v is JsonPrimitive && !v.isString && v !is JsonNull) v.content.toDoubleOrNull() else null
+
+private fun isInteger(v: JsonElement, min: Double, max: Double): Boolean {
+    val n = number(v) ?: return false
+    return n == kotlin.math.floor(n) && n >= min && n <= max
+}
+
+private val RFC3339 = Regex("""(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))""")
+
+private fun isTimestamp(v: JsonElement): Boolean {
+    if (v !is JsonPrimitive || !v.isString) return false
+    val g = RFC3339.matchEntire(v.content)?.groupValues ?: return false
+    val year = g[1].toInt()
+    val month = g[2].toInt()
+    val day = g[3].toInt()
+    if (month !in 1..12 || g[4].toInt() > 23 || g[5].toInt() > 59 || g[6].toInt() > 60) return false
+    val leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    val days = when (month) {
+        2 -> if (leap) 29 else 28
+        4, 6, 9, 11 -> 30
+        else -> 31
+    }
+    if (day < 1 || day > days) return false
+    return g[8].isEmpty() || (g[8].toInt() <= 23 && g[9].toInt() <= 59)
+}
@@ -0,0 +1,69 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+@file:Suppress("FunctionName", "USELESS_IS_CHECK", "unused")
+
+import kotlinx.serialization.json.JsonArray
+import kotlinx.serialization.json.JsonElement
+import kotlinx.serialization.json.JsonNull
+import kotlinx.serialization.json.JsonObject
+import kotlinx.serialization.json.JsonPrimitive
+
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+data class ValidationError(val instancePath: String, val schemaPath: String)
+
+/** Calls of recursive definitions in progress. */
+private val refDepth: ThreadLocal<Int> = ThreadLocal.withInitial { 0 }
+
+fun validate(instance: JsonElement): List<ValidationError> {
+    val e = mutableListOf<ValidationError>()
+    validate_node(instance, e, "")
+    return e
+}
+
+private fun validate_node(v: JsonElement, e: MutableList<ValidationError>, p: String) {
+    val depth = refDepth.get()
+    if (depth >= 256) {
+        e.add(ValidationError(p, "/definitions/node"))
+        return
+    }
+    refDepth.set(depth + 1)
+    if (v !is JsonObject) {
+        e.add(ValidationError(p, "/definitions/node/properties"))
+    } else {
+        val v1 = v["children"]
+        if (v1 == null) {
+            e.add(ValidationError(p, "/definitions/node/properties/children"))
+        } else {
+            if (v1 !is JsonArray) {
+                e.add(ValidationError(p + "/children", "/definitions/node/properties/children/elements"))
+            } else {
+                for ((i2, v3) in v1.withIndex()) {
+                    validate_node(v3, e, p + "/children" + "/" + i2)
+                }
+            }
+        }
+        val v4 = v["value"]
+        if (v4 == null) {
+            e.add(ValidationError(p, "/definitions/node/properties/value"))
+        } else {
+            if (!(isInteger(v4, -32768.0, 32767.0))) e.add(ValidationError(p + "/value", "/definitions/node/properties/value/type"))
+        }
+        val v5 = v["parent"]
+        if (v5 != null) {
+            if (v5 !is JsonNull) {
+                validate_node(v5, e, p + "/parent")
+            }
+        }
+        for (k6 in v.keys) if (k6 != "children" && k6 != "value" && k6 != "parent") e.add(ValidationError(p + "/" + k6.replace("~", "~0").replace("/", "~1"), "/definitions/node"))
+    }
+    refDepth.set(depth)
+}
+
+private fun number(v: JsonElement): Double? =
+    if (v is JsonPrimitive && !v.isString && v !is JsonNull) v.content.toDoubleOrNull() else null
+
+private fun isInteger(v: JsonElement, min: Double, max: Double): Boolean {
+    val n = number(v) ?: return false
+    return n == kotlin.math.floor(n) && n >= min && n <= max
+}
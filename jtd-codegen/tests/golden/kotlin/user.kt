@@ -0,0 +1,111 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+@file:Suppress("FunctionName", "USELESS_IS_CHECK", "unused")
+
+import kotlinx.serialization.json.JsonArray
+import kotlinx.serialization.json.JsonElement
+import kotlinx.serialization.json.JsonNull
+import kotlinx.serialization.json.JsonObject
+import kotlinx.serialization.json.JsonPrimitive
+
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+data class ValidationError(val instancePath: String, val schemaPath: String)
+
+private val ENUM_0 = setOf("admin", "member", "guest")
+
+/**
+ * An account holder.
+ */
+fun validate(instance: JsonElement): List<ValidationError> {
+    val e = mutableListOf<ValidationError>()
+    if (instance !is JsonObject) {
+        e.add(ValidationError("", "/properties"))
+    } else {
+        val v1 = instance["age"]
+        if (v1 == null) {
+            e.add(ValidationError("", "/properties/age"))
+        } else {
+            if (!(isInteger(v1, 0.0, 255.0))) e.add(ValidationError("" + "/age", "/properties/age/type"))
+        }
+        val v2 = instance["createdAt"]
+        if (v2 == null) {
+            e.add(ValidationError("", "/properties/createdAt"))
+        } else {
+            if (!(isTimestamp(v2))) e.add(ValidationError("" + "/createdAt", "/properties/createdAt/type"))
+        }
+        val v3 = instance["id"]
+        if (v3 == null) {
+            e.add(ValidationError("", "/properties/id"))
+        } else {
+            if (!(v3 is JsonPrimitive && v3.isString)) e.add(ValidationError("" + "/id", "/properties/id/type"))
+        }
+        val v4 = instance["role"]
+        if (v4 == null) {
+            e.add(ValidationError("", "/properties/role"))
+        } else {
+            if (v4 !is JsonPrimitive || !v4.isString || v4.content !in ENUM_0) e.add(ValidationError("" + "/role", "/properties/role/enum"))
+        }
+        val v5 = instance["tags"]
+        if (v5 == null) {
+            e.add(ValidationError("", "/properties/tags"))
+        } else {
+            if (v5 !is JsonArray) {
+                e.add(ValidationError("" + "/tags", "/properties/tags/elements"))
+            } else {
+                for ((i6, v7) in v5.withIndex()) {
+                    if (!(v7 is JsonPrimitive && v7.isString)) e.add(ValidationError("" + "/tags" + "/" + i6, "/properties/tags/elements/type"))
+                }
+            }
+        }
+        val v8 = instance["email"]
+        if (v8 != null) {
+            if (v8 !is JsonNull) {
+                if (!(v8 is JsonPrimitive && v8.isString)) e.add(ValidationError("" + "/email", "/optionalProperties/email/type"))
+            }
+        }
+        val v9 = instance["preferences"]
+        if (v9 != null) {
+            if (v9 !is JsonObject) {
+                e.add(ValidationError("" + "/preferences", "/optionalProperties/preferences/values"))
+            } else {
+                for ((k10, v11) in v9) {
+                    if (!(v11 is JsonPrimitive && !v11.isString && (v11.content == "true" || v11.content == "false"))) e.add(ValidationError("" + "/preferences" + "/" + k10.replace("~", "~0").replace("/", "~1"), "/optionalProperties/preferences/values/type"))
+                }
+            }
+        }
+        val v12 = instance["score"]
+        if (v12 != null) {
+            if (!(number(v12) != null)) e.add(ValidationError("" + "/score", "/optionalProperties/score/type"))
+        }
+        for (k13 in instance.keys) if (k13 != "age" && k13 != "createdAt" && k13 != "id" && k13 != "role" && k13 != "tags" && k13 != "email" && k13 != "preferences" && k13 != "score") e.add(ValidationError("" + "/" + k13.replace("~", "~0").replace("/", "~1"), ""))
+    }
+    return e
+}
+
+private fun number(v: JsonElement): Double? =
+    if (v is JsonPrimitive && !v.isString && v !is JsonNull) v.content.toDoubleOrNull() else null
+
+private fun isInteger(v: JsonElement, min: Double, max: Double): Boolean {
+    val n = number(v) ?: return false
+    return n == kotlin.math.floor(n) && n >= min && n <= max
+}
+
+private val RFC3339 = Regex("""(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?(?:[Zz]|[+-](\d{2}):(\d{2}))""")
+
+private fun isTimestamp(v: JsonElement): Boolean {
+    if (v !is JsonPrimitive || !v.isString) return false
+    val g = RFC3339.matchEntire(v.content)?.groupValues ?: return false
+    val year = g[1].toInt()
+    val month = g[2].toInt()
+    val day = g[3].toInt()
+    if (month !in 1..12 || g[4].toInt() > 23 || g[5].toInt() > 59 || g[6].toInt() > 60) return false
+    val leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    val days = when (month) {
+        2 -> if (leap) 29 else 28
+        4, 6, 9, 11 -> 30
+        else -> 31
+    }
+    if (day < 1 || day > days) return false
+    return g[8].isEmpty() || (g[8].toInt() <= 23 && g[9].toInt() <= 59)
+}
@@ -0,0 +1,166 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+local M = {}
+-- Generated validators require dkjson for null sentinel handling.
+-- Lua 5.1 cannot distinguish JSON null from table absence; dkjson.null
+-- provides a reliable sentinel that preserves JTD validation semantics.
+local dkjson = require("dkjson")
+
+local function is_integer(v)
+  return type(v) == "number" and v == math.floor(v)
+end
+local function is_array(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "object" then return false end
+  if mt and mt.__jsontype == "array" then return true end
+  if #v > 0 then return true end
+  return next(v) == nil
+end
+local function is_object(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "array" then return false end
+  if mt and mt.__jsontype == "object" then return true end
+  if #v > 0 then return false end
+  return true
+end
+
+local function is_rfc3339(s)
+  if type(s) ~= "string" then return false end
+  local y, mo, d, h, mi, sec, rest = s:match("^([0-9][0-9][0-9][0-9])%-([0-9][0-9])%-([0-9][0-9])[Tt]([0-9][0-9]):([0-9][0-9]):([0-9][0-9])(.*)$")
+  if not y then return false end
+  y, mo, d = tonumber(y), tonumber(mo), tonumber(d)
+  if mo < 1 or mo > 12 or d < 1 then return false end
+  local leap = y % 4 == 0 and (y % 100 ~= 0 or y % 400 == 0)
+  if d > ({31, leap and 29 or 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31})[mo] then return false end
+  if tonumber(h) > 23 or tonumber(mi) > 59 or tonumber(sec) > 60 then return false end
+  -- An optional fraction of at least one digit, then the offset
+  local frac = rest:match("^%.[0-9]+")
+  if frac then rest = rest:sub(#frac + 1) end
+  if rest == "Z" or rest == "z" then return true end
+  local oh, om = rest:match("^[+-]([0-9][0-9]):([0-9][0-9])$")
+  return oh ~= nil and tonumber(oh) <= 23 and tonumber(om) <= 59
+end
+
+local validate_actor, validate_login
+
+function validate_actor(v, e, p, sp)
+  if is_object(v) then
+    if v["name"] == nil then
+      table.insert(e, {instancePath = p, schemaPath = sp .. "/properties/name"})
+    else
+      if type(v["name"]) ~= "string" then
+        table.insert(e, {instancePath = p .. "/name", schemaPath = sp .. "/properties/name" .. "/type"})
+      end
+    end
+  else
+    table.insert(e, {instancePath = p, schemaPath = sp .. "/properties"})
+  end
+end
+
+function validate_login(v, e, p, sp)
+  if is_object(v) then
+    if v["actor"] == nil then
+      table.insert(e, {instancePath = p, schemaPath = sp .. "/properties/actor"})
+    else
+      validate_actor(v["actor"], e, p .. "/actor", "/definitions/actor")
+    end
+    if v["at"] == nil then
+      table.insert(e, {instancePath = p, schemaPath = sp .. "/properties/at"})
+    else
+      if not is_rfc3339(v["at"]) then
+        table.insert(e, {instancePath = p .. "/at", schemaPath = sp .. "/properties/at" .. "/type"})
+      end
+    end
+    for k in pairs(v) do
+      if k ~= "actor" and k ~= "at" then
+        table.insert(e, {instancePath = p .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = sp})
+      end
+    end
+  else
+    table.insert(e, {instancePath = p, schemaPath = sp .. "/properties"})
+  end
+end
+
+function M.validate(instance)
+  local e = {}
+  if is_object(instance) then
+    if instance["kind"] ~= nil then
+      if type(instance["kind"]) == "string" then
+        if instance["kind"] == "login" then
+          if is_object(instance) then
+            if instance["actor"] == nil then
+              table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/login" .. "/properties/actor"})
+            else
+              validate_actor(instance["actor"], e, "" .. "/actor", "/definitions/actor")
+            end
+            if instance["at"] == nil then
+              table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/login" .. "/properties/at"})
+            else
+              if not is_rfc3339(instance["at"]) then
+                table.insert(e, {instancePath = "" .. "/at", schemaPath = "" .. "/mapping/login" .. "/properties/at" .. "/type"})
+              end
+            end
+            for k in pairs(instance) do
+              if k ~= "kind" and k ~= "actor" and k ~= "at" then
+                table.insert(e, {instancePath = "" .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = "" .. "/mapping/login"})
+              end
+            end
+          else
+            table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/login" .. "/properties"})
+          end
+        elseif instance["kind"] == "transfer" then
+          if is_object(instance) then
+            if instance["actor"] == nil then
+              table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/transfer" .. "/properties/actor"})
+            else
+              validate_actor(instance["actor"], e, "" .. "/actor", "/definitions/actor")
+            end
+            if instance["amount"] == nil then
+              table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/transfer" .. "/properties/amount"})
+            else
+              if not is_integer(instance["amount"]) or instance["amount"] < -2147483648 or instance["amount"] > 2147483647 then
+                table.insert(e, {instancePath = "" .. "/amount", schemaPath = "" .. "/mapping/transfer" .. "/properties/amount" .. "/type"})
+              end
+            end
+            if instance["currency"] == nil then
+              table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/transfer" .. "/properties/currency"})
+            else
+              if instance["currency"] ~= "EUR" and instance["currency"] ~= "GBP" and instance["currency"] ~= "USD" then
+                table.insert(e, {instancePath = "" .. "/currency", schemaPath = "" .. "/mapping/transfer" .. "/properties/currency" .. "/enum"})
+              end
+            end
+            if instance["note"] ~= nil then
+              if type(instance["note"]) ~= "string" then
+                table.insert(e, {instancePath = "" .. "/note", schemaPath = "" .. "/mapping/transfer" .. "/optionalProperties/note" .. "/type"})
+              end
+            end
+            for k in pairs(instance) do
+              if k ~= "kind" and k ~= "actor" and k ~= "amount" and k ~= "currency" and k ~= "note" then
+                table.insert(e, {instancePath = "" .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = "" .. "/mapping/transfer"})
+              end
+            end
+          else
+            table.insert(e, {instancePath = "", schemaPath = "" .. "/mapping/transfer" .. "/properties"})
+          end
+        else
+          table.insert(e, {instancePath = "" .. "/kind", schemaPath = "" .. "/mapping"})
+        end
+      else
+        table.insert(e, {instancePath = "" .. "/kind", schemaPath = "" .. "/discriminator"})
+      end
+    else
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/discriminator"})
+    end
+  else
+    table.insert(e, {instancePath = "", schemaPath = "" .. "/discriminator"})
+  end
+  return e
+end
+
+return M
@@ -0,0 +1,84 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+local M = {}
+-- Generated validators require dkjson for null sentinel handling.
+-- Lua 5.1 cannot distinguish JSON null from table absence; dkjson.null
+-- provides a reliable sentinel that preserves JTD validation semantics.
+local dkjson = require("dkjson")
+
+local function is_integer(v)
+  return type(v) == "number" and v == math.floor(v)
+end
+local function is_array(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "object" then return false end
+  if mt and mt.__jsontype == "array" then return true end
+  if #v > 0 then return true end
+  return next(v) == nil
+end
+local function is_object(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "array" then return false end
+  if mt and mt.__jsontype == "object" then return true end
+  if #v > 0 then return false end
+  return true
+end
+
+local validate_node
+
+local ref_depth = 0
+
+function validate_node(v, e, p, sp)
+  if ref_depth >= 256 then
+    table.insert(e, {instancePath = p, schemaPath = sp})
+    return
+  end
+  ref_depth = ref_depth + 1
+  if is_object(v) then
+    if v["children"] == nil then
+      table.insert(e, {instancePath = p, schemaPath = sp .. "/properties/children"})
+    else
+      if is_array(v["children"]) then
+        for i, elem in ipairs(v["children"]) do
+          validate_node(elem, e, p .. "/children" .. "/" .. (i - 1), "/definitions/node")
+        end
+      else
+        table.insert(e, {instancePath = p .. "/children", schemaPath = sp .. "/properties/children" .. "/elements"})
+      end
+    end
+    if v["value"] == nil then
+      table.insert(e, {instancePath = p, schemaPath = sp .. "/properties/value"})
+    else
+      if not is_integer(v["value"]) or v["value"] < -32768 or v["value"] > 32767 then
+        table.insert(e, {instancePath = p .. "/value", schemaPath = sp .. "/properties/value" .. "/type"})
+      end
+    end
+    if v["parent"] ~= nil then
+      if v["parent"] ~= nil and v["parent"] ~= dkjson.null then
+        validate_node(v["parent"], e, p .. "/parent", "/definitions/node")
+      end
+    end
+    for k in pairs(v) do
+      if k ~= "children" and k ~= "value" and k ~= "parent" then
+        table.insert(e, {instancePath = p .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = sp})
+      end
+    end
+  else
+    table.insert(e, {instancePath = p, schemaPath = sp .. "/properties"})
+  end
+  ref_depth = ref_depth - 1
+end
+
+function M.validate(instance)
+  local e = {}
+  validate_node(instance, e, "", "/definitions/node")
+  return e
+end
+
+return M
@@ -0,0 +1,129 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+local M = {}
+-- Generated validators require dkjson for null sentinel handling.
+-- Lua 5.1 cannot distinguish JSON null from table absence; dkjson.null
+-- provides a reliable sentinel that preserves JTD validation semantics.
+local dkjson = require("dkjson")
+
+local function is_integer(v)
+  return type(v) == "number" and v == math.floor(v)
+end
+local function is_array(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "object" then return false end
+  if mt and mt.__jsontype == "array" then return true end
+  if #v > 0 then return true end
+  return next(v) == nil
+end
+local function is_object(v)
+  if v == dkjson.null then return false end
+  if type(v) ~= "table" then return false end
+  local mt = getmetatable(v)
+  if mt and mt.__jsontype == "array" then return false end
+  if mt and mt.__jsontype == "object" then return true end
+  if #v > 0 then return false end
+  return true
+end
+
+local function is_rfc3339(s)
+  if type(s) ~= "string" then return false end
+  local y, mo, d, h, mi, sec, rest = s:match("^([0-9][0-9][0-9][0-9])%-([0-9][0-9])%-([0-9][0-9])[Tt]([0-9][0-9]):([0-9][0-9]):([0-9][0-9])(.*)$")
+  if not y then return false end
+  y, mo, d = tonumber(y), tonumber(mo), tonumber(d)
+  if mo < 1 or mo > 12 or d < 1 then return false end
+  local leap = y % 4 == 0 and (y % 100 ~= 0 or y % 400 == 0)
+  if d > ({31, leap and 29 or 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31})[mo] then return false end
+  if tonumber(h) > 23 or tonumber(mi) > 59 or tonumber(sec) > 60 then return false end
+  -- An optional fraction of at least one digit, then the offset
+  local frac = rest:match("^%.[0-9]+")
+  if frac then rest = rest:sub(#frac + 1) end
+  if rest == "Z" or rest == "z" then return true end
+  local oh, om = rest:match("^[+-]([0-9][0-9]):([0-9][0-9])$")
+  return oh ~= nil and tonumber(oh) <= 23 and tonumber(om) <= 59
+end
+
+--- An account holder.
+function M.validate(instance)
+  local e = {}
+  if is_object(instance) then
+    if instance["age"] == nil then
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/properties/age"})
+    else
+      if not is_integer(instance["age"]) or instance["age"] < 0 or instance["age"] > 255 then
+        table.insert(e, {instancePath = "" .. "/age", schemaPath = "" .. "/properties/age" .. "/type"})
+      end
+    end
+    if instance["createdAt"] == nil then
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/properties/createdAt"})
+    else
+      if not is_rfc3339(instance["createdAt"]) then
+        table.insert(e, {instancePath = "" .. "/createdAt", schemaPath = "" .. "/properties/createdAt" .. "/type"})
+      end
+    end
+    if instance["id"] == nil then
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/properties/id"})
+    else
+      if type(instance["id"]) ~= "string" then
+        table.insert(e, {instancePath = "" .. "/id", schemaPath = "" .. "/properties/id" .. "/type"})
+      end
+    end
+    if instance["role"] == nil then
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/properties/role"})
+    else
+      if instance["role"] ~= "admin" and instance["role"] ~= "member" and instance["role"] ~= "guest" then
+        table.insert(e, {instancePath = "" .. "/role", schemaPath = "" .. "/properties/role" .. "/enum"})
+      end
+    end
+    if instance["tags"] == nil then
+      table.insert(e, {instancePath = "", schemaPath = "" .. "/properties/tags"})
+    else
+      if is_array(instance["tags"]) then
+        for i, elem in ipairs(instance["tags"]) do
+          if type(elem) ~= "string" then
+            table.insert(e, {instancePath = "" .. "/tags" .. "/" .. (i - 1), schemaPath = "" .. "/properties/tags" .. "/elements" .. "/type"})
+          end
+        end
+      else
+        table.insert(e, {instancePath = "" .. "/tags", schemaPath = "" .. "/properties/tags" .. "/elements"})
+      end
+    end
+    if instance["email"] ~= nil then
+      if instance["email"] ~= nil and instance["email"] ~= dkjson.null then
+        if type(instance["email"]) ~= "string" then
+          table.insert(e, {instancePath = "" .. "/email", schemaPath = "" .. "/optionalProperties/email" .. "/type"})
+        end
+      end
+    end
+    if instance["preferences"] ~= nil then
+      if is_object(instance["preferences"]) then
+        for k, val in pairs(instance["preferences"]) do
+          if type(val) ~= "boolean" then
+            table.insert(e, {instancePath = "" .. "/preferences" .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = "" .. "/optionalProperties/preferences" .. "/values" .. "/type"})
+          end
+        end
+      else
+        table.insert(e, {instancePath = "" .. "/preferences", schemaPath = "" .. "/optionalProperties/preferences" .. "/values"})
+      end
+    end
+    if instance["score"] ~= nil then
+      if type(instance["score"]) ~= "number" then
+        table.insert(e, {instancePath = "" .. "/score", schemaPath = "" .. "/optionalProperties/score" .. "/type"})
+      end
+    end
+    for k in pairs(instance) do
+      if k ~= "age" and k ~= "createdAt" and k ~= "id" and k ~= "role" and k ~= "tags" and k ~= "email" and k ~= "preferences" and k ~= "score" then
+        table.insert(e, {instancePath = "" .. "/" .. (k:gsub("~", "~0"):gsub("/", "~1")), schemaPath = ""})
+      end
+    end
+  else
+    table.insert(e, {instancePath = "", schemaPath = "" .. "/properties"})
+  end
+  return e
+end
+
+return M
@@ -0,0 +1,103 @@
+# fmt: off
+# Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+# Do not edit manually.
+import re
+
+_TS_RE = re.compile(r"([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))\Z")
+
+def _is_rfc3339(s: object) -> bool:
+    if not isinstance(s, str):
+        return False
+    m = _TS_RE.match(s)
+    if m is None:
+        return False
+    y, mo, d = int(m.group(1)), int(m.group(2)), int(m.group(3))
+    if mo < 1 or mo > 12 or d < 1:
+        return False
+    leap = y % 4 == 0 and (y % 100 != 0 or y % 400 == 0)
+    if d > (31, 29 if leap else 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31)[mo - 1]:
+        return False
+    if int(m.group(4)) > 23 or int(m.group(5)) > 59 or int(m.group(6)) > 60:
+        return False
+    return m.group(7) is None or (int(m.group(7)) <= 23 and int(m.group(8)) <= 59)
+
+
+def validate_actor(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:
+    if not isinstance(v, dict):
+        e.append({"instancePath": p, "schemaPath": sp + "/properties"})
+    else:
+        if "name" not in v:
+            e.append({"instancePath": p, "schemaPath": sp + "/properties/name"})
+        else:
+            if not isinstance(v["name"], str):
+                e.append({"instancePath": p + "/name", "schemaPath": sp + "/properties/name" + "/type"})
+
+def validate_login(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:
+    if not isinstance(v, dict):
+        e.append({"instancePath": p, "schemaPath": sp + "/properties"})
+    else:
+        if "actor" not in v:
+            e.append({"instancePath": p, "schemaPath": sp + "/properties/actor"})
+        else:
+            validate_actor(v["actor"], e, p + "/actor", "/definitions/actor")
+        if "at" not in v:
+            e.append({"instancePath": p, "schemaPath": sp + "/properties/at"})
+        else:
+            if not _is_rfc3339(v["at"]):
+                e.append({"instancePath": p + "/at", "schemaPath": sp + "/properties/at" + "/type"})
+        for k in v:
+            if k != "actor" and k != "at":
+                e.append({"instancePath": p + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": sp})
+
+def validate(instance: object) -> list[dict[str, str]]:
+    e: list[dict[str, str]] = []
+    if not isinstance(instance, dict):
+        e.append({"instancePath": "", "schemaPath": "" + "/discriminator"})
+    elif "kind" not in instance:
+        e.append({"instancePath": "", "schemaPath": "" + "/discriminator"})
+    elif not isinstance(instance["kind"], str):
+        e.append({"instancePath": "" + "/kind", "schemaPath": "" + "/discriminator"})
+    elif instance["kind"] == "login":
+        if not isinstance(instance, dict):
+            e.append({"instancePath": "", "schemaPath": "" + "/mapping/login" + "/properties"})
+        else:
+            if "actor" not in instance:
+                e.append({"instancePath": "", "schemaPath": "" + "/mapping/login" + "/properties/actor"})
+            else:
+                validate_actor(instance["actor"], e, "" + "/actor", "/definitions/actor")
+            if "at" not in instance:
+                e.append({"instancePath": "", "schemaPath": "" + "/mapping/login" + "/properties/at"})
+            else:
+                if not _is_rfc3339(instance["at"]):
+                    e.append({"instancePath": "" + "/at", "schemaPath": "" + "/mapping/login" + "/properties/at" + "/type"})
+            for k in instance:
+                if k != "kind" and k != "actor" and k != "at":
+                    e.append({"instancePath": "" + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": "" + "/mapping/login"})
+    elif instance["kind"] == "transfer":
+        if not isinstance(instance, dict):
+            e.append({"instancePath": "", "schemaPath": "" + "/mapping/transfer" + "/properties"})
+        else:
+            if "actor" not in instance:
+                e.append({"instancePath": "", "schemaPath": "" + "/mapping/transfer" + "/properties/actor"})
+            else:
+                validate_actor(instance["actor"], e, "" + "/actor", "/definitions/actor")
+            if "amount" not in instance:
+                e.append({"instancePath": "", "schemaPath": "" + "/mapping/transfer" + "/properties/amount"})
+            else:
+                if not isinstance(instance["amount"], (int, float)) or isinstance(instance["amount"], bool) or instance["amount"] % 1 != 0 or instance["amount"] < -2147483648 or instance["amount"] > 2147483647:
+                    e.append({"instancePath": "" + "/amount", "schemaPath": "" + "/mapping/transfer" + "/properties/amount" + "/type"})
+            if "currency" not in instance:
+                e.append({"instancePath": "", "schemaPath": "" + "/mapping/transfer" + "/properties/currency"})
+            else:
+                if not isinstance(instance["currency"], str) or instance["currency"] not in {"EUR", "GBP", "USD"}:
+                    e.append({"instancePath": "" + "/currency", "schemaPath": "" + "/mapping/transfer" + "/properties/currency" + "/enum"})
+            if "note" in instance:
+                if not isinstance(instance["note"], str):
+                    e.append({"instancePath": "" + "/note", "schemaPath": "" + "/mapping/transfer" + "/optionalProperties/note" + "/type"})
+            for k in instance:
+                if k != "kind" and k != "actor" and k != "amount" and k != "currency" and k != "note":
+                    e.append({"instancePath": "" + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": "" + "/mapping/transfer"})
+    else:
+        e.append({"instancePath": "" + "/kind", "schemaPath": "" + "/mapping"})
+    return e
+# fmt: on
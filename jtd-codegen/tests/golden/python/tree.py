@@ -0,0 +1,43 @@
+# fmt: off
+# Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+# Do not edit manually.
+import threading
+
+_ref_depth = threading.local()
+
+
+def validate_node(v: object, e: list[dict[str, str]], p: str, sp: str) -> None:
+    depth = getattr(_ref_depth, "n", 0)
+    if depth >= 256:
+        e.append({"instancePath": p, "schemaPath": sp})
+        return
+    _ref_depth.n = depth + 1
+    if not isinstance(v, dict):
+        e.append({"instancePath": p, "schemaPath": sp + "/properties"})
+    else:
+        if "children" not in v:
+            e.append({"instancePath": p, "schemaPath": sp + "/properties/children"})
+        else:
+            if not isinstance(v["children"], list):
+                e.append({"instancePath": p + "/children", "schemaPath": sp + "/properties/children" + "/elements"})
+            else:
+                for i in range(len(v["children"])):
+                    validate_node(v["children"][i], e, p + "/children" + "/" + str(i), "/definitions/node")
+        if "value" not in v:
+            e.append({"instancePath": p, "schemaPath": sp + "/properties/value"})
+        else:
+            if not isinstance(v["value"], (int, float)) or isinstance(v["value"], bool) or v["value"] % 1 != 0 or v["value"] < -32768 or v["value"] > 32767:
+                e.append({"instancePath": p + "/value", "schemaPath": sp + "/properties/value" + "/type"})
+        if "parent" in v:
+            if v["parent"] is not None:
+                validate_node(v["parent"], e, p + "/parent", "/definitions/node")
+        for k in v:
+            if k != "children" and k != "value" and k != "parent":
+                e.append({"instancePath": p + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": sp})
+    _ref_depth.n = depth
+
+def validate(instance: object) -> list[dict[str, str]]:
+    e: list[dict[str, str]] = []
+    validate_node(instance, e, "", "/definitions/node")
+    return e
+# fmt: on
@@ -0,0 +1,78 @@
+# fmt: off
+# Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+# Do not edit manually.
+import re
+
+_TS_RE = re.compile(r"([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))\Z")
+
+def _is_rfc3339(s: object) -> bool:
+    if not isinstance(s, str):
+        return False
+    m = _TS_RE.match(s)
+    if m is None:
+        return False
+    y, mo, d = int(m.group(1)), int(m.group(2)), int(m.group(3))
+    if mo < 1 or mo > 12 or d < 1:
+        return False
+    leap = y % 4 == 0 and (y % 100 != 0 or y % 400 == 0)
+    if d > (31, 29 if leap else 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31)[mo - 1]:
+        return False
+    if int(m.group(4)) > 23 or int(m.group(5)) > 59 or int(m.group(6)) > 60:
+        return False
+    return m.group(7) is None or (int(m.group(7)) <= 23 and int(m.group(8)) <= 59)
+
+
+def validate(instance: object) -> list[dict[str, str]]:
+    """An account holder."""
+    e: list[dict[str, str]] = []
+    if not isinstance(instance, dict):
+        e.append({"instancePath": "", "schemaPath": "" + "/properties"})
+    else:
+        if "age" not in instance:
+            e.append({"instancePath": "", "schemaPath": "" + "/properties/age"})
+        else:
+            if not isinstance(instance["age"], (int, float)) or isinstance(instance["age"], bool) or instance["age"] % 1 != 0 or instance["age"] < 0 or instance["age"] > 255:
+                e.append({"instancePath": "" + "/age", "schemaPath": "" + "/properties/age" + "/type"})
+        if "createdAt" not in instance:
+            e.append({"instancePath": "", "schemaPath": "" + "/properties/createdAt"})
+        else:
+            if not _is_rfc3339(instance["createdAt"]):
+                e.append({"instancePath": "" + "/createdAt", "schemaPath": "" + "/properties/createdAt" + "/type"})
+        if "id" not in instance:
+            e.append({"instancePath": "", "schemaPath": "" + "/properties/id"})
+        else:
+            if not isinstance(instance["id"], str):
+                e.append({"instancePath": "" + "/id", "schemaPath": "" + "/properties/id" + "/type"})
+        if "role" not in instance:
+            e.append({"instancePath": "", "schemaPath": "" + "/properties/role"})
+        else:
+            if not isinstance(instance["role"], str) or instance["role"] not in {"admin", "member", "guest"}:
+                e.append({"instancePath": "" + "/role", "schemaPath": "" + "/properties/role" + "/enum"})
+        if "tags" not in instance:
+            e.append({"instancePath": "", "schemaPath": "" + "/properties/tags"})
+        else:
+            if not isinstance(instance["tags"], list):
+                e.append({"instancePath": "" + "/tags", "schemaPath": "" + "/properties/tags" + "/elements"})
+            else:
+                for i in range(len(instance["tags"])):
+                    if not isinstance(instance["tags"][i], str):
+                        e.append({"instancePath": "" + "/tags" + "/" + str(i), "schemaPath": "" + "/properties/tags" + "/elements" + "/type"})
+        if "email" in instance:
+            if instance["email"] is not None:
+                if not isinstance(instance["email"], str):
+                    e.append({"instancePath": "" + "/email", "schemaPath": "" + "/optionalProperties/email" + "/type"})
+        if "preferences" in instance:
+            if not isinstance(instance["preferences"], dict):
+                e.append({"instancePath": "" + "/preferences", "schemaPath": "" + "/optionalProperties/preferences" + "/values"})
+            else:
+                for k in instance["preferences"]:
+                    if not isinstance(instance["preferences"][k], bool):
+                        e.append({"instancePath": "" + "/preferences" + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": "" + "/optionalProperties/preferences" + "/values" + "/type"})
+        if "score" in instance:
+            if not isinstance(instance["score"], (int, float)) or isinstance(instance["score"], bool):
+                e.append({"instancePath": "" + "/score", "schemaPath": "" + "/optionalProperties/score" + "/type"})
+        for k in instance:
+            if k != "age" and k != "createdAt" and k != "id" and k != "role" and k != "tags" and k != "email" and k != "preferences" and k != "score":
+                e.append({"instancePath": "" + "/" + k.replace("~", "~0").replace("/", "~1"), "schemaPath": ""})
+    return e
+# fmt: on
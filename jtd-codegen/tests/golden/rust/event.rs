@@ -0,0 +1,241 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+
+use serde_json::Value;
+
+/// A JSON Pointer built only when an error reports it: each descent
+/// links a segment onto its parent instead of formatting a new string.
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+enum LazyPointer<'a> {
+  /// A whole pointer, e.g. `""` or `"/definitions/node"`.
+  Base(&'a str),
+  /// The parent followed by already escaped text, e.g. `"/properties/id"`.
+  Lit(&'a LazyPointer<'a>, &'static str),
+  /// The parent followed by an object key, escaped per RFC 6901.
+  Key(&'a LazyPointer<'a>, &'a str),
+  /// The parent followed by an array index.
+  Index(&'a LazyPointer<'a>, usize),
+}
+
+impl std::fmt::Display for LazyPointer<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LazyPointer::Base(s) => f.write_str(s),
+      LazyPointer::Lit(p, s) => write!(f, "{p}{s}"),
+      LazyPointer::Key(p, k) => write!(f, "{p}/{}", k.replace('~', "~0").replace('/', "~1")),
+      LazyPointer::Index(p, i) => write!(f, "{p}/{i}"),
+    }
+  }
+}
+
+/// A validation error: the value at `instance_path` fails the schema
+/// keyword at `schema_path`, both JSON Pointers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValidationError {
+  /// Where the value is in the instance, e.g. `/items/0`.
+  pub instance_path: String,
+  /// The keyword it fails, e.g. `/properties/items/elements/type`.
+  pub schema_path: String,
+}
+
+impl ValidationError {
+  /// The error as an `(instance path, schema path)` tuple.
+  pub fn into_tuple(self) -> (String, String) {
+    (self.instance_path, self.schema_path)
+  }
+}
+
+impl std::fmt::Display for ValidationError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "value at {:?} fails {:?}", self.instance_path, self.schema_path)
+  }
+}
+
+impl std::error::Error for ValidationError {}
+
+fn is_rfc3339(s: &str) -> bool {
+  let b = s.as_bytes();
+  let digits = |at: usize, n: usize| -> Option<u32> {
+    b.get(at..at + n)?
+      .iter()
+      .try_fold(0u32, |acc, c| c.is_ascii_digit().then(|| acc * 10 + u32::from(c - b'0')))
+  };
+  let sep = |i: usize, options: &[u8]| b.get(i).is_some_and(|c| options.contains(c));
+  if !(sep(4, b"-") && sep(7, b"-") && sep(10, b"Tt") && sep(13, b":") && sep(16, b":")) {
+    return false;
+  }
+  let (Some(year), Some(month), Some(day)) = (digits(0, 4), digits(5, 2), digits(8, 2)) else {
+    return false;
+  };
+  let (Some(hour), Some(minute), Some(second)) = (digits(11, 2), digits(14, 2), digits(17, 2)) else {
+    return false;
+  };
+  let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+  let days = match month {
+    2 => 28 + u32::from(leap),
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  };
+  if !(1..=12).contains(&month) || day < 1 || day > days || hour > 23 || minute > 59 || second > 60 {
+    return false;
+  }
+  let mut rest = &b[19..];
+  if let Some(frac) = rest.strip_prefix(b".") {
+    let n = frac.iter().take_while(|c| c.is_ascii_digit()).count();
+    if n == 0 {
+      return false;
+    }
+    rest = &frac[n..];
+  }
+  match rest {
+    [b'Z' | b'z'] => true,
+    [b'+' | b'-', ..] if rest.len() == 6 && rest[3] == b':' => {
+      let b = rest;
+      let two = |at: usize| (b[at].is_ascii_digit() && b[at + 1].is_ascii_digit()).then(|| u32::from(b[at] - b'0') * 10 + u32::from(b[at + 1] - b'0'));
+      matches!((two(1), two(4)), (Some(h), Some(m)) if h <= 23 && m <= 59)
+    }
+    _ => false,
+  }
+}
+
+fn validate_actor(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("name") {
+      let ip_p0 = LazyPointer::Lit(&p, "/name");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/name");
+      if !pv.is_string() {
+        e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/name") });
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+}
+
+fn validate_login(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("actor") {
+      let ip_p0 = LazyPointer::Lit(&p, "/actor");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/actor");
+      validate_actor(pv, e, ip_p0, LazyPointer::Base("/definitions/actor"));
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/actor") });
+    }
+    if let Some(pv) = obj.get("at") {
+      let ip_p1 = LazyPointer::Lit(&p, "/at");
+      let sp_p1 = LazyPointer::Lit(&sp, "/properties/at");
+      if !pv.as_str().map_or(false, |s| is_rfc3339(s)) {
+        e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/at") });
+    }
+    for k in obj.keys() {
+      if k.as_str() != "actor" && k.as_str() != "at" {
+        e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp.to_string() });
+      }
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+}
+
+pub fn validate(instance: &Value) -> Vec<ValidationError> {
+  let mut e: Vec<ValidationError> = Vec::new();
+  let p = LazyPointer::Base("");
+  let sp = LazyPointer::Base("");
+  if let Some(obj) = instance.as_object() {
+    if let Some(tag_val) = obj.get("kind") {
+      if let Some(tag_str) = tag_val.as_str() {
+        match tag_str {
+          "login" => {
+            let sp_m0 = LazyPointer::Lit(&sp, "/mapping/login");
+            if let Some(obj) = instance.as_object() {
+              if let Some(pv) = obj.get("actor") {
+                let ip_p0 = LazyPointer::Lit(&p, "/actor");
+                let sp_p0 = LazyPointer::Lit(&sp_m0, "/properties/actor");
+                validate_actor(pv, &mut e, ip_p0, LazyPointer::Base("/definitions/actor"));
+              } else {
+                e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m0}/properties/actor") });
+              }
+              if let Some(pv) = obj.get("at") {
+                let ip_p1 = LazyPointer::Lit(&p, "/at");
+                let sp_p1 = LazyPointer::Lit(&sp_m0, "/properties/at");
+                if !pv.as_str().map_or(false, |s| is_rfc3339(s)) {
+                  e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+                }
+              } else {
+                e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m0}/properties/at") });
+              }
+              for k in obj.keys() {
+                if k.as_str() != "kind" && k.as_str() != "actor" && k.as_str() != "at" {
+                  e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp_m0.to_string() });
+                }
+              }
+            } else {
+              e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m0}/properties") });
+            }
+          }
+          "transfer" => {
+            let sp_m1 = LazyPointer::Lit(&sp, "/mapping/transfer");
+            if let Some(obj) = instance.as_object() {
+              if let Some(pv) = obj.get("actor") {
+                let ip_p0 = LazyPointer::Lit(&p, "/actor");
+                let sp_p0 = LazyPointer::Lit(&sp_m1, "/properties/actor");
+                validate_actor(pv, &mut e, ip_p0, LazyPointer::Base("/definitions/actor"));
+              } else {
+                e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m1}/properties/actor") });
+              }
+              if let Some(pv) = obj.get("amount") {
+                let ip_p1 = LazyPointer::Lit(&p, "/amount");
+                let sp_p1 = LazyPointer::Lit(&sp_m1, "/properties/amount");
+                if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= -2147483648_f64 && n <= 2147483647_f64) {
+                  e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+                }
+              } else {
+                e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m1}/properties/amount") });
+              }
+              if let Some(pv) = obj.get("currency") {
+                let ip_p2 = LazyPointer::Lit(&p, "/currency");
+                let sp_p2 = LazyPointer::Lit(&sp_m1, "/properties/currency");
+                if !pv.as_str().map_or(false, |s| ["EUR", "GBP", "USD"].contains(&s)) {
+                  e.push(ValidationError { instance_path: ip_p2.to_string(), schema_path: format!("{sp_p2}/enum") });
+                }
+              } else {
+                e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m1}/properties/currency") });
+              }
+              if let Some(pv) = obj.get("note") {
+                let ip_o0 = LazyPointer::Lit(&p, "/note");
+                let sp_o0 = LazyPointer::Lit(&sp_m1, "/optionalProperties/note");
+                if !pv.is_string() {
+                  e.push(ValidationError { instance_path: ip_o0.to_string(), schema_path: format!("{sp_o0}/type") });
+                }
+              }
+              for k in obj.keys() {
+                if k.as_str() != "kind" && k.as_str() != "actor" && k.as_str() != "amount" && k.as_str() != "currency" && k.as_str() != "note" {
+                  e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp_m1.to_string() });
+                }
+              }
+            } else {
+              e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp_m1}/properties") });
+            }
+          }
+          _ => {
+            e.push(ValidationError { instance_path: format!("{p}/kind"), schema_path: format!("{sp}/mapping") });
+          }
+        }
+      } else {
+        e.push(ValidationError { instance_path: format!("{p}/kind"), schema_path: format!("{sp}/discriminator") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/discriminator") });
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/discriminator") });
+  }
+  e
+}
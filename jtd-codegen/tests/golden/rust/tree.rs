@@ -0,0 +1,118 @@
+// Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+// This code is generated from a JSON Type Definition schema.
+// Do not edit manually.
+
+use serde_json::Value;
+
+/// A JSON Pointer built only when an error reports it: each descent
+/// links a segment onto its parent instead of formatting a new string.
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+enum LazyPointer<'a> {
+  /// A whole pointer, e.g. `""` or `"/definitions/node"`.
+  Base(&'a str),
+  /// The parent followed by already escaped text, e.g. `"/properties/id"`.
+  Lit(&'a LazyPointer<'a>, &'static str),
+  /// The parent followed by an object key, escaped per RFC 6901.
+  Key(&'a LazyPointer<'a>, &'a str),
+  /// The parent followed by an array index.
+  Index(&'a LazyPointer<'a>, usize),
+}
+
+impl std::fmt::Display for LazyPointer<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LazyPointer::Base(s) => f.write_str(s),
+      LazyPointer::Lit(p, s) => write!(f, "{p}{s}"),
+      LazyPointer::Key(p, k) => write!(f, "{p}/{}", k.replace('~', "~0").replace('/', "~1")),
+      LazyPointer::Index(p, i) => write!(f, "{p}/{i}"),
+    }
+  }
+}
+
+/// A validation error: the value at `instance_path` fails the schema
+/// keyword at `schema_path`, both JSON Pointers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValidationError {
+  /// Where the value is in the instance, e.g. `/items/0`.
+  pub instance_path: String,
+  /// The keyword it fails, e.g. `/properties/items/elements/type`.
+  pub schema_path: String,
+}
+
+impl ValidationError {
+  /// The error as an `(instance path, schema path)` tuple.
+  pub fn into_tuple(self) -> (String, String) {
+    (self.instance_path, self.schema_path)
+  }
+}
+
+impl std::fmt::Display for ValidationError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "value at {:?} fails {:?}", self.instance_path, self.schema_path)
+  }
+}
+
+impl std::error::Error for ValidationError {}
+
+thread_local! {
+  static REF_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn validate_node(v: &Value, e: &mut Vec<ValidationError>, p: LazyPointer<'_>, sp: LazyPointer<'_>) {
+  let depth = REF_DEPTH.with(|d| d.get());
+  if depth >= 256 {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: sp.to_string() });
+    return;
+  }
+  REF_DEPTH.with(|d| d.set(depth + 1));
+  if let Some(obj) = v.as_object() {
+    if let Some(pv) = obj.get("children") {
+      let ip_p0 = LazyPointer::Lit(&p, "/children");
+      let sp_p0 = LazyPointer::Lit(&sp, "/properties/children");
+      if let Some(arr) = pv.as_array() {
+        for (i, elem) in arr.iter().enumerate() {
+          let ip_e0 = LazyPointer::Index(&ip_p0, i);
+          let sp_e0 = LazyPointer::Lit(&sp_p0, "/elements");
+          validate_node(elem, e, ip_e0, LazyPointer::Base("/definitions/node"));
+        }
+      } else {
+        e.push(ValidationError { instance_path: ip_p0.to_string(), schema_path: format!("{sp_p0}/elements") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/children") });
+    }
+    if let Some(pv) = obj.get("value") {
+      let ip_p1 = LazyPointer::Lit(&p, "/value");
+      let sp_p1 = LazyPointer::Lit(&sp, "/properties/value");
+      if !pv.as_f64().map_or(false, |n| n.fract() == 0.0 && n >= -32768_f64 && n <= 32767_f64) {
+        e.push(ValidationError { instance_path: ip_p1.to_string(), schema_path: format!("{sp_p1}/type") });
+      }
+    } else {
+      e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties/value") });
+    }
+    if let Some(pv) = obj.get("parent") {
+      let ip_o0 = LazyPointer::Lit(&p, "/parent");
+      let sp_o0 = LazyPointer::Lit(&sp, "/optionalProperties/parent");
+      if !pv.is_null() {
+        validate_node(pv, e, ip_o0, LazyPointer::Base("/definitions/node"));
+      }
+    }
+    for k in obj.keys() {
+      if k.as_str() != "children" && k.as_str() != "value" && k.as_str() != "parent" {
+        e.push(ValidationError { instance_path: format!("{p}/{}", k.replace('~', "~0").replace('/', "~1")), schema_path: sp.to_string() });
+      }
+    }
+  } else {
+    e.push(ValidationError { instance_path: p.to_string(), schema_path: format!("{sp}/properties") });
+  }
+  REF_DEPTH.with(|d| d.set(depth));
+}
+
+pub fn validate(instance: &Value) -> Vec<ValidationError> {
+  let mut e: Vec<ValidationError> = Vec::new();
+  let p = LazyPointer::Base("");
+  let sp = LazyPointer::Base("");
+  validate_node(instance, &mut e, p, LazyPointer::Base("/definitions/node"));
+  e
+}
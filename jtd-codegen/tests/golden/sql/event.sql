@@ -0,0 +1,168 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+CREATE OR REPLACE FUNCTION jtd_is_integer(v jsonb, lo numeric, hi numeric)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT CASE WHEN jsonb_typeof(v) = 'number'
+        THEN v::numeric % 1 = 0 AND v::numeric BETWEEN lo AND hi
+        ELSE false
+    END
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_is_timestamp(v jsonb)
+RETURNS boolean
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    g text[];
+    days integer;
+BEGIN
+    IF jsonb_typeof(v) <> 'string' THEN
+        RETURN false;
+    END IF;
+    g := regexp_match(v #>> '{}',
+        '^([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))$');
+    IF g IS NULL THEN
+        RETURN false;
+    END IF;
+    IF g[2]::int NOT BETWEEN 1 AND 12 OR g[4]::int > 23 OR g[5]::int > 59 OR g[6]::int > 60 THEN
+        RETURN false;
+    END IF;
+    days := CASE
+        WHEN g[2]::int = 2 AND g[1]::int % 4 = 0 AND (g[1]::int % 100 <> 0 OR g[1]::int % 400 = 0) THEN 29
+        WHEN g[2]::int = 2 THEN 28
+        WHEN g[2]::int IN (4, 6, 9, 11) THEN 30
+        ELSE 31
+    END;
+    IF g[3]::int NOT BETWEEN 1 AND days THEN
+        RETURN false;
+    END IF;
+    RETURN g[8] IS NULL OR (g[8]::int <= 23 AND g[9]::int <= 59);
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_validate_actor(v jsonb, p text)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    v1 jsonb;
+BEGIN
+    IF jsonb_typeof(v) <> 'object' THEN
+        RETURN QUERY VALUES (p, '/definitions/actor/properties');
+    ELSE
+        IF NOT v ? 'name' THEN
+            RETURN QUERY VALUES (p, '/definitions/actor/properties/name');
+        ELSE
+            v1 := v -> 'name';
+            IF jsonb_typeof(v1) <> 'string' THEN RETURN QUERY VALUES (p || '/name', '/definitions/actor/properties/name/type'); END IF;
+        END IF;
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_validate_login(v jsonb, p text)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    v1 jsonb;
+    v2 jsonb;
+BEGIN
+    IF jsonb_typeof(v) <> 'object' THEN
+        RETURN QUERY VALUES (p, '/definitions/login/properties');
+    ELSE
+        IF NOT v ? 'actor' THEN
+            RETURN QUERY VALUES (p, '/definitions/login/properties/actor');
+        ELSE
+            v1 := v -> 'actor';
+            RETURN QUERY SELECT * FROM jtd_validate_actor(v1, p || '/actor');
+        END IF;
+        IF NOT v ? 'at' THEN
+            RETURN QUERY VALUES (p, '/definitions/login/properties/at');
+        ELSE
+            v2 := v -> 'at';
+            IF NOT jtd_is_timestamp(v2) THEN RETURN QUERY VALUES (p || '/at', '/definitions/login/properties/at/type'); END IF;
+        END IF;
+        RETURN QUERY SELECT p || '/' || replace(replace(k, '~', '~0'), '/', '~1'), '/definitions/login' FROM jsonb_object_keys(v) AS k WHERE k NOT IN ('actor', 'at');
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_validate(instance jsonb)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    v1 jsonb;
+    v2 jsonb;
+    v3 jsonb;
+    v4 jsonb;
+    v5 jsonb;
+    v6 jsonb;
+BEGIN
+    IF jsonb_typeof(instance) <> 'object' THEN
+        RETURN QUERY VALUES ('', '/discriminator');
+    ELSIF NOT instance ? 'kind' THEN
+        RETURN QUERY VALUES ('', '/discriminator');
+    ELSIF jsonb_typeof(instance -> 'kind') <> 'string' THEN
+        RETURN QUERY VALUES ('/kind', '/discriminator');
+    ELSE
+        CASE instance ->> 'kind'
+        WHEN 'login' THEN
+            IF jsonb_typeof(instance) <> 'object' THEN
+                RETURN QUERY VALUES ('', '/mapping/login/properties');
+            ELSE
+                IF NOT instance ? 'actor' THEN
+                    RETURN QUERY VALUES ('', '/mapping/login/properties/actor');
+                ELSE
+                    v1 := instance -> 'actor';
+                    RETURN QUERY SELECT * FROM jtd_validate_actor(v1, '/actor');
+                END IF;
+                IF NOT instance ? 'at' THEN
+                    RETURN QUERY VALUES ('', '/mapping/login/properties/at');
+                ELSE
+                    v2 := instance -> 'at';
+                    IF NOT jtd_is_timestamp(v2) THEN RETURN QUERY VALUES ('/at', '/mapping/login/properties/at/type'); END IF;
+                END IF;
+                RETURN QUERY SELECT '/' || replace(replace(k, '~', '~0'), '/', '~1'), '/mapping/login' FROM jsonb_object_keys(instance) AS k WHERE k NOT IN ('actor', 'at', 'kind');
+            END IF;
+        WHEN 'transfer' THEN
+            IF jsonb_typeof(instance) <> 'object' THEN
+                RETURN QUERY VALUES ('', '/mapping/transfer/properties');
+            ELSE
+                IF NOT instance ? 'actor' THEN
+                    RETURN QUERY VALUES ('', '/mapping/transfer/properties/actor');
+                ELSE
+                    v3 := instance -> 'actor';
+                    RETURN QUERY SELECT * FROM jtd_validate_actor(v3, '/actor');
+                END IF;
+                IF NOT instance ? 'amount' THEN
+                    RETURN QUERY VALUES ('', '/mapping/transfer/properties/amount');
+                ELSE
+                    v4 := instance -> 'amount';
+                    IF NOT jtd_is_integer(v4, -2147483648, 2147483647) THEN RETURN QUERY VALUES ('/amount', '/mapping/transfer/properties/amount/type'); END IF;
+                END IF;
+                IF NOT instance ? 'currency' THEN
+                    RETURN QUERY VALUES ('', '/mapping/transfer/properties/currency');
+                ELSE
+                    v5 := instance -> 'currency';
+                    IF v5 NOT IN ('"EUR"', '"GBP"', '"USD"') THEN RETURN QUERY VALUES ('/currency', '/mapping/transfer/properties/currency/enum'); END IF;
+                END IF;
+                IF instance ? 'note' THEN
+                    v6 := instance -> 'note';
+                    IF jsonb_typeof(v6) <> 'string' THEN RETURN QUERY VALUES ('/note', '/mapping/transfer/optionalProperties/note/type'); END IF;
+                END IF;
+                RETURN QUERY SELECT '/' || replace(replace(k, '~', '~0'), '/', '~1'), '/mapping/transfer' FROM jsonb_object_keys(instance) AS k WHERE k NOT IN ('actor', 'amount', 'currency', 'note', 'kind');
+            END IF;
+        ELSE
+            RETURN QUERY VALUES ('/kind', '/mapping');
+        END CASE;
+    END IF;
+END;
+$$;
+
+-- True when instance has no validation errors; usable in CHECK constraints.
+CREATE OR REPLACE FUNCTION jtd_is_valid(instance jsonb)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT NOT EXISTS (SELECT 1 FROM jtd_validate(instance))
+$$;
@@ -0,0 +1,69 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+CREATE OR REPLACE FUNCTION jtd_is_integer(v jsonb, lo numeric, hi numeric)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT CASE WHEN jsonb_typeof(v) = 'number'
+        THEN v::numeric % 1 = 0 AND v::numeric BETWEEN lo AND hi
+        ELSE false
+    END
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_validate_node(v jsonb, p text)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    v1 jsonb;
+    v3 jsonb;
+    v4 jsonb;
+    v5 jsonb;
+BEGIN
+    IF jsonb_typeof(v) <> 'object' THEN
+        RETURN QUERY VALUES (p, '/definitions/node/properties');
+    ELSE
+        IF NOT v ? 'children' THEN
+            RETURN QUERY VALUES (p, '/definitions/node/properties/children');
+        ELSE
+            v1 := v -> 'children';
+            IF jsonb_typeof(v1) <> 'array' THEN
+                RETURN QUERY VALUES (p || '/children', '/definitions/node/properties/children/elements');
+            ELSE
+                FOR i2 IN 0 .. jsonb_array_length(v1) - 1 LOOP
+                    v3 := v1 -> i2;
+                    RETURN QUERY SELECT * FROM jtd_validate_node(v3, p || '/children' || '/' || i2);
+                END LOOP;
+            END IF;
+        END IF;
+        IF NOT v ? 'value' THEN
+            RETURN QUERY VALUES (p, '/definitions/node/properties/value');
+        ELSE
+            v4 := v -> 'value';
+            IF NOT jtd_is_integer(v4, -32768, 32767) THEN RETURN QUERY VALUES (p || '/value', '/definitions/node/properties/value/type'); END IF;
+        END IF;
+        IF v ? 'parent' THEN
+            v5 := v -> 'parent';
+            IF jsonb_typeof(v5) <> 'null' THEN
+                RETURN QUERY SELECT * FROM jtd_validate_node(v5, p || '/parent');
+            END IF;
+        END IF;
+        RETURN QUERY SELECT p || '/' || replace(replace(k, '~', '~0'), '/', '~1'), '/definitions/node' FROM jsonb_object_keys(v) AS k WHERE k NOT IN ('children', 'value', 'parent');
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_validate(instance jsonb)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+BEGIN
+    RETURN QUERY SELECT * FROM jtd_validate_node(instance, '');
+END;
+$$;
+
+-- True when instance has no validation errors; usable in CHECK constraints.
+CREATE OR REPLACE FUNCTION jtd_is_valid(instance jsonb)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT NOT EXISTS (SELECT 1 FROM jtd_validate(instance))
+$$;
@@ -0,0 +1,132 @@
+-- Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+-- This code is generated from a JSON Type Definition schema.
+-- Do not edit manually.
+
+CREATE OR REPLACE FUNCTION jtd_is_integer(v jsonb, lo numeric, hi numeric)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT CASE WHEN jsonb_typeof(v) = 'number'
+        THEN v::numeric % 1 = 0 AND v::numeric BETWEEN lo AND hi
+        ELSE false
+    END
+$$;
+
+CREATE OR REPLACE FUNCTION jtd_is_timestamp(v jsonb)
+RETURNS boolean
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    g text[];
+    days integer;
+BEGIN
+    IF jsonb_typeof(v) <> 'string' THEN
+        RETURN false;
+    END IF;
+    g := regexp_match(v #>> '{}',
+        '^([0-9]{4})-([0-9]{2})-([0-9]{2})[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(\.[0-9]+)?(?:[Zz]|[+-]([0-9]{2}):([0-9]{2}))$');
+    IF g IS NULL THEN
+        RETURN false;
+    END IF;
+    IF g[2]::int NOT BETWEEN 1 AND 12 OR g[4]::int > 23 OR g[5]::int > 59 OR g[6]::int > 60 THEN
+        RETURN false;
+    END IF;
+    days := CASE
+        WHEN g[2]::int = 2 AND g[1]::int % 4 = 0 AND (g[1]::int % 100 <> 0 OR g[1]::int % 400 = 0) THEN 29
+        WHEN g[2]::int = 2 THEN 28
+        WHEN g[2]::int IN (4, 6, 9, 11) THEN 30
+        ELSE 31
+    END;
+    IF g[3]::int NOT BETWEEN 1 AND days THEN
+        RETURN false;
+    END IF;
+    RETURN g[8] IS NULL OR (g[8]::int <= 23 AND g[9]::int <= 59);
+END;
+$$;
+
+-- An account holder.
+CREATE OR REPLACE FUNCTION jtd_validate(instance jsonb)
+RETURNS TABLE (instance_path text, schema_path text)
+LANGUAGE plpgsql IMMUTABLE AS $$
+DECLARE
+    v1 jsonb;
+    v2 jsonb;
+    v3 jsonb;
+    v4 jsonb;
+    v5 jsonb;
+    v7 jsonb;
+    v8 jsonb;
+    v9 jsonb;
+    k10 text;
+    v11 jsonb;
+    v12 jsonb;
+BEGIN
+    IF jsonb_typeof(instance) <> 'object' THEN
+        RETURN QUERY VALUES ('', '/properties');
+    ELSE
+        IF NOT instance ? 'age' THEN
+            RETURN QUERY VALUES ('', '/properties/age');
+        ELSE
+            v1 := instance -> 'age';
+            IF NOT jtd_is_integer(v1, 0, 255) THEN RETURN QUERY VALUES ('/age', '/properties/age/type'); END IF;
+        END IF;
+        IF NOT instance ? 'createdAt' THEN
+            RETURN QUERY VALUES ('', '/properties/createdAt');
+        ELSE
+            v2 := instance -> 'createdAt';
+            IF NOT jtd_is_timestamp(v2) THEN RETURN QUERY VALUES ('/createdAt', '/properties/createdAt/type'); END IF;
+        END IF;
+        IF NOT instance ? 'id' THEN
+            RETURN QUERY VALUES ('', '/properties/id');
+        ELSE
+            v3 := instance -> 'id';
+            IF jsonb_typeof(v3) <> 'string' THEN RETURN QUERY VALUES ('/id', '/properties/id/type'); END IF;
+        END IF;
+        IF NOT instance ? 'role' THEN
+            RETURN QUERY VALUES ('', '/properties/role');
+        ELSE
+            v4 := instance -> 'role';
+            IF v4 NOT IN ('"admin"', '"member"', '"guest"') THEN RETURN QUERY VALUES ('/role', '/properties/role/enum'); END IF;
+        END IF;
+        IF NOT instance ? 'tags' THEN
+            RETURN QUERY VALUES ('', '/properties/tags');
+        ELSE
+            v5 := instance -> 'tags';
+            IF jsonb_typeof(v5) <> 'array' THEN
+                RETURN QUERY VALUES ('/tags', '/properties/tags/elements');
+            ELSE
+                FOR i6 IN 0 .. jsonb_array_length(v5) - 1 LOOP
+                    v7 := v5 -> i6;
+                    IF jsonb_typeof(v7) <> 'string' THEN RETURN QUERY VALUES ('/tags' || '/' || i6, '/properties/tags/elements/type'); END IF;
+                END LOOP;
+            END IF;
+        END IF;
+        IF instance ? 'email' THEN
+            v8 := instance -> 'email';
+            IF jsonb_typeof(v8) <> 'null' THEN
+                IF jsonb_typeof(v8) <> 'string' THEN RETURN QUERY VALUES ('/email', '/optionalProperties/email/type'); END IF;
+            END IF;
+        END IF;
+        IF instance ? 'preferences' THEN
+            v9 := instance -> 'preferences';
+            IF jsonb_typeof(v9) <> 'object' THEN
+                RETURN QUERY VALUES ('/preferences', '/optionalProperties/preferences/values');
+            ELSE
+                FOR k10, v11 IN SELECT key, value FROM jsonb_each(v9) LOOP
+                    IF jsonb_typeof(v11) <> 'boolean' THEN RETURN QUERY VALUES ('/preferences' || '/' || replace(replace(k10, '~', '~0'), '/', '~1'), '/optionalProperties/preferences/values/type'); END IF;
+                END LOOP;
+            END IF;
+        END IF;
+        IF instance ? 'score' THEN
+            v12 := instance -> 'score';
+            IF jsonb_typeof(v12) <> 'number' THEN RETURN QUERY VALUES ('/score', '/optionalProperties/score/type'); END IF;
+        END IF;
+        RETURN QUERY SELECT '/' || replace(replace(k, '~', '~0'), '/', '~1'), '' FROM jsonb_object_keys(instance) AS k WHERE k NOT IN ('age', 'createdAt', 'id', 'role', 'tags', 'email', 'preferences', 'score');
+    END IF;
+END;
+$$;
+
+-- True when instance has no validation errors; usable in CHECK constraints.
+CREATE OR REPLACE FUNCTION jtd_is_valid(instance jsonb)
+RETURNS boolean
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT NOT EXISTS (SELECT 1 FROM jtd_validate(instance))
+$$;
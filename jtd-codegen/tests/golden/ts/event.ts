@@ -0,0 +1,102 @@
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+export interface ValidationError {
+  instancePath: string;
+  schemaPath: string;
+}
+
+function isRfc3339(s: unknown): boolean {
+  if (typeof s !== "string") return false;
+  const m = /^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?([Zz]|[+-](\d{2}):(\d{2}))$/.exec(s);
+  if (m === null) return false;
+  const y = +m[1], mo = +m[2], d = +m[3];
+  if (mo < 1 || mo > 12 || d < 1) return false;
+  const leap = y % 4 === 0 && (y % 100 !== 0 || y % 400 === 0);
+  const dim = [31, leap ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][mo - 1];
+  if (d > dim) return false;
+  if (+m[4] > 23 || +m[5] > 59 || +m[6] > 60) return false;
+  if (m[9] !== undefined && (+m[9] > 23 || +m[10] > 59)) return false;
+  return true;
+}
+
+function validate_actor(v: any, e: ValidationError[], p: string, sp: string): void {
+  if (v === null || typeof v !== "object" || Array.isArray(v)) {
+    e.push({instancePath: p, schemaPath: sp + "/properties"});
+  } else {
+    if (!("name" in v)) e.push({instancePath: p, schemaPath: sp + "/properties/name"});
+    else {
+      if (typeof v["name"] !== "string") e.push({instancePath: p + "/name", schemaPath: sp + "/properties/name" + "/type"});
+    }
+  }
+}
+
+function validate_login(v: any, e: ValidationError[], p: string, sp: string): void {
+  if (v === null || typeof v !== "object" || Array.isArray(v)) {
+    e.push({instancePath: p, schemaPath: sp + "/properties"});
+  } else {
+    if (!("actor" in v)) e.push({instancePath: p, schemaPath: sp + "/properties/actor"});
+    else {
+      validate_actor(v["actor"], e, p + "/actor", "/definitions/actor");
+    }
+    if (!("at" in v)) e.push({instancePath: p, schemaPath: sp + "/properties/at"});
+    else {
+      if (!isRfc3339(v["at"])) e.push({instancePath: p + "/at", schemaPath: sp + "/properties/at" + "/type"});
+    }
+    for (const k in v) {
+      if (k !== "actor" && k !== "at") e.push({instancePath: p + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: sp});
+    }
+  }
+}
+
+export function validate(instance: unknown): ValidationError[] {
+  const e: ValidationError[] = [];
+  const v: any = instance;
+  if (v === null || typeof v !== "object" || Array.isArray(v)) {
+    e.push({instancePath: "", schemaPath: "" + "/discriminator"});
+  } else if (!("kind" in v)) {
+    e.push({instancePath: "", schemaPath: "" + "/discriminator"});
+  } else if (typeof v["kind"] !== "string") {
+    e.push({instancePath: "" + "/kind", schemaPath: "" + "/discriminator"});
+  } else if (v["kind"] === "login") {
+    if (v === null || typeof v !== "object" || Array.isArray(v)) {
+      e.push({instancePath: "", schemaPath: "" + "/mapping/login" + "/properties"});
+    } else {
+      if (!("actor" in v)) e.push({instancePath: "", schemaPath: "" + "/mapping/login" + "/properties/actor"});
+      else {
+        validate_actor(v["actor"], e, "" + "/actor", "/definitions/actor");
+      }
+      if (!("at" in v)) e.push({instancePath: "", schemaPath: "" + "/mapping/login" + "/properties/at"});
+      else {
+        if (!isRfc3339(v["at"])) e.push({instancePath: "" + "/at", schemaPath: "" + "/mapping/login" + "/properties/at" + "/type"});
+      }
+      for (const k in v) {
+        if (k !== "kind" && k !== "actor" && k !== "at") e.push({instancePath: "" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: "" + "/mapping/login"});
+      }
+    }
+  } else if (v["kind"] === "transfer") {
+    if (v === null || typeof v !== "object" || Array.isArray(v)) {
+      e.push({instancePath: "", schemaPath: "" + "/mapping/transfer" + "/properties"});
+    } else {
+      if (!("actor" in v)) e.push({instancePath: "", schemaPath: "" + "/mapping/transfer" + "/properties/actor"});
+      else {
+        validate_actor(v["actor"], e, "" + "/actor", "/definitions/actor");
+      }
+      if (!("amount" in v)) e.push({instancePath: "", schemaPath: "" + "/mapping/transfer" + "/properties/amount"});
+      else {
+        if (typeof v["amount"] !== "number" || !Number.isInteger(v["amount"]) || v["amount"] < -2147483648 || v["amount"] > 2147483647) e.push({instancePath: "" + "/amount", schemaPath: "" + "/mapping/transfer" + "/properties/amount" + "/type"});
+      }
+      if (!("currency" in v)) e.push({instancePath: "", schemaPath: "" + "/mapping/transfer" + "/properties/currency"});
+      else {
+        if (typeof v["currency"] !== "string" || !["EUR","GBP","USD"].includes(v["currency"])) e.push({instancePath: "" + "/currency", schemaPath: "" + "/mapping/transfer" + "/properties/currency" + "/enum"});
+      }
+      if ("note" in v) {
+        if (typeof v["note"] !== "string") e.push({instancePath: "" + "/note", schemaPath: "" + "/mapping/transfer" + "/optionalProperties/note" + "/type"});
+      }
+      for (const k in v) {
+        if (k !== "kind" && k !== "actor" && k !== "amount" && k !== "currency" && k !== "note") e.push({instancePath: "" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: "" + "/mapping/transfer"});
+      }
+    }
+  } else {
+    e.push({instancePath: "" + "/kind", schemaPath: "" + "/mapping"});
+  }
+  return e;
+}
@@ -0,0 +1,49 @@
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+export interface ValidationError {
+  instancePath: string;
+  schemaPath: string;
+}
+
+let refDepth = 0;
+
+function validate_node(v: any, e: ValidationError[], p: string, sp: string): void {
+  if (refDepth >= 256) {
+    e.push({instancePath: p, schemaPath: sp});
+    return;
+  }
+  refDepth++;
+  if (v === null || typeof v !== "object" || Array.isArray(v)) {
+    e.push({instancePath: p, schemaPath: sp + "/properties"});
+  } else {
+    if (!("children" in v)) e.push({instancePath: p, schemaPath: sp + "/properties/children"});
+    else {
+      if (!Array.isArray(v["children"])) {
+        e.push({instancePath: p + "/children", schemaPath: sp + "/properties/children" + "/elements"});
+      } else {
+        for (let i = 0; i < v["children"].length; i++) {
+          validate_node(v["children"][i], e, p + "/children" + "/" + i, "/definitions/node");
+        }
+      }
+    }
+    if (!("value" in v)) e.push({instancePath: p, schemaPath: sp + "/properties/value"});
+    else {
+      if (typeof v["value"] !== "number" || !Number.isInteger(v["value"]) || v["value"] < -32768 || v["value"] > 32767) e.push({instancePath: p + "/value", schemaPath: sp + "/properties/value" + "/type"});
+    }
+    if ("parent" in v) {
+      if (v["parent"] !== null) {
+        validate_node(v["parent"], e, p + "/parent", "/definitions/node");
+      }
+    }
+    for (const k in v) {
+      if (k !== "children" && k !== "value" && k !== "parent") e.push({instancePath: p + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: sp});
+    }
+  }
+  refDepth--;
+}
+
+export function validate(instance: unknown): ValidationError[] {
+  const e: ValidationError[] = [];
+  const v: any = instance;
+  validate_node(v, e, "", "/definitions/node");
+  return e;
+}
@@ -0,0 +1,78 @@
+/** A validation failure, as JSON Pointers into the instance and the schema. */
+export interface ValidationError {
+  instancePath: string;
+  schemaPath: string;
+}
+
+function isRfc3339(s: unknown): boolean {
+  if (typeof s !== "string") return false;
+  const m = /^(\d{4})-(\d{2})-(\d{2})[Tt](\d{2}):(\d{2}):(\d{2})(\.\d+)?([Zz]|[+-](\d{2}):(\d{2}))$/.exec(s);
+  if (m === null) return false;
+  const y = +m[1], mo = +m[2], d = +m[3];
+  if (mo < 1 || mo > 12 || d < 1) return false;
+  const leap = y % 4 === 0 && (y % 100 !== 0 || y % 400 === 0);
+  const dim = [31, leap ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][mo - 1];
+  if (d > dim) return false;
+  if (+m[4] > 23 || +m[5] > 59 || +m[6] > 60) return false;
+  if (m[9] !== undefined && (+m[9] > 23 || +m[10] > 59)) return false;
+  return true;
+}
+
+/**
+ * An account holder.
+ */
+export function validate(instance: unknown): ValidationError[] {
+  const e: ValidationError[] = [];
+  const v: any = instance;
+  if (v === null || typeof v !== "object" || Array.isArray(v)) {
+    e.push({instancePath: "", schemaPath: "" + "/properties"});
+  } else {
+    if (!("age" in v)) e.push({instancePath: "", schemaPath: "" + "/properties/age"});
+    else {
+      if (typeof v["age"] !== "number" || !Number.isInteger(v["age"]) || v["age"] < 0 || v["age"] > 255) e.push({instancePath: "" + "/age", schemaPath: "" + "/properties/age" + "/type"});
+    }
+    if (!("createdAt" in v)) e.push({instancePath: "", schemaPath: "" + "/properties/createdAt"});
+    else {
+      if (!isRfc3339(v["createdAt"])) e.push({instancePath: "" + "/createdAt", schemaPath: "" + "/properties/createdAt" + "/type"});
+    }
+    if (!("id" in v)) e.push({instancePath: "", schemaPath: "" + "/properties/id"});
+    else {
+      if (typeof v["id"] !== "string") e.push({instancePath: "" + "/id", schemaPath: "" + "/properties/id" + "/type"});
+    }
+    if (!("role" in v)) e.push({instancePath: "", schemaPath: "" + "/properties/role"});
+    else {
+      if (typeof v["role"] !== "string" || !["admin","member","guest"].includes(v["role"])) e.push({instancePath: "" + "/role", schemaPath: "" + "/properties/role" + "/enum"});
+    }
+    if (!("tags" in v)) e.push({instancePath: "", schemaPath: "" + "/properties/tags"});
+    else {
+      if (!Array.isArray(v["tags"])) {
+        e.push({instancePath: "" + "/tags", schemaPath: "" + "/properties/tags" + "/elements"});
+      } else {
+        for (let i = 0; i < v["tags"].length; i++) {
+          if (typeof v["tags"][i] !== "string") e.push({instancePath: "" + "/tags" + "/" + i, schemaPath: "" + "/properties/tags" + "/elements" + "/type"});
+        }
+      }
+    }
+    if ("email" in v) {
+      if (v["email"] !== null) {
+        if (typeof v["email"] !== "string") e.push({instancePath: "" + "/email", schemaPath: "" + "/optionalProperties/email" + "/type"});
+      }
+    }
+    if ("preferences" in v) {
+      if (v["preferences"] === null || typeof v["preferences"] !== "object" || Array.isArray(v["preferences"])) {
+        e.push({instancePath: "" + "/preferences", schemaPath: "" + "/optionalProperties/preferences" + "/values"});
+      } else {
+        for (const k in v["preferences"]) {
+          if (typeof v["preferences"][k] !== "boolean") e.push({instancePath: "" + "/preferences" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: "" + "/optionalProperties/preferences" + "/values" + "/type"});
+        }
+      }
+    }
+    if ("score" in v) {
+      if (typeof v["score"] !== "number" || !Number.isFinite(v["score"])) e.push({instancePath: "" + "/score", schemaPath: "" + "/optionalProperties/score" + "/type"});
+    }
+    for (const k in v) {
+      if (k !== "age" && k !== "createdAt" && k !== "id" && k !== "role" && k !== "tags" && k !== "email" && k !== "preferences" && k !== "score") e.push({instancePath: "" + "/" + k.replace(/~/g, "~0").replace(/\//g, "~1"), schemaPath: ""});
+    }
+  }
+  return e;
+}
@@ -0,0 +1,28 @@
+import { Type, type Static } from "@sinclair/typebox";
+
+export const Actor = Type.Object({
+  name: Type.String(),
+});
+export type Actor = Static<typeof Actor>;
+
+export const Login = Type.Object({
+  actor: Actor,
+  at: Type.String({ format: "date-time" }),
+}, { additionalProperties: false });
+export type Login = Static<typeof Login>;
+
+export const Root = Type.Union([
+  Type.Object({
+    kind: Type.Literal("login"),
+    actor: Actor,
+    at: Type.String({ format: "date-time" }),
+  }, { additionalProperties: false }),
+  Type.Object({
+    kind: Type.Literal("transfer"),
+    actor: Actor,
+    amount: Type.Integer({ minimum: -2147483648, maximum: 2147483647 }),
+    currency: Type.Union([Type.Literal("EUR"), Type.Literal("GBP"), Type.Literal("USD")]),
+    note: Type.Optional(Type.String()),
+  }, { additionalProperties: false }),
+]);
+export type Root = Static<typeof Root>;
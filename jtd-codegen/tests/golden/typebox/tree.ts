@@ -0,0 +1,13 @@
+import { Type, type Static } from "@sinclair/typebox";
+
+export const Node = Type.Recursive((nodeSelf) =>
+  Type.Object({
+    children: Type.Array(nodeSelf),
+    value: Type.Integer({ minimum: -32768, maximum: 32767 }),
+    parent: Type.Optional(Type.Union([nodeSelf, Type.Null()])),
+  }, { additionalProperties: false })
+);
+export type Node = Static<typeof Node>;
+
+export const Root = Node;
+export type Root = Static<typeof Root>;
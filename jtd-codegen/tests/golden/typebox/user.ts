@@ -0,0 +1,16 @@
+import { Type, type Static } from "@sinclair/typebox";
+
+/**
+ * An account holder.
+ */
+export const Root = Type.Object({
+  age: Type.Integer({ minimum: 0, maximum: 255 }),
+  createdAt: Type.String({ format: "date-time" }),
+  id: Type.String(),
+  role: Type.Union([Type.Literal("admin"), Type.Literal("member"), Type.Literal("guest")]),
+  tags: Type.Array(Type.String()),
+  email: Type.Optional(Type.Union([Type.String(), Type.Null()])),
+  preferences: Type.Optional(Type.Record(Type.String(), Type.Boolean())),
+  score: Type.Optional(Type.Number()),
+}, { additionalProperties: false });
+export type Root = Static<typeof Root>;
@@ -0,0 +1,405 @@
+;; Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+;; This code is generated from a JSON Type Definition schema.
+;; Do not edit manually.
+(module
+  (memory (export "memory") 1)
+  ;; Where the host may write tokens and strings
+  (global (export "heap_base") i32 (i32.const 928))
+  ;; (address, length) of each schema path, indexed by error records
+  (global (export "schema_paths") i32 (i32.const 740))
+  (global (export "schema_path_count") i32 (i32.const 23))
+  (global $tokens (mut i32) (i32.const 0))
+  (global $errors (mut i32) (i32.const 0))
+  (global $cap (mut i32) (i32.const 0))
+  (global $count (mut i32) (i32.const 0))
+  (data (i32.const 0) "/discriminatorkindlogin/mapping/login/propertiesactor/mapping/login/properties/actorat/mapping/login/properties/at/mapping/login/properties/at/type/mapping/logintransfer/mapping/transfer/properties/mapping/transfer/properties/actoramount/mapping/transfer/properties/amount/mapping/transfer/properties/amount/typecurrency/mapping/transfer/properties/currencyEURGBPUSD/mapping/transfer/properties/currency/enumnote/mapping/transfer/optionalProperties/note/type/mapping/transfer/mapping/definitions/actor/propertiesname/definitions/actor/properties/name/definitions/actor/properties/name/type/definitions/login/properties/definitions/login/properties/actor/definitions/login/properties/at/definitions/login/properties/at/type/definitions/login\00\00\00\00\0e\00\00\00\17\00\00\00\19\00\00\005\00\00\00\1f\00\00\00V\00\00\00\1c\00\00\00r\00\00\00!\00\00\00\93\00\00\00\0e\00\00\00\a9\00\00\00\1c\00\00\00\c5\00\00\00\"\00\00\00\ed\00\00\00#\00\00\00\10\01\00\00(\00\00\00@\01\00\00%\00\00\00n\01\00\00*\00\00\00\9c\01\00\00.\00\00\00\ca\01\00\00\11\00\00\00\db\01\00\00\08\00\00\00\e3\01\00\00\1d\00\00\00\04\02\00\00\"\00\00\00&\02\00\00'\00\00\00M\02\00\00\1d\00\00\00j\02\00\00#\00\00\00\8d\02\00\00 \00\00\00\ad\02\00\00%\00\00\00\d2\02\00\00\12\00\00\00")
+  ;; Address of token $t
+  (func $tok (param $t i32) (result i32)
+    (i32.add (global.get $tokens) (i32.shl (local.get $t) (i32.const 4))))
+  (func $kind (param $t i32) (result i32)
+    (i32.load (call $tok (local.get $t))))
+  ;; Byte length of a string, member or element count of a container
+  (func $len (param $t i32) (result i32)
+    (i32.load offset=4 (call $tok (local.get $t))))
+  ;; Address of a string's bytes, or the end index of a container
+  (func $ptr (param $t i32) (result i32)
+    (i32.load offset=8 (call $tok (local.get $t))))
+  (func $num (param $t i32) (result f64)
+    (f64.load offset=8 (call $tok (local.get $t))))
+  ;; The token after $t and everything inside it
+  (func $next (param $t i32) (result i32)
+    (if (result i32) (i32.ge_u (call $kind (local.get $t)) (i32.const 5))
+      (then (call $ptr (local.get $t)))
+      (else (i32.add (local.get $t) (i32.const 1)))))
+  ;; Record an error at token $t with schema path $sp, counting past capacity
+  (func $err (param $t i32) (param $sp i32)
+    (local $at i32)
+    (if (i32.lt_u (global.get $count) (global.get $cap))
+      (then
+        (local.set $at
+          (i32.add (global.get $errors) (i32.shl (global.get $count) (i32.const 3))))
+        (i32.store (local.get $at) (local.get $t))
+        (i32.store offset=4 (local.get $at) (local.get $sp))))
+    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+  ;; 1 when token $t is the string of the $n bytes at $p
+  (func $eq (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $q i32)
+    (local $i i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (if (i32.ne (call $len (local.get $t)) (local.get $n))
+      (then (return (i32.const 0))))
+    (local.set $q (call $ptr (local.get $t)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+        (if (i32.ne (i32.load8_u (i32.add (local.get $p) (local.get $i)))
+                    (i32.load8_u (i32.add (local.get $q) (local.get $i))))
+          (then (return (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $each)))
+    (i32.const 1))
+  ;; The value token of the member of object $t named by the $n bytes at $p,
+  ;; or -1
+  (func $find (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $left i32)
+    (local $k i32)
+    (local.set $left (call $len (local.get $t)))
+    (local.set $k (i32.add (local.get $t) (i32.const 1)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $left)))
+        (if (call $eq (local.get $k) (local.get $p) (local.get $n))
+          (then (return (i32.add (local.get $k) (i32.const 1)))))
+        (local.set $k (call $next (i32.add (local.get $k) (i32.const 1))))
+        (local.set $left (i32.sub (local.get $left) (i32.const 1)))
+        (br $each)))
+    (i32.const -1))
+  ;; 1 when token $t is a number with no fractional part in [$min, $max]
+  (func $int (param $t i32) (param $min f64) (param $max f64) (result i32)
+    (local $x f64)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 3))
+      (then (return (i32.const 0))))
+    (local.set $x (call $num (local.get $t)))
+    (i32.and
+      (f64.eq (f64.floor (local.get $x)) (local.get $x))
+      (i32.and (f64.ge (local.get $x) (local.get $min))
+               (f64.le (local.get $x) (local.get $max)))))
+  ;; The value of the $n ASCII digits at $p, or -1
+  (func $digits (param $p i32) (param $n i32) (result i32)
+    (local $v i32)
+    (local $c i32)
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $c (i32.sub (i32.load8_u (local.get $p)) (i32.const 48)))
+        (if (i32.gt_u (local.get $c) (i32.const 9))
+          (then (return (i32.const -1))))
+        (local.set $v (i32.add (i32.mul (local.get $v) (i32.const 10)) (local.get $c)))
+        (local.set $p (i32.add (local.get $p) (i32.const 1)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $each)))
+    (local.get $v))
+  ;; 1 when token $t is a string holding an RFC 3339 date-time
+  (func $timestamp (param $t i32) (result i32)
+    (local $p i32)
+    (local $n i32)
+    (local $y i32)
+    (local $m i32)
+    (local $days i32)
+    (local $i i32)
+    (local $c i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (local.set $p (call $ptr (local.get $t)))
+    (local.set $n (call $len (local.get $t)))
+    ;; YYYY-MM-DDThh:mm:ssZ is the shortest form
+    (if (i32.lt_u (local.get $n) (i32.const 20))
+      (then (return (i32.const 0))))
+    (if (i32.or
+          (i32.or (i32.ne (i32.load8_u offset=4 (local.get $p)) (i32.const 45))
+                  (i32.ne (i32.load8_u offset=7 (local.get $p)) (i32.const 45)))
+          (i32.or
+            (i32.ne (i32.or (i32.load8_u offset=10 (local.get $p)) (i32.const 32))
+                    (i32.const 116))
+            (i32.or (i32.ne (i32.load8_u offset=13 (local.get $p)) (i32.const 58))
+                    (i32.ne (i32.load8_u offset=16 (local.get $p)) (i32.const 58)))))
+      (then (return (i32.const 0))))
+    (local.set $y (call $digits (local.get $p) (i32.const 4)))
+    (local.set $m (call $digits (i32.add (local.get $p) (i32.const 5)) (i32.const 2)))
+    (if (i32.or (i32.lt_s (local.get $y) (i32.const 0))
+                (i32.gt_u (i32.sub (local.get $m) (i32.const 1)) (i32.const 11)))
+      (then (return (i32.const 0))))
+    ;; 31 days in months 1, 3, 5, 7, 8, 10 and 12
+    (local.set $days
+      (i32.add (i32.const 30)
+        (i32.and (i32.add (local.get $m) (i32.shr_u (local.get $m) (i32.const 3)))
+                 (i32.const 1))))
+    (if (i32.eq (local.get $m) (i32.const 2))
+      (then
+        (local.set $days
+          (i32.add (i32.const 28)
+            (i32.and
+              (i32.eqz (i32.rem_u (local.get $y) (i32.const 4)))
+              (i32.or (i32.ne (i32.rem_u (local.get $y) (i32.const 100)) (i32.const 0))
+                      (i32.eqz (i32.rem_u (local.get $y) (i32.const 400)))))))))
+    ;; Unsigned comparisons also reject -1 from $digits
+    (if (i32.or
+          (i32.ge_u
+            (i32.sub (call $digits (i32.add (local.get $p) (i32.const 8)) (i32.const 2))
+                     (i32.const 1))
+            (local.get $days))
+          (i32.or
+            (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 11)) (i32.const 2))
+                      (i32.const 23))
+            (i32.or
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 14)) (i32.const 2))
+                        (i32.const 59))
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 17)) (i32.const 2))
+                        (i32.const 60)))))
+      (then (return (i32.const 0))))
+    (local.set $i (i32.const 19))
+    (if (i32.eq (i32.load8_u offset=19 (local.get $p)) (i32.const 46))
+      (then
+        (local.set $i (i32.const 20))
+        (block $done
+          (loop $each
+            (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+            (local.set $c
+              (i32.sub (i32.load8_u (i32.add (local.get $p) (local.get $i))) (i32.const 48)))
+            (br_if $done (i32.gt_u (local.get $c) (i32.const 9)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $each)))
+        (if (i32.eq (local.get $i) (i32.const 20))
+          (then (return (i32.const 0))))))
+    (local.set $p (i32.add (local.get $p) (local.get $i)))
+    (local.set $n (i32.sub (local.get $n) (local.get $i)))
+    (if (i32.eq (local.get $n) (i32.const 1))
+      (then (return (i32.eq (i32.or (i32.load8_u (local.get $p)) (i32.const 32))
+                            (i32.const 122)))))
+    (if (i32.ne (local.get $n) (i32.const 6))
+      (then (return (i32.const 0))))
+    (local.set $c (i32.load8_u (local.get $p)))
+    (i32.and
+      (i32.and
+        (i32.or (i32.eq (local.get $c) (i32.const 43)) (i32.eq (local.get $c) (i32.const 45)))
+        (i32.eq (i32.load8_u offset=3 (local.get $p)) (i32.const 58)))
+      (i32.and
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 1)) (i32.const 2))
+                  (i32.const 23))
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 4)) (i32.const 2))
+                  (i32.const 59)))))
+  ;; Validate the token buffer at $tokens, writing up to $cap error records
+  ;; of (token index, schema path id) to $errors; returns the error count
+  (func (export "validate") (param $tokens i32) (param $errors i32) (param $cap i32) (result i32)
+    (global.set $tokens (local.get $tokens))
+    (global.set $errors (local.get $errors))
+    (global.set $cap (local.get $cap))
+    (global.set $count (i32.const 0))
+    (call $root (i32.const 0))
+    (global.get $count))
+  ;; root
+  (func $root (param $t i32)
+    (local $l1 i32)
+    (local $l2 i32)
+    (local $l3 i32)
+    (local $l4 i32)
+    (local $l5 i32)
+    (local $l6 i32)
+    (local $l7 i32)
+    (local $l8 i32)
+    (local $l9 i32)
+    (local $l10 i32)
+    (local $l11 i32)
+    block $b1
+      (i32.ne (call $kind (local.get $t)) (i32.const 6))
+      if
+        (call $err (local.get $t) (i32.const 0))  ;; "/discriminator"
+        br $b1
+      end
+      (local.set $l1 (call $find (local.get $t) (i32.const 14) (i32.const 4)))  ;; "kind"
+      (i32.lt_s (local.get $l1) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 0))  ;; "/discriminator"
+        br $b1
+      end
+      (i32.ne (call $kind (local.get $l1)) (i32.const 4))
+      if
+        (call $err (local.get $l1) (i32.const 0))  ;; "/discriminator"
+        br $b1
+      end
+      (call $eq (local.get $l1) (i32.const 18) (i32.const 5))  ;; "login"
+      if
+        block $b2
+          (i32.ne (call $kind (local.get $t)) (i32.const 6))
+          if
+            (call $err (local.get $t) (i32.const 1))  ;; "/mapping/login/properties"
+            br $b2
+          end
+          (local.set $l2 (call $find (local.get $t) (i32.const 48) (i32.const 5)))  ;; "actor"
+          (i32.lt_s (local.get $l2) (i32.const 0))
+          if
+            (call $err (local.get $t) (i32.const 2))  ;; "/mapping/login/properties/actor"
+          else
+            (call $d0 (local.get $l2))
+          end
+          (local.set $l3 (call $find (local.get $t) (i32.const 84) (i32.const 2)))  ;; "at"
+          (i32.lt_s (local.get $l3) (i32.const 0))
+          if
+            (call $err (local.get $t) (i32.const 3))  ;; "/mapping/login/properties/at"
+          else
+            (i32.eqz (call $timestamp (local.get $l3)))
+            if
+              (call $err (local.get $l3) (i32.const 4))  ;; "/mapping/login/properties/at/type"
+            end
+          end
+          (local.set $l4 (call $len (local.get $t)))
+          (local.set $l5 (i32.add (local.get $t) (i32.const 1)))
+          loop $b3
+            (br_if $b2 (i32.eqz (local.get $l4)))
+            block $b4
+              (br_if $b4 (call $eq (local.get $l5) (i32.const 48) (i32.const 5)))  ;; "actor"
+              (br_if $b4 (call $eq (local.get $l5) (i32.const 84) (i32.const 2)))  ;; "at"
+              (br_if $b4 (call $eq (local.get $l5) (i32.const 14) (i32.const 4)))  ;; "kind"
+              (call $err (local.get $l5) (i32.const 5))  ;; "/mapping/login"
+            end
+            (local.set $l5 (call $next (i32.add (local.get $l5) (i32.const 1))))
+            (local.set $l4 (i32.sub (local.get $l4) (i32.const 1)))
+            br $b3
+          end
+        end
+        br $b1
+      end
+      (call $eq (local.get $l1) (i32.const 161) (i32.const 8))  ;; "transfer"
+      if
+        block $b5
+          (i32.ne (call $kind (local.get $t)) (i32.const 6))
+          if
+            (call $err (local.get $t) (i32.const 6))  ;; "/mapping/transfer/properties"
+            br $b5
+          end
+          (local.set $l6 (call $find (local.get $t) (i32.const 48) (i32.const 5)))  ;; "actor"
+          (i32.lt_s (local.get $l6) (i32.const 0))
+          if
+            (call $err (local.get $t) (i32.const 7))  ;; "/mapping/transfer/properties/actor"
+          else
+            (call $d0 (local.get $l6))
+          end
+          (local.set $l7 (call $find (local.get $t) (i32.const 231) (i32.const 6)))  ;; "amount"
+          (i32.lt_s (local.get $l7) (i32.const 0))
+          if
+            (call $err (local.get $t) (i32.const 8))  ;; "/mapping/transfer/properties/amount"
+          else
+            (i32.eqz (call $int (local.get $l7) (f64.const -2147483648) (f64.const 2147483647)))
+            if
+              (call $err (local.get $l7) (i32.const 9))  ;; "/mapping/transfer/properties/amount/type"
+            end
+          end
+          (local.set $l8 (call $find (local.get $t) (i32.const 312) (i32.const 8)))  ;; "currency"
+          (i32.lt_s (local.get $l8) (i32.const 0))
+          if
+            (call $err (local.get $t) (i32.const 10))  ;; "/mapping/transfer/properties/currency"
+          else
+            block $b6
+              (br_if $b6 (call $eq (local.get $l8) (i32.const 357) (i32.const 3)))  ;; "EUR"
+              (br_if $b6 (call $eq (local.get $l8) (i32.const 360) (i32.const 3)))  ;; "GBP"
+              (br_if $b6 (call $eq (local.get $l8) (i32.const 363) (i32.const 3)))  ;; "USD"
+              (call $err (local.get $l8) (i32.const 11))  ;; "/mapping/transfer/properties/currency/enum"
+            end
+          end
+          (local.set $l9 (call $find (local.get $t) (i32.const 408) (i32.const 4)))  ;; "note"
+          (i32.ge_s (local.get $l9) (i32.const 0))
+          if
+            (i32.eqz (i32.eq (call $kind (local.get $l9)) (i32.const 4)))
+            if
+              (call $err (local.get $l9) (i32.const 12))  ;; "/mapping/transfer/optionalProperties/note/type"
+            end
+          end
+          (local.set $l10 (call $len (local.get $t)))
+          (local.set $l11 (i32.add (local.get $t) (i32.const 1)))
+          loop $b7
+            (br_if $b5 (i32.eqz (local.get $l10)))
+            block $b8
+              (br_if $b8 (call $eq (local.get $l11) (i32.const 48) (i32.const 5)))  ;; "actor"
+              (br_if $b8 (call $eq (local.get $l11) (i32.const 231) (i32.const 6)))  ;; "amount"
+              (br_if $b8 (call $eq (local.get $l11) (i32.const 312) (i32.const 8)))  ;; "currency"
+              (br_if $b8 (call $eq (local.get $l11) (i32.const 408) (i32.const 4)))  ;; "note"
+              (br_if $b8 (call $eq (local.get $l11) (i32.const 14) (i32.const 4)))  ;; "kind"
+              (call $err (local.get $l11) (i32.const 13))  ;; "/mapping/transfer"
+            end
+            (local.set $l11 (call $next (i32.add (local.get $l11) (i32.const 1))))
+            (local.set $l10 (i32.sub (local.get $l10) (i32.const 1)))
+            br $b7
+          end
+        end
+        br $b1
+      end
+      (call $err (local.get $l1) (i32.const 14))  ;; "/mapping"
+    end
+  )
+  ;; "/definitions/actor"
+  (func $d0 (param $t i32)
+    (local $l1 i32)
+    block $b1
+      (i32.ne (call $kind (local.get $t)) (i32.const 6))
+      if
+        (call $err (local.get $t) (i32.const 15))  ;; "/definitions/actor/properties"
+        br $b1
+      end
+      (local.set $l1 (call $find (local.get $t) (i32.const 512) (i32.const 4)))  ;; "name"
+      (i32.lt_s (local.get $l1) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 16))  ;; "/definitions/actor/properties/name"
+      else
+        (i32.eqz (i32.eq (call $kind (local.get $l1)) (i32.const 4)))
+        if
+          (call $err (local.get $l1) (i32.const 17))  ;; "/definitions/actor/properties/name/type"
+        end
+      end
+    end
+  )
+  ;; "/definitions/login"
+  (func $d1 (param $t i32)
+    (local $l1 i32)
+    (local $l2 i32)
+    (local $l3 i32)
+    (local $l4 i32)
+    block $b1
+      (i32.ne (call $kind (local.get $t)) (i32.const 6))
+      if
+        (call $err (local.get $t) (i32.const 18))  ;; "/definitions/login/properties"
+        br $b1
+      end
+      (local.set $l1 (call $find (local.get $t) (i32.const 48) (i32.const 5)))  ;; "actor"
+      (i32.lt_s (local.get $l1) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 19))  ;; "/definitions/login/properties/actor"
+      else
+        (call $d0 (local.get $l1))
+      end
+      (local.set $l2 (call $find (local.get $t) (i32.const 84) (i32.const 2)))  ;; "at"
+      (i32.lt_s (local.get $l2) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 20))  ;; "/definitions/login/properties/at"
+      else
+        (i32.eqz (call $timestamp (local.get $l2)))
+        if
+          (call $err (local.get $l2) (i32.const 21))  ;; "/definitions/login/properties/at/type"
+        end
+      end
+      (local.set $l3 (call $len (local.get $t)))
+      (local.set $l4 (i32.add (local.get $t) (i32.const 1)))
+      loop $b2
+        (br_if $b1 (i32.eqz (local.get $l3)))
+        block $b3
+          (br_if $b3 (call $eq (local.get $l4) (i32.const 48) (i32.const 5)))  ;; "actor"
+          (br_if $b3 (call $eq (local.get $l4) (i32.const 84) (i32.const 2)))  ;; "at"
+          (call $err (local.get $l4) (i32.const 22))  ;; "/definitions/login"
+        end
+        (local.set $l4 (call $next (i32.add (local.get $l4) (i32.const 1))))
+        (local.set $l3 (i32.sub (local.get $l3) (i32.const 1)))
+        br $b2
+      end
+    end
+  )
+)
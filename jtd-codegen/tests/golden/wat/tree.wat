@@ -0,0 +1,182 @@
+;; Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+;; This code is generated from a JSON Type Definition schema.
+;; Do not edit manually.
+(module
+  (memory (export "memory") 1)
+  ;; Where the host may write tokens and strings
+  (global (export "heap_base") i32 (i32.const 272))
+  ;; (address, length) of each schema path, indexed by error records
+  (global (export "schema_paths") i32 (i32.const 220))
+  (global (export "schema_path_count") i32 (i32.const 6))
+  (global $tokens (mut i32) (i32.const 0))
+  (global $errors (mut i32) (i32.const 0))
+  (global $cap (mut i32) (i32.const 0))
+  (global $count (mut i32) (i32.const 0))
+  ;; Calls of recursive definitions in progress
+  (global $depth (mut i32) (i32.const 0))
+  (data (i32.const 0) "/definitions/node/definitions/node/propertieschildren/definitions/node/properties/children/definitions/node/properties/children/elementsvalue/definitions/node/properties/value/definitions/node/properties/value/typeparent\00\00\00\00\11\00\00\00\11\00\00\00\1c\00\00\005\00\00\00%\00\00\00Z\00\00\00.\00\00\00\8d\00\00\00\"\00\00\00\af\00\00\00'\00\00\00")
+  ;; Address of token $t
+  (func $tok (param $t i32) (result i32)
+    (i32.add (global.get $tokens) (i32.shl (local.get $t) (i32.const 4))))
+  (func $kind (param $t i32) (result i32)
+    (i32.load (call $tok (local.get $t))))
+  ;; Byte length of a string, member or element count of a container
+  (func $len (param $t i32) (result i32)
+    (i32.load offset=4 (call $tok (local.get $t))))
+  ;; Address of a string's bytes, or the end index of a container
+  (func $ptr (param $t i32) (result i32)
+    (i32.load offset=8 (call $tok (local.get $t))))
+  (func $num (param $t i32) (result f64)
+    (f64.load offset=8 (call $tok (local.get $t))))
+  ;; The token after $t and everything inside it
+  (func $next (param $t i32) (result i32)
+    (if (result i32) (i32.ge_u (call $kind (local.get $t)) (i32.const 5))
+      (then (call $ptr (local.get $t)))
+      (else (i32.add (local.get $t) (i32.const 1)))))
+  ;; Record an error at token $t with schema path $sp, counting past capacity
+  (func $err (param $t i32) (param $sp i32)
+    (local $at i32)
+    (if (i32.lt_u (global.get $count) (global.get $cap))
+      (then
+        (local.set $at
+          (i32.add (global.get $errors) (i32.shl (global.get $count) (i32.const 3))))
+        (i32.store (local.get $at) (local.get $t))
+        (i32.store offset=4 (local.get $at) (local.get $sp))))
+    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+  ;; 1 when token $t is the string of the $n bytes at $p
+  (func $eq (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $q i32)
+    (local $i i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (if (i32.ne (call $len (local.get $t)) (local.get $n))
+      (then (return (i32.const 0))))
+    (local.set $q (call $ptr (local.get $t)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+        (if (i32.ne (i32.load8_u (i32.add (local.get $p) (local.get $i)))
+                    (i32.load8_u (i32.add (local.get $q) (local.get $i))))
+          (then (return (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $each)))
+    (i32.const 1))
+  ;; The value token of the member of object $t named by the $n bytes at $p,
+  ;; or -1
+  (func $find (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $left i32)
+    (local $k i32)
+    (local.set $left (call $len (local.get $t)))
+    (local.set $k (i32.add (local.get $t) (i32.const 1)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $left)))
+        (if (call $eq (local.get $k) (local.get $p) (local.get $n))
+          (then (return (i32.add (local.get $k) (i32.const 1)))))
+        (local.set $k (call $next (i32.add (local.get $k) (i32.const 1))))
+        (local.set $left (i32.sub (local.get $left) (i32.const 1)))
+        (br $each)))
+    (i32.const -1))
+  ;; 1 when token $t is a number with no fractional part in [$min, $max]
+  (func $int (param $t i32) (param $min f64) (param $max f64) (result i32)
+    (local $x f64)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 3))
+      (then (return (i32.const 0))))
+    (local.set $x (call $num (local.get $t)))
+    (i32.and
+      (f64.eq (f64.floor (local.get $x)) (local.get $x))
+      (i32.and (f64.ge (local.get $x) (local.get $min))
+               (f64.le (local.get $x) (local.get $max)))))
+  ;; Validate the token buffer at $tokens, writing up to $cap error records
+  ;; of (token index, schema path id) to $errors; returns the error count
+  (func (export "validate") (param $tokens i32) (param $errors i32) (param $cap i32) (result i32)
+    (global.set $tokens (local.get $tokens))
+    (global.set $errors (local.get $errors))
+    (global.set $cap (local.get $cap))
+    (global.set $count (i32.const 0))
+    (global.set $depth (i32.const 0))
+    (call $root (i32.const 0))
+    (global.get $count))
+  ;; root
+  (func $root (param $t i32)
+    (call $d0 (local.get $t))
+  )
+  ;; "/definitions/node"
+  (func $d0 (param $t i32)
+    (local $l1 i32)
+    (local $l2 i32)
+    (local $l3 i32)
+    (local $l4 i32)
+    (local $l5 i32)
+    (local $l6 i32)
+    (local $l7 i32)
+    (i32.ge_u (global.get $depth) (i32.const 256))
+    if
+      (call $err (local.get $t) (i32.const 0))  ;; "/definitions/node"
+      return
+    end
+    (global.set $depth (i32.add (global.get $depth) (i32.const 1)))
+    block $b1
+      (i32.ne (call $kind (local.get $t)) (i32.const 6))
+      if
+        (call $err (local.get $t) (i32.const 1))  ;; "/definitions/node/properties"
+        br $b1
+      end
+      (local.set $l1 (call $find (local.get $t) (i32.const 45) (i32.const 8)))  ;; "children"
+      (i32.lt_s (local.get $l1) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 2))  ;; "/definitions/node/properties/children"
+      else
+        block $b2
+          (i32.ne (call $kind (local.get $l1)) (i32.const 5))
+          if
+            (call $err (local.get $l1) (i32.const 3))  ;; "/definitions/node/properties/children/elements"
+            br $b2
+          end
+          (local.set $l2 (call $len (local.get $l1)))
+          (local.set $l3 (i32.add (local.get $l1) (i32.const 1)))
+          loop $b3
+            (br_if $b2 (i32.eqz (local.get $l2)))
+            (call $d0 (local.get $l3))
+            (local.set $l3 (call $next (local.get $l3)))
+            (local.set $l2 (i32.sub (local.get $l2) (i32.const 1)))
+            br $b3
+          end
+        end
+      end
+      (local.set $l4 (call $find (local.get $t) (i32.const 136) (i32.const 5)))  ;; "value"
+      (i32.lt_s (local.get $l4) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 4))  ;; "/definitions/node/properties/value"
+      else
+        (i32.eqz (call $int (local.get $l4) (f64.const -32768) (f64.const 32767)))
+        if
+          (call $err (local.get $l4) (i32.const 5))  ;; "/definitions/node/properties/value/type"
+        end
+      end
+      (local.set $l5 (call $find (local.get $t) (i32.const 214) (i32.const 6)))  ;; "parent"
+      (i32.ge_s (local.get $l5) (i32.const 0))
+      if
+        (i32.ne (call $kind (local.get $l5)) (i32.const 0))
+        if
+          (call $d0 (local.get $l5))
+        end
+      end
+      (local.set $l6 (call $len (local.get $t)))
+      (local.set $l7 (i32.add (local.get $t) (i32.const 1)))
+      loop $b4
+        (br_if $b1 (i32.eqz (local.get $l6)))
+        block $b5
+          (br_if $b5 (call $eq (local.get $l7) (i32.const 45) (i32.const 8)))  ;; "children"
+          (br_if $b5 (call $eq (local.get $l7) (i32.const 136) (i32.const 5)))  ;; "value"
+          (br_if $b5 (call $eq (local.get $l7) (i32.const 214) (i32.const 6)))  ;; "parent"
+          (call $err (local.get $l7) (i32.const 0))  ;; "/definitions/node"
+        end
+        (local.set $l7 (call $next (i32.add (local.get $l7) (i32.const 1))))
+        (local.set $l6 (i32.sub (local.get $l6) (i32.const 1)))
+        br $b4
+      end
+    end
+    (global.set $depth (i32.sub (global.get $depth) (i32.const 1)))
+  )
+)
@@ -0,0 +1,356 @@
+;; Generated by jtd-codegen (https://github.com/simbo1905/jtd-wasm)
+;; This code is generated from a JSON Type Definition schema.
+;; Do not edit manually.
+(module
+  (memory (export "memory") 1)
+  ;; Where the host may write tokens and strings
+  (global (export "heap_base") i32 (i32.const 576))
+  ;; (address, length) of each schema path, indexed by error records
+  (global (export "schema_paths") i32 (i32.const 436))
+  (global (export "schema_path_count") i32 (i32.const 17))
+  (global $tokens (mut i32) (i32.const 0))
+  (global $errors (mut i32) (i32.const 0))
+  (global $cap (mut i32) (i32.const 0))
+  (global $count (mut i32) (i32.const 0))
+  (data (i32.const 0) "/propertiesage/properties/age/properties/age/typecreatedAt/properties/createdAt/properties/createdAt/typeid/properties/id/properties/id/typerole/properties/roleadminmemberguest/properties/role/enumtags/properties/tags/properties/tags/elements/properties/tags/elements/typeemail/optionalProperties/email/typepreferences/optionalProperties/preferences/values/optionalProperties/preferences/values/typescore/optionalProperties/score/type\00\00\00\00\00\00\0b\00\00\00\0e\00\00\00\0f\00\00\00\1d\00\00\00\14\00\00\00:\00\00\00\15\00\00\00O\00\00\00\1a\00\00\00k\00\00\00\0e\00\00\00y\00\00\00\13\00\00\00\90\00\00\00\10\00\00\00\b0\00\00\00\15\00\00\00\c9\00\00\00\10\00\00\00\d9\00\00\00\19\00\00\00\f2\00\00\00\1e\00\00\00\15\01\00\00\1e\00\00\00>\01\00\00&\00\00\00d\01\00\00+\00\00\00\94\01\00\00\1e\00\00\00\b2\01\00\00\00\00\00\00")
+  ;; Address of token $t
+  (func $tok (param $t i32) (result i32)
+    (i32.add (global.get $tokens) (i32.shl (local.get $t) (i32.const 4))))
+  (func $kind (param $t i32) (result i32)
+    (i32.load (call $tok (local.get $t))))
+  ;; Byte length of a string, member or element count of a container
+  (func $len (param $t i32) (result i32)
+    (i32.load offset=4 (call $tok (local.get $t))))
+  ;; Address of a string's bytes, or the end index of a container
+  (func $ptr (param $t i32) (result i32)
+    (i32.load offset=8 (call $tok (local.get $t))))
+  (func $num (param $t i32) (result f64)
+    (f64.load offset=8 (call $tok (local.get $t))))
+  ;; The token after $t and everything inside it
+  (func $next (param $t i32) (result i32)
+    (if (result i32) (i32.ge_u (call $kind (local.get $t)) (i32.const 5))
+      (then (call $ptr (local.get $t)))
+      (else (i32.add (local.get $t) (i32.const 1)))))
+  ;; Record an error at token $t with schema path $sp, counting past capacity
+  (func $err (param $t i32) (param $sp i32)
+    (local $at i32)
+    (if (i32.lt_u (global.get $count) (global.get $cap))
+      (then
+        (local.set $at
+          (i32.add (global.get $errors) (i32.shl (global.get $count) (i32.const 3))))
+        (i32.store (local.get $at) (local.get $t))
+        (i32.store offset=4 (local.get $at) (local.get $sp))))
+    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+  ;; 1 when token $t is the string of the $n bytes at $p
+  (func $eq (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $q i32)
+    (local $i i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (if (i32.ne (call $len (local.get $t)) (local.get $n))
+      (then (return (i32.const 0))))
+    (local.set $q (call $ptr (local.get $t)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+        (if (i32.ne (i32.load8_u (i32.add (local.get $p) (local.get $i)))
+                    (i32.load8_u (i32.add (local.get $q) (local.get $i))))
+          (then (return (i32.const 0))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $each)))
+    (i32.const 1))
+  ;; The value token of the member of object $t named by the $n bytes at $p,
+  ;; or -1
+  (func $find (param $t i32) (param $p i32) (param $n i32) (result i32)
+    (local $left i32)
+    (local $k i32)
+    (local.set $left (call $len (local.get $t)))
+    (local.set $k (i32.add (local.get $t) (i32.const 1)))
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $left)))
+        (if (call $eq (local.get $k) (local.get $p) (local.get $n))
+          (then (return (i32.add (local.get $k) (i32.const 1)))))
+        (local.set $k (call $next (i32.add (local.get $k) (i32.const 1))))
+        (local.set $left (i32.sub (local.get $left) (i32.const 1)))
+        (br $each)))
+    (i32.const -1))
+  ;; 1 when token $t is a number with no fractional part in [$min, $max]
+  (func $int (param $t i32) (param $min f64) (param $max f64) (result i32)
+    (local $x f64)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 3))
+      (then (return (i32.const 0))))
+    (local.set $x (call $num (local.get $t)))
+    (i32.and
+      (f64.eq (f64.floor (local.get $x)) (local.get $x))
+      (i32.and (f64.ge (local.get $x) (local.get $min))
+               (f64.le (local.get $x) (local.get $max)))))
+  ;; The value of the $n ASCII digits at $p, or -1
+  (func $digits (param $p i32) (param $n i32) (result i32)
+    (local $v i32)
+    (local $c i32)
+    (block $done
+      (loop $each
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $c (i32.sub (i32.load8_u (local.get $p)) (i32.const 48)))
+        (if (i32.gt_u (local.get $c) (i32.const 9))
+          (then (return (i32.const -1))))
+        (local.set $v (i32.add (i32.mul (local.get $v) (i32.const 10)) (local.get $c)))
+        (local.set $p (i32.add (local.get $p) (i32.const 1)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $each)))
+    (local.get $v))
+  ;; 1 when token $t is a string holding an RFC 3339 date-time
+  (func $timestamp (param $t i32) (result i32)
+    (local $p i32)
+    (local $n i32)
+    (local $y i32)
+    (local $m i32)
+    (local $days i32)
+    (local $i i32)
+    (local $c i32)
+    (if (i32.ne (call $kind (local.get $t)) (i32.const 4))
+      (then (return (i32.const 0))))
+    (local.set $p (call $ptr (local.get $t)))
+    (local.set $n (call $len (local.get $t)))
+    ;; YYYY-MM-DDThh:mm:ssZ is the shortest form
+    (if (i32.lt_u (local.get $n) (i32.const 20))
+      (then (return (i32.const 0))))
+    (if (i32.or
+          (i32.or (i32.ne (i32.load8_u offset=4 (local.get $p)) (i32.const 45))
+                  (i32.ne (i32.load8_u offset=7 (local.get $p)) (i32.const 45)))
+          (i32.or
+            (i32.ne (i32.or (i32.load8_u offset=10 (local.get $p)) (i32.const 32))
+                    (i32.const 116))
+            (i32.or (i32.ne (i32.load8_u offset=13 (local.get $p)) (i32.const 58))
+                    (i32.ne (i32.load8_u offset=16 (local.get $p)) (i32.const 58)))))
+      (then (return (i32.const 0))))
+    (local.set $y (call $digits (local.get $p) (i32.const 4)))
+    (local.set $m (call $digits (i32.add (local.get $p) (i32.const 5)) (i32.const 2)))
+    (if (i32.or (i32.lt_s (local.get $y) (i32.const 0))
+                (i32.gt_u (i32.sub (local.get $m) (i32.const 1)) (i32.const 11)))
+      (then (return (i32.const 0))))
+    ;; 31 days in months 1, 3, 5, 7, 8, 10 and 12
+    (local.set $days
+      (i32.add (i32.const 30)
+        (i32.and (i32.add (local.get $m) (i32.shr_u (local.get $m) (i32.const 3)))
+                 (i32.const 1))))
+    (if (i32.eq (local.get $m) (i32.const 2))
+      (then
+        (local.set $days
+          (i32.add (i32.const 28)
+            (i32.and
+              (i32.eqz (i32.rem_u (local.get $y) (i32.const 4)))
+              (i32.or (i32.ne (i32.rem_u (local.get $y) (i32.const 100)) (i32.const 0))
+                      (i32.eqz (i32.rem_u (local.get $y) (i32.const 400)))))))))
+    ;; Unsigned comparisons also reject -1 from $digits
+    (if (i32.or
+          (i32.ge_u
+            (i32.sub (call $digits (i32.add (local.get $p) (i32.const 8)) (i32.const 2))
+                     (i32.const 1))
+            (local.get $days))
+          (i32.or
+            (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 11)) (i32.const 2))
+                      (i32.const 23))
+            (i32.or
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 14)) (i32.const 2))
+                        (i32.const 59))
+              (i32.gt_u (call $digits (i32.add (local.get $p) (i32.const 17)) (i32.const 2))
+                        (i32.const 60)))))
+      (then (return (i32.const 0))))
+    (local.set $i (i32.const 19))
+    (if (i32.eq (i32.load8_u offset=19 (local.get $p)) (i32.const 46))
+      (then
+        (local.set $i (i32.const 20))
+        (block $done
+          (loop $each
+            (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+            (local.set $c
+              (i32.sub (i32.load8_u (i32.add (local.get $p) (local.get $i))) (i32.const 48)))
+            (br_if $done (i32.gt_u (local.get $c) (i32.const 9)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $each)))
+        (if (i32.eq (local.get $i) (i32.const 20))
+          (then (return (i32.const 0))))))
+    (local.set $p (i32.add (local.get $p) (local.get $i)))
+    (local.set $n (i32.sub (local.get $n) (local.get $i)))
+    (if (i32.eq (local.get $n) (i32.const 1))
+      (then (return (i32.eq (i32.or (i32.load8_u (local.get $p)) (i32.const 32))
+                            (i32.const 122)))))
+    (if (i32.ne (local.get $n) (i32.const 6))
+      (then (return (i32.const 0))))
+    (local.set $c (i32.load8_u (local.get $p)))
+    (i32.and
+      (i32.and
+        (i32.or (i32.eq (local.get $c) (i32.const 43)) (i32.eq (local.get $c) (i32.const 45)))
+        (i32.eq (i32.load8_u offset=3 (local.get $p)) (i32.const 58)))
+      (i32.and
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 1)) (i32.const 2))
+                  (i32.const 23))
+        (i32.le_u (call $digits (i32.add (local.get $p) (i32.const 4)) (i32.const 2))
+                  (i32.const 59)))))
+  ;; Validate the token buffer at $tokens, writing up to $cap error records
+  ;; of (token index, schema path id) to $errors; returns the error count
+  (func (export "validate") (param $tokens i32) (param $errors i32) (param $cap i32) (result i32)
+    (global.set $tokens (local.get $tokens))
+    (global.set $errors (local.get $errors))
+    (global.set $cap (local.get $cap))
+    (global.set $count (i32.const 0))
+    (call $root (i32.const 0))
+    (global.get $count))
+  ;; root
+  ;; An account holder.
+  (func $root (param $t i32)
+    (local $l1 i32)
+    (local $l2 i32)
+    (local $l3 i32)
+    (local $l4 i32)
+    (local $l5 i32)
+    (local $l6 i32)
+    (local $l7 i32)
+    (local $l8 i32)
+    (local $l9 i32)
+    (local $l10 i32)
+    (local $l11 i32)
+    (local $l12 i32)
+    (local $l13 i32)
+    (local $l14 i32)
+    (local $l15 i32)
+    block $b1
+      (i32.ne (call $kind (local.get $t)) (i32.const 6))
+      if
+        (call $err (local.get $t) (i32.const 0))  ;; "/properties"
+        br $b1
+      end
+      (local.set $l1 (call $find (local.get $t) (i32.const 11) (i32.const 3)))  ;; "age"
+      (i32.lt_s (local.get $l1) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 1))  ;; "/properties/age"
+      else
+        (i32.eqz (call $int (local.get $l1) (f64.const 0) (f64.const 255)))
+        if
+          (call $err (local.get $l1) (i32.const 2))  ;; "/properties/age/type"
+        end
+      end
+      (local.set $l2 (call $find (local.get $t) (i32.const 49) (i32.const 9)))  ;; "createdAt"
+      (i32.lt_s (local.get $l2) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 3))  ;; "/properties/createdAt"
+      else
+        (i32.eqz (call $timestamp (local.get $l2)))
+        if
+          (call $err (local.get $l2) (i32.const 4))  ;; "/properties/createdAt/type"
+        end
+      end
+      (local.set $l3 (call $find (local.get $t) (i32.const 105) (i32.const 2)))  ;; "id"
+      (i32.lt_s (local.get $l3) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 5))  ;; "/properties/id"
+      else
+        (i32.eqz (i32.eq (call $kind (local.get $l3)) (i32.const 4)))
+        if
+          (call $err (local.get $l3) (i32.const 6))  ;; "/properties/id/type"
+        end
+      end
+      (local.set $l4 (call $find (local.get $t) (i32.const 140) (i32.const 4)))  ;; "role"
+      (i32.lt_s (local.get $l4) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 7))  ;; "/properties/role"
+      else
+        block $b2
+          (br_if $b2 (call $eq (local.get $l4) (i32.const 160) (i32.const 5)))  ;; "admin"
+          (br_if $b2 (call $eq (local.get $l4) (i32.const 165) (i32.const 6)))  ;; "member"
+          (br_if $b2 (call $eq (local.get $l4) (i32.const 171) (i32.const 5)))  ;; "guest"
+          (call $err (local.get $l4) (i32.const 8))  ;; "/properties/role/enum"
+        end
+      end
+      (local.set $l5 (call $find (local.get $t) (i32.const 197) (i32.const 4)))  ;; "tags"
+      (i32.lt_s (local.get $l5) (i32.const 0))
+      if
+        (call $err (local.get $t) (i32.const 9))  ;; "/properties/tags"
+      else
+        block $b3
+          (i32.ne (call $kind (local.get $l5)) (i32.const 5))
+          if
+            (call $err (local.get $l5) (i32.const 10))  ;; "/properties/tags/elements"
+            br $b3
+          end
+          (local.set $l6 (call $len (local.get $l5)))
+          (local.set $l7 (i32.add (local.get $l5) (i32.const 1)))
+          loop $b4
+            (br_if $b3 (i32.eqz (local.get $l6)))
+            (i32.eqz (i32.eq (call $kind (local.get $l7)) (i32.const 4)))
+            if
+              (call $err (local.get $l7) (i32.const 11))  ;; "/properties/tags/elements/type"
+            end
+            (local.set $l7 (call $next (local.get $l7)))
+            (local.set $l6 (i32.sub (local.get $l6) (i32.const 1)))
+            br $b4
+          end
+        end
+      end
+      (local.set $l8 (call $find (local.get $t) (i32.const 272) (i32.const 5)))  ;; "email"
+      (i32.ge_s (local.get $l8) (i32.const 0))
+      if
+        (i32.ne (call $kind (local.get $l8)) (i32.const 0))
+        if
+          (i32.eqz (i32.eq (call $kind (local.get $l8)) (i32.const 4)))
+          if
+            (call $err (local.get $l8) (i32.const 12))  ;; "/optionalProperties/email/type"
+          end
+        end
+      end
+      (local.set $l9 (call $find (local.get $t) (i32.const 307) (i32.const 11)))  ;; "preferences"
+      (i32.ge_s (local.get $l9) (i32.const 0))
+      if
+        block $b5
+          (i32.ne (call $kind (local.get $l9)) (i32.const 6))
+          if
+            (call $err (local.get $l9) (i32.const 13))  ;; "/optionalProperties/preferences/values"
+            br $b5
+          end
+          (local.set $l10 (call $len (local.get $l9)))
+          (local.set $l11 (i32.add (local.get $l9) (i32.const 1)))
+          loop $b6
+            (br_if $b5 (i32.eqz (local.get $l10)))
+            (local.set $l12 (i32.add (local.get $l11) (i32.const 1)))
+            (i32.eqz (i32.le_u (i32.sub (call $kind (local.get $l12)) (i32.const 1)) (i32.const 1)))
+            if
+              (call $err (local.get $l12) (i32.const 14))  ;; "/optionalProperties/preferences/values/type"
+            end
+            (local.set $l11 (call $next (local.get $l12)))
+            (local.set $l10 (i32.sub (local.get $l10) (i32.const 1)))
+            br $b6
+          end
+        end
+      end
+      (local.set $l13 (call $find (local.get $t) (i32.const 399) (i32.const 5)))  ;; "score"
+      (i32.ge_s (local.get $l13) (i32.const 0))
+      if
+        (i32.eqz (i32.eq (call $kind (local.get $l13)) (i32.const 3)))
+        if
+          (call $err (local.get $l13) (i32.const 15))  ;; "/optionalProperties/score/type"
+        end
+      end
+      (local.set $l14 (call $len (local.get $t)))
+      (local.set $l15 (i32.add (local.get $t) (i32.const 1)))
+      loop $b7
+        (br_if $b1 (i32.eqz (local.get $l14)))
+        block $b8
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 11) (i32.const 3)))  ;; "age"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 49) (i32.const 9)))  ;; "createdAt"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 105) (i32.const 2)))  ;; "id"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 140) (i32.const 4)))  ;; "role"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 197) (i32.const 4)))  ;; "tags"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 272) (i32.const 5)))  ;; "email"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 307) (i32.const 11)))  ;; "preferences"
+          (br_if $b8 (call $eq (local.get $l15) (i32.const 399) (i32.const 5)))  ;; "score"
+          (call $err (local.get $l15) (i32.const 16))  ;; ""
+        end
+        (local.set $l15 (call $next (i32.add (local.get $l15) (i32.const 1))))
+        (local.set $l14 (i32.sub (local.get $l14) (i32.const 1)))
+        br $b7
+      end
+    end
+  )
+)
@@ -0,0 +1,107 @@
+/// Integration test: runs the official JTD validation suite through the AST
+/// interpreter (`jtd_codegen::interpret`), no generated code involved.
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+fn default_suite_path() -> PathBuf {
+    // jtd-codegen/ -> workspace root
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
+}
+
+fn segments_to_pointer(segments: &[Value]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
+    let arr = errors.as_array().expect("errors must be array");
+    arr.iter()
+        .map(|e| {
+            let ip = segments_to_pointer(e["instancePath"].as_array().unwrap());
+            let sp = segments_to_pointer(e["schemaPath"].as_array().unwrap());
+            (ip, sp)
+        })
+        .collect()
+}
+
+#[test]
+fn test_interpreter_validation_suite() {
+    let suite = load_suite();
+    let mut passed = 0u32;
+    let mut skipped = 0u32;
+    let mut failures: Vec<String> = Vec::new();
+
+    for (name, case) in &suite {
+        let expected = normalize_errors(&case["errors"]);
+        let compiled = match jtd_codegen::compiler::compile(&case["schema"]) {
+            Ok(c) => c,
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let actual: BTreeSet<_> = jtd_codegen::interpret::validate(&compiled, &case["instance"])
+            .into_iter()
+            .collect();
+        if actual == expected {
+            passed += 1;
+        } else {
+            failures.push(format!(
+                "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+            ));
+        }
+    }
+
+    eprintln!("=== JTD Validation Suite (interpreter) ===");
+    eprintln!("Passed:  {passed}");
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert!(failures.is_empty(), "{} test cases failed", failures.len());
+}
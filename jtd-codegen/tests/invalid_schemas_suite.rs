@@ -0,0 +1,89 @@
+/// Integration test: every schema in the official JTD `invalid_schemas.json`
+/// suite must fail to compile. The default compile ignores unknown members,
+/// so the suite's schemas whose only fault is one must fail strict
+/// compilation instead.
+use jtd_codegen::compiler::{self, CompileError};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+fn default_suite_path() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("invalid_schemas.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_INVALID_SCHEMAS_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read invalid schemas suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_INVALID_SCHEMAS_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse invalid_schemas.json");
+    v.as_object().unwrap().clone()
+}
+
+#[test]
+fn test_invalid_schemas() {
+    let suite = load_suite();
+    let mut accepted = Vec::new();
+    for (name, schema) in &suite {
+        if compiler::compile(schema).is_err() {
+            continue;
+        }
+        match compiler::compile_strict(schema) {
+            Err(CompileError::UnknownKeyword(_)) => {}
+            _ => accepted.push(name),
+        }
+    }
+
+    eprintln!("=== JTD Invalid Schemas Suite ===");
+    eprintln!("Rejected: {}", suite.len() - accepted.len());
+    eprintln!("Accepted: {}", accepted.len());
+    for name in &accepted {
+        eprintln!("ACCEPTED: {name}");
+    }
+
+    assert!(
+        accepted.is_empty(),
+        "{} invalid schemas accepted",
+        accepted.len()
+    );
+}
+
+#[test]
+fn test_invalid_schemas_strict() {
+    let suite = load_suite();
+    let accepted: Vec<&String> = suite
+        .iter()
+        .filter(|(_, schema)| compiler::compile_strict(schema).is_ok())
+        .map(|(name, _)| name)
+        .collect();
+
+    eprintln!("=== JTD Invalid Schemas Suite (strict) ===");
+    eprintln!("Rejected: {}", suite.len() - accepted.len());
+    eprintln!("Accepted: {}", accepted.len());
+    for name in &accepted {
+        eprintln!("ACCEPTED: {name}");
+    }
+
+    assert!(
+        accepted.is_empty(),
+        "{} invalid schemas accepted",
+        accepted.len()
+    );
+}
@@ -0,0 +1,121 @@
+/// Integration test: converts each schema in the official JTD validation
+/// suite to JSON Schema (Draft 2020-12) and checks that the Python
+/// `jsonschema` package accepts exactly the instances JTD does. Error
+/// locations differ between the languages, so only validity is compared.
+///
+/// Needs `python3` with `jsonschema` 4.x; skipped otherwise.
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+const CHECK: &str = r#"
+import json, sys
+from jsonschema import Draft202012Validator
+for line in open(sys.argv[1]):
+    case = json.loads(line)
+    Draft202012Validator.check_schema(case["schema"])
+    valid = Draft202012Validator(case["schema"]).is_valid(case["instance"])
+    print(case["name"] + "\t" + ("valid" if valid else "invalid"))
+"#;
+
+fn default_suite_path() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    v.as_object().unwrap().clone()
+}
+
+fn has_jsonschema() -> bool {
+    let found = Command::new("python3")
+        .args(["-c", "from jsonschema import Draft202012Validator"])
+        .output()
+        .is_ok_and(|out| out.status.success());
+    if !found {
+        eprintln!("SKIP: python3 with jsonschema not found, skipping JSON Schema suite");
+    }
+    found
+}
+
+#[test]
+fn test_json_schema_suite() {
+    if !has_jsonschema() {
+        return;
+    }
+
+    let suite = load_suite();
+    let mut lines = String::new();
+    let mut skipped = 0u32;
+    let mut expected = std::collections::BTreeMap::new();
+    for (name, case) in &suite {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            skipped += 1;
+            continue;
+        };
+        let schema = jtd_codegen::convert::to_json_schema(&compiled);
+        let line = json!({"name": name, "schema": schema, "instance": case["instance"]});
+        lines.push_str(&format!("{line}\n"));
+        let valid = case["errors"].as_array().is_some_and(Vec::is_empty);
+        expected.insert(name.as_str(), if valid { "valid" } else { "invalid" });
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let cases = dir.path().join("cases.ndjson");
+    std::fs::write(&cases, lines).unwrap();
+    let out = Command::new("python3")
+        .args(["-c", CHECK])
+        .arg(&cases)
+        .output()
+        .expect("run python3");
+    assert!(
+        out.status.success(),
+        "python3 failed:\n{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let mut failures = Vec::new();
+    for line in stdout.lines() {
+        let (name, actual) = line.split_once('\t').unwrap();
+        if expected[name] != actual {
+            failures.push(format!(
+                "FAIL: {name}: expected {}, got {actual}",
+                expected[name]
+            ));
+        }
+    }
+
+    eprintln!("=== JTD Validation Suite (JSON Schema) ===");
+    eprintln!("Passed:  {}", expected.len() - failures.len());
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert_eq!(stdout.lines().count(), expected.len());
+    assert!(failures.is_empty(), "{} test cases failed", failures.len());
+}
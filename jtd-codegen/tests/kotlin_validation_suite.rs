@@ -0,0 +1,215 @@
+/// Integration test: generates Kotlin from each test case in the official
+/// JTD validation suite, one package per case, compiles them with a runner
+/// in a single kotlinc invocation and runs it on the instances.
+///
+/// Needs `kotlinc` and `kotlin` on PATH and `JTD_KOTLIN_CLASSPATH` naming
+/// the kotlinx-serialization-json (and -core) JVM jars; skipped otherwise.
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+/// `(instancePath, schemaPath)` pairs.
+type Errors = BTreeSet<(String, String)>;
+
+fn default_suite_path() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
+}
+
+fn segments_to_pointer(segments: &[Value]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+fn normalize_errors(errors: &Value) -> Errors {
+    let arr = errors.as_array().expect("errors must be array");
+    arr.iter()
+        .map(|e| {
+            let ip = segments_to_pointer(e["instancePath"].as_array().unwrap());
+            let sp = segments_to_pointer(e["schemaPath"].as_array().unwrap());
+            (ip, sp)
+        })
+        .collect()
+}
+
+/// `main` for the runner: reads `{case: instance}` from the file in its
+/// argument and prints `case\tinstancePath\tschemaPath` per error, the
+/// paths as JSON strings since keys may hold tabs and newlines, failing
+/// when `isValid` disagrees with `validate`.
+fn runner(cases: &[String]) -> String {
+    let mut out = String::from(
+        "import kotlinx.serialization.json.Json\n\
+         import kotlinx.serialization.json.JsonPrimitive\n\
+         import kotlinx.serialization.json.jsonObject\n\n\
+         fun main(args: Array<String>) {\n    \
+         val cases = Json.parseToJsonElement(java.io.File(args[0]).readText()).jsonObject\n    \
+         for ((name, instance) in cases) {\n        \
+         val errors = when (name) {\n",
+    );
+    for case in cases {
+        out.push_str(&format!(
+            "            \"{case}\" -> {case}.validate(instance).map {{ it.instancePath to it.schemaPath }}\n                .also {{ check({case}.isValid(instance) == it.isEmpty()) {{ \"isValid disagrees: $name\" }} }}\n"
+        ));
+    }
+    out.push_str(
+        "            else -> error(name)\n        \
+         }\n        \
+         for ((ip, sp) in errors) println(\"$name\\t${JsonPrimitive(ip)}\\t${JsonPrimitive(sp)}\")\n    \
+         }\n\
+         }\n",
+    );
+    out
+}
+
+#[test]
+fn test_kotlin_validation_suite() {
+    let Ok(classpath) = std::env::var("JTD_KOTLIN_CLASSPATH") else {
+        eprintln!("SKIP: JTD_KOTLIN_CLASSPATH not set, skipping Kotlin validation suite");
+        return;
+    };
+    if !Command::new("kotlinc")
+        .arg("-version")
+        .output()
+        .is_ok_and(|out| out.status.success())
+    {
+        eprintln!("SKIP: kotlinc not found, skipping Kotlin validation suite");
+        return;
+    }
+
+    let suite = load_suite();
+    let dir = tempfile::tempdir().unwrap();
+    let mut instances = serde_json::Map::new();
+    let mut expected: BTreeMap<String, (&String, Errors)> = BTreeMap::new();
+    let mut sources = Vec::new();
+    let mut skipped = 0u32;
+    for (i, (name, case)) in suite.iter().enumerate() {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            skipped += 1;
+            continue;
+        };
+        let package = format!("case{i}");
+        let opts = jtd_codegen::options::EmitOptions {
+            kt_package: Some(package.clone()),
+            is_valid: true,
+            ..Default::default()
+        };
+        let source = dir.path().join(format!("{package}.kt"));
+        std::fs::write(
+            &source,
+            jtd_codegen::emit_kt::emit_with_options(&compiled, &opts),
+        )
+        .unwrap();
+        sources.push(source);
+        instances.insert(package.clone(), case["instance"].clone());
+        expected.insert(package, (name, normalize_errors(&case["errors"])));
+    }
+    let main = dir.path().join("Main.kt");
+    let packages: Vec<String> = expected.keys().cloned().collect();
+    std::fs::write(&main, runner(&packages)).unwrap();
+    sources.push(main);
+    let input = dir.path().join("instances.json");
+    std::fs::write(&input, Value::Object(instances).to_string()).unwrap();
+
+    let jar = dir.path().join("suite.jar");
+    let out = Command::new("kotlinc")
+        .args(["-nowarn", "-cp", &classpath, "-d"])
+        .arg(&jar)
+        .args(&sources)
+        .output()
+        .expect("run kotlinc");
+    assert!(
+        out.status.success(),
+        "kotlinc failed:\n{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let sep = if cfg!(windows) { ";" } else { ":" };
+    let out = Command::new("kotlin")
+        .arg("-cp")
+        .arg(format!("{}{sep}{classpath}", jar.display()))
+        .arg("MainKt")
+        .arg(&input)
+        .output()
+        .expect("run kotlin");
+    assert!(
+        out.status.success(),
+        "runner failed:\n{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let mut actual: BTreeMap<&str, Errors> = BTreeMap::new();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    for line in stdout.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (case, ip, sp) = (parts.next().unwrap(), parts.next(), parts.next());
+        let text = |s: Option<&str>| -> String {
+            serde_json::from_str(s.unwrap_or("\"\"")).expect("runner printed a JSON string")
+        };
+        actual.entry(case).or_default().insert((text(ip), text(sp)));
+    }
+
+    let mut failures = Vec::new();
+    for (package, (name, expected)) in &expected {
+        let actual = actual.remove(package.as_str()).unwrap_or_default();
+        if &actual != expected {
+            failures.push(format!(
+                "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+            ));
+        }
+    }
+
+    eprintln!("=== JTD Validation Suite (Kotlin) ===");
+    eprintln!("Passed:  {}", expected.len() - failures.len());
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert!(failures.is_empty(), "{} test cases failed", failures.len());
+}
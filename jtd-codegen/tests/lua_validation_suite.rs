@@ -1,9 +1,12 @@
 /// Integration test: generates Lua from each test case in the official
-/// JTD validation suite and evaluates it with embedded Lua 5.1 (mlua).
+/// JTD validation suite and evaluates it with embedded Lua 5.1 (mlua), and
+/// optionally with external interpreters for the other dialects.
+use jtd_codegen::options::{EmitOptions, LuaDialect};
 use mlua::Lua;
 use serde_json::Value;
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
 
@@ -33,7 +36,21 @@ fn load_suite() -> serde_json::Map<String, Value> {
     });
 
     let v: Value = serde_json::from_str(&data).expect("parse validation.json");
-    v.as_object().unwrap().clone()
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
 }
 
 fn segments_to_pointer(segments: &[Value]) -> String {
@@ -42,7 +59,10 @@ fn segments_to_pointer(segments: &[Value]) -> String {
     }
     segments
         .iter()
-        .map(|s| format!("/{}", s.as_str().unwrap()))
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
         .collect::<Vec<_>>()
         .join("")
 }
@@ -58,24 +78,113 @@ fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
         .collect()
 }
 
-fn parse_lua_output(json_out: &str) -> BTreeSet<(String, String)> {
-    let arr: Vec<Vec<String>> = serde_json::from_str(json_out).expect("parse lua output");
+/// A path as the validator returned it: a JSON Pointer, or with
+/// `--path-segments` an array of segments.
+fn path_to_pointer(path: &Value) -> String {
+    match path {
+        Value::String(p) => p.clone(),
+        Value::Array(segments) => segments_to_pointer(segments),
+        other => panic!("unexpected error path {other}"),
+    }
+}
+
+fn parse_lua_output(json_out: &str) -> Vec<(String, String)> {
+    let arr: Vec<Vec<Value>> = serde_json::from_str(json_out).expect("parse lua output");
     arr.into_iter()
-        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .map(|pair| (path_to_pointer(&pair[0]), path_to_pointer(&pair[1])))
         .collect()
 }
 
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+/// All the expected errors, or with a `limit` (1 when failing fast) that
+/// many of them when there are more.
+fn errors_match(
+    actual: &[(String, String)],
+    expected: &BTreeSet<(String, String)>,
+    limit: Option<usize>,
+) -> bool {
+    if let Some(limit) = limit {
+        actual.len() == expected.len().min(limit) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<BTreeSet<_>>() == *expected
+    }
+}
+
 #[test]
 fn test_lua_validation_suite() {
     eprintln!("INFO: test_lua_validation_suite");
+    run_suite("default", &EmitOptions::default());
+}
 
-    let suite = load_suite();
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_lua_validation_suite_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    };
+    run_suite("fail-fast", &opts);
+}
 
-    // Load dkjson source
-    let dkjson_path = std::env::var("JTD_DKJSON_PATH")
+/// Validators with an error limit return no more errors than that.
+#[test]
+fn test_lua_validation_suite_max_errors() {
+    let opts = EmitOptions {
+        max_errors: Some(2),
+        ..EmitOptions::default()
+    };
+    run_suite("max-errors", &opts);
+}
+
+/// Errors with messages have the same paths, and every one has a message.
+#[test]
+fn test_lua_validation_suite_messages() {
+    let opts = EmitOptions {
+        messages: true,
+        ..EmitOptions::default()
+    };
+    run_suite("messages", &opts);
+}
+
+/// With `--path-segments` the paths come back as arrays of segments.
+#[test]
+fn test_lua_validation_suite_path_segments() {
+    let opts = EmitOptions {
+        path_segments: true,
+        ..EmitOptions::default()
+    };
+    run_suite("path-segments", &opts);
+}
+
+/// `M.is_valid` agrees with `M.validate` on every case.
+#[test]
+fn test_lua_validation_suite_is_valid() {
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("is-valid", &opts);
+}
+
+fn dkjson_path() -> PathBuf {
+    std::env::var("JTD_DKJSON_PATH")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from(".tmp/dkjson.lua"));
+        .unwrap_or_else(|_| PathBuf::from(".tmp/dkjson.lua"))
+}
 
+fn run_suite(label: &str, opts: &EmitOptions) {
+    let suite = load_suite();
+
+    // Load dkjson source
+    let dkjson_path = dkjson_path();
     let dkjson_src = std::fs::read_to_string(&dkjson_path).unwrap_or_else(|e| {
         panic!("Cannot read dkjson.lua at {}: {}", dkjson_path.display(), e);
     });
@@ -116,7 +225,7 @@ fn test_lua_validation_suite() {
             }
         };
 
-        let lua_code = jtd_codegen::emit_lua::emit(&compiled);
+        let lua_code = jtd_codegen::emit_lua::emit_with_options(&compiled, opts);
         let instance_json = serde_json::to_string(instance).unwrap();
 
         // Prepare test script
@@ -132,14 +241,20 @@ fn test_lua_validation_suite() {
             local instance = dkjson.decode(instance_json, 1, dkjson.null)
 
             local errors = M.validate(instance)
-            
+            if {} and M.is_valid(instance) ~= (#errors == 0) then
+                error("is_valid disagrees with validate")
+            end
+
             local out = {{}}
             for _, err in ipairs(errors) do
+                if {} and (type(err.message) ~= "string" or err.message == "") then
+                    error("error without a message")
+                end
                 table.insert(out, {{err.instancePath, err.schemaPath}})
             end
             return dkjson.encode(out)
         "#,
-            lua_code
+            lua_code, opts.is_valid, opts.messages
         );
 
         let res: Result<String, _> = lua.load(&run_script).call(instance_json.clone());
@@ -147,7 +262,7 @@ fn test_lua_validation_suite() {
         match res {
             Ok(json_out) => {
                 let actual = parse_lua_output(&json_out);
-                if actual == expected {
+                if errors_match(&actual, &expected, error_limit(opts)) {
                     passed += 1;
                 } else {
                     failed += 1;
@@ -163,7 +278,7 @@ fn test_lua_validation_suite() {
         }
     }
 
-    eprintln!("=== JTD Validation Suite (Lua) ===");
+    eprintln!("=== JTD Validation Suite (Lua, {label}) ===");
     eprintln!("Passed:  {passed}");
     eprintln!("Failed:  {failed}");
     eprintln!("Skipped: {skipped}");
@@ -171,5 +286,342 @@ fn test_lua_validation_suite() {
         eprintln!("{f}");
     }
 
-    assert_eq!(failed, 0, "{failed} Lua test cases failed");
+    assert_eq!(failed, 0, "{failed} Lua {label} test cases failed");
+}
+
+/// Runs the suite under each interpreter in `JTD_LUA_INTERPRETERS`
+/// (executables, separated as in `PATH`), generating for the dialect the
+/// interpreter reports. Skipped when unset.
+#[test]
+fn test_lua_validation_suite_interpreters() {
+    let Some(list) = std::env::var_os("JTD_LUA_INTERPRETERS") else {
+        eprintln!("SKIP: JTD_LUA_INTERPRETERS not set, skipping external Lua interpreters");
+        return;
+    };
+    for interpreter in std::env::split_paths(&list) {
+        let dialect = interpreter_dialect(&interpreter);
+        let option_sets = [
+            ("default", EmitOptions::default()),
+            (
+                "fail-fast",
+                EmitOptions {
+                    fail_fast: true,
+                    is_valid: true,
+                    ..EmitOptions::default()
+                },
+            ),
+            (
+                "max-errors",
+                EmitOptions {
+                    max_errors: Some(2),
+                    messages: true,
+                    suggestions: true,
+                    ..EmitOptions::default()
+                },
+            ),
+        ];
+        for (label, opts) in option_sets {
+            let opts = EmitOptions {
+                lua_dialect: dialect,
+                ..opts
+            };
+            run_suite_external(&interpreter, label, &opts);
+        }
+    }
+}
+
+/// The dialect `interpreter` implements, from `_VERSION` and `jit`.
+fn interpreter_dialect(interpreter: &Path) -> LuaDialect {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("version.lua");
+    std::fs::write(&script, "io.write(jit and \"luajit\" or _VERSION)").unwrap();
+    let output = Command::new(interpreter)
+        .arg(&script)
+        .output()
+        .unwrap_or_else(|e| panic!("Cannot run {}: {e}", interpreter.display()));
+    let version = String::from_utf8_lossy(&output.stdout);
+    let dialect = version.strip_prefix("Lua ").unwrap_or(&version);
+    LuaDialect::parse(dialect).unwrap_or_else(|| {
+        panic!(
+            "{} is {version}, not a supported dialect",
+            interpreter.display()
+        )
+    })
+}
+
+/// Decodes `cases.json` from the directory in `arg[1]`, validates each
+/// case's instance with its module and writes the errors, as JSON text per
+/// case, as one JSON object.
+const LUA_RUNNER: &str = r#"
+local dir = arg[1]
+package.path = dir .. "/?.lua;" .. package.path
+local dkjson = require("dkjson")
+local f = assert(io.open(dir .. "/cases.json"))
+local data = dkjson.decode(f:read("*a"), 1, dkjson.null)
+f:close()
+local load_code = loadstring or load
+
+local results = {}
+for _, case in ipairs(data.cases) do
+  local ok, out = pcall(function()
+    local M = assert(load_code(case.code))()
+    local instance = dkjson.decode(case.instance, 1, dkjson.null)
+    local errors = M.validate(instance)
+    if data.is_valid and M.is_valid(instance) ~= (#errors == 0) then
+      error("is_valid disagrees with validate")
+    end
+    local pairs_out = {}
+    for _, err in ipairs(errors) do
+      if data.messages and (type(err.message) ~= "string" or err.message == "") then
+        error("error without a message")
+      end
+      table.insert(pairs_out, {err.instancePath, err.schemaPath})
+    end
+    return dkjson.encode(pairs_out)
+  end)
+  results[case.name] = ok and out or dkjson.encode({error = tostring(out)})
+end
+io.write(dkjson.encode(results))
+"#;
+
+/// [`run_suite`] in a separate `interpreter` process, one for all cases.
+fn run_suite_external(interpreter: &Path, label: &str, opts: &EmitOptions) {
+    let suite = load_suite();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::copy(dkjson_path(), dir.path().join("dkjson.lua")).expect("copy dkjson.lua");
+
+    let mut cases = Vec::new();
+    let mut expected_map = std::collections::BTreeMap::new();
+    let mut skipped = 0u32;
+    for (name, case) in &suite {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            skipped += 1;
+            continue;
+        };
+        cases.push(serde_json::json!({
+            "name": name,
+            "code": jtd_codegen::emit_lua::emit_with_options(&compiled, opts),
+            "instance": serde_json::to_string(&case["instance"]).unwrap(),
+        }));
+        expected_map.insert(name.clone(), normalize_errors(&case["errors"]));
+    }
+    let data = serde_json::json!({
+        "cases": cases,
+        "is_valid": opts.is_valid,
+        "messages": opts.messages,
+    });
+    std::fs::write(dir.path().join("cases.json"), data.to_string()).unwrap();
+    let script = dir.path().join("runner.lua");
+    std::fs::write(&script, LUA_RUNNER).unwrap();
+
+    let output = Command::new(interpreter)
+        .arg(&script)
+        .arg(dir.path())
+        .output()
+        .unwrap_or_else(|e| panic!("Cannot run {}: {e}", interpreter.display()));
+    assert!(
+        output.status.success(),
+        "{} failed:\n{}",
+        interpreter.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let results: serde_json::Map<String, Value> =
+        serde_json::from_slice(&output.stdout).expect("parse interpreter output");
+
+    let mut passed = 0u32;
+    let mut failures: Vec<String> = Vec::new();
+    for (name, expected) in &expected_map {
+        let Some(json_out) = results.get(name).and_then(Value::as_str) else {
+            failures.push(format!("FAIL: {name}\n  No result from the interpreter"));
+            continue;
+        };
+        if let Ok(Value::Object(err)) = serde_json::from_str(json_out) {
+            failures.push(format!("FAIL: {name}\n  Lua error: {}", err["error"]));
+            continue;
+        }
+        let actual = parse_lua_output(json_out);
+        if errors_match(&actual, expected, error_limit(opts)) {
+            passed += 1;
+        } else {
+            failures.push(format!(
+                "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+            ));
+        }
+    }
+
+    eprintln!(
+        "=== JTD Validation Suite (Lua, {label}, {}) ===",
+        opts.lua_dialect.as_str()
+    );
+    eprintln!("Passed:  {passed}");
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+    assert!(
+        failures.is_empty(),
+        "{} Lua {label} test cases failed under {}",
+        failures.len(),
+        interpreter.display()
+    );
+}
+
+#[test]
+fn test_lua_deep_recursion() {
+    // Mutually recursive definitions, the first calling the second
+    let schema = serde_json::json!({
+        "definitions": {
+            "a": {"properties": {"b": {"ref": "b"}}},
+            "b": {"optionalProperties": {"a": {"ref": "a"}}}
+        },
+        "ref": "a"
+    });
+    let compiled = jtd_codegen::compiler::compile(&schema).unwrap();
+    let lua_code = jtd_codegen::emit_lua::emit(&compiled);
+    let run_script = format!(
+        r#"
+        -- Only the null sentinel is needed
+        package.loaded["dkjson"] = {{null = {{}}}}
+        local M = (function()
+            {lua_code}
+        end)()
+
+        local depth = ...
+        local instance = {{b = {{}}}}
+        for _ = 1, depth do
+            instance = {{b = {{a = instance}}}}
+        end
+        local errors = M.validate(instance)
+        if #errors == 0 then
+            return ""
+        end
+        return #errors .. " " .. errors[1].schemaPath
+    "#
+    );
+
+    let lua = Lua::new();
+    let shallow: String = lua.load(&run_script).call(100).unwrap();
+    assert_eq!(shallow, "");
+    let deep: String = lua.load(&run_script).call(1_000_000).unwrap();
+    assert_eq!(deep, "1 /definitions/a");
+}
+
+/// The OpenResty module's `access()` under stub `ngx`, `cjson` and
+/// `cjson.safe` modules: valid bodies pass, invalid ones get 400 and the
+/// error array. Default options, so the module must pick lua-cjson's null
+/// sentinel and `array_mt` itself.
+#[test]
+fn test_lua_openresty_access() {
+    let schema = serde_json::json!({
+        "properties": {"id": {"type": "uint32"}, "tags": {"elements": {"type": "string"}}},
+        "optionalProperties": {"note": {"type": "string", "nullable": true}}
+    });
+    let compiled = jtd_codegen::compiler::compile(&schema).unwrap();
+    let opts = EmitOptions {
+        lua_format: jtd_codegen::options::LuaModuleFormat::OpenResty,
+        ..EmitOptions::default()
+    };
+    let lua_code = jtd_codegen::emit_lua::emit_with_options(&compiled, &opts);
+    let dkjson_src = std::fs::read_to_string(dkjson_path()).expect("read dkjson.lua");
+    let run_script = format!(
+        r#"
+        local dkjson = (function()
+            {dkjson_src}
+        end)()
+        package.loaded["dkjson"] = dkjson
+        local cjson = {{ null = {{}}, array_mt = {{}}, empty_array_mt = {{}} }}
+        package.loaded["cjson"] = cjson
+        -- Mark arrays as lua-cjson does, in place of dkjson's metatables
+        local function mark(v)
+            if type(v) ~= "table" then return v end
+            local mt = getmetatable(v)
+            setmetatable(v, mt and mt.__jsontype == "array" and cjson.array_mt or nil)
+            for k, item in pairs(v) do v[k] = mark(item) end
+            return v
+        end
+        package.loaded["cjson.safe"] = {{
+            new = function()
+                local array_mt = false
+                return {{
+                    decode = function(s)
+                        local ok, v, _, err = pcall(dkjson.decode, s, 1, cjson.null)
+                        if not ok then return nil, v end
+                        if v == nil then return nil, err end
+                        assert(array_mt, "decoder not set to mark arrays")
+                        return mark(v)
+                    end,
+                    decode_array_with_array_mt = function(on) array_mt = on end,
+                    encode = dkjson.encode,
+                    encode_empty_table_as_object = function() end,
+                }}
+            end,
+        }}
+        local body = ...
+        local response = {{}}
+        ngx = {{
+            HTTP_BAD_REQUEST = 400,
+            header = {{}},
+            req = {{
+                read_body = function() end,
+                get_body_data = function() return body end,
+                get_body_file = function() return nil end,
+            }},
+            say = function(s) response.body = s end,
+            exit = function(status) response.exit = status end,
+        }}
+        local M = (function()
+            {lua_code}
+        end)()
+        M.access()
+        return dkjson.encode({{
+            exit = response.exit or 0,
+            status = ngx.status or 0,
+            type = ngx.header["Content-Type"] or "",
+            body = response.body or "",
+        }})
+    "#
+    );
+
+    let lua = Lua::new();
+    let run = |body: Option<&str>| -> Value {
+        let out: String = lua.load(&run_script).call(body).unwrap();
+        serde_json::from_str(&out).unwrap()
+    };
+
+    let ok = run(Some(r#"{"id": 7, "tags": [], "note": null}"#));
+    assert_eq!(ok["exit"], 0);
+    assert_eq!(ok["body"], "");
+
+    let invalid = run(Some(r#"{"id": -1, "tags": {}, "x": true}"#));
+    assert_eq!(invalid["exit"], 400);
+    assert_eq!(invalid["status"], 400);
+    assert_eq!(invalid["type"], "application/json");
+    let errors: Vec<Value> = serde_json::from_str(invalid["body"].as_str().unwrap()).unwrap();
+    let errors: BTreeSet<(String, String)> = errors
+        .iter()
+        .map(|e| {
+            (
+                path_to_pointer(&e["instancePath"]),
+                path_to_pointer(&e["schemaPath"]),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        BTreeSet::from([
+            ("/id".to_string(), "/properties/id/type".to_string()),
+            ("/tags".to_string(), "/properties/tags/elements".to_string()),
+            ("/x".to_string(), String::new()),
+        ])
+    );
+
+    let not_json = run(Some("not json"));
+    assert_eq!(not_json["exit"], 400);
+    let body: Value = serde_json::from_str(not_json["body"].as_str().unwrap()).unwrap();
+    assert!(body["error"].as_str().unwrap().starts_with("invalid JSON"));
+
+    let empty = run(None);
+    assert_eq!(empty["exit"], 400);
+    assert_eq!(empty["body"], "{\"error\":\"empty request body\"}");
 }
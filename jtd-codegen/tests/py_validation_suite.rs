@@ -1,5 +1,6 @@
 /// Integration test: generates Python from each test case in the official
 /// JTD validation suite and evaluates it with python3 via subprocess.
+use jtd_codegen::options::{EmitOptions, PyNumbers};
 use serde_json::Value;
 use std::collections::BTreeSet;
 use std::io::Write;
@@ -34,7 +35,21 @@ fn load_suite() -> serde_json::Map<String, Value> {
     });
 
     let v: Value = serde_json::from_str(&data).expect("parse validation.json");
-    v.as_object().unwrap().clone()
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
 }
 
 fn segments_to_pointer(segments: &[Value]) -> String {
@@ -43,7 +58,10 @@ fn segments_to_pointer(segments: &[Value]) -> String {
     }
     segments
         .iter()
-        .map(|s| format!("/{}", s.as_str().unwrap()))
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
         .collect::<Vec<_>>()
         .join("")
 }
@@ -59,13 +77,46 @@ fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
         .collect()
 }
 
-fn parse_py_output(json_out: &str) -> BTreeSet<(String, String)> {
-    let arr: Vec<Vec<String>> = serde_json::from_str(json_out).expect("parse py output");
+/// A path as the validator returned it: a JSON Pointer, or with
+/// `--path-segments` an array of segments.
+fn path_to_pointer(path: &Value) -> String {
+    match path {
+        Value::String(p) => p.clone(),
+        Value::Array(segments) => segments_to_pointer(segments),
+        other => panic!("unexpected error path {other}"),
+    }
+}
+
+fn parse_py_output(json_out: &str) -> Vec<(String, String)> {
+    let arr: Vec<Vec<Value>> = serde_json::from_str(json_out).expect("parse py output");
     arr.into_iter()
-        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .map(|pair| (path_to_pointer(&pair[0]), path_to_pointer(&pair[1])))
         .collect()
 }
 
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+/// All the expected errors, or with a `limit` (1 when failing fast) that
+/// many of them when there are more.
+fn errors_match(
+    actual: &[(String, String)],
+    expected: &BTreeSet<(String, String)>,
+    limit: Option<usize>,
+) -> bool {
+    if let Some(limit) = limit {
+        actual.len() == expected.len().min(limit) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<BTreeSet<_>>() == *expected
+    }
+}
+
 /// Python test runner script. Reads a JSON object from stdin where each key
 /// is a test name and value has "code" and "instance". Evaluates each code
 /// via exec(), calls validate(), and outputs results as JSON to stdout.
@@ -83,6 +134,10 @@ for name in sorted(data.keys()):
     try:
         exec(code, ns)
         errors = ns["validate"](instance)
+        if case["messages"] and not all(isinstance(e.get("message"), str) and e["message"] for e in errors):
+            raise ValueError("error without a message")
+        if case["is_valid"] and ns["is_valid"](instance) != (not errors):
+            raise ValueError("is_valid disagrees with validate")
         results[name] = [[e["instancePath"], e["schemaPath"]] for e in errors]
     except Exception as ex:
         results[name] = {"error": str(ex)}
@@ -93,7 +148,340 @@ json.dump(results, sys.stdout)
 #[test]
 fn test_py_validation_suite() {
     eprintln!("INFO: test_py_validation_suite");
+    run_suite("default", &EmitOptions::default());
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_py_validation_suite_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    };
+    run_suite("fail-fast", &opts);
+}
+
+/// Validators with an error limit return no more errors than that.
+#[test]
+fn test_py_validation_suite_max_errors() {
+    let opts = EmitOptions {
+        max_errors: Some(2),
+        ..EmitOptions::default()
+    };
+    run_suite("max-errors", &opts);
+}
+
+/// Errors with messages have the same paths, and every one has a message.
+#[test]
+fn test_py_validation_suite_messages() {
+    let opts = EmitOptions {
+        messages: true,
+        ..EmitOptions::default()
+    };
+    run_suite("messages", &opts);
+}
+
+/// With `--path-segments` the paths come back as arrays of segments.
+#[test]
+fn test_py_validation_suite_path_segments() {
+    let opts = EmitOptions {
+        path_segments: true,
+        ..EmitOptions::default()
+    };
+    run_suite("path-segments", &opts);
+}
+
+/// `is_valid` agrees with `validate` on every case.
+#[test]
+fn test_py_validation_suite_is_valid() {
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("is_valid", &opts);
+}
+
+/// Strict numbers agree with the suite, which has no integral floats or
+/// non-finite numbers.
+#[test]
+fn test_py_validation_suite_strict_numbers() {
+    let opts = EmitOptions {
+        py_numbers: PyNumbers::Strict,
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("strict-numbers", &opts);
+}
+
+/// What a [`NUMERIC_INSTANCES`] expression evaluates to.
+#[derive(Clone, Copy)]
+enum Num {
+    Bool,
+    Int(i128),
+    Float(f64),
+    Other,
+}
+
+/// Python expressions probing the numeric checks. `10**400` is an `int`
+/// too large for a float; `i128::MAX` stands in for it.
+const NUMERIC_INSTANCES: &[(&str, Num)] = &[
+    ("True", Num::Bool),
+    ("False", Num::Bool),
+    ("0", Num::Int(0)),
+    ("1", Num::Int(1)),
+    ("-1", Num::Int(-1)),
+    ("255", Num::Int(255)),
+    ("256", Num::Int(256)),
+    ("2**31", Num::Int(1 << 31)),
+    ("10**400", Num::Int(i128::MAX)),
+    ("1.0", Num::Float(1.0)),
+    ("1.5", Num::Float(1.5)),
+    ("-128.0", Num::Float(-128.0)),
+    ("float(\"nan\")", Num::Float(f64::NAN)),
+    ("float(\"inf\")", Num::Float(f64::INFINITY)),
+    ("float(\"-inf\")", Num::Float(f64::NEG_INFINITY)),
+    ("\"1\"", Num::Other),
+    ("None", Num::Other),
+];
+
+/// Whether `n` is a valid `ty` under `numbers`.
+fn numeric_valid(ty: &str, numbers: PyNumbers, n: Num) -> bool {
+    let range = match ty {
+        "int8" => Some((-128, 127)),
+        "uint8" => Some((0, 255)),
+        "int16" => Some((-32768, 32767)),
+        "uint16" => Some((0, 65535)),
+        "int32" => Some((-2_147_483_648, 2_147_483_647)),
+        "uint32" => Some((0, 4_294_967_295)),
+        _ => None,
+    };
+    match (n, range) {
+        (Num::Bool | Num::Other, _) => false,
+        (Num::Int(_), None) => true,
+        (Num::Float(f), None) => numbers == PyNumbers::Loose || f.is_finite(),
+        (Num::Int(i), Some((min, max))) => (min..=max).contains(&i),
+        (Num::Float(f), Some((min, max))) => {
+            numbers == PyNumbers::Loose && f.fract() == 0.0 && (min..=max).contains(&(f as i128))
+        }
+    }
+}
+
+/// Runs `validate` and `is_valid` over each expression in
+/// `data["instances"]` for each module in `data["modules"]`.
+const PY_NUMERIC_RUNNER: &str = r#"
+import json, sys
+
+data = json.load(sys.stdin)
+results = {}
+for name, code in data["modules"].items():
+    ns = {}
+    exec(code, ns)
+    results[name] = [[ns["validate"](eval(x)), ns["is_valid"](eval(x))] for x in data["instances"]]
+json.dump(results, sys.stdout)
+"#;
+
+/// Booleans are `int`s in Python but no numeric type accepts them, in
+/// either number mode; strict mode also turns away integral floats for
+/// the integer types and non-finite floats.
+#[test]
+fn test_py_numbers_reject_booleans() {
+    if !Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_ok_and(|out| out.status.success())
+    {
+        eprintln!("SKIP: python3 not found, skipping Python number checks");
+        return;
+    }
+
+    let types = [
+        "float32", "float64", "int8", "uint8", "int16", "uint16", "int32", "uint32",
+    ];
+    let modes = [("loose", PyNumbers::Loose), ("strict", PyNumbers::Strict)];
+    let mut modules = serde_json::Map::new();
+    for ty in types {
+        let compiled = jtd_codegen::compiler::compile(&serde_json::json!({"type": ty})).unwrap();
+        for (label, numbers) in modes {
+            let opts = EmitOptions {
+                py_numbers: numbers,
+                is_valid: true,
+                ..EmitOptions::default()
+            };
+            modules.insert(
+                format!("{ty} {label}"),
+                Value::String(jtd_codegen::emit_py::emit_with_options(&compiled, &opts)),
+            );
+        }
+    }
+    let instances: Vec<&str> = NUMERIC_INSTANCES.iter().map(|(x, _)| *x).collect();
+    let input = serde_json::json!({"modules": modules, "instances": instances});
+
+    let mut child = Command::new("python3")
+        .arg("-c")
+        .arg(PY_NUMERIC_RUNNER)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn python3");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.to_string().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "python3 failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let results: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let type_error = serde_json::json!([{"instancePath": "", "schemaPath": "/type"}]);
+    let mut failures = Vec::new();
+    for ty in types {
+        for (label, numbers) in modes {
+            let name = format!("{ty} {label}");
+            for (result, (expr, n)) in results[&name]
+                .as_array()
+                .unwrap()
+                .iter()
+                .zip(NUMERIC_INSTANCES)
+            {
+                let valid = numeric_valid(ty, numbers, *n);
+                let expected = if valid {
+                    serde_json::json!([])
+                } else {
+                    type_error.clone()
+                };
+                if result[0] != expected || result[1] != valid {
+                    failures.push(format!(
+                        "{name}: {expr} gave {result}, expected valid={valid}"
+                    ));
+                }
+            }
+        }
+    }
+    eprintln!(
+        "=== Python numbers: {} types x {} modes x {} instances ===",
+        types.len(),
+        modes.len(),
+        NUMERIC_INSTANCES.len()
+    );
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// A schema reaching the helpers the suite's schemas do not: recursion,
+/// definition examples, defaults and deprecated properties.
+const MYPY_SCHEMA: &str = r#"{
+    "definitions": {
+        "node": {
+            "properties": {"kids": {"elements": {"ref": "node"}}, "at": {"type": "timestamp"}},
+            "optionalProperties": {
+                "tag": {"enum": ["a", "b"], "metadata": {"default": "a"}},
+                "old": {"type": "string", "metadata": {"deprecated": true}},
+                "sizes": {"values": {"type": "uint8"}}
+            },
+            "metadata": {"examples": [{"kids": [], "at": "2020-01-01T00:00:00Z"}]}
+        },
+        "event": {
+            "discriminator": "kind",
+            "mapping": {"x": {"properties": {"n": {"type": "float64", "nullable": true}}}}
+        }
+    },
+    "properties": {"root": {"ref": "node"}, "events": {"elements": {"ref": "event"}}},
+    "optionalProperties": {"flag": {"type": "boolean"}},
+    "metadata": {"examples": [{"root": {"kids": [], "at": "2020-01-01T00:00:00Z"}, "events": []}]}
+}"#;
+
+/// The generated modules pass `mypy --strict`, checked over every eighth
+/// suite schema and [`MYPY_SCHEMA`] with each extra turned on. Skipped
+/// without mypy.
+#[test]
+fn test_py_mypy_strict() {
+    let found = Command::new("python3")
+        .args(["-m", "mypy", "--version"])
+        .output()
+        .is_ok_and(|out| out.status.success());
+    if !found {
+        eprintln!("SKIP: mypy not found, skipping Python type check");
+        return;
+    }
+
+    let everything = EmitOptions {
+        is_valid: true,
+        self_test: true,
+        deprecation_warnings: true,
+        validate_at: true,
+        suggestions: true,
+        coerce: true,
+        strip_additional: true,
+        apply_defaults: true,
+        mock: true,
+        shape: true,
+        enum_constants: true,
+        ..EmitOptions::default()
+    };
+    let option_sets = [
+        EmitOptions::default(),
+        everything.clone(),
+        EmitOptions {
+            messages: true,
+            max_errors: Some(2),
+            path_segments: true,
+            ..everything.clone()
+        },
+        EmitOptions {
+            fail_fast: true,
+            py_numbers: PyNumbers::Strict,
+            ..everything
+        },
+    ];
+
+    let suite = load_suite();
+    let mut schemas: Vec<Value> = suite
+        .values()
+        .step_by(8)
+        .map(|case| case["schema"].clone())
+        .collect();
+    schemas.push(serde_json::from_str(MYPY_SCHEMA).unwrap());
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut files = Vec::new();
+    for (i, schema) in schemas.iter().enumerate() {
+        let Ok(compiled) = jtd_codegen::compiler::compile(schema) else {
+            continue;
+        };
+        for (j, opts) in option_sets.iter().enumerate() {
+            let path = dir.path().join(format!("validator_{i}_{j}.py"));
+            std::fs::write(
+                &path,
+                jtd_codegen::emit_py::emit_with_options(&compiled, opts),
+            )
+            .unwrap();
+            files.push(path);
+        }
+    }
+
+    let output = Command::new("python3")
+        .args(["-m", "mypy", "--strict", "--no-incremental", "--cache-dir"])
+        .arg(dir.path().join(".mypy_cache"))
+        .args(&files)
+        .output()
+        .expect("run mypy");
+    eprintln!(
+        "=== mypy --strict over {} generated modules ===",
+        files.len()
+    );
+    assert!(
+        output.status.success(),
+        "mypy --strict failed:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
 
+fn run_suite(label: &str, opts: &EmitOptions) {
     // Check for python3
     match Command::new("python3").arg("--version").output() {
         Ok(out) if out.status.success() => {
@@ -127,11 +515,13 @@ fn test_py_validation_suite() {
             }
         };
 
-        let py_code = jtd_codegen::emit_py::emit(&compiled);
+        let py_code = jtd_codegen::emit_py::emit_with_options(&compiled, opts);
 
         let mut entry = serde_json::Map::new();
         entry.insert("code".into(), Value::String(py_code));
         entry.insert("instance".into(), instance.clone());
+        entry.insert("messages".into(), Value::Bool(opts.messages));
+        entry.insert("is_valid".into(), Value::Bool(opts.is_valid));
         test_data.insert(name.clone(), Value::Object(entry));
         expected_map.insert(name.clone(), expected);
     }
@@ -198,7 +588,7 @@ fn test_py_validation_suite() {
         let actual_json = serde_json::to_string(result).unwrap();
         let actual = parse_py_output(&actual_json);
 
-        if actual == *expected {
+        if errors_match(&actual, expected, error_limit(opts)) {
             passed += 1;
         } else {
             failed += 1;
@@ -208,7 +598,7 @@ fn test_py_validation_suite() {
         }
     }
 
-    eprintln!("=== JTD Validation Suite (Python) ===");
+    eprintln!("=== JTD Validation Suite (Python, {label}) ===");
     eprintln!("Passed:  {passed}");
     eprintln!("Failed:  {failed}");
     eprintln!("Skipped: {skipped}");
@@ -216,5 +606,5 @@ fn test_py_validation_suite() {
         eprintln!("{f}");
     }
 
-    assert_eq!(failed, 0, "{failed} Python test cases failed");
+    assert_eq!(failed, 0, "{failed} Python {label} test cases failed");
 }
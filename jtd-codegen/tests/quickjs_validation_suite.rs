@@ -1,6 +1,9 @@
 #![cfg(not(windows))]
 /// Integration test: generates JavaScript from each test case in the official
 /// JTD validation suite and evaluates it with embedded QuickJS (no node/bun).
+use jtd_codegen::ast::{CompiledSchema, Node};
+use jtd_codegen::emit_js::definition_entry_names;
+use jtd_codegen::options::{EmitOptions, JsModuleFormat, JsRuntime};
 use quickjs_rs::Context;
 use serde_json::Value;
 use std::collections::BTreeSet;
@@ -35,7 +38,21 @@ fn load_suite() -> serde_json::Map<String, Value> {
     });
 
     let v: Value = serde_json::from_str(&data).expect("parse validation.json");
-    v.as_object().unwrap().clone()
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
 }
 
 fn segments_to_pointer(segments: &[Value]) -> String {
@@ -44,7 +61,10 @@ fn segments_to_pointer(segments: &[Value]) -> String {
     }
     segments
         .iter()
-        .map(|s| format!("/{}", s.as_str().unwrap()))
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
         .collect::<Vec<_>>()
         .join("")
 }
@@ -60,17 +80,501 @@ fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
         .collect()
 }
 
-fn parse_quickjs_output(stdout: &str) -> BTreeSet<(String, String)> {
-    let arr: Vec<Vec<String>> = serde_json::from_str(stdout).expect("parse quickjs output");
+/// A path as the validator returned it: a JSON Pointer, or with
+/// `--path-segments` an array of segments.
+fn path_to_pointer(path: &Value) -> String {
+    match path {
+        Value::String(p) => p.clone(),
+        Value::Array(segments) => segments_to_pointer(segments),
+        other => panic!("unexpected error path {other}"),
+    }
+}
+
+fn parse_quickjs_output(stdout: &str) -> Vec<(String, String)> {
+    let arr: Vec<Vec<Value>> = serde_json::from_str(stdout).expect("parse quickjs output");
     arr.into_iter()
-        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .map(|pair| (path_to_pointer(&pair[0]), path_to_pointer(&pair[1])))
         .collect()
 }
 
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+/// All the expected errors, or with a `limit` (1 when failing fast) that
+/// many of them when there are more.
+fn errors_match(
+    actual: &[(String, String)],
+    expected: &BTreeSet<(String, String)>,
+    limit: Option<usize>,
+) -> bool {
+    if let Some(limit) = limit {
+        actual.len() == expected.len().min(limit) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<BTreeSet<_>>() == *expected
+    }
+}
+
 #[test]
 fn test_quickjs_validation_suite() {
     eprintln!("INFO: test_quickjs_validation_suite");
+    run_suite("default", &EmitOptions::default(), "validate");
+}
+
+/// The browser preset wraps the module in an IIFE exposing a global.
+#[test]
+fn test_quickjs_validation_suite_browser_runtime() {
+    let opts = EmitOptions {
+        js_runtime: JsRuntime::Browser,
+        ..EmitOptions::default()
+    };
+    run_suite("browser", &opts, "globalThis.JtdValidator.validate");
+}
+
+/// Bare output declares the functions and exports nothing.
+#[test]
+fn test_quickjs_validation_suite_bare_format() {
+    let opts = EmitOptions {
+        js_module_format: Some(JsModuleFormat::Bare),
+        ..EmitOptions::default()
+    };
+    run_suite("bare", &opts, "validate");
+}
+
+/// A definition's exported entry point reports what `validate` does when
+/// the root is a ref to that definition.
+#[test]
+fn test_quickjs_validation_suite_export_definitions() {
+    let opts = EmitOptions {
+        js_export_definitions: true,
+        inline_threshold: usize::MAX,
+        ..EmitOptions::default()
+    };
+    let emit = |schema: &CompiledSchema| {
+        let code = jtd_codegen::emit_js::emit_with_options(schema, &opts);
+        let entry = match &schema.root {
+            Node::Ref { name } => {
+                let names =
+                    definition_entry_names(schema.definitions.keys(), &["validate", "validateAt"]);
+                names.into_iter().find(|(_, def)| def == name).unwrap().0
+            }
+            _ => "validate".to_string(),
+        };
+        format!("{code}\nconst entry = {entry};\n")
+    };
+    run_suite_with("export definitions", emit, "entry", None);
+}
+
+/// `isValid` agrees with `validate` on every case; a disagreement shows
+/// up as an error no case expects.
+const IS_VALID_ENTRY: &str = "((v) => isValid(v) === (validate(v).length === 0) ? validate(v) : [{instancePath: \"isValid disagrees\", schemaPath: \"\"}])";
+
+#[test]
+fn test_quickjs_validation_suite_is_valid() {
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("isValid", &opts, IS_VALID_ENTRY);
+    // Shared subschemas and inlined definitions are checked in place
+    let opts = EmitOptions {
+        is_valid: true,
+        dedup: true,
+        inline_threshold: 1,
+        ..EmitOptions::default()
+    };
+    run_suite("isValid dedup", &opts, IS_VALID_ENTRY);
+}
+
+/// Minified modules report what the readable ones do, with recursive
+/// definitions, shared subschemas and `isValid` renamed alike.
+#[test]
+fn test_quickjs_validation_suite_minify() {
+    let opts = EmitOptions {
+        js_minify: true,
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    run_suite("minify", &opts, "validate");
+    run_suite("minify validateAt", &opts, "((v) => validateAt(\"\", v))");
+    let opts = EmitOptions {
+        js_minify: true,
+        is_valid: true,
+        dedup: true,
+        inline_threshold: 1,
+        ..EmitOptions::default()
+    };
+    run_suite("minify isValid dedup", &opts, IS_VALID_ENTRY);
+    let opts = EmitOptions {
+        js_minify: true,
+        js_module_format: Some(JsModuleFormat::Iife),
+        ..EmitOptions::default()
+    };
+    run_suite("minify iife", &opts, "globalThis.JtdValidator.validate");
+}
+
+/// The SAX validator, fed the events of walking the parsed instance.
+#[test]
+fn test_quickjs_validation_suite_sax() {
+    let opts = EmitOptions {
+        js_sax: true,
+        ..EmitOptions::default()
+    };
+    let entry = "(function (v) {
+        const s = createSaxValidator();
+        (function walk(x) {
+            if (Array.isArray(x)) { s.openArray(); x.forEach((y) => walk(y)); s.closeArray(); }
+            else if (x !== null && typeof x === \"object\") {
+                s.openObject();
+                for (const k of Object.keys(x)) { s.key(k); walk(x[k]); }
+                s.closeObject();
+            } else s.value(x);
+        })(v);
+        return s.end();
+    })";
+    run_suite("sax", &opts, entry);
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_quickjs_validation_suite_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    run_suite("fail-fast", &opts, "validate");
+    run_suite(
+        "fail-fast validateAt",
+        &opts,
+        "((v) => validateAt(\"\", v))",
+    );
+}
+
+/// The TypeScript target, run as the JavaScript left by removing its
+/// annotations (see `strip_types`).
+#[test]
+fn test_quickjs_validation_suite_typescript() {
+    let emit = |schema: &CompiledSchema| strip_types(&jtd_codegen::emit_ts::emit(schema));
+    run_suite_with("typescript", emit, "validate", None);
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    let emit = |schema: &CompiledSchema| {
+        strip_types(&jtd_codegen::emit_ts::emit_with_options(schema, &opts))
+    };
+    run_suite_with("typescript isValid", emit, IS_VALID_ENTRY, None);
+}
+
+#[test]
+fn test_quickjs_validation_suite_typescript_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    };
+    let emit = |schema: &CompiledSchema| {
+        strip_types(&jtd_codegen::emit_ts::emit_with_options(schema, &opts))
+    };
+    run_suite_with("typescript fail-fast", emit, "validate", error_limit(&opts));
+}
+
+/// Validators with an error limit return no more errors than that, or
+/// than the limit they are called with.
+#[test]
+fn test_quickjs_validation_suite_max_errors() {
+    let opts = EmitOptions {
+        max_errors: Some(2),
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    run_suite("max-errors", &opts, "validate");
+    run_suite(
+        "max-errors validateAt",
+        &opts,
+        "((v) => validateAt(\"\", v))",
+    );
+    let emit = |schema: &CompiledSchema| jtd_codegen::emit_js::emit_with_options(schema, &opts);
+    run_suite_with("max-errors 1", emit, "((v) => validate(v, 1))", Some(1));
+    let emit = |schema: &CompiledSchema| {
+        strip_types(&jtd_codegen::emit_ts::emit_with_options(schema, &opts))
+    };
+    run_suite_with("typescript max-errors", emit, "validate", Some(2));
+}
+
+/// Errors with messages have the same paths, and every one has a message.
+#[test]
+fn test_quickjs_validation_suite_messages() {
+    let opts = EmitOptions {
+        messages: true,
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    let check = "((errors) => errors.map((e) => {
+        if (typeof e.message !== \"string\" || e.message === \"\") throw new Error(\"no message\");
+        return e;
+    }))";
+    run_suite("messages", &opts, &format!("((v) => {check}(validate(v)))"));
+    run_suite(
+        "messages validateAt",
+        &opts,
+        &format!("((v) => {check}(validateAt(\"\", v)))"),
+    );
+}
+
+/// With `--path-segments` the paths come back as arrays, from `validate`,
+/// `validateAt` and the SAX validator alike.
+#[test]
+fn test_quickjs_validation_suite_path_segments() {
+    let opts = EmitOptions {
+        path_segments: true,
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    run_suite("path-segments", &opts, "validate");
+    run_suite(
+        "path-segments validateAt",
+        &opts,
+        "((v) => validateAt(\"\", v))",
+    );
+    let opts = EmitOptions {
+        path_segments: true,
+        js_sax: true,
+        ..EmitOptions::default()
+    };
+    let entry = "(function (v) {
+        const s = createSaxValidator();
+        (function walk(x) {
+            if (Array.isArray(x)) { s.openArray(); x.forEach((y) => walk(y)); s.closeArray(); }
+            else if (x !== null && typeof x === \"object\") {
+                s.openObject();
+                for (const k of Object.keys(x)) { s.key(k); walk(x[k]); }
+                s.closeObject();
+            } else s.value(x);
+        })(v);
+        return s.end();
+    })";
+    run_suite("path-segments sax", &opts, entry);
+}
+
+/// The messages themselves, for each kind of error.
+#[test]
+fn test_quickjs_messages() {
+    let schema = jtd_codegen::compiler::compile(&serde_json::json!({
+        "definitions": {"pet": {
+            "discriminator": "kind",
+            "mapping": {"cat": {"properties": {"lives": {"type": "uint8"}}}}
+        }},
+        "properties": {
+            "name": {"type": "string"},
+            "size": {"enum": ["S", "M"]},
+            "tags": {"elements": {"type": "string"}},
+            "pets": {"values": {"ref": "pet"}}
+        }
+    }))
+    .unwrap();
+    let opts = EmitOptions {
+        messages: true,
+        ..EmitOptions::default()
+    };
+    let code = jtd_codegen::emit_js::emit_with_options(&schema, &opts)
+        .replace("export function", "function");
+    let ctx = Context::new().expect("create quickjs context");
+    ctx.eval(&code).expect("eval validator");
+    let messages = |instance: &str| -> Vec<String> {
+        let out: String = ctx
+            .eval_as(&format!(
+                "JSON.stringify(validate({instance}).map((e) => e.message))"
+            ))
+            .expect("run validator");
+        serde_json::from_str(&out).unwrap()
+    };
+    assert_eq!(messages("[]"), vec!["expected object, got array"]);
+    assert_eq!(
+        messages(r#"{"size": "XL", "tags": [1.5], "pets": 3, "x": 1}"#),
+        vec![
+            "missing required property 'name'",
+            "expected object, got 3",
+            "expected one of 'S', 'M', got string",
+            "expected string, got 1.5",
+            "unknown property 'x'",
+        ]
+    );
+    assert_eq!(
+        messages(
+            r#"{"name": "a", "size": "S", "tags": null, "pets": {"a": {}, "b": {"kind": 1}, "c": {"kind": "dog"}, "d": {"kind": "cat", "lives": 300}}}"#
+        ),
+        vec![
+            "missing discriminator 'kind'",
+            "discriminator 'kind' must be a string, got 1",
+            "unknown discriminator 'kind' value 'dog'",
+            "expected uint8, got 300",
+            "expected array, got null",
+        ]
+    );
+}
+
+/// Timestamp checks and repeated enum sets go through shared helpers, and
+/// still check the same things.
+#[test]
+fn test_quickjs_hoisted_helpers() {
+    let schema = jtd_codegen::compiler::compile(&serde_json::json!({
+        "definitions": {"size": {"enum": ["S", "M"]}},
+        "properties": {
+            "created": {"type": "timestamp"},
+            "updated": {"type": "timestamp"},
+            "sizes": {"elements": {"enum": ["M", "S"]}},
+            "size": {"ref": "size"},
+            "fit": {"enum": ["S", "M"]}
+        }
+    }))
+    .unwrap();
+    let code = jtd_codegen::emit_js::emit(&schema).replace("export function", "function");
+    assert_eq!(code.matches("function isRfc3339(s)").count(), 1);
+    assert_eq!(code.matches("\"S\"").count(), 1);
+    let ctx = Context::new().expect("create quickjs context");
+    ctx.eval(&code).expect("eval validator");
+    let schema_paths = |instance: &str| -> Vec<String> {
+        let out: String = ctx
+            .eval_as(&format!(
+                "JSON.stringify(validate({instance}).map((e) => e.schemaPath))"
+            ))
+            .expect("run validator");
+        serde_json::from_str(&out).unwrap()
+    };
+    let ok = r#"{"created": "2024-02-29T12:00:00Z", "updated": "1990-12-31T23:59:60Z", "sizes": ["S", "M"], "size": "M", "fit": "S"}"#;
+    assert!(schema_paths(ok).is_empty());
+    let mut paths = schema_paths(
+        r#"{"created": "yesterday", "updated": 1, "sizes": ["L", 2], "size": "has", "fit": "constructor"}"#,
+    );
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            "/definitions/size/enum",
+            "/properties/created/type",
+            "/properties/fit/enum",
+            "/properties/sizes/elements/enum",
+            "/properties/sizes/elements/enum",
+            "/properties/updated/type",
+        ]
+    );
+}
+
+/// With `--dedup` the suite still passes, collecting every error or
+/// stopping at the limit.
+#[test]
+fn test_quickjs_validation_suite_dedup() {
+    let opts = EmitOptions {
+        dedup: true,
+        validate_at: true,
+        ..EmitOptions::default()
+    };
+    run_suite("dedup", &opts, "validate");
+    run_suite("dedup validateAt", &opts, "((v) => validateAt(\"\", v))");
+    let opts = EmitOptions {
+        dedup: true,
+        max_errors: Some(1),
+        ..EmitOptions::default()
+    };
+    run_suite("dedup max-errors", &opts, "validate");
+}
+
+/// With `--inline-threshold` the suite still passes, whether definitions
+/// referenced once or all non-recursive ones are checked in place.
+#[test]
+fn test_quickjs_validation_suite_inline_threshold() {
+    for inline_threshold in [1, usize::MAX] {
+        let opts = EmitOptions {
+            inline_threshold,
+            validate_at: true,
+            ..EmitOptions::default()
+        };
+        run_suite("inline", &opts, "validate");
+        run_suite("inline validateAt", &opts, "((v) => validateAt(\"\", v))");
+        let opts = EmitOptions {
+            inline_threshold,
+            fail_fast: true,
+            ..EmitOptions::default()
+        };
+        run_suite("inline fail-fast", &opts, "validate");
+    }
+}
+
+/// A repeated subschema is checked by one function, which reports each
+/// use at its own paths.
+#[test]
+fn test_quickjs_dedup_shapes() {
+    let schema = jtd_codegen::compiler::compile(&serde_json::json!({
+        "properties": {
+            "home": {"properties": {"zip": {"type": "string"}}},
+            "work": {"properties": {"zip": {"type": "string"}}}
+        }
+    }))
+    .unwrap();
+    let opts = EmitOptions {
+        dedup: true,
+        ..EmitOptions::default()
+    };
+    let code = jtd_codegen::emit_js::emit_with_options(&schema, &opts)
+        .replace("export function", "function");
+    assert_eq!(code.matches("function shape_").count(), 1);
+    assert_eq!(code.matches("\"/zip\"").count(), 1);
+    let ctx = Context::new().expect("create quickjs context");
+    ctx.eval(&code).expect("eval validator");
+    let out: String = ctx
+        .eval_as(r#"JSON.stringify(validate({"home": {"zip": 1}, "work": {}}))"#)
+        .expect("run validator");
+    let errors: Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        errors,
+        serde_json::json!([
+            {"instancePath": "/home/zip", "schemaPath": "/properties/home/properties/zip/type"},
+            {"instancePath": "/work", "schemaPath": "/properties/work/properties/zip"}
+        ])
+    );
+}
+
+/// The TypeScript module as JavaScript. It annotates only the error
+/// interface and the function signatures and locals below, so exactly
+/// those are removed.
+fn strip_types(ts: &str) -> String {
+    let start = ts
+        .find("export interface ValidationError {")
+        .expect("interface");
+    let end = start + ts[start..].find("}\n").expect("interface end") + 2;
+    let js = format!("{}{}", &ts[..start], &ts[end..]);
+    js.replace("maxErrors: number", "maxErrors")
+        .replace(
+            "(v: any, e: ValidationError[], p: string, sp: string",
+            "(v, e, p, sp",
+        )
+        .replace("): void", ")")
+        .replace("(v: any): boolean", "(v)")
+        .replace("): boolean", ")")
+        .replace("(instance: unknown", "(instance")
+        .replace("(s: unknown)", "(s)")
+        .replace("): ValidationError[]", ")")
+        .replace("const e: ValidationError[] = [];", "const e = [];")
+        .replace("const v: any = instance;", "const v = instance;")
+}
+
+fn run_suite(label: &str, opts: &EmitOptions, entry: &str) {
+    let emit = |schema: &CompiledSchema| jtd_codegen::emit_js::emit_with_options(schema, opts);
+    run_suite_with(label, emit, entry, error_limit(opts));
+}
 
+fn run_suite_with(
+    label: &str,
+    emit: impl Fn(&CompiledSchema) -> String,
+    entry: &str,
+    limit: Option<usize>,
+) {
     let suite = load_suite();
     let mut passed = 0u32;
     let mut failed = 0u32;
@@ -90,8 +594,7 @@ fn test_quickjs_validation_suite() {
             }
         };
 
-        let js_code = jtd_codegen::emit_js::emit(&compiled);
-        let code = js_code.replace("export function validate", "function validate");
+        let code = emit(&compiled).replace("export function", "function");
 
         let instance_json = serde_json::to_string(instance).unwrap();
         let instance_json_js_str = serde_json::to_string(&instance_json).unwrap();
@@ -104,7 +607,7 @@ fn test_quickjs_validation_suite() {
         }
 
         let run_expr = format!(
-            "JSON.stringify(validate(JSON.parse({instance_json_js_str})).map(e => [e.instancePath, e.schemaPath]))"
+            "JSON.stringify({entry}(JSON.parse({instance_json_js_str})).map(e => [e.instancePath, e.schemaPath]))"
         );
 
         let out: String = match ctx.eval_as(&run_expr) {
@@ -119,7 +622,7 @@ fn test_quickjs_validation_suite() {
         };
 
         let actual = parse_quickjs_output(&out);
-        if actual == expected {
+        if errors_match(&actual, &expected, limit) {
             passed += 1;
         } else {
             failed += 1;
@@ -129,7 +632,7 @@ fn test_quickjs_validation_suite() {
         }
     }
 
-    eprintln!("=== JTD Validation Suite (QuickJS) ===");
+    eprintln!("=== JTD Validation Suite (QuickJS, {label}) ===");
     eprintln!("Passed:  {passed}");
     eprintln!("Failed:  {failed}");
     eprintln!("Skipped: {skipped}");
@@ -1,6 +1,7 @@
 /// Integration test: generates Rust from each test case in the official
 /// JTD validation suite, writes a single combined Rust test binary,
 /// compiles it once, and runs all 316 test cases.
+use jtd_codegen::options::{EmitOptions, RsBackend};
 use serde_json::Value;
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
@@ -35,7 +36,21 @@ fn load_suite() -> serde_json::Map<String, Value> {
     });
 
     let v: Value = serde_json::from_str(&data).expect("parse validation.json");
-    v.as_object().unwrap().clone()
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
 }
 
 fn segments_to_pointer(segments: &[Value]) -> String {
@@ -44,7 +59,10 @@ fn segments_to_pointer(segments: &[Value]) -> String {
     }
     segments
         .iter()
-        .map(|s| format!("/{}", s.as_str().unwrap()))
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
         .collect::<Vec<_>>()
         .join("")
 }
@@ -60,6 +78,9 @@ fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
         .collect()
 }
 
+/// (module name, instance JSON, expected errors) for one suite case.
+type TestEntry = (String, String, BTreeSet<(String, String)>);
+
 /// Sanitize a test name into a valid Rust identifier.
 fn sanitize_name(name: &str) -> String {
     name.chars()
@@ -69,14 +90,181 @@ fn sanitize_name(name: &str) -> String {
 
 #[test]
 fn test_rs_validation_suite() {
+    run_suite(&EmitOptions::default());
+}
+
+/// Same suite against `simd_json::BorrowedValue`.
+#[test]
+fn test_rs_validation_suite_simd_json() {
+    run_suite(&EmitOptions {
+        rs_backend: RsBackend::SimdJson,
+        ..EmitOptions::default()
+    });
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_rs_validation_suite_fail_fast() {
+    run_suite(&EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    });
+}
+
+/// Validators with an error limit return no more errors than that.
+#[test]
+fn test_rs_validation_suite_max_errors() {
+    run_suite(&EmitOptions {
+        max_errors: Some(2),
+        ..EmitOptions::default()
+    });
+}
+
+/// Errors with messages have the same paths, and every one has a message.
+#[test]
+fn test_rs_validation_suite_messages() {
+    run_suite(&EmitOptions {
+        messages: true,
+        ..EmitOptions::default()
+    });
+    run_suite(&EmitOptions {
+        messages: true,
+        rs_backend: RsBackend::SimdJson,
+        ..EmitOptions::default()
+    });
+}
+
+/// `is_valid` agrees with `validate` on every case, on both backends.
+#[test]
+fn test_rs_validation_suite_is_valid() {
+    run_suite(&EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    });
+    run_suite(&EmitOptions {
+        is_valid: true,
+        rs_backend: RsBackend::SimdJson,
+        ..EmitOptions::default()
+    });
+}
+
+/// `#![no_std]` validators behave the same, and every suite schema's
+/// builds in a `#![no_std]` library against serde_json without std.
+#[test]
+fn test_rs_validation_suite_no_std() {
+    let opts = EmitOptions {
+        rs_no_std: true,
+        ..EmitOptions::default()
+    };
+    run_suite(&opts);
+
+    let mut src = String::from("#![no_std]\n\n");
+    for (name, case) in &load_suite() {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            continue;
+        };
+        src.push_str(&format!("pub mod test_{} {{\n", sanitize_name(name)));
+        src.push_str(&jtd_codegen::emit_rs::emit_with_options(&compiled, &opts));
+        src.push_str("}\n\n");
+    }
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let proj_dir = tmp_dir.path();
+    std::fs::write(
+        proj_dir.join("Cargo.toml"),
+        r#"[package]
+name = "rs-no-std-test"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde_json = { version = "1", default-features = false, features = ["alloc"] }
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(proj_dir.join("src")).unwrap();
+    std::fs::write(proj_dir.join("src/lib.rs"), &src).unwrap();
+    let build = Command::new("cargo")
+        .args(["build", "--release"])
+        .env("RUSTFLAGS", "-Awarnings")
+        .current_dir(proj_dir)
+        .output()
+        .expect("cargo build");
+    assert!(
+        build.status.success(),
+        "no_std validators failed to build:\n{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+}
+
+/// `--rs-tests`: the smoke tests generated for every suite schema pass
+/// under `cargo test`, so the validators agree with the interpreter on
+/// each synthesized instance.
+#[test]
+fn test_rs_smoke_tests() {
+    let opts = EmitOptions {
+        rs_tests: true,
+        ..EmitOptions::default()
+    };
+    let mut src = String::new();
+    for (name, case) in &load_suite() {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            continue;
+        };
+        src.push_str(&format!("pub mod test_{} {{\n", sanitize_name(name)));
+        src.push_str(&jtd_codegen::emit_rs::emit_with_options(&compiled, &opts));
+        src.push_str("}\n\n");
+    }
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let proj_dir = tmp_dir.path();
+    std::fs::write(
+        proj_dir.join("Cargo.toml"),
+        r#"[package]
+name = "rs-smoke-test"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde_json = "1"
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(proj_dir.join("src")).unwrap();
+    std::fs::write(proj_dir.join("src/lib.rs"), &src).unwrap();
+    let test = Command::new("cargo")
+        .args(["test", "--release", "--lib"])
+        .env("RUSTFLAGS", "-Awarnings")
+        .current_dir(proj_dir)
+        .output()
+        .expect("cargo test");
+    assert!(
+        test.status.success(),
+        "generated smoke tests failed:\n{}\n{}",
+        String::from_utf8_lossy(&test.stdout),
+        String::from_utf8_lossy(&test.stderr)
+    );
+}
+
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+fn run_suite(opts: &EmitOptions) {
     let suite = load_suite();
+    let backend = opts.rs_backend;
 
     // Build a single Rust source file with all validators + a main that runs them
     let mut src = String::new();
-    src.push_str("use serde_json::Value;\n\n");
+    if backend == RsBackend::SerdeJson {
+        src.push_str("use serde_json::Value;\n\n");
+    }
 
     // Collect test cases as (mod_name, instance_json, expected_errors)
-    let mut test_entries: Vec<(String, String, BTreeSet<(String, String)>)> = Vec::new();
+    let mut test_entries: Vec<TestEntry> = Vec::new();
 
     for (name, case) in &suite {
         let schema = &case["schema"];
@@ -88,7 +276,7 @@ fn test_rs_validation_suite() {
             Err(_) => continue,
         };
 
-        let rs_code = jtd_codegen::emit_rs::emit(&compiled);
+        let rs_code = jtd_codegen::emit_rs::emit_with_options(&compiled, opts);
         let mod_name = format!("test_{}", sanitize_name(name));
 
         src.push_str(&format!("mod {mod_name} {{\n"));
@@ -111,33 +299,55 @@ fn test_rs_validation_suite() {
     for (mod_name, instance_json, expected) in &test_entries {
         let expected_str: Vec<String> = expected
             .iter()
-            .map(|(ip, sp)| format!("(\"{ip}\".to_string(), \"{sp}\".to_string())"))
+            .map(|(ip, sp)| format!("({ip:?}.to_string(), {sp:?}.to_string())"))
             .collect();
         let expected_set = expected_str.join(", ");
 
-        src.push_str(&format!("  {{\n"));
-        src.push_str(&format!(
-            "    let instance: Value = serde_json::from_str(r#\"{}\"#).unwrap();\n",
-            instance_json
-        ));
-        src.push_str(&format!(
-            "    let errors = {mod_name}::validate(&instance);\n"
-        ));
-        src.push_str(&format!(
-            "    let actual: std::collections::BTreeSet<(String, String)> = errors.into_iter().collect();\n"
-        ));
+        src.push_str("  {\n");
+        match backend {
+            RsBackend::SerdeJson => src.push_str(&format!(
+                "    let instance: Value = serde_json::from_str({:?}).unwrap();\n",
+                instance_json
+            )),
+            RsBackend::SimdJson => src.push_str(&format!(
+                "    let mut buf = {:?}.as_bytes().to_vec();\n    let instance = simd_json::to_borrowed_value(&mut buf).unwrap();\n",
+                instance_json
+            )),
+        }
+        if opts.messages {
+            src.push_str(&format!(
+                "    let errors: Vec<(String, String)> = {mod_name}::validate(&instance).into_iter().map(|e| {{ assert!(!e.message.is_empty()); (e.instance_path, e.schema_path) }}).collect();\n"
+            ));
+        } else {
+            src.push_str(&format!(
+                "    let errors: Vec<(String, String)> = {mod_name}::validate(&instance).into_iter().map({mod_name}::ValidationError::into_tuple).collect();\n"
+            ));
+        }
         src.push_str(&format!(
             "    let expected: std::collections::BTreeSet<(String, String)> = [{expected_set}].into_iter().collect();\n"
         ));
-        src.push_str(&format!("    if actual == expected {{\n"));
-        src.push_str(&format!("      passed += 1;\n"));
-        src.push_str(&format!("    }} else {{\n"));
-        src.push_str(&format!("      failed += 1;\n"));
+        if let Some(limit) = error_limit(opts) {
+            // As many of the expected errors as the limit allows
+            src.push_str(&format!("    let ok = errors.len() == expected.len().min({limit}) && errors.iter().all(|e| expected.contains(e));\n"));
+            src.push_str("    let actual = errors;\n");
+        } else {
+            src.push_str("    let actual: std::collections::BTreeSet<(String, String)> = errors.into_iter().collect();\n");
+            src.push_str("    let ok = actual == expected;\n");
+        }
+        if opts.is_valid {
+            src.push_str(&format!(
+                "    let ok = ok && {mod_name}::is_valid(&instance) == expected.is_empty();\n"
+            ));
+        }
+        src.push_str("    if ok {\n");
+        src.push_str("      passed += 1;\n");
+        src.push_str("    } else {\n");
+        src.push_str("      failed += 1;\n");
         src.push_str(&format!(
             "      failures.push(format!(\"FAIL: {mod_name}\\n  expected: {{:?}}\\n  actual:   {{:?}}\", expected, actual));\n"
         ));
-        src.push_str(&format!("    }}\n"));
-        src.push_str(&format!("  }}\n\n"));
+        src.push_str("    }\n");
+        src.push_str("  }\n\n");
     }
 
     src.push_str("  eprintln!(\"=== Rust Validation Suite ===\");\n");
@@ -147,24 +357,122 @@ fn test_rs_validation_suite() {
     src.push_str("  assert_eq!(failed, 0, \"{} tests failed\", failed);\n");
     src.push_str("}\n");
 
+    let extra_deps = match backend {
+        RsBackend::SimdJson => "simd-json = \"0.14\"\n",
+        RsBackend::SerdeJson => "",
+    };
+    cargo_run(&src, extra_deps);
+}
+
+/// `--rs-types`: the types for every suite schema compile, and every
+/// instance the suite calls valid deserializes into `Root`, except where
+/// serde is stricter than JTD (integers written with a fraction).
+#[test]
+fn test_rs_types_suite() {
+    let suite = load_suite();
+    let mut src = String::new();
+    let mut main = String::from("fn main() {\n  let mut failed = 0u32;\n");
+    for (name, case) in &suite {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            continue;
+        };
+        let mod_name = format!("test_{}", sanitize_name(name));
+        src.push_str(&format!("#[allow(dead_code)]\nmod {mod_name} {{\n"));
+        for line in jtd_codegen::emit_rs::emit_types(&compiled).lines() {
+            src.push_str(&format!("  {line}\n"));
+        }
+        src.push_str("}\n\n");
+        let fractional_int = case["instance"].is_f64()
+            && matches!(
+                case["schema"]["type"].as_str(),
+                Some("int8" | "uint8" | "int16" | "uint16" | "int32" | "uint32")
+            );
+        if case["errors"].as_array().is_some_and(Vec::is_empty) && !fractional_int {
+            main.push_str(&format!(
+                "  if let Err(e) = serde_json::from_str::<{mod_name}::Root>({:?}) {{\n    \
+                 failed += 1;\n    eprintln!(\"FAIL: {mod_name}: {{e}}\");\n  }}\n",
+                case["instance"].to_string()
+            ));
+        }
+    }
+    main.push_str("  assert_eq!(failed, 0, \"{} instances failed to deserialize\", failed);\n}\n");
+    src.push_str(&main);
+    cargo_run(
+        &src,
+        "serde = { version = \"1\", features = [\"derive\"] }\n\
+         chrono = { version = \"0.4\", features = [\"serde\"] }\n",
+    );
+}
+
+/// `--rs-deserialize`: `deserialize_validated` and `Validated` accept
+/// exactly the instances the suite calls valid, and malformed JSON is one
+/// error at the root.
+#[test]
+fn test_rs_deserialize_suite() {
+    let opts = EmitOptions {
+        rs_deserialize: true,
+        ..EmitOptions::default()
+    };
+    let suite = load_suite();
+    let mut src = String::new();
+    let mut main = String::from("fn main() {\n  let mut failed = 0u32;\n");
+    let mut malformed_checked = false;
+    for (name, case) in &suite {
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            continue;
+        };
+        let mod_name = format!("test_{}", sanitize_name(name));
+        src.push_str(&format!("#[allow(dead_code)]\nmod {mod_name} {{\n"));
+        for line in jtd_codegen::emit_rs::emit_with_options(&compiled, &opts).lines() {
+            src.push_str(&format!("  {line}\n"));
+        }
+        src.push_str("}\n\n");
+        let valid = case["errors"].as_array().is_some_and(Vec::is_empty);
+        main.push_str(&format!(
+            "  let json = {:?};\n  \
+             if {mod_name}::deserialize_validated(json).is_ok() != {valid}\n    \
+             || serde_json::from_str::<{mod_name}::Validated>(json).is_ok() != {valid} {{\n    \
+             failed += 1;\n    eprintln!(\"FAIL: {mod_name}\");\n  }}\n",
+            case["instance"].to_string()
+        ));
+        if !malformed_checked {
+            malformed_checked = true;
+            main.push_str(&format!(
+                "  let errors = {mod_name}::deserialize_validated(\"{{\").unwrap_err();\n  \
+                 assert_eq!(errors.len(), 1);\n  \
+                 assert_eq!(errors[0].instance_path, \"\");\n  \
+                 assert_eq!(errors[0].schema_path, \"\");\n"
+            ));
+        }
+    }
+    main.push_str(
+        "  assert_eq!(failed, 0, \"{} instances were judged differently\", failed);\n}\n",
+    );
+    src.push_str(&main);
+    cargo_run(&src, "serde = \"1\"\n");
+}
+
+/// Build and run `src` as the main of a Cargo project depending on
+/// serde_json and `extra_deps`; panics when either fails.
+fn cargo_run(src: &str, extra_deps: &str) {
     // Write to a temp directory as a Cargo project
     let tmp_dir = tempfile::tempdir().expect("create temp dir");
     let proj_dir = tmp_dir.path();
 
     // Cargo.toml
-    let cargo_toml = r#"[package]
+    let mut cargo_toml = r#"[package]
 name = "rs-validation-test"
 version = "0.1.0"
 edition = "2021"
 
 [dependencies]
 serde_json = "1"
-regex = "1"
-chrono = "0.4"
-"#;
+"#
+    .to_string();
+    cargo_toml.push_str(extra_deps);
     std::fs::write(proj_dir.join("Cargo.toml"), cargo_toml).unwrap();
     std::fs::create_dir_all(proj_dir.join("src")).unwrap();
-    std::fs::write(proj_dir.join("src/main.rs"), &src).unwrap();
+    std::fs::write(proj_dir.join("src/main.rs"), src).unwrap();
 
     // Build
     let build = Command::new("cargo")
@@ -178,7 +486,7 @@ chrono = "0.4"
         let stderr = String::from_utf8_lossy(&build.stderr);
         // Save the source for debugging
         let debug_path = "/tmp/rs_validation_debug.rs";
-        std::fs::write(debug_path, &src).unwrap();
+        std::fs::write(debug_path, src).unwrap();
         panic!(
             "Generated Rust code failed to compile.\nSource saved to: {debug_path}\nErrors:\n{stderr}"
         );
@@ -0,0 +1,250 @@
+/// Integration test: generates PL/pgSQL from each test case in the official
+/// JTD validation suite, one function prefix per case, and runs them all in
+/// one psql session inside a transaction that is rolled back.
+///
+/// Needs `psql` on PATH and `JTD_POSTGRES_URL` naming a database the tests
+/// may create functions and tables in; skipped otherwise.
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+/// `(instancePath, schemaPath)` pairs.
+type Errors = BTreeSet<(String, String)>;
+
+fn default_suite_path() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
+}
+
+fn segments_to_pointer(segments: &[Value]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+fn normalize_errors(errors: &Value) -> Errors {
+    let arr = errors.as_array().expect("errors must be array");
+    arr.iter()
+        .map(|e| {
+            let ip = segments_to_pointer(e["instancePath"].as_array().unwrap());
+            let sp = segments_to_pointer(e["schemaPath"].as_array().unwrap());
+            (ip, sp)
+        })
+        .collect()
+}
+
+/// Run `script` with psql against `url`, returning its unaligned,
+/// tab-separated output.
+fn psql(url: &str, script: &str) -> Result<String, String> {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("script.sql");
+    std::fs::write(&file, script).unwrap();
+    let out = Command::new("psql")
+        .args([
+            "-X",
+            "-q",
+            "-A",
+            "-t",
+            "-v",
+            "ON_ERROR_STOP=1",
+            "-F",
+            "\t",
+            "-d",
+            url,
+            "-f",
+        ])
+        .arg(&file)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).into_owned());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn postgres_url() -> Option<String> {
+    let Ok(url) = std::env::var("JTD_POSTGRES_URL") else {
+        eprintln!("SKIP: JTD_POSTGRES_URL not set, skipping PostgreSQL validation suite");
+        return None;
+    };
+    if !Command::new("psql")
+        .arg("--version")
+        .output()
+        .is_ok_and(|out| out.status.success())
+    {
+        eprintln!("SKIP: psql not found, skipping PostgreSQL validation suite");
+        return None;
+    }
+    Some(url)
+}
+
+fn sql_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// `--sql-check` adds a constraint that rejects invalid rows.
+#[test]
+fn test_sql_check_constraint() {
+    let Some(url) = postgres_url() else {
+        return;
+    };
+    let schema = serde_json::json!({
+        "properties": {"id": {"type": "uint32"}, "at": {"type": "timestamp"}}
+    });
+    let opts = jtd_codegen::options::EmitOptions {
+        sql_prefix: Some("check_test".into()),
+        sql_check: Some("check_test_orders.doc".into()),
+        ..Default::default()
+    };
+    let compiled = jtd_codegen::compiler::compile(&schema).unwrap();
+    let sql = jtd_codegen::emit_sql::emit_with_options(&compiled, &opts);
+    let script = format!(
+        "BEGIN;\nCREATE TABLE check_test_orders (doc jsonb);\n{sql}\
+         INSERT INTO check_test_orders VALUES ('{{\"id\": 7, \"at\": \"2024-02-29T12:00:00Z\"}}');\n\
+         SELECT count(*) FROM check_test_orders;\n\
+         INSERT INTO check_test_orders VALUES ('{{\"id\": -1, \"at\": \"2023-02-29T12:00:00Z\"}}');\n\
+         ROLLBACK;\n"
+    );
+    let err = psql(&url, &script).unwrap_err();
+    assert!(err.contains("check_test_orders_doc_jtd"), "{err}");
+}
+
+/// All the expected errors, or with `fail_fast` exactly one of them when
+/// there are any.
+fn errors_match(actual: &[(String, String)], expected: &Errors, fail_fast: bool) -> bool {
+    if fail_fast {
+        actual.len() == expected.len().min(1) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<Errors>() == *expected
+    }
+}
+
+#[test]
+fn test_sql_validation_suite() {
+    run_suite("default", false);
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_sql_validation_suite_fail_fast() {
+    run_suite("fail-fast", true);
+}
+
+fn run_suite(label: &str, fail_fast: bool) {
+    let Some(url) = postgres_url() else {
+        return;
+    };
+
+    let suite = load_suite();
+    let mut script = String::from("BEGIN;\n");
+    let mut queries = String::new();
+    let mut expected: BTreeMap<String, (&String, Errors)> = BTreeMap::new();
+    let mut skipped = 0u32;
+    for (i, (name, case)) in suite.iter().enumerate() {
+        // PostgreSQL text and jsonb cannot hold NUL
+        if case.to_string().contains("\\u0000") {
+            skipped += 1;
+            continue;
+        }
+        let Ok(compiled) = jtd_codegen::compiler::compile(&case["schema"]) else {
+            skipped += 1;
+            continue;
+        };
+        let prefix = format!("case{i}");
+        let opts = jtd_codegen::options::EmitOptions {
+            sql_prefix: Some(prefix.clone()),
+            fail_fast,
+            ..Default::default()
+        };
+        script.push_str(&jtd_codegen::emit_sql::emit_with_options(&compiled, &opts));
+        // As JSON strings, since keys may hold tabs and newlines
+        queries.push_str(&format!(
+            "SELECT '{prefix}', to_json(instance_path), to_json(schema_path) \
+             FROM {prefix}_validate({}::jsonb);\n",
+            sql_literal(&case["instance"].to_string())
+        ));
+        expected.insert(prefix, (name, normalize_errors(&case["errors"])));
+    }
+    script.push_str(&queries);
+    script.push_str("ROLLBACK;\n");
+
+    let stdout = psql(&url, &script).unwrap_or_else(|e| panic!("psql failed:\n{e}"));
+    let mut actual: BTreeMap<&str, Vec<(String, String)>> = BTreeMap::new();
+    for line in stdout.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (case, ip, sp) = (parts.next().unwrap(), parts.next(), parts.next());
+        let text = |s: Option<&str>| -> String {
+            serde_json::from_str(s.unwrap_or("\"\"")).expect("psql printed a JSON string")
+        };
+        actual.entry(case).or_default().push((text(ip), text(sp)));
+    }
+
+    let mut failures = Vec::new();
+    for (prefix, (name, expected)) in &expected {
+        let actual = actual.remove(prefix.as_str()).unwrap_or_default();
+        if !errors_match(&actual, expected, fail_fast) {
+            failures.push(format!(
+                "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+            ));
+        }
+    }
+
+    eprintln!("=== JTD Validation Suite (PostgreSQL, {label}) ===");
+    eprintln!("Passed:  {}", expected.len() - failures.len());
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} PostgreSQL {label} test cases failed",
+        failures.len()
+    );
+}
@@ -0,0 +1,229 @@
+#![cfg(feature = "wasm")]
+/// Integration test: generates a WebAssembly module (`--target wasm`) from
+/// each test case in the official JTD validation suite and runs it in the
+/// wasmi interpreter, feeding the instance as a token buffer.
+use jtd_codegen::emit_wasm::{self, tape};
+use jtd_codegen::options::EmitOptions;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use wasmi::core::Pages;
+use wasmi::{Engine, Linker, Module, Store};
+
+const JSON_TYPEDEF_SPEC_COMMIT: &str = "71ca275847318717c36f5a2322a8061070fe185d";
+
+/// Error records the test leaves room for; the suite's cases have fewer.
+const MAX_ERRORS: u32 = 64;
+
+fn default_suite_path() -> PathBuf {
+    // jtd-codegen/ -> workspace root
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = manifest_dir
+        .parent()
+        .expect("jtd-codegen must have a workspace parent");
+    root.join(".tmp")
+        .join("json-typedef-spec")
+        .join(JSON_TYPEDEF_SPEC_COMMIT)
+        .join("tests")
+        .join("validation.json")
+}
+
+fn load_suite() -> serde_json::Map<String, Value> {
+    let suite_path = std::env::var("JTD_VALIDATION_JSON")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_suite_path());
+
+    let data = std::fs::read_to_string(&suite_path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read validation suite at {}: {}\n\nRun: xmake run fetch_suite\n\nOr set JTD_VALIDATION_JSON=...",
+            suite_path.display(),
+            e
+        )
+    });
+
+    let v: Value = serde_json::from_str(&data).expect("parse validation.json");
+    let mut suite = v.as_object().unwrap().clone();
+    // Keys with `/` and `~`, which the suite has none of
+    let extra: Value = serde_json::from_str(include_str!("pointer_escaping.json"))
+        .expect("parse pointer_escaping.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Keys with quotes, backslashes and the like, which must not break out
+    // of the generated string literals
+    let extra: Value =
+        serde_json::from_str(include_str!("hostile_keys.json")).expect("parse hostile_keys.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
+}
+
+fn segments_to_pointer(segments: &[Value]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let s = s.as_str().unwrap();
+            format!("/{}", s.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
+    let arr = errors.as_array().expect("errors must be array");
+    arr.iter()
+        .map(|e| {
+            let ip = segments_to_pointer(e["instancePath"].as_array().unwrap());
+            let sp = segments_to_pointer(e["schemaPath"].as_array().unwrap());
+            (ip, sp)
+        })
+        .collect()
+}
+
+/// Instantiate `wasm`, validate `value` and decode the errors. When the
+/// module exports `is_valid`, a disagreement with `validate` shows as an
+/// extra error.
+fn run(wasm: &[u8], value: &Value) -> Result<Vec<(String, String)>, wasmi::Error> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::<()>::new(&engine)
+        .instantiate(&mut store, &module)?
+        .start(&mut store)?;
+    let global = |name: &str| {
+        let global = instance.get_global(&store, name).expect("exported global");
+        global.get(&store).i32().expect("i32 global") as u32
+    };
+    let (heap_base, table) = (global("heap_base"), global("schema_paths"));
+
+    let errors = heap_base;
+    let tape = tape::encode(value, heap_base + MAX_ERRORS * 8);
+    let memory = instance.get_memory(&store, "memory").expect("memory");
+    let needed = tape.base as usize + tape.bytes.len();
+    let pages = (needed.saturating_sub(memory.data(&store).len())).div_ceil(65536);
+    memory.grow(&mut store, Pages::new(pages as u32).expect("page count"))?;
+    memory.write(&mut store, tape.base as usize, &tape.bytes)?;
+
+    let validate = instance.get_typed_func::<(i32, i32, i32), i32>(&store, "validate")?;
+    let count = validate.call(
+        &mut store,
+        (tape.base as i32, errors as i32, MAX_ERRORS as i32),
+    )?;
+    let mut decoded = tape::errors(
+        &tape,
+        memory.data(&store),
+        errors,
+        (count as u32).min(MAX_ERRORS),
+        table,
+    );
+    if let Ok(is_valid) = instance.get_typed_func::<i32, i32>(&store, "is_valid") {
+        if (is_valid.call(&mut store, tape.base as i32)? == 1) != (count == 0) {
+            decoded.push(("is_valid disagrees".to_string(), String::new()));
+        }
+    }
+    Ok(decoded)
+}
+
+/// How many errors validators generated with `opts` report at most.
+fn error_limit(opts: &EmitOptions) -> Option<usize> {
+    if opts.fail_fast {
+        Some(1)
+    } else {
+        opts.max_errors
+    }
+}
+
+/// All the expected errors, or with a `limit` (1 when failing fast) that
+/// many of them when there are more.
+fn errors_match(
+    actual: &[(String, String)],
+    expected: &BTreeSet<(String, String)>,
+    limit: Option<usize>,
+) -> bool {
+    if let Some(limit) = limit {
+        actual.len() == expected.len().min(limit) && actual.iter().all(|e| expected.contains(e))
+    } else {
+        actual.iter().cloned().collect::<BTreeSet<_>>() == *expected
+    }
+}
+
+#[test]
+fn test_wasmi_validation_suite() {
+    run_suite("default", &EmitOptions::default());
+}
+
+/// Fail-fast validators return the first error only.
+#[test]
+fn test_wasmi_validation_suite_fail_fast() {
+    let opts = EmitOptions {
+        fail_fast: true,
+        ..EmitOptions::default()
+    };
+    run_suite("fail-fast", &opts);
+}
+
+/// Validators with an error limit return no more errors than that.
+#[test]
+fn test_wasmi_validation_suite_max_errors() {
+    let opts = EmitOptions {
+        max_errors: Some(2),
+        ..EmitOptions::default()
+    };
+    run_suite("max-errors", &opts);
+}
+
+/// `is_valid` agrees with `validate` on every case.
+#[test]
+fn test_wasmi_validation_suite_is_valid() {
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    run_suite("is-valid", &opts);
+}
+
+fn run_suite(label: &str, opts: &EmitOptions) {
+    let suite = load_suite();
+    let mut passed = 0u32;
+    let mut skipped = 0u32;
+    let mut failures: Vec<String> = Vec::new();
+
+    for (name, case) in &suite {
+        let expected = normalize_errors(&case["errors"]);
+        let compiled = match jtd_codegen::compiler::compile(&case["schema"]) {
+            Ok(c) => c,
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let wasm = emit_wasm::assemble(&emit_wasm::emit_with_options(&compiled, opts));
+        match run(&wasm, &case["instance"]) {
+            Ok(actual) => {
+                if errors_match(&actual, &expected, error_limit(opts)) {
+                    passed += 1;
+                } else {
+                    failures.push(format!(
+                        "FAIL: {name}\n  expected: {expected:?}\n  actual:   {actual:?}"
+                    ));
+                }
+            }
+            Err(e) => failures.push(format!("FAIL: {name}\n  wasmi: {e}")),
+        }
+    }
+
+    eprintln!("=== JTD Validation Suite (wasmi, {label}) ===");
+    eprintln!("Passed:  {passed}");
+    eprintln!("Failed:  {}", failures.len());
+    eprintln!("Skipped: {skipped}");
+    for f in failures.iter().take(20) {
+        eprintln!("{f}");
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} wasmi {label} test cases failed",
+        failures.len()
+    );
+}
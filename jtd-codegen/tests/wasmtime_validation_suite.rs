@@ -35,7 +35,12 @@ fn load_suite() -> serde_json::Map<String, Value> {
     });
 
     let v: Value = serde_json::from_str(&data).expect("parse validation.json");
-    v.as_object().unwrap().clone()
+    let mut suite = v.as_object().unwrap().clone();
+    // Date-times at the edges of RFC 3339, judged alike by every target
+    let extra: Value =
+        serde_json::from_str(include_str!("timestamps.json")).expect("parse timestamps.json");
+    suite.extend(extra.as_object().unwrap().clone());
+    suite
 }
 
 fn segments_to_pointer(segments: &[Value]) -> String {
@@ -60,6 +65,9 @@ fn normalize_errors(errors: &Value) -> BTreeSet<(String, String)> {
         .collect()
 }
 
+/// (module name, instance JSON, expected errors) for one suite case.
+type TestEntry = (String, String, BTreeSet<(String, String)>);
+
 fn sanitize_name(name: &str) -> String {
     name.chars()
         .map(|c| if c.is_alphanumeric() { c } else { '_' })
@@ -89,7 +97,7 @@ fn test_wasmtime_validation_suite() {
     let mut src = String::new();
     src.push_str("use serde_json::Value;\n\n");
 
-    let mut test_entries: Vec<(String, String, BTreeSet<(String, String)>)> = Vec::new();
+    let mut test_entries: Vec<TestEntry> = Vec::new();
 
     for (name, case) in &suite {
         let schema = &case["schema"];
@@ -133,7 +141,7 @@ fn test_wasmtime_validation_suite() {
             instance_json
         ));
         src.push_str(&format!(
-            "    let errors = {mod_name}::validate(&instance);\n"
+            "    let errors: Vec<(String, String)> = {mod_name}::validate(&instance).into_iter().map({mod_name}::ValidationError::into_tuple).collect();\n"
         ));
         src.push_str("    let actual: std::collections::BTreeSet<(String, String)> = errors.into_iter().collect();\n");
         src.push_str(&format!(
@@ -168,8 +176,6 @@ edition = "2021"
 
 [dependencies]
 serde_json = "1"
-regex = "1"
-chrono = "0.4"
 "#,
     )
     .unwrap();
@@ -0,0 +1,98 @@
+/// `#[jtd_schema("schemas/user.json")] struct UserValidator;` compiles the
+/// JTD schema at the path (relative to the crate's `Cargo.toml`) into a
+/// Rust validator while the crate builds, without a build script.
+///
+/// The generated code goes in a module named after the struct in snake
+/// case (`user_validator`), with the struct's visibility, and the struct
+/// gains `validate`, `is_valid` and `SCHEMA`, the schema's text. The crate
+/// needs `serde_json`. Unreadable files and invalid schemas are compile
+/// errors pointing at the path.
+use std::path::Path;
+
+use jtd_codegen::options::EmitOptions;
+use jtd_codegen::Target;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, LitStr};
+
+/// Expands to the struct, its validator module and the methods above.
+#[proc_macro_attribute]
+pub fn jtd_schema(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(attr as LitStr);
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand(&path, &input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(path: &LitStr, input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !matches!(input.data, syn::Data::Struct(_)) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "#[jtd_schema] goes on a struct",
+        ));
+    }
+    let error = |msg: String| syn::Error::new(path.span(), msg);
+    let dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| error("CARGO_MANIFEST_DIR is not set".to_string()))?;
+    let full = Path::new(&dir).join(path.value());
+    let schema = std::fs::read_to_string(&full)
+        .map_err(|e| error(format!("cannot read {}: {e}", full.display())))?;
+    let opts = EmitOptions {
+        is_valid: true,
+        ..EmitOptions::default()
+    };
+    let code = jtd_codegen::generate(&schema, Target::Rust, &opts)
+        .map_err(|e| error(format!("{}: {e}", path.value())))?;
+    let code: proc_macro2::TokenStream = code
+        .parse()
+        .map_err(|e| error(format!("generated code does not parse: {e}")))?;
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let module = format_ident!("{}", snake_case(&name.to_string()));
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // include_str! rather than the text read above, so Cargo rebuilds the
+    // crate when the schema changes
+    let full = full.to_string_lossy().into_owned();
+    Ok(quote! {
+        #input
+
+        #[allow(clippy::all, dead_code)]
+        #vis mod #module {
+            #code
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The JTD schema this validator was generated from.
+            pub const SCHEMA: &'static str = include_str!(#full);
+
+            /// Validate `instance`, returning every error.
+            pub fn validate(instance: &serde_json::Value) -> Vec<#module::ValidationError> {
+                #module::validate(instance)
+            }
+
+            /// Whether `instance` is valid, stopping at the first failure.
+            pub fn is_valid(instance: &serde_json::Value) -> bool {
+                #module::is_valid(instance)
+            }
+        }
+    })
+}
+
+/// `UserValidator` → `user_validator`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
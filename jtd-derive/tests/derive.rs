@@ -0,0 +1,28 @@
+use jtd_derive::jtd_schema;
+use serde_json::json;
+
+#[jtd_schema("tests/user.json")]
+struct UserValidator;
+
+#[test]
+fn test_validates_with_generated_code() {
+    assert!(UserValidator::is_valid(&json!({"name": "Ada", "age": 36})));
+    let errors = UserValidator::validate(&json!({"name": 1, "age": 36, "extra": true}));
+    let pairs: Vec<(String, String)> = errors
+        .into_iter()
+        .map(user_validator::ValidationError::into_tuple)
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("/name".to_string(), "/properties/name/type".to_string()),
+            ("/extra".to_string(), "".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_schema_text() {
+    let schema: serde_json::Value = serde_json::from_str(UserValidator::SCHEMA).unwrap();
+    assert_eq!(schema["properties"]["age"], json!({"type": "uint8"}));
+}
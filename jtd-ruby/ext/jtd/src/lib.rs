@@ -0,0 +1,72 @@
+/// Ruby bindings (magnus) over jtd-codegen, loaded as `jtd/jtd` by `lib/jtd.rb`.
+///
+/// Defines `JTD.compile(json) -> JTD::Schema`, `JTD::Schema#emit(target)` and
+/// `JTD::Schema#validate_json(json)`. Failures raise `JTD::Error`.
+use jtd_codegen::ast::CompiledSchema;
+use jtd_codegen::options::EmitOptions;
+use jtd_codegen::Target;
+use magnus::{
+    function, method, prelude::*, value::Lazy, Error, ExceptionClass, RArray, RModule, Ruby,
+};
+
+static JTD_ERROR: Lazy<ExceptionClass> = Lazy::new(|ruby| {
+    ruby.define_module("JTD")
+        .and_then(|m| m.const_get::<_, ExceptionClass>("Error"))
+        .expect("JTD::Error is defined in init")
+});
+
+fn error(ruby: &Ruby, msg: String) -> Error {
+    Error::new(ruby.get_inner(&JTD_ERROR), msg)
+}
+
+#[magnus::wrap(class = "JTD::Schema", free_immediately, size)]
+struct Schema(CompiledSchema);
+
+/// `JTD.compile(json)`: parse and compile a schema given as JSON text.
+fn compile(ruby: &Ruby, json: String) -> Result<Schema, Error> {
+    let value: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| error(ruby, format!("invalid JSON: {e}")))?;
+    jtd_codegen::compiler::compile(&value)
+        .map(Schema)
+        .map_err(|e| error(ruby, format!("invalid JTD schema: {e}")))
+}
+
+impl Schema {
+    /// `schema.emit("js" | "lua" | "python" | "rust")`: validator source text.
+    fn emit(ruby: &Ruby, rb_self: &Self, target: String) -> Result<String, Error> {
+        let target = Target::parse(&target)
+            .ok_or_else(|| error(ruby, format!("Unknown target: {target}")))?;
+        Ok(jtd_codegen::generate::emit(
+            &rb_self.0,
+            target,
+            &EmitOptions::default(),
+        ))
+    }
+
+    /// `schema.validate_json(json)`: array of `{"instancePath", "schemaPath"}`
+    /// hashes, empty when the instance is valid.
+    fn validate_json(ruby: &Ruby, rb_self: &Self, json: String) -> Result<RArray, Error> {
+        let instance: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| error(ruby, format!("invalid JSON: {e}")))?;
+        let errors = ruby.ary_new();
+        for (ip, sp) in jtd_codegen::interpret::validate(&rb_self.0, &instance) {
+            let h = ruby.hash_new();
+            h.aset("instancePath", ip)?;
+            h.aset("schemaPath", sp)?;
+            errors.push(h)?;
+        }
+        Ok(errors)
+    }
+}
+
+#[magnus::init]
+fn init(ruby: &Ruby) -> Result<(), Error> {
+    let module: RModule = ruby.define_module("JTD")?;
+    module.define_error("Error", ruby.exception_standard_error())?;
+    module.define_singleton_method("compile", function!(compile, 1))?;
+
+    let class = module.define_class("Schema", ruby.class_object())?;
+    class.define_method("emit", method!(Schema::emit, 1))?;
+    class.define_method("validate_json", method!(Schema::validate_json, 1))?;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+# frozen_string_literal: true
+
+require "json"
+require_relative "jtd/version"
+require "jtd/jtd"
+
+# JSON Type Definition codegen and validation, backed by jtd-codegen.
+#
+#   schema = JTD.compile(File.read("schema.jtd.json"))
+#   File.write("app/javascript/validate.mjs", JTD.emit(schema, :js))
+#   JTD.validate(schema, params.to_unsafe_h) # => [] when valid
+module JTD
+  # Emit validator source for +target+ (:js, :lua, :python or :rust).
+  def self.emit(schema, target)
+    schema.emit(target.to_s)
+  end
+
+  # Validate a Ruby object (anything JSON.generate accepts). Returns an array
+  # of {"instancePath" => ..., "schemaPath" => ...} hashes.
+  def self.validate(schema, instance)
+    schema.validate_json(JSON.generate(instance))
+  end
+end
@@ -0,0 +1,28 @@
+# frozen_string_literal: true
+
+require "minitest/autorun"
+require "jtd"
+
+class TestJTD < Minitest::Test
+  SCHEMA = '{"properties": {"name": {"type": "string"}}}'
+
+  def test_emit_each_target
+    schema = JTD.compile(SCHEMA)
+    assert_includes JTD.emit(schema, :js), "export function validate(instance)"
+    assert_includes JTD.emit(schema, :lua), "function M.validate(instance)"
+    assert_includes JTD.emit(schema, :python), "def validate("
+    assert_includes JTD.emit(schema, :rust), "pub fn validate(instance: &Value)"
+  end
+
+  def test_validate
+    schema = JTD.compile(SCHEMA)
+    assert_equal [], JTD.validate(schema, { "name" => "a" })
+    assert_equal [{ "instancePath" => "/name", "schemaPath" => "/properties/name/type" }],
+                 JTD.validate(schema, { "name" => 1 })
+  end
+
+  def test_errors
+    assert_raises(JTD::Error) { JTD.compile('{"type": "nope"}') }
+    assert_raises(JTD::Error) { JTD.emit(JTD.compile("{}"), :go) }
+  end
+end
@@ -0,0 +1,167 @@
+// The generated scaffolding trips this lint; our own code does not.
+#![allow(clippy::empty_line_after_doc_comments)]
+
+/// UniFFI bindings over jtd-codegen, described by `src/jtd.udl`.
+///
+/// Kotlin and Swift hosts get `compile`, `generate`, `Schema.emit` and
+/// `Schema.validate`; generate them with the bundled `uniffi-bindgen` binary.
+use std::sync::Arc;
+
+use jtd_codegen::ast::CompiledSchema;
+use jtd_codegen::options::EmitOptions;
+
+uniffi::include_scaffolding!("jtd");
+
+/// Code generation targets (mirrors `jtd_codegen::Target`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    JavaScript,
+    Lua,
+    Python,
+    Rust,
+}
+
+impl From<Target> for jtd_codegen::Target {
+    fn from(t: Target) -> Self {
+        match t {
+            Target::JavaScript => jtd_codegen::Target::JavaScript,
+            Target::Lua => jtd_codegen::Target::Lua,
+            Target::Python => jtd_codegen::Target::Python,
+            Target::Rust => jtd_codegen::Target::Rust,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub instance_path: String,
+    pub schema_path: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JtdError {
+    #[error("invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("invalid JTD schema: {0}")]
+    InvalidSchema(String),
+}
+
+impl From<jtd_codegen::GenerateError> for JtdError {
+    fn from(e: jtd_codegen::GenerateError) -> Self {
+        match e {
+            jtd_codegen::GenerateError::Json(e) => JtdError::InvalidJson(e.to_string()),
+            jtd_codegen::GenerateError::Compile(e) => JtdError::InvalidSchema(e.to_string()),
+        }
+    }
+}
+
+/// A compiled schema, shared with the host as an object handle.
+pub struct Schema(CompiledSchema);
+
+impl Schema {
+    pub fn emit(&self, target: Target) -> String {
+        jtd_codegen::generate::emit(&self.0, target.into(), &EmitOptions::default())
+    }
+
+    pub fn validate(&self, instance_json: String) -> Result<Vec<ValidationError>, JtdError> {
+        let instance = parse_instance(&instance_json)?;
+        Ok(to_errors(jtd_codegen::interpret::validate(
+            &self.0, &instance,
+        )))
+    }
+
+    pub fn revalidate(
+        &self,
+        instance_json: String,
+        previous: Vec<ValidationError>,
+        changed: Vec<String>,
+    ) -> Result<Vec<ValidationError>, JtdError> {
+        let instance = parse_instance(&instance_json)?;
+        let previous: Vec<(String, String)> = previous
+            .into_iter()
+            .map(|e| (e.instance_path, e.schema_path))
+            .collect();
+        let changed: Vec<&str> = changed.iter().map(String::as_str).collect();
+        Ok(to_errors(jtd_codegen::interpret::revalidate(
+            &self.0, &instance, &previous, &changed,
+        )))
+    }
+}
+
+fn parse_instance(instance_json: &str) -> Result<serde_json::Value, JtdError> {
+    serde_json::from_str(instance_json).map_err(|e| JtdError::InvalidJson(e.to_string()))
+}
+
+fn to_errors(errors: Vec<(String, String)>) -> Vec<ValidationError> {
+    errors
+        .into_iter()
+        .map(|(instance_path, schema_path)| ValidationError {
+            instance_path,
+            schema_path,
+        })
+        .collect()
+}
+
+pub fn compile(schema_json: String) -> Result<Arc<Schema>, JtdError> {
+    let value: serde_json::Value =
+        serde_json::from_str(&schema_json).map_err(|e| JtdError::InvalidJson(e.to_string()))?;
+    jtd_codegen::compiler::compile(&value)
+        .map(|c| Arc::new(Schema(c)))
+        .map_err(|e| JtdError::InvalidSchema(e.to_string()))
+}
+
+pub fn generate(schema_json: String, target: Target) -> Result<String, JtdError> {
+    Ok(jtd_codegen::generate(
+        &schema_json,
+        target.into(),
+        &EmitOptions::default(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_emit_validate() {
+        let schema = compile(r#"{"properties": {"name": {"type": "string"}}}"#.into()).unwrap();
+        assert!(schema
+            .emit(Target::Lua)
+            .contains("function M.validate(instance)"));
+        assert!(schema
+            .validate(r#"{"name": "a"}"#.into())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            schema.validate(r#"{"name": 1}"#.into()).unwrap(),
+            vec![ValidationError {
+                instance_path: "/name".into(),
+                schema_path: "/properties/name/type".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_revalidate() {
+        let schema = compile(r#"{"properties": {"name": {"type": "string"}}}"#.into()).unwrap();
+        let previous = schema.validate(r#"{"name": 1}"#.into()).unwrap();
+        let errors = schema
+            .revalidate(r#"{"name": "a"}"#.into(), previous, vec!["/name".into()])
+            .unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(compile("{".into()), Err(JtdError::InvalidJson(_))));
+        assert!(matches!(
+            generate(r#"{"type": "nope"}"#.into(), Target::Rust),
+            Err(JtdError::InvalidSchema(_))
+        ));
+        let schema = compile("{}".into()).unwrap();
+        assert!(matches!(
+            schema.validate("nope".into()),
+            Err(JtdError::InvalidJson(_))
+        ));
+    }
+}
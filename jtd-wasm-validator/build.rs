@@ -1,17 +1,58 @@
-/// Build script: reads schema.json, generates Rust validation code via
-/// jtd-codegen, writes it to OUT_DIR for inclusion in lib.rs.
+/// Build script: generates Rust validation code for schema.json via
+/// jtd-codegen into OUT_DIR for inclusion in lib.rs, plus the streaming plan.
 fn main() {
     let schema_path = "schema.json";
-    println!("cargo:rerun-if-changed={schema_path}");
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    // validate_at lets the streaming feed validate one element at a time
+    let opts = jtd_codegen::options::EmitOptions {
+        validate_at: true,
+        ..Default::default()
+    };
+    jtd_codegen::build::generate_with_options(
+        schema_path,
+        jtd_codegen::Target::Rust,
+        &out_dir,
+        &opts,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
 
     let schema_str = std::fs::read_to_string(schema_path).expect("Cannot read schema.json");
     let schema: serde_json::Value =
         serde_json::from_str(&schema_str).expect("Invalid JSON in schema.json");
     let compiled =
         jtd_codegen::compiler::compile(&schema).expect("Invalid JTD schema in schema.json");
-    let rs_code = jtd_codegen::emit_rs::emit(&compiled);
+    let plan = std::path::Path::new(&out_dir).join("stream_plan.rs");
+    std::fs::write(&plan, stream_plan(&compiled)).expect("Cannot write stream_plan.rs");
+}
 
-    let out_dir = std::env::var("OUT_DIR").unwrap();
-    let dest = std::path::Path::new(&out_dir).join("validator.rs");
-    std::fs::write(&dest, rs_code).expect("Cannot write generated validator.rs");
+/// `Option<stream::Plan>` source: whether the root (through nullable and
+/// refs) is an elements or values form, and its children's schema path.
+fn stream_plan(compiled: &jtd_codegen::ast::CompiledSchema) -> String {
+    use jtd_codegen::ast::Node;
+    let mut node = &compiled.root;
+    let mut base = String::new();
+    // Bounded so a definition that only refers to itself cannot loop
+    for _ in 0..=compiled.definitions.len() {
+        match node {
+            Node::Nullable { inner } => node = inner,
+            Node::Ref { name } => {
+                // validate_at matches definition names escaped
+                base = format!(
+                    "/definitions/{}",
+                    jtd_codegen::compiler::escape_pointer(name)
+                );
+                node = &compiled.definitions[name];
+            }
+            _ => break,
+        }
+    }
+    let (container, child) = match node {
+        Node::Elements { .. } => ("Array", "elements"),
+        Node::Values { .. } => ("Object", "values"),
+        _ => return "None".to_string(),
+    };
+    format!(
+        "Some(stream::Plan {{ container: stream::Container::{container}, child_schema: {:?} }})",
+        format!("{base}/{child}")
+    )
 }
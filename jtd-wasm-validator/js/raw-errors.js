@@ -0,0 +1,42 @@
+/// Lazy decoder for `validate_raw`. Pass the initialised wasm-bindgen module
+/// and the count `validate_raw` returned; strings are only decoded for the
+/// records you read. Results are valid until the next `validate_raw` call.
+///
+///   import init, * as wasm from './jtd_wasm_validator.js';
+///   import { rawErrors } from './raw-errors.js';
+///   await init();
+///   const errors = rawErrors(wasm, wasm.validate_raw(json));
+///   if (errors.length) console.log(errors.get(0).instancePath);
+
+const decoder = new TextDecoder();
+
+export function rawErrors(wasm, count) {
+  const tablePtr = wasm.raw_errors_ptr();
+  const stringsPtr = wasm.raw_strings_ptr();
+  const stringsLen = wasm.raw_strings_len();
+
+  // Views are rebuilt per read: growing memory detaches old ArrayBuffers.
+  const record = (i) => new Uint32Array(wasm.memory().buffer, tablePtr + i * 16, 4);
+  const text = (offset, len) =>
+    decoder.decode(new Uint8Array(wasm.memory().buffer, stringsPtr, stringsLen).subarray(offset, offset + len));
+
+  return {
+    length: count,
+    get(i) {
+      if (i < 0 || i >= count) return undefined;
+      const [ipOff, ipLen, spOff, spLen] = record(i);
+      return { instancePath: text(ipOff, ipLen), schemaPath: text(spOff, spLen) };
+    },
+    instancePath(i) {
+      const [off, len] = record(i);
+      return text(off, len);
+    },
+    schemaPath(i) {
+      const [, , off, len] = record(i);
+      return text(off, len);
+    },
+    *[Symbol.iterator]() {
+      for (let i = 0; i < count; i++) yield this.get(i);
+    },
+  };
+}
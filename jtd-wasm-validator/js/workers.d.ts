@@ -0,0 +1,55 @@
+/// Types for workers.js, the edge entry point of the wasm validator.
+
+export interface ValidationError {
+  /** JSON Pointer to the offending part of the instance. */
+  instancePath: string;
+  /** JSON Pointer to the schema keyword that rejected it. */
+  schemaPath: string;
+}
+
+/** Validate a JSON document; throws on malformed JSON. */
+export function validate(json: string): ValidationError[];
+
+/**
+ * Validate an already-parsed value. Dates become ISO strings and undefined
+ * members are left out, as with JSON.stringify; Map, Set, NaN, BigInt,
+ * symbol or non-enumerable keys and cycles throw, naming their location.
+ */
+export function validate_value(instance: unknown): ValidationError[];
+
+/** Validate and write errors to linear memory; returns their count. */
+export function validate_raw(json: string): number;
+export function raw_errors_ptr(): number;
+export function raw_strings_ptr(): number;
+export function raw_strings_len(): number;
+export function memory(): WebAssembly.Memory;
+
+/** Start a document delivered in chunks. */
+export function begin(): void;
+/** Pass the next chunk; throws on malformed JSON. */
+export function feed(chunk: Uint8Array): void;
+/** Finish the document and return its errors. */
+export function end(): ValidationError[];
+
+/** `begin`, `feed` each chunk of `stream`, then `end`. */
+export function validateStream(stream: ReadableStream<Uint8Array>): Promise<ValidationError[]>;
+
+/** Present when built with `--features metrics`. */
+export function metrics(): {
+  validations: number;
+  failures: number;
+  errors: number;
+  p50Ms: number;
+  p99Ms: number;
+};
+export function reset_metrics(): void;
+
+export interface RawErrors extends Iterable<ValidationError> {
+  readonly length: number;
+  get(i: number): ValidationError | undefined;
+  instancePath(i: number): string;
+  schemaPath(i: number): string;
+}
+
+/** Lazily decode the records `validate_raw` wrote; pass this module. */
+export function rawErrors(wasm: object, count: number): RawErrors;
@@ -0,0 +1,30 @@
+/// Cloudflare Workers (and other edge runtimes) entry point. Wrangler turns
+/// the `.wasm` import into a `WebAssembly.Module` compiled at deploy time, so
+/// the validator is instantiated synchronously when this module loads: no
+/// `fetch` of the wasm file and no `WebAssembly.compile` on bytes, which
+/// Workers forbid. Import from here instead of `jtd_wasm_validator.js` and
+/// skip `init()`:
+///
+///   import { validate, validateStream } from './workers.js';
+///   const errors = await validateStream(request.body);
+
+import wasmModule from './jtd_wasm_validator_bg.wasm';
+import { initSync, begin, feed, end } from './jtd_wasm_validator.js';
+
+initSync({ module: wasmModule });
+
+export * from './jtd_wasm_validator.js';
+export { rawErrors } from './raw-errors.js';
+
+/// Validate a body stream chunk by chunk with `begin`/`feed`/`end`, without
+/// buffering it as a string first.
+export async function validateStream(stream) {
+  begin();
+  const reader = stream.getReader();
+  for (;;) {
+    const { done, value } = await reader.read();
+    if (done) break;
+    feed(value);
+  }
+  return end();
+}
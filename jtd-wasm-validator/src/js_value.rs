@@ -0,0 +1,142 @@
+/// Conversion of already-parsed JS values for `validate_value`. Browser
+/// callers often pass values that `JSON.stringify` would quietly change or
+/// lose, so the rules are explicit:
+///
+/// - A `Date` becomes its ISO string and an object member holding
+///   `undefined` is left out, as `JSON.stringify` does.
+/// - Values with no JSON form are errors naming where they are: `Map`,
+///   `Set`, binary data, `undefined` at the root or in an array (holes
+///   included), `NaN` and the infinities, BigInt, functions, symbols,
+///   symbol keys, non-enumerable own properties and cycles.
+use js_sys::{Array, ArrayBuffer, Date, Object, Reflect};
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// `value` as JSON, or an error naming the instance path (a JSON Pointer)
+/// of the first part that has no JSON form.
+pub fn to_json(value: &JsValue) -> Result<Value, String> {
+    convert(value, &mut String::new(), &mut Vec::new())
+}
+
+fn fail<T>(path: &str, what: &str) -> Result<T, String> {
+    let at = if path.is_empty() { "the root" } else { path };
+    Err(format!("{what} at {at}"))
+}
+
+/// `value` at instance path `path`; `ancestors` are the objects and arrays
+/// enclosing it, to catch cycles.
+fn convert(
+    value: &JsValue,
+    path: &mut String,
+    ancestors: &mut Vec<JsValue>,
+) -> Result<Value, String> {
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    if let Some(b) = value.as_bool() {
+        return Ok(Value::Bool(b));
+    }
+    if let Some(n) = value.as_f64() {
+        return match Number::from_f64(n) {
+            Some(n) => Ok(Value::Number(n)),
+            None => fail(path, &format!("{n} is not a JSON number")),
+        };
+    }
+    if let Some(s) = value.as_string() {
+        return Ok(Value::String(s));
+    }
+    if value.is_undefined() {
+        return fail(path, "undefined is not a JSON value");
+    }
+    if value.is_bigint() {
+        return fail(
+            path,
+            "a BigInt is not a JSON value; convert it to a number or string",
+        );
+    }
+    if value.is_symbol() || value.is_function() {
+        return fail(path, "symbols and functions are not JSON values");
+    }
+    if let Some(date) = value.dyn_ref::<Date>() {
+        if date.get_time().is_nan() {
+            return fail(path, "an invalid Date has no JSON form");
+        }
+        return Ok(Value::String(date.to_iso_string().into()));
+    }
+    if value.is_instance_of::<js_sys::Map>() {
+        return fail(
+            path,
+            "a Map is not a JSON value; convert it with Object.fromEntries",
+        );
+    }
+    if value.is_instance_of::<js_sys::Set>() {
+        return fail(
+            path,
+            "a Set is not a JSON value; convert it with Array.from",
+        );
+    }
+    if ArrayBuffer::is_view(value) || value.is_instance_of::<ArrayBuffer>() {
+        return fail(
+            path,
+            "binary data is not a JSON value; convert it with Array.from",
+        );
+    }
+    if ancestors.contains(value) {
+        return fail(path, "a cyclic reference has no JSON form");
+    }
+
+    ancestors.push(value.clone());
+    let out = if Array::is_array(value) {
+        array(value.unchecked_ref(), path, ancestors)
+    } else {
+        object(value, path, ancestors)
+    };
+    ancestors.pop();
+    out
+}
+
+fn array(array: &Array, path: &mut String, ancestors: &mut Vec<JsValue>) -> Result<Value, String> {
+    let mut items = Vec::with_capacity(array.length() as usize);
+    for i in 0..array.length() {
+        let len = path.len();
+        path.push_str(&format!("/{i}"));
+        items.push(convert(&array.get(i), path, ancestors)?);
+        path.truncate(len);
+    }
+    Ok(Value::Array(items))
+}
+
+fn object(
+    value: &JsValue,
+    path: &mut String,
+    ancestors: &mut Vec<JsValue>,
+) -> Result<Value, String> {
+    let obj: &Object = value.unchecked_ref();
+    let keys = Reflect::own_keys(value).or_else(|_| fail(path, "listing the keys threw"))?;
+    let mut members = Map::new();
+    for key in keys.iter() {
+        let Some(name) = key.as_string() else {
+            return fail(
+                path,
+                "a symbol key has no JSON form (JSON.stringify drops it)",
+            );
+        };
+        let len = path.len();
+        path.push('/');
+        path.push_str(&name.replace('~', "~0").replace('/', "~1"));
+        if !obj.property_is_enumerable(&key) {
+            return fail(
+                path,
+                "a non-enumerable property has no JSON form (JSON.stringify drops it)",
+            );
+        }
+        let member =
+            Reflect::get(value, &key).or_else(|_| fail(path, "reading the property threw"))?;
+        // Left out, as JSON.stringify does
+        if !member.is_undefined() {
+            members.insert(name, convert(&member, path, ancestors)?);
+        }
+        path.truncate(len);
+    }
+    Ok(Value::Object(members))
+}
@@ -1,10 +1,118 @@
 use wasm_bindgen::prelude::*;
 
+mod js_value;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod raw;
+mod stream;
+
 /// Generated validator -- compiled from schema.json at build time.
 #[allow(clippy::all)]
 #[allow(unused_imports)]
 mod generated {
-    include!(concat!(env!("OUT_DIR"), "/validator.rs"));
+    include!(concat!(env!("OUT_DIR"), "/schema.rs"));
+}
+
+/// How `feed` can split the document, worked out from schema.json.
+const STREAM_PLAN: Option<stream::Plan> = include!(concat!(env!("OUT_DIR"), "/stream_plan.rs"));
+
+thread_local! {
+    /// The document `begin` started, if any.
+    static FEED: std::cell::RefCell<Option<stream::Feed>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(feature = "metrics")]
+thread_local! {
+    /// Milliseconds spent in `feed` calls since `begin`.
+    static FEED_MS: std::cell::Cell<f64> = const { std::cell::Cell::new(0.0) };
+}
+
+/// Run the generated validator, counting the call when the `metrics` feature
+/// is on.
+fn run(instance: &serde_json::Value) -> Vec<(String, String)> {
+    #[cfg(feature = "metrics")]
+    let start = now_ms();
+    let errors = pairs(generated::validate(instance));
+    #[cfg(feature = "metrics")]
+    metrics::METRICS.with(|m| m.borrow_mut().record(now_ms() - start, errors.len()));
+    errors
+}
+
+/// Parse `instance_json` and [`run`] the validator on it.
+fn run_json(instance_json: &str) -> Result<stream::Errors, String> {
+    let instance: serde_json::Value =
+        serde_json::from_str(instance_json).map_err(|e| format!("Invalid JSON: {e}"))?;
+    Ok(run(&instance))
+}
+
+/// Pass `chunk` to the document `begin` started.
+fn feed_chunk(chunk: &[u8]) -> Result<(), String> {
+    #[cfg(feature = "metrics")]
+    let start = now_ms();
+    let result = FEED.with(|f| match f.borrow_mut().as_mut() {
+        Some(feed) => feed.feed(chunk),
+        None => Err("feed called before begin".to_string()),
+    });
+    #[cfg(feature = "metrics")]
+    FEED_MS.with(|ms| ms.set(ms.get() + now_ms() - start));
+    result
+}
+
+/// Finish the document `begin` started, counting it as one validation that
+/// took the time spent in its `feed` and `end` calls.
+fn finish() -> Result<stream::Errors, String> {
+    #[cfg(feature = "metrics")]
+    let start = now_ms();
+    let feed = FEED
+        .with(|f| f.borrow_mut().take())
+        .ok_or("end called before begin")?;
+    let errors = feed.end()?;
+    #[cfg(feature = "metrics")]
+    {
+        let ms = FEED_MS.with(|ms| ms.take()) + now_ms() - start;
+        metrics::METRICS.with(|m| m.borrow_mut().record(ms, errors.len()));
+    }
+    Ok(errors)
+}
+
+/// The generated validator's errors as `(instancePath, schemaPath)` pairs.
+fn pairs(errors: Vec<generated::ValidationError>) -> stream::Errors {
+    errors
+        .into_iter()
+        .map(generated::ValidationError::into_tuple)
+        .collect()
+}
+
+#[cfg(all(feature = "metrics", not(test)))]
+thread_local! {
+    /// `performance` and its `now`, looked up once.
+    static PERFORMANCE: Option<(JsValue, js_sys::Function)> = {
+        let perf = js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+            .ok()
+            .filter(JsValue::is_object);
+        perf.and_then(|p| {
+            let now = js_sys::Reflect::get(&p, &"now".into()).ok()?;
+            Some((p, now.dyn_into().ok()?))
+        })
+    };
+}
+
+/// Milliseconds from `performance.now()` (browsers, workers, Node), falling
+/// back to the coarser `Date.now()` on hosts without it.
+#[cfg(all(feature = "metrics", not(test)))]
+fn now_ms() -> f64 {
+    PERFORMANCE
+        .with(|p| {
+            p.as_ref()
+                .and_then(|(perf, now)| now.call0(perf).ok()?.as_f64())
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+/// Native tests have no JS clock.
+#[cfg(all(feature = "metrics", test))]
+fn now_ms() -> f64 {
+    0.0
 }
 
 /// Validate a JSON string against the compiled schema.
@@ -12,12 +120,29 @@ mod generated {
 /// Returns an empty array `[]` when the instance is valid.
 #[wasm_bindgen]
 pub fn validate(instance_json: &str) -> Result<JsValue, JsError> {
-    let instance: serde_json::Value = serde_json::from_str(instance_json)
-        .map_err(|e| JsError::new(&format!("Invalid JSON: {e}")))?;
+    let errors = run_json(instance_json).map_err(|e| JsError::new(&e))?;
+
+    Ok(errors_to_js(errors))
+}
 
-    let errors = generated::validate(&instance);
+/// Validate an already-parsed JS value, skipping the `JSON.stringify` round
+/// trip. Dates become ISO strings and `undefined` members are left out, as
+/// `JSON.stringify` would; a value with no JSON form (`Map`, `Set`,
+/// `undefined` elsewhere, `NaN`, BigInt, functions, symbol or
+/// non-enumerable keys, cycles) throws an error saying where it is instead
+/// of being silently dropped.
+#[wasm_bindgen]
+pub fn validate_value(instance: JsValue) -> Result<JsValue, JsError> {
+    let instance = js_value::to_json(&instance)
+        .map_err(|e| JsError::new(&format!("Not a JSON value: {e}")))?;
+
+    let errors = run(&instance);
+
+    Ok(errors_to_js(errors))
+}
 
-    // Build a JS array of {instancePath, schemaPath} objects
+/// Build a JS array of {instancePath, schemaPath} objects
+fn errors_to_js(errors: Vec<(String, String)>) -> JsValue {
     let arr = js_sys::Array::new();
     for (ip, sp) in errors {
         let obj = js_sys::Object::new();
@@ -25,5 +150,136 @@ pub fn validate(instance_json: &str) -> Result<JsValue, JsError> {
         js_sys::Reflect::set(&obj, &"schemaPath".into(), &sp.into()).unwrap();
         arr.push(&obj);
     }
-    Ok(arr.into())
+    arr.into()
+}
+
+/// Validate a JSON string, leaving the errors in linear memory instead of
+/// building JS objects. Returns the error count; the records are read via
+/// `raw_errors_ptr`, `raw_strings_ptr` and `raw_strings_len` (see
+/// `js/raw-errors.js`) and stay valid until the next `validate_raw` call.
+#[wasm_bindgen]
+pub fn validate_raw(instance_json: &str) -> Result<u32, JsError> {
+    let errors = run_json(instance_json).map_err(|e| JsError::new(&e))?;
+    Ok(raw::ERRORS.with(|buf| buf.borrow_mut().fill(&errors)) as u32)
+}
+
+/// Start validating a document delivered in chunks with `feed`, e.g. from
+/// a `fetch` body stream. Discards any document already in progress.
+#[wasm_bindgen]
+pub fn begin() {
+    let validators = stream::Validators {
+        validate: |value| pairs(generated::validate(value)),
+        validate_at: |pointer, value| generated::validate_at(pointer, value).map(pairs),
+    };
+    FEED.with(|f| *f.borrow_mut() = Some(stream::Feed::new(STREAM_PLAN, validators)));
+    #[cfg(feature = "metrics")]
+    FEED_MS.with(|ms| ms.set(0.0));
+}
+
+/// Pass the next chunk of the document. When the schema's root is an
+/// `elements` or `values` form, each array element or member is validated
+/// as soon as it is complete and then dropped, so memory stays bounded by
+/// the largest one; other documents are buffered until `end`. Throws on
+/// malformed JSON.
+#[wasm_bindgen]
+pub fn feed(chunk: &[u8]) -> Result<(), JsError> {
+    feed_chunk(chunk).map_err(|e| JsError::new(&e))
+}
+
+/// Finish the document; returns its errors like `validate`.
+#[wasm_bindgen]
+pub fn end() -> Result<JsValue, JsError> {
+    let errors = finish().map_err(|e| JsError::new(&e))?;
+    Ok(errors_to_js(errors))
+}
+
+/// Address of the error table written by the last `validate_raw` call.
+#[wasm_bindgen]
+pub fn raw_errors_ptr() -> *const u32 {
+    raw::ERRORS.with(|buf| buf.borrow().table_ptr())
+}
+
+/// Address of the string region the error table's offsets index.
+#[wasm_bindgen]
+pub fn raw_strings_ptr() -> *const u8 {
+    raw::ERRORS.with(|buf| buf.borrow().strings_ptr())
+}
+
+/// Length in bytes of the string region.
+#[wasm_bindgen]
+pub fn raw_strings_len() -> u32 {
+    raw::ERRORS.with(|buf| buf.borrow().strings_len()) as u32
+}
+
+/// The module's linear memory, for decoding `validate_raw` results.
+#[wasm_bindgen]
+pub fn memory() -> JsValue {
+    wasm_bindgen::memory()
+}
+
+/// Counters since instantiation or the last `reset_metrics`: `{validations,
+/// failures, errors, p50Ms, p99Ms}`. Every `validate`, `validate_value` and
+/// `validate_raw` call counts once, and so does each streamed document, at
+/// its `end`, with the time spent in its `feed` and `end` calls. Invalid
+/// JSON (and a value `validate_value` rejects) is not counted. Percentiles
+/// come from coarse duration buckets, so read them as "at most".
+#[cfg(feature = "metrics")]
+#[wasm_bindgen]
+pub fn metrics() -> JsValue {
+    let s = metrics::METRICS.with(|m| m.borrow().snapshot());
+    let obj = js_sys::Object::new();
+    let fields = [
+        ("validations", s.validations as f64),
+        ("failures", s.failures as f64),
+        ("errors", s.errors as f64),
+        ("p50Ms", s.p50_ms),
+        ("p99Ms", s.p99_ms),
+    ];
+    for (key, value) in fields {
+        js_sys::Reflect::set(&obj, &key.into(), &value.into()).unwrap();
+    }
+    obj.into()
+}
+
+/// Zero the counters returned by `metrics`.
+#[cfg(feature = "metrics")]
+#[wasm_bindgen]
+pub fn reset_metrics() {
+    metrics::METRICS.with(|m| *m.borrow_mut() = metrics::Metrics::default());
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    fn counts() -> (u64, u64, u64) {
+        let s = metrics::METRICS.with(|m| m.borrow().snapshot());
+        (s.validations, s.failures, s.errors)
+    }
+
+    #[test]
+    fn test_every_entry_point_is_counted() {
+        // validate
+        run_json(r#"{"name": "a", "age": 1, "tags": []}"#).unwrap();
+        assert_eq!(counts(), (1, 0, 0));
+        assert!(run_json("[").is_err());
+        assert_eq!(counts(), (1, 0, 0));
+        // validate_raw: name, age and tags missing
+        assert!(matches!(validate_raw("{}"), Ok(3)));
+        assert_eq!(counts(), (2, 1, 3));
+        // validate_value, once its argument is converted
+        run(&serde_json::json!({"name": 1, "age": 1, "tags": []}));
+        assert_eq!(counts(), (3, 2, 4));
+        // begin/feed/end: one validation per document, at end
+        begin();
+        feed_chunk(br#"{"name": "a", "age": 300,"#).unwrap();
+        feed_chunk(br#" "tags": []}"#).unwrap();
+        assert_eq!(counts(), (3, 2, 4));
+        assert_eq!(finish().unwrap().len(), 1);
+        assert_eq!(counts(), (4, 3, 5));
+        begin();
+        feed_chunk(b"[1").unwrap();
+        assert!(finish().is_err());
+        assert_eq!(counts(), (4, 3, 5));
+    }
 }
@@ -0,0 +1,116 @@
+/// Validation counters behind the `metrics` feature. Durations go into fixed
+/// exponential buckets, so recording is O(1) with no allocation, and
+/// percentiles are reported as the upper bound of the bucket they fall in,
+/// capped at the slowest validation seen.
+use std::cell::RefCell;
+
+/// Bucket upper bounds in milliseconds. A final overflow bucket catches
+/// anything slower.
+const BOUNDS_MS: [f64; 13] = [
+    0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0,
+];
+
+#[derive(Default)]
+pub struct Metrics {
+    validations: u64,
+    failures: u64,
+    errors: u64,
+    max_ms: f64,
+    buckets: [u64; BOUNDS_MS.len() + 1],
+}
+
+/// A point-in-time copy of the counters.
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    pub validations: u64,
+    pub failures: u64,
+    pub errors: u64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl Metrics {
+    /// Count one validation that took `duration_ms` and found `errors` errors.
+    pub fn record(&mut self, duration_ms: f64, errors: usize) {
+        self.validations += 1;
+        if errors > 0 {
+            self.failures += 1;
+        }
+        self.errors += errors as u64;
+        self.max_ms = self.max_ms.max(duration_ms);
+        let bucket = BOUNDS_MS
+            .iter()
+            .position(|&b| duration_ms <= b)
+            .unwrap_or(BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            validations: self.validations,
+            failures: self.failures,
+            errors: self.errors,
+            p50_ms: self.percentile(0.5),
+            p99_ms: self.percentile(0.99),
+        }
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile; 0 before any
+    /// validation.
+    fn percentile(&self, q: f64) -> f64 {
+        let rank = (q * self.validations as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = BOUNDS_MS.get(i).copied().unwrap_or(f64::INFINITY);
+                return bound.min(self.max_ms);
+            }
+        }
+        0.0
+    }
+}
+
+thread_local! {
+    /// Counters since module instantiation or the last `reset_metrics`.
+    pub static METRICS: RefCell<Metrics> = RefCell::new(Metrics::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let m = Metrics::default();
+        assert_eq!(
+            m.snapshot(),
+            Snapshot {
+                validations: 0,
+                failures: 0,
+                errors: 0,
+                p50_ms: 0.0,
+                p99_ms: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_counts_and_percentiles() {
+        let mut m = Metrics::default();
+        for _ in 0..98 {
+            m.record(0.02, 0);
+        }
+        m.record(3.0, 2);
+        m.record(400.0, 5);
+        let s = m.snapshot();
+        assert_eq!((s.validations, s.failures, s.errors), (100, 2, 7));
+        assert_eq!(s.p50_ms, 0.025);
+        // The 99th validation lands in the (2.5, 5] bucket
+        assert_eq!(s.p99_ms, 5.0);
+        m.record(400.0, 0);
+        m.record(400.0, 0);
+        // Overflow bucket reports the slowest validation seen
+        assert_eq!(m.snapshot().p99_ms, 400.0);
+    }
+}
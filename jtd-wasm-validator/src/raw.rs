@@ -0,0 +1,95 @@
+/// Compact error encoding for `validate_raw`. Instead of building one JS
+/// object per error, the errors are laid out in linear memory: a table of
+/// `count` records of four `u32`s
+/// `[instancePathOffset, instancePathLen, schemaPathOffset, schemaPathLen]`
+/// indexing one UTF-8 string region. `js/raw-errors.js` decodes records on
+/// demand.
+use std::cell::RefCell;
+
+/// Backing storage for the most recent `validate_raw` call. Both vectors are
+/// reused across calls, so once they have grown to the largest error list
+/// seen, validation stops allocating for errors.
+#[derive(Default)]
+pub struct ErrorBuffer {
+    strings: Vec<u8>,
+    table: Vec<u32>,
+}
+
+impl ErrorBuffer {
+    /// Replace the buffer contents with `errors`. Returns the record count.
+    pub fn fill(&mut self, errors: &[(String, String)]) -> usize {
+        self.strings.clear();
+        self.table.clear();
+        for (ip, sp) in errors {
+            for s in [ip, sp] {
+                self.table.push(self.strings.len() as u32);
+                self.table.push(s.len() as u32);
+                self.strings.extend_from_slice(s.as_bytes());
+            }
+        }
+        errors.len()
+    }
+
+    /// Address of the first record.
+    pub fn table_ptr(&self) -> *const u32 {
+        self.table.as_ptr()
+    }
+
+    /// Address of the string region the offsets index.
+    pub fn strings_ptr(&self) -> *const u8 {
+        self.strings.as_ptr()
+    }
+
+    /// Length in bytes of the string region.
+    pub fn strings_len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// The `index`th string, two per record.
+    #[cfg(test)]
+    fn string(&self, index: usize) -> &str {
+        let start = self.table[index * 2] as usize;
+        let len = self.table[index * 2 + 1] as usize;
+        std::str::from_utf8(&self.strings[start..start + len]).unwrap_or_default()
+    }
+}
+
+thread_local! {
+    /// Errors from the last `validate_raw` call, valid until the next one.
+    pub static ERRORS: RefCell<ErrorBuffer> = RefCell::new(ErrorBuffer::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_and_read_back() {
+        let mut buf = ErrorBuffer::default();
+        let n = buf.fill(&errors(&[("/age", "/properties/age/type"), ("", "")]));
+        assert_eq!(n, 2);
+        assert_eq!(buf.table, [0, 4, 4, 20, 24, 0, 24, 0]);
+        assert_eq!(buf.strings_len(), 24);
+        assert_eq!(buf.string(0), "/age");
+        assert_eq!(buf.string(1), "/properties/age/type");
+        assert_eq!(buf.string(2), "");
+    }
+
+    #[test]
+    fn test_refill_replaces_contents() {
+        let mut buf = ErrorBuffer::default();
+        buf.fill(&errors(&[("/a", "/b"), ("/c", "/d")]));
+        assert_eq!(buf.fill(&errors(&[("/x", "/y")])), 1);
+        assert_eq!(buf.table, [0, 2, 2, 2]);
+        assert_eq!(buf.strings, b"/x/y");
+        assert_eq!(buf.fill(&[]), 0);
+        assert_eq!(buf.strings_len(), 0);
+    }
+}
@@ -0,0 +1,423 @@
+/// Incremental validation for `begin()` / `feed(bytes)` / `end()`. When the
+/// root schema is an `elements` or `values` form, the top-level array or
+/// object is scanned as bytes arrive and each element or member value is
+/// parsed and validated as soon as it is complete, then dropped, so memory
+/// is bounded by the largest single child rather than the whole document.
+/// Any other root (or a document whose top level is not the expected
+/// container) is buffered and validated in one go at `end()`.
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Array,
+    Object,
+}
+
+/// The container a streamable root schema expects, and the schema path
+/// of its children for `validate_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plan {
+    pub container: Container,
+    pub child_schema: &'static str,
+}
+
+/// `(instancePath, schemaPath)` pairs, as the generated validator reports.
+pub type Errors = Vec<(String, String)>;
+
+/// The generated validator's entry points.
+pub struct Validators {
+    pub validate: fn(&Value) -> Errors,
+    pub validate_at: fn(&str, &Value) -> Option<Errors>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the first non-whitespace byte.
+    Start,
+    /// After `[` / `{` or a `,`; `first` allows the closing bracket.
+    BeforeChild {
+        first: bool,
+    },
+    Key,
+    Colon,
+    ValueStart,
+    Value,
+    AfterChild,
+    Done,
+}
+
+/// Scanner for the top level of a streamed document.
+pub struct Feed {
+    plan: Option<Plan>,
+    validators: Validators,
+    state: State,
+    /// Whole document, when not streaming.
+    buffer: Option<Vec<u8>>,
+    /// Bytes of the key and the child value being read.
+    key: Vec<u8>,
+    child: Vec<u8>,
+    index: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    errors: Errors,
+}
+
+impl Feed {
+    pub fn new(plan: Option<Plan>, validators: Validators) -> Self {
+        Feed {
+            plan,
+            validators,
+            state: State::Start,
+            buffer: None,
+            key: Vec::new(),
+            child: Vec::new(),
+            index: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Consume the next chunk. Fails on malformed JSON.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), String> {
+        for (i, &b) in chunk.iter().enumerate() {
+            if let Some(buffer) = &mut self.buffer {
+                buffer.extend_from_slice(&chunk[i..]);
+                return Ok(());
+            }
+            self.byte(b)?;
+            // A scalar ends at the delimiter after it, which belongs to
+            // the container and is scanned again.
+            if self.state == State::AfterChild && is_delimiter(b) && self.child_pending() {
+                self.finish_child()?;
+                self.byte(b)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish the document and return every error found.
+    pub fn end(mut self) -> Result<Errors, String> {
+        if let Some(buffer) = self.buffer.take() {
+            let instance: Value =
+                serde_json::from_slice(&buffer).map_err(|e| format!("Invalid JSON: {e}"))?;
+            return Ok((self.validators.validate)(&instance));
+        }
+        match self.state {
+            State::Done => Ok(self.errors),
+            State::Start => Err("Invalid JSON: empty document".to_string()),
+            _ => Err("Invalid JSON: unexpected end of document".to_string()),
+        }
+    }
+
+    fn child_pending(&self) -> bool {
+        !self.child.is_empty()
+    }
+
+    fn byte(&mut self, b: u8) -> Result<(), String> {
+        let unexpected = || Err(format!("Invalid JSON: unexpected '{}'", b as char));
+        let ws = matches!(b, b' ' | b'\t' | b'\r' | b'\n');
+        match self.state {
+            State::Start if ws => {}
+            State::Start => {
+                let plan = self.plan.filter(|p| {
+                    b == match p.container {
+                        Container::Array => b'[',
+                        Container::Object => b'{',
+                    }
+                });
+                match plan {
+                    Some(_) => self.state = State::BeforeChild { first: true },
+                    None => self.buffer = Some(vec![b]),
+                }
+            }
+            State::BeforeChild { .. } if ws => {}
+            State::BeforeChild { first } => {
+                let object = self.container() == Container::Object;
+                if first && b == if object { b'}' } else { b']' } {
+                    self.state = State::Done;
+                } else if object {
+                    if b != b'"' {
+                        return unexpected();
+                    }
+                    self.key.clear();
+                    self.key.push(b);
+                    self.state = State::Key;
+                } else {
+                    self.state = State::ValueStart;
+                    return self.byte(b);
+                }
+            }
+            State::Key => {
+                self.key.push(b);
+                if self.escaped {
+                    self.escaped = false;
+                } else if b == b'\\' {
+                    self.escaped = true;
+                } else if b == b'"' {
+                    self.state = State::Colon;
+                }
+            }
+            State::Colon if ws => {}
+            State::Colon if b == b':' => self.state = State::ValueStart,
+            State::Colon => return unexpected(),
+            State::ValueStart if ws => {}
+            State::ValueStart => {
+                if matches!(b, b',' | b']' | b'}' | b':') {
+                    return unexpected();
+                }
+                self.child.clear();
+                self.child.push(b);
+                match b {
+                    b'{' | b'[' => {
+                        self.depth = 1;
+                        self.state = State::Value;
+                    }
+                    b'"' => {
+                        self.in_string = true;
+                        self.state = State::Value;
+                    }
+                    // Scalars run until a delimiter shows up.
+                    _ => self.state = State::AfterChild,
+                }
+            }
+            State::Value => {
+                self.child.push(b);
+                if self.in_string {
+                    if self.escaped {
+                        self.escaped = false;
+                    } else if b == b'\\' {
+                        self.escaped = true;
+                    } else if b == b'"' {
+                        self.in_string = false;
+                    }
+                } else {
+                    match b {
+                        b'"' => self.in_string = true,
+                        b'{' | b'[' => self.depth += 1,
+                        b'}' | b']' => self.depth -= 1,
+                        _ => {}
+                    }
+                }
+                if self.depth == 0 && !self.in_string {
+                    self.finish_child()?;
+                    self.state = State::AfterChild;
+                }
+            }
+            State::AfterChild if self.child_pending() => {
+                if !is_delimiter(b) {
+                    self.child.push(b); // rest of a scalar
+                }
+            }
+            State::AfterChild if ws => {}
+            State::AfterChild => {
+                let close = match self.container() {
+                    Container::Array => b']',
+                    Container::Object => b'}',
+                };
+                if b == b',' {
+                    self.state = State::BeforeChild { first: false };
+                } else if b == close {
+                    self.state = State::Done;
+                } else {
+                    return unexpected();
+                }
+            }
+            State::Done if ws => {}
+            State::Done => return unexpected(),
+        }
+        Ok(())
+    }
+
+    fn container(&self) -> Container {
+        self.plan.map_or(Container::Array, |p| p.container)
+    }
+
+    /// Parse and validate the completed child, then drop its bytes.
+    fn finish_child(&mut self) -> Result<(), String> {
+        let value: Value =
+            serde_json::from_slice(&self.child).map_err(|e| format!("Invalid JSON: {e}"))?;
+        self.child.clear();
+        let segment = match self.container() {
+            Container::Array => {
+                self.index += 1;
+                (self.index - 1).to_string()
+            }
+            Container::Object => serde_json::from_slice::<String>(&self.key)
+                .map_err(|e| format!("Invalid JSON: {e}"))?
+                .replace('~', "~0")
+                .replace('/', "~1"),
+        };
+        let plan = self.plan.expect("streaming implies a plan");
+        // A plan naming no subschema would pass every child unchecked
+        let errors = (self.validators.validate_at)(plan.child_schema, &value)
+            .ok_or_else(|| format!("No subschema at {} to stream against", plan.child_schema))?;
+        for (ip, sp) in errors {
+            self.errors.push((format!("/{segment}{ip}"), sp));
+        }
+        Ok(())
+    }
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b',' | b']' | b'}' | b' ' | b'\t' | b'\r' | b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Children must be integers; the root must be an array.
+    fn validate_at(pointer: &str, value: &Value) -> Option<Vec<(String, String)>> {
+        match pointer {
+            "/elements" => Some(if value.is_i64() {
+                Vec::new()
+            } else {
+                vec![(String::new(), "/elements/type".to_string())]
+            }),
+            _ => None,
+        }
+    }
+
+    fn validate(value: &Value) -> Vec<(String, String)> {
+        match value.as_array() {
+            Some(items) => items
+                .iter()
+                .enumerate()
+                .flat_map(|(i, v)| {
+                    validate_at("/elements", v)
+                        .unwrap()
+                        .into_iter()
+                        .map(move |(ip, sp)| (format!("/{i}{ip}"), sp))
+                })
+                .collect(),
+            None => vec![(String::new(), "/elements".to_string())],
+        }
+    }
+
+    fn feed(plan: Option<Plan>, chunks: &[&str]) -> Result<Vec<(String, String)>, String> {
+        let mut f = Feed::new(
+            plan,
+            Validators {
+                validate,
+                validate_at,
+            },
+        );
+        for chunk in chunks {
+            f.feed(chunk.as_bytes())?;
+        }
+        f.end()
+    }
+
+    const ARRAY: Option<Plan> = Some(Plan {
+        container: Container::Array,
+        child_schema: "/elements",
+    });
+
+    fn errs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_streams_array_children_across_chunks() {
+        let expected = errs(&[("/1", "/elements/type"), ("/3", "/elements/type")]);
+        let doc = r#" [1, "a,]", 23 , {"x": [1, {"y": "]"}]}, -4]"#;
+        assert_eq!(feed(ARRAY, &[doc]).unwrap(), expected);
+        // Same result whatever the chunk boundaries
+        for split in 1..doc.len() {
+            let (a, b) = doc.split_at(split);
+            assert_eq!(feed(ARRAY, &[a, b]).unwrap(), expected, "split at {split}");
+        }
+        assert_eq!(feed(ARRAY, &["[", "]"]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_streams_object_members() {
+        let plan = Some(Plan {
+            container: Container::Object,
+            child_schema: "/elements",
+        });
+        let got = feed(plan, &[r#"{"a": 1, "b\"c": true}"#]).unwrap();
+        assert_eq!(got, errs(&[("/b\"c", "/elements/type")]));
+    }
+
+    /// A `values` root, reporting member keys escaped as the generated
+    /// validator does.
+    fn validate_values(value: &Value) -> Vec<(String, String)> {
+        match value.as_object() {
+            Some(members) => members
+                .iter()
+                .flat_map(|(k, v)| {
+                    let key = k.replace('~', "~0").replace('/', "~1");
+                    validate_at("/elements", v)
+                        .unwrap()
+                        .into_iter()
+                        .map(move |(ip, sp)| (format!("/{key}{ip}"), sp))
+                })
+                .collect(),
+            None => vec![(String::new(), "/elements".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_streamed_keys_are_escaped_like_whole_documents() {
+        let plan = Some(Plan {
+            container: Container::Object,
+            child_schema: "/elements",
+        });
+        let run = |plan| {
+            let mut f = Feed::new(
+                plan,
+                Validators {
+                    validate: validate_values,
+                    validate_at,
+                },
+            );
+            f.feed(br#"{"a/b~c": "x", "~1": true, "ok": 1}"#).unwrap();
+            f.end().unwrap()
+        };
+        let streamed = run(plan);
+        assert_eq!(
+            streamed,
+            errs(&[("/a~1b~0c", "/elements/type"), ("/~01", "/elements/type")])
+        );
+        assert_eq!(streamed, run(None));
+    }
+
+    #[test]
+    fn test_unknown_child_schema_is_an_error() {
+        let plan = Some(Plan {
+            container: Container::Array,
+            child_schema: "/definitions/a/b/elements",
+        });
+        let err = feed(plan, &["[1, 2]"]).unwrap_err();
+        assert!(err.contains("/definitions/a/b/elements"), "{err}");
+    }
+
+    #[test]
+    fn test_buffers_other_roots() {
+        assert_eq!(
+            feed(ARRAY, &["{\"a\"", ": 1}"]).unwrap(),
+            errs(&[("", "/elements")])
+        );
+        assert_eq!(
+            feed(None, &["[1, ", "true]"]).unwrap(),
+            errs(&[("/1", "/elements/type")])
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(feed(ARRAY, &["[1 2]"]).is_err());
+        assert!(feed(ARRAY, &["[1,]"]).is_err());
+        assert!(feed(ARRAY, &["[1, tru]"]).is_err());
+        assert!(feed(ARRAY, &["[1"]).is_err());
+        assert!(feed(ARRAY, &["[1] x"]).is_err());
+        assert!(feed(ARRAY, &[""]).is_err());
+    }
+}
@@ -93,6 +93,8 @@ target("test_rust")
         os.setenv("JTD_VALIDATION_JSON", validation)
         cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test rs_validation_suite -- --nocapture")
         os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "rs_validation_suite", "--", "--nocapture"})
+        cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --features arrow --lib arrow")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--features", "arrow", "--lib", "arrow"})
         cprint("${green}OK:${clear} test_rust")
     end)
 target_end()
@@ -115,6 +117,37 @@ target("test_js")
     end)
 target_end()
 
+-- Type-check the TypeScript target under --strict (needs node for npx).
+target("test_ts")
+    set_kind("phony")
+    on_run(function ()
+        local outdir = path.join(os.projectdir(), ".tmp", "ts")
+        os.mkdir(outdir)
+        local files = {}
+        for _, schema in ipairs(os.files(path.join(os.projectdir(), "examples", "*", "schema.json"))) do
+            local out = path.join(outdir, path.basename(path.directory(schema)) .. ".ts")
+            os.vrunv("cargo", {"run", "-q", "-p", "jtd-codegen", "--", "--target", "ts", "-o", out, schema})
+            table.insert(files, out)
+        end
+        cprint("${cyan}Running:${clear} tsc --strict --noEmit")
+        os.vrunv("npx", table.join({"-y", "-p", "typescript", "tsc", "--strict", "--noEmit", "--target", "es2020"}, files))
+        cprint("${green}OK:${clear} test_ts")
+    end)
+target_end()
+
+target("test_c")
+    set_kind("phony")
+    on_run(function ()
+        cprint("${cyan}Running:${clear} fetch_suite")
+        os.vrunv("xmake", {"run", "fetch_suite"})
+        local validation = path.join(os.projectdir(), ".tmp", "json-typedef-spec", JSON_TYPEDEF_SPEC_COMMIT, "tests", "validation.json")
+        os.setenv("JTD_VALIDATION_JSON", validation)
+        cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test c_validation_suite -- --nocapture")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "c_validation_suite", "--", "--nocapture"})
+        cprint("${green}OK:${clear} test_c")
+    end)
+target_end()
+
 target("test_lua")
     set_kind("phony")
     on_run(function ()
@@ -154,6 +187,87 @@ target("test_wasm")
         os.setenv("JTD_VALIDATION_JSON", validation)
         cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test wasmtime_validation_suite -- --nocapture")
         os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "wasmtime_validation_suite", "--", "--nocapture"})
+        cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test wasmi_validation_suite -- --nocapture")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "wasmi_validation_suite", "--", "--nocapture"})
+    end)
+target_end()
+
+-- Cloudflare Workers / edge bundle of jtd-wasm-validator in workers-pkg/:
+-- wasm-bindgen glue, a size-optimised .wasm, and workers.js which
+-- instantiates it synchronously from the deploy-time WebAssembly.Module.
+target("build_workers")
+    set_kind("phony")
+    on_run(function ()
+        local projectdir = os.projectdir()
+        local out = path.join(projectdir, "workers-pkg")
+        os.vrunv("rustup", {"target", "add", "wasm32-unknown-unknown"})
+        cprint("${cyan}Running:${clear} cargo build -p jtd-wasm-validator --target wasm32-unknown-unknown --profile wasm-release")
+        os.vrunv("cargo", {"build", "-p", "jtd-wasm-validator", "--target", "wasm32-unknown-unknown", "--profile", "wasm-release"})
+
+        local result = os.iorun("which wasm-bindgen")
+        if not result or result:trim() == "" then
+            raise("wasm-bindgen not found. Install the version matching Cargo.lock: cargo install wasm-bindgen-cli --version <v>")
+        end
+        local wasm = path.join(projectdir, "target", "wasm32-unknown-unknown", "wasm-release", "jtd_wasm_validator.wasm")
+        os.vrunv("wasm-bindgen", {"--target", "web", "--out-dir", out, wasm})
+
+        -- wasm-opt (binaryen) is optional but usually saves another 10-20%
+        local bg = path.join(out, "jtd_wasm_validator_bg.wasm")
+        result = os.iorun("which wasm-opt")
+        if result and result:trim() ~= "" then
+            os.vrunv("wasm-opt", {"-Oz", "--strip-debug", "-o", bg, bg})
+        else
+            cprint("${yellow}Skipping:${clear} wasm-opt not found")
+        end
+
+        local js = path.join(projectdir, "jtd-wasm-validator", "js")
+        for _, file in ipairs({"workers.js", "workers.d.ts", "raw-errors.js"}) do
+            os.cp(path.join(js, file), out)
+        end
+        cprint("${green}Built:${clear} workers-pkg/ (" .. os.filesize(bg) .. " bytes of wasm)")
+    end)
+target_end()
+
+-- Golden snapshots of every target's output (jtd-codegen/tests/golden).
+-- update_golden rewrites the stale ones; review the diff before committing.
+target("test_golden")
+    set_kind("phony")
+    on_run(function ()
+        cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test golden_snapshots")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "golden_snapshots"})
+        cprint("${green}OK:${clear} test_golden")
+    end)
+target_end()
+
+target("update_golden")
+    set_kind("phony")
+    on_run(function ()
+        os.setenv("JTD_UPDATE_GOLDEN", "1")
+        cprint("${cyan}Running:${clear} JTD_UPDATE_GOLDEN=1 cargo test -p jtd-codegen --test golden_snapshots")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "golden_snapshots"})
+        cprint("${green}Updated:${clear} jtd-codegen/tests/golden")
+    end)
+target_end()
+
+-- Throughput of the generated JS against ajv's JTD validator, both in
+-- embedded QuickJS, over the validation suite. Needs npm to fetch ajv-dist.
+target("bench_ajv")
+    set_kind("phony")
+    on_run(function ()
+        cprint("${cyan}Running:${clear} fetch_suite")
+        os.vrunv("xmake", {"run", "fetch_suite"})
+        local tmp = path.join(os.projectdir(), ".tmp")
+        local bundle = path.join(tmp, "package", "dist", "ajvJTD.bundle.js")
+        if not os.isfile(bundle) then
+            cprint("${cyan}Fetching:${clear} ajv-dist@8")
+            os.vrunv("npm", {"pack", "ajv-dist@8", "--pack-destination", tmp})
+            local tarball = os.files(path.join(tmp, "ajv-dist-*.tgz"))[1]
+            os.vrunv("tar", {"-xzf", tarball, "-C", tmp})
+        end
+        local validation = path.join(tmp, "json-typedef-spec", JSON_TYPEDEF_SPEC_COMMIT, "tests", "validation.json")
+        cprint("${cyan}Running:${clear} cargo run -p jtd-codegen --release --features bench --bin jtd-bench")
+        os.vrunv("cargo", {"run", "-p", "jtd-codegen", "--release", "--features", "bench", "--bin", "jtd-bench", "--",
+            "--ajv", bundle, "--suite", validation})
     end)
 target_end()
 
@@ -184,6 +298,15 @@ target("test_all")
         cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test py_validation_suite -- --nocapture")
         os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "py_validation_suite", "--", "--nocapture"})
 
+        cprint("${cyan}Running:${clear} cargo test -p jtd-codegen --test c_validation_suite -- --nocapture")
+        os.vrunv("cargo", {"test", "-p", "jtd-codegen", "--test", "c_validation_suite", "--", "--nocapture"})
+
+        cprint("${cyan}Running:${clear} xmake run test_ts")
+        os.vrunv("xmake", {"run", "test_ts"})
+
+        cprint("${cyan}Running:${clear} xmake run test_golden")
+        os.vrunv("xmake", {"run", "test_golden"})
+
         cprint("${cyan}Running:${clear} xmake run test_wasm")
         os.vrunv("xmake", {"run", "test_wasm"})
         cprint("${green}OK:${clear} test_all")
@@ -0,0 +1,255 @@
+/// Workspace automation, run as `cargo xtask <command>`.
+///
+/// Usage:
+///   cargo xtask npm-package [--out <dir>] [--features <list>]
+///
+/// `npm-package` builds jtd-wasm-validator for wasm32 with the size-optimised
+/// `wasm-release` profile, runs wasm-bindgen for the `web` and `nodejs`
+/// targets and assembles a publishable npm directory (default `npm-pkg/`):
+///
+///   package.json   name, version and description from Cargo metadata
+///   web/           ES module glue + typings, raw-errors.js, workers.js
+///   node/          CommonJS glue + typings
+///   LICENSE, README.md
+///
+/// Then `npm publish npm-pkg`.
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CRATE: &str = "jtd-wasm-validator";
+const STEM: &str = "jtd_wasm_validator";
+
+/// Helpers copied from jtd-wasm-validator/js into `web/`.
+const WEB_EXTRAS: &[&str] = &["raw-errors.js", "workers.js", "workers.d.ts"];
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("npm-package") => npm_package(&args[1..]),
+        _ => {
+            eprintln!("Usage: cargo xtask npm-package [--out <dir>] [--features <list>]");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("xtask: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn npm_package(args: &[String]) -> Result<(), String> {
+    let root = workspace_root();
+    let mut out = root.join("npm-pkg");
+    let mut features: Option<&str> = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--out" => {
+                i += 1;
+                let dir = args.get(i).ok_or("--out needs a directory")?;
+                out = PathBuf::from(dir);
+            }
+            "--features" => {
+                i += 1;
+                features = Some(args.get(i).ok_or("--features needs a list")?);
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+        i += 1;
+    }
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut build = vec![
+        "build",
+        "-p",
+        CRATE,
+        "--target",
+        "wasm32-unknown-unknown",
+        "--profile",
+        "wasm-release",
+    ];
+    if let Some(features) = features {
+        build.extend(["--features", features]);
+    }
+    run(Command::new(&cargo).args(&build).current_dir(&root))?;
+    let wasm = root
+        .join("target/wasm32-unknown-unknown/wasm-release")
+        .join(format!("{STEM}.wasm"));
+
+    // Start clean so files from an older build never ship
+    if out.exists() {
+        std::fs::remove_dir_all(&out).map_err(|e| format!("{}: {e}", out.display()))?;
+    }
+    for (target, dir) in [("web", "web"), ("nodejs", "node")] {
+        let dir = out.join(dir);
+        run(Command::new("wasm-bindgen")
+            .args(["--target", target, "--out-dir"])
+            .arg(&dir)
+            .arg(&wasm))
+        .map_err(|e| format!("{e}\n(install the wasm-bindgen-cli version matching Cargo.lock)"))?;
+        optimize(&dir.join(format!("{STEM}_bg.wasm")));
+    }
+
+    let js = root.join(CRATE).join("js");
+    for file in WEB_EXTRAS {
+        copy(&js.join(file), &out.join("web").join(file))?;
+    }
+    // The package is `"type": "module"`; the nodejs glue is CommonJS
+    write(
+        &out.join("node/package.json"),
+        "{\n  \"type\": \"commonjs\"\n}\n",
+    )?;
+    copy(&root.join("LICENSE"), &out.join("LICENSE"))?;
+    write(&out.join("README.md"), README)?;
+
+    let manifest = package_json(&crate_metadata(&cargo, &root)?);
+    let text = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    write(&out.join("package.json"), &format!("{text}\n"))?;
+    eprintln!("npm package ready in {}", out.display());
+    Ok(())
+}
+
+/// `package.json` for the crate's `cargo metadata` entry.
+fn package_json(meta: &Value) -> Value {
+    let repository = meta["repository"]
+        .as_str()
+        .unwrap_or("https://github.com/simbo1905/jtd-wasm");
+    json!({
+        "name": CRATE,
+        "version": meta["version"],
+        "description": meta["description"],
+        "license": meta["license"].as_str().unwrap_or("MIT"),
+        "repository": {"type": "git", "url": format!("git+{repository}.git")},
+        "type": "module",
+        "main": format!("./node/{STEM}.js"),
+        "module": format!("./web/{STEM}.js"),
+        "types": format!("./web/{STEM}.d.ts"),
+        "exports": {
+            ".": {
+                "types": format!("./web/{STEM}.d.ts"),
+                "node": format!("./node/{STEM}.js"),
+                "default": format!("./web/{STEM}.js"),
+            },
+            "./workers": {
+                "types": "./web/workers.d.ts",
+                "default": "./web/workers.js",
+            },
+            "./raw-errors": "./web/raw-errors.js",
+        },
+        "files": ["web", "node", "LICENSE", "README.md"],
+        "sideEffects": ["./web/workers.js", "./node/*"],
+    })
+}
+
+const README: &str = "# jtd-wasm-validator
+
+A JSON Type Definition validator compiled ahead of time to WebAssembly.
+
+```javascript
+import init, { validate } from 'jtd-wasm-validator';
+await init();
+const errors = validate(json); // [{instancePath, schemaPath}, ...]
+```
+
+Node.js loads the wasm synchronously, so `init()` is not needed there. On Cloudflare
+Workers, import `jtd-wasm-validator/workers`, which is ready on import and adds
+`validateStream(request.body)`.
+
+See https://github.com/simbo1905/jtd-wasm for the raw-errors, streaming and metrics APIs.
+";
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace")
+        .to_path_buf()
+}
+
+fn crate_metadata(cargo: &str, root: &Path) -> Result<Value, String> {
+    let output = Command::new(cargo)
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(root)
+        .output()
+        .map_err(|e| format!("cargo metadata: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+    let meta: Value = serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    meta["packages"]
+        .as_array()
+        .and_then(|packages| packages.iter().find(|p| p["name"] == CRATE))
+        .cloned()
+        .ok_or_else(|| format!("{CRATE} not in cargo metadata"))
+}
+
+/// Shrink with binaryen's wasm-opt when it is installed.
+fn optimize(wasm: &Path) {
+    let status = Command::new("wasm-opt")
+        .args(["-Oz", "--strip-debug", "-o"])
+        .arg(wasm)
+        .arg(wasm)
+        .status();
+    if !matches!(status, Ok(s) if s.success()) {
+        eprintln!("skipping wasm-opt for {}", wasm.display());
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<(), String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let status = cmd
+        .status()
+        .map_err(|e| format!("cannot run {program}: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} failed ({status})"))
+    }
+}
+
+fn copy(from: &Path, to: &Path) -> Result<(), String> {
+    std::fs::copy(from, to)
+        .map(drop)
+        .map_err(|e| format!("copy {}: {e}", from.display()))
+}
+
+fn write(path: &Path, text: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+    }
+    std::fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_json() {
+        let meta = json!({
+            "version": "0.2.0",
+            "description": "validator",
+            "license": "MIT",
+            "repository": "https://github.com/simbo1905/jtd-wasm"
+        });
+        let pkg = package_json(&meta);
+        assert_eq!(pkg["version"], "0.2.0");
+        assert_eq!(
+            pkg["repository"]["url"],
+            "git+https://github.com/simbo1905/jtd-wasm.git"
+        );
+        assert_eq!(pkg["exports"]["."]["node"], "./node/jtd_wasm_validator.js");
+        assert_eq!(pkg["exports"]["./workers"]["default"], "./web/workers.js");
+        // Every exported file is inside a published directory
+        let files: Vec<&str> = pkg["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        for path in ["./web/raw-errors.js", "./node/jtd_wasm_validator.js"] {
+            let dir = path.trim_start_matches("./").split('/').next().unwrap();
+            assert!(files.contains(&dir), "{path}");
+        }
+    }
+}